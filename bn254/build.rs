@@ -0,0 +1,4 @@
+// select BN254 in `src/curve.rs` for the sources shared with `rdf-proofs`
+fn main() {
+    println!("cargo:rustc-cfg=curve_bn254");
+}
@@ -0,0 +1,237 @@
+//! walkthrough of the credential lifecycle across the roles, i.e.,
+//! issuer key generation, blind issuance of a VC bound to the holder's secret,
+//! presentation with PPID, predicate, and encrypted uid, verification,
+//! and deanonymization of the holder by the opener
+//!
+//! ```sh
+//! cargo run --example lifecycle
+//! ```
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use legogroth16::circom::{CircomCircuit, R1CS};
+use multibase::Base;
+use oxrdf::{vocab::xsd, Literal};
+use rdf_proofs::{
+    ark_to_base64url, blind_sign_string, blind_verify_string,
+    context::ENCRYPTED_UID,
+    derive_proof_string, elliptic_elgamal_decrypt, elliptic_elgamal_keygen,
+    error::RDFProofsError,
+    export_key_graph_for_vp_string, get_encrypted_uid,
+    key_gen::{
+        encode_public_key, encode_secret_key, generate_keypair, generate_params, KeyEncoding,
+    },
+    multibase_to_ark, request_blind_sign_string, unblind_string, verify_blind_sign_request_string,
+    verify_proof_string, CircuitString, DeriveOptionsString, ElGamalCiphertext, PredicateGraph,
+    VariableRef, VcPairString, VerifyOptionsString,
+};
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+const VERIFICATION_METHOD: &str = "did:example:issuer0#bls12_381-g2-pub001";
+const LESS_THAN_PRV_PUB: &str = "https://zkp-ld.org/circuit/lessThanPrvPub";
+const VACCINATED_BEFORE: &str = "2022-12-31T00:00:00Z";
+
+const VC: &str = r#"<did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+<did:example:john> <http://schema.org/name> "John Smith" .
+<did:example:john> <http://example.org/vocab/isPatientOf> _:b0 .
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+_:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
+_:b0 <http://example.org/vocab/vaccinationDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+<http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+
+const PROOF_OPTIONS: &str = r#"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+_:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+_:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+_:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+"#;
+
+// the name and vaccination date are hidden, where the latter is proven to be
+// before the verifier's deadline by predicate
+const DISCLOSED_VC: &str = r#"_:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+_:e0 <http://schema.org/name> _:e3 .
+_:e0 <http://example.org/vocab/isPatientOf> _:b0 .
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+_:b0 <http://example.org/vocab/vaccine> _:e1 .
+_:b0 <http://example.org/vocab/vaccinationDate> _:e4 .
+_:e2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+_:e2 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+_:e2 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+_:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+
+const DEANON_MAP: [(&str, &str); 5] = [
+    ("_:e0", "<did:example:john>"),
+    ("_:e1", "<http://example.org/vaccine/a>"),
+    ("_:e2", "<http://example.org/vcred/00>"),
+    ("_:e3", "\"John Smith\""),
+    (
+        "_:e4",
+        "\"2022-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
+    ),
+];
+
+const SECRET: &[u8] = b"SECRET";
+const CHALLENGE: &str = "abcde";
+const DOMAIN: &str = "example.org";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // issuer: generate keys and publish the key graph without the secret key
+    let keypair = generate_keypair(&mut rng)?;
+    let public_key = encode_public_key(&keypair.public_key, KeyEncoding::Ark)?;
+    let secret_key = encode_secret_key(&keypair.secret_key, KeyEncoding::Ark)?;
+    let key_graph = format!(
+        r#"<did:example:issuer0> <https://w3id.org/security#verificationMethod> <{VERIFICATION_METHOD}> .
+<{VERIFICATION_METHOD}> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+<{VERIFICATION_METHOD}> <https://w3id.org/security#controller> <did:example:issuer0> .
+<{VERIFICATION_METHOD}> <https://w3id.org/security#publicKeyMultibase> "{public_key}" .
+"#
+    );
+    let issuer_key_graph = format!(
+        "{key_graph}<{VERIFICATION_METHOD}> <https://w3id.org/security#secretKeyMultibase> \"{secret_key}\" .\n"
+    );
+    println!("issuer key graph:\n{}", key_graph);
+
+    // holder: request blind issuance by committing to the secret
+    let request = request_blind_sign_string(&mut rng, SECRET, Some(CHALLENGE), None, None, None)?;
+    let pok_for_commitment = request
+        .pok_for_commitment
+        .as_deref()
+        .ok_or("missing proof of knowledge of commitment")?;
+
+    // issuer: check the request and sign the VC with the holder's commitment
+    verify_blind_sign_request_string(
+        &mut rng,
+        &request.commitment,
+        pok_for_commitment,
+        Some(CHALLENGE),
+        None,
+        None,
+    )?;
+    let blinded_proof = blind_sign_string(
+        &mut rng,
+        &request.commitment,
+        VC,
+        PROOF_OPTIONS,
+        &issuer_key_graph,
+        None,
+        None,
+        None,
+    )?;
+
+    // holder: unblind the proof and check the VC bound to the secret
+    let proof = unblind_string(VC, &blinded_proof, &request.blinding)?;
+    blind_verify_string(SECRET, VC, &proof, &key_graph)?;
+    println!("bound VC proof:\n{}", proof);
+
+    // verifier: set up the circuit and the opener who can deanonymize holders
+    let circuit = setup_less_than_prv_pub_circuit(&mut rng)?;
+    let snark_verifying_keys = HashMap::from([(
+        LESS_THAN_PRV_PUB.to_string(),
+        circuit[LESS_THAN_PRV_PUB].snark_proving_key.clone(),
+    )]);
+    let public_values = HashMap::from([(
+        LESS_THAN_PRV_PUB.to_string(),
+        HashMap::from([(
+            "greater".to_string(),
+            format!("\"{VACCINATED_BEFORE}\"^^<http://www.w3.org/2001/XMLSchema#dateTime>"),
+        )]),
+    )]);
+    let (opener_pub_key, opener_secret_key) = elliptic_elgamal_keygen(&mut rng)?;
+
+    // holder: present the VC with PPID for the domain, predicate on the hidden vaccination date,
+    // and uid encrypted for the opener
+    let disclosed_proof = proof
+        .lines()
+        .filter(|l| !l.contains("https://w3id.org/security#proofValue"))
+        .map(|l| format!("{}\n", l.trim()))
+        .collect::<String>();
+    let vc_pairs = vec![VcPairString::new(
+        VC,
+        &proof,
+        DISCLOSED_VC,
+        &disclosed_proof,
+    )];
+    let deanon_map = DEANON_MAP
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>();
+    let predicates = vec![PredicateGraph::new_string(LESS_THAN_PRV_PUB)?
+        .private("lesser", VariableRef::new("e4")?)
+        .public(
+            "greater",
+            Literal::new_typed_literal(VACCINATED_BEFORE, xsd::DATE_TIME),
+        )
+        .to_ntriples()];
+    let vp = derive_proof_string(
+        &mut rng,
+        &vc_pairs,
+        &deanon_map,
+        &key_graph,
+        Some(CHALLENGE),
+        Some(DOMAIN),
+        DeriveOptionsString {
+            secret: Some(SECRET),
+            with_ppid: Some(true),
+            predicates: Some(&predicates),
+            circuits: Some(&circuit),
+            opener_pub_keys: Some(&vec![opener_pub_key]),
+            ..Default::default()
+        },
+    )?;
+    println!("VP:\n{}", vp);
+
+    // verifier: verify VP against the key graph reduced to its verification methods
+    let vp_key_graph = export_key_graph_for_vp_string(&vp, &key_graph)?;
+    let result = verify_proof_string(
+        &mut rng,
+        &vp,
+        &vp_key_graph,
+        Some(CHALLENGE),
+        Some(DOMAIN),
+        VerifyOptionsString {
+            snark_verifying_keys: Some(&snark_verifying_keys),
+            opener_pub_keys: Some(&vec![opener_pub_key]),
+            public_values: Some(&public_values),
+            ..Default::default()
+        },
+    )?;
+    println!("verification result: {:?}", result);
+
+    // opener: decrypt the uid in VP and match it against the holder's
+    let encrypted_uid = vp
+        .lines()
+        .find(|l| l.contains(ENCRYPTED_UID.as_str()))
+        .and_then(|l| l.split('"').nth(1))
+        .ok_or("missing encrypted uid")?;
+    let cipher_text: ElGamalCiphertext = multibase_to_ark(encrypted_uid)?;
+    let uid = ark_to_base64url(&elliptic_elgamal_decrypt(&opener_secret_key, &cipher_text)?)?;
+    let holder_uid = get_encrypted_uid(&SECRET.to_vec(), &generate_params(1).h[0])?;
+    println!("opened uid matches the holder: {}", uid == holder_uid);
+    Ok(())
+}
+
+// proving key of the bundled lessThanPrvPub circuit, which also serves as the verifying key
+fn setup_less_than_prv_pub_circuit(
+    rng: &mut StdRng,
+) -> Result<HashMap<String, CircuitString>, Box<dyn Error>> {
+    let circom_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("circom/bls12381");
+    let circuit_r1cs = R1CS::from_file(circom_dir.join("less_than_prv_pub_64.r1cs"))
+        .map_err(|e| RDFProofsError::Other(format!("{:?}", e)))?;
+    let circuit_wasm = fs::read(circom_dir.join("less_than_prv_pub_64.wasm"))?;
+    let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+        .generate_proving_key(1, rng)
+        .map_err(|e| RDFProofsError::Other(format!("{:?}", e)))?;
+    Ok(HashMap::from([(
+        LESS_THAN_PRV_PUB.to_string(),
+        CircuitString {
+            circuit_r1cs: ark_to_base64url(&circuit_r1cs)?,
+            circuit_wasm: multibase::encode(Base::Base64Url, circuit_wasm),
+            snark_proving_key: ark_to_base64url(&snark_proving_key)?,
+            input_bit_lengths: HashMap::new(),
+        },
+    )]))
+}
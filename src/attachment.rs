@@ -0,0 +1,129 @@
+use crate::{common::get_term_from_string, context::DIGEST_MULTIBASE, error::RDFProofsError};
+use multibase::Base;
+use oxrdf::{Graph, Literal, LiteralRef, Term, TermRef, Triple};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// multihash prefix for sha2-256 (code: 0x12, length: 0x20)
+pub(crate) const SHA2_256_MULTIHASH_PREFIX: [u8; 2] = [0x12, 0x20];
+
+/// replace literals whose lexical values are longer than `threshold` bytes
+/// (e.g., base64-encoded images or PDFs) with digest-valued literals
+/// typed as `zkpld:digestMultibase`, so that only the digests are signed and hashed into terms;
+/// the original literals are returned keyed by the digest literals
+/// so that they can be disclosed out-of-band
+pub fn digest_large_literals(
+    document: &Graph,
+    threshold: usize,
+) -> (Graph, HashMap<Literal, Literal>) {
+    let mut attachments = HashMap::new();
+    let digested_document = document
+        .iter()
+        .map(|triple| {
+            let object = match triple.object {
+                TermRef::Literal(v) if v.value().len() > threshold => {
+                    let digest = get_digest_literal(v);
+                    attachments.insert(digest.clone(), v.into_owned());
+                    Term::Literal(digest)
+                }
+                object => object.into_owned(),
+            };
+            Triple::new(
+                triple.subject.into_owned(),
+                triple.predicate.into_owned(),
+                object,
+            )
+        })
+        .collect();
+    (digested_document, attachments)
+}
+
+/// check that the original literal disclosed out-of-band
+/// matches the digest-valued literal in the (disclosed) credential
+pub fn verify_attachment_digest(
+    digest: LiteralRef,
+    original: LiteralRef,
+) -> Result<(), RDFProofsError> {
+    if digest.datatype() != DIGEST_MULTIBASE {
+        return Err(RDFProofsError::InvalidAttachmentDigest);
+    }
+    if get_digest_literal(original).as_ref() == digest {
+        Ok(())
+    } else {
+        Err(RDFProofsError::AttachmentDigestMismatch)
+    }
+}
+
+/// `verify_attachment_digest` with literals in N-Triples format
+pub fn verify_attachment_digest_string(digest: &str, original: &str) -> Result<(), RDFProofsError> {
+    match (
+        get_term_from_string(digest)?,
+        get_term_from_string(original)?,
+    ) {
+        (Term::Literal(digest), Term::Literal(original)) => {
+            verify_attachment_digest(digest.as_ref(), original.as_ref())
+        }
+        _ => Err(RDFProofsError::InvalidAttachmentDigest),
+    }
+}
+
+// digest is calculated over the N-Triples serialization of the literal
+// so that its datatype or language tag is also bound
+fn get_digest_literal(literal: LiteralRef) -> Literal {
+    let mut multihash = SHA2_256_MULTIHASH_PREFIX.to_vec();
+    multihash.extend(Sha256::digest(literal.to_string().as_bytes()));
+    Literal::new_typed_literal(
+        multibase::encode(Base::Base58Btc, multihash),
+        DIGEST_MULTIBASE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digest_large_literals, verify_attachment_digest, verify_attachment_digest_string};
+    use crate::{common::get_graph_from_ntriples, error::RDFProofsError};
+    use oxrdf::{Literal, LiteralRef, TermRef};
+
+    const VC_WITH_IMAGE: &str = r#"
+<did:example:john> <http://schema.org/name> "John Smith" .
+<did:example:john> <http://schema.org/image> "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg=="^^<http://www.w3.org/2001/XMLSchema#base64Binary> .
+"#;
+
+    #[test]
+    fn digest_and_verify_large_literal_success() {
+        let document = get_graph_from_ntriples(VC_WITH_IMAGE).unwrap();
+        let (digested, attachments) = digest_large_literals(&document, 64);
+
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(digested.len(), document.len());
+        let (digest, original) = attachments.iter().next().unwrap();
+        assert!(digested
+            .iter()
+            .any(|t| t.object == TermRef::Literal(digest.as_ref())));
+        assert!(digested
+            .iter()
+            .any(|t| t.object == TermRef::Literal(LiteralRef::new_simple_literal("John Smith"))));
+
+        assert!(verify_attachment_digest(digest.as_ref(), original.as_ref()).is_ok());
+        assert!(
+            verify_attachment_digest_string(&digest.to_string(), &original.to_string()).is_ok()
+        );
+    }
+
+    #[test]
+    fn verify_attachment_digest_failure() {
+        let document = get_graph_from_ntriples(VC_WITH_IMAGE).unwrap();
+        let (_, attachments) = digest_large_literals(&document, 64);
+        let (digest, _) = attachments.iter().next().unwrap();
+
+        let tampered = Literal::new_simple_literal("tampered");
+        assert!(matches!(
+            verify_attachment_digest(digest.as_ref(), tampered.as_ref()),
+            Err(RDFProofsError::AttachmentDigestMismatch)
+        ));
+        assert!(matches!(
+            verify_attachment_digest(tampered.as_ref(), tampered.as_ref()),
+            Err(RDFProofsError::InvalidAttachmentDigest)
+        ));
+    }
+}
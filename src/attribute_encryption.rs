@@ -0,0 +1,262 @@
+use crate::{
+    common::{
+        ark_to_base64url, get_hasher, get_term_from_string, hash_term_to_field, multibase_to_ark,
+        BlankNodeLabeler, Fr,
+    },
+    context::{
+        CIPHER_TEXTS, DATA_INTEGRITY_PROOF, ENCRYPTED_ATTRIBUTE, ENCRYPTED_VALUE, ENCRYPTION_KEYS,
+        MULTIBASE,
+    },
+    curve::G1Affine,
+    elliptic_elgamal::{
+        elliptic_elgamal_escrowed_encryption_with_bbs_plus, ElGamalCiphertext,
+        ElGamalEscrowedEncryption, ElGamalPublicKey,
+    },
+    error::RDFProofsError,
+    key_gen::generate_params,
+};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_std::rand::RngCore;
+use oxrdf::{
+    dataset::GraphView, vocab::rdf::TYPE, BlankNode, Dataset, LiteralRef, NamedNodeRef,
+    NamedOrBlankNode, QuadRef, Term, TermRef,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// holder's request to encrypt the hidden term `value` to `pub_keys` with the proof that
+/// the ciphertexts encrypt the signed term, e.g., to the verifier's own key, which may
+/// be combined with a second key held by an authorizer for dual control;
+/// the term is split into random shares each encrypted to one of the keys,
+/// so that all the keys are needed to decrypt it
+#[derive(Clone, Debug)]
+pub struct AttributeEncryptionRequest {
+    pub value: BlankNode,
+    pub pub_keys: Vec<ElGamalPublicKey>,
+}
+
+/// `AttributeEncryptionRequest` with the blank node in N-Triples
+/// and the multibase-encoded public keys
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttributeEncryptionRequestString {
+    pub value: String,
+    pub pub_keys: Vec<String>,
+}
+
+impl TryFrom<&AttributeEncryptionRequestString> for AttributeEncryptionRequest {
+    type Error = RDFProofsError;
+
+    fn try_from(req: &AttributeEncryptionRequestString) -> Result<Self, Self::Error> {
+        let Term::BlankNode(value) = get_term_from_string(&req.value)? else {
+            return Err(RDFProofsError::InvalidAttributeEncryption);
+        };
+        Ok(Self {
+            value,
+            pub_keys: req
+                .pub_keys
+                .iter()
+                .map(|pk| multibase_to_ark(pk))
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+/// hidden term encrypted in VP, reported by `verify_proof` so that the holders of
+/// all the keys can later decrypt their shares with `elliptic_elgamal_decrypt`
+/// and combine them with `combine_decrypted_shares` into `get_encrypted_attribute` of the term
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptedAttribute {
+    /// blank node of the hidden term in the disclosed VC documents of the verification result
+    pub value: BlankNode,
+    pub pub_keys: Vec<ElGamalPublicKey>,
+    /// ciphertexts of the shares in the same order as `pub_keys`
+    pub cipher_texts: Vec<ElGamalCiphertext>,
+}
+
+/// group element `h[0] * term` encrypted for the term, to which decrypted shares are compared,
+/// since the term itself is not recovered from the ciphertexts
+pub fn get_encrypted_attribute(term: &Term) -> Result<G1Affine, RDFProofsError> {
+    let params = generate_params(1);
+    let hasher = get_hasher();
+    let value = hash_term_to_field(term.into(), &hasher)?;
+    Ok(params.h[0].mul_bigint(value.into_bigint()).into())
+}
+
+/// `get_encrypted_attribute` with the term in N-Triples, returning the multibase-encoded element
+pub fn get_encrypted_attribute_string(term: &str) -> Result<String, RDFProofsError> {
+    ark_to_base64url(&get_encrypted_attribute(&get_term_from_string(term)?)?)
+}
+
+// verifiable encryption of the hidden term requested by holder
+pub(crate) struct AttributeEncryption {
+    pub value: BlankNode,
+    pub pub_keys: Vec<ElGamalPublicKey>,
+    pub encryption: ElGamalEscrowedEncryption,
+}
+
+// encrypt the hidden terms deanonymized by `deanon_map` to the requested keys
+pub(crate) fn encrypt_attributes<R: RngCore>(
+    rng: &mut R,
+    requests: &[AttributeEncryptionRequest],
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<Vec<AttributeEncryption>, RDFProofsError> {
+    let params = generate_params(1);
+    let hasher = get_hasher();
+    requests
+        .iter()
+        .map(|AttributeEncryptionRequest { value, pub_keys }| {
+            if pub_keys.is_empty() {
+                return Err(RDFProofsError::InvalidAttributeEncryption);
+            }
+            let term = deanon_map
+                .get(&NamedOrBlankNode::from(value.clone()))
+                .ok_or(RDFProofsError::InvalidAttributeEncryption)?;
+            let term: Fr = hash_term_to_field(term.into(), &hasher)?;
+            Ok(AttributeEncryption {
+                value: value.clone(),
+                pub_keys: pub_keys.clone(),
+                encryption: elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+                    pub_keys,
+                    &params.h[0],
+                    &term,
+                    rng,
+                )?,
+            })
+        })
+        .collect()
+}
+
+// add encrypted hidden terms to VP proof, i.e.,
+// `_:proof encryptedAttribute [ encryptedValue _:value; encryptionKeys "..."; cipherTexts "..." ]`
+pub(crate) fn insert_attribute_encryptions(
+    vp: &mut Dataset,
+    vp_proof_id: &BlankNode,
+    vp_proof_graph_id: &BlankNode,
+    attribute_encryptions: &[AttributeEncryption],
+    labeler: &mut BlankNodeLabeler,
+) -> Result<(), RDFProofsError> {
+    for AttributeEncryption {
+        value,
+        pub_keys,
+        encryption,
+    } in attribute_encryptions
+    {
+        let encryption_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            vp_proof_id,
+            ENCRYPTED_ATTRIBUTE,
+            &encryption_id,
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &encryption_id,
+            ENCRYPTED_VALUE,
+            value,
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &encryption_id,
+            ENCRYPTION_KEYS,
+            LiteralRef::new_typed_literal(&ark_to_base64url(pub_keys)?, MULTIBASE),
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &encryption_id,
+            CIPHER_TEXTS,
+            LiteralRef::new_typed_literal(&ark_to_base64url(&encryption.cipher_texts)?, MULTIBASE),
+            vp_proof_graph_id,
+        ));
+    }
+    Ok(())
+}
+
+// get encrypted hidden terms from VP proof
+pub(crate) fn get_encrypted_attributes(
+    vp_proof: &GraphView,
+) -> Result<Vec<EncryptedAttribute>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    vp_proof
+        .objects_for_subject_predicate(proof_subject, ENCRYPTED_ATTRIBUTE)
+        .map(|encryption| {
+            let TermRef::BlankNode(encryption) = encryption else {
+                return Err(RDFProofsError::InvalidAttributeEncryption);
+            };
+            let Some(TermRef::BlankNode(value)) =
+                vp_proof.object_for_subject_predicate(encryption, ENCRYPTED_VALUE)
+            else {
+                return Err(RDFProofsError::InvalidAttributeEncryption);
+            };
+            let get_multibase = |predicate: NamedNodeRef| match vp_proof
+                .object_for_subject_predicate(encryption, predicate)
+            {
+                Some(TermRef::Literal(v)) if v.datatype() == MULTIBASE => Ok(v.value()),
+                _ => Err(RDFProofsError::InvalidAttributeEncryption),
+            };
+            let pub_keys: Vec<ElGamalPublicKey> =
+                multibase_to_ark(get_multibase(ENCRYPTION_KEYS)?)?;
+            let cipher_texts: Vec<ElGamalCiphertext> =
+                multibase_to_ark(get_multibase(CIPHER_TEXTS)?)?;
+            if pub_keys.is_empty() || pub_keys.len() != cipher_texts.len() {
+                return Err(RDFProofsError::InvalidAttributeEncryption);
+            }
+            Ok(EncryptedAttribute {
+                value: value.into_owned(),
+                pub_keys,
+                cipher_texts,
+            })
+        })
+        .collect()
+}
+
+// pair the encrypted hidden terms in canonicalized VP proof with their encryptions
+pub(crate) fn match_attribute_encryptions(
+    vp_proof: &GraphView,
+    mut attribute_encryptions: Vec<AttributeEncryption>,
+) -> Result<Vec<(NamedOrBlankNode, ElGamalEscrowedEncryption)>, RDFProofsError> {
+    let encrypted_attributes = get_encrypted_attributes(vp_proof)?;
+    if encrypted_attributes.len() != attribute_encryptions.len() {
+        return Err(RDFProofsError::InvalidAttributeEncryption);
+    }
+    encrypted_attributes
+        .into_iter()
+        .map(
+            |EncryptedAttribute {
+                 value,
+                 cipher_texts,
+                 ..
+             }| {
+                let index = attribute_encryptions
+                    .iter()
+                    .position(|e| e.encryption.cipher_texts == cipher_texts)
+                    .ok_or(RDFProofsError::InvalidAttributeEncryption)?;
+                Ok((
+                    value.into(),
+                    attribute_encryptions.swap_remove(index).encryption,
+                ))
+            },
+        )
+        .collect()
+}
+
+// check that the hidden terms are encrypted exactly to the key sets expected by verifier,
+// each of which is used once
+pub(crate) fn validate_encryption_keys(
+    encrypted_attributes: &[EncryptedAttribute],
+    expected_keys: &[Vec<ElGamalPublicKey>],
+) -> Result<(), RDFProofsError> {
+    let mut expected_keys = expected_keys.iter().collect::<Vec<_>>();
+    if encrypted_attributes.len() != expected_keys.len() {
+        return Err(RDFProofsError::MissingAttributeEncryption);
+    }
+    for EncryptedAttribute { pub_keys, .. } in encrypted_attributes {
+        let index = expected_keys
+            .iter()
+            .position(|keys| *keys == pub_keys)
+            .ok_or(RDFProofsError::MissingAttributeEncryption)?;
+        expected_keys.swap_remove(index);
+    }
+    Ok(())
+}
@@ -0,0 +1,389 @@
+//! generator of public test vectors for compatible implementations in other languages,
+//! which writes keys, VCs, blind issuance transcripts, predicates, VPs, and the expected
+//! verification outcomes derived from fixed seeds into the given directory, e.g.,
+//!
+//! ```sh
+//! cargo run --features vectors --bin generate-vectors -- vectors
+//! ```
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use legogroth16::circom::{CircomCircuit, R1CS};
+use multibase::Base;
+use oxrdf::{vocab::xsd, Literal};
+use rdf_proofs::{
+    ark_to_base64url, blind_sign_string, blind_verify_string, derive_proof_string,
+    error::RDFProofsError, request_blind_sign_string, sign_string, unblind_string,
+    verify_proof_string, verify_string, CircuitString, DeriveOptionsString, PredicateGraph,
+    VariableRef, VcPairString, VerifyOptionsString, SERIALIZATION_VERSION,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fs, path::Path};
+
+const SEED: u64 = 0;
+
+const KEY_GRAPH: &str = r#"<did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+<did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+"#;
+
+const VC: &str = r#"<did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+<did:example:john> <http://schema.org/name> "John Smith" .
+<did:example:john> <http://example.org/vocab/isPatientOf> _:b0 .
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+_:b0 <http://example.org/vocab/lotNumber> "0000001" .
+_:b0 <http://example.org/vocab/vaccinationDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+_:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
+<http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+
+const PROOF_OPTIONS: &str = r#"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+_:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+_:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+_:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+"#;
+
+const DISCLOSED_VC: &str = r#"_:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+_:e0 <http://example.org/vocab/isPatientOf> _:b0 .
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+_:b0 <http://example.org/vocab/vaccine> _:e1 .
+_:e2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+_:e2 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+_:e2 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+_:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+
+const DISCLOSED_VC_WITH_HIDDEN_LITERALS: &str = r#"_:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+_:e0 <http://schema.org/name> _:e3 .
+_:e0 <http://example.org/vocab/isPatientOf> _:b0 .
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+_:b0 <http://example.org/vocab/vaccine> _:e1 .
+_:b0 <http://example.org/vocab/vaccinationDate> _:e4 .
+_:e2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+_:e2 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+_:e2 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+_:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+
+const DEANON_MAP: [(&str, &str); 5] = [
+    ("_:e0", "<did:example:john>"),
+    ("_:e1", "<http://example.org/vaccine/a>"),
+    ("_:e2", "<http://example.org/vcred/00>"),
+    ("_:e3", "\"John Smith\""),
+    (
+        "_:e4",
+        "\"2022-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
+    ),
+];
+
+const SECRET: &[u8] = b"SECRET";
+const CHALLENGE: &str = "abcde";
+const DOMAIN: &str = "example.org";
+const LESS_THAN_PRV_PUB: &str = "https://zkp-ld.org/circuit/lessThanPrvPub";
+const VACCINATED_BEFORE: &str = "2022-12-31T00:00:00Z";
+
+// options of derive_proof varied across the cases of presentations
+#[derive(Default)]
+struct PresentationOptions {
+    name: &'static str,
+    bound: bool,
+    hidden_literals: bool,
+    challenge: bool,
+    domain: bool,
+    with_ppid: bool,
+    predicate: bool,
+}
+
+struct Circuit {
+    circuits: HashMap<String, CircuitString>,
+    snark_verifying_keys: HashMap<String, String>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "vectors".to_string());
+    let out_dir = Path::new(&out_dir);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut index = vec![];
+
+    // keys
+    write_case(
+        out_dir,
+        "keys",
+        &json!({ "keyGraph": KEY_GRAPH }),
+        &[("key_graph.nt", KEY_GRAPH)],
+        &mut index,
+    )?;
+
+    // signature
+    let proof = sign_string(&mut rng, VC, PROOF_OPTIONS, KEY_GRAPH, None, None)?;
+    write_case(
+        out_dir,
+        "sign",
+        &json!({
+            "document": VC,
+            "proofOptions": PROOF_OPTIONS,
+            "proof": proof,
+            "expected": outcome(verify_string(VC, &proof, KEY_GRAPH)),
+        }),
+        &[("vc.nt", VC), ("proof.nt", &proof)],
+        &mut index,
+    )?;
+
+    // blind issuance of VC bound to holder's secret
+    let request = request_blind_sign_string(&mut rng, SECRET, Some(CHALLENGE), None, None, None)?;
+    let blinded_proof = blind_sign_string(
+        &mut rng,
+        &request.commitment,
+        VC,
+        PROOF_OPTIONS,
+        KEY_GRAPH,
+        None,
+        None,
+        None,
+    )?;
+    let bound_proof = unblind_string(VC, &blinded_proof, &request.blinding)?;
+    write_case(
+        out_dir,
+        "blind-issuance",
+        &json!({
+            "document": VC,
+            "proofOptions": PROOF_OPTIONS,
+            "secret": multibase::encode(Base::Base64Url, SECRET),
+            "challenge": CHALLENGE,
+            "commitment": request.commitment,
+            "pokForCommitment": request.pok_for_commitment,
+            "blinding": request.blinding,
+            "blindedProof": blinded_proof,
+            "proof": bound_proof,
+            "expected": outcome(blind_verify_string(SECRET, VC, &bound_proof, KEY_GRAPH)),
+        }),
+        &[("vc.nt", VC), ("proof.nt", &bound_proof)],
+        &mut index,
+    )?;
+
+    // presentations covering the combinations of options
+    let circuit = setup_less_than_prv_pub_circuit(&mut rng)?;
+    let presentations = [
+        PresentationOptions {
+            name: "derive-hidden-terms",
+            ..Default::default()
+        },
+        PresentationOptions {
+            name: "derive-hidden-literals",
+            hidden_literals: true,
+            ..Default::default()
+        },
+        PresentationOptions {
+            name: "derive-challenge-domain",
+            challenge: true,
+            domain: true,
+            ..Default::default()
+        },
+        PresentationOptions {
+            name: "derive-bound",
+            bound: true,
+            challenge: true,
+            ..Default::default()
+        },
+        PresentationOptions {
+            name: "derive-bound-ppid",
+            bound: true,
+            challenge: true,
+            domain: true,
+            with_ppid: true,
+            ..Default::default()
+        },
+        PresentationOptions {
+            name: "derive-less-than-prv-pub-predicate",
+            hidden_literals: true,
+            predicate: true,
+            ..Default::default()
+        },
+    ];
+    for options in presentations {
+        let proof = if options.bound { &bound_proof } else { &proof };
+        write_presentation_cases(out_dir, &mut rng, &options, proof, &circuit, &mut index)?;
+    }
+
+    fs::write(
+        out_dir.join("index.json"),
+        serde_json::to_string_pretty(&json!({
+            "seed": SEED,
+            "serializationVersion": SERIALIZATION_VERSION,
+            "cases": index,
+        }))?,
+    )?;
+    Ok(())
+}
+
+// write the VP derived with the options and its tampered version, which must not verify
+fn write_presentation_cases(
+    out_dir: &Path,
+    rng: &mut StdRng,
+    options: &PresentationOptions,
+    proof: &str,
+    circuit: &Circuit,
+    index: &mut Vec<Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let disclosed_document = if options.hidden_literals {
+        DISCLOSED_VC_WITH_HIDDEN_LITERALS
+    } else {
+        DISCLOSED_VC
+    };
+    let disclosed_proof = proof
+        .lines()
+        .filter(|l| !l.contains("https://w3id.org/security#proofValue"))
+        .map(|l| format!("{}\n", l.trim()))
+        .collect::<String>();
+    let vc_pairs = vec![VcPairString::new(
+        VC,
+        proof,
+        disclosed_document,
+        &disclosed_proof,
+    )];
+    let deanon_map = DEANON_MAP
+        .iter()
+        .filter(|(k, _)| disclosed_document.contains(k))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>();
+    let challenge = options.challenge.then_some(CHALLENGE);
+    let domain = options.domain.then_some(DOMAIN);
+    let secret = options.bound.then_some(SECRET);
+    let predicates = if options.predicate {
+        Some(vec![PredicateGraph::new_string(LESS_THAN_PRV_PUB)?
+            .private("lesser", VariableRef::new("e4")?)
+            .public(
+                "greater",
+                Literal::new_typed_literal(VACCINATED_BEFORE, xsd::DATE_TIME),
+            )
+            .to_ntriples()])
+    } else {
+        None
+    };
+    let public_values = HashMap::from([(
+        LESS_THAN_PRV_PUB.to_string(),
+        HashMap::from([(
+            "greater".to_string(),
+            format!("\"{VACCINATED_BEFORE}\"^^<http://www.w3.org/2001/XMLSchema#dateTime>"),
+        )]),
+    )]);
+
+    let vp = derive_proof_string(
+        rng,
+        &vc_pairs,
+        &deanon_map,
+        KEY_GRAPH,
+        challenge,
+        domain,
+        DeriveOptionsString {
+            secret,
+            with_ppid: Some(options.with_ppid),
+            predicates: predicates.as_ref(),
+            circuits: options.predicate.then_some(&circuit.circuits),
+            ..Default::default()
+        },
+    )?;
+    let verify = |rng: &mut StdRng, vp: &str| {
+        outcome(verify_proof_string(
+            rng,
+            vp,
+            KEY_GRAPH,
+            challenge,
+            domain,
+            VerifyOptionsString {
+                snark_verifying_keys: options.predicate.then_some(&circuit.snark_verifying_keys),
+                public_values: options.predicate.then_some(&public_values),
+                ..Default::default()
+            },
+        ))
+    };
+    let case = json!({
+        "document": VC,
+        "proof": proof,
+        "disclosedDocument": disclosed_document,
+        "disclosedProof": disclosed_proof,
+        "deanonMap": deanon_map,
+        "challenge": challenge,
+        "domain": domain,
+        "secret": secret.map(|s| multibase::encode(Base::Base64Url, s)),
+        "withPpid": options.with_ppid,
+        "predicates": predicates,
+        "publicValues": options.predicate.then_some(&public_values),
+        "vp": vp,
+    });
+
+    let mut valid = case.clone();
+    valid["expected"] = verify(rng, &vp);
+    write_case(out_dir, options.name, &valid, &[("vp.nq", &vp)], index)?;
+
+    // replace the issuer of the disclosed VC
+    let tampered_vp = vp.replace("<did:example:issuer0> ", "<did:example:issuer1> ");
+    let mut tampered = case;
+    tampered["vp"] = tampered_vp.clone().into();
+    tampered["expected"] = verify(rng, &tampered_vp);
+    write_case(
+        out_dir,
+        &format!("{}-tampered", options.name),
+        &tampered,
+        &[("vp.nq", &tampered_vp)],
+        index,
+    )
+}
+
+// proving key of the bundled lessThanPrvPub circuit, which also serves as the verifying key
+fn setup_less_than_prv_pub_circuit(
+    rng: &mut StdRng,
+) -> Result<Circuit, Box<dyn std::error::Error>> {
+    let circom_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("circom/bls12381");
+    let circuit_r1cs = R1CS::from_file(circom_dir.join("less_than_prv_pub_64.r1cs"))
+        .map_err(|e| RDFProofsError::Other(format!("{:?}", e)))?;
+    let circuit_wasm = fs::read(circom_dir.join("less_than_prv_pub_64.wasm"))?;
+    let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+        .generate_proving_key(1, rng)
+        .map_err(|e| RDFProofsError::Other(format!("{:?}", e)))?;
+    let snark_proving_key = ark_to_base64url(&snark_proving_key)?;
+    Ok(Circuit {
+        circuits: HashMap::from([(
+            LESS_THAN_PRV_PUB.to_string(),
+            CircuitString {
+                circuit_r1cs: ark_to_base64url(&circuit_r1cs)?,
+                circuit_wasm: multibase::encode(Base::Base64Url, circuit_wasm),
+                snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
+            },
+        )]),
+        snark_verifying_keys: HashMap::from([(LESS_THAN_PRV_PUB.to_string(), snark_proving_key)]),
+    })
+}
+
+fn outcome<T>(result: Result<T, RDFProofsError>) -> Value {
+    match result {
+        Ok(_) => json!({ "verified": true }),
+        Err(e) => json!({ "verified": false, "error": e.to_string() }),
+    }
+}
+
+fn write_case(
+    out_dir: &Path,
+    name: &str,
+    case: &Value,
+    files: &[(&str, &str)],
+    index: &mut Vec<Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let case_dir = out_dir.join(name);
+    fs::create_dir_all(&case_dir)?;
+    fs::write(
+        case_dir.join("case.json"),
+        serde_json::to_string_pretty(case)?,
+    )?;
+    for (file_name, content) in files {
+        fs::write(case_dir.join(file_name), content)?;
+    }
+    index.push(json!({ "name": name, "expected": case.get("expected") }));
+    Ok(())
+}
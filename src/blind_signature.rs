@@ -1,20 +1,24 @@
 use crate::{
+    clock::{clock_or_system, Clock},
     common::{
         ark_to_base64url, configure_proof_core, get_graph_from_ntriples, get_hasher,
         get_vc_from_ntriples, get_verification_method_identifier, hash_byte_to_field,
         multibase_to_ark, BBSPlusSignature, Fr, Proof, Statements,
     },
     constants::{BLIND_SIG_REQUEST_CONTEXT, CRYPTOSUITE_BOUND_SIGN},
-    context::{DATA_INTEGRITY_PROOF, MULTIBASE, PROOF_VALUE},
+    context::{CONTROLLER, DATA_INTEGRITY_PROOF, MULTIBASE, PROOF_VALUE},
+    curve::G1Affine,
     error::RDFProofsError,
-    key_gen::generate_params,
+    key_gen::{generate_params, get_params_profile},
+    message_layout::{SecretSlot, TermEncoding},
+    redact::{redact_ark, redact_str},
     signature::{hash, transform, verify_base_proof},
     KeyGraph, VerifiableCredential,
 };
-use ark_bls12_381::G1Affine;
 use ark_std::{rand::RngCore, UniformRand};
 use blake2::Blake2b512;
-use oxrdf::{vocab::rdf::TYPE, Graph, LiteralRef, TripleRef};
+use chrono::{DateTime, Utc};
+use oxrdf::{vocab::rdf::TYPE, Graph, LiteralRef, NamedNodeRef, TripleRef};
 use proof_system::{
     prelude::MetaStatements,
     proof_spec::ProofSpec,
@@ -24,19 +28,65 @@ use proof_system::{
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Debug)]
 pub struct BlindSignRequest {
     pub commitment: G1Affine,
     pub blinding: Fr,
     pub pok_for_commitment: Option<Proof>,
+    /// issuer IRI to which the request is addressed, bound to `pok_for_commitment`
+    pub audience: Option<String>,
+    /// xsd:dateTime after which the request is rejected, bound to `pok_for_commitment`
+    pub expires_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct BlindSignRequestString {
     pub commitment: String,
     pub blinding: String,
     #[serde(rename = "pokForCommitment")]
     pub pok_for_commitment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    #[serde(rename = "expiresAt", default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+// blinding must not be leaked to issuer, so it is redacted in logs
+impl std::fmt::Debug for BlindSignRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlindSignRequest")
+            .field("commitment", &self.commitment)
+            .field("blinding", &redact_ark(&self.blinding))
+            .field("pok_for_commitment", &self.pok_for_commitment)
+            .field("audience", &self.audience)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for BlindSignRequestString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlindSignRequestString")
+            .field("commitment", &self.commitment)
+            .field("blinding", &redact_str(&self.blinding))
+            .field("pok_for_commitment", &self.pok_for_commitment)
+            .field("audience", &self.audience)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+// context of PoK for commitment, to which audience and expiry are appended if given
+// so that the request cannot be replayed to other issuers or after the expiry
+fn get_blind_sign_request_context(audience: Option<&str>, expires_at: Option<&str>) -> Vec<u8> {
+    let mut context = BLIND_SIG_REQUEST_CONTEXT.to_vec();
+    for (tag, value) in [(b'a', audience), (b'e', expires_at)] {
+        if let Some(value) = value {
+            context.push(tag);
+            context.extend((value.len() as u64).to_le_bytes());
+            context.extend(value.as_bytes());
+        }
+    }
+    context
 }
 
 pub fn request_blind_sign<R: RngCore>(
@@ -44,7 +94,13 @@ pub fn request_blind_sign<R: RngCore>(
     secret: &[u8],
     challenge: Option<&str>,
     skip_pok: Option<bool>,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
 ) -> Result<BlindSignRequest, RDFProofsError> {
+    if let Some(expires_at) = expires_at {
+        expires_at.parse::<DateTime<Utc>>()?;
+    }
+
     // bases := [h_0, h[0]]
     let params = generate_params(1);
     let bases = vec![params.h_0, params.h[0]];
@@ -71,6 +127,8 @@ pub fn request_blind_sign<R: RngCore>(
             commitment,
             blinding,
             pok_for_commitment: None,
+            audience: audience.map(String::from),
+            expires_at: expires_at.map(String::from),
         });
     }
 
@@ -81,7 +139,7 @@ pub fn request_blind_sign<R: RngCore>(
     ));
 
     // proof_spec := [statements, meta_statements, _, context]
-    let context = Some(BLIND_SIG_REQUEST_CONTEXT.to_vec());
+    let context = Some(get_blind_sign_request_context(audience, expires_at));
     let proof_spec = ProofSpec::new(statements, MetaStatements::new(), vec![], context);
     proof_spec.validate()?;
 
@@ -101,6 +159,8 @@ pub fn request_blind_sign<R: RngCore>(
         commitment,
         blinding,
         pok_for_commitment: Some(pok_for_commitment),
+        audience: audience.map(String::from),
+        expires_at: expires_at.map(String::from),
     })
 }
 
@@ -109,12 +169,16 @@ pub fn request_blind_sign_string<R: RngCore>(
     secret: &[u8],
     challenge: Option<&str>,
     skip_pok: Option<bool>,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
 ) -> Result<BlindSignRequestString, RDFProofsError> {
     let BlindSignRequest {
         commitment,
         blinding,
         pok_for_commitment,
-    } = request_blind_sign(rng, secret, challenge, skip_pok)?;
+        audience,
+        expires_at,
+    } = request_blind_sign(rng, secret, challenge, skip_pok, audience, expires_at)?;
     let commitment_base64url = ark_to_base64url(&commitment)?;
 
     let pok_for_commitment_base64url = if let Some(v) = pok_for_commitment {
@@ -128,6 +192,8 @@ pub fn request_blind_sign_string<R: RngCore>(
         commitment: commitment_base64url,
         pok_for_commitment: pok_for_commitment_base64url,
         blinding: blinding_base64url,
+        audience,
+        expires_at,
     })
 }
 
@@ -136,6 +202,8 @@ pub fn verify_blind_sign_request<R: RngCore>(
     commitment: &G1Affine,
     pok_for_commitment: Proof,
     challenge: Option<&str>,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
 ) -> Result<(), RDFProofsError> {
     // bases := [h_0, h[0], h[1], ...]
     let params = generate_params(1);
@@ -150,7 +218,7 @@ pub fn verify_blind_sign_request<R: RngCore>(
     ));
 
     // proof_spec := [statements, meta_statements, _, context]
-    let context = Some(BLIND_SIG_REQUEST_CONTEXT.to_vec());
+    let context = Some(get_blind_sign_request_context(audience, expires_at));
     let proof_spec = ProofSpec::new(statements, MetaStatements::new(), vec![], context);
     proof_spec.validate()?;
 
@@ -171,19 +239,42 @@ pub fn verify_blind_sign_request_string<R: RngCore>(
     commitment: &str,
     pok_for_commitment: &str,
     challenge: Option<&str>,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
 ) -> Result<(), RDFProofsError> {
     let commitment = multibase_to_ark(commitment)?;
     let pok_for_commitment = multibase_to_ark(pok_for_commitment)?;
-    verify_blind_sign_request(rng, &commitment, pok_for_commitment, challenge)
+    verify_blind_sign_request(
+        rng,
+        &commitment,
+        pok_for_commitment,
+        challenge,
+        audience,
+        expires_at,
+    )
 }
 
+/// blind sign the credential for the commitment in blind sign request,
+/// whose `audience` and `expires_at` (verified with `verify_blind_sign_request`) are enforced if given,
+/// where `clock` defaults to the system clock
 pub fn blind_sign<R: RngCore>(
     rng: &mut R,
     commitment: &G1Affine,
     unsecured_credential: &mut VerifiableCredential,
     key_graph: &KeyGraph,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
+    clock: Option<&dyn Clock>,
 ) -> Result<(), RDFProofsError> {
-    let proof = blind_sign_core(rng, commitment, unsecured_credential, key_graph)?;
+    let proof = blind_sign_core(
+        rng,
+        commitment,
+        unsecured_credential,
+        key_graph,
+        audience,
+        expires_at,
+        clock_or_system(clock),
+    )?;
     unsecured_credential.proof = proof;
     Ok(())
 }
@@ -194,6 +285,9 @@ pub fn blind_sign_string<R: RngCore>(
     document: &str,
     proof_options: &str,
     key_graph: &str,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
+    clock: Option<&dyn Clock>,
 ) -> Result<String, RDFProofsError> {
     let unsecured_credential = get_vc_from_ntriples(document, proof_options)?;
     let key_graph = get_graph_from_ntriples(key_graph)?.into();
@@ -202,6 +296,9 @@ pub fn blind_sign_string<R: RngCore>(
         &multibase_to_ark(commitment)?,
         &unsecured_credential,
         &key_graph,
+        audience,
+        expires_at,
+        clock_or_system(clock),
     )?;
     let result: String = proof
         .iter()
@@ -215,12 +312,23 @@ fn blind_sign_core<R: RngCore>(
     commitment: &G1Affine,
     unsecured_credential: &VerifiableCredential,
     key_graph: &KeyGraph,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
+    clock: &dyn Clock,
 ) -> Result<Graph, RDFProofsError> {
     let VerifiableCredential { document, proof } = unsecured_credential;
+    let proof_config = configure_proof(proof, clock)?;
+    validate_blind_sign_request_constraints(&proof_config, key_graph, audience, expires_at, clock)?;
     let transformed_data = transform(document)?;
-    let proof_config = configure_proof(proof)?;
     let canonical_proof_config = transform(&proof_config)?;
-    let hash_data = hash(None, &transformed_data, &canonical_proof_config)?;
+    // blind sign request commits to the secret in the first slot
+    let hash_data = hash(
+        None,
+        &transformed_data,
+        &canonical_proof_config,
+        SecretSlot::First,
+        TermEncoding::Termwise,
+    )?;
     let proof_value = serialize_proof_with_committed_messages(
         rng,
         commitment,
@@ -232,8 +340,34 @@ fn blind_sign_core<R: RngCore>(
     Ok(proof_value)
 }
 
-fn configure_proof(proof_options: &Graph) -> Result<Graph, RDFProofsError> {
-    configure_proof_core(proof_options, CRYPTOSUITE_BOUND_SIGN)
+// the request must be addressed to the controller of the signing key and not be expired
+fn validate_blind_sign_request_constraints(
+    proof_config: &Graph,
+    key_graph: &KeyGraph,
+    audience: Option<&str>,
+    expires_at: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<(), RDFProofsError> {
+    if let Some(audience) = audience {
+        let verification_method_identifier = get_verification_method_identifier(proof_config)?;
+        let verification_method =
+            key_graph.retrieve_verification_method(verification_method_identifier)?;
+        let controller = verification_method
+            .object_for_subject_predicate(verification_method_identifier, CONTROLLER);
+        if controller != Some(NamedNodeRef::new(audience)?.into()) {
+            return Err(RDFProofsError::MismatchedBlindSignRequestAudience);
+        }
+    }
+    if let Some(expires_at) = expires_at {
+        if expires_at.parse::<DateTime<Utc>>()? <= clock.now() {
+            return Err(RDFProofsError::ExpiredBlindSignRequest);
+        }
+    }
+    Ok(())
+}
+
+fn configure_proof(proof_options: &Graph, clock: &dyn Clock) -> Result<Graph, RDFProofsError> {
+    configure_proof_core(proof_options, CRYPTOSUITE_BOUND_SIGN, clock)
 }
 
 fn serialize_proof_with_committed_messages<R: RngCore>(
@@ -247,6 +381,12 @@ fn serialize_proof_with_committed_messages<R: RngCore>(
         .len()
         .try_into()
         .map_err(|_| RDFProofsError::MessageSizeOverflow)?;
+    // blind sign request commits to the secret with the shared params
+    if let Some(profile) = get_params_profile(proof_options)? {
+        return Err(RDFProofsError::UnsupportedParamsProfile(
+            profile.to_string(),
+        ));
+    }
     let params = generate_params(message_count);
 
     let verification_method_identifier = get_verification_method_identifier(proof_options)?;
@@ -330,10 +470,18 @@ pub fn blind_verify(
     let proof_config = secured_credential.get_proof_config();
     let proof_value = secured_credential.get_proof_value()?;
     // TODO: validate proof_config
+    let secret_slot = secured_credential.secret_slot()?;
+    let term_encoding = secured_credential.term_encoding()?;
     let transformed_data = transform(document)?;
     let canonical_proof_config = transform(&proof_config)?;
-    let hash_data = hash(Some(secret), &transformed_data, &canonical_proof_config)?;
-    verify_base_proof(hash_data, &proof_value, &proof_config, key_graph)
+    let hash_data = hash(
+        Some(secret),
+        &transformed_data,
+        &canonical_proof_config,
+        secret_slot,
+        term_encoding,
+    )?;
+    verify_base_proof(hash_data, &proof_value, document, &proof_config, key_graph)
 }
 
 pub fn blind_verify_string(
@@ -353,7 +501,7 @@ mod tests {
         blind_sign, blind_sign_string, blind_verify, blind_verify_string,
         common::get_graph_from_ntriples, context::PROOF_VALUE, error::RDFProofsError,
         request_blind_sign, request_blind_sign_string, unblind, unblind_string,
-        verify_blind_sign_request, verify_blind_sign_request_string, KeyGraph,
+        verify_blind_sign_request, verify_blind_sign_request_string, FixedClock, KeyGraph,
         VerifiableCredential,
     };
     use ark_std::rand::{rngs::StdRng, SeedableRng};
@@ -390,12 +538,15 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
         let verified = verify_blind_sign_request(
             &mut rng,
             &request.commitment,
             request.pok_for_commitment.unwrap(),
             Some(challenge),
+            None,
+            None,
         );
         assert!(verified.is_ok())
     }
@@ -405,12 +556,15 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign_string(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign_string(&mut rng, secret, Some(challenge), None, None, None).unwrap();
         let verified = verify_blind_sign_request_string(
             &mut rng,
             &request.commitment,
             &request.pok_for_commitment.unwrap(),
             Some(challenge),
+            None,
+            None,
         );
         assert!(verified.is_ok());
     }
@@ -464,13 +618,22 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        let result = blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph);
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        );
         assert!(result.is_ok());
     }
 
@@ -479,14 +642,23 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config =
             get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_AND_DATETIME_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        let result = blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph);
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        );
         assert!(result.is_ok());
     }
 
@@ -495,14 +667,23 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config =
             get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_CRYPTOSUITE).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        let result = blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph);
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        );
         assert!(result.is_ok())
     }
 
@@ -511,7 +692,8 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
@@ -519,7 +701,15 @@ mod tests {
             get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_INVALID_CRYPTOSUITE)
                 .unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        let result = blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph);
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        );
         assert!(matches!(
             result,
             Err(RDFProofsError::InvalidProofConfiguration)
@@ -531,7 +721,8 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign_string(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign_string(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let result = blind_sign_string(
             &mut rng,
@@ -539,22 +730,154 @@ mod tests {
             VC_1,
             VC_PROOF_WITHOUT_PROOFVALUE_1,
             KEY_GRAPH,
+            None,
+            None,
+            None,
         );
         assert!(result.is_ok())
     }
 
+    #[test]
+    fn blind_sign_with_audience_and_expiry() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let secret = b"SECRET";
+        let challenge = "challenge";
+        let audience = "did:example:issuer0";
+        let expires_at = "2100-01-01T00:00:00Z";
+        let request = request_blind_sign(
+            &mut rng,
+            secret,
+            Some(challenge),
+            None,
+            Some(audience),
+            Some(expires_at),
+        )
+        .unwrap();
+
+        // audience and expiry are bound to PoK for commitment
+        let verified = verify_blind_sign_request(
+            &mut rng,
+            &request.commitment,
+            request.pok_for_commitment.clone().unwrap(),
+            Some(challenge),
+            Some(audience),
+            Some(expires_at),
+        );
+        assert!(verified.is_ok());
+        let verified = verify_blind_sign_request(
+            &mut rng,
+            &request.commitment,
+            request.pok_for_commitment.clone().unwrap(),
+            Some(challenge),
+            Some("did:example:issuer1"),
+            Some(expires_at),
+        );
+        assert!(verified.is_err());
+        let verified = verify_blind_sign_request(
+            &mut rng,
+            &request.commitment,
+            request.pok_for_commitment.unwrap(),
+            Some(challenge),
+            Some(audience),
+            None,
+        );
+        assert!(verified.is_err());
+
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
+        let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
+        let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc.clone(),
+            &key_graph,
+            Some(audience),
+            Some(expires_at),
+            None,
+        );
+        assert!(result.is_ok());
+
+        // issuer other than audience
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc.clone(),
+            &key_graph,
+            Some("did:example:issuer1"),
+            Some(expires_at),
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(RDFProofsError::MismatchedBlindSignRequestAudience)
+        ));
+
+        // expired request
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc.clone(),
+            &key_graph,
+            Some(audience),
+            Some("2000-01-01T00:00:00Z"),
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(RDFProofsError::ExpiredBlindSignRequest)
+        ));
+
+        // expiry is checked against the given clock
+        let clock = FixedClock::new("2099-12-31T23:59:59Z".parse().unwrap());
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc.clone(),
+            &key_graph,
+            Some(audience),
+            Some(expires_at),
+            Some(&clock),
+        );
+        assert!(result.is_ok());
+        let clock = FixedClock::new(expires_at.parse().unwrap());
+        let result = blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            Some(audience),
+            Some(expires_at),
+            Some(&clock),
+        );
+        assert!(matches!(
+            result,
+            Err(RDFProofsError::ExpiredBlindSignRequest)
+        ));
+    }
+
     #[test]
     fn blind_sign_and_unblind_success() {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph).unwrap();
+        blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
         let result = unblind(&mut vc, &request.blinding);
         assert!(result.is_ok());
@@ -566,7 +889,8 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign_string(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign_string(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let proof = blind_sign_string(
             &mut rng,
@@ -574,6 +898,9 @@ mod tests {
             VC_1,
             VC_PROOF_WITHOUT_PROOFVALUE_1,
             KEY_GRAPH,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -586,13 +913,23 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph).unwrap();
+        blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
         unblind(&mut vc, &request.blinding).unwrap();
 
@@ -605,7 +942,8 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign_string(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign_string(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let blinded_proof = blind_sign_string(
             &mut rng,
@@ -613,6 +951,9 @@ mod tests {
             VC_1,
             VC_PROOF_WITHOUT_PROOFVALUE_1,
             KEY_GRAPH,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -627,13 +968,23 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0u64);
         let secret = b"SECRET";
         let challenge = "challenge";
-        let request = request_blind_sign(&mut rng, secret, Some(challenge), None).unwrap();
+        let request =
+            request_blind_sign(&mut rng, secret, Some(challenge), None, None, None).unwrap();
 
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        blind_sign(&mut rng, &request.commitment, &mut vc, &key_graph).unwrap();
+        blind_sign(
+            &mut rng,
+            &request.commitment,
+            &mut vc,
+            &key_graph,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
         unblind(&mut vc, &request.blinding).unwrap();
 
@@ -0,0 +1,225 @@
+use crate::{
+    common::{get_dataset_from_nquads, get_graph_from_ntriples, hash_str_to_str},
+    constants::VERIFICATION_BUNDLE_VERSION,
+    context::{CIRCUIT, SECRET_KEY_MULTIBASE},
+    error::RDFProofsError,
+    key_graph::export_key_graph_for_vp_string,
+    policy::PolicyDigest,
+    vc::VerifiablePresentation,
+    verify_proof::{verify_proof_string, VerificationResult, VerifyOptionsString},
+};
+use ark_std::rand::RngCore;
+use oxrdf::TermRef;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// SNARK verifying key of circuit in bundle with its digest,
+/// i.e., multibase-encoded SHA-256 of the multibase-encoded verifying key,
+/// which only proves the consistency of the bundle unless pinned by verifiers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledVerifyingKey {
+    #[serde(rename = "k")]
+    pub verifying_key: String,
+    #[serde(rename = "d")]
+    pub digest: String,
+}
+
+/// single-file CBOR bundle for offline verification, containing the VP, the minimal key graph,
+/// verifying keys of the circuits used in VP keyed by circuit IRIs, and verifier's policy,
+/// along with the challenge and domain the VP was requested with
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationBundle {
+    #[serde(rename = "v")]
+    pub version: u8,
+    #[serde(rename = "p")]
+    pub vp: String,
+    #[serde(rename = "k")]
+    pub key_graph: String,
+    #[serde(rename = "c", default)]
+    pub verifying_keys: BTreeMap<String, BundledVerifyingKey>,
+    #[serde(rename = "y", default)]
+    pub policy: Option<String>,
+    #[serde(rename = "n", default)]
+    pub challenge: Option<String>,
+    #[serde(rename = "d", default)]
+    pub domain: Option<String>,
+}
+
+impl VerificationBundle {
+    /// bundle VP with the key graph reduced to the verification methods referenced by VP
+    /// and the digests of verifying keys
+    pub fn new(
+        vp: &str,
+        key_graph: &str,
+        verifying_keys: &HashMap<String, String>,
+        policy: Option<&str>,
+        challenge: Option<&str>,
+        domain: Option<&str>,
+    ) -> Result<Self, RDFProofsError> {
+        Ok(Self {
+            version: VERIFICATION_BUNDLE_VERSION,
+            vp: vp.to_string(),
+            key_graph: export_key_graph_for_vp_string(vp, key_graph)?,
+            verifying_keys: verifying_keys
+                .iter()
+                .map(|(circuit, vk)| {
+                    (
+                        circuit.clone(),
+                        BundledVerifyingKey {
+                            verifying_key: vk.clone(),
+                            digest: hash_str_to_str(vk),
+                        },
+                    )
+                })
+                .collect(),
+            policy: policy.map(str::to_string),
+            challenge: challenge.map(str::to_string),
+            domain: domain.map(str::to_string),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RDFProofsError> {
+        Ok(serde_cbor::to_vec(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RDFProofsError> {
+        serde_cbor::from_slice(bytes)
+            .map_err(|e| RDFProofsError::InvalidVerificationBundle(e.to_string()))
+    }
+
+    // check that the bundle is consistent by itself before verifying VP;
+    // note that the bundled digests only prove the consistency of the bundle, not its authenticity,
+    // which requires the digests pinned by the caller
+    fn validate(
+        &self,
+        pinned_digests: Option<&HashMap<String, String>>,
+    ) -> Result<(), RDFProofsError> {
+        let invalid = |msg: &str| RDFProofsError::InvalidVerificationBundle(msg.to_string());
+
+        if self.version != VERIFICATION_BUNDLE_VERSION {
+            return Err(invalid("unsupported bundle version"));
+        }
+        if get_graph_from_ntriples(&self.key_graph)?
+            .triples_for_predicate(SECRET_KEY_MULTIBASE)
+            .next()
+            .is_some()
+        {
+            return Err(invalid("key graph must not contain secret keys"));
+        }
+        if self
+            .verifying_keys
+            .values()
+            .any(|vk| hash_str_to_str(&vk.verifying_key) != vk.digest)
+        {
+            return Err(invalid("verifying key does not match its digest"));
+        }
+        if let Some(pinned_digests) = pinned_digests {
+            if self
+                .verifying_keys
+                .iter()
+                .any(|(circuit, vk)| pinned_digests.get(circuit) != Some(&vk.digest))
+            {
+                return Err(invalid("verifying key does not match the pinned digest"));
+            }
+        }
+
+        // verifying keys must be given exactly for the circuits used in VP
+        let vp = get_dataset_from_nquads(&self.vp)?;
+        let vp: VerifiablePresentation = (&vp).try_into()?;
+        let circuits = vp
+            .predicates
+            .values()
+            .flat_map(|predicate| predicate.triples_for_predicate(CIRCUIT))
+            .map(|t| match t.object {
+                TermRef::NamedNode(circuit) => Ok(circuit.as_str().to_string()),
+                _ => Err(RDFProofsError::InvalidPredicate),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        if !circuits.iter().eq(self.verifying_keys.keys()) {
+            return Err(invalid(
+                "verifying keys do not match the circuits used in VP",
+            ));
+        }
+        Ok(())
+    }
+
+    // check that the bundled key graph, policy, challenge, and domain are those trusted by
+    // the caller, where the key graph must be the one exported for VP from the trusted key graph
+    fn validate_trusted(
+        &self,
+        key_graph: &str,
+        challenge: Option<&str>,
+        domain: Option<&str>,
+        policy: Option<&str>,
+    ) -> Result<(), RDFProofsError> {
+        let mismatched = |field: &str| {
+            RDFProofsError::InvalidVerificationBundle(format!(
+                "{} does not match the trusted one",
+                field
+            ))
+        };
+
+        let canonicalize = |graph: &str| {
+            Ok::<_, RDFProofsError>(rdf_canon::canonicalize_graph(&get_graph_from_ntriples(
+                graph,
+            )?)?)
+        };
+        if canonicalize(&self.key_graph)?
+            != canonicalize(&export_key_graph_for_vp_string(&self.vp, key_graph)?)?
+        {
+            return Err(mismatched("key graph"));
+        }
+        let policy_digest =
+            |policy: Option<&str>| policy.map(PolicyDigest::from_ntriples).transpose();
+        if policy_digest(self.policy.as_deref())? != policy_digest(policy)? {
+            return Err(mismatched("policy"));
+        }
+        if self.challenge.as_deref() != challenge {
+            return Err(mismatched("challenge"));
+        }
+        if self.domain.as_deref() != domain {
+            return Err(mismatched("domain"));
+        }
+        Ok(())
+    }
+}
+
+/// verify the bundle created by `VerificationBundle::to_bytes` without network access,
+/// checking its internal consistency before verifying the VP against the bundled verifying keys;
+/// the bundle is rejected unless its key graph, policy, challenge, and domain match
+/// the key graph, policy, and expected challenge and domain trusted by the caller,
+/// against which the VP is verified;
+/// the bundled digests of verifying keys are only checked for consistency with the bundled keys,
+/// so verifiers who do not trust the bundle's origin must give the digests they trust,
+/// keyed by circuit IRIs, as `pinned_digests`;
+/// note that verifiers must check by themselves that the challenge is fresh
+pub fn verify_bundle<R: RngCore>(
+    rng: &mut R,
+    bytes: &[u8],
+    key_graph: &str,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    policy: Option<&str>,
+    pinned_digests: Option<&HashMap<String, String>>,
+) -> Result<VerificationResult, RDFProofsError> {
+    let bundle = VerificationBundle::from_bytes(bytes)?;
+    bundle.validate(pinned_digests)?;
+    bundle.validate_trusted(key_graph, challenge, domain, policy)?;
+    let snark_verifying_keys = bundle
+        .verifying_keys
+        .into_iter()
+        .map(|(circuit, vk)| (circuit, vk.verifying_key))
+        .collect();
+    verify_proof_string(
+        rng,
+        &bundle.vp,
+        &bundle.key_graph,
+        challenge,
+        domain,
+        VerifyOptionsString {
+            snark_verifying_keys: Some(&snark_verifying_keys),
+            policy,
+            ..Default::default()
+        },
+    )
+}
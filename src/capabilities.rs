@@ -0,0 +1,165 @@
+use crate::{
+    constants::DECIMAL_SCALE,
+    curve::CURVE_NAME,
+    migrate::SERIALIZATION_VERSION,
+    proof_layout::{
+        supported_statement_tags, GENERATOR, PROOF_CONTEXT_VERSION, PROOF_LAYOUT_VERSION,
+    },
+    vc::supported_cryptosuites,
+};
+use serde::{Deserialize, Serialize};
+
+// all the cargo features declared in Cargo.toml except `default`,
+// reported in `Capabilities::features` if enabled in this build
+const FEATURES: [(&str, bool); 21] = [
+    ("rdf-star", cfg!(feature = "rdf-star")),
+    ("std", cfg!(feature = "std")),
+    ("parallel", cfg!(feature = "parallel")),
+    ("wasmer-js", cfg!(feature = "wasmer-js")),
+    ("wasmer-sys", cfg!(feature = "wasmer-sys")),
+    ("prove", cfg!(feature = "prove")),
+    ("verify", cfg!(feature = "verify")),
+    ("blind", cfg!(feature = "blind")),
+    ("predicates", cfg!(feature = "predicates")),
+    ("encryption", cfg!(feature = "encryption")),
+    ("reveal-debug", cfg!(feature = "reveal-debug")),
+    ("debug-print", cfg!(feature = "debug-print")),
+    ("paranoid", cfg!(feature = "paranoid")),
+    ("vc-store", cfg!(feature = "vc-store")),
+    ("report", cfg!(feature = "report")),
+    ("async", cfg!(feature = "async")),
+    ("store", cfg!(feature = "store")),
+    ("registry", cfg!(feature = "registry")),
+    ("sparql", cfg!(feature = "sparql")),
+    ("vectors", cfg!(feature = "vectors")),
+];
+
+// circuit templates bundled under `circom/` for which predicate builders are provided,
+// none of which are bundled until their compiled circuits are checked in
+const PREDICATE_TEMPLATES: [&str; 0] = [];
+
+/// capabilities of this build, depending on the enabled features, to be queried by
+/// wallets and verifiers negotiating what can be issued, presented, and verified
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// crate name and version, e.g., `rdf-proofs/0.10.0`
+    pub generator: String,
+    /// enabled cargo features, including those only for development, e.g., `debug-print`
+    pub features: Vec<String>,
+    /// pairing-friendly curve of keys, signatures, and proofs, i.e., `BLS12-381` or `BN254`
+    pub curve: String,
+    pub cryptosuites: Vec<String>,
+    /// types of statements in composite proofs that can be proven and verified
+    pub statement_types: Vec<String>,
+    /// circuit templates with predicate builders
+    pub predicate_templates: Vec<String>,
+    pub limits: CapabilityLimits,
+}
+
+/// limits and versions fixed at build time
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityLimits {
+    /// number of fractional digits preserved in `xsd:decimal` literals by `*-scaled-decimal-*` cryptosuites
+    pub decimal_scale: usize,
+    pub serialization_version: u8,
+    pub proof_layout_version: u32,
+    pub proof_context_version: u32,
+}
+
+/// machine-readable descriptor of the capabilities of this build
+pub fn capabilities() -> Capabilities {
+    fn to_strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+    Capabilities {
+        generator: GENERATOR.to_string(),
+        features: FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(feature, _)| feature.to_string())
+            .collect(),
+        curve: CURVE_NAME.to_string(),
+        cryptosuites: to_strings(&supported_cryptosuites()),
+        statement_types: to_strings(&supported_statement_tags()),
+        predicate_templates: if cfg!(feature = "predicates") {
+            to_strings(&PREDICATE_TEMPLATES)
+        } else {
+            vec![]
+        },
+        limits: CapabilityLimits {
+            decimal_scale: DECIMAL_SCALE,
+            serialization_version: SERIALIZATION_VERSION,
+            proof_layout_version: PROOF_LAYOUT_VERSION,
+            proof_context_version: PROOF_CONTEXT_VERSION,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capabilities, Capabilities, FEATURES};
+    use crate::{constants::CRYPTOSUITE_SIGN, supported_cryptosuites};
+
+    #[test]
+    fn capabilities_of_build() {
+        let capabilities = capabilities();
+        assert!(capabilities.generator.starts_with("rdf-proofs/"));
+        assert!(capabilities
+            .cryptosuites
+            .contains(&CRYPTOSUITE_SIGN.to_string()));
+        assert_eq!(
+            capabilities.cryptosuites.len(),
+            supported_cryptosuites().len()
+        );
+        assert!(capabilities.statement_types.contains(&"bbs+".to_string()));
+        assert_eq!(
+            capabilities
+                .statement_types
+                .contains(&"predicate".to_string()),
+            cfg!(feature = "predicates")
+        );
+        assert_eq!(
+            capabilities.features.contains(&"prove".to_string()),
+            cfg!(feature = "prove")
+        );
+        assert_eq!(
+            capabilities.curve,
+            if cfg!(curve_bn254) {
+                "BN254"
+            } else {
+                "BLS12-381"
+            }
+        );
+
+        // round trip via CBOR
+        let bytes = serde_cbor::to_vec(&capabilities).unwrap();
+        assert_eq!(
+            serde_cbor::from_slice::<Capabilities>(&bytes).unwrap(),
+            capabilities
+        );
+    }
+
+    #[test]
+    fn features_match_cargo_manifest() {
+        let manifest = include_str!("../Cargo.toml");
+        let declared = manifest
+            .split("[features]")
+            .nth(1)
+            .and_then(|features| features.split("\n[").next())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(" = ").map(|(feature, _)| feature.trim()))
+            .filter(|feature| *feature != "default")
+            .collect::<Vec<_>>();
+        assert_eq!(
+            declared,
+            FEATURES
+                .iter()
+                .map(|(feature, _)| *feature)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    clock::Clock,
+    common::{
+        get_graph_from_ntriples, get_vc_from_ntriples, get_verification_method_identifier,
+        hash_str_to_str, randomize_bnodes,
+    },
+    constants::CRYPTOSUITE_PROOF,
+    context::{
+        AUTHENTICATION, CHALLENGE, CRYPTOSUITE, DATA_INTEGRITY_PROOF, DIGEST_MULTIBASE, DOMAIN,
+        PROOF, PROOF_PURPOSE, PROOF_VALUE, VERIFIABLE_CREDENTIAL, VERIFIABLE_CREDENTIAL_TYPE,
+        VERIFIABLE_PRESENTATION_TYPE, VERIFICATION_METHOD,
+    },
+    error::RDFProofsError,
+    key_graph::{KeyGraph, KeyStore},
+    signature::{sign, verify, verify_with_key_store},
+    vc::VerifiableCredential,
+};
+#[cfg(feature = "verify")]
+use crate::{
+    inequality::validate_unique_credentials,
+    ordered_triple::{OrderedGraphNameRef, OrderedVerifiableCredentialGraphViews},
+    policy::{requires_unique_credentials, validate_acceptance_rules},
+    replay::ReplayGuard,
+    vc::VerifiableCredentialView,
+    verify_proof::VerificationResult,
+};
+use ark_std::rand::RngCore;
+use oxrdf::{
+    vocab::rdf::TYPE, BlankNode, Dataset, Graph, GraphNameRef, LiteralRef, NamedNode, NamedNodeRef,
+    QuadRef, SubjectRef, TermRef, TripleRef,
+};
+use std::collections::{HashMap, HashSet};
+
+/// derive VP disclosing the VCs as is, where the original VCs with their signatures are
+/// embedded and no zero-knowledge proof is generated, as a cheaper alternative to
+/// `derive_proof` when nothing is hidden; the VP is signed by the holder with the key of
+/// `holder_verification_method` in `key_graph` if given, which binds `challenge` and `domain`
+pub fn derive_classic_vp<R: RngCore>(
+    rng: &mut R,
+    vcs: &[VerifiableCredential],
+    key_graph: &KeyGraph,
+    holder_verification_method: Option<NamedNodeRef>,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    clock: Option<&dyn Clock>,
+) -> Result<Dataset, RDFProofsError> {
+    if vcs.is_empty() {
+        return Err(RDFProofsError::MissingInputToDeriveProof);
+    }
+    if holder_verification_method.is_none() && (challenge.is_some() || domain.is_some()) {
+        return Err(RDFProofsError::InvalidClassicVP(
+            "challenge and domain require holder's verification method".to_string(),
+        ));
+    }
+
+    let vp_id = BlankNode::default();
+    let mut vp = Dataset::default();
+    vp.insert(QuadRef::new(
+        &vp_id,
+        TYPE,
+        VERIFIABLE_PRESENTATION_TYPE,
+        GraphNameRef::DefaultGraph,
+    ));
+
+    for vc in vcs {
+        // VCs bound to holder's secret cannot be verified without the secret
+        if vc.is_bound()? {
+            return Err(RDFProofsError::InvalidClassicVP(
+                "VC bound to secret cannot be disclosed as is".to_string(),
+            ));
+        }
+        verify(vc, key_graph)?;
+
+        // blank nodes are randomized so as not to collide across VCs
+        let document = randomize_bnodes(&vc.document, &HashSet::new());
+        let proof = randomize_bnodes(&vc.proof, &HashSet::new());
+        let vc_id = document
+            .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+            .ok_or(RDFProofsError::VCWithoutVCType)?;
+
+        let vc_graph_id = BlankNode::default();
+        let vc_proof_graph_id = BlankNode::default();
+        vp.insert(QuadRef::new(
+            &vp_id,
+            VERIFIABLE_CREDENTIAL,
+            &vc_graph_id,
+            GraphNameRef::DefaultGraph,
+        ));
+        for triple in document.iter() {
+            vp.insert(triple.in_graph(&vc_graph_id));
+        }
+        vp.insert(QuadRef::new(vc_id, PROOF, &vc_proof_graph_id, &vc_graph_id));
+        for triple in proof.iter() {
+            vp.insert(triple.in_graph(&vc_proof_graph_id));
+        }
+    }
+
+    // holder's proof over the digest of VP, signed as a credential
+    if let Some(holder_verification_method) = holder_verification_method {
+        let holder_proof_id = BlankNode::default();
+        let mut proof_options = Graph::default();
+        proof_options.insert(TripleRef::new(&holder_proof_id, TYPE, DATA_INTEGRITY_PROOF));
+        proof_options.insert(TripleRef::new(
+            &holder_proof_id,
+            PROOF_PURPOSE,
+            AUTHENTICATION,
+        ));
+        proof_options.insert(TripleRef::new(
+            &holder_proof_id,
+            VERIFICATION_METHOD,
+            holder_verification_method,
+        ));
+        if let Some(challenge) = challenge {
+            proof_options.insert(TripleRef::new(
+                &holder_proof_id,
+                CHALLENGE,
+                LiteralRef::new_simple_literal(challenge),
+            ));
+        }
+        if let Some(domain) = domain {
+            proof_options.insert(TripleRef::new(
+                &holder_proof_id,
+                DOMAIN,
+                LiteralRef::new_simple_literal(domain),
+            ));
+        }
+        let mut holder_credential = VerifiableCredential::new(
+            get_holder_signed_document(&get_classic_vp_digest(&vp)?),
+            proof_options,
+        );
+        sign(rng, &mut holder_credential, key_graph, None, clock)?;
+
+        let holder_proof_graph_id = BlankNode::default();
+        vp.insert(QuadRef::new(
+            &vp_id,
+            PROOF,
+            &holder_proof_graph_id,
+            GraphNameRef::DefaultGraph,
+        ));
+        for triple in holder_credential.proof.iter() {
+            vp.insert(triple.in_graph(&holder_proof_graph_id));
+        }
+    }
+
+    Ok(vp)
+}
+
+/// `derive_classic_vp` with the VCs given as pairs of document and proof in N-Triples,
+/// returning VP in canonical N-Quads
+pub fn derive_classic_vp_string<R: RngCore>(
+    rng: &mut R,
+    vcs: &[(&str, &str)],
+    key_graph: &str,
+    holder_verification_method: Option<&str>,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    clock: Option<&dyn Clock>,
+) -> Result<String, RDFProofsError> {
+    let vcs = vcs
+        .iter()
+        .map(|(document, proof)| get_vc_from_ntriples(document, proof))
+        .collect::<Result<Vec<_>, _>>()?;
+    let key_graph = get_graph_from_ntriples(key_graph)?.into();
+    let holder_verification_method = holder_verification_method.map(NamedNode::new).transpose()?;
+    let vp = derive_classic_vp(
+        rng,
+        &vcs,
+        &key_graph,
+        holder_verification_method.as_ref().map(|vm| vm.as_ref()),
+        challenge,
+        domain,
+        clock,
+    )?;
+    Ok(rdf_canon::canonicalize(&vp)?)
+}
+
+// document signed by holder, i.e., `_:vp a VerifiablePresentation; digestMultibase "..."`
+fn get_holder_signed_document(vp_digest: &str) -> Graph {
+    let vp_id = BlankNode::default();
+    let mut document = Graph::default();
+    document.insert(TripleRef::new(&vp_id, TYPE, VERIFIABLE_PRESENTATION_TYPE));
+    document.insert(TripleRef::new(
+        &vp_id,
+        DIGEST_MULTIBASE,
+        LiteralRef::new_simple_literal(vp_digest),
+    ));
+    document
+}
+
+// multibase-encoded SHA-256 hash of canonical N-Quads of VP
+fn get_classic_vp_digest(vp: &Dataset) -> Result<String, RDFProofsError> {
+    Ok(hash_str_to_str(&rdf_canon::canonicalize(vp)?))
+}
+
+// the VP is classic if it has no derived proof and every VC comes with its signature
+pub(crate) fn is_classic_vp(vp: &Dataset) -> bool {
+    let has_derived_proof = vp.iter().any(|q| {
+        q.predicate == CRYPTOSUITE
+            && matches!(q.object, TermRef::Literal(v) if v.value() == CRYPTOSUITE_PROOF)
+    });
+    let vc_graph_names = vp
+        .iter()
+        .filter(|q| q.predicate == VERIFIABLE_CREDENTIAL && q.graph_name.is_default_graph())
+        .collect::<Vec<_>>();
+    !has_derived_proof
+        && !vc_graph_names.is_empty()
+        && vc_graph_names.iter().all(|q| {
+            get_graph_name(q.object).is_some_and(|vc_graph_name| {
+                vp.quads_for_graph_name(vc_graph_name)
+                    .filter(|q| q.predicate == PROOF)
+                    .filter_map(|q| get_graph_name(q.object))
+                    .any(|proof_graph_name| {
+                        vp.quads_for_graph_name(proof_graph_name)
+                            .any(|q| q.predicate == PROOF_VALUE)
+                    })
+            })
+        })
+}
+
+fn get_graph_name(term: TermRef) -> Option<GraphNameRef> {
+    match term {
+        TermRef::BlankNode(b) => Some(b.into()),
+        TermRef::NamedNode(n) => Some(n.into()),
+        _ => None,
+    }
+}
+
+// single graph linked from `subject` in `graph_name` via `link`
+fn get_linked_graph_name<'a>(
+    vp: &'a Dataset,
+    subject: SubjectRef<'a>,
+    link: NamedNodeRef,
+    graph_name: GraphNameRef<'a>,
+) -> Result<Option<GraphNameRef<'a>>, RDFProofsError> {
+    let mut linked = vp
+        .quads_for_subject(subject)
+        .filter(|q| q.predicate == link && q.graph_name == graph_name)
+        .map(|q| get_graph_name(q.object).ok_or(RDFProofsError::InvalidVP));
+    match (linked.next(), linked.next()) {
+        (None, _) => Ok(None),
+        (Some(graph_name), None) => Ok(Some(graph_name?)),
+        (Some(_), Some(_)) => Err(RDFProofsError::InvalidVP),
+    }
+}
+
+fn get_graph(vp: &Dataset, graph_name: GraphNameRef) -> Graph {
+    vp.quads_for_graph_name(graph_name)
+        .map(|q| TripleRef::new(q.subject, q.predicate, q.object))
+        .collect()
+}
+
+/// verify classic VP derived by `derive_classic_vp`, where the embedded VCs and
+/// the holder's proof if any are verified with their signatures;
+/// verifier's policy is applied to the VCs disclosed as is without being bound to VP,
+/// while classic VP cannot satisfy requirements that need derived proofs, e.g., protocol
+#[cfg(feature = "verify")]
+pub(crate) fn verify_classic_vp(
+    vp_dataset: &Dataset,
+    key_graph: &dyn KeyStore,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    policy: Option<&Graph>,
+    replay_guard: Option<&dyn ReplayGuard>,
+    min_distinct_credentials: &HashMap<NamedNode, usize>,
+    protocol: Option<&str>,
+) -> Result<VerificationResult, RDFProofsError> {
+    if protocol.is_some() {
+        return Err(RDFProofsError::InvalidClassicVP(
+            "protocol cannot be bound to classic VP".to_string(),
+        ));
+    }
+
+    let vp_id = vp_dataset
+        .quads_for_graph_name(GraphNameRef::DefaultGraph)
+        .find(|q| q.predicate == TYPE && q.object == TermRef::from(VERIFIABLE_PRESENTATION_TYPE))
+        .map(|q| q.subject)
+        .ok_or(RDFProofsError::InvalidVP)?;
+
+    // verify each VC with its signature
+    let mut known_graph_names = HashSet::new();
+    let mut disclosed_vcs = OrderedVerifiableCredentialGraphViews::new();
+    let mut distinct_credentials: HashMap<NamedNode, HashSet<String>> = HashMap::new();
+    let mut documents = vec![];
+    for q in vp_dataset
+        .quads_for_subject(vp_id)
+        .filter(|q| q.predicate == VERIFIABLE_CREDENTIAL && q.graph_name.is_default_graph())
+    {
+        let vc_graph_name = get_graph_name(q.object).ok_or(RDFProofsError::InvalidVP)?;
+        let vc_id = vp_dataset
+            .quads_for_graph_name(vc_graph_name)
+            .find(|q| q.predicate == TYPE && q.object == TermRef::from(VERIFIABLE_CREDENTIAL_TYPE))
+            .map(|q| q.subject)
+            .ok_or(RDFProofsError::VCWithoutVCType)?;
+        let vc_proof_graph_name = get_linked_graph_name(vp_dataset, vc_id, PROOF, vc_graph_name)?
+            .ok_or(RDFProofsError::InvalidVP)?;
+        let document: Graph = vp_dataset
+            .quads_for_graph_name(vc_graph_name)
+            .filter(|q| !(q.subject == vc_id && q.predicate == PROOF))
+            .map(|q| TripleRef::new(q.subject, q.predicate, q.object))
+            .collect();
+        let vc = VerifiableCredential::new(document, get_graph(vp_dataset, vc_proof_graph_name));
+        if vc.is_bound()? {
+            return Err(RDFProofsError::InvalidClassicVP(
+                "VC bound to secret cannot be disclosed as is".to_string(),
+            ));
+        }
+        verify_with_key_store(&vc, key_graph)?;
+
+        // VCs disclosed as is are distinct if their IDs differ,
+        // or their canonicalized documents differ if without IDs
+        let credential_id = match vc_id {
+            SubjectRef::NamedNode(id) => id.as_str().to_string(),
+            _ => hash_str_to_str(&rdf_canon::canonicalize_graph(&vc.document)?),
+        };
+        let verification_method = get_verification_method_identifier(&vc.proof)?.into_owned();
+        distinct_credentials
+            .entry(verification_method)
+            .or_default()
+            .insert(credential_id);
+        disclosed_vcs.insert(
+            OrderedGraphNameRef::new(vc_graph_name),
+            VerifiableCredentialView::new(
+                vp_dataset.graph(vc_graph_name),
+                vp_dataset.graph(vc_proof_graph_name),
+            ),
+        );
+        known_graph_names.insert(vc_graph_name.into_owned());
+        known_graph_names.insert(vc_proof_graph_name.into_owned());
+        documents.push(vc.document);
+    }
+
+    // verify holder's proof binding challenge and domain
+    let holder_proof_graph_name =
+        get_linked_graph_name(vp_dataset, vp_id, PROOF, GraphNameRef::DefaultGraph)?;
+    let (challenge_in_vp, domain_in_vp, proof_value_digest) = match holder_proof_graph_name {
+        None => (None, None, None),
+        Some(holder_proof_graph_name) => {
+            known_graph_names.insert(holder_proof_graph_name.into_owned());
+            let holder_proof = get_graph(vp_dataset, holder_proof_graph_name);
+            let vp_without_holder_proof = Dataset::from_iter(vp_dataset.iter().filter(|q| {
+                q.graph_name != holder_proof_graph_name
+                    && !(q.subject == vp_id && q.predicate == PROOF)
+            }));
+            let holder_credential = VerifiableCredential::new(
+                get_holder_signed_document(&get_classic_vp_digest(&vp_without_holder_proof)?),
+                holder_proof,
+            );
+            verify_with_key_store(&holder_credential, key_graph)?;
+            let proof_config = holder_credential.get_proof_config();
+            let proof_subject = proof_config
+                .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+                .ok_or(RDFProofsError::InvalidVP)?;
+            let get_literal = |predicate: NamedNodeRef| match proof_config
+                .object_for_subject_predicate(proof_subject, predicate)
+            {
+                Some(TermRef::Literal(v)) => Ok(Some(v.value().to_string())),
+                Some(_) => Err(RDFProofsError::MissingProofConfigLiteral(
+                    predicate.into_owned(),
+                )),
+                None => Ok(None),
+            };
+            (
+                get_literal(CHALLENGE)?,
+                get_literal(DOMAIN)?,
+                Some(hash_str_to_str(&holder_credential.get_proof_value()?)),
+            )
+        }
+    };
+    match (challenge, challenge_in_vp) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(RDFProofsError::MissingChallengeInRequest),
+        (Some(_), None) => Err(RDFProofsError::MissingChallengeInVP),
+        (Some(given), Some(in_vp)) if given == in_vp => Ok(()),
+        (Some(_), Some(_)) => Err(RDFProofsError::MismatchedChallenge),
+    }?;
+    match (domain, domain_in_vp) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(RDFProofsError::MissingDomainInRequest),
+        (Some(_), None) => Err(RDFProofsError::MissingDomainInVP),
+        (Some(given), Some(in_vp)) if given == in_vp => Ok(()),
+        (Some(_), Some(_)) => Err(RDFProofsError::MismatchedDomain),
+    }?;
+
+    // no other graphs than those of VCs and proofs are allowed
+    if vp_dataset.iter().any(|q| {
+        !q.graph_name.is_default_graph() && !known_graph_names.contains(&q.graph_name.into_owned())
+    }) {
+        return Err(RDFProofsError::InvalidVP);
+    }
+
+    // accept only issuers, verification methods, and cryptosuites allowed by verifier's policy
+    if let Some(policy) = policy {
+        validate_acceptance_rules(policy, &disclosed_vcs)?;
+        if requires_unique_credentials(policy) {
+            validate_unique_credentials(&[], &disclosed_vcs)?;
+        }
+    }
+
+    let distinct_credential_counts = distinct_credentials
+        .into_iter()
+        .map(|(verification_method, ids)| (verification_method, ids.len()))
+        .collect::<HashMap<_, _>>();
+    for (verification_method, min) in min_distinct_credentials {
+        let count = distinct_credential_counts
+            .get(verification_method)
+            .copied()
+            .unwrap_or(0);
+        if count < *min {
+            return Err(RDFProofsError::InsufficientDistinctCredentials(format!(
+                "{} of {} required for {}",
+                count, min, verification_method
+            )));
+        }
+    }
+
+    // VP digest without the holder's proof value, as with derived VPs
+    let vp_without_proof_value = Dataset::from_iter(vp_dataset.iter().filter(|q| {
+        !(q.predicate == PROOF_VALUE
+            && holder_proof_graph_name.is_some_and(|name| q.graph_name == name))
+    }));
+    let vp_digest = get_classic_vp_digest(&vp_without_proof_value)?;
+    if let Some(replay_guard) = replay_guard {
+        replay_guard.check_and_record(
+            challenge,
+            domain,
+            proof_value_digest.as_deref().unwrap_or(&vp_digest),
+        )?;
+    }
+
+    // disclosed documents with canonical blank node labels
+    let c14n_map = rdf_canon::issue(vp_dataset)?;
+    let disclosed_documents = documents
+        .iter()
+        .map(|document| Ok(rdf_canon::relabel_graph(document, &c14n_map)?))
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+
+    Ok(VerificationResult {
+        predicate_outputs: vec![],
+        distinct_credential_counts,
+        vp_digest,
+        disclosed_documents,
+        canonical_vp: rdf_canon::canonicalize(&vp_without_proof_value)?,
+        encrypted_attributes: vec![],
+        warnings: vec![],
+        secret_groups: vec![],
+    })
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+
+/// source of the current time used for timestamps and expiry checks,
+/// e.g., a trusted time source like roughtime or an HSM clock in deployments,
+/// or a frozen time in tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// system clock, used when no clock is given
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// clock frozen at the given time
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(now)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+// given clock or the system clock
+pub(crate) fn clock_or_system(clock: Option<&dyn Clock>) -> &dyn Clock {
+    clock.unwrap_or(&SystemClock)
+}
@@ -0,0 +1,143 @@
+//! Multibase codecs for the cryptographic objects exchanged in string form,
+//! e.g., `proofValue` of VCs, secret commitments, and SNARK proving keys.
+//!
+//! Each wrapper decodes a multibase string via `TryFrom<&str>` and encodes it back
+//! (in base64url) via `Display`, so that downstream crates do not have to depend on
+//! the generic `multibase_to_ark` / `ark_to_base64url` helpers.
+
+pub use crate::common::{BBSPlusSignature, ProvingKey};
+use crate::curve::G1Affine;
+use crate::error::RDFProofsError;
+use crate::migrate::{deserialize_versioned, serialize_versioned};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+/// error while encoding or decoding multibase strings
+#[derive(Debug)]
+pub enum CodecError {
+    Multibase(multibase::Error),
+    ArkSerialization(ark_serialize::SerializationError),
+}
+
+impl std::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecError::Multibase(e) => write!(f, "multibase error: {}", e),
+            CodecError::ArkSerialization(e) => write!(f, "arkworks serialization error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<multibase::Error> for CodecError {
+    fn from(e: multibase::Error) -> Self {
+        Self::Multibase(e)
+    }
+}
+
+impl From<ark_serialize::SerializationError> for CodecError {
+    fn from(e: ark_serialize::SerializationError) -> Self {
+        Self::ArkSerialization(e)
+    }
+}
+
+impl From<CodecError> for RDFProofsError {
+    fn from(e: CodecError) -> Self {
+        match e {
+            CodecError::Multibase(e) => Self::Multibase(e),
+            CodecError::ArkSerialization(e) => Self::ArkSerialization(e),
+        }
+    }
+}
+
+/// encode arkworks object into base64url multibase string
+pub fn encode<A: CanonicalSerialize>(ark: &A) -> Result<String, CodecError> {
+    let bytes = serialize_versioned(ark)?;
+    Ok(multibase::encode(multibase::Base::Base64Url, bytes))
+}
+
+/// decode multibase string (in any base) into arkworks object
+pub fn decode<A: CanonicalDeserialize>(s: &str) -> Result<A, CodecError> {
+    let (_, bytes) = multibase::decode(s)?;
+    Ok(deserialize_versioned(&bytes)?)
+}
+
+macro_rules! multibase_wrapper {
+    ($(#[$meta:meta])* $name:ident, $inner:ty) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name(pub $inner);
+
+        impl $name {
+            pub fn into_inner(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl From<$inner> for $name {
+            fn from(inner: $inner) -> Self {
+                Self(inner)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = CodecError;
+
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                Ok(Self(decode(s)?))
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let encoded = encode(&self.0).map_err(|_| std::fmt::Error)?;
+                write!(f, "{}", encoded)
+            }
+        }
+    };
+}
+
+multibase_wrapper!(
+    /// BBS+ signature, i.e., `proofValue` of VC
+    SignatureMultibase,
+    BBSPlusSignature
+);
+multibase_wrapper!(
+    /// Pedersen commitment to holder's secret, e.g., `secretCommitment` in VP
+    CommitmentMultibase,
+    G1Affine
+);
+multibase_wrapper!(
+    /// LegoGroth16 proving key for predicate circuits
+    ProvingKeyMultibase,
+    ProvingKey
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, CodecError, CommitmentMultibase, SignatureMultibase};
+    use crate::curve::G1Affine;
+    use ark_ec::AffineRepr;
+
+    #[test]
+    fn commitment_multibase_roundtrip() {
+        let commitment = CommitmentMultibase(G1Affine::generator());
+        let encoded = commitment.to_string();
+        assert!(encoded.starts_with('u'));
+        let decoded = CommitmentMultibase::try_from(encoded.as_str()).unwrap();
+        assert_eq!(decoded, commitment);
+        assert_eq!(encode(&decoded.into_inner()).unwrap(), encoded);
+    }
+
+    #[test]
+    fn decode_invalid_multibase_failure() {
+        assert!(matches!(
+            SignatureMultibase::try_from("!invalid"),
+            Err(CodecError::Multibase(_))
+        ));
+        assert!(matches!(
+            decode::<G1Affine>("uAAAA"),
+            Err(CodecError::ArkSerialization(_))
+        ));
+    }
+}
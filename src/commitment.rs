@@ -0,0 +1,128 @@
+use crate::{
+    common::{
+        ark_to_base64url, get_hasher, get_term_from_string, hash_term_to_field, multibase_to_ark,
+        Fr,
+    },
+    context::{COMMITMENT, COMMITTED_VALUE, DATA_INTEGRITY_PROOF, EQUAL_TO_COMMITMENT, MULTIBASE},
+    curve::G1Affine,
+    error::RDFProofsError,
+    key_gen::generate_params,
+    redact::{redact_ark, redact_str},
+};
+use ark_std::{rand::RngCore, UniformRand};
+use oxrdf::{dataset::GraphView, vocab::rdf::TYPE, BlankNode, Term, TermRef};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// holder's request to prove that the hidden term `value` is equal to
+/// the term committed by the verifier, where `blinding` is shared by the verifier
+pub struct CommitmentEqualityRequest {
+    pub commitment: G1Affine,
+    pub blinding: Fr,
+    pub value: BlankNode,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CommitmentEqualityRequestString {
+    pub commitment: String,
+    pub blinding: String,
+    pub value: String,
+}
+
+// blinding opens the verifier's commitment, so it is redacted in logs
+impl std::fmt::Debug for CommitmentEqualityRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommitmentEqualityRequest")
+            .field("commitment", &self.commitment)
+            .field("blinding", &redact_ark(&self.blinding))
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for CommitmentEqualityRequestString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommitmentEqualityRequestString")
+            .field("commitment", &self.commitment)
+            .field("blinding", &redact_str(&self.blinding))
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl TryFrom<&CommitmentEqualityRequestString> for CommitmentEqualityRequest {
+    type Error = RDFProofsError;
+
+    fn try_from(req: &CommitmentEqualityRequestString) -> Result<Self, Self::Error> {
+        let Term::BlankNode(value) = get_term_from_string(&req.value)? else {
+            return Err(RDFProofsError::InvalidCommitmentEquality);
+        };
+        Ok(Self {
+            commitment: multibase_to_ark(&req.commitment)?,
+            blinding: multibase_to_ark(&req.blinding)?,
+            value,
+        })
+    }
+}
+
+/// verifier's commitment to the expected term, returning the commitment and its blinding
+pub fn commit_to_term<R: RngCore>(
+    rng: &mut R,
+    term: &Term,
+) -> Result<(G1Affine, Fr), RDFProofsError> {
+    let params = generate_params(1);
+    let blinding = Fr::rand(rng);
+    let hasher = get_hasher();
+    let term_int = hash_term_to_field(term.into(), &hasher)?;
+
+    // commitment := h_0^{blinding} * h[0]^{term_int}
+    let committed_term = BTreeMap::from([(0_usize, &term_int)]);
+    let commitment = params.commit_to_messages(committed_term, &blinding)?;
+
+    Ok((commitment, blinding))
+}
+
+pub fn commit_to_term_string<R: RngCore>(
+    rng: &mut R,
+    term: &str,
+) -> Result<(String, String), RDFProofsError> {
+    let term = get_term_from_string(term)?;
+    let (commitment, blinding) = commit_to_term(rng, &term)?;
+    Ok((ark_to_base64url(&commitment)?, ark_to_base64url(&blinding)?))
+}
+
+pub(crate) fn get_commitment_bases() -> Vec<G1Affine> {
+    let params = generate_params(1);
+    vec![params.h_0, params.h[0]]
+}
+
+// get pairs of commitment and committed blank node from VP proof
+pub(crate) fn get_commitment_equalities(
+    vp_proof: &GraphView,
+) -> Result<Vec<(G1Affine, BlankNode)>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    vp_proof
+        .objects_for_subject_predicate(proof_subject, EQUAL_TO_COMMITMENT)
+        .map(|equality| {
+            let TermRef::BlankNode(equality) = equality else {
+                return Err(RDFProofsError::InvalidCommitmentEquality);
+            };
+            let Some(TermRef::Literal(commitment)) =
+                vp_proof.object_for_subject_predicate(equality, COMMITMENT)
+            else {
+                return Err(RDFProofsError::InvalidCommitmentEquality);
+            };
+            if commitment.datatype() != MULTIBASE {
+                return Err(RDFProofsError::InvalidCommitmentEquality);
+            }
+            let Some(TermRef::BlankNode(value)) =
+                vp_proof.object_for_subject_predicate(equality, COMMITTED_VALUE)
+            else {
+                return Err(RDFProofsError::InvalidCommitmentEquality);
+            };
+            Ok((multibase_to_ark(commitment.value())?, value.into_owned()))
+        })
+        .collect()
+}
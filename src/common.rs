@@ -1,14 +1,19 @@
 use crate::{
-    constants::{DELIMITER, MAP_TO_SCALAR_AS_HASH_DST, NYM_IRI_PREFIX},
+    clock::Clock,
+    conformance::term_to_hash_input,
+    constants::{DECIMAL_SCALE, DELIMITER, MAP_TO_SCALAR_AS_HASH_DST, NYM_IRI_PREFIX},
     context::{
-        CREATED, CRYPTOSUITE, DATA_INTEGRITY_PROOF, PREDICATE_VAL, PREDICATE_VAR, SCO_DATE,
+        CREATED, CRYPTOSUITE, DATA_INTEGRITY_PROOF, OUTPUT, PREDICATE_VAL, PREDICATE_VAR, SCO_DATE,
         SCO_DATETIME, VERIFICATION_METHOD,
     },
+    curve::{Curve, G1Affine},
     error::RDFProofsError,
+    message_layout::TermEncoding,
+    migrate::{deserialize_versioned, serialize_versioned},
+    proof_layout::{ProofExtension, PROOF_FORMAT},
     vc::{DisclosedVerifiableCredential, VerifiableCredentialTriples},
     VerifiableCredential,
 };
-use ark_bls12_381::{Bls12_381, G1Affine};
 use ark_ec::pairing::Pairing;
 use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
@@ -18,6 +23,7 @@ use bbs_plus::{
 };
 use blake2::Blake2b512;
 use chrono::{DateTime, NaiveDate, Utc};
+#[cfg(feature = "predicates")]
 use legogroth16::circom::R1CS as R1CSOrig;
 use multibase::Base;
 use oxrdf::{
@@ -25,16 +31,18 @@ use oxrdf::{
     vocab::{
         self,
         rdf::{FIRST, NIL, REST, TYPE},
-        xsd::{self, DATE, DATE_TIME, INTEGER},
+        xsd::{self, DATE, DATE_TIME, DECIMAL, INTEGER},
     },
     BlankNode, BlankNodeRef, Dataset, Graph, Literal, LiteralRef, NamedNode, NamedNodeRef,
     NamedOrBlankNode, SubjectRef, Term, TermRef, Triple, TripleRef,
 };
-use oxsdatatypes::DateTime as DateTimeOxsDataTypes;
+use oxsdatatypes::{Date as DateOxsDataTypes, DateTime as DateTimeOxsDataTypes, GYearMonth};
 use oxttl::{NQuadsParser, NTriplesParser};
+#[cfg(feature = "predicates")]
+use proof_system::prelude::R1CSCircomWitness as R1CSCircomWitnessOrig;
 use proof_system::{
-    prelude::R1CSCircomWitness as R1CSCircomWitnessOrig,
     proof::Proof as ProofOrig,
+    setup_params::SetupParams as SetupParamsOrig,
     statement::{
         bbs_plus::PoKBBSSignatureG1 as PoKBBSSignatureG1Stmt,
         ped_comm::PedersenCommitment,
@@ -51,23 +59,26 @@ use std::{
     str::FromStr,
 };
 
-pub type Fr = <Bls12_381 as Pairing>::ScalarField;
-pub type Proof = ProofOrig<Bls12_381, G1Affine>;
-pub type Statements = StatementsOrig<Bls12_381, <Bls12_381 as Pairing>::G1Affine>;
+pub type Fr = <Curve as Pairing>::ScalarField;
+pub type Proof = ProofOrig<Curve, G1Affine>;
+pub type Statements = StatementsOrig<Curve, <Curve as Pairing>::G1Affine>;
+pub type SetupParams = SetupParamsOrig<Curve, <Curve as Pairing>::G1Affine>;
 pub type BBSPlusHash = Blake2b512;
 pub type BBSPlusDefaultFieldHasher = DefaultFieldHasher<BBSPlusHash>;
-pub type BBSPlusParams = SignatureParamsG1<Bls12_381>;
-pub type BBSPlusKeypair = KeypairG2<Bls12_381>;
+pub type BBSPlusParams = SignatureParamsG1<Curve>;
+pub type BBSPlusKeypair = KeypairG2<Curve>;
 pub type BBSPlusSecretKey = SecretKey<Fr>;
-pub type BBSPlusPublicKey = PublicKeyG2<Bls12_381>;
-pub type BBSPlusSignature = SignatureG1<Bls12_381>;
+pub type BBSPlusPublicKey = PublicKeyG2<Curve>;
+pub type BBSPlusSignature = SignatureG1<Curve>;
 pub type PoKBBSPlusStmt<E> = PoKBBSSignatureG1Stmt<E>;
 pub type PoKBBSPlusWit<E> = PoKBBSSignatureG1Wit<E>;
 pub type PedersenCommitmentStmt = PedersenCommitment<G1Affine>;
-pub type ProvingKey = ProvingKeyOrig<Bls12_381>;
-pub type VerifyingKey = VerifyingKeyOrig<Bls12_381>;
-pub type R1CS = R1CSOrig<Bls12_381>;
-pub type R1CSCircomWitness = R1CSCircomWitnessOrig<Bls12_381>;
+pub type ProvingKey = ProvingKeyOrig<Curve>;
+pub type VerifyingKey = VerifyingKeyOrig<Curve>;
+#[cfg(feature = "predicates")]
+pub type R1CS = R1CSOrig<Curve>;
+#[cfg(feature = "predicates")]
+pub type R1CSCircomWitness = R1CSCircomWitnessOrig<Curve>;
 
 pub fn serialize_ark<S: serde::Serializer, A: CanonicalSerialize>(
     ark: &A,
@@ -90,8 +101,7 @@ pub fn ark_to_multibase<A: CanonicalSerialize>(
     base: Base,
     ark: &A,
 ) -> Result<String, RDFProofsError> {
-    let mut bytes = Vec::new();
-    ark.serialize_compressed(&mut bytes)?;
+    let bytes = serialize_versioned(ark)?;
     Ok(multibase::encode(base, bytes))
 }
 
@@ -101,22 +111,73 @@ pub fn ark_to_base64url<A: CanonicalSerialize>(ark: &A) -> Result<String, RDFPro
 
 pub fn multibase_to_ark<A: CanonicalDeserialize>(s: &str) -> Result<A, RDFProofsError> {
     let (_, bytes) = multibase::decode(s)?;
-    let ark = A::deserialize_compressed(&*bytes)?;
+    let ark = deserialize_versioned(&bytes)?;
     Ok(ark)
 }
 
+// legacy context of proofs without proof layout version
 #[derive(Serialize)]
-struct ProofSpecContext(pub String, pub Vec<StatementIndexMap>);
+struct LegacyProofSpecContext(pub String, pub Vec<StatementIndexMap>);
 
+#[derive(Serialize)]
+struct ProofSpecContext(
+    pub String,
+    pub Vec<StatementIndexMap>,
+    pub String,
+    pub Option<String>,
+);
+
+#[derive(Serialize)]
+struct ProofSpecContextWithCredentials(
+    pub String,
+    pub Vec<StatementIndexMap>,
+    pub String,
+    pub Option<String>,
+    pub Vec<String>,
+);
+
+/// context hashed into the proof spec, binding the canonicalized VP, the index map,
+/// the proof format (`PROOF_FORMAT`), and the application protocol identifier if any,
+/// so that proofs cannot be replayed across protocols with compatible structure;
+/// per-credential sub-contexts, if any, are appended as the fifth element
+/// so that legacy proofs without them are verified against the same context as before,
+/// and proofs without proof layout version (`0`) made by earlier versions are verified
+/// against the legacy context of the canonicalized VP and the index map only,
+/// unless an application protocol is required
 pub(crate) fn generate_proof_spec_context(
     vp: &Dataset,
     statement_index_map: &Vec<StatementIndexMap>,
+    layout_version: u32,
+    protocol: Option<&str>,
+    credential_contexts: Option<Vec<String>>,
 ) -> Result<Vec<u8>, RDFProofsError> {
     let serialized_vp = rdf_canon::serialize(&vp);
-    let serialized_vp_with_index_map = ProofSpecContext(serialized_vp, statement_index_map.clone());
-    Ok(serde_cbor::to_vec(&serialized_vp_with_index_map)?) // TODO: CBOR is overkill as we do not need deserialization
+    if layout_version == 0 && protocol.is_none() && credential_contexts.is_none() {
+        return Ok(serde_cbor::to_vec(&LegacyProofSpecContext(
+            serialized_vp,
+            statement_index_map.clone(),
+        ))?);
+    }
+    let Some(credential_contexts) = credential_contexts else {
+        let serialized_vp_with_index_map = ProofSpecContext(
+            serialized_vp,
+            statement_index_map.clone(),
+            PROOF_FORMAT.to_string(),
+            protocol.map(String::from),
+        );
+        return Ok(serde_cbor::to_vec(&serialized_vp_with_index_map)?); // TODO: CBOR is overkill as we do not need deserialization
+    };
+    Ok(serde_cbor::to_vec(&ProofSpecContextWithCredentials(
+        serialized_vp,
+        statement_index_map.clone(),
+        PROOF_FORMAT.to_string(),
+        protocol.map(String::from),
+        credential_contexts,
+    ))?)
 }
 
+/// mapping from the triples of disclosed VC in VP to the positions in the original VC,
+/// which is serialized into the proof value so that verifier can reconstruct BBS+ messages
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct StatementIndexMap {
     #[serde(rename = "a")]
@@ -143,6 +204,107 @@ impl StatementIndexMap {
             proof_len,
         }
     }
+
+    pub fn document_map(&self) -> &[usize] {
+        &self.document_map
+    }
+
+    pub fn document_len(&self) -> usize {
+        self.document_len
+    }
+
+    pub fn proof_map(&self) -> &[usize] {
+        &self.proof_map
+    }
+
+    pub fn proof_len(&self) -> usize {
+        self.proof_len
+    }
+
+    /// number of BBS+ messages: secret, terms of document, delimiter, and terms of proof
+    pub fn term_count(&self) -> Result<u32, RDFProofsError> {
+        self.document_len
+            .checked_add(self.proof_len)
+            .and_then(|n| n.checked_mul(3))
+            .and_then(|n| n.checked_add(2))
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or(RDFProofsError::InvalidStatementIndexMap(
+                "too many terms".to_string(),
+            ))
+    }
+
+    /// check that the map is valid for the disclosed VC with the given numbers of triples,
+    /// i.e., indices are within bounds without duplicates and the lengths match
+    pub fn validate(
+        &self,
+        disclosed_document_len: usize,
+        disclosed_proof_len: usize,
+    ) -> Result<(), RDFProofsError> {
+        self.term_count()?;
+        validate_index_map_entries(
+            "document",
+            &self.document_map,
+            self.document_len,
+            disclosed_document_len,
+        )?;
+        validate_index_map_entries(
+            "proof",
+            &self.proof_map,
+            self.proof_len,
+            disclosed_proof_len,
+        )
+    }
+}
+
+fn validate_index_map_entries(
+    name: &str,
+    map: &[usize],
+    len: usize,
+    disclosed_len: usize,
+) -> Result<(), RDFProofsError> {
+    if map.len() != disclosed_len {
+        return Err(RDFProofsError::InvalidStatementIndexMap(format!(
+            "{} map has {} entries for {} disclosed triples",
+            name,
+            map.len(),
+            disclosed_len
+        )));
+    }
+    let mut seen = HashSet::new();
+    for i in map {
+        if *i >= len {
+            return Err(RDFProofsError::InvalidStatementIndexMap(format!(
+                "{} index {} is out of bounds {}",
+                name, i, len
+            )));
+        }
+        if !seen.insert(i) {
+            return Err(RDFProofsError::InvalidStatementIndexMap(format!(
+                "duplicated {} index {}",
+                name, i
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// validate the index maps deserialized from proof value against the disclosed VCs in VP
+pub(crate) fn validate_index_maps(
+    index_map: &[StatementIndexMap],
+    vc_triples: &[VerifiableCredentialTriples],
+) -> Result<(), RDFProofsError> {
+    if index_map.len() != vc_triples.len() {
+        return Err(RDFProofsError::InvalidStatementIndexMap(format!(
+            "{} maps for {} VCs",
+            index_map.len(),
+            vc_triples.len()
+        )));
+    }
+    index_map.iter().zip(vc_triples).try_for_each(
+        |(map, VerifiableCredentialTriples { document, proof })| {
+            map.validate(document.len(), proof.len())
+        },
+    )
 }
 
 #[derive(Serialize, Deserialize)]
@@ -155,6 +317,60 @@ pub struct ProofWithIndexMap {
     pub proof: Proof,
     #[serde(rename = "b")]
     pub index_map: Vec<StatementIndexMap>,
+    /// proof layout version, which is `0` for proofs serialized without the following fields
+    #[serde(rename = "v", default)]
+    pub version: u32,
+    /// types of the statements in the composite proof, in the order of statements
+    #[serde(rename = "t", default)]
+    pub statement_tags: Vec<String>,
+    #[serde(rename = "x", default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<ProofExtension>,
+    /// crate name and version that generated the proof, e.g., `rdf-proofs/0.10.0`
+    #[serde(rename = "g", default, skip_serializing_if = "String::is_empty")]
+    pub generator: String,
+    /// cargo features required to verify the proof, e.g., `predicates`
+    #[serde(rename = "f", default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// proof spec context version, which is `0` for proofs without per-credential sub-contexts
+    #[serde(rename = "c", default)]
+    pub context_version: u32,
+}
+
+/// generator of blank node labels in VP, which are random by default
+/// or derived from a seed so that the VP layout is reproducible
+pub(crate) struct BlankNodeLabeler {
+    seed: Option<Vec<u8>>,
+    counter: u64,
+}
+
+impl BlankNodeLabeler {
+    pub(crate) fn random() -> Self {
+        Self {
+            seed: None,
+            counter: 0,
+        }
+    }
+
+    pub(crate) fn deterministic(seed: &[u8]) -> Self {
+        Self {
+            seed: Some(seed.to_vec()),
+            counter: 0,
+        }
+    }
+
+    pub(crate) fn generate(&mut self) -> BlankNode {
+        let Some(seed) = &self.seed else {
+            return BlankNode::default();
+        };
+        let digest = Sha256::new()
+            .chain_update(seed)
+            .chain_update(self.counter.to_be_bytes())
+            .finalize();
+        self.counter += 1;
+        // 128-bit labels as with random blank nodes, prefixed to avoid clashing with them
+        let label: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+        BlankNode::new_unchecked(format!("d{}", label))
+    }
 }
 
 pub fn is_nym(node: &NamedNode) -> bool {
@@ -200,32 +416,94 @@ pub fn hash_term_to_field(
     term: TermRef,
     hasher: &BBSPlusDefaultFieldHasher,
 ) -> Result<Fr, RDFProofsError> {
-    // limit integers to 64-bits
-    match term {
-        TermRef::Literal(v) if v.datatype() == INTEGER => {
-            let num: i64 = v.value().parse()?;
-            Ok(Fr::from(num))
+    if let TermRef::Literal(v) = term {
+        if let Some(encoded) = encode_literal_to_field(v)? {
+            return Ok(encoded);
         }
-        TermRef::Literal(v) if v.datatype() == DATE_TIME || v.datatype() == SCO_DATETIME => {
-            let datetime: DateTime<Utc> = v.value().parse()?;
-            let timestamp = datetime.timestamp();
-            Fr::try_from(timestamp)
-                .map_err(|_| RDFProofsError::InvalidDateTime(v.value().to_string()))
-        }
-        TermRef::Literal(v) if v.datatype() == DATE || v.datatype() == SCO_DATE => {
-            let date: NaiveDate = v.value().parse()?;
-            let datetime = date
-                .and_hms_opt(0, 0, 0)
-                .ok_or(RDFProofsError::InvalidDateTime(v.value().to_string()))?;
-            let timestamp = datetime.timestamp();
-            Fr::try_from(timestamp)
-                .map_err(|_| RDFProofsError::InvalidDateTime(v.value().to_string()))
+    }
+    hasher
+        .hash_to_field(term_to_hash_input(term).as_bytes(), 1)
+        .pop()
+        .ok_or(RDFProofsError::HashToField)
+}
+
+/// `hash_term_to_field` for the terms in the message vector of VC signed with the cryptosuite
+/// of `term_encoding`, where `xsd:decimal` literals are encoded into integers scaled by
+/// 10^`DECIMAL_SCALE` under `TermEncoding::ScaledDecimal`, e.g., `"1.5"` into `1500000`,
+/// rejecting the ones that cannot be represented without loss of precision
+pub(crate) fn hash_term_to_field_with_encoding(
+    term: TermRef,
+    term_encoding: TermEncoding,
+    hasher: &BBSPlusDefaultFieldHasher,
+) -> Result<Fr, RDFProofsError> {
+    match term {
+        TermRef::Literal(v)
+            if term_encoding == TermEncoding::ScaledDecimal && v.datatype() == DECIMAL =>
+        {
+            Ok(Fr::from(parse_scaled_decimal(v.value())?))
         }
-        _ => hasher
-            .hash_to_field(term.to_string().as_bytes(), 1)
-            .pop()
-            .ok_or(RDFProofsError::HashToField),
+        _ => hash_term_to_field(term, hasher),
+    }
+}
+
+/// value-preserving encoding of numeric and temporal literals into field elements,
+/// which is used for both signed terms and predicate inputs so that circuits can compare them:
+/// - `xsd:integer`: the integer itself
+/// - `xsd:dateTime` and `schema:DateTime`: UNIX timestamp in seconds (less than one second is rounded down)
+/// - `xsd:date` and `schema:Date`: UNIX timestamp in seconds at 00:00:00 UTC
+///
+/// values are limited to 64-bit signed integers, where negative values are mapped to `p - |v|`;
+/// `None` is returned for the other literals, which are hashed instead,
+/// including `xsd:decimal` unless VC is signed with `*-scaled-decimal-*` cryptosuites
+pub fn encode_literal_to_field(literal: LiteralRef) -> Result<Option<Fr>, RDFProofsError> {
+    let datatype = literal.datatype();
+    let value = literal.value();
+    if datatype == INTEGER {
+        let num: i64 = value.parse()?;
+        Ok(Some(Fr::from(num)))
+    } else if datatype == DATE_TIME || datatype == SCO_DATETIME {
+        let datetime: DateTime<Utc> = value.parse()?;
+        let timestamp = datetime.timestamp();
+        Fr::try_from(timestamp)
+            .map(Some)
+            .map_err(|_| RDFProofsError::InvalidDateTime(value.to_string()))
+    } else if datatype == DATE || datatype == SCO_DATE {
+        let date: NaiveDate = value.parse()?;
+        let datetime = date
+            .and_hms_opt(0, 0, 0)
+            .ok_or(RDFProofsError::InvalidDateTime(value.to_string()))?;
+        let timestamp = datetime.timestamp();
+        Fr::try_from(timestamp)
+            .map(Some)
+            .map_err(|_| RDFProofsError::InvalidDateTime(value.to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+// parse decimal into integer scaled by 10^`DECIMAL_SCALE`,
+// rejecting the ones that cannot be represented without loss of precision
+fn parse_scaled_decimal(value: &str) -> Result<i64, RDFProofsError> {
+    let invalid = || RDFProofsError::InvalidDecimal(value.to_string());
+    let (is_negative, unsigned) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if (int_part.is_empty() && frac_part.is_empty())
+        || !int_part.chars().all(|c| c.is_ascii_digit())
+        || !frac_part.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let frac_part = frac_part.trim_end_matches('0');
+    if frac_part.len() > DECIMAL_SCALE {
+        return Err(invalid());
     }
+    let scaled: i64 = format!("{}{:0<width$}", int_part, frac_part, width = DECIMAL_SCALE)
+        .parse()
+        .map_err(|_| invalid())?;
+    Ok(if is_negative { -scaled } else { scaled })
 }
 
 pub fn hash_byte_to_field(
@@ -418,6 +696,7 @@ pub fn get_vc_from_ntriples(
 pub(crate) fn configure_proof_core(
     proof_options: &Graph,
     cryptosuite: &str,
+    clock: &dyn Clock,
 ) -> Result<Graph, RDFProofsError> {
     let mut proof_config = proof_options.clone();
 
@@ -458,20 +737,47 @@ pub(crate) fn configure_proof_core(
         proof_config.insert(TripleRef::new(
             proof_options_subject,
             CREATED,
-            LiteralRef::new_typed_literal(&format!("{:?}", Utc::now()), xsd::DATE_TIME),
+            LiteralRef::new_typed_literal(&format!("{:?}", clock.now()), xsd::DATE_TIME),
         ));
     }
 
     Ok(proof_config)
 }
 
+/// validate `created` of VP proof, which is either `xsd:dateTime` or
+/// truncated by holder to `xsd:date` or `xsd:gYearMonth`
+pub(crate) fn validate_vp_created(created: Option<TermRef>) -> Result<(), RDFProofsError> {
+    let Some(created) = created else {
+        return Ok(());
+    };
+    let TermRef::Literal(v) = created else {
+        return Err(RDFProofsError::InvalidProofDatetime);
+    };
+    let (value, typ, _) = v.destruct();
+    let is_valid = match typ {
+        Some(t) if t == DATE_TIME => DateTimeOxsDataTypes::from_str(value).is_ok(),
+        Some(t) if t == DATE => DateOxsDataTypes::from_str(value).is_ok(),
+        Some(t) if t == xsd::G_YEAR_MONTH => GYearMonth::from_str(value).is_ok(),
+        _ => false,
+    };
+    if is_valid {
+        Ok(())
+    } else {
+        Err(RDFProofsError::InvalidProofDatetime)
+    }
+}
+
 pub(crate) fn canonicalize_graph_into_terms(graph: &Graph) -> Result<Vec<Term>, RDFProofsError> {
     let (canonicalized_graph, _) = canonicalize_graph(graph)?;
-    let canonicalized_triples = rdf_canon::sort_graph(&canonicalized_graph);
-    Ok(canonicalized_triples
+    Ok(canonicalized_graph_into_terms(&canonicalized_graph))
+}
+
+pub(crate) fn canonicalized_graph_into_terms(canonicalized_graph: &Graph) -> Vec<Term> {
+    let canonicalized_triples = rdf_canon::sort_graph(canonicalized_graph);
+    canonicalized_triples
         .into_iter()
         .flat_map(|t| vec![t.subject.into(), t.predicate.into(), t.object])
-        .collect())
+        .collect()
 }
 
 pub(crate) fn get_term_from_string(term_string: &str) -> Result<Term, RDFProofsError> {
@@ -549,12 +855,42 @@ pub(crate) fn read_public_var_list(
     }
 }
 
+// read the optional `output` list of predicate, whose values are the public outputs
+// of the circuit disclosed as literals in VP; the circuit is expected to output `1` if omitted
+pub(crate) fn read_predicate_outputs(
+    predicate_subject: SubjectRef,
+    graph: &GraphView,
+) -> Result<Option<Vec<(String, Term)>>, RDFProofsError> {
+    let mut outputs = vec![];
+    match graph.object_for_subject_predicate(predicate_subject, OUTPUT) {
+        None => return Ok(None),
+        Some(TermRef::BlankNode(output)) => read_public_var_list(output, &mut outputs, graph)?,
+        Some(_) => return Err(RDFProofsError::InvalidPredicate),
+    };
+    if outputs
+        .iter()
+        .any(|(_, val)| !matches!(val, Term::Literal(_)))
+    {
+        return Err(RDFProofsError::InvalidPredicate);
+    }
+    Ok(Some(outputs))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{get_hasher, hash_term_to_field, Fr};
-    use ark_ff::BigInt;
+    use super::{
+        encode_literal_to_field, get_hasher, hash_term_to_field, hash_term_to_field_with_encoding,
+        Fr, StatementIndexMap,
+    };
+    use crate::{
+        conformance::term_to_hash_input,
+        context::{SCO_DATE, SCO_DATETIME},
+        error::RDFProofsError,
+        message_layout::TermEncoding,
+    };
+    use ark_ff::{field_hashers::HashToField, BigInt, PrimeField};
     use oxrdf::{
-        vocab::xsd::{DATE, DATE_TIME, INTEGER},
+        vocab::xsd::{DATE, DATE_TIME, DECIMAL, INTEGER},
         LiteralRef, NamedNodeRef, TermRef,
     };
 
@@ -668,4 +1004,143 @@ mod tests {
             Err(crate::error::RDFProofsError::DateTimeParse(_))
         ));
     }
+
+    #[test]
+    fn encode_decimals_success() {
+        let hasher = get_hasher();
+        let scale = |decimal: &str| {
+            hash_term_to_field_with_encoding(
+                LiteralRef::new_typed_literal(decimal, DECIMAL).into(),
+                TermEncoding::ScaledDecimal,
+                &hasher,
+            )
+        };
+        let eqs = vec![
+            ("1.5", 1_500_000),
+            ("+1.50", 1_500_000),
+            ("-0.25", -250_000),
+            (".5", 500_000),
+            ("42", 42_000_000),
+            ("0.000001", 1),
+        ];
+        for (decimal, scaled) in eqs {
+            assert_eq!(scale(decimal).unwrap(), Fr::from(scaled as i64));
+        }
+
+        // decimals that cannot be encoded without loss of precision
+        for decimal in ["0.0000001", "1e3", ".", "92233720368547.75808"] {
+            assert!(matches!(
+                scale(decimal),
+                Err(crate::error::RDFProofsError::InvalidDecimal(_))
+            ));
+        }
+
+        // decimals are hashed as before unless the cryptosuite scales them,
+        // so that signatures over them are unchanged and any precision is accepted
+        for decimal in ["1.5", "0.0000001"] {
+            let term: TermRef = LiteralRef::new_typed_literal(decimal, DECIMAL).into();
+            let hashed = hasher
+                .hash_to_field(term_to_hash_input(term).as_bytes(), 1)
+                .pop()
+                .unwrap();
+            assert_eq!(hash_term_to_field(term, &hasher).unwrap(), hashed);
+            for term_encoding in [TermEncoding::Termwise, TermEncoding::SplitIri] {
+                assert_eq!(
+                    hash_term_to_field_with_encoding(term, term_encoding, &hasher).unwrap(),
+                    hashed
+                );
+            }
+            assert_eq!(
+                encode_literal_to_field(LiteralRef::new_typed_literal(decimal, DECIMAL)).unwrap(),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn encode_literals_preserving_order() {
+        // encoded values of non-negative literals must be ordered as their original values
+        let ordered = vec![
+            LiteralRef::new_typed_literal("1999-12-31", SCO_DATE),
+            LiteralRef::new_typed_literal("1999-12-31T00:00:01Z", DATE_TIME),
+            LiteralRef::new_typed_literal("2000-01-01T00:00:00Z", SCO_DATETIME),
+            LiteralRef::new_typed_literal("2000-01-01T00:00:00.5Z", DATE_TIME),
+            LiteralRef::new_typed_literal("2000-01-01T00:00:01+00:00", SCO_DATETIME),
+            LiteralRef::new_typed_literal("2000-01-01T09:00:02+09:00", DATE_TIME),
+        ];
+        let encoded = ordered
+            .iter()
+            .map(|v| encode_literal_to_field(*v).unwrap().unwrap().into_bigint())
+            .collect::<Vec<_>>();
+        assert!(encoded.windows(2).all(|w| w[0] <= w[1]));
+
+        // xsd and schema.org datatypes are encoded into the same values
+        assert_eq!(
+            encode_literal_to_field(LiteralRef::new_typed_literal("2000-01-01", DATE)).unwrap(),
+            encode_literal_to_field(LiteralRef::new_typed_literal(
+                "2000-01-01T00:00:00Z",
+                SCO_DATETIME
+            ))
+            .unwrap()
+        );
+
+        let hasher = get_hasher();
+        let decimals = ["0.1", "0.25", "1", "1.000001", "300.5"]
+            .iter()
+            .map(|v| {
+                hash_term_to_field_with_encoding(
+                    LiteralRef::new_typed_literal(v, DECIMAL).into(),
+                    TermEncoding::ScaledDecimal,
+                    &hasher,
+                )
+                .unwrap()
+                .into_bigint()
+            })
+            .collect::<Vec<_>>();
+        assert!(decimals.windows(2).all(|w| w[0] < w[1]));
+
+        // other literals are not encoded
+        assert_eq!(
+            encode_literal_to_field(LiteralRef::new_simple_literal("300")).unwrap(),
+            None
+        );
+        assert_eq!(
+            encode_literal_to_field(LiteralRef::new_typed_literal("300", INTEGER)).unwrap(),
+            Some(Fr::from(300))
+        );
+    }
+
+    #[test]
+    fn validate_statement_index_map() {
+        let index_map = StatementIndexMap::new(vec![0, 2, 5], 6, vec![0, 1], 4);
+        assert!(index_map.validate(3, 2).is_ok());
+        assert_eq!(index_map.term_count().unwrap(), 32);
+
+        // lengths must match the numbers of disclosed triples
+        assert!(matches!(
+            index_map.validate(2, 2),
+            Err(RDFProofsError::InvalidStatementIndexMap(_))
+        ));
+
+        // indices must be within bounds
+        let index_map = StatementIndexMap::new(vec![0, 2, 6], 6, vec![0, 1], 4);
+        assert!(matches!(
+            index_map.validate(3, 2),
+            Err(RDFProofsError::InvalidStatementIndexMap(_))
+        ));
+
+        // indices must not be duplicated
+        let index_map = StatementIndexMap::new(vec![0, 2, 5], 6, vec![1, 1], 4);
+        assert!(matches!(
+            index_map.validate(3, 2),
+            Err(RDFProofsError::InvalidStatementIndexMap(_))
+        ));
+
+        // term count must fit in the message count
+        let index_map = StatementIndexMap::new(vec![], usize::MAX, vec![], 0);
+        assert!(matches!(
+            index_map.validate(0, 0),
+            Err(RDFProofsError::InvalidStatementIndexMap(_))
+        ));
+    }
 }
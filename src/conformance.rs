@@ -0,0 +1,251 @@
+use crate::error::RDFProofsError;
+#[cfg(feature = "rdf-star")]
+use oxrdf::SubjectRef;
+use oxrdf::{vocab::xsd, Dataset, Graph, LiteralRef, TermRef, TripleRef};
+use std::fmt::Write;
+
+const DIRECTION_SEPARATOR: &str = "--";
+
+/// RDF version whose term syntax is accepted in credentials and VPs.
+///
+/// terms are hashed into messages in a serialization owned by this crate rather than
+/// the `Display` of the RDF library, which is the canonical N-Triples of RDF 1.1
+/// for RDF 1.1 terms in either mode, so that signatures made today remain verifiable
+/// after parsers are upgraded to RDF 1.2
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RdfConformance {
+    /// directional language tags, e.g., `"..."@ar--rtl`, and triple terms are rejected
+    #[default]
+    Rdf11,
+    /// directional language tags and triple terms are accepted, where the former are hashed
+    /// in their RDF 1.2 canonical N-Triples forms and the latter in the quoted triple syntax
+    /// of RDF-star N-Triples, i.e., `<<s p o>>`
+    Rdf12,
+}
+
+/// check that the dataset only uses RDF features of `conformance`
+pub fn check_conformance(
+    dataset: &Dataset,
+    conformance: RdfConformance,
+) -> Result<(), RDFProofsError> {
+    dataset
+        .iter()
+        .try_for_each(|quad| check_triple(quad.into(), conformance))
+}
+
+// check that the graph only uses RDF features of `conformance`
+pub(crate) fn check_graph_conformance(
+    graph: &Graph,
+    conformance: RdfConformance,
+) -> Result<(), RDFProofsError> {
+    graph
+        .iter()
+        .try_for_each(|triple| check_triple(triple, conformance))
+}
+
+fn check_triple(triple: TripleRef, conformance: RdfConformance) -> Result<(), RDFProofsError> {
+    #[cfg(feature = "rdf-star")]
+    if let SubjectRef::Triple(t) = triple.subject {
+        check_term(TermRef::Triple(t), conformance)?;
+    }
+    check_term(triple.object, conformance)
+}
+
+fn check_term(term: TermRef, conformance: RdfConformance) -> Result<(), RDFProofsError> {
+    match term {
+        TermRef::Literal(literal) => match (get_direction(literal), conformance) {
+            (None, _) => Ok(()),
+            (Some(_), RdfConformance::Rdf11) => Err(RDFProofsError::UnsupportedRdfFeature(
+                format!("directional language tag in {}", literal),
+            )),
+            (Some((_, direction)), RdfConformance::Rdf12) => {
+                if direction.eq_ignore_ascii_case("ltr") || direction.eq_ignore_ascii_case("rtl") {
+                    Ok(())
+                } else {
+                    Err(RDFProofsError::UnsupportedRdfFeature(format!(
+                        "invalid base direction in {}",
+                        literal
+                    )))
+                }
+            }
+        },
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(t) => match conformance {
+            RdfConformance::Rdf11 => Err(RDFProofsError::UnsupportedRdfFeature(format!(
+                "triple term {}",
+                t
+            ))),
+            RdfConformance::Rdf12 => check_triple(t.as_ref(), conformance),
+        },
+        _ => Ok(()),
+    }
+}
+
+// language and base direction of directional language-tagged string
+fn get_direction(literal: LiteralRef) -> Option<(&str, &str)> {
+    literal
+        .language()
+        .and_then(|language| language.split_once(DIRECTION_SEPARATOR))
+}
+
+// serialization of term to be hashed into message, which must never change
+// once signatures are made with it
+pub(crate) fn term_to_hash_input(term: TermRef) -> String {
+    let mut output = String::new();
+    write_term(&mut output, term);
+    output
+}
+
+fn write_term(output: &mut String, term: TermRef) {
+    match term {
+        TermRef::NamedNode(n) => {
+            let _ = write!(output, "<{}>", n.as_str());
+        }
+        TermRef::BlankNode(b) => {
+            let _ = write!(output, "_:{}", b.as_str());
+        }
+        TermRef::Literal(literal) => {
+            write_quoted_str(output, literal.value());
+            match (literal.language(), get_direction(literal)) {
+                (_, Some((language, direction))) => {
+                    let _ = write!(
+                        output,
+                        "@{}{}{}",
+                        language,
+                        DIRECTION_SEPARATOR,
+                        direction.to_ascii_lowercase()
+                    );
+                }
+                (Some(language), None) => {
+                    let _ = write!(output, "@{}", language);
+                }
+                (None, None) if literal.datatype() == xsd::STRING => {}
+                (None, None) => {
+                    let _ = write!(output, "^^<{}>", literal.datatype().as_str());
+                }
+            }
+        }
+        // quoted triple as written by oxrdf with `rdf-star` feature, e.g., `<<_:s <p> "o">>`
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(t) => {
+            output.push_str("<<");
+            write_term(output, t.subject.as_ref().into());
+            output.push(' ');
+            write_term(output, (&t.predicate).into());
+            output.push(' ');
+            write_term(output, (&t.object).into());
+            output.push_str(">>");
+        }
+    }
+}
+
+// string escaping of canonical N-Triples
+fn write_quoted_str(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '\u{08}' => output.push_str("\\b"),
+            '\t' => output.push_str("\\t"),
+            '\n' => output.push_str("\\n"),
+            '\u{0C}' => output.push_str("\\f"),
+            '\r' => output.push_str("\\r"),
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\0'..='\u{1F}' | '\u{7F}' => {
+                let _ = write!(output, "\\u{:04X}", u32::from(c));
+            }
+            _ => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_conformance, term_to_hash_input, RdfConformance};
+    use crate::{common::get_dataset_from_nquads, error::RDFProofsError};
+    use oxrdf::{Dataset, GraphNameRef, Literal, NamedNodeRef, QuadRef, TermRef};
+
+    #[test]
+    fn hash_input_of_rdf11_terms_is_unchanged() {
+        let dataset = get_dataset_from_nquads(
+            r#"
+            _:b0 <http://example.org/name> "John \"Jack\" Smith\n\\" .
+            _:b0 <http://example.org/greeting> "Hello"@en-US .
+            _:b0 <http://example.org/age> "42"^^<http://www.w3.org/2001/XMLSchema#integer> .
+            _:b0 <http://example.org/knows> <did:example:jane> .
+            "#,
+        )
+        .unwrap();
+        for quad in dataset.iter() {
+            for term in [quad.subject.into(), quad.predicate.into(), quad.object] {
+                assert_eq!(term_to_hash_input(term), term.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn directional_language_tags() {
+        let literal = Literal::new_language_tagged_literal_unchecked("مرحبا", "ar--RTL");
+        assert_eq!(
+            term_to_hash_input(TermRef::Literal(literal.as_ref())),
+            "\"مرحبا\"@ar--rtl"
+        );
+
+        let mut dataset = Dataset::new();
+        dataset.insert(QuadRef::new(
+            NamedNodeRef::new_unchecked("http://example.org/s"),
+            NamedNodeRef::new_unchecked("http://example.org/greeting"),
+            &literal,
+            GraphNameRef::DefaultGraph,
+        ));
+        assert!(matches!(
+            check_conformance(&dataset, RdfConformance::Rdf11),
+            Err(RDFProofsError::UnsupportedRdfFeature(_))
+        ));
+        assert!(check_conformance(&dataset, RdfConformance::Rdf12).is_ok());
+
+        let mut dataset = Dataset::new();
+        let literal = Literal::new_language_tagged_literal_unchecked("hello", "en--up");
+        dataset.insert(QuadRef::new(
+            NamedNodeRef::new_unchecked("http://example.org/s"),
+            NamedNodeRef::new_unchecked("http://example.org/greeting"),
+            &literal,
+            GraphNameRef::DefaultGraph,
+        ));
+        assert!(matches!(
+            check_conformance(&dataset, RdfConformance::Rdf12),
+            Err(RDFProofsError::UnsupportedRdfFeature(_))
+        ));
+    }
+
+    #[cfg(feature = "rdf-star")]
+    #[test]
+    fn hash_input_of_quoted_triples() {
+        use oxrdf::{BlankNode, NamedNode, Subject, Term, Triple};
+
+        let quoted = Triple::new(
+            BlankNode::new_unchecked("b0"),
+            NamedNode::new_unchecked("http://example.org/name"),
+            Literal::new_language_tagged_literal_unchecked("John", "en"),
+        );
+        let term = Term::Triple(Box::new(quoted.clone()));
+        assert_eq!(
+            term_to_hash_input(term.as_ref()),
+            r#"<<_:b0 <http://example.org/name> "John"@en>>"#
+        );
+        assert_eq!(term_to_hash_input(term.as_ref()), term.to_string());
+
+        // nested quoted triple
+        let nested = Term::Triple(Box::new(Triple::new(
+            Subject::Triple(Box::new(quoted)),
+            NamedNode::new_unchecked("http://example.org/source"),
+            NamedNode::new_unchecked("did:example:issuer0"),
+        )));
+        assert_eq!(
+            term_to_hash_input(nested.as_ref()),
+            r#"<<<<_:b0 <http://example.org/name> "John"@en>> <http://example.org/source> <did:example:issuer0>>>"#
+        );
+        assert_eq!(term_to_hash_input(nested.as_ref()), nested.to_string());
+    }
+}
@@ -1,10 +1,20 @@
-pub const CRYPTOSUITE_SIGN: &str = "bbs-termwise-signature-2023";
-pub const CRYPTOSUITE_BOUND_SIGN: &str = "bbs-termwise-bound-signature-2023";
-pub const CRYPTOSUITE_PROOF: &str = "bbs-termwise-proof-2023";
+pub use crate::curve::{
+    CRYPTOSUITE_BOUND_SIGN, CRYPTOSUITE_BOUND_SIGN_2024, CRYPTOSUITE_PROOF,
+    CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN, CRYPTOSUITE_SCALED_DECIMAL_SIGN, CRYPTOSUITE_SIGN,
+    CRYPTOSUITE_SIGN_2024, CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN, CRYPTOSUITE_SPLIT_IRI_SIGN,
+};
 pub const NYM_IRI_PREFIX: &str = "urn:nym:";
+pub const SKOLEM_IRI_INFIX: &str = "/.well-known/genid/";
 pub const GENERATOR_SEED: &[u8; 28] = b"BBS_*_MESSAGE_GENERATOR_SEED"; // TODO: fix it later
 pub const MAP_TO_SCALAR_AS_HASH_DST: &[u8; 32] = b"BBS_*_MAP_MSG_TO_SCALAR_AS_HASH_"; // TODO: fix it later
 pub const DELIMITER: &[u8; 13] = b"__DELIMITER__"; // TODO: fix it later
 pub const BLIND_SIG_REQUEST_CONTEXT: &[u8; 23] = b"BBS_*_BLIND_SIG_REQUEST"; // TODO: fix it later
+pub const HOLDER_STATE_CONTEXT: &[u8; 24] = b"RDF_PROOFS_HOLDER_STATE_";
+pub const HOLDER_STATE_VERSION: u8 = 2;
+pub const VERIFICATION_BUNDLE_VERSION: u8 = 1;
+pub const VC_STORE_CONTEXT: &[u8; 19] = b"RDF_PROOFS_VC_STORE";
+pub const VC_STORE_VERSION: u8 = 1;
+pub const CREDENTIAL_CONTEXT_DST: &[u8; 29] = b"RDF_PROOFS_CREDENTIAL_CONTEXT";
 pub const PPID_SEED: &[u8; 15] = b"BBS_*_PPID_SEED"; // TODO: fix it later
+pub const DECIMAL_SCALE: usize = 6; // number of fractional digits preserved in xsd:decimal scaled by `*-scaled-decimal-*` cryptosuites
 pub const PPID_PREFIX: &str = "https://zkp-ld.org/.well-known/genid/"; // TODO: fix it later
@@ -12,6 +12,18 @@ pub const VERIFIABLE_CREDENTIAL: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#verifiableCredential");
 pub const HOLDER: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#holder");
+pub const ISSUER: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#issuer");
+pub const CREDENTIAL_SUBJECT: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#credentialSubject");
+pub const ISSUANCE_DATE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#issuanceDate");
+pub const EXPIRATION_DATE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#expirationDate");
+pub const EVIDENCE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#evidence");
+pub const TERMS_OF_USE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://www.w3.org/2018/credentials#termsOfUse");
 
 // https://w3id.org/security#
 pub const DATA_INTEGRITY_PROOF: NamedNodeRef =
@@ -24,6 +36,8 @@ pub const CRYPTOSUITE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://w3id.org/security#cryptosuite");
 pub const PROOF_PURPOSE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://w3id.org/security#proofPurpose");
+pub const PREVIOUS_PROOF: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://w3id.org/security#previousProof");
 pub const PROOF_VALUE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://w3id.org/security#proofValue");
 pub const VERIFICATION_METHOD: NamedNodeRef =
@@ -34,6 +48,10 @@ pub const AUTHENTICATION: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://w3id.org/security#authenticationMethod");
 pub const PUBLIC_KEY_MULTIBASE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://w3id.org/security#publicKeyMultibase");
+pub const CONTROLLER: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://w3id.org/security#controller");
+pub const KEY_EXPIRES: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://w3id.org/security#expires");
 pub const SECRET_KEY_MULTIBASE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://w3id.org/security#secretKeyMultibase");
 pub const MULTIBASE: NamedNodeRef =
@@ -42,6 +60,52 @@ pub const MULTIBASE: NamedNodeRef =
 // https://zkp-ld.org/security#
 pub const SECRET_COMMITMENT: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://zkp-ld.org/security#secretCommitment");
+pub const ROTATED_SECRET_COMMITMENT: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#rotatedSecretCommitment");
+pub const EQUAL_TO_COMMITMENT: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#equalToCommitment");
+pub const CREDENTIAL_SUMMARY: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#credentialSummary");
+pub const CREDENTIAL_COUNT: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#credentialCount");
+pub const COMMITMENT: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#commitment");
+pub const COMMITTED_VALUE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#committedValue");
+pub const ENCRYPTED_ATTRIBUTE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#encryptedAttribute");
+pub const ENCRYPTED_VALUE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#encryptedValue");
+pub const ENCRYPTION_KEYS: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#encryptionKeys");
+pub const CIPHER_TEXTS: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#cipherTexts");
+pub const DISTINCT_CREDENTIALS: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#distinctCredentials");
+pub const LHS: NamedNodeRef = NamedNodeRef::new_unchecked("https://zkp-ld.org/security#lhs");
+pub const RHS: NamedNodeRef = NamedNodeRef::new_unchecked("https://zkp-ld.org/security#rhs");
+pub const SAME_NAMESPACE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#sameNamespace");
+pub const NOT_EQUAL: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#notEqual");
+pub const NOT_EQUAL_TYPE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#NotEqual");
+pub const DIGEST_MULTIBASE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#digestMultibase");
+pub const EQUIVALENCES: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#equivalences");
+pub const EQUIVALENCE_CLASS_TYPE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#EquivalenceClass");
+pub const EQUIVALENT_TERM: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#equivalentTerm");
+pub const OCCURRENCE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#occurrence");
+pub const IN_CREDENTIAL: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#inCredential");
+pub const PARAMS_PROFILE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#paramsProfile");
+pub const POLICY_DIGEST: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#policyDigest");
 pub const PREDICATE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://zkp-ld.org/security#predicate");
 pub const PREDICATE_TYPE: NamedNodeRef =
@@ -51,6 +115,7 @@ pub const CIRCUIT: NamedNodeRef =
 pub const PRIVATE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://zkp-ld.org/security#private");
 pub const PUBLIC: NamedNodeRef = NamedNodeRef::new_unchecked("https://zkp-ld.org/security#public");
+pub const OUTPUT: NamedNodeRef = NamedNodeRef::new_unchecked("https://zkp-ld.org/security#output");
 pub const PRIVATE_VARIABLE: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://zkp-ld.org/security#PrivateVariable");
 pub const PUBLIC_VARIABLE: NamedNodeRef =
@@ -59,6 +124,51 @@ pub const PREDICATE_VAR: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://zkp-ld.org/security#var");
 pub const PREDICATE_VAL: NamedNodeRef =
     NamedNodeRef::new_unchecked("https://zkp-ld.org/security#val");
+pub const DEVICE_BINDING: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deviceBinding");
+pub const KEY_HANDLE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#keyHandle");
+pub const DEVICE_KEY: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deviceKey");
+pub const DEVICE_CERTIFICATE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deviceCertificate");
+pub const AUTHENTICATOR_DATA: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#authenticatorData");
+pub const CLIENT_DATA_JSON: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#clientDataJSON");
+pub const DEVICE_SIGNATURE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deviceSignature");
+pub const SECRET_GROUP: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#secretGroup");
+pub const BOUND_CREDENTIAL: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#boundCredential");
+pub const NONCE_TOKEN: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#NonceToken");
+pub const NONCE: NamedNodeRef = NamedNodeRef::new_unchecked("https://zkp-ld.org/security#nonce");
+pub const AUDIENCE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#audience");
+pub const EXPIRES: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#expires");
+pub const ALLOWED_ISSUER: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#allowedIssuer");
+pub const DENIED_ISSUER: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deniedIssuer");
+pub const ALLOWED_VERIFICATION_METHOD: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#allowedVerificationMethod");
+pub const DENIED_VERIFICATION_METHOD: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deniedVerificationMethod");
+pub const ALLOWED_CRYPTOSUITE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#allowedCryptosuite");
+pub const DENIED_CRYPTOSUITE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#deniedCryptosuite");
+pub const UNIQUE_CREDENTIALS: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#uniqueCredentials");
+pub const ALLOWED_CREDENTIAL_TYPE: NamedNodeRef =
+    NamedNodeRef::new_unchecked("https://zkp-ld.org/security#allowedCredentialType");
+
+// http://www.w3.org/ns/prov#
+pub const WAS_DERIVED_FROM: NamedNodeRef =
+    NamedNodeRef::new_unchecked("http://www.w3.org/ns/prov#wasDerivedFrom");
 
 // http://schema.org/
 pub const SCO_DATE: NamedNodeRef = NamedNodeRef::new_unchecked("http://schema.org/Date");
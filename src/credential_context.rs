@@ -0,0 +1,133 @@
+use crate::{
+    constants::CREDENTIAL_CONTEXT_DST, context::VERIFICATION_METHOD, error::RDFProofsError,
+    proof_layout::PROOF_CONTEXT_VERSION, vc::VerifiableCredentialTriples,
+};
+use multibase::Base;
+use oxrdf::Term;
+use sha2::{Digest, Sha256};
+
+// per-credential sub-contexts mixed into the proof spec context from context version `1`,
+// in the order of VC graphs in canonicalized VP, or none for the legacy context `0`
+pub(crate) fn get_credential_contexts(
+    context_version: u32,
+    disclosed_vcs: &[VerifiableCredentialTriples],
+) -> Result<Option<Vec<String>>, RDFProofsError> {
+    match context_version {
+        0 => Ok(None),
+        PROOF_CONTEXT_VERSION => disclosed_vcs
+            .iter()
+            .enumerate()
+            .map(|(index, vc)| generate_credential_context(index, vc))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+        _ => Err(RDFProofsError::UnsupportedProofLayoutVersion(
+            context_version,
+        )),
+    }
+}
+
+// sub-context of the `index`-th disclosed VC in VP, i.e., multibase (base64url)-encoded
+// SHA-256 of `CREDENTIAL_CONTEXT_DST`, the index, the issuer key (`verificationMethod`),
+// and the credential digest, each followed by a newline, where the credential digest is
+// SHA-256 of the sorted N-Triples of the disclosed document followed by those of its proof;
+// it binds each BBS+ sub-proof to the credential and its position in the VP layout
+pub(crate) fn generate_credential_context(
+    index: usize,
+    vc: &VerifiableCredentialTriples,
+) -> Result<String, RDFProofsError> {
+    let issuer = match vc
+        .proof
+        .iter()
+        .find(|t| t.predicate == VERIFICATION_METHOD)
+        .map(|t| &t.object)
+    {
+        Some(Term::NamedNode(vm)) => vm.as_str(),
+        _ => return Err(RDFProofsError::InvalidVerificationMethod),
+    };
+    let mut credential_digest = Sha256::new();
+    for triple in vc.document.iter().chain(&vc.proof) {
+        credential_digest.update(format!("{} .\n", triple));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(CREDENTIAL_CONTEXT_DST);
+    hasher.update(b"\n");
+    hasher.update(format!("{}\n{}\n", index, issuer));
+    hasher.update(multibase::encode(
+        Base::Base64Url,
+        credential_digest.finalize(),
+    ));
+    hasher.update(b"\n");
+    Ok(multibase::encode(Base::Base64Url, hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_credential_context, get_credential_contexts};
+    use crate::{
+        common::get_graph_from_ntriples, error::RDFProofsError,
+        proof_layout::PROOF_CONTEXT_VERSION, vc::VerifiableCredentialTriples,
+    };
+
+    const DOCUMENT: &str = r#"
+<did:example:john> <http://schema.org/name> "John Smith" .
+"#;
+    const PROOF: &str = r#"
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+_:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+"#;
+
+    fn get_vc(document: &str, proof: &str) -> VerifiableCredentialTriples {
+        VerifiableCredentialTriples {
+            document: get_graph_from_ntriples(document)
+                .unwrap()
+                .iter()
+                .map(|t| t.into_owned())
+                .collect(),
+            proof: get_graph_from_ntriples(proof)
+                .unwrap()
+                .iter()
+                .map(|t| t.into_owned())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn credential_contexts_bind_issuer_credential_and_position() {
+        let vc = get_vc(DOCUMENT, PROOF);
+        let context = generate_credential_context(0, &vc).unwrap();
+        assert_eq!(generate_credential_context(0, &vc).unwrap(), context);
+
+        // another position in VP layout
+        assert_ne!(generate_credential_context(1, &vc).unwrap(), context);
+        // another issuer key
+        let other_issuer = get_vc(DOCUMENT, &PROOF.replace("pub001", "pub002"));
+        assert_ne!(
+            generate_credential_context(0, &other_issuer).unwrap(),
+            context
+        );
+        // another credential
+        let other_credential = get_vc(&DOCUMENT.replace("John", "Jane"), PROOF);
+        assert_ne!(
+            generate_credential_context(0, &other_credential).unwrap(),
+            context
+        );
+
+        // legacy context without sub-contexts
+        assert_eq!(get_credential_contexts(0, &[vc.clone()]).unwrap(), None);
+        assert_eq!(
+            get_credential_contexts(PROOF_CONTEXT_VERSION, &[vc.clone()]).unwrap(),
+            Some(vec![context])
+        );
+        assert!(matches!(
+            get_credential_contexts(PROOF_CONTEXT_VERSION + 1, &[vc]),
+            Err(RDFProofsError::UnsupportedProofLayoutVersion(_))
+        ));
+
+        // issuer key is required
+        assert!(matches!(
+            generate_credential_context(0, &get_vc(DOCUMENT, "")),
+            Err(RDFProofsError::InvalidVerificationMethod)
+        ));
+    }
+}
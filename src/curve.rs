@@ -0,0 +1,79 @@
+// pairing-friendly curve over which keys, signatures, and proofs are built:
+// BLS12-381 in `rdf-proofs`, or BN254 in `rdf-proofs-bn254` built from the same sources with
+// `curve_bn254` for verifiers limited to BN254 precompiles, e.g., EVM-based smart contracts;
+// the curve is not a feature so that both crates can be used side by side, and credentials and
+// proofs are not interchangeable between the two, so that they are distinguished by cryptosuite
+// identifiers
+
+#[cfg(not(curve_bn254))]
+pub use ark_bls12_381::{Bls12_381 as Curve, G1Affine, G1Projective, G2Affine};
+#[cfg(curve_bn254)]
+pub use ark_bn254::{Bn254 as Curve, G1Affine, G1Projective, G2Affine};
+
+#[cfg(not(curve_bn254))]
+mod constants {
+    pub const CRYPTOSUITE_SIGN: &str = "bbs-termwise-signature-2023";
+    pub const CRYPTOSUITE_BOUND_SIGN: &str = "bbs-termwise-bound-signature-2023";
+    pub const CRYPTOSUITE_PROOF: &str = "bbs-termwise-proof-2023";
+    // signatures with the secret slot placed last in the message vector
+    pub const CRYPTOSUITE_SIGN_2024: &str = "bbs-termwise-signature-2024";
+    pub const CRYPTOSUITE_BOUND_SIGN_2024: &str = "bbs-termwise-bound-signature-2024";
+    // signatures with each term followed by its IRI namespace, and the secret slot placed last
+    pub const CRYPTOSUITE_SPLIT_IRI_SIGN: &str = "bbs-termwise-split-iri-signature-2024";
+    pub const CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN: &str =
+        "bbs-termwise-split-iri-bound-signature-2024";
+    // signatures with `xsd:decimal` literals encoded into scaled integers, and the secret slot placed last
+    pub const CRYPTOSUITE_SCALED_DECIMAL_SIGN: &str = "bbs-termwise-scaled-decimal-signature-2024";
+    pub const CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN: &str =
+        "bbs-termwise-scaled-decimal-bound-signature-2024";
+
+    pub(crate) const CURVE_NAME: &str = "BLS12-381";
+    // curve name in snarkjs
+    pub(crate) const SNARKJS_CURVE_NAME: &str = "bls12381";
+
+    pub(crate) const KEY_TYPE_G1_PUB: &str = "bls12_381-g1-pub";
+    pub(crate) const KEY_TYPE_G2_PUB: &str = "bls12_381-g2-pub";
+    pub(crate) const KEY_TYPE_G2_PRIV: &str = "bls12_381-g2-priv";
+
+    // sizes of compressed points
+    pub(crate) const G1_COMPRESSED_SIZE: usize = 48;
+    pub(crate) const G2_COMPRESSED_SIZE: usize = 96;
+
+    // multicodec prefixes (varint-encoded) of Multikey
+    pub(crate) const MULTICODEC_G1_PUB: Option<[u8; 2]> = Some([0xea, 0x01]);
+    pub(crate) const MULTICODEC_G2_PUB: Option<[u8; 2]> = Some([0xeb, 0x01]);
+    pub(crate) const MULTICODEC_G2_PRIV: Option<[u8; 2]> = Some([0x8a, 0x26]);
+}
+
+#[cfg(curve_bn254)]
+mod constants {
+    pub const CRYPTOSUITE_SIGN: &str = "bbs-termwise-bn254-signature-2023";
+    pub const CRYPTOSUITE_BOUND_SIGN: &str = "bbs-termwise-bn254-bound-signature-2023";
+    pub const CRYPTOSUITE_PROOF: &str = "bbs-termwise-bn254-proof-2023";
+    pub const CRYPTOSUITE_SIGN_2024: &str = "bbs-termwise-bn254-signature-2024";
+    pub const CRYPTOSUITE_BOUND_SIGN_2024: &str = "bbs-termwise-bn254-bound-signature-2024";
+    pub const CRYPTOSUITE_SPLIT_IRI_SIGN: &str = "bbs-termwise-bn254-split-iri-signature-2024";
+    pub const CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN: &str =
+        "bbs-termwise-bn254-split-iri-bound-signature-2024";
+    pub const CRYPTOSUITE_SCALED_DECIMAL_SIGN: &str =
+        "bbs-termwise-bn254-scaled-decimal-signature-2024";
+    pub const CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN: &str =
+        "bbs-termwise-bn254-scaled-decimal-bound-signature-2024";
+
+    pub(crate) const CURVE_NAME: &str = "BN254";
+    pub(crate) const SNARKJS_CURVE_NAME: &str = "bn128";
+
+    pub(crate) const KEY_TYPE_G1_PUB: &str = "bn254-g1-pub";
+    pub(crate) const KEY_TYPE_G2_PUB: &str = "bn254-g2-pub";
+    pub(crate) const KEY_TYPE_G2_PRIV: &str = "bn254-g2-priv";
+
+    pub(crate) const G1_COMPRESSED_SIZE: usize = 32;
+    pub(crate) const G2_COMPRESSED_SIZE: usize = 64;
+
+    // no multicodec is registered for BN254 keys, so that Multikey encoding is unavailable
+    pub(crate) const MULTICODEC_G1_PUB: Option<[u8; 2]> = None;
+    pub(crate) const MULTICODEC_G2_PUB: Option<[u8; 2]> = None;
+    pub(crate) const MULTICODEC_G2_PRIV: Option<[u8; 2]> = None;
+}
+
+pub use constants::*;
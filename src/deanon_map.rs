@@ -0,0 +1,793 @@
+use crate::{
+    common::{get_graph_from_ntriples, get_term_from_string, get_vc_from_ntriples},
+    constants::NYM_IRI_PREFIX,
+    error::RDFProofsError,
+    message_layout::{TermPosition, POSITIONS},
+    predicate_graph::VariableRef,
+    vc::{VcPair, VcPairString, VerifiableCredential},
+};
+use oxrdf::{
+    BlankNode, BlankNodeRef, Graph, NamedNode, NamedOrBlankNode, Subject, Term, TermRef, Triple,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// template entry of deanonymization map for a single placeholder
+#[derive(Debug, Clone)]
+pub struct DeanonMapSkeletonEntry {
+    /// positions where the placeholder appears in the disclosed VCs
+    pub positions: BTreeSet<TermPosition>,
+    /// original terms consistent with every disclosed triple containing the placeholder
+    pub candidates: Vec<Term>,
+}
+
+impl DeanonMapSkeletonEntry {
+    /// the original term if it is uniquely determined by the disclosed VCs
+    pub fn resolved(&self) -> Option<&Term> {
+        match self.candidates.as_slice() {
+            [term] => Some(term),
+            _ => None,
+        }
+    }
+
+    /// placeholders that only appear as objects can hide literals
+    pub fn may_be_literal(&self) -> bool {
+        self.positions.iter().all(|p| *p == TermPosition::Object)
+    }
+}
+
+/// generate a deanonymization map template from the placeholders in disclosed VCs,
+/// i.e., blank nodes not present in the original VCs and nym IRIs
+pub fn generate_deanon_map_skeleton(
+    vc_pairs: &Vec<VcPair>,
+) -> Result<HashMap<NamedOrBlankNode, DeanonMapSkeletonEntry>, RDFProofsError> {
+    let mut partial_skeleton = HashMap::new();
+
+    for VcPair {
+        original,
+        disclosed,
+    } in vc_pairs
+    {
+        collect_candidates(
+            &original.document,
+            &disclosed.document,
+            &mut partial_skeleton,
+        );
+        collect_candidates(&original.proof, &disclosed.proof, &mut partial_skeleton);
+    }
+
+    Ok(partial_skeleton
+        .into_iter()
+        .map(|(placeholder, (positions, candidates))| {
+            let mut candidates = candidates
+                .unwrap_or_default()
+                .into_iter()
+                .collect::<Vec<_>>();
+            candidates.sort_by_cached_key(|t| t.to_string());
+            (
+                placeholder,
+                DeanonMapSkeletonEntry {
+                    positions,
+                    candidates,
+                },
+            )
+        })
+        .collect())
+}
+
+pub fn generate_deanon_map_skeleton_string(
+    vc_pairs: &Vec<VcPairString>,
+) -> Result<HashMap<String, Vec<String>>, RDFProofsError> {
+    let vc_pairs = vc_pairs
+        .iter()
+        .map(|pair| {
+            Ok(VcPair::new(
+                get_vc_from_ntriples(&pair.original_document, &pair.original_proof)?,
+                get_vc_from_ntriples(&pair.disclosed_document, &pair.disclosed_proof)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+
+    Ok(generate_deanon_map_skeleton(&vc_pairs)?
+        .into_iter()
+        .map(|(placeholder, entry)| {
+            (
+                placeholder.to_string(),
+                entry.candidates.iter().map(|t| t.to_string()).collect(),
+            )
+        })
+        .collect())
+}
+
+/// builder of disclosed VCs hiding predicate IRIs, e.g., sensitive relationship types
+/// like diagnosis codes used as predicates.
+///
+/// each hidden predicate is replaced with a nym IRI `urn:nym:<label>`, which is treated
+/// as an undisclosed term in `derive_proof` and `verify_proof` like blank nodes.
+/// the same nym IRI is used for the same predicate across disclosed VCs,
+/// so that the verifier learns only that the hidden predicates are equal
+/// without learning what they are; labels must not reveal the predicates
+/// and are randomly generated unless given
+#[derive(Debug, Default, Clone)]
+pub struct HiddenPredicates {
+    nyms: HashMap<NamedNode, NamedNode>,
+}
+
+impl HiddenPredicates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// hide `predicate` with a random nym IRI, returning the nym IRI
+    pub fn hide(&mut self, predicate: NamedNode) -> NamedNode {
+        self.nyms
+            .entry(predicate)
+            .or_insert_with(|| {
+                NamedNode::new_unchecked(format!(
+                    "{}{}",
+                    NYM_IRI_PREFIX,
+                    BlankNode::default().as_str()
+                ))
+            })
+            .clone()
+    }
+
+    /// hide `predicate` with the nym IRI `urn:nym:<label>`
+    pub fn hide_with_label(
+        &mut self,
+        predicate: NamedNode,
+        label: &str,
+    ) -> Result<NamedNode, RDFProofsError> {
+        let nym = NamedNode::new(format!("{}{}", NYM_IRI_PREFIX, label))?;
+        match self.nyms.iter().find(|(_, n)| **n == nym) {
+            Some((p, _)) if *p != predicate => Err(RDFProofsError::InvalidDeanonMapFormat(
+                format!("{} is already used for {}", nym, p),
+            )),
+            _ => {
+                self.nyms.insert(predicate, nym.clone());
+                Ok(nym)
+            }
+        }
+    }
+
+    /// replace the hidden predicates in disclosed VC document or proof with their nym IRIs
+    pub fn apply(&self, disclosed: &Graph) -> Graph {
+        disclosed
+            .iter()
+            .map(
+                |triple| match self.nyms.get(&triple.predicate.into_owned()) {
+                    Some(nym) => Triple::new(
+                        triple.subject.into_owned(),
+                        nym.clone(),
+                        triple.object.into_owned(),
+                    ),
+                    None => triple.into_owned(),
+                },
+            )
+            .collect()
+    }
+
+    pub fn apply_string(&self, disclosed: &str) -> Result<String, RDFProofsError> {
+        let disclosed = get_graph_from_ntriples(disclosed)?;
+        Ok(self
+            .apply(&disclosed)
+            .iter()
+            .map(|t| format!("{} .\n", t.to_string()))
+            .collect())
+    }
+
+    /// entries of deanonymization map from nym IRIs to the hidden predicates,
+    /// to be merged into the one given to `derive_proof`
+    pub fn deanon_map(&self) -> HashMap<NamedOrBlankNode, Term> {
+        self.nyms
+            .iter()
+            .map(|(predicate, nym)| (nym.clone().into(), predicate.clone().into()))
+            .collect()
+    }
+
+    pub fn deanon_map_string(&self) -> HashMap<String, String> {
+        self.nyms
+            .iter()
+            .map(|(predicate, nym)| (nym.to_string(), predicate.to_string()))
+            .collect()
+    }
+}
+
+/// deanonymization map scoped per credential, where entries are keyed by the index of
+/// the VC pair and the blank node label in its disclosed VC, e.g., `(0, "e0")`;
+/// unlike the global deanonymization map forcing `_:e0` in unrelated VCs to be equal,
+/// the same label in different VCs stands for different hidden terms unless explicitly linked
+#[derive(Debug, Default, Clone)]
+pub struct ScopedDeanonMap {
+    entries: BTreeMap<(usize, String), Term>,
+    links: Vec<((usize, String), (usize, String))>,
+}
+
+impl ScopedDeanonMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// map the blank node `label` in the `vc_index`-th disclosed VC to the hidden term
+    pub fn insert(&mut self, vc_index: usize, label: &BlankNode, term: Term) {
+        self.entries
+            .insert((vc_index, label.as_str().to_string()), term);
+    }
+
+    /// `insert` with the blank node and the term in N-Triples format, e.g., `_:e0` and `<did:example:john>`
+    pub fn insert_string(
+        &mut self,
+        vc_index: usize,
+        label: &str,
+        term: &str,
+    ) -> Result<(), RDFProofsError> {
+        let label = get_scoped_label(label)?;
+        self.insert(vc_index, &label, get_term_from_string(term)?);
+        Ok(())
+    }
+
+    /// declare that the blank nodes in two disclosed VCs stand for the same hidden term,
+    /// which the VP proves to be equal
+    pub fn link(
+        &mut self,
+        vc_index_1: usize,
+        label_1: &BlankNode,
+        vc_index_2: usize,
+        label_2: &BlankNode,
+    ) {
+        self.links.push((
+            (vc_index_1, label_1.as_str().to_string()),
+            (vc_index_2, label_2.as_str().to_string()),
+        ));
+    }
+
+    pub fn link_string(
+        &mut self,
+        vc_index_1: usize,
+        label_1: &str,
+        vc_index_2: usize,
+        label_2: &str,
+    ) -> Result<(), RDFProofsError> {
+        let (label_1, label_2) = (get_scoped_label(label_1)?, get_scoped_label(label_2)?);
+        self.link(vc_index_1, &label_1, vc_index_2, &label_2);
+        Ok(())
+    }
+
+    /// relabel the scoped blank nodes in the disclosed VCs into `_:s<vc_index>-<label>`,
+    /// where linked ones share the label of the first one, returning the relabeled VC pairs
+    /// and the global deanonymization map to be given to `derive_proof`
+    pub fn apply(
+        &self,
+        vc_pairs: &[VcPair],
+    ) -> Result<(Vec<VcPair>, HashMap<NamedOrBlankNode, Term>), RDFProofsError> {
+        let representatives = self.get_representatives();
+        if let Some((vc_index, label)) = representatives.keys().find(|(i, _)| *i >= vc_pairs.len())
+        {
+            return Err(RDFProofsError::InvalidDeanonMapFormat(format!(
+                "_:{} is scoped to VC {} out of {} VCs",
+                label,
+                vc_index,
+                vc_pairs.len()
+            )));
+        }
+
+        let mut deanon_map: HashMap<NamedOrBlankNode, Term> = HashMap::new();
+        for (key, representative) in &representatives {
+            let bnode: NamedOrBlankNode = get_scoped_bnode(representative).into();
+            let Some(term) = self.entries.get(key) else {
+                continue;
+            };
+            match deanon_map.get(&bnode) {
+                Some(existing) if existing != term => {
+                    return Err(RDFProofsError::InvalidDeanonMapFormat(format!(
+                        "_:{} in VC {} is linked to different terms {} and {}",
+                        key.1, key.0, existing, term
+                    )))
+                }
+                _ => {
+                    deanon_map.insert(bnode, term.clone());
+                }
+            }
+        }
+        if let Some(((vc_index, label), _)) = representatives
+            .iter()
+            .find(|(_, r)| !deanon_map.contains_key(&NamedOrBlankNode::from(get_scoped_bnode(r))))
+        {
+            return Err(RDFProofsError::InvalidDeanonMapFormat(format!(
+                "_:{} in VC {} is not mapped to any term",
+                label, vc_index
+            )));
+        }
+
+        // blank nodes neither scoped nor in the original VC would fall into the global scope
+        for (vc_index, pair) in vc_pairs.iter().enumerate() {
+            let original_bnodes = get_bnode_labels(&pair.original.document);
+            if let Some(label) = get_bnode_labels(&pair.disclosed.document)
+                .into_iter()
+                .find(|l| {
+                    !original_bnodes.contains(l)
+                        && !representatives.contains_key(&(vc_index, l.clone()))
+                })
+            {
+                return Err(RDFProofsError::InvalidDeanonMapFormat(format!(
+                    "_:{} in VC {} is not mapped to any term",
+                    label, vc_index
+                )));
+            }
+        }
+
+        let relabeled_vc_pairs = vc_pairs
+            .iter()
+            .enumerate()
+            .map(|(vc_index, pair)| {
+                let relabel = |graph: &Graph| -> Graph {
+                    graph
+                        .iter()
+                        .map(|t| {
+                            let mut triple = t.into_owned();
+                            if let Subject::BlankNode(b) = &triple.subject {
+                                if let Some(r) =
+                                    representatives.get(&(vc_index, b.as_str().to_string()))
+                                {
+                                    triple.subject = get_scoped_bnode(r).into();
+                                }
+                            }
+                            if let Term::BlankNode(b) = &triple.object {
+                                if let Some(r) =
+                                    representatives.get(&(vc_index, b.as_str().to_string()))
+                                {
+                                    triple.object = get_scoped_bnode(r).into();
+                                }
+                            }
+                            triple
+                        })
+                        .collect()
+                };
+                VcPair::new(
+                    pair.original.clone(),
+                    VerifiableCredential::new(
+                        relabel(&pair.disclosed.document),
+                        relabel(&pair.disclosed.proof),
+                    ),
+                )
+            })
+            .collect();
+
+        Ok((relabeled_vc_pairs, deanon_map))
+    }
+
+    /// reference to the blank node `label` in the `vc_index`-th disclosed VC as relabeled by
+    /// `apply`, to be bound to private variables of predicates instead of the scoped label
+    pub fn variable_ref(
+        &self,
+        vc_index: usize,
+        label: &BlankNode,
+    ) -> Result<VariableRef, RDFProofsError> {
+        self.get_representatives()
+            .get(&(vc_index, label.as_str().to_string()))
+            .map(|r| get_scoped_bnode(r).into())
+            .ok_or_else(|| {
+                RDFProofsError::InvalidDeanonMapFormat(format!(
+                    "_:{} in VC {} is not mapped to any term",
+                    label.as_str(),
+                    vc_index
+                ))
+            })
+    }
+
+    // representatives of linked blank nodes, i.e., the smallest keys
+    fn get_representatives(&self) -> BTreeMap<(usize, String), (usize, String)> {
+        let mut representatives: BTreeMap<(usize, String), (usize, String)> = self
+            .entries
+            .keys()
+            .chain(self.links.iter().flat_map(|(k1, k2)| [k1, k2]))
+            .map(|k| (k.clone(), k.clone()))
+            .collect();
+        for (k1, k2) in &self.links {
+            let (r1, r2) = (representatives[k1].clone(), representatives[k2].clone());
+            let (min, max) = if r1 <= r2 { (r1, r2) } else { (r2, r1) };
+            for r in representatives.values_mut() {
+                if *r == max {
+                    *r = min.clone();
+                }
+            }
+        }
+        representatives
+    }
+
+    pub fn apply_string(
+        &self,
+        vc_pairs: &[VcPairString],
+    ) -> Result<(Vec<VcPairString>, HashMap<String, String>), RDFProofsError> {
+        let parsed_vc_pairs = vc_pairs
+            .iter()
+            .map(|pair| {
+                Ok(VcPair::new(
+                    get_vc_from_ntriples(&pair.original_document, &pair.original_proof)?,
+                    get_vc_from_ntriples(&pair.disclosed_document, &pair.disclosed_proof)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, RDFProofsError>>()?;
+        let (relabeled_vc_pairs, deanon_map) = self.apply(&parsed_vc_pairs)?;
+        let to_ntriples =
+            |graph: &Graph| -> String { graph.iter().map(|t| format!("{} .\n", t)).collect() };
+        Ok((
+            vc_pairs
+                .iter()
+                .zip(relabeled_vc_pairs)
+                .map(|(pair, relabeled)| {
+                    VcPairString::new(
+                        &pair.original_document,
+                        &pair.original_proof,
+                        &to_ntriples(&relabeled.disclosed.document),
+                        &to_ntriples(&relabeled.disclosed.proof),
+                    )
+                })
+                .collect(),
+            deanon_map
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ))
+    }
+}
+
+fn get_scoped_label(label: &str) -> Result<BlankNode, RDFProofsError> {
+    match get_term_from_string(label)? {
+        Term::BlankNode(b) => Ok(b),
+        _ => Err(RDFProofsError::InvalidDeanonMapFormat(label.to_string())),
+    }
+}
+
+fn get_bnode_labels(graph: &Graph) -> HashSet<String> {
+    graph
+        .iter()
+        .flat_map(|t| [t.subject.into(), t.object])
+        .filter_map(|term| match term {
+            TermRef::BlankNode(b) => Some(b.as_str().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn get_scoped_bnode((vc_index, label): &(usize, String)) -> BlankNode {
+    BlankNode::new_unchecked(format!("s{}-{}", vc_index, label))
+}
+
+type PartialSkeleton = HashMap<NamedOrBlankNode, (BTreeSet<TermPosition>, Option<HashSet<Term>>)>;
+
+fn collect_candidates(original: &Graph, disclosed: &Graph, partial_skeleton: &mut PartialSkeleton) {
+    let original_bnodes = original
+        .iter()
+        .flat_map(|t| [t.subject.into(), t.object])
+        .filter_map(|term| match term {
+            TermRef::BlankNode(b) => Some(b),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    for disclosed_triple in disclosed.iter() {
+        let disclosed_terms: [TermRef; 3] = [
+            disclosed_triple.subject.into(),
+            disclosed_triple.predicate.into(),
+            disclosed_triple.object,
+        ];
+        let placeholders = disclosed_terms
+            .iter()
+            .map(|term| get_placeholder(*term, &original_bnodes))
+            .collect::<Vec<_>>();
+        if placeholders.iter().all(Option::is_none) {
+            continue;
+        }
+
+        // find the original triples that match the disclosed triple
+        // with its placeholders treated as variables
+        let mut candidates: HashMap<&NamedOrBlankNode, HashSet<Term>> = placeholders
+            .iter()
+            .flatten()
+            .map(|p| (p, HashSet::new()))
+            .collect();
+        for original_triple in original.iter() {
+            let original_terms: [TermRef; 3] = [
+                original_triple.subject.into(),
+                original_triple.predicate.into(),
+                original_triple.object,
+            ];
+            let mut bindings: HashMap<&NamedOrBlankNode, TermRef> = HashMap::new();
+            let matched = placeholders
+                .iter()
+                .zip(disclosed_terms.iter().zip(original_terms.iter()))
+                .all(|(placeholder, (d, o))| match placeholder {
+                    Some(p) => *bindings.entry(p).or_insert(*o) == *o,
+                    None => d == o,
+                });
+            if matched {
+                for (p, o) in bindings {
+                    candidates.entry(p).or_default().insert(o.into_owned());
+                }
+            }
+        }
+
+        for (position, placeholder) in POSITIONS.iter().zip(&placeholders) {
+            if let Some(p) = placeholder {
+                partial_skeleton
+                    .entry(p.clone())
+                    .or_default()
+                    .0
+                    .insert(*position);
+            }
+        }
+
+        // narrow down the candidates by intersecting them with the ones from other triples
+        for (p, c) in candidates {
+            let (_, current) = partial_skeleton.entry(p.clone()).or_default();
+            *current = match current.take() {
+                Some(current) => Some(current.intersection(&c).cloned().collect()),
+                None => Some(c),
+            };
+        }
+    }
+}
+
+fn get_placeholder(
+    term: TermRef,
+    original_bnodes: &HashSet<BlankNodeRef>,
+) -> Option<NamedOrBlankNode> {
+    match term {
+        TermRef::BlankNode(b) if !original_bnodes.contains(&b) => Some(b.into_owned().into()),
+        TermRef::NamedNode(n) if n.as_str().starts_with(NYM_IRI_PREFIX) => {
+            Some(n.into_owned().into())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        generate_deanon_map_skeleton, generate_deanon_map_skeleton_string, HiddenPredicates,
+        ScopedDeanonMap, TermPosition,
+    };
+    use crate::{common::get_vc_from_ntriples, error::RDFProofsError, VcPair, VcPairString};
+    use oxrdf::{BlankNode, Literal, NamedNode, NamedOrBlankNode, Term};
+
+    const VC_1: &str = r#"
+    <did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://example.org/vocab/isPatientOf> _:b0 .
+    <did:example:john> <http://schema.org/worksFor> _:b1 .
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+    _:b0 <http://example.org/vocab/lotNumber> "0000001" .
+    _:b0 <http://example.org/vocab/vaccinationDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
+    _:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/b> .
+    _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Organization> .
+    _:b1 <http://schema.org/name> "ABC inc." .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_1: &str = r#"
+    _:b0 <https://w3id.org/security#proofValue> "ui_TYLyZXnF1LRhdzEDrKiAWA0Tbrm1GmCHXBVnX39BTBnIbdFLc9p2jRAw0H4jzznHL4DdyqBDvkUBbr0eTTUk3vNVI1LRxSfXRqqLng4Qx6SX7tptjtHzjJMkQnolGpiiFfE9k8OhOKcntcJwGSaQ"^^<https://w3id.org/security#multibase> .
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const VC_2: &str = r#"
+    <http://example.org/vaccine/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccine> .
+    <http://example.org/vaccine/a> <http://schema.org/name> "AwesomeVaccine" .
+    <http://example.org/vaccine/a> <http://schema.org/manufacturer> <http://example.org/awesomeCompany> .
+    <http://example.org/vaccine/a> <http://schema.org/status> "active" .
+    <http://example.org/vicred/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vicred/a> <https://www.w3.org/2018/credentials#credentialSubject> <http://example.org/vaccine/a> .
+    <http://example.org/vicred/a> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer3> .
+    <http://example.org/vicred/a> <https://www.w3.org/2018/credentials#issuanceDate> "2020-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vicred/a> <https://www.w3.org/2018/credentials#expirationDate> "2023-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_2: &str = r#"
+    _:b0 <https://w3id.org/security#proofValue> "uoB9zdaILAqel15HTh6MtIkDZjoeQn2g-fqACEgZvKNMRbgGqTOmNDclM2Pv-WF7BnHL4DdyqBDvkUBbr0eTTUk3vNVI1LRxSfXRqqLng4Qx6SX7tptjtHzjJMkQnolGpiiFfE9k8OhOKcntcJwGSaQ"^^<https://w3id.org/security#multibase> .
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-03T09:49:25Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer3#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC_1: &str = r#"
+    _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+    _:e0 <http://schema.org/name> _:e4 .
+    _:e0 <http://example.org/vocab/isPatientOf> _:b0 .
+    _:e0 <http://schema.org/worksFor> _:b1 .
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+    _:b0 <http://example.org/vocab/vaccine> _:e1 .
+    _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Organization> .
+    _:e2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e2 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+    _:e2 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    _:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e2 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const DISCLOSED_VC_PROOF_1: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC_2: &str = r#"
+    _:e1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccine> .
+    _:e1 <http://schema.org/status> "active" .
+    _:e3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e3 <https://www.w3.org/2018/credentials#credentialSubject> _:e1 .
+    _:e3 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer3> .
+    _:e3 <https://www.w3.org/2018/credentials#issuanceDate> "2020-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e3 <https://www.w3.org/2018/credentials#expirationDate> "2023-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const DISCLOSED_VC_PROOF_2: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-03T09:49:25Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer3#bls12_381-g2-pub001> .
+    "#;
+
+    fn placeholder(id: &str) -> NamedOrBlankNode {
+        BlankNode::new_unchecked(id).into()
+    }
+
+    #[test]
+    fn generate_deanon_map_skeleton_success() {
+        let vc_pairs = vec![
+            VcPair::new(
+                get_vc_from_ntriples(VC_1, VC_PROOF_1).unwrap(),
+                get_vc_from_ntriples(DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1).unwrap(),
+            ),
+            VcPair::new(
+                get_vc_from_ntriples(VC_2, VC_PROOF_2).unwrap(),
+                get_vc_from_ntriples(DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2).unwrap(),
+            ),
+        ];
+
+        let skeleton = generate_deanon_map_skeleton(&vc_pairs).unwrap();
+        println!("skeleton:\n{:#?}", skeleton);
+
+        assert_eq!(skeleton.len(), 5);
+        assert_eq!(
+            skeleton[&placeholder("e0")].resolved(),
+            Some(&Term::NamedNode(NamedNode::new_unchecked(
+                "did:example:john"
+            )))
+        );
+        // `_:e1` is ambiguous in VC_1 but determined by VC_2
+        assert_eq!(
+            skeleton[&placeholder("e1")].resolved(),
+            Some(&Term::NamedNode(NamedNode::new_unchecked(
+                "http://example.org/vaccine/a"
+            )))
+        );
+        assert_eq!(
+            skeleton[&placeholder("e2")].resolved(),
+            Some(&Term::NamedNode(NamedNode::new_unchecked(
+                "http://example.org/vcred/00"
+            )))
+        );
+        assert_eq!(
+            skeleton[&placeholder("e3")].resolved(),
+            Some(&Term::NamedNode(NamedNode::new_unchecked(
+                "http://example.org/vicred/a"
+            )))
+        );
+        let e4 = &skeleton[&placeholder("e4")];
+        assert_eq!(
+            e4.resolved(),
+            Some(&Term::Literal(Literal::new_simple_literal("John Smith")))
+        );
+        assert!(e4.may_be_literal());
+        assert!(e4.positions.contains(&TermPosition::Object));
+        assert!(!skeleton[&placeholder("e0")].may_be_literal());
+    }
+
+    #[test]
+    fn generate_deanon_map_skeleton_with_ambiguous_placeholder() {
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            VC_PROOF_1,
+            r#"
+            _:b0 <http://example.org/vocab/vaccine> _:e1 .
+            "#,
+            DISCLOSED_VC_PROOF_1,
+        )];
+
+        let skeleton = generate_deanon_map_skeleton_string(&vc_pairs).unwrap();
+        assert_eq!(
+            skeleton["_:e1"],
+            vec![
+                "<http://example.org/vaccine/a>".to_string(),
+                "<http://example.org/vaccine/b>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn hide_predicates() {
+        let mut hidden_predicates = HiddenPredicates::new();
+        let name = NamedNode::new_unchecked("http://schema.org/name");
+        let nym = hidden_predicates.hide(name.clone());
+        assert!(nym.as_str().starts_with("urn:nym:"));
+        // the same predicate is always hidden with the same nym
+        assert_eq!(hidden_predicates.hide(name.clone()), nym);
+        // nym must not be shared by different predicates
+        assert!(hidden_predicates
+            .hide_with_label(
+                NamedNode::new_unchecked("http://schema.org/worksFor"),
+                nym.as_str().trim_start_matches("urn:nym:"),
+            )
+            .is_err());
+
+        let disclosed = hidden_predicates.apply_string(DISCLOSED_VC_1).unwrap();
+        println!("disclosed:\n{}", disclosed);
+        assert!(!disclosed.contains("<http://schema.org/name>"));
+        assert!(disclosed.contains(&nym.to_string()));
+        assert_eq!(
+            hidden_predicates.deanon_map_string()[&nym.to_string()],
+            "<http://schema.org/name>"
+        );
+    }
+
+    #[test]
+    fn scoped_deanon_map() {
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let mut scoped = ScopedDeanonMap::new();
+        for (label, term) in [
+            ("_:e0", "<did:example:john>"),
+            ("_:e1", "<http://example.org/vaccine/a>"),
+            ("_:e2", "<http://example.org/vcred/00>"),
+            ("_:e4", "\"John Smith\""),
+        ] {
+            scoped.insert_string(0, label, term).unwrap();
+        }
+        scoped
+            .insert_string(1, "_:e3", "<http://example.org/vicred/a>")
+            .unwrap();
+
+        // `_:e1` in VC 2 is not mapped unless linked
+        assert!(matches!(
+            scoped.apply_string(&vc_pairs),
+            Err(RDFProofsError::InvalidDeanonMapFormat(_))
+        ));
+
+        scoped.link_string(0, "_:e1", 1, "_:e1").unwrap();
+        let (relabeled, deanon_map) = scoped.apply_string(&vc_pairs).unwrap();
+        assert_eq!(deanon_map.len(), 5);
+        assert_eq!(deanon_map["_:s0-e1"], "<http://example.org/vaccine/a>");
+        assert_eq!(deanon_map["_:s1-e3"], "<http://example.org/vicred/a>");
+        assert!(relabeled[0].disclosed_document.contains("_:s0-e1"));
+        assert!(relabeled[1].disclosed_document.contains("_:s0-e1"));
+        // blank nodes not hidden are left as they are
+        assert!(relabeled[0].disclosed_document.contains("_:b0"));
+        // predicates refer to the relabeled blank nodes
+        assert_eq!(
+            scoped
+                .variable_ref(1, &BlankNode::new_unchecked("e1"))
+                .unwrap()
+                .label(),
+            "s0-e1"
+        );
+        assert!(scoped
+            .variable_ref(1, &BlankNode::new_unchecked("e0"))
+            .is_err());
+
+        // linked blank nodes must not be mapped to different terms
+        let mut conflicting = scoped.clone();
+        conflicting.link_string(0, "_:e0", 1, "_:e3").unwrap();
+        assert!(matches!(
+            conflicting.apply_string(&vc_pairs),
+            Err(RDFProofsError::InvalidDeanonMapFormat(_))
+        ));
+    }
+}
@@ -1,45 +1,104 @@
 use super::constants::CRYPTOSUITE_PROOF;
 use crate::{
     ark_to_base64url,
+    attribute_encryption::{
+        encrypt_attributes, insert_attribute_encryptions, match_attribute_encryptions,
+        AttributeEncryption, AttributeEncryptionRequest, AttributeEncryptionRequestString,
+    },
     blind_signature::{blind_verify, BlindSignRequest, BlindSignRequestString},
+    clock::{clock_or_system, Clock, SystemClock},
+    commitment::{
+        get_commitment_bases, get_commitment_equalities, CommitmentEqualityRequest,
+        CommitmentEqualityRequestString,
+    },
     common::{
         canonicalize_graph, generate_proof_spec_context, get_delimiter, get_graph_from_ntriples,
-        get_hasher, get_term_from_string, get_vc_from_ntriples, hash_byte_to_field,
-        hash_term_to_field, is_nym, multibase_to_ark, randomize_bnodes,
-        randomize_bnodes_in_vc_pairs, read_private_var_list, read_public_var_list,
-        reorder_vc_triples, BBSPlusDefaultFieldHasher, BBSPlusHash, BBSPlusPublicKey,
-        BBSPlusSignature, Fr, PedersenCommitmentStmt, PoKBBSPlusStmt, PoKBBSPlusWit, Proof,
-        ProofWithIndexMap, R1CSCircomWitness, StatementIndexMap, Statements,
+        get_hasher, get_term_from_string, get_vc_from_ntriples, get_verification_method_identifier,
+        hash_byte_to_field, hash_term_to_field, hash_term_to_field_with_encoding, is_nym,
+        multibase_to_ark, randomize_bnodes, randomize_bnodes_in_vc_pairs, reorder_vc_triples,
+        validate_index_maps, BBSPlusDefaultFieldHasher, BBSPlusHash, BBSPlusPublicKey,
+        BBSPlusSignature, BlankNodeLabeler, Fr, PedersenCommitmentStmt, PoKBBSPlusStmt,
+        PoKBBSPlusWit, Proof, ProofWithIndexMap, R1CSCircomWitness, SetupParams, StatementIndexMap,
+        Statements,
     },
     constants::PPID_PREFIX,
     context::{
-        AUTHENTICATION, CHALLENGE, CIRCUIT, CREATED, CRYPTOSUITE, DATA_INTEGRITY_PROOF, DOMAIN,
-        ENCRYPTED_UID, HOLDER, MULTIBASE, PREDICATE, PREDICATE_TYPE, PRIVATE, PROOF, PROOF_PURPOSE,
-        PROOF_VALUE, PUBLIC, SECRET_COMMITMENT, VERIFIABLE_CREDENTIAL, VERIFIABLE_CREDENTIAL_TYPE,
-        VERIFIABLE_PRESENTATION_TYPE, VERIFICATION_METHOD,
+        AUTHENTICATION, CHALLENGE, COMMITMENT, COMMITTED_VALUE, CREATED, CRYPTOSUITE,
+        DATA_INTEGRITY_PROOF, DISTINCT_CREDENTIALS, DOMAIN, ENCRYPTED_UID, EQUAL_TO_COMMITMENT,
+        HOLDER, MULTIBASE, NOT_EQUAL, PARAMS_PROFILE, POLICY_DIGEST, PREDICATE, PREVIOUS_PROOF,
+        PROOF, PROOF_PURPOSE, PROOF_VALUE, ROTATED_SECRET_COMMITMENT, SECRET_COMMITMENT,
+        VERIFIABLE_CREDENTIAL, VERIFIABLE_CREDENTIAL_TYPE, VERIFIABLE_PRESENTATION_TYPE,
+        VERIFICATION_METHOD,
+    },
+    credential_context::get_credential_contexts,
+    curve::G1Affine,
+    device_binding::{
+        insert_device_binding, present_device_binding, DeviceBinding, DeviceBindingString,
+        PresentedDeviceBinding,
+    },
+    elliptic_elgamal::{
+        elliptic_elgamal_escrowed_encryption_with_bbs_plus, get_escrowed_encryption_equalities,
+        ElGamalEscrowedEncryption,
     },
-    elliptic_elgamal_verifiable_encryption_with_bbs_plus,
+    equivalence::{get_equivalences, insert_equivalence_graph},
     error::RDFProofsError,
-    key_gen::{generate_params, generate_ppid, PPID},
+    holder_binding::HolderBindingPolicy,
+    inequality::{
+        commit_to_distinct_terms, commit_to_inequality, get_hidden_credential_ids,
+        get_inequalities, get_inequality_openings, get_inequality_statements,
+        get_inequality_witnesses, get_unequal_values, insert_inequalities, parse_not_equal_request,
+        plan_distinct_terms, plan_inequality, Inequality, InequalityOpening,
+    },
+    key_gen::{
+        generate_params, generate_params_with_profile, generate_ppid, get_params_profile, PPID,
+    },
     key_graph::KeyGraph,
+    limits::{check_canonicalization_limits, Limits},
+    linkability::StrictDisclosure,
+    message_layout::{
+        get_hidden_term_encoding, get_predicate_term_encoding, SecretSlot, TermEncoding,
+    },
+    metadata::{get_credential_summary, insert_credential_summary},
+    metrics::{elapsed_ms, start_timer, Metrics},
+    migrate::encode_proof_value,
+    namespace::{
+        get_namespace_equalities, get_same_namespaces, hash_namespace_to_field,
+        insert_same_namespaces, validate_same_namespaces,
+    },
     ordered_triple::{
         OrderedGraphViews, OrderedNamedOrBlankNode, OrderedVerifiableCredentialGraphViews,
     },
-    predicate::{Circuit, CircuitString},
-    signature::verify,
+    policy::{requires_unique_credentials, PolicyDigest},
+    precomputed_canonicalization::{
+        canonicalize_graph_with_precomputed, PrecomputedCanonicalization,
+    },
+    predicate::{validate_input_bit_length, Circuit, CircuitString},
+    predicate_graph::{
+        group_array_inputs, split_array_input, validate_private_variables, PredicateGraph,
+    },
+    progress::{enter_phase, CancellationToken, Progress, ProofPhase},
+    proof_layout::{
+        get_required_features, StatementKind, StatementLayout, GENERATOR, PROOF_CONTEXT_VERSION,
+        PROOF_LAYOUT_VERSION,
+    },
+    secret_group::{
+        get_secret_group_indexes, get_secret_groups, insert_secret_groups, split_embedded_secrets,
+        CredentialSecrets,
+    },
+    signature::{verify, verify_with_precomputed_canonicalization},
     vc::{
         DisclosedVerifiableCredential, VcPair, VcPairString, VerifiableCredential,
         VerifiableCredentialTriples, VerifiablePresentation,
     },
-    ElGamalCiphertext, ElGamalPublicKey, ElGamalVerifiableEncryption,
+    ElGamalCiphertext, ElGamalPublicKey,
 };
 use ark_std::rand::RngCore;
-use chrono::offset::Utc;
-use multibase::Base;
+#[cfg(feature = "async")]
+use futures::io::{AsyncWrite, AsyncWriteExt};
 use oxrdf::{
     vocab::{rdf::TYPE, xsd},
-    BlankNode, Dataset, Graph, GraphNameRef, LiteralRef, NamedNode, NamedOrBlankNode, Quad,
-    QuadRef, Subject, Term, TermRef, Triple,
+    BlankNode, Dataset, Graph, GraphNameRef, Literal, LiteralRef, NamedNode, NamedOrBlankNode,
+    Quad, QuadRef, Subject, SubjectRef, Term, TermRef, Triple,
 };
 use proof_system::{
     prelude::{EqualWitnesses, MetaStatements},
@@ -47,7 +106,132 @@ use proof_system::{
     statement::r1cs_legogroth16::R1CSCircomProver,
     witness::{Witness, Witnesses},
 };
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    io::Write,
+};
+
+/// labeling of blank nodes newly introduced in VP, e.g., graph names of disclosed VCs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlankNodeLabeling {
+    /// fresh random labels for each derivation
+    #[default]
+    Random,
+    /// labels derived from the digests of the credentials and the disclosures,
+    /// so that the same VP layout is obtained for the same inputs and can be cached by holders
+    Deterministic,
+}
+
+/// precision of `created` in VP proof, which can be truncated to reduce
+/// correlation risk from high-precision timestamps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CreatedPrecision {
+    /// current datetime as `xsd:dateTime`
+    #[default]
+    Exact,
+    /// current date in UTC as `xsd:date`
+    Day,
+    /// current year and month in UTC as `xsd:gYearMonth`
+    Month,
+}
+
+impl CreatedPrecision {
+    fn now(&self, clock: &dyn Clock) -> Literal {
+        let now = clock.now();
+        match self {
+            CreatedPrecision::Exact => {
+                Literal::new_typed_literal(format!("{:?}", now), xsd::DATE_TIME)
+            }
+            CreatedPrecision::Day => {
+                Literal::new_typed_literal(now.format("%Y-%m-%d").to_string(), xsd::DATE)
+            }
+            CreatedPrecision::Month => {
+                Literal::new_typed_literal(now.format("%Y-%m").to_string(), xsd::G_YEAR_MONTH)
+            }
+        }
+    }
+}
+
+impl TryFrom<&str> for CreatedPrecision {
+    type Error = RDFProofsError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "exact" => Ok(CreatedPrecision::Exact),
+            "day" => Ok(CreatedPrecision::Day),
+            "month" => Ok(CreatedPrecision::Month),
+            _ => Err(RDFProofsError::InvalidCreatedPrecision(value.to_string())),
+        }
+    }
+}
+
+/// optional inputs to `derive_proof`, which default to none, i.e., no holder binding,
+/// no predicates, and so on; collections are owned and default to empty,
+/// whereas single values and services given by the caller are borrowed as `Option<&'a _>`
+#[derive(Default)]
+pub struct DeriveOptions<'a> {
+    pub holder_binding: HolderBindingPolicy<'a>,
+    pub blind_sign_request: Option<BlindSignRequest>,
+    pub predicates: Vec<Graph>,
+    pub circuits: HashMap<NamedNode, Circuit>,
+    pub previous_proofs: Vec<Graph>,
+    pub new_secret: Option<&'a [u8]>,
+    pub commitment_equalities: Vec<CommitmentEqualityRequest>,
+    pub policy: Option<&'a Graph>,
+    /// proof config terms of disclosed VCs to be redacted, which defaults to `created`
+    pub redacted_proof_configs: Option<Vec<NamedNode>>,
+    pub bnode_labeling: BlankNodeLabeling,
+    pub distinct_credentials: Vec<BlankNode>,
+    pub not_equals: Vec<Graph>,
+    pub protocol: Option<&'a str>,
+    pub created_precision: CreatedPrecision,
+    /// indexed as `vc_pairs`
+    pub precomputed_canonicalizations: Vec<Option<PrecomputedCanonicalization>>,
+    pub device_binding: Option<DeviceBinding<'a>>,
+    pub strict_disclosure: Option<&'a StrictDisclosure>,
+    pub credential_secrets: Option<&'a CredentialSecrets<'a>>,
+    pub clock: Option<&'a dyn Clock>,
+    pub metrics: Option<&'a dyn Metrics>,
+    pub limits: Option<&'a Limits>,
+    pub attribute_encryptions: Vec<AttributeEncryptionRequest>,
+    pub cancellation: Option<&'a CancellationToken>,
+    pub progress: Option<&'a dyn Progress>,
+    pub same_namespaces: Vec<(BlankNode, BlankNode)>,
+}
+
+/// string-based version of `DeriveOptions` given to `derive_proof_string`
+#[derive(Default)]
+pub struct DeriveOptionsString<'a> {
+    pub secret: Option<&'a [u8]>,
+    pub blind_sign_request: Option<&'a BlindSignRequestString>,
+    pub with_ppid: Option<bool>,
+    pub predicates: Option<&'a Vec<String>>,
+    pub circuits: Option<&'a HashMap<String, CircuitString>>,
+    pub opener_pub_keys: Option<&'a Vec<ElGamalPublicKey>>,
+    pub previous_proofs: Option<&'a Vec<String>>,
+    pub new_secret: Option<&'a [u8]>,
+    pub commitment_equalities: Option<&'a Vec<CommitmentEqualityRequestString>>,
+    pub policy: Option<&'a str>,
+    pub redacted_proof_configs: Option<&'a Vec<String>>,
+    pub deterministic_bnode_labels: Option<bool>,
+    pub distinct_credentials: Option<&'a Vec<String>>,
+    pub not_equals: Option<&'a Vec<String>>,
+    pub protocol: Option<&'a str>,
+    /// `"exact"`, `"day"`, or `"month"`
+    pub created_precision: Option<&'a str>,
+    pub precomputed_canonicalizations: Option<&'a Vec<Option<PrecomputedCanonicalization>>>,
+    pub device_binding: Option<&'a DeviceBindingString<'a>>,
+    pub strict_disclosure: Option<&'a StrictDisclosure>,
+    pub credential_secrets: Option<&'a CredentialSecrets<'a>>,
+    pub clock: Option<&'a dyn Clock>,
+    pub metrics: Option<&'a dyn Metrics>,
+    pub limits: Option<&'a Limits>,
+    pub attribute_encryptions: Option<&'a Vec<AttributeEncryptionRequestString>>,
+    pub cancellation: Option<&'a CancellationToken>,
+    pub progress: Option<&'a dyn Progress>,
+    pub same_namespaces: Option<&'a Vec<(String, String)>>,
+}
 
 /// derive VP from VCs, disclosed VCs, and deanonymization map
 pub fn derive_proof<R: RngCore>(
@@ -57,23 +241,93 @@ pub fn derive_proof<R: RngCore>(
     key_graph: &KeyGraph,
     challenge: Option<&str>,
     domain: Option<&str>,
-    secret: Option<&[u8]>,
-    blind_sign_request: Option<BlindSignRequest>,
-    with_ppid: Option<bool>,
-    predicates: Vec<Graph>,
-    circuits: HashMap<NamedNode, Circuit>,
-    opener_pub_key: Option<ElGamalPublicKey>,
+    options: DeriveOptions,
 ) -> Result<Dataset, RDFProofsError> {
+    let DeriveOptions {
+        holder_binding,
+        blind_sign_request,
+        predicates,
+        circuits,
+        previous_proofs,
+        new_secret,
+        commitment_equalities,
+        policy,
+        redacted_proof_configs,
+        bnode_labeling,
+        distinct_credentials,
+        not_equals,
+        protocol,
+        created_precision,
+        precomputed_canonicalizations,
+        device_binding,
+        strict_disclosure,
+        credential_secrets,
+        clock,
+        metrics,
+        limits,
+        attribute_encryptions,
+        cancellation,
+        progress,
+        same_namespaces,
+    } = options;
+
     for vc in vc_pairs {
-        println!("{}", vc.to_string());
+        debug_println!("{}", vc.to_string());
     }
-    println!("deanon map:\n{:#?}\n", deanon_map);
+    debug_println!("deanon map:\n{:#?}\n", deanon_map);
 
     // either VCs or a blind sign request must be provided as input
     if vc_pairs.is_empty() && blind_sign_request.is_none() {
         return Err(RDFProofsError::MissingInputToDeriveProof);
     }
 
+    // reject correlatable literals to be disclosed unless whitelisted
+    if let Some(strict_disclosure) = strict_disclosure {
+        let redacted_proof_configs = redacted_proof_configs
+            .clone()
+            .unwrap_or_else(|| vec![CREATED.into_owned()]);
+        strict_disclosure.check(vc_pairs, &redacted_proof_configs, created_precision)?;
+    }
+
+    // holder's secret, which is bound to VCs and used for PPID and encrypted uid if required
+    let secret = holder_binding.secret();
+    let domain = holder_binding.resolve_domain(domain)?;
+
+    // secret bound to each VC, which may differ from the holder's secret if given per credential
+    let vc_secrets = match credential_secrets {
+        Some(credential_secrets) => credential_secrets.get_vc_secrets(vc_pairs, secret)?,
+        None => vec![secret; vc_pairs.len()],
+    };
+
+    // secret rotation requires both the old secret bound in VCs
+    // and the blind sign request committing to the new secret
+    if new_secret.is_some() {
+        if secret.is_none() {
+            return Err(RDFProofsError::MissingSecret);
+        }
+        if blind_sign_request.is_none() {
+            return Err(RDFProofsError::MissingSecretRotationRequest);
+        }
+    }
+
+    // device binding requires the challenge to be signed by the device key
+    // over the key handle re-randomized for this VP,
+    // and the registered key handle must commit to the holder's secret
+    let device_binding = match device_binding {
+        None => None,
+        Some(binding) => {
+            let Some(secret) = secret else {
+                return Err(RDFProofsError::MissingSecret);
+            };
+            let Some(challenge) = challenge else {
+                return Err(RDFProofsError::InvalidDeviceBinding(
+                    "challenge is required".to_string(),
+                ));
+            };
+            Some(present_device_binding(rng, &binding, secret, challenge)?)
+        }
+    };
+
     // TODO:
     // check: each disclosed VCs must be the derived subset of corresponding VCs via deanon map
 
@@ -82,21 +336,52 @@ pub fn derive_proof<R: RngCore>(
         .iter()
         .map(|VcPair { original: vc, .. }| get_public_keys(&vc.proof, key_graph))
         .collect::<Result<Vec<_>, _>>()?;
-    println!("public keys:\n{:#?}\n", public_keys);
+    debug_println!("public keys:\n{:#?}\n", public_keys);
 
-    // verify VCs
+    // verify VCs, using the precomputed canonicalizations of documents
+    // indexed as `vc_pairs` if given
     vc_pairs
         .iter()
-        .map(
-            |VcPair { original: vc, .. }| match (vc.is_bound(), secret) {
-                (Ok(false), _) => verify(vc, key_graph),
-                (Ok(true), Some(s)) => blind_verify(s, vc, key_graph),
-                (Ok(true), None) => Err(RDFProofsError::MissingSecret),
-                _ => Err(RDFProofsError::VCWithUnsupportedCryptosuite),
-            },
-        )
+        .zip(&vc_secrets)
+        .enumerate()
+        .map(|(i, (VcPair { original: vc, .. }, secret))| {
+            let precomputed = precomputed_canonicalizations
+                .get(i)
+                .and_then(Option::as_ref);
+            match (vc.is_bound(), *secret, precomputed) {
+                (Ok(false), _, None) => verify(vc, key_graph),
+                (Ok(true), Some(s), None) => blind_verify(s, vc, key_graph),
+                (Ok(false), _, Some(p)) => {
+                    verify_with_precomputed_canonicalization(None, vc, p, key_graph)
+                }
+                (Ok(true), Some(s), Some(p)) => {
+                    verify_with_precomputed_canonicalization(Some(s), vc, p, key_graph)
+                }
+                (Ok(true), None, _) => Err(RDFProofsError::MissingSecret),
+                (Err(e), _, _) => Err(e),
+            }
+        })
         .collect::<Result<(), _>>()?;
 
+    // group bound VCs by their secrets other than the holder's secret,
+    // whose embedded secrets are proven equal within each group
+    let secret_groups = get_secret_group_indexes(
+        &vc_secrets,
+        &vc_pairs
+            .iter()
+            .map(|VcPair { original, .. }| original.is_bound())
+            .collect::<Result<Vec<_>, _>>()?,
+        secret,
+    );
+
+    // labeler for blank nodes in VP, seeded before blank nodes in VCs are randomized
+    let mut labeler = match bnode_labeling {
+        BlankNodeLabeling::Random => BlankNodeLabeler::random(),
+        BlankNodeLabeling::Deterministic => {
+            BlankNodeLabeler::deterministic(&get_bnode_label_seed(vc_pairs)?)
+        }
+    };
+
     // randomize blank node identifiers in VC documents and VC proofs
     // for avoiding identifier collisions among multiple VCs
     let randomized_vc_pairs = vc_pairs
@@ -118,11 +403,19 @@ pub fn derive_proof<R: RngCore>(
         )
         .collect::<Vec<_>>();
     for vc in &randomized_vc_pairs {
-        println!("randomized vc: {}", vc.to_string());
+        debug_println!("randomized vc: {}", vc.to_string());
     }
 
     // randomize blank node identifiers in predicate graphs
-    // except for user-defined blank node identifiers in `deanon_map`
+    // except for user-defined blank node identifiers in `deanon_map`,
+    // to which every private variable must be bound
+    validate_private_variables(
+        &predicates,
+        deanon_map,
+        vc_pairs
+            .iter()
+            .map(|VcPair { disclosed, .. }| &disclosed.document),
+    )?;
     let anon_bnodes: HashSet<_> = deanon_map.keys().cloned().collect();
     let randomized_predicates = predicates
         .iter()
@@ -140,32 +433,84 @@ pub fn derive_proof<R: RngCore>(
         )
         .unzip();
 
-    // get PPID
-    let ppid = get_ppid(&domain, &secret, with_ppid)?;
+    // hide selected proof config terms of disclosed VCs, e.g., `created`,
+    // which otherwise could be used for correlation
+    let mut deanon_map = deanon_map.clone();
+    let disclosed_vcs = redact_proof_configs(
+        disclosed_vcs,
+        &redacted_proof_configs.unwrap_or_else(|| vec![CREATED.into_owned()]),
+        &mut deanon_map,
+    )?;
 
-    // encrypt secret as usk
-    let verifiable_encryption_for_uid = match (secret, opener_pub_key) {
-        (Some(secret), Some(opener_pub_key)) => {
-            get_encrypted_secret_and_pok(&opener_pub_key, secret, rng).map(Some)
-        }
-        (Some(_), None) | (None, None) => Ok(None),
-        _ => Err(RDFProofsError::MissingSecretOrOpenerPubKey), // This already returns Err
-    }
-    .unwrap();
-    let cipher_text = verifiable_encryption_for_uid
+    // get PPID
+    let ppid = holder_binding
+        .ppid()
+        .map(|(secret, domain)| generate_ppid(domain, secret))
+        .transpose()?;
+
+    // encrypt secret as usk, escrowed among the openers if multiple keys are given
+    let verifiable_encryption_for_uid = holder_binding
+        .audit()
+        .map(|(secret, opener_pub_keys)| get_encrypted_secret_and_pok(opener_pub_keys, secret, rng))
+        .transpose()?;
+    let cipher_texts = verifiable_encryption_for_uid
         .as_ref()
-        .map(|e| e.cipher_text)
-        .or(None);
+        .map(|e| e.cipher_texts.clone());
+
+    // bind verifier's policy to VP
+    let policy_digest = policy.map(PolicyDigest::new).transpose()?;
+
+    // commit to pairwise differences of hidden credential IDs to prove that they are distinct,
+    // which defaults to all the hidden credential IDs if verifier's policy requires unique credentials
+    let distinct_credentials = get_distinct_credentials(distinct_credentials, vc_pairs, policy);
+    let distinct_credential_openings =
+        commit_to_distinct_terms(rng, &distinct_credentials, &deanon_map)?;
+
+    // commit to differences of hidden terms requested to be proven unequal
+    let not_equal_openings = not_equals
+        .iter()
+        .map(|request| {
+            let (lhs, rhs) = parse_not_equal_request(request)?;
+            commit_to_inequality(rng, &lhs, &rhs, &deanon_map)
+        })
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+
+    // hidden IRIs requested to be proven to share a namespace
+    validate_same_namespaces(&same_namespaces, &deanon_map)?;
+
+    // encrypt hidden terms to the keys requested by verifier
+    let attribute_encryptions = encrypt_attributes(rng, &attribute_encryptions, &deanon_map)?;
 
     // build VP draft (= canonicalized VP without proofValue) based on disclosed VCs
+    enter_phase(ProofPhase::Canonicalization, cancellation, progress)?;
     let (vp_draft, vp_draft_bnode_map, vc_document_graph_names) = build_vp(
         disclosed_vcs,
         &challenge,
         &domain,
+        &policy_digest,
         &blind_sign_request,
         &ppid,
-        &cipher_text,
+        &cipher_texts,
         randomized_predicates,
+        previous_proofs,
+        new_secret.is_some(),
+        &commitment_equalities,
+        &attribute_encryptions,
+        &distinct_credential_openings
+            .iter()
+            .map(|opening| opening.inequality.clone())
+            .collect(),
+        &not_equal_openings
+            .iter()
+            .map(|opening| opening.inequality.clone())
+            .collect(),
+        same_namespaces,
+        &device_binding,
+        created_precision,
+        clock_or_system(clock),
+        &secret_groups,
+        &mut labeler,
+        limits,
     )?;
 
     // decompose VP draft into graphs
@@ -173,8 +518,10 @@ pub fn derive_proof<R: RngCore>(
         metadata: _vp_metadata_graph,
         proof: vp_proof_graph,
         proof_graph_name: vp_proof_graph_name,
+        previous_proofs: _previous_proof_graphs,
         disclosed_vcs: canonicalized_disclosed_vc_graphs,
         predicates: predicate_graphs,
+        equivalences: _equivalence_graph,
     } = (&vp_draft).try_into()?;
 
     // extract `proofValue`s from original VCs
@@ -193,26 +540,39 @@ pub fn derive_proof<R: RngCore>(
         .unzip();
 
     // canonicalize original VCs
-    let (canonicalized_original_vcs, original_vcs_bnode_map) =
-        canonicalize_vcs(&original_vcs_without_proof_value)?;
+    let canonicalization_started = start_timer(metrics);
+    let (canonicalized_original_vcs, original_vcs_bnode_map) = canonicalize_vcs(
+        &original_vcs_without_proof_value,
+        &precomputed_canonicalizations,
+    )?;
+    if let (Some(metrics), Some(started)) = (metrics, canonicalization_started) {
+        metrics.canonicalization_ms(elapsed_ms(started));
+    }
 
     for v in &canonicalized_original_vcs {
-        println!("canonicalized_original_vcs: {}", v);
+        debug_println!("canonicalized_original_vcs: {}", v);
     }
-    println!("original vcs bnode map: {:#?}", original_vcs_bnode_map);
+    debug_println!("original vcs bnode map: {:#?}", original_vcs_bnode_map);
 
     // construct extended deanonymization map
     let extended_deanon_map =
-        extend_deanon_map(deanon_map, &vp_draft_bnode_map, &original_vcs_bnode_map)?;
-    println!("extended deanon map:");
+        extend_deanon_map(&deanon_map, &vp_draft_bnode_map, &original_vcs_bnode_map)?;
+    debug_println!("extended deanon map:");
     for (f, t) in &extended_deanon_map {
-        println!("{}: {}", f.to_string(), t.to_string());
+        debug_println!("{}: {}", f.to_string(), t.to_string());
     }
-    println!("");
+    debug_println!("");
 
     // reorder the original VC graphs and proof values
     // according to the order of canonicalized graph names of disclosed VCs
-    let (original_vc_vec, disclosed_vc_vec, vc_proof_values_vec, is_bound_vec) = reorder_vc_graphs(
+    let (
+        original_vc_vec,
+        disclosed_vc_vec,
+        vc_proof_values_vec,
+        is_bound_vec,
+        secret_slot_vec,
+        term_encoding_vec,
+    ) = reorder_vc_graphs(
         &canonicalized_original_vcs,
         &vc_proof_values.iter().map(|s| s.as_str()).collect(),
         &canonicalized_disclosed_vc_graphs,
@@ -220,55 +580,74 @@ pub fn derive_proof<R: RngCore>(
         &vc_document_graph_names,
     )?;
 
-    println!("canonicalized original VC (sorted):");
+    debug_println!("canonicalized original VC (sorted):");
     for VerifiableCredentialTriples { document, proof } in &original_vc_vec {
-        println!(
-            "document:\n{}",
-            document
-                .iter()
-                .map(|t| format!("{} .\n", t.to_string()))
-                .reduce(|l, r| format!("{}{}", l, r))
-                .unwrap()
-        );
-        println!(
-            "proof:\n{}",
-            proof
-                .iter()
-                .map(|t| format!("{} .\n", t.to_string()))
-                .reduce(|l, r| format!("{}{}", l, r))
-                .unwrap()
-        );
+        debug_println!("document:\n{}", triples_to_string(document));
+        debug_println!("proof:\n{}", triples_to_string(proof));
     }
-    println!("canonicalized disclosed VC (sorted):");
+    debug_println!("canonicalized disclosed VC (sorted):");
     for VerifiableCredentialTriples { document, proof } in &disclosed_vc_vec {
-        println!(
-            "document:\n{}",
-            document
-                .iter()
-                .map(|t| format!("{} .\n", t.to_string()))
-                .reduce(|l, r| format!("{}{}", l, r))
-                .unwrap()
-        );
-        println!(
-            "proof:\n{}",
-            proof
-                .iter()
-                .map(|t| format!("{} .\n", t.to_string()))
-                .reduce(|l, r| format!("{}{}", l, r))
-                .unwrap()
-        );
+        debug_println!("document:\n{}", triples_to_string(document));
+        debug_println!("proof:\n{}", triples_to_string(proof));
     }
 
+    // reorder the secrets bound to VCs in the same way, and get their groups in that order
+    let vc_secrets_vec = get_original_vc_indexes(
+        &canonicalized_disclosed_vc_graphs,
+        &extended_deanon_map,
+        &vc_document_graph_names,
+    )?
+    .into_iter()
+    .map(|i| vc_secrets.get(i).copied().flatten())
+    .collect::<Vec<_>>();
+    let secret_groups = get_secret_groups(&vp_proof_graph, &canonicalized_disclosed_vc_graphs)?;
+
+    // get commitments to be proven equal to hidden terms with their blindings
+    let commitment_equalities = get_commitment_equalities(&vp_proof_graph)?
+        .into_iter()
+        .map(|(commitment, value)| {
+            let req = commitment_equalities
+                .iter()
+                .find(|req| req.commitment == commitment)
+                .ok_or(RDFProofsError::InvalidCommitmentEquality)?;
+            Ok((commitment, req.blinding, NamedOrBlankNode::from(value)))
+        })
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+
+    // get encrypted hidden terms with their encryptions
+    let attribute_encryptions =
+        match_attribute_encryptions(&vp_proof_graph, attribute_encryptions)?;
+
+    // get inequalities of hidden credential IDs and those of requested terms with their blindings
+    let inequalities = [
+        get_inequality_openings(
+            &vp_proof_graph,
+            DISTINCT_CREDENTIALS,
+            &distinct_credential_openings,
+        )?,
+        get_inequality_openings(&vp_proof_graph, NOT_EQUAL, &not_equal_openings)?,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    // get hidden IRIs proven to share a namespace
+    let same_namespaces = get_same_namespaces(&vp_proof_graph)?;
+
     // generate index map
     let index_map = gen_index_map(&original_vc_vec, &disclosed_vc_vec, &extended_deanon_map)?;
-    println!("index_map:\n{:#?}\n", index_map);
+    debug_println!("index_map:\n{:#?}\n", index_map);
 
     // derive proof value
     let derived_proof_value = derive_proof_value(
         rng,
         secret,
+        vc_secrets_vec,
+        secret_groups,
         original_vc_vec,
         is_bound_vec,
+        secret_slot_vec,
+        term_encoding_vec,
         disclosed_vc_vec,
         public_keys,
         vc_proof_values_vec,
@@ -281,7 +660,20 @@ pub fn derive_proof<R: RngCore>(
         circuits,
         &extended_deanon_map,
         &verifiable_encryption_for_uid,
+        new_secret,
+        commitment_equalities,
+        inequalities,
+        protocol,
+        &device_binding,
+        attribute_encryptions,
+        same_namespaces,
+        metrics,
+        cancellation,
+        progress,
     )?;
+    if let Some(metrics) = metrics {
+        metrics.proof_bytes(derived_proof_value.len());
+    }
 
     // add derived proof value to VP
     let vp_proof_subject = vp_proof_graph
@@ -306,13 +698,38 @@ pub fn derive_proof_string<R: RngCore>(
     key_graph: &str,
     challenge: Option<&str>,
     domain: Option<&str>,
-    secret: Option<&[u8]>,
-    blind_sign_request: Option<BlindSignRequestString>,
-    with_ppid: Option<bool>,
-    predicates: Option<&Vec<String>>,
-    circuits: Option<&HashMap<String, CircuitString>>,
-    opener_pub_key: Option<ElGamalPublicKey>,
+    options: DeriveOptionsString,
 ) -> Result<String, RDFProofsError> {
+    let DeriveOptionsString {
+        secret,
+        blind_sign_request,
+        with_ppid,
+        predicates,
+        circuits,
+        opener_pub_keys,
+        previous_proofs,
+        new_secret,
+        commitment_equalities,
+        policy,
+        redacted_proof_configs,
+        deterministic_bnode_labels,
+        distinct_credentials,
+        not_equals,
+        protocol,
+        created_precision,
+        precomputed_canonicalizations,
+        device_binding,
+        strict_disclosure,
+        credential_secrets,
+        clock,
+        metrics,
+        limits,
+        attribute_encryptions,
+        cancellation,
+        progress,
+        same_namespaces,
+    } = options;
+
     // construct inputs for `derive_proof` from string-based inputs
     let vc_pairs = vc_pairs
         .iter()
@@ -329,11 +746,13 @@ pub fn derive_proof_string<R: RngCore>(
         Some(BlindSignRequest {
             commitment: multibase_to_ark(&req.commitment)?,
             blinding: multibase_to_ark(&req.blinding)?,
-            pok_for_commitment: if let Some(s) = req.pok_for_commitment {
-                Some(multibase_to_ark(&s)?)
+            pok_for_commitment: if let Some(s) = &req.pok_for_commitment {
+                Some(multibase_to_ark(s)?)
             } else {
                 None
             },
+            audience: req.audience.clone(),
+            expires_at: req.expires_at.clone(),
         })
     } else {
         None
@@ -355,12 +774,100 @@ pub fn derive_proof_string<R: RngCore>(
                     &circuit_str.circuit_r1cs,
                     &circuit_str.circuit_wasm,
                     &circuit_str.snark_proving_key,
+                    circuit_str.input_bit_lengths.clone(),
                 )?;
                 Ok((NamedNode::new(circuit_id)?, circuit))
             })
             .collect::<Result<HashMap<_, _>, RDFProofsError>>()?,
     };
 
+    let previous_proofs = match previous_proofs {
+        None => vec![],
+        Some(previous_proofs) => previous_proofs
+            .iter()
+            .map(|previous_proof| Ok(get_graph_from_ntriples(previous_proof)?))
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    };
+
+    let commitment_equalities = match commitment_equalities {
+        None => vec![],
+        Some(commitment_equalities) => commitment_equalities
+            .iter()
+            .map(|req| req.try_into())
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    };
+
+    let policy = policy.map(get_graph_from_ntriples).transpose()?;
+
+    let redacted_proof_configs = match redacted_proof_configs {
+        None => None,
+        Some(redacted_proof_configs) => Some(
+            redacted_proof_configs
+                .iter()
+                .map(|iri| Ok(NamedNode::new(iri)?))
+                .collect::<Result<Vec<_>, RDFProofsError>>()?,
+        ),
+    };
+
+    let distinct_credentials = match distinct_credentials {
+        None => vec![],
+        Some(distinct_credentials) => distinct_credentials
+            .iter()
+            .map(|bnode| match get_term_from_string(bnode)? {
+                Term::BlankNode(n) => Ok(n),
+                _ => Err(RDFProofsError::InvalidInequality),
+            })
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    };
+
+    let not_equals = match not_equals {
+        None => vec![],
+        Some(not_equals) => not_equals
+            .iter()
+            .map(|request| Ok(get_graph_from_ntriples(request)?))
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    };
+
+    let created_precision = match created_precision {
+        None => CreatedPrecision::Exact,
+        Some(precision) => precision.try_into()?,
+    };
+
+    let device_binding = match device_binding {
+        None => None,
+        Some(binding) => Some(binding.try_into()?),
+    };
+
+    let attribute_encryptions = attribute_encryptions
+        .map(|reqs| {
+            reqs.iter()
+                .map(|req| req.try_into())
+                .collect::<Result<Vec<_>, RDFProofsError>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let same_namespaces = same_namespaces
+        .map(|pairs| {
+            let get_bnode = |bnode: &str| match get_term_from_string(bnode)? {
+                Term::BlankNode(n) => Ok(n),
+                _ => Err(RDFProofsError::InvalidNamespaceEquality),
+            };
+            pairs
+                .iter()
+                .map(|(lhs, rhs)| Ok((get_bnode(lhs)?, get_bnode(rhs)?)))
+                .collect::<Result<Vec<_>, RDFProofsError>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let holder_binding = HolderBindingPolicy::new(
+        secret,
+        with_ppid.unwrap_or(false),
+        domain,
+        opener_pub_keys.cloned().unwrap_or_default(),
+    )?;
+
     let derived_proof = derive_proof(
         rng,
         &vc_pairs,
@@ -368,106 +875,597 @@ pub fn derive_proof_string<R: RngCore>(
         &key_graph,
         challenge,
         domain,
-        secret,
-        blind_sign_request,
-        with_ppid,
-        predicates,
-        circuits,
-        opener_pub_key,
+        DeriveOptions {
+            holder_binding,
+            blind_sign_request,
+            predicates,
+            circuits,
+            previous_proofs,
+            new_secret,
+            commitment_equalities,
+            policy: policy.as_ref(),
+            redacted_proof_configs,
+            bnode_labeling: if deterministic_bnode_labels.unwrap_or(false) {
+                BlankNodeLabeling::Deterministic
+            } else {
+                BlankNodeLabeling::Random
+            },
+            distinct_credentials,
+            not_equals,
+            protocol,
+            created_precision,
+            precomputed_canonicalizations: precomputed_canonicalizations
+                .cloned()
+                .unwrap_or_default(),
+            device_binding,
+            strict_disclosure,
+            credential_secrets,
+            clock,
+            metrics,
+            limits,
+            attribute_encryptions,
+            cancellation,
+            progress,
+            same_namespaces,
+        },
     )?;
 
     Ok(rdf_canon::serialize(&derived_proof))
 }
 
-fn get_ppid(
-    domain: &Option<&str>,
-    secret: &Option<&[u8]>,
-    with_nym: Option<bool>,
-) -> Result<Option<PPID>, RDFProofsError> {
-    let with_nym = match with_nym {
-        Some(v) => v,
-        None => false,
-    };
-
-    if !with_nym {
-        return Ok(None);
+/// derive VP as in `derive_proof`, writing its N-Quads into `writer` quad by quad
+/// without buffering the serialized VP, where the proof value is written last
+pub fn derive_proof_to_writer<R: RngCore, W: Write>(
+    rng: &mut R,
+    vc_pairs: &Vec<VcPair>,
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+    key_graph: &KeyGraph,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    options: DeriveOptions,
+    writer: &mut W,
+) -> Result<(), RDFProofsError> {
+    let derived_proof = derive_proof(
+        rng, vc_pairs, deanon_map, key_graph, challenge, domain, options,
+    )?;
+    for quad in quads_with_proof_value_last(&derived_proof) {
+        writeln!(writer, "{} .", quad)?;
     }
+    Ok(writer.flush()?)
+}
 
-    if let (Some(domain), Some(secret)) = (domain, secret) {
-        Ok(Some(generate_ppid(domain, secret)?))
-    } else {
-        Err(RDFProofsError::MissingSecretOrDomain)
+/// async version of `derive_proof_to_writer`, where the proof is derived synchronously
+/// before writing the VP
+#[cfg(feature = "async")]
+pub async fn derive_proof_to_async_writer<R: RngCore, W: AsyncWrite + Unpin>(
+    rng: &mut R,
+    vc_pairs: &Vec<VcPair>,
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+    key_graph: &KeyGraph,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    options: DeriveOptions,
+    writer: &mut W,
+) -> Result<(), RDFProofsError> {
+    let derived_proof = derive_proof(
+        rng, vc_pairs, deanon_map, key_graph, challenge, domain, options,
+    )?;
+    for quad in quads_with_proof_value_last(&derived_proof) {
+        writer.write_all(format!("{} .\n", quad).as_bytes()).await?;
     }
+    Ok(writer.flush().await?)
 }
 
-fn get_encrypted_secret_and_pok<R: RngCore>(
-    opener_pub_key: &ElGamalPublicKey,
-    secret: &[u8],
-    rng: &mut R,
-) -> Result<ElGamalVerifiableEncryption, RDFProofsError> {
-    let params = generate_params(1);
-    let hasher = get_hasher();
-    let secret = hash_byte_to_field(secret, &hasher).unwrap();
-    Ok(elliptic_elgamal_verifiable_encryption_with_bbs_plus(
-        &opener_pub_key,
-        &params.h[0],
-        &secret,
-        rng,
-    )?)
+// quads of VP followed by the proof value, so that readers can process the VP
+// until the proof value arrives
+fn quads_with_proof_value_last(vp: &Dataset) -> impl Iterator<Item = QuadRef<'_>> + '_ {
+    vp.iter()
+        .filter(|quad| quad.predicate != PROOF_VALUE)
+        .chain(vp.iter().filter(|quad| quad.predicate == PROOF_VALUE))
 }
 
-fn get_deanon_map_from_string(
-    deanon_map_string: &HashMap<String, String>,
-) -> Result<HashMap<NamedOrBlankNode, Term>, RDFProofsError> {
-    deanon_map_string
-        .iter()
-        .map(|(k, v)| {
-            let key: NamedOrBlankNode = match get_term_from_string(k)? {
-                Term::NamedNode(n) => Ok(n.into()),
-                Term::BlankNode(n) => Ok(n.into()),
-                Term::Literal(_) => Err(RDFProofsError::InvalidDeanonMapFormat(k.to_string())),
-            }?;
-            let value = get_term_from_string(v)?;
-            Ok((key, value))
-        })
-        .collect()
+/// statement planned to be proven in VP, in the order of statements in the proof
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlannedStatement {
+    /// proof of knowledge of BBS+ signature on the `credential`-th VC in VP
+    BBSPlusSignature {
+        credential: usize,
+        disclosed: usize,
+        undisclosed: usize,
+    },
+    PPID,
+    /// one of the statements for verifiable encryption of uid
+    VerifiableEncryption,
+    SecretCommitment,
+    Predicate {
+        circuit: NamedNode,
+        private: Vec<String>,
+        public: Vec<String>,
+    },
+    CommitmentEquality {
+        value: NamedOrBlankNode,
+    },
+    /// one of the two statements for inequality of hidden terms
+    Inequality {
+        lhs: NamedOrBlankNode,
+        rhs: NamedOrBlankNode,
+    },
 }
 
-fn get_public_keys(
-    proof_graph: &Graph,
-    key_graph: &KeyGraph,
-) -> Result<BBSPlusPublicKey, RDFProofsError> {
-    let vm_triple = proof_graph
-        .triples_for_predicate(VERIFICATION_METHOD)
-        .next()
-        .ok_or(RDFProofsError::InvalidVerificationMethod)?;
-    let vm = match vm_triple.object {
-        TermRef::NamedNode(v) => Ok(v),
-        _ => Err(RDFProofsError::InvalidVerificationMethodURL),
-    }?;
-    key_graph.get_public_key(vm)
+/// structure of the proof planned by `derive_proof_dry_run`,
+/// where witness equalities are given as sets of (statement index, witness index)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofPlan {
+    pub statements: Vec<PlannedStatement>,
+    pub witness_equalities: Vec<BTreeSet<(usize, usize)>>,
 }
 
-fn deanonymize_subject(
+/// validate the inputs to `derive_proof` and return the planned statement structure
+/// without cryptographic operations, e.g., for wallets to check the user's selection
+/// before generating proof; note that VC signatures are not verified here
+pub fn derive_proof_dry_run(
+    vc_pairs: &Vec<VcPair>,
     deanon_map: &HashMap<NamedOrBlankNode, Term>,
-    subject: &mut Subject,
-) -> Result<(), RDFProofsError> {
-    match subject {
-        Subject::BlankNode(bnode) => {
-            if let Some(v) = deanon_map.get(&NamedOrBlankNode::BlankNode(bnode.clone())) {
-                match v {
-                    Term::NamedNode(n) => *subject = Subject::NamedNode(n.clone()),
-                    Term::BlankNode(n) => *subject = Subject::BlankNode(n.clone()),
-                    _ => return Err(RDFProofsError::DeAnonymization),
-                }
-            }
-        }
-        Subject::NamedNode(node) => {
-            if let Some(v) = deanon_map.get(&NamedOrBlankNode::NamedNode(node.clone())) {
-                match v {
-                    Term::NamedNode(n) => *subject = Subject::NamedNode(n.clone()),
-                    Term::BlankNode(n) => *subject = Subject::BlankNode(n.clone()),
-                    _ => return Err(RDFProofsError::DeAnonymization),
+    key_graph: &KeyGraph,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    options: &DeriveOptions,
+) -> Result<ProofPlan, RDFProofsError> {
+    let hasher = get_hasher();
+    let holder_binding = &options.holder_binding;
+    let blind_sign_request = &options.blind_sign_request;
+    let predicates = &options.predicates;
+    let circuits = &options.circuits;
+    let new_secret = options.new_secret;
+    let commitment_equalities = &options.commitment_equalities;
+    let policy = options.policy;
+    let distinct_credentials = &options.distinct_credentials;
+
+    // check the inputs in the same way as `derive_proof`
+    if vc_pairs.is_empty() && blind_sign_request.is_none() {
+        return Err(RDFProofsError::MissingInputToDeriveProof);
+    }
+    let secret = holder_binding.secret();
+    let domain = holder_binding.resolve_domain(domain)?;
+    if new_secret.is_some() {
+        if secret.is_none() {
+            return Err(RDFProofsError::MissingSecret);
+        }
+        if blind_sign_request.is_none() {
+            return Err(RDFProofsError::MissingSecretRotationRequest);
+        }
+    }
+
+    // check that issuer public keys are available and bound VCs come with secret
+    for VcPair { original: vc, .. } in vc_pairs {
+        get_public_keys(&vc.proof, key_graph)?;
+        match (vc.is_bound(), secret) {
+            (Ok(false), _) | (Ok(true), Some(_)) => Ok(()),
+            (Ok(true), None) => Err(RDFProofsError::MissingSecret),
+            (Err(e), _) => Err(e),
+        }?;
+    }
+
+    // randomize blank node identifiers as in `derive_proof`
+    let (original_vcs, disclosed_vcs): (Vec<_>, Vec<_>) = vc_pairs
+        .iter()
+        .map(
+            |VcPair {
+                 original,
+                 disclosed,
+             }| {
+                let (r_original_document, r_disclosed_document) =
+                    randomize_bnodes_in_vc_pairs(&original.document, &disclosed.document);
+                let (r_original_proof, r_disclosed_proof) =
+                    randomize_bnodes_in_vc_pairs(&original.proof, &disclosed.proof);
+                (
+                    VerifiableCredential::new(r_original_document, r_original_proof),
+                    VerifiableCredential::new(r_disclosed_document, r_disclosed_proof),
+                )
+            },
+        )
+        .unzip();
+    let mut deanon_map = deanon_map.clone();
+    let disclosed_vcs = redact_proof_configs(
+        disclosed_vcs,
+        options
+            .redacted_proof_configs
+            .as_ref()
+            .unwrap_or(&vec![CREATED.into_owned()]),
+        &mut deanon_map,
+    )?;
+    validate_private_variables(
+        predicates,
+        &deanon_map,
+        vc_pairs
+            .iter()
+            .map(|VcPair { disclosed, .. }| &disclosed.document),
+    )?;
+    let anon_bnodes: HashSet<_> = deanon_map.keys().cloned().collect();
+    let randomized_predicates = predicates
+        .iter()
+        .map(|predicate| randomize_bnodes(predicate, &anon_bnodes))
+        .collect::<Vec<_>>();
+    let policy_digest = policy.map(PolicyDigest::new).transpose()?;
+    let distinct_credentials = plan_distinct_terms(
+        &get_distinct_credentials(distinct_credentials.clone(), vc_pairs, policy),
+        &deanon_map,
+    )?;
+    let not_equals = options
+        .not_equals
+        .iter()
+        .map(|request| {
+            let (lhs, rhs) = parse_not_equal_request(request)?;
+            plan_inequality(&lhs, &rhs, &deanon_map)
+        })
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+
+    // build VP draft without PPID and encrypted uid, which do not affect the structure
+    let (vp_draft, vp_draft_bnode_map, vc_document_graph_names) = build_vp(
+        disclosed_vcs,
+        &challenge,
+        &domain,
+        &policy_digest,
+        blind_sign_request,
+        &None,
+        &None,
+        randomized_predicates,
+        options.previous_proofs.clone(),
+        new_secret.is_some(),
+        commitment_equalities,
+        &[],
+        &distinct_credentials,
+        &not_equals,
+        &[],
+        &None,
+        CreatedPrecision::Exact,
+        &SystemClock,
+        &[],
+        &mut BlankNodeLabeler::random(),
+        None,
+    )?;
+    let VerifiablePresentation {
+        proof: vp_proof_graph,
+        disclosed_vcs: canonicalized_disclosed_vc_graphs,
+        predicates: predicate_graphs,
+        ..
+    } = (&vp_draft).try_into()?;
+
+    // canonicalize original VCs and map them to disclosed VCs
+    let (original_vcs_without_proof_value, vc_proof_values): (Vec<_>, Vec<_>) = original_vcs
+        .iter()
+        .map(|original_vc| {
+            let proof_value = original_vc.get_proof_value()?;
+            Ok((
+                VerifiableCredential::new(
+                    original_vc.document.clone(),
+                    original_vc.get_proof_config(),
+                ),
+                proof_value,
+            ))
+        })
+        .collect::<Result<Vec<_>, RDFProofsError>>()?
+        .into_iter()
+        .unzip();
+    let (canonicalized_original_vcs, original_vcs_bnode_map) =
+        canonicalize_vcs(&original_vcs_without_proof_value, &vec![])?;
+    let extended_deanon_map =
+        extend_deanon_map(&deanon_map, &vp_draft_bnode_map, &original_vcs_bnode_map)?;
+    let (original_vc_vec, disclosed_vc_vec, _, is_bound_vec, secret_slot_vec, term_encoding_vec) =
+        reorder_vc_graphs(
+            &canonicalized_original_vcs,
+            &vc_proof_values.iter().map(|s| s.as_str()).collect(),
+            &canonicalized_disclosed_vc_graphs,
+            &extended_deanon_map,
+            &vc_document_graph_names,
+        )?;
+    let index_map = gen_index_map(&original_vc_vec, &disclosed_vc_vec, &extended_deanon_map)?;
+
+    // plan statements in the same order as `derive_proof_value`
+    let reordered_disclosed_vc_triples = reorder_vc_triples(&disclosed_vc_vec, &index_map)?;
+    let disclosed_and_undisclosed_terms = reordered_disclosed_vc_triples
+        .iter()
+        .zip(original_vc_vec)
+        .zip(
+            is_bound_vec
+                .iter()
+                .zip(secret_slot_vec.into_iter().zip(term_encoding_vec)),
+        )
+        .enumerate()
+        .map(
+            |(
+                i,
+                (
+                    (disclosed_vc_triples, original_vc_triples),
+                    (is_bound, (secret_slot, term_encoding)),
+                ),
+            )| {
+                get_disclosed_and_undisclosed_terms(
+                    disclosed_vc_triples,
+                    &original_vc_triples,
+                    i,
+                    if *is_bound { secret } else { None },
+                    secret_slot,
+                    term_encoding,
+                    &hasher,
+                )
+            },
+        )
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+    let mut equivs: BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>> = BTreeMap::new();
+    let mut statements: Vec<PlannedStatement> = disclosed_and_undisclosed_terms
+        .iter()
+        .enumerate()
+        .map(|(credential, terms)| {
+            for (k, v) in &terms.equivs {
+                equivs
+                    .entry(k.clone().into())
+                    .or_default()
+                    .extend(v.clone());
+            }
+            PlannedStatement::BBSPlusSignature {
+                credential,
+                disclosed: terms.disclosed.len(),
+                undisclosed: terms.undisclosed.len(),
+            }
+        })
+        .collect();
+    let mut witness_equalities = vec![];
+    let mut secret_equiv_set: BTreeSet<(usize, usize)> = is_bound_vec
+        .iter()
+        .zip(&disclosed_and_undisclosed_terms)
+        .enumerate()
+        .filter(|(_, (&is_bound, _))| is_bound)
+        .map(|(i, (_, terms))| (i, terms.secret_index))
+        .collect();
+    if holder_binding.ppid().is_some() {
+        statements.push(PlannedStatement::PPID);
+        secret_equiv_set.insert((statements.len() - 1, 0));
+    }
+    if let Some((_, opener_pub_keys)) = holder_binding.audit() {
+        let (randomness_equalities, uid_position) =
+            get_escrowed_encryption_equalities(statements.len(), opener_pub_keys.len());
+        let encryption_statement_count = if opener_pub_keys.len() > 1 {
+            2 * opener_pub_keys.len() + 1
+        } else {
+            2
+        };
+        statements.extend(
+            (0..encryption_statement_count).map(|_| PlannedStatement::VerifiableEncryption),
+        );
+        witness_equalities.extend(randomness_equalities);
+        secret_equiv_set.insert(uid_position);
+    }
+    if blind_sign_request.is_some() {
+        statements.push(PlannedStatement::SecretCommitment);
+        if new_secret.is_none() {
+            secret_equiv_set.insert((statements.len() - 1, 1));
+        }
+    }
+    if secret_equiv_set.len() > 1 {
+        witness_equalities.push(secret_equiv_set);
+    }
+
+    // predicates must refer to the given circuits
+    let mut predicate_privates = vec![];
+    for (_, predicate_graph) in predicate_graphs {
+        let PredicateGraph {
+            circuit,
+            private,
+            public,
+            ..
+        } = PredicateGraph::try_from(&predicate_graph)?;
+        if !circuits.contains_key(&circuit) {
+            return Err(RDFProofsError::MissingPredicateCircuit);
+        }
+        statements.push(PlannedStatement::Predicate {
+            circuit,
+            private: private.iter().map(|(var, _)| var.clone()).collect(),
+            public: public.iter().map(|(var, _)| var.clone()).collect(),
+        });
+        predicate_privates.push((statements.len() - 1, private));
+    }
+
+    // committed values must be hidden terms in VCs
+    let mut commitment_equality_indexes = vec![];
+    for (_, value) in get_commitment_equalities(&vp_proof_graph)? {
+        let value = NamedOrBlankNode::from(value);
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(value.clone())) {
+            return Err(RDFProofsError::InvalidCommitmentEquality);
+        }
+        statements.push(PlannedStatement::CommitmentEquality {
+            value: value.clone(),
+        });
+        commitment_equality_indexes.push((statements.len() - 1, value));
+    }
+
+    // terms proven unequal must be hidden terms in VCs
+    let mut inequality_indexes = vec![];
+    for Inequality { lhs, rhs, .. } in [
+        get_inequalities(&vp_proof_graph, DISTINCT_CREDENTIALS)?,
+        get_inequalities(&vp_proof_graph, NOT_EQUAL)?,
+    ]
+    .concat()
+    {
+        let (lhs, rhs) = (NamedOrBlankNode::from(lhs), NamedOrBlankNode::from(rhs));
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(lhs.clone()))
+            || !equivs.contains_key(&OrderedNamedOrBlankNode(rhs.clone()))
+        {
+            return Err(RDFProofsError::InvalidInequality);
+        }
+        inequality_indexes.push((statements.len(), lhs.clone(), rhs.clone()));
+        for _ in 0..2 {
+            statements.push(PlannedStatement::Inequality {
+                lhs: lhs.clone(),
+                rhs: rhs.clone(),
+            });
+        }
+    }
+
+    // equalities of hidden terms among credentials, predicates, commitments, and inequalities
+    for (equiv_c14n_id, equiv_vec) in equivs {
+        let mut equiv_set: BTreeSet<(usize, usize)> = equiv_vec.into_iter().collect();
+        for (predicate_index, privates) in &predicate_privates {
+            if let Some(idx_in_predicate) = privates
+                .iter()
+                .position(|(_, bnode_in_private)| *bnode_in_private == equiv_c14n_id.0)
+            {
+                equiv_set.insert((*predicate_index, idx_in_predicate));
+            }
+        }
+        for (commitment_index, value) in &commitment_equality_indexes {
+            if *value == equiv_c14n_id.0 {
+                equiv_set.insert((*commitment_index, 1));
+            }
+        }
+        for (inequality_index, lhs, rhs) in &inequality_indexes {
+            if *lhs == equiv_c14n_id.0 {
+                equiv_set.insert((*inequality_index, 0));
+            }
+            if *rhs == equiv_c14n_id.0 {
+                equiv_set.insert((*inequality_index, 1));
+            }
+        }
+        if equiv_set.len() > 1 {
+            witness_equalities.push(equiv_set);
+        }
+    }
+
+    Ok(ProofPlan {
+        statements,
+        witness_equalities,
+    })
+}
+
+// replace the objects of the given predicates in disclosed VC proofs with fresh blank nodes,
+// so that they are treated as undisclosed messages, and add them to deanon map
+fn redact_proof_configs(
+    disclosed_vcs: Vec<VerifiableCredential>,
+    redacted_proof_configs: &[NamedNode],
+    deanon_map: &mut HashMap<NamedOrBlankNode, Term>,
+) -> Result<Vec<VerifiableCredential>, RDFProofsError> {
+    // terms required by verifier to identify the cryptosuite and the issuer's public key and params
+    if let Some(predicate) = redacted_proof_configs
+        .iter()
+        .find(|p| [TYPE, CRYPTOSUITE, VERIFICATION_METHOD, PARAMS_PROFILE].contains(&p.as_ref()))
+    {
+        return Err(RDFProofsError::NonRedactableProofConfig(predicate.clone()));
+    }
+
+    Ok(disclosed_vcs
+        .into_iter()
+        .map(|VerifiableCredential { document, proof }| {
+            let proof = proof
+                .iter()
+                .map(|triple| match triple.object {
+                    TermRef::BlankNode(_) => triple.into_owned(),
+                    object
+                        if redacted_proof_configs
+                            .iter()
+                            .any(|p| p.as_ref() == triple.predicate) =>
+                    {
+                        let bnode = BlankNode::default();
+                        deanon_map.insert(bnode.clone().into(), object.into_owned());
+                        Triple::new(
+                            triple.subject.into_owned(),
+                            triple.predicate.into_owned(),
+                            bnode,
+                        )
+                    }
+                    _ => triple.into_owned(),
+                })
+                .collect();
+            VerifiableCredential::new(document, proof)
+        })
+        .collect())
+}
+
+// hidden credential IDs to be proven pairwise distinct, which default to those of
+// all the disclosed VCs if verifier's policy requires unique credentials
+fn get_distinct_credentials(
+    distinct_credentials: Vec<BlankNode>,
+    vc_pairs: &[VcPair],
+    policy: Option<&Graph>,
+) -> Vec<BlankNode> {
+    if distinct_credentials.is_empty() && policy.is_some_and(requires_unique_credentials) {
+        get_hidden_credential_ids(
+            vc_pairs
+                .iter()
+                .map(|VcPair { disclosed, .. }| &disclosed.document),
+        )
+    } else {
+        distinct_credentials
+    }
+}
+
+fn get_encrypted_secret_and_pok<R: RngCore>(
+    opener_pub_keys: &[ElGamalPublicKey],
+    secret: &[u8],
+    rng: &mut R,
+) -> Result<ElGamalEscrowedEncryption, RDFProofsError> {
+    let params = generate_params(1);
+    let hasher = get_hasher();
+    let secret = hash_byte_to_field(secret, &hasher)?;
+    Ok(elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+        opener_pub_keys,
+        &params.h[0],
+        &secret,
+        rng,
+    )?)
+}
+
+fn get_deanon_map_from_string(
+    deanon_map_string: &HashMap<String, String>,
+) -> Result<HashMap<NamedOrBlankNode, Term>, RDFProofsError> {
+    deanon_map_string
+        .iter()
+        .map(|(k, v)| {
+            let key: NamedOrBlankNode = match get_term_from_string(k)? {
+                Term::NamedNode(n) => Ok(n.into()),
+                Term::BlankNode(n) => Ok(n.into()),
+                Term::Literal(_) => Err(RDFProofsError::InvalidDeanonMapFormat(k.to_string())),
+            }?;
+            let value = get_term_from_string(v)?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn get_public_keys(
+    proof_graph: &Graph,
+    key_graph: &KeyGraph,
+) -> Result<BBSPlusPublicKey, RDFProofsError> {
+    let vm_triple = proof_graph
+        .triples_for_predicate(VERIFICATION_METHOD)
+        .next()
+        .ok_or(RDFProofsError::InvalidVerificationMethod)?;
+    let vm = match vm_triple.object {
+        TermRef::NamedNode(v) => Ok(v),
+        _ => Err(RDFProofsError::InvalidVerificationMethodURL),
+    }?;
+    key_graph.get_public_key(vm)
+}
+
+fn deanonymize_subject(
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+    subject: &mut Subject,
+) -> Result<(), RDFProofsError> {
+    match subject {
+        Subject::BlankNode(bnode) => {
+            if let Some(v) = deanon_map.get(&NamedOrBlankNode::BlankNode(bnode.clone())) {
+                match v {
+                    Term::NamedNode(n) => *subject = Subject::NamedNode(n.clone()),
+                    Term::BlankNode(n) => *subject = Subject::BlankNode(n.clone()),
+                    _ => return Err(RDFProofsError::DeAnonymization),
+                }
+            }
+        }
+        Subject::NamedNode(node) => {
+            if let Some(v) = deanon_map.get(&NamedOrBlankNode::NamedNode(node.clone())) {
+                match v {
+                    Term::NamedNode(n) => *subject = Subject::NamedNode(n.clone()),
+                    Term::BlankNode(n) => *subject = Subject::BlankNode(n.clone()),
+                    _ => return Err(RDFProofsError::DeAnonymization),
                 }
             }
         }
@@ -518,12 +1516,19 @@ fn deanonymize_term(
 
 fn canonicalize_vcs(
     vcs: &Vec<VerifiableCredential>,
+    precomputed_canonicalizations: &Vec<Option<PrecomputedCanonicalization>>,
 ) -> Result<(Vec<VerifiableCredential>, HashMap<String, String>), RDFProofsError> {
     let mut bnode_map = HashMap::new();
     let canonicalized_vcs = vcs
         .iter()
-        .map(|VerifiableCredential { document, proof }| {
-            let (canonicalized_document, document_bnode_map) = canonicalize_graph(document)?;
+        .enumerate()
+        .map(|(i, VerifiableCredential { document, proof })| {
+            let (canonicalized_document, document_bnode_map) = canonicalize_graph_with_precomputed(
+                document,
+                precomputed_canonicalizations
+                    .get(i)
+                    .and_then(Option::as_ref),
+            )?;
             let (canonicalized_proof, proof_bnode_map) = canonicalize_graph(proof)?;
             for (k, v) in &document_bnode_map {
                 if bnode_map.contains_key(k) {
@@ -549,18 +1554,52 @@ fn canonicalize_vcs(
     Ok((canonicalized_vcs, bnode_map))
 }
 
+// seed for deterministic blank node labels, given by the digests of
+// the original VC signatures and the canonicalized disclosed VCs
+fn get_bnode_label_seed(vc_pairs: &Vec<VcPair>) -> Result<Vec<u8>, RDFProofsError> {
+    let mut hasher = Sha256::new();
+    for VcPair {
+        original,
+        disclosed,
+    } in vc_pairs
+    {
+        hasher.update(Sha256::digest(original.get_proof_value()?));
+        hasher.update(Sha256::digest(rdf_canon::canonicalize_graph(
+            &disclosed.document,
+        )?));
+        hasher.update(Sha256::digest(rdf_canon::canonicalize_graph(
+            &disclosed.proof,
+        )?));
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
 fn build_vp(
     disclosed_vcs: Vec<VerifiableCredential>,
     challenge: &Option<&str>,
     domain: &Option<&str>,
+    policy_digest: &Option<PolicyDigest>,
     blind_sign_request: &Option<BlindSignRequest>,
     ppid: &Option<PPID>,
-    encrypted_uid: &Option<ElGamalCiphertext>,
+    encrypted_uid: &Option<Vec<ElGamalCiphertext>>,
     predicates: Vec<Graph>,
+    previous_proofs: Vec<Graph>,
+    is_secret_rotation: bool,
+    commitment_equalities: &Vec<CommitmentEqualityRequest>,
+    attribute_encryptions: &[AttributeEncryption],
+    distinct_credentials: &Vec<Inequality>,
+    not_equals: &Vec<Inequality>,
+    same_namespaces: &[(BlankNode, BlankNode)],
+    device_binding: &Option<PresentedDeviceBinding>,
+    created_precision: CreatedPrecision,
+    clock: &dyn Clock,
+    secret_groups: &[Vec<usize>],
+    labeler: &mut BlankNodeLabeler,
+    limits: Option<&Limits>,
 ) -> Result<(Dataset, HashMap<String, String>, Vec<BlankNode>), RDFProofsError> {
-    let vp_id = BlankNode::default();
-    let vp_proof_id = BlankNode::default();
-    let vp_proof_graph_id = BlankNode::default();
+    let vp_id = labeler.generate();
+    let vp_proof_id = labeler.generate();
+    let vp_proof_graph_id = labeler.generate();
 
     let mut vp = Dataset::default();
     vp.insert(QuadRef::new(
@@ -596,7 +1635,7 @@ fn build_vp(
     vp.insert(QuadRef::new(
         &vp_proof_id,
         CREATED,
-        LiteralRef::new_typed_literal(&format!("{:?}", Utc::now()), xsd::DATE_TIME),
+        &created_precision.now(clock),
         &vp_proof_graph_id,
     ));
 
@@ -620,12 +1659,28 @@ fn build_vp(
         ));
     }
 
+    // add policy digest if exists
+    if let Some(policy_digest) = policy_digest {
+        vp.insert(QuadRef::new(
+            &vp_proof_id,
+            POLICY_DIGEST,
+            LiteralRef::new_simple_literal(policy_digest.as_str()),
+            &vp_proof_graph_id,
+        ));
+    }
+
     // use PPID as holder's ID if it is given, otherwise blank node is used,
-    // and add secret commitment if exists
+    // and add secret commitment if exists, which is distinguished as
+    // a rotated one if it commits to the new secret for secret rotation
+    let secret_commitment_predicate = if is_secret_rotation {
+        ROTATED_SECRET_COMMITMENT
+    } else {
+        SECRET_COMMITMENT
+    };
     match (ppid, blind_sign_request) {
         (None, None) => (),
         (None, Some(req)) => {
-            let vp_holder_id = BlankNode::default();
+            let vp_holder_id = labeler.generate();
             vp.insert(QuadRef::new(
                 &vp_id,
                 HOLDER,
@@ -634,7 +1689,7 @@ fn build_vp(
             ));
             vp.insert(QuadRef::new(
                 &vp_holder_id,
-                SECRET_COMMITMENT,
+                secret_commitment_predicate,
                 LiteralRef::new_typed_literal(&ark_to_base64url(&req.commitment)?, MULTIBASE),
                 GraphNameRef::DefaultGraph,
             ));
@@ -651,7 +1706,7 @@ fn build_vp(
             if let Some(req) = blind_sign_request {
                 vp.insert(QuadRef::new(
                     &vp_holder_id,
-                    SECRET_COMMITMENT,
+                    secret_commitment_predicate,
                     LiteralRef::new_typed_literal(&ark_to_base64url(&req.commitment)?, MULTIBASE),
                     GraphNameRef::DefaultGraph,
                 ));
@@ -659,23 +1714,140 @@ fn build_vp(
         }
     }
 
-    // add encrypted uid if exists
+    // add encrypted uid if exists,
+    // where a single opener's ciphertext is encoded as is for backward compatibility
     if let Some(encrypted_uid) = encrypted_uid {
+        let encoded = match encrypted_uid.as_slice() {
+            [cipher_text] => ark_to_base64url(cipher_text)?,
+            cipher_texts => ark_to_base64url(&cipher_texts.to_vec())?,
+        };
         vp.insert(QuadRef::new(
             &vp_proof_id,
             ENCRYPTED_UID,
-            LiteralRef::new_simple_literal(&ark_to_base64url(encrypted_uid).unwrap()),
+            LiteralRef::new_simple_literal(&encoded),
             &vp_proof_graph_id,
         ));
     }
 
-    // add predicates if exist
-    for predicate in predicates {
-        let predicate_graph_id = BlankNode::default();
+    // add equalities of hidden terms to verifier's commitments if exist
+    for CommitmentEqualityRequest {
+        commitment, value, ..
+    } in commitment_equalities
+    {
+        let equality_id = labeler.generate();
         vp.insert(QuadRef::new(
-            &vp_id,
-            PREDICATE,
-            &predicate_graph_id,
+            &vp_proof_id,
+            EQUAL_TO_COMMITMENT,
+            &equality_id,
+            &vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &equality_id,
+            COMMITMENT,
+            LiteralRef::new_typed_literal(&ark_to_base64url(commitment)?, MULTIBASE),
+            &vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &equality_id,
+            COMMITTED_VALUE,
+            value,
+            &vp_proof_graph_id,
+        ));
+    }
+
+    // add verifiable encryptions of hidden terms if exist
+    insert_attribute_encryptions(
+        &mut vp,
+        &vp_proof_id,
+        &vp_proof_graph_id,
+        attribute_encryptions,
+        labeler,
+    )?;
+
+    // add device binding if exists
+    if let Some(device_binding) = device_binding {
+        insert_device_binding(
+            &mut vp,
+            &vp_proof_id,
+            &vp_proof_graph_id,
+            &labeler.generate(),
+            device_binding,
+        )?;
+    }
+
+    // add inequalities of hidden credential IDs if exist
+    insert_inequalities(
+        &mut vp,
+        &vp_proof_id,
+        &vp_proof_graph_id,
+        DISTINCT_CREDENTIALS,
+        distinct_credentials,
+        labeler,
+    )?;
+
+    // add requested inequalities of hidden terms if exist
+    insert_inequalities(
+        &mut vp,
+        &vp_proof_id,
+        &vp_proof_graph_id,
+        NOT_EQUAL,
+        not_equals,
+        labeler,
+    )?;
+
+    // add requested namespace equalities of hidden IRIs if exist
+    insert_same_namespaces(
+        &mut vp,
+        &vp_proof_id,
+        &vp_proof_graph_id,
+        same_namespaces,
+        labeler,
+    );
+
+    // add previous proofs if exist, where the VP proof refers to
+    // the latest ones, i.e., those not referred to by any other previous proof
+    let previous_proof_ids = previous_proofs
+        .iter()
+        .map(get_previous_proof_id)
+        .collect::<Result<Vec<_>, _>>()?;
+    let referred_ids = previous_proofs
+        .iter()
+        .flat_map(|previous_proof| previous_proof.triples_for_predicate(PREVIOUS_PROOF))
+        .map(|t| t.object.into_owned())
+        .collect::<HashSet<_>>();
+    for (previous_proof, previous_proof_id) in previous_proofs.iter().zip(&previous_proof_ids) {
+        let previous_proof_graph_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            &vp_id,
+            PROOF,
+            &previous_proof_graph_id,
+            GraphNameRef::DefaultGraph,
+        ));
+        for triple in previous_proof.iter() {
+            vp.insert(QuadRef::new(
+                triple.subject,
+                triple.predicate,
+                triple.object,
+                &previous_proof_graph_id,
+            ));
+        }
+        if !referred_ids.contains(&Term::NamedNode(previous_proof_id.clone())) {
+            vp.insert(QuadRef::new(
+                &vp_proof_id,
+                PREVIOUS_PROOF,
+                previous_proof_id,
+                &vp_proof_graph_id,
+            ));
+        }
+    }
+
+    // add predicates if exist
+    for predicate in predicates {
+        let predicate_graph_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            &vp_id,
+            PREDICATE,
+            &predicate_graph_id,
             GraphNameRef::DefaultGraph,
         ));
         for triple in predicate.iter() {
@@ -688,21 +1860,48 @@ fn build_vp(
         }
     }
 
+    // add summary of verification methods and cryptosuites relied on by disclosed VCs
+    let credential_summary = get_credential_summary(
+        disclosed_vcs
+            .iter()
+            .map(|vc| {
+                Ok((
+                    get_verification_method_identifier(&vc.proof)?.into_owned(),
+                    vc.get_cryptosuite()?,
+                ))
+            })
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    );
+    insert_credential_summary(
+        &mut vp,
+        &vp_proof_id,
+        &vp_proof_graph_id,
+        &credential_summary,
+        labeler,
+    );
+
     // convert disclosed VC graphs (triples) into disclosed VC dataset (quads)
     let mut disclosed_vc_document_graph_names = Vec::with_capacity(disclosed_vcs.len());
     let disclosed_vc_quads = disclosed_vcs
         .iter()
         .map(|disclosed_vc| {
-            // generate random blank nodes as graph names
-            let disclosed_vc_document_graph_name = BlankNode::default();
-            let disclosed_vc_proof_graph_name = BlankNode::default();
+            // generate blank nodes as graph names
+            let disclosed_vc_document_graph_name = labeler.generate();
+            let disclosed_vc_proof_graph_name = labeler.generate();
 
             disclosed_vc_document_graph_names.push(disclosed_vc_document_graph_name.clone());
 
-            let disclosed_vc_document_id = disclosed_vc
-                .document
-                .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
-                .ok_or(RDFProofsError::VCWithoutVCType)?;
+            // fully hidden document, which discloses nothing but the proof configuration,
+            // is linked to its proof graph via a fresh blank node
+            let disclosed_vc_document_id: Subject = if disclosed_vc.document.is_empty() {
+                labeler.generate().into()
+            } else {
+                disclosed_vc
+                    .document
+                    .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+                    .ok_or(RDFProofsError::VCWithoutVCType)?
+                    .into_owned()
+            };
 
             let mut disclosed_vc_document_quads: Vec<Quad> = disclosed_vc
                 .document
@@ -749,13 +1948,39 @@ fn build_vp(
         vp.extend(disclosed_vc_quad);
     }
 
-    println!("vp draft (before canonicalization):\n{}\n", vp.to_string());
+    // add equivalence classes of hidden terms shared among disclosed VCs
+    let disclosed_vc_document_graphs = disclosed_vc_document_graph_names
+        .iter()
+        .map(|name| {
+            let name = GraphNameRef::from(name.as_ref());
+            (name, vp.graph(name))
+        })
+        .collect::<Vec<_>>();
+    let equivalences = get_equivalences(
+        disclosed_vc_document_graphs
+            .iter()
+            .map(|(name, graph)| (*name, graph)),
+    )?;
+    insert_equivalence_graph(&mut vp, &vp_id, &equivalences, labeler);
+
+    // add groups of VCs bound to per-credential secrets if exist
+    insert_secret_groups(
+        &mut vp,
+        &vp_proof_id,
+        &vp_proof_graph_id,
+        secret_groups,
+        &disclosed_vc_document_graph_names,
+        labeler,
+    )?;
+
+    debug_println!("vp draft (before canonicalization):\n{}\n", vp.to_string());
 
     // canonicalize VP draft
+    check_canonicalization_limits(&vp, limits)?;
     let canonicalized_vp_bnode_map = rdf_canon::issue(&vp)?;
     let canonicalized_vp = rdf_canon::relabel(&vp, &canonicalized_vp_bnode_map)?;
-    println!("VP draft bnode map:\n{:#?}\n", canonicalized_vp_bnode_map);
-    println!("VP draft:\n{}", rdf_canon::serialize(&canonicalized_vp));
+    debug_println!("VP draft bnode map:\n{:#?}\n", canonicalized_vp_bnode_map);
+    debug_println!("VP draft:\n{}", rdf_canon::serialize(&canonicalized_vp));
 
     Ok((
         canonicalized_vp,
@@ -764,6 +1989,29 @@ fn build_vp(
     ))
 }
 
+fn get_previous_proof_id(previous_proof: &Graph) -> Result<NamedNode, RDFProofsError> {
+    let previous_proof_subject = previous_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidProofChain(
+            "previous proof must be a DataIntegrityProof".to_string(),
+        ))?;
+    if previous_proof
+        .object_for_subject_predicate(previous_proof_subject, CRYPTOSUITE)
+        .is_some_and(|c| matches!(c, TermRef::Literal(v) if v.value() == CRYPTOSUITE_PROOF))
+    {
+        return Err(RDFProofsError::InvalidProofChain(format!(
+            "previous proof must not use {}",
+            CRYPTOSUITE_PROOF
+        )));
+    }
+    match previous_proof_subject {
+        SubjectRef::NamedNode(n) => Ok(n.into_owned()),
+        _ => Err(RDFProofsError::InvalidProofChain(
+            "previous proof must be identified by IRI".to_string(),
+        )),
+    }
+}
+
 fn extend_deanon_map(
     deanon_map: &HashMap<NamedOrBlankNode, Term>,
     vp_draft_bnode_map: &HashMap<String, String>,
@@ -796,6 +2044,31 @@ fn extend_deanon_map(
     Ok(res)
 }
 
+// indexes of the original VCs in the order of canonicalized graph names of disclosed VCs
+fn get_original_vc_indexes(
+    canonicalized_disclosed_vc_graphs: &OrderedVerifiableCredentialGraphViews,
+    extended_deanon_map: &HashMap<NamedOrBlankNode, Term>,
+    vc_document_graph_names: &Vec<BlankNode>,
+) -> Result<Vec<usize>, RDFProofsError> {
+    canonicalized_disclosed_vc_graphs
+        .keys()
+        .map(|k| {
+            let canonicalized_disclosed_vc_graph_name: &GraphNameRef = k.into();
+            let original_vc_graph_name = match canonicalized_disclosed_vc_graph_name {
+                GraphNameRef::BlankNode(n) => match extended_deanon_map.get(&(*n).into()) {
+                    Some(Term::BlankNode(n)) => Ok(n),
+                    _ => Err(RDFProofsError::Other("invalid VC graph name".to_string())),
+                },
+                _ => Err(RDFProofsError::Other("invalid VC graph name".to_string())),
+            }?;
+            vc_document_graph_names
+                .iter()
+                .position(|v| v == original_vc_graph_name)
+                .ok_or(RDFProofsError::Other("invalid VC index".to_string()))
+        })
+        .collect()
+}
+
 fn reorder_vc_graphs(
     canonicalized_original_vcs: &Vec<VerifiableCredential>,
     proof_values: &Vec<&str>,
@@ -808,30 +2081,32 @@ fn reorder_vc_graphs(
         Vec<VerifiableCredentialTriples>,
         Vec<String>,
         Vec<bool>,
+        Vec<SecretSlot>,
+        Vec<TermEncoding>,
     ),
     RDFProofsError,
 > {
     let mut ordered_original_vcs = BTreeMap::new();
     let mut ordered_proof_values = BTreeMap::new();
     let mut ordered_is_bounds = BTreeMap::new();
+    let mut ordered_secret_slots = BTreeMap::new();
+    let mut ordered_term_encodings = BTreeMap::new();
 
-    for k in canonicalized_disclosed_vc_graphs.keys() {
-        let canonicalized_disclosed_vc_graph_name: &GraphNameRef = k.into();
-        let original_vc_graph_name = match canonicalized_disclosed_vc_graph_name {
-            GraphNameRef::BlankNode(n) => match extended_deanon_map.get(&(*n).into()) {
-                Some(Term::BlankNode(n)) => Ok(n),
-                _ => Err(RDFProofsError::Other("invalid VC graph name".to_string())),
-            },
-            _ => Err(RDFProofsError::Other("invalid VC graph name".to_string())),
-        }?;
-        let original_index = vc_document_graph_names
-            .iter()
-            .position(|v| v == original_vc_graph_name)
-            .ok_or(RDFProofsError::Other("invalid VC index".to_string()))?;
+    let original_indexes = get_original_vc_indexes(
+        canonicalized_disclosed_vc_graphs,
+        extended_deanon_map,
+        vc_document_graph_names,
+    )?;
+    for (k, original_index) in canonicalized_disclosed_vc_graphs
+        .keys()
+        .zip(original_indexes)
+    {
         let original_vc = canonicalized_original_vcs
             .get(original_index)
             .ok_or(RDFProofsError::Other("invalid VC index".to_string()))?;
         let is_bound = original_vc.is_bound()?;
+        let secret_slot = original_vc.secret_slot()?;
+        let term_encoding = original_vc.term_encoding()?;
         let proof_value = proof_values
             .get(original_index)
             .ok_or(RDFProofsError::Other(
@@ -840,6 +2115,8 @@ fn reorder_vc_graphs(
         ordered_original_vcs.insert(k.clone(), original_vc);
         ordered_proof_values.insert(k.clone(), proof_value.to_owned());
         ordered_is_bounds.insert(k.clone(), is_bound);
+        ordered_secret_slots.insert(k.clone(), secret_slot);
+        ordered_term_encodings.insert(k.clone(), term_encoding);
     }
 
     // assert the keys of two VC graphs are equivalent
@@ -869,12 +2146,22 @@ fn reorder_vc_graphs(
         .into_iter()
         .map(|(_, v)| v)
         .collect::<Vec<_>>();
+    let secret_slot_vec = ordered_secret_slots
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>();
+    let term_encoding_vec = ordered_term_encodings
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>();
 
     Ok((
         original_vc_vec,
         disclosed_vc_vec,
         vc_proof_values_vec,
         is_bound_vec,
+        secret_slot_vec,
+        term_encoding_vec,
     ))
 }
 
@@ -898,24 +2185,10 @@ fn gen_index_map(
             deanonymize_term(extended_deanon_map, &mut triple.object)?;
         }
     }
-    println!("deanonymized canonicalized disclosed VC graphs:");
+    debug_println!("deanonymized canonicalized disclosed VC graphs:");
     for VerifiableCredentialTriples { document, proof } in &disclosed_vc_triples_cloned {
-        println!(
-            "document:\n{}",
-            document
-                .iter()
-                .map(|t| format!("{} .\n", t.to_string()))
-                .reduce(|l, r| format!("{}{}", l, r))
-                .unwrap()
-        );
-        println!(
-            "proof:\n{}",
-            proof
-                .iter()
-                .map(|t| format!("{} .\n", t.to_string()))
-                .reduce(|l, r| format!("{}{}", l, r))
-                .unwrap()
-        );
+        debug_println!("document:\n{}", triples_to_string(document));
+        debug_println!("proof:\n{}", triples_to_string(proof));
     }
 
     // calculate index mapping
@@ -933,24 +2206,8 @@ fn gen_index_map(
                     proof: original_proof,
                 },
             )| {
-                let document_map = disclosed_document
-                    .iter()
-                    .map(|disclosed_triple| {
-                        original_document
-                            .iter()
-                            .position(|original_triple| *disclosed_triple == *original_triple)
-                            .ok_or(RDFProofsError::DisclosedVCIsNotSubsetOfOriginalVC)
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-                let proof_map = disclosed_proof
-                    .iter()
-                    .map(|disclosed_triple| {
-                        original_proof
-                            .iter()
-                            .position(|original_triple| *disclosed_triple == *original_triple)
-                            .ok_or(RDFProofsError::DisclosedVCIsNotSubsetOfOriginalVC)
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+                let document_map = map_triple_positions(disclosed_document, original_document)?;
+                let proof_map = map_triple_positions(disclosed_proof, original_proof)?;
                 let document_len = original_document.len();
                 let proof_len = original_proof.len();
                 Ok(StatementIndexMap::new(
@@ -962,15 +2219,58 @@ fn gen_index_map(
             },
         )
         .collect::<Result<Vec<_>, RDFProofsError>>()?;
+    invariant!(
+        validate_index_maps(&index_map, disclosed_vc_vec).is_ok(),
+        "index maps are inconsistent with disclosed VCs"
+    );
 
     Ok(index_map)
 }
 
+fn triples_to_string(triples: &[Triple]) -> String {
+    triples.iter().map(|t| format!("{} .\n", t)).collect()
+}
+
+// map each disclosed triple to its position in the original triples
+// in O(n + m) time, using a hash index from triple to positions;
+// disclosed triples that coincide after deanonymization are assigned to the positions of
+// the duplicated triples in the original in order, and rejected if they outnumber them,
+// since mapping them to the same position would make the proof unverifiable
+fn map_triple_positions(
+    disclosed_triples: &[Triple],
+    original_triples: &[Triple],
+) -> Result<Vec<usize>, RDFProofsError> {
+    let mut original_positions: HashMap<&Triple, VecDeque<usize>> =
+        HashMap::with_capacity(original_triples.len());
+    for (i, original_triple) in original_triples.iter().enumerate() {
+        original_positions
+            .entry(original_triple)
+            .or_default()
+            .push_back(i);
+    }
+
+    disclosed_triples
+        .iter()
+        .map(|disclosed_triple| {
+            let positions = original_positions
+                .get_mut(disclosed_triple)
+                .ok_or(RDFProofsError::DisclosedVCIsNotSubsetOfOriginalVC)?;
+            positions.pop_front().ok_or_else(|| {
+                RDFProofsError::DuplicatedDisclosedTriple(disclosed_triple.to_string())
+            })
+        })
+        .collect()
+}
+
 fn derive_proof_value<R: RngCore>(
     rng: &mut R,
     secret: Option<&[u8]>,
+    vc_secrets: Vec<Option<&[u8]>>,
+    secret_groups: Vec<BTreeSet<usize>>,
     original_vc_triples: Vec<VerifiableCredentialTriples>,
     is_bounds: Vec<bool>,
+    secret_slots: Vec<SecretSlot>,
+    term_encodings: Vec<TermEncoding>,
     disclosed_vc_triples: Vec<VerifiableCredentialTriples>,
     public_keys: Vec<BBSPlusPublicKey>,
     proof_values: Vec<String>,
@@ -980,15 +2280,26 @@ fn derive_proof_value<R: RngCore>(
     blind_sign_request: &Option<BlindSignRequest>,
     ppid: &Option<PPID>,
     predicate_graphs: OrderedGraphViews,
-    circuits: HashMap<NamedNode, Circuit>,
+    mut circuits: HashMap<NamedNode, Circuit>,
     extended_deanon_map: &HashMap<NamedOrBlankNode, Term>,
-    verifiable_encryption_for_uid: &Option<ElGamalVerifiableEncryption>,
+    verifiable_encryption_for_uid: &Option<ElGamalEscrowedEncryption>,
+    new_secret: Option<&[u8]>,
+    commitment_equalities: Vec<(G1Affine, Fr, NamedOrBlankNode)>,
+    inequalities: Vec<InequalityOpening>,
+    protocol: Option<&str>,
+    device_binding: &Option<PresentedDeviceBinding>,
+    attribute_encryptions: Vec<(NamedOrBlankNode, ElGamalEscrowedEncryption)>,
+    same_namespaces: Vec<(BlankNode, BlankNode)>,
+    metrics: Option<&dyn Metrics>,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&dyn Progress>,
 ) -> Result<String, RDFProofsError> {
+    enter_phase(ProofPhase::StatementBuild, cancellation, progress)?;
     let hasher = get_hasher();
 
     // reorder disclosed VC triples according to index map
     let reordered_disclosed_vc_triples = reorder_vc_triples(&disclosed_vc_triples, &index_map)?;
-    println!(
+    debug_println!(
         "reordered_disclosed_vc_triples:\n{:#?}\n",
         reordered_disclosed_vc_triples
     );
@@ -997,11 +2308,21 @@ fn derive_proof_value<R: RngCore>(
     let disclosed_and_undisclosed_terms = reordered_disclosed_vc_triples
         .iter()
         .zip(original_vc_triples)
-        .zip(&is_bounds)
+        .zip(
+            is_bounds
+                .iter()
+                .zip(secret_slots.into_iter().zip(&term_encodings)),
+        )
         .enumerate()
         .map(
-            |(i, ((disclosed_vc_triples, original_vc_triples), is_bound))| {
-                let s = match (is_bound, secret) {
+            |(
+                i,
+                (
+                    (disclosed_vc_triples, original_vc_triples),
+                    (is_bound, (secret_slot, term_encoding)),
+                ),
+            )| {
+                let s = match (is_bound, vc_secrets.get(i).copied().flatten()) {
                     (true, Some(s)) => Ok(Some(s)),
                     (true, None) => Err(RDFProofsError::MissingSecret),
                     (false, _) => Ok(None),
@@ -1011,16 +2332,18 @@ fn derive_proof_value<R: RngCore>(
                     &original_vc_triples,
                     i,
                     s,
+                    secret_slot,
+                    *term_encoding,
                     &hasher,
                 )
             },
         )
         .collect::<Result<Vec<_>, RDFProofsError>>()?;
-    println!(
+    debug_println!(
         "disclosed_and_undisclosed:\n{:#?}\n",
         disclosed_and_undisclosed_terms
     );
-    println!("proof values: {:?}", proof_values);
+    debug_println!("proof values: {:?}", proof_values);
 
     let term_counts = disclosed_and_undisclosed_terms
         .iter()
@@ -1030,11 +2353,16 @@ fn derive_proof_value<R: RngCore>(
                 .map_err(|_| RDFProofsError::MessageSizeOverflow)
         })
         .collect::<Result<Vec<u32>, _>>()?;
+    let params_profiles = disclosed_vc_triples
+        .iter()
+        .map(|vc| get_params_profile(vc.proof.iter().map(|t| t.as_ref())))
+        .collect::<Result<Vec<_>, _>>()?;
     let params_for_commitment = generate_params(1);
     let params_and_pks = term_counts
         .iter()
+        .zip(params_profiles)
         .zip(public_keys)
-        .map(|(t, pk)| (generate_params(*t), pk));
+        .map(|((t, profile), pk)| (generate_params_with_profile(profile, *t), pk));
 
     // merge each partial equivs
     let mut equivs: BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>> = BTreeMap::new();
@@ -1050,9 +2378,40 @@ fn derive_proof_value<R: RngCore>(
                 .extend(v.clone());
         }
     }
+    // messages are partitioned into disclosed and undisclosed ones,
+    // and each undisclosed one belongs to at most one equivalence class
+    invariant!(
+        disclosed_and_undisclosed_terms.iter().all(|terms| {
+            terms.disclosed.len() + terms.undisclosed.len() == terms.term_count
+                && terms
+                    .disclosed
+                    .keys()
+                    .chain(terms.undisclosed.keys())
+                    .all(|i| *i < terms.term_count)
+                && terms
+                    .disclosed
+                    .keys()
+                    .all(|i| !terms.undisclosed.contains_key(i))
+        }),
+        "disclosed and undisclosed messages are inconsistent"
+    );
+    invariant!(
+        {
+            let mut seen = HashSet::new();
+            equivs.values().flatten().all(|(vc_index, term_index)| {
+                seen.insert((*vc_index, *term_index))
+                    && disclosed_and_undisclosed_terms
+                        .get(*vc_index)
+                        .is_some_and(|terms| terms.undisclosed.contains_key(term_index))
+            })
+        },
+        "equivalence classes overlap or refer to disclosed messages"
+    );
 
-    // build statements
+    // build statements, tagged with their types in the serialized proof
+    // in the order of `StatementKind`
     let mut statements = Statements::new();
+    let mut layout = StatementLayout::new();
     // statements for BBS+ signatures
     for (DisclosedAndUndisclosedTerms { disclosed, .. }, (params, public_key)) in
         disclosed_and_undisclosed_terms.iter().zip(params_and_pks)
@@ -1062,6 +2421,7 @@ fn derive_proof_value<R: RngCore>(
             public_key,
             disclosed.clone(),
         ));
+        layout.push(StatementKind::BbsPlusSignature, 1)?;
     }
     // statement for PPID
     let mut ppid_index = None;
@@ -1070,13 +2430,22 @@ fn derive_proof_value<R: RngCore>(
             vec![ppid.base],
             ppid.ppid,
         ));
-        ppid_index = Some(statements.len() - 1);
+        ppid_index = Some(layout.push(StatementKind::Ppid, 1)?);
     }
-    // statements for verifiable encryption of uid
+    // statements for verifiable encryption of uid escrowed among openers
+    let mut encryption_equalities = None;
     if let Some(verifiable_encryption_for_uid) = verifiable_encryption_for_uid {
+        let encryption_index = layout.push(
+            StatementKind::VerifiableEncryption,
+            verifiable_encryption_for_uid.statements.0.len(),
+        )?;
         for statement in verifiable_encryption_for_uid.statements.0.iter() {
             statements.add(statement.clone());
         }
+        encryption_equalities = Some(get_escrowed_encryption_equalities(
+            encryption_index,
+            verifiable_encryption_for_uid.cipher_texts.len(),
+        ));
     }
     // statement for secret commitment
     let mut secret_commitment_index = None;
@@ -1085,76 +2454,181 @@ fn derive_proof_value<R: RngCore>(
             vec![params_for_commitment.h_0, params_for_commitment.h[0]],
             req.commitment,
         ));
-        secret_commitment_index = Some(statements.len() - 1);
+        secret_commitment_index = Some(layout.push(StatementKind::SecretCommitment, 1)?);
     }
-    // statements for predicates
+    // statements for predicates, where the setup parameters of each circuit
+    // are parsed once and shared among the predicates using the same circuit
+    let mut setup_params: Vec<SetupParams> = vec![];
+    let mut circuit_setup_params_indexes: HashMap<NamedNode, (usize, HashMap<String, u32>)> =
+        HashMap::new();
     let mut predicate_indexes = vec![];
     let mut predicate_privates = vec![];
     let mut predicate_publics = vec![];
+    let mut predicate_term_encodings = vec![];
+    let mut predicate_input_bit_lengths = vec![];
     for (_, predicate_graph) in predicate_graphs {
-        let predicate_subject = predicate_graph
-            .subject_for_predicate_object(TYPE, PREDICATE_TYPE)
-            .ok_or(RDFProofsError::InvalidPredicate)?;
-        let TermRef::NamedNode(predicate_circuit) = predicate_graph
-            .object_for_subject_predicate(predicate_subject, CIRCUIT)
-            .ok_or(RDFProofsError::InvalidPredicate)?
-        else {
-            return Err(RDFProofsError::InvalidPredicate);
-        };
-        let circuit = circuits
-            .get(&predicate_circuit.into_owned())
-            .ok_or(RDFProofsError::MissingPredicateCircuit)?;
-        statements.add(R1CSCircomProver::new_statement_from_params(
-            circuit.get_r1cs(),
-            circuit.get_wasm(),
-            circuit.get_proving_key(),
+        // declared outputs are computed by the circuit itself, so that they are only validated here
+        let PredicateGraph {
+            circuit: predicate_circuit,
+            private,
+            public,
+            ..
+        } = PredicateGraph::try_from(&predicate_graph)?;
+        let (setup_params_index, input_bit_lengths) =
+            match circuit_setup_params_indexes.get(&predicate_circuit) {
+                Some((idx, input_bit_lengths)) => (*idx, input_bit_lengths.clone()),
+                None => {
+                    let circuit = circuits
+                        .remove(&predicate_circuit)
+                        .ok_or(RDFProofsError::MissingPredicateCircuit)?;
+                    let idx = setup_params.len();
+                    let input_bit_lengths = circuit.get_input_bit_lengths().clone();
+                    setup_params.extend(circuit.into_setup_params());
+                    circuit_setup_params_indexes
+                        .insert(predicate_circuit, (idx, input_bit_lengths.clone()));
+                    (idx, input_bit_lengths)
+                }
+            };
+        predicate_input_bit_lengths.push(input_bit_lengths);
+        statements.add(R1CSCircomProver::new_statement_from_params_ref(
+            setup_params_index,
+            setup_params_index + 1,
+            setup_params_index + 2,
         )?);
-        predicate_indexes.push(statements.len() - 1);
-
-        let mut privates = vec![];
-        let TermRef::BlankNode(predicate_private) = predicate_graph
-            .object_for_subject_predicate(predicate_subject, PRIVATE)
-            .ok_or(RDFProofsError::InvalidPredicate)?
-        else {
-            return Err(RDFProofsError::InvalidPredicate);
-        };
-        read_private_var_list(predicate_private, &mut privates, &predicate_graph)?;
-        predicate_privates.push(privates);
-
-        let mut publics = vec![];
-        let TermRef::BlankNode(predicate_public) = predicate_graph
-            .object_for_subject_predicate(predicate_subject, PUBLIC)
-            .ok_or(RDFProofsError::InvalidPredicate)?
-        else {
-            return Err(RDFProofsError::InvalidPredicate);
-        };
-        read_public_var_list(predicate_public, &mut publics, &predicate_graph)?;
-        predicate_publics.push(publics);
+        predicate_indexes.push(layout.push(StatementKind::Predicate, 1)?);
+
+        // private inputs are encoded as the signed messages they are proven equal to,
+        // and public inputs as the private ones
+        let private_term_encodings = private
+            .iter()
+            .map(|(_, term)| get_hidden_term_encoding(term, &equivs, &term_encodings))
+            .collect::<Vec<_>>();
+        let public_term_encoding = get_predicate_term_encoding(&private, &equivs, &term_encodings);
+        predicate_term_encodings.push((private_term_encodings, public_term_encoding));
+
+        predicate_privates.push(private);
+        predicate_publics.push(public);
+    }
+    // statements for equalities to verifier's commitments,
+    // whose committed values must be hidden terms in VCs
+    let mut commitment_equality_indexes = vec![];
+    for (commitment, _, value) in &commitment_equalities {
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(value.clone())) {
+            return Err(RDFProofsError::InvalidCommitmentEquality);
+        }
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            get_commitment_bases(),
+            *commitment,
+        ));
+        commitment_equality_indexes.push(layout.push(StatementKind::CommitmentEquality, 1)?);
+    }
+    // statements for inequalities of hidden terms
+    let mut inequality_indexes = vec![];
+    for InequalityOpening {
+        inequality: Inequality {
+            commitment,
+            lhs,
+            rhs,
+        },
+        ..
+    } in &inequalities
+    {
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(lhs.clone().into()))
+            || !equivs.contains_key(&OrderedNamedOrBlankNode(rhs.clone().into()))
+        {
+            return Err(RDFProofsError::InvalidInequality);
+        }
+        let inequality_statements = get_inequality_statements(commitment).0;
+        inequality_indexes
+            .push(layout.push(StatementKind::Inequality, inequality_statements.len())?);
+        for statement in inequality_statements {
+            statements.add(statement);
+        }
+    }
+    // statement for device binding, whose key handle commits to the holder's secret
+    let mut device_binding_index = None;
+    if let Some(binding) = device_binding {
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            get_commitment_bases(),
+            binding.key_handle,
+        ));
+        device_binding_index = Some(layout.push(StatementKind::DeviceBinding, 1)?);
+    }
+    // statements for verifiable encryptions of hidden terms, which must be hidden terms in VCs
+    let mut attribute_encryption_equalities = vec![];
+    for (value, encryption) in &attribute_encryptions {
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(value.clone())) {
+            return Err(RDFProofsError::InvalidAttributeEncryption);
+        }
+        let encryption_index = layout.push(
+            StatementKind::AttributeEncryption,
+            encryption.statements.0.len(),
+        )?;
+        for statement in encryption.statements.0.iter() {
+            statements.add(statement.clone());
+        }
+        attribute_encryption_equalities.push(get_escrowed_encryption_equalities(
+            encryption_index,
+            encryption.cipher_texts.len(),
+        ));
     }
 
     // build meta statements
     let mut meta_statements = MetaStatements::new();
 
-    // proof of equality for embedded secrets
-    let mut secret_equiv_set: BTreeSet<(usize, usize)> = is_bounds
-        .iter()
-        .enumerate()
-        .filter(|(_, &is_bound)| is_bound)
-        .map(|(i, _)| (i, 0)) // `0` is the index for embedded secret in VC
-        .collect();
+    // proof of equality for embedded secrets, separately for each group of VCs
+    // bound to per-credential secret
+    let (mut secret_equiv_set, secret_group_equiv_sets) = split_embedded_secrets(
+        is_bounds
+            .iter()
+            .zip(&disclosed_and_undisclosed_terms)
+            .enumerate()
+            .filter(|(_, (&is_bound, _))| is_bound)
+            .map(|(i, (_, terms))| (i, terms.secret_index)) // embedded secret in VC
+            .collect(),
+        &secret_groups,
+    );
+    for equiv_set in secret_group_equiv_sets {
+        if equiv_set.len() > 1 {
+            meta_statements.add_witness_equality(EqualWitnesses(equiv_set));
+        }
+    }
     // add PPID to the proof of equalities if exists
     if let Some(idx) = ppid_index {
         // `0` corresponds to the committed secret in PPID
         secret_equiv_set.insert((idx, 0));
     }
-    // add secret commitment to the proof of equalities if exists
-    if let Some(idx) = secret_commitment_index {
+    // add secret commitment to the proof of equalities if exists,
+    // except for the rotated one committing to the new secret
+    if let (Some(idx), None) = (secret_commitment_index, new_secret) {
         // `1` corresponds to the committed secret in Pedersen Commitment (`0` corresponds to the blinding)
         secret_equiv_set.insert((idx, 1));
     }
+    // add key handle of device binding to the proof of equalities if exists
+    if let Some(idx) = device_binding_index {
+        secret_equiv_set.insert((idx, 1));
+    }
+    // add encrypted uid to the proof of equalities if exists,
+    // along with the equalities of the randomness used for each opener
+    if let Some((randomness_equalities, uid_position)) = encryption_equalities {
+        secret_equiv_set.insert(uid_position);
+        for equality in randomness_equalities {
+            meta_statements.add_witness_equality(EqualWitnesses(equality));
+        }
+    }
     if secret_equiv_set.len() > 1 {
         meta_statements.add_witness_equality(EqualWitnesses(secret_equiv_set));
     }
+    // equalities of the randomness used for each key of encrypted hidden terms
+    for (randomness_equalities, _) in &attribute_encryption_equalities {
+        for equality in randomness_equalities {
+            meta_statements.add_witness_equality(EqualWitnesses(equality.clone()));
+        }
+    }
+    // equalities of the namespaces of hidden IRIs
+    for equality in get_namespace_equalities(&same_namespaces, &equivs, &term_encodings)? {
+        meta_statements.add_witness_equality(EqualWitnesses(equality));
+    }
 
     // proof of equality
     for (equiv_c14n_id, equiv_vec) in equivs {
@@ -1172,19 +2646,63 @@ fn derive_proof_value<R: RngCore>(
                 equiv_set.insert((*predicate_index, idx_in_predicate));
             }
         }
-        println!("equiv_set: {:?}", equiv_set);
+
+        // add equality for committed values
+        for ((_, _, value), commitment_index) in commitment_equalities
+            .iter()
+            .zip(&commitment_equality_indexes)
+        {
+            if *value == equiv_c14n_id.0 {
+                // `1` corresponds to the committed value (`0` corresponds to the blinding)
+                equiv_set.insert((*commitment_index, 1));
+            }
+        }
+
+        // add equality for terms proven unequal
+        for (InequalityOpening { inequality, .. }, inequality_index) in
+            inequalities.iter().zip(&inequality_indexes)
+        {
+            // `0` and `1` correspond to the left-hand and right-hand terms
+            if NamedOrBlankNode::from(inequality.lhs.clone()) == equiv_c14n_id.0 {
+                equiv_set.insert((*inequality_index, 0));
+            }
+            if NamedOrBlankNode::from(inequality.rhs.clone()) == equiv_c14n_id.0 {
+                equiv_set.insert((*inequality_index, 1));
+            }
+        }
+
+        // add equality for encrypted hidden terms
+        for ((value, _), (_, value_position)) in attribute_encryptions
+            .iter()
+            .zip(&attribute_encryption_equalities)
+        {
+            if *value == equiv_c14n_id.0 {
+                equiv_set.insert(*value_position);
+            }
+        }
+        debug_println!("equiv_set: {:?}", equiv_set);
         if equiv_set.len() > 1 {
             meta_statements.add_witness_equality(EqualWitnesses(equiv_set));
         }
     }
-    println!("meta_statements: {:?}", meta_statements);
+    debug_println!("meta_statements: {:?}", meta_statements);
 
     // build proof spec
-    let context = generate_proof_spec_context(&canonicalized_vp, &index_map)?;
-    let proof_spec = ProofSpec::new(statements, meta_statements, vec![], Some(context));
+    if let Some(metrics) = metrics {
+        metrics.statements_built(statements.len());
+    }
+    let context = generate_proof_spec_context(
+        &canonicalized_vp,
+        &index_map,
+        PROOF_LAYOUT_VERSION,
+        protocol,
+        get_credential_contexts(PROOF_CONTEXT_VERSION, &disclosed_vc_triples)?,
+    )?;
+    let proof_spec = ProofSpec::new(statements, meta_statements, setup_params, Some(context));
     proof_spec.validate()?;
 
     // build witnesses
+    enter_phase(ProofPhase::SnarkWitness, cancellation, progress)?;
     let mut witnesses = Witnesses::new();
     // witnesses for BBS+ signatures
     for (DisclosedAndUndisclosedTerms { undisclosed, .. }, proof_value) in
@@ -1214,7 +2732,7 @@ fn derive_proof_value<R: RngCore>(
     }
     // witness for secret commitment
     if let Some(req) = blind_sign_request {
-        if let Some(s) = secret {
+        if let Some(s) = new_secret.or(secret) {
             witnesses.add(Witness::PedersenCommitment(vec![
                 req.blinding,
                 hash_byte_to_field(s, &hasher)?,
@@ -1223,33 +2741,93 @@ fn derive_proof_value<R: RngCore>(
             return Err(RDFProofsError::MissingSecret);
         }
     }
-    // witness for predicates
-    for (private, public) in predicate_privates.iter().zip(&predicate_publics) {
+    // witness for predicates, whose inputs are validated against the bit lengths
+    // expected by the circuit if given, where the elements of input array like `addends[0]`
+    // are grouped into the array signal and share the bit length given to the array
+    for (((private, public), (private_term_encodings, public_term_encoding)), input_bit_lengths) in
+        predicate_privates
+            .iter()
+            .zip(&predicate_publics)
+            .zip(&predicate_term_encodings)
+            .zip(&predicate_input_bit_lengths)
+    {
+        let get_bit_length = |var: &str| {
+            input_bit_lengths.get(var).or_else(|| {
+                split_array_input(var).and_then(|(name, _)| input_bit_lengths.get(name))
+            })
+        };
         let mut r1cs_wit = R1CSCircomWitness::new();
         // private
-        for (var, val) in private {
-            println!("{}", val);
+        let mut private_inputs = vec![];
+        for ((var, val), term_encoding) in private.iter().zip(private_term_encodings) {
+            debug_println!("{}", val);
             let val = extended_deanon_map
                 .get(val)
                 .ok_or(RDFProofsError::InvalidPredicate)?;
-            r1cs_wit.set_private(
-                var.to_string(),
-                vec![hash_term_to_field(val.into(), &hasher)?],
-            )
+            let val_fr = hash_term_to_field_with_encoding(val.into(), *term_encoding, &hasher)?;
+            if let Some(bit_length) = get_bit_length(var) {
+                validate_input_bit_length(var, val.into(), &val_fr, *bit_length)?;
+            }
+            private_inputs.push((var.to_string(), val_fr));
+        }
+        for (var, vals) in group_array_inputs(private_inputs)? {
+            r1cs_wit.set_private(var, vals)
         }
         // public
+        let mut public_inputs = vec![];
         for (var, val) in public {
-            println!("{}", val);
-            r1cs_wit.set_public(
-                var.to_string(),
-                vec![hash_term_to_field(val.into(), &hasher)?],
-            )
+            debug_println!("{}", val);
+            let val_fr =
+                hash_term_to_field_with_encoding(val.into(), *public_term_encoding, &hasher)?;
+            if let Some(bit_length) = get_bit_length(var) {
+                validate_input_bit_length(var, val.into(), &val_fr, *bit_length)?;
+            }
+            public_inputs.push((var.to_string(), val_fr));
+        }
+        for (var, vals) in group_array_inputs(public_inputs)? {
+            r1cs_wit.set_public(var, vals)
         }
         witnesses.add(Witness::R1CSLegoGroth16(r1cs_wit));
     }
-    println!("witnesses:\n{:#?}\n", witnesses);
+    // witnesses for equalities to verifier's commitments
+    for (_, blinding, value) in &commitment_equalities {
+        let value = extended_deanon_map
+            .get(value)
+            .ok_or(RDFProofsError::InvalidCommitmentEquality)?;
+        witnesses.add(Witness::PedersenCommitment(vec![
+            *blinding,
+            hash_term_to_field(value.into(), &hasher)?,
+        ]));
+    }
+    // witnesses for inequalities of hidden terms
+    for InequalityOpening {
+        inequality: Inequality { lhs, rhs, .. },
+        blinding,
+    } in &inequalities
+    {
+        let (lhs, rhs) = get_unequal_values(lhs, rhs, extended_deanon_map)?;
+        for witness in get_inequality_witnesses(&lhs, &rhs, blinding)?.0 {
+            witnesses.add(witness);
+        }
+    }
+    // witness for device binding
+    if let Some(binding) = device_binding {
+        let secret = secret.ok_or(RDFProofsError::MissingSecret)?;
+        witnesses.add(Witness::PedersenCommitment(vec![
+            binding.blinding,
+            hash_byte_to_field(secret, &hasher)?,
+        ]));
+    }
+    // witnesses for verifiable encryptions of hidden terms
+    for (_, encryption) in &attribute_encryptions {
+        for witness in encryption.witnesses.0.iter() {
+            witnesses.add(witness.clone());
+        }
+    }
+    debug_println!("witnesses:\n{:#?}\n", witnesses);
 
     // build proof
+    enter_phase(ProofPhase::Proof, cancellation, progress)?;
     let proof = Proof::new::<R, BBSPlusHash>(
         rng,
         proof_spec,
@@ -1258,26 +2836,34 @@ fn derive_proof_value<R: RngCore>(
         Default::default(),
     )?
     .0;
-    println!("proof:\n{:#?}\n", proof);
+    debug_println!("proof:\n{:#?}\n", proof);
 
     // serialize proof and index_map
-    serialize_proof_with_index_map(proof, &index_map)
+    serialize_proof_with_index_map(
+        proof,
+        &index_map,
+        layout.tags().into_iter().map(String::from).collect(),
+    )
 }
 
 fn serialize_proof_with_index_map(
     proof: Proof,
     index_map: &Vec<StatementIndexMap>,
+    statement_tags: Vec<String>,
 ) -> Result<String, RDFProofsError> {
     // TODO: optimize
     // TODO: use multicodec
     let proof_with_index_map = ProofWithIndexMap {
         proof,
         index_map: index_map.clone(),
+        version: PROOF_LAYOUT_VERSION,
+        features: get_required_features(&statement_tags),
+        statement_tags,
+        extensions: vec![],
+        generator: GENERATOR.to_string(),
+        context_version: PROOF_CONTEXT_VERSION,
     };
-    let proof_with_index_map_cbor = serde_cbor::to_vec(&proof_with_index_map)?;
-    let proof_with_index_map_multibase =
-        multibase::encode(Base::Base64Url, proof_with_index_map_cbor);
-    Ok(proof_with_index_map_multibase)
+    encode_proof_value(&proof_with_index_map)
 }
 
 #[derive(Debug)]
@@ -1286,6 +2872,7 @@ struct DisclosedAndUndisclosedTerms {
     undisclosed: BTreeMap<usize, Fr>,
     equivs: HashMap<NamedOrBlankNode, Vec<(usize, usize)>>,
     term_count: usize,
+    secret_index: usize,
 }
 
 fn get_disclosed_and_undisclosed_terms(
@@ -1293,6 +2880,8 @@ fn get_disclosed_and_undisclosed_terms(
     original_vc_triples: &VerifiableCredentialTriples,
     vc_index: usize,
     secret: Option<&[u8]>,
+    secret_slot: SecretSlot,
+    term_encoding: TermEncoding,
     hasher: &BBSPlusDefaultFieldHasher,
 ) -> Result<DisclosedAndUndisclosedTerms, RDFProofsError> {
     let mut disclosed_terms = BTreeMap::<usize, Fr>::new();
@@ -1308,13 +2897,21 @@ fn get_disclosed_and_undisclosed_terms(
         proof: original_proof,
     } = original_vc_triples;
 
-    let mut current_term_index = 0;
-
+    // the secret slot precedes the document or follows the proof config, depending on cryptosuite
+    let triple_width = 3 * term_encoding.messages_per_term();
+    let secret_index = match secret_slot {
+        SecretSlot::First => 0,
+        SecretSlot::Last => triple_width * (disclosed_document.len() + disclosed_proof.len()) + 1,
+    };
     match secret {
-        Some(s) => undisclosed_terms.insert(current_term_index, hash_byte_to_field(s, hasher)?),
-        None => disclosed_terms.insert(current_term_index, Fr::from(1)),
+        Some(s) => undisclosed_terms.insert(secret_index, hash_byte_to_field(s, hasher)?),
+        None => disclosed_terms.insert(secret_index, Fr::from(1)),
     };
-    current_term_index += 1;
+
+    let mut current_term_index = 0;
+    if secret_slot == SecretSlot::First {
+        current_term_index += 1;
+    }
 
     for (j, disclosed_triple) in disclosed_document {
         let original = original_document
@@ -1329,9 +2926,10 @@ fn get_disclosed_and_undisclosed_terms(
             &mut disclosed_terms,
             &mut undisclosed_terms,
             &mut equivs,
+            term_encoding,
             hasher,
         )?;
-        current_term_index += 3;
+        current_term_index += triple_width;
     }
 
     let delimiter = get_delimiter()?;
@@ -1351,15 +2949,20 @@ fn get_disclosed_and_undisclosed_terms(
             &mut disclosed_terms,
             &mut undisclosed_terms,
             &mut equivs,
+            term_encoding,
             hasher,
         )?;
-        current_term_index += 3;
+        current_term_index += triple_width;
+    }
+    if secret_slot == SecretSlot::Last {
+        current_term_index += 1;
     }
     Ok(DisclosedAndUndisclosedTerms {
         disclosed: disclosed_terms,
         undisclosed: undisclosed_terms,
         equivs,
         term_count: current_term_index,
+        secret_index,
     })
 }
 
@@ -1371,14 +2974,18 @@ fn build_disclosed_and_undisclosed_terms(
     disclosed_terms: &mut BTreeMap<usize, Fr>,
     undisclosed_terms: &mut BTreeMap<usize, Fr>,
     equivs: &mut HashMap<NamedOrBlankNode, Vec<(usize, usize)>>,
+    term_encoding: TermEncoding,
     hasher: &BBSPlusDefaultFieldHasher,
 ) -> Result<(), RDFProofsError> {
-    let predicate_index = subject_index + 1;
-    let object_index = subject_index + 2;
+    let predicate_index = subject_index + term_encoding.messages_per_term();
+    let object_index = subject_index + 2 * term_encoding.messages_per_term();
 
-    let subject_fr = hash_term_to_field((&original.subject).into(), hasher)?;
-    let predicate_fr = hash_term_to_field((&original.predicate).into(), hasher)?;
-    let object_fr = hash_term_to_field((&original.object).into(), hasher)?;
+    let subject_fr =
+        hash_term_to_field_with_encoding((&original.subject).into(), term_encoding, hasher)?;
+    let predicate_fr =
+        hash_term_to_field_with_encoding((&original.predicate).into(), term_encoding, hasher)?;
+    let object_fr =
+        hash_term_to_field_with_encoding((&original.object).into(), term_encoding, hasher)?;
 
     match disclosed_triple {
         Some(triple) => {
@@ -1443,27 +3050,90 @@ fn build_disclosed_and_undisclosed_terms(
             undisclosed_terms.insert(object_index, object_fr);
         }
     };
+
+    // namespace following each term is hidden along with the term
+    if term_encoding == TermEncoding::SplitIri {
+        for (index, namespace_fr) in [
+            (
+                subject_index,
+                hash_namespace_to_field((&original.subject).into(), hasher)?,
+            ),
+            (
+                predicate_index,
+                hash_namespace_to_field((&original.predicate).into(), hasher)?,
+            ),
+            (
+                object_index,
+                hash_namespace_to_field((&original.object).into(), hasher)?,
+            ),
+        ] {
+            if undisclosed_terms.contains_key(&index) {
+                undisclosed_terms.insert(index + 1, namespace_fr);
+            } else {
+                disclosed_terms.insert(index + 1, namespace_fr);
+            }
+        }
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::CircuitString;
+    use super::{get_bnode_label_seed, map_triple_positions, CircuitString, PROOF_CONTEXT_VERSION};
     use crate::{
-        ark_to_base64url, blind_sign_string, blind_verify_string,
-        common::{get_dataset_from_nquads, get_graph_from_ntriples, R1CS},
-        derive_proof,
-        derive_proof::get_deanon_map_from_string,
-        derive_proof_string, elliptic_elgamal_keygen,
+        ark_to_base64url, attach_proof_value_string, blind_sign_string, blind_verify_string,
+        combine_decrypted_shares, commit_to_term_string,
+        common::{
+            get_dataset_from_nquads, get_graph_from_ntriples, get_vc_from_ntriples,
+            hash_str_to_str, BlankNodeLabeler, ProofWithIndexMap, VerifyingKey, R1CS,
+        },
+        context::{CREATED, ENCRYPTED_UID, PROOF, PROOF_VALUE, VERIFICATION_METHOD},
+        curve::Curve,
+        derive_classic_vp_string, derive_proof,
+        derive_proof::{get_deanon_map_from_string, PlannedStatement},
+        derive_proof_dry_run, derive_proof_string, derive_proof_to_writer,
+        detach_proof_value_string, device_binding_challenge_string, device_key_handle_string,
+        elliptic_elgamal_decrypt, elliptic_elgamal_keygen,
         error::RDFProofsError,
-        request_blind_sign_string, unblind_string, verify_blind_sign_request_string, verify_proof,
-        verify_proof_string, KeyGraph, VcPair, VcPairString, VerifiableCredential,
+        export_key_graph_for_vp_string, get_encrypted_attribute_string, get_encrypted_uid,
+        issue_nonce_token_string,
+        key_gen::{
+            encode_public_key, encode_secret_key, generate_keypair, generate_params, KeyEncoding,
+        },
+        migrate::{decode_proof_value, encode_proof_value},
+        migrate_vp_string, multibase_to_ark, not_equal_request_string,
+        onchain::{
+            export_onchain_verifying_key_string, extract_onchain_predicate_proofs,
+            extract_predicate_snark_proofs,
+        },
+        request_blind_sign_string, sign, sign_string, unblind_string,
+        vc::VerifiablePresentation,
+        verify_blind_sign_request_string, verify_bundle, verify_proof, verify_proof_string,
+        verify_secret_rotation_string, verify_string, vp_credential_summary_string,
+        vp_digest_string, vp_equivalence_classes_string, vp_metadata, vp_metadata_string,
+        AttributeEncryptionRequestString, BlankNodeLabeling, BundledVerifyingKey,
+        CancellationToken, CommitmentEqualityRequestString, CredentialSecrets, CredentialSummary,
+        DeriveOptions, DeriveOptionsString, DeviceAttestation, DeviceAttestationVerifier,
+        DeviceBindingString, DeviceSigner, ElGamalCiphertext, ExtensionPolicy, FixedClock,
+        HiddenPredicates, HolderBindingPolicy, InMemoryReplayGuard, InMemoryVerificationCache,
+        KeyGraph, Limits, Metrics, NonceTokenValidation, PolicyDigest, PredicateGraph,
+        PredicateOutput, Progress, ProofExtension, ProofPhase, SharedKeyGraph, StrictDisclosure,
+        VariableRef, VcPair, VcPairString, VerifiableCredential, VerificationBundle,
+        VerificationCache, VerificationResult, VerificationWarning, VerifyOptions,
+        VerifyOptionsString, WarningPolicy,
     };
+    use ark_ec::pairing::Pairing;
     use ark_std::rand::{rngs::StdRng, SeedableRng};
     use legogroth16::circom::CircomCircuit;
     use multibase::Base;
-    use oxrdf::{NamedOrBlankNode, Term};
-    use std::collections::HashMap;
+    use oxrdf::{
+        vocab::xsd, BlankNode, Dataset, Literal, NamedNode, NamedOrBlankNode, Term, TermRef, Triple,
+    };
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        sync::Mutex,
+        time::Duration,
+    };
 
     const KEY_GRAPH: &str = r#"
         # issuer0
@@ -1597,23 +3267,49 @@ mod tests {
         _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
         _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer3#bls12_381-g2-pub001> .
         "#;
-    const DEANON_MAP: [(&str, &str); 4] = [
-        ("_:e0", "<did:example:john>"),
-        ("_:e1", "<http://example.org/vaccine/a>"),
-        ("_:e2", "<http://example.org/vcred/00>"),
-        ("_:e3", "<http://example.org/vicred/a>"),
-    ];
-    fn get_example_deanon_map_string() -> HashMap<String, String> {
-        DEANON_MAP
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect()
-    }
-    fn get_example_deanon_map() -> HashMap<NamedOrBlankNode, Term> {
-        get_deanon_map_from_string(&get_example_deanon_map_string()).unwrap()
-    }
-    const VP: &str = r#"
-        _:c14n1 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> _:c14n10 .
+    const DISCLOSED_VC_2_WITH_NAME: &str = r#"
+        _:e1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccine> .
+        _:e1 <http://schema.org/name> "AwesomeVaccine" .
+        _:e1 <http://schema.org/status> "active" .
+        _:e3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+        _:e3 <https://www.w3.org/2018/credentials#credentialSubject> _:e1 .
+        _:e3 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer3> .
+        _:e3 <https://www.w3.org/2018/credentials#issuanceDate> "2020-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:e3 <https://www.w3.org/2018/credentials#expirationDate> "2023-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE_2_WITH_PARAMS_PROFILE: &str = r#"
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+        _:b0 <http://purl.org/dc/terms/created> "2023-02-03T09:49:25Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer3#bls12_381-g2-pub001> .
+        _:b0 <https://zkp-ld.org/security#paramsProfile> "example-profile" .
+        "#;
+    const DISCLOSED_VC_PROOF_2_WITH_PARAMS_PROFILE: &str = r#"
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+        _:b0 <http://purl.org/dc/terms/created> "2023-02-03T09:49:25Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer3#bls12_381-g2-pub001> .
+        _:b0 <https://zkp-ld.org/security#paramsProfile> "example-profile" .
+        "#;
+    const DEANON_MAP: [(&str, &str); 4] = [
+        ("_:e0", "<did:example:john>"),
+        ("_:e1", "<http://example.org/vaccine/a>"),
+        ("_:e2", "<http://example.org/vcred/00>"),
+        ("_:e3", "<http://example.org/vicred/a>"),
+    ];
+    fn get_example_deanon_map_string() -> HashMap<String, String> {
+        DEANON_MAP
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+    fn get_example_deanon_map() -> HashMap<NamedOrBlankNode, Term> {
+        get_deanon_map_from_string(&get_example_deanon_map_string()).unwrap()
+    }
+    const VP: &str = r#"
+        _:c14n1 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> _:c14n10 .
         _:c14n1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> _:c14n10 .
         _:c14n1 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" _:c14n10 .
         _:c14n1 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> _:c14n10 .
@@ -1691,12 +3387,7 @@ mod tests {
             &key_graph,
             Some(challenge),
             None,
-            None,
-            None,
-            None,
-            vec![],
-            HashMap::new(),
-            None,
+            DeriveOptions::default(),
         )
         .unwrap();
         println!("derived_proof.vp: {}", rdf_canon::serialize(&derived_proof));
@@ -1707,23 +3398,79 @@ mod tests {
             &key_graph,
             Some(challenge),
             None,
-            HashMap::new(),
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // verify with the key graph shared among verifiers
+        let shared_key_graph = SharedKeyGraph::from(key_graph);
+        let verified = verify_proof(
+            &mut rng,
+            &derived_proof,
+            &shared_key_graph,
+            Some(challenge),
             None,
+            VerifyOptions::default(),
         );
         assert!(verified.is_ok(), "{:?}", verified)
     }
 
     #[test]
-    fn derive_and_verify_proof_string_success() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+    fn derive_proof_to_writer_and_verify() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let vcs = vec![
+            VcPair::new(
+                get_vc_from_ntriples(VC_1, VC_PROOF_1).unwrap(),
+                get_vc_from_ntriples(DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1).unwrap(),
+            ),
+            VcPair::new(
+                get_vc_from_ntriples(VC_2, VC_PROOF_2).unwrap(),
+                get_vc_from_ntriples(DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2).unwrap(),
+            ),
+        ];
+        let deanon_map = get_example_deanon_map();
+        let challenge = "abcde";
+
+        let mut writer = vec![];
+        derive_proof_to_writer(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            Some(challenge),
+            None,
+            DeriveOptions::default(),
+            &mut writer,
+        )
+        .unwrap();
+        let derived_proof = String::from_utf8(writer).unwrap();
+
+        // proof value comes last
+        let last_quad = derived_proof.lines().last().unwrap();
+        assert!(last_quad.contains(PROOF_VALUE.as_str()));
+
+        let verified = verify_proof(
+            &mut rng,
+            &get_dataset_from_nquads(&derived_proof).unwrap(),
+            &key_graph,
+            Some(challenge),
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_verification_cache() {
+        let mut rng = StdRng::seed_from_u64(0u64);
 
         let vc_pairs = vec![
             VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
             VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
         ];
-
         let deanon_map = get_example_deanon_map_string();
-
         let challenge = "abcde";
 
         let derived_proof = derive_proof_string(
@@ -1733,336 +3480,520 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString::default(),
         )
         .unwrap();
-        println!("derived_proof: {}", derived_proof);
 
-        let verified = verify_proof_string(
+        let cache = InMemoryVerificationCache::new(Duration::from_secs(60), 16);
+        let verify_with_cache = |rng: &mut StdRng, challenge| {
+            verify_proof_string(
+                rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    verification_cache: Some(&cache),
+                    ..Default::default()
+                },
+            )
+        };
+        let verified = verify_with_cache(&mut rng, challenge).unwrap();
+
+        // identical VP is answered from cache
+        assert_eq!(verify_with_cache(&mut rng, challenge).unwrap(), verified);
+
+        // request parameters are still validated
+        assert!(matches!(
+            verify_with_cache(&mut rng, "fghij"),
+            Err(RDFProofsError::MismatchedChallenge)
+        ));
+
+        // cache keys differ with verifier's inputs
+        struct KeyRecordingCache(Mutex<Vec<String>>);
+        impl VerificationCache for KeyRecordingCache {
+            fn get(&self, _key: &str) -> Option<VerificationResult> {
+                None
+            }
+            fn insert(&self, key: &str, _result: &VerificationResult) {
+                self.0.lock().unwrap().push(key.to_string());
+            }
+        }
+        let recording_cache = KeyRecordingCache(Mutex::new(vec![]));
+        for ignore_non_critical_extensions in [false, true] {
+            let verified = verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    verification_cache: Some(&recording_cache),
+                    ignore_non_critical_extensions: Some(ignore_non_critical_extensions),
+                    ..Default::default()
+                },
+            );
+            assert!(verified.is_ok(), "{:?}", verified);
+        }
+        let keys = recording_cache.0.into_inner().unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_ne!(keys[0], keys[1]);
+
+        // cache is not consulted before the checks independent of the proof
+        struct ForgedCache;
+        impl VerificationCache for ForgedCache {
+            fn get(&self, _key: &str) -> Option<VerificationResult> {
+                Some(VerificationResult::default())
+            }
+            fn insert(&self, _key: &str, _result: &VerificationResult) {}
+        }
+        let verification_method =
+            NamedNode::new_unchecked("did:example:issuer0#bls12_381-g2-pub001");
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    verification_cache: Some(&ForgedCache),
+                    min_distinct_credentials: Some(&HashMap::from([(
+                        verification_method.as_str().to_string(),
+                        2
+                    )])),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::InsufficientDistinctCredentials(_))
+        ));
+        let restricted_key_graph = format!(
+            "{}<did:example:issuer0#bls12_381-g2-pub001> <https://zkp-ld.org/security#allowedCredentialType> <http://example.org/vocab/VaccinationCredential> .\n",
+            KEY_GRAPH
+        );
+        assert!(verify_proof_string(
             &mut rng,
             &derived_proof,
-            KEY_GRAPH,
+            &restricted_key_graph,
             Some(challenge),
             None,
-            None,
-            None,
-        );
-        assert!(verified.is_ok(), "{:?}", verified)
+            VerifyOptionsString {
+                verification_cache: Some(&ForgedCache),
+                ..Default::default()
+            },
+        )
+        .is_err());
     }
 
     #[test]
-    fn verify_proof_success() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+    fn derive_and_verify_proof_with_precomputed_canonicalization() {
+        let mut rng = StdRng::seed_from_u64(0u64);
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
-        let vp = get_dataset_from_nquads(VP).unwrap();
-        let challenge = "abcde";
+
+        let vcs = vec![
+            VcPair::new(
+                get_vc_from_ntriples(VC_1, VC_PROOF_1).unwrap(),
+                get_vc_from_ntriples(DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1).unwrap(),
+            ),
+            VcPair::new(
+                get_vc_from_ntriples(VC_2, VC_PROOF_2).unwrap(),
+                get_vc_from_ntriples(DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2).unwrap(),
+            ),
+        ];
+        let deanon_map = get_example_deanon_map();
+
+        // canonicalization of the first VC is precomputed by its publisher
+        let precomputed = PrecomputedCanonicalization::new_string(VC_1).unwrap();
+
+        let derive_with_precomputed = |rng: &mut StdRng, precomputed| {
+            derive_proof(
+                rng,
+                &vcs,
+                &deanon_map,
+                &key_graph,
+                None,
+                None,
+                DeriveOptions {
+                    precomputed_canonicalizations: vec![Some(precomputed), None],
+                    ..Default::default()
+                },
+            )
+        };
+
+        let derived_proof = derive_with_precomputed(&mut rng, precomputed.clone()).unwrap();
         let verified = verify_proof(
             &mut rng,
-            &vp,
+            &derived_proof,
             &key_graph,
-            Some(challenge),
             None,
-            HashMap::new(),
             None,
+            VerifyOptions::default(),
         );
-        assert!(verified.is_ok(), "{:?}", verified)
-    }
+        assert!(verified.is_ok(), "{:?}", verified);
 
-    #[test]
-    fn verify_proof_string_success() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
-        let challenge = "abcde";
-        let verified =
-            verify_proof_string(&mut rng, VP, KEY_GRAPH, Some(challenge), None, None, None);
-        assert!(verified.is_ok(), "{:?}", verified)
+        // precomputed digest not matching the VC
+        let mut tampered = precomputed;
+        tampered.digest = PrecomputedCanonicalization::new_string(VC_2)
+            .unwrap()
+            .digest;
+        assert!(matches!(
+            derive_with_precomputed(&mut rng, tampered),
+            Err(RDFProofsError::InvalidPrecomputedCanonicalization(_))
+        ));
     }
 
     #[test]
-    fn derive_and_verify_proof_with_challenge_and_domain() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+    fn derive_and_verify_proof_with_deterministic_bnode_labels() {
+        let mut rng = StdRng::seed_from_u64(0u64);
         let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
 
-        let vc_doc_1 = get_graph_from_ntriples(VC_1).unwrap();
-        let vc_proof_1 = get_graph_from_ntriples(VC_PROOF_1).unwrap();
-        let vc_1 = VerifiableCredential::new(vc_doc_1, vc_proof_1);
-
-        let disclosed_vc_doc_1 = get_graph_from_ntriples(DISCLOSED_VC_1).unwrap();
-        let disclosed_vc_proof_1 = get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap();
-        let disclosed_1 = VerifiableCredential::new(disclosed_vc_doc_1, disclosed_vc_proof_1);
-
-        let vc_doc_2 = get_graph_from_ntriples(VC_2).unwrap();
-        let vc_proof_2 = get_graph_from_ntriples(VC_PROOF_2).unwrap();
-        let vc_2 = VerifiableCredential::new(vc_doc_2, vc_proof_2);
+        let vc_1 = VerifiableCredential::new(
+            get_graph_from_ntriples(VC_1).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+        );
+        let disclosed_1 = VerifiableCredential::new(
+            get_graph_from_ntriples(DISCLOSED_VC_1).unwrap(),
+            get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+        );
+        let vc_2 = VerifiableCredential::new(
+            get_graph_from_ntriples(VC_2).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_2).unwrap(),
+        );
+        let disclosed_2 = VerifiableCredential::new(
+            get_graph_from_ntriples(DISCLOSED_VC_2).unwrap(),
+            get_graph_from_ntriples(DISCLOSED_VC_PROOF_2).unwrap(),
+        );
+        let vcs = vec![
+            VcPair::new(vc_1.clone(), disclosed_1),
+            VcPair::new(vc_2, disclosed_2),
+        ];
 
-        let disclosed_vc_doc_2 = get_graph_from_ntriples(DISCLOSED_VC_2).unwrap();
-        let disclosed_vc_proof_2 = get_graph_from_ntriples(DISCLOSED_VC_PROOF_2).unwrap();
-        let disclosed_2 = VerifiableCredential::new(disclosed_vc_doc_2, disclosed_vc_proof_2);
+        // the same labels for the same credentials and disclosures
+        let seed = get_bnode_label_seed(&vcs).unwrap();
+        assert_eq!(seed, get_bnode_label_seed(&vcs).unwrap());
+        let mut labeler_1 = BlankNodeLabeler::deterministic(&seed);
+        let mut labeler_2 = BlankNodeLabeler::deterministic(&seed);
+        let labels_1 = (0..5).map(|_| labeler_1.generate()).collect::<Vec<_>>();
+        let labels_2 = (0..5).map(|_| labeler_2.generate()).collect::<Vec<_>>();
+        assert_eq!(labels_1, labels_2);
+        assert_eq!(labels_1.iter().collect::<HashSet<_>>().len(), 5);
 
-        let vc_with_disclosed_1 = VcPair::new(vc_1, disclosed_1);
-        let vc_with_disclosed_2 = VcPair::new(vc_2, disclosed_2);
-        let vcs = vec![vc_with_disclosed_1, vc_with_disclosed_2];
+        // different labels for different disclosures
+        let other_vcs = vec![VcPair::new(vc_1.clone(), vc_1)];
+        assert_ne!(seed, get_bnode_label_seed(&other_vcs).unwrap());
 
         let deanon_map = get_example_deanon_map();
-
-        let challenge = Some("challenge");
-        let domain = Some("example.org");
-
         let derived_proof = derive_proof(
             &mut rng,
             &vcs,
             &deanon_map,
             &key_graph,
-            challenge,
-            domain,
-            None,
-            None,
-            None,
-            vec![],
-            HashMap::new(),
+            Some("abcde"),
             None,
+            DeriveOptions {
+                bnode_labeling: BlankNodeLabeling::Deterministic,
+                ..Default::default()
+            },
         )
         .unwrap();
-        assert!(verify_proof(
+
+        let verified = verify_proof(
             &mut rng,
             &derived_proof,
             &key_graph,
-            challenge,
-            domain,
-            HashMap::new(),
-            None
-        )
-        .is_ok());
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                None,
-                domain,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInRequest)
-        ));
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                challenge,
-                None,
-                HashMap::new(),
-                None,
+            Some("abcde"),
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_redacted_proof_configs() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let vcs = vec![
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_1).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(DISCLOSED_VC_1).unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+                ),
             ),
-            Err(RDFProofsError::MissingDomainInRequest)
-        ));
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                None,
-                None,
-                HashMap::new(),
-                None
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_2).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_2).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(DISCLOSED_VC_2).unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_2).unwrap(),
+                ),
             ),
-            Err(RDFProofsError::MissingChallengeInRequest)
-        ));
+        ];
+        let deanon_map = get_example_deanon_map();
+        let challenge = "abcde";
 
+        // `created` of VCs is hidden by default
         let derived_proof = derive_proof(
             &mut rng,
             &vcs,
             &deanon_map,
             &key_graph,
+            Some(challenge),
             None,
-            domain,
-            None,
-            None,
-            None,
-            vec![],
-            HashMap::new(),
-            None,
+            DeriveOptions::default(),
         )
         .unwrap();
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                challenge,
-                domain,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInVP)
-        ));
-        assert!(verify_proof(
+        println!("derived_proof.vp: {}", rdf_canon::serialize(&derived_proof));
+        let metadata = vp_metadata(&derived_proof).unwrap();
+        assert!(metadata.credentials.iter().all(|vc| vc.created.is_none()));
+        assert!(metadata
+            .credentials
+            .iter()
+            .all(|vc| vc.verification_method.is_some()));
+        let verified = verify_proof(
             &mut rng,
             &derived_proof,
             &key_graph,
+            Some(challenge),
             None,
-            domain,
-            HashMap::new(),
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // disclose `created` by giving no proof config to be redacted
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            Some(challenge),
             None,
+            DeriveOptions {
+                redacted_proof_configs: Some(vec![]),
+                ..Default::default()
+            },
         )
-        .is_ok());
+        .unwrap();
+        let metadata = vp_metadata(&derived_proof).unwrap();
+        assert!(metadata.credentials.iter().all(|vc| vc.created.is_some()));
+        let verified = verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            Some(challenge),
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // verification method is required by verifier
         assert!(matches!(
-            verify_proof(
+            derive_proof(
                 &mut rng,
-                &derived_proof,
+                &vcs,
+                &deanon_map,
                 &key_graph,
-                challenge,
-                None,
-                HashMap::new(),
+                Some(challenge),
                 None,
+                DeriveOptions {
+                    redacted_proof_configs: Some(vec![VERIFICATION_METHOD.into_owned()]),
+                    ..Default::default()
+                },
             ),
-            Err(RDFProofsError::MissingChallengeInVP)
+            Err(RDFProofsError::NonRedactableProofConfig(_))
         ));
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                None,
-                None,
-                HashMap::new(),
-                None,
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_different_params_profiles() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        // VC 1 is signed with the shared params, whereas VC 2 is signed with issuer's own profile
+        let mut vc_2 = VerifiableCredential::new(
+            get_graph_from_ntriples(VC_2).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_2_WITH_PARAMS_PROFILE).unwrap(),
+        );
+        sign(&mut rng, &mut vc_2, &key_graph, None, None).unwrap();
+
+        let vcs = vec![
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_1).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(DISCLOSED_VC_1).unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+                ),
             ),
-            Err(RDFProofsError::MissingDomainInRequest)
-        ));
+            VcPair::new(
+                vc_2,
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(DISCLOSED_VC_2).unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_2_WITH_PARAMS_PROFILE).unwrap(),
+                ),
+            ),
+        ];
+        let deanon_map = get_example_deanon_map();
+        let challenge = "abcde";
 
         let derived_proof = derive_proof(
             &mut rng,
             &vcs,
             &deanon_map,
             &key_graph,
-            challenge,
-            None,
-            None,
-            None,
-            None,
-            vec![],
-            HashMap::new(),
+            Some(challenge),
             None,
+            DeriveOptions::default(),
         )
         .unwrap();
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                challenge,
-                domain,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingDomainInVP)
-        ));
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                None,
-                domain,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInRequest)
-        ));
-        assert!(verify_proof(
+        println!("derived_proof.vp: {}", rdf_canon::serialize(&derived_proof));
+
+        let verified = verify_proof(
             &mut rng,
             &derived_proof,
             &key_graph,
-            challenge,
-            None,
-            HashMap::new(),
+            Some(challenge),
             None,
-        )
-        .is_ok());
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                None,
-                None,
-                HashMap::new(),
-                None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_hidden_predicates() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        // hide `schema:name` in both VCs with the same nym IRI
+        let mut hidden_predicates = HiddenPredicates::new();
+        let nym = hidden_predicates.hide(NamedNode::new_unchecked("http://schema.org/name"));
+
+        let vcs = vec![
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_1).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    hidden_predicates.apply(&get_graph_from_ntriples(DISCLOSED_VC_1).unwrap()),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+                ),
             ),
-            Err(RDFProofsError::MissingChallengeInRequest)
-        ));
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_2).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_2).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    hidden_predicates
+                        .apply(&get_graph_from_ntriples(DISCLOSED_VC_2_WITH_NAME).unwrap()),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_2).unwrap(),
+                ),
+            ),
+        ];
+        let mut deanon_map = get_example_deanon_map();
+        deanon_map.extend(hidden_predicates.deanon_map());
+        let challenge = "abcde";
 
         let derived_proof = derive_proof(
             &mut rng,
             &vcs,
             &deanon_map,
             &key_graph,
+            Some(challenge),
             None,
-            None,
-            None,
-            None,
-            None,
-            vec![],
-            HashMap::new(),
-            None,
+            DeriveOptions::default(),
         )
         .unwrap();
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                challenge,
-                domain,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInVP)
-        ));
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                None,
-                domain,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingDomainInVP)
-        ));
-        assert!(matches!(
-            verify_proof(
-                &mut rng,
-                &derived_proof,
-                &key_graph,
-                challenge,
-                None,
-                HashMap::new(),
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInVP)
-        ));
-        assert!(verify_proof(
+        let vp = rdf_canon::serialize(&derived_proof);
+        println!("derived_proof.vp: {}", vp);
+        assert!(!vp.contains("<http://schema.org/name>"));
+
+        let verified = verify_proof(
             &mut rng,
             &derived_proof,
             &key_graph,
+            Some(challenge),
             None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // the hidden predicates are proven equal across the two VCs
+        let classes = vp_equivalence_classes_string(&vp).unwrap();
+        let nym_class = classes
+            .iter()
+            .find(|c| c.term == Term::NamedNode(nym.clone()))
+            .unwrap();
+        assert_eq!(nym_class.occurrences.len(), 2);
+    }
+
+    #[test]
+    fn derive_proof_dry_run_success() {
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let vcs = vec![
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_1).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(DISCLOSED_VC_1).unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+                ),
+            ),
+            VcPair::new(
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(VC_2).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_2).unwrap(),
+                ),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(DISCLOSED_VC_2).unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_2).unwrap(),
+                ),
+            ),
+        ];
+        let deanon_map = get_example_deanon_map();
+
+        let plan = derive_proof_dry_run(
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            Some("abcde"),
             None,
-            HashMap::new(),
-            None,
+            &DeriveOptions::default(),
         )
-        .is_ok());
+        .unwrap();
+        println!("plan: {:#?}", plan);
+
+        assert_eq!(plan.statements.len(), 2);
+        assert!(plan
+            .statements
+            .iter()
+            .all(|s| matches!(s, PlannedStatement::BBSPlusSignature { .. })));
+        // hidden terms shared between the two VCs must be proven equal
+        assert!(plan
+            .witness_equalities
+            .iter()
+            .any(|equality| equality.iter().any(|(i, _)| *i == 0)
+                && equality.iter().any(|(i, _)| *i == 1)));
+
+        // PPID requires both secret and domain
+        assert!(matches!(
+            HolderBindingPolicy::new(None, true, None, vec![]),
+            Err(RDFProofsError::MissingSecretOrDomain)
+        ));
     }
 
     #[test]
-    fn derive_and_verify_proof_string_with_challenge_and_domain() {
+    fn derive_and_verify_proof_string_success() {
         let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
 
         let vc_pairs = vec![
@@ -2072,233 +4003,2408 @@ mod tests {
 
         let deanon_map = get_example_deanon_map_string();
 
-        let challenge = Some("challenge");
-        let domain = Some("example.org");
+        let challenge = "abcde";
 
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
             KEY_GRAPH,
-            challenge,
-            domain,
-            None,
-            None,
-            None,
-            None,
-            None,
+            Some(challenge),
             None,
+            DeriveOptionsString::default(),
         )
         .unwrap();
-        assert!(verify_proof_string(
+        println!("derived_proof: {}", derived_proof);
+
+        let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
             KEY_GRAPH,
-            challenge,
-            domain,
+            Some(challenge),
             None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_possession_only() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let challenge = "abcde";
+
+        let derive_and_verify =
+            |rng: &mut StdRng,
+             vc_pairs: &Vec<VcPairString>,
+             deanon_map: &HashMap<String, String>| {
+                let derived_proof = derive_proof_string(
+                    rng,
+                    vc_pairs,
+                    deanon_map,
+                    KEY_GRAPH,
+                    Some(challenge),
+                    None,
+                    DeriveOptionsString::default(),
+                )?;
+                verify_proof_string(
+                    rng,
+                    &derived_proof,
+                    KEY_GRAPH,
+                    Some(challenge),
+                    None,
+                    VerifyOptionsString::default(),
+                )
+            };
+
+        // fully hidden document along with partially disclosed one
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::possession_only(VC_2, VC_PROOF_2),
+        ];
+        let verified = derive_and_verify(&mut rng, &vc_pairs, &get_example_deanon_map_string());
+        assert!(verified.is_ok(), "{:?}", verified);
+        // fully hidden document has nothing but the link to its proof
+        let disclosed_documents = verified.unwrap().disclosed_documents;
+        assert_eq!(disclosed_documents.len(), 2);
+        assert_eq!(
+            disclosed_documents
+                .iter()
+                .filter(|document| document.iter().all(|t| t.predicate == PROOF))
+                .count(),
+            1
+        );
+
+        // only fully hidden documents without deanonymization map
+        let vc_pairs = vec![
+            VcPairString::possession_only(VC_1, VC_PROOF_1),
+            VcPairString::possession_only(VC_2, VC_PROOF_2),
+        ];
+        let verified = derive_and_verify(&mut rng, &vc_pairs, &HashMap::new());
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn detach_and_attach_proof_value() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let challenge = "abcde";
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &get_example_deanon_map_string(),
+            KEY_GRAPH,
+            Some(challenge),
             None,
+            DeriveOptionsString::default(),
         )
-        .is_ok());
+        .unwrap();
+
+        let (detached, proof_value) = detach_proof_value_string(&derived_proof).unwrap();
+        assert!(!detached.contains(PROOF_VALUE.as_str()));
         assert!(matches!(
-            verify_proof_string(
-                &mut rng,
-                &derived_proof,
-                KEY_GRAPH,
-                None,
-                domain,
-                None,
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInRequest)
+            detach_proof_value_string(&detached),
+            Err(RDFProofsError::VCWithoutProofValue)
         ));
+
+        // re-attached VP is verified as the original one
+        let attached = attach_proof_value_string(&detached, &proof_value).unwrap();
+        let verified = verify_proof_string(
+            &mut rng,
+            &attached,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            verified.vp_digest,
+            vp_digest_string(&derived_proof).unwrap()
+        );
+        assert_eq!(
+            hash_str_to_str(&verified.canonical_vp),
+            proof_value.vp_digest
+        );
+
+        // VP already with proof value
         assert!(matches!(
-            verify_proof_string(
-                &mut rng,
-                &derived_proof,
-                KEY_GRAPH,
-                challenge,
-                None,
-                None,
-                None,
-            ),
-            Err(RDFProofsError::MissingDomainInRequest)
+            attach_proof_value_string(&attached, &proof_value),
+            Err(RDFProofsError::ProofValueAttachment(_))
         ));
+
+        // VP modified after detaching proof value
+        let tampered = detached.replace("\"abcde\"", "\"abcdf\"");
+        assert_ne!(tampered, detached);
         assert!(matches!(
-            verify_proof_string(&mut rng, &derived_proof, KEY_GRAPH, None, None, None, None,),
-            Err(RDFProofsError::MissingChallengeInRequest)
+            attach_proof_value_string(&tampered, &proof_value),
+            Err(RDFProofsError::ProofValueAttachment(_))
         ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_protocol() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
 
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
             KEY_GRAPH,
+            Some(challenge),
             None,
-            domain,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString {
+                protocol: Some("https://example.org/protocols/app-a"),
+                ..Default::default()
+            },
         )
         .unwrap();
-        assert!(matches!(
+
+        let mut verify_with_protocol = |protocol| {
             verify_proof_string(
                 &mut rng,
                 &derived_proof,
                 KEY_GRAPH,
-                challenge,
-                domain,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    protocol,
+                    ..Default::default()
+                },
+            )
+        };
+        let verified = verify_with_protocol(Some("https://example.org/protocols/app-a"));
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // proof derived for a protocol cannot be replayed into another protocol
+        assert!(verify_with_protocol(Some("https://example.org/protocols/app-b")).is_err());
+        assert!(verify_with_protocol(None).is_err());
+    }
+
+    #[test]
+    fn derive_proof_string_with_strict_disclosure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let strict_disclosure = StrictDisclosure::new();
+
+        let mut derive = |created_precision: Option<&str>| {
+            derive_proof_string(
+                &mut rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
                 None,
                 None,
-            ),
-            Err(RDFProofsError::MissingChallengeInVP)
-        ));
-        assert!(verify_proof_string(
-            &mut rng,
-            &derived_proof,
-            KEY_GRAPH,
-            None,
-            domain,
-            None,
-            None,
-        )
-        .is_ok());
+                DeriveOptionsString {
+                    created_precision,
+                    strict_disclosure: Some(&strict_disclosure),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // `created` of VP proof is precise to the second
         assert!(matches!(
-            verify_proof_string(
+            derive(None),
+            Err(RDFProofsError::StrictDisclosureViolation(_))
+        ));
+        let derived_proof = derive(Some("day"));
+        assert!(derived_proof.is_ok(), "{:?}", derived_proof);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_truncated_created() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        for (precision, datatype) in [("day", xsd::DATE), ("month", xsd::G_YEAR_MONTH)] {
+            let derived_proof = derive_proof_string(
+                &mut rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                None,
+                None,
+                DeriveOptionsString {
+                    created_precision: Some(precision),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // `created` of VP proof is truncated to the given precision
+            let vp = get_dataset_from_nquads(&derived_proof).unwrap();
+            let created = vp
+                .iter()
+                .find(|q| q.predicate == CREATED && !q.graph_name.is_default_graph())
+                .map(|q| q.object.into_owned());
+            assert!(
+                matches!(created, Some(Term::Literal(ref v)) if v.datatype() == datatype),
+                "{:?}",
+                created
+            );
+
+            let verified = verify_proof_string(
                 &mut rng,
                 &derived_proof,
                 KEY_GRAPH,
-                challenge,
                 None,
                 None,
+                VerifyOptionsString::default(),
+            );
+            assert!(verified.is_ok(), "{:?}", verified);
+        }
+
+        assert!(matches!(
+            derive_proof_string(
+                &mut rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                None,
                 None,
+                DeriveOptionsString {
+                    created_precision: Some("week"),
+                    ..Default::default()
+                },
             ),
-            Err(RDFProofsError::MissingChallengeInVP)
-        ));
-        assert!(matches!(
-            verify_proof_string(&mut rng, &derived_proof, KEY_GRAPH, None, None, None, None,),
-            Err(RDFProofsError::MissingDomainInRequest)
+            Err(RDFProofsError::InvalidCreatedPrecision(_))
         ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_fixed_clock() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let clock = FixedClock::new("2024-02-03T04:05:06Z".parse().unwrap());
 
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
             KEY_GRAPH,
-            challenge,
-            None,
-            None,
-            None,
-            None,
-            None,
             None,
             None,
+            DeriveOptionsString {
+                created_precision: Some("day"),
+                clock: Some(&clock),
+                ..Default::default()
+            },
         )
         .unwrap();
-        assert!(matches!(
-            verify_proof_string(
-                &mut rng,
-                &derived_proof,
-                KEY_GRAPH,
-                challenge,
-                domain,
-                None,
-                None
-            ),
-            Err(RDFProofsError::MissingDomainInVP)
-        ));
-        assert!(matches!(
-            verify_proof_string(
-                &mut rng,
-                &derived_proof,
-                KEY_GRAPH,
-                None,
-                domain,
-                None,
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInRequest)
-        ));
-        assert!(verify_proof_string(
+
+        // `created` of VP proof is taken from the given clock
+        assert!(derived_proof.contains("\"2024-02-03\"^^<http://www.w3.org/2001/XMLSchema#date>"));
+
+        let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
             KEY_GRAPH,
-            challenge,
-            None,
             None,
             None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_nonce_token() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let verifier_key = "did:example:issuer3#bls12_381-g2-pub001";
+        let audience = "https://verifier.example.org";
+        let issued_at = FixedClock::new("2024-02-03T04:05:06Z".parse().unwrap());
+
+        // verifier issues token to be used as challenge
+        let nonce_token = issue_nonce_token_string(
+            &mut rng,
+            KEY_GRAPH,
+            verifier_key,
+            Some(audience),
+            "2024-02-03T04:10:06Z",
+            Some(&issued_at),
         )
-        .is_ok());
-        assert!(matches!(
-            verify_proof_string(&mut rng, &derived_proof, KEY_GRAPH, None, None, None, None,),
-            Err(RDFProofsError::MissingChallengeInRequest)
-        ));
+        .unwrap();
 
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
             KEY_GRAPH,
+            Some(&nonce_token),
             None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString::default(),
         )
         .unwrap();
-        assert!(matches!(
+
+        let verify = |rng: &mut StdRng, validation: &NonceTokenValidation| {
             verify_proof_string(
-                &mut rng,
+                rng,
                 &derived_proof,
                 KEY_GRAPH,
-                challenge,
-                domain,
-                None,
+                Some(&nonce_token),
                 None,
-            ),
-            Err(RDFProofsError::MissingChallengeInVP)
-        ));
+                VerifyOptionsString {
+                    nonce_token: Some(validation),
+                    ..Default::default()
+                },
+            )
+        };
+
+        let mut validation =
+            NonceTokenValidation::new_string(verifier_key, Some(audience)).unwrap();
+        validation.set_clock(Box::new(FixedClock::new(
+            "2024-02-03T04:06:06Z".parse().unwrap(),
+        )));
+        let verified = verify(&mut rng, &validation);
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // expired token
+        validation.set_clock(Box::new(FixedClock::new(
+            "2024-02-03T04:10:06Z".parse().unwrap(),
+        )));
+        let verified = verify(&mut rng, &validation);
+        assert!(matches!(verified, Err(RDFProofsError::ExpiredNonceToken)));
+
+        // token addressed to another verifier
+        let validation =
+            NonceTokenValidation::new_string(verifier_key, Some("https://other.example.org"))
+                .unwrap();
+        let verified = verify(&mut rng, &validation);
         assert!(matches!(
-            verify_proof_string(
-                &mut rng,
-                &derived_proof,
-                KEY_GRAPH,
-                None,
-                domain,
-                None,
-                None,
-            ),
-            Err(RDFProofsError::MissingDomainInVP)
+            verified,
+            Err(RDFProofsError::InvalidNonceToken(_))
         ));
+
+        // token not signed by the verifier
+        let validation = NonceTokenValidation::new_string(
+            "did:example:issuer0#bls12_381-g2-pub001",
+            Some(audience),
+        )
+        .unwrap();
+        let verified = verify(&mut rng, &validation);
         assert!(matches!(
-            verify_proof_string(
-                &mut rng,
-                &derived_proof,
-                KEY_GRAPH,
-                challenge,
-                None,
-                None,
-                None,
-            ),
-            Err(RDFProofsError::MissingChallengeInVP)
+            verified,
+            Err(RDFProofsError::InvalidNonceToken(_))
         ));
-        assert!(
-            verify_proof_string(&mut rng, &derived_proof, KEY_GRAPH, None, None, None, None,)
-                .is_ok()
+
+        // plain challenge is not a token
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some("abcde"),
+            None,
+            VerifyOptionsString {
+                nonce_token: Some(
+                    &NonceTokenValidation::new_string(verifier_key, Some(audience)).unwrap(),
+                ),
+                ..Default::default()
+            },
         );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::InvalidNonceToken(_))
+        ));
     }
 
-    const DISCLOSED_VC_1_WITH_HIDDEN_LITERALS: &str = r#"
-        _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
-        _:e0 <http://schema.org/name> _:e4 .
-        _:e0 <http://example.org/vocab/isPatientOf> _:b0 .
-        _:e0 <http://schema.org/worksFor> _:b1 .
+    #[test]
+    fn derive_and_verify_proof_string_with_metrics() {
+        #[derive(Default)]
+        struct RecordingMetrics(Mutex<Vec<(&'static str, usize)>>);
+        impl Metrics for RecordingMetrics {
+            fn canonicalization_ms(&self, _ms: u64) {
+                self.0.lock().unwrap().push(("canonicalization_ms", 0));
+            }
+            fn statements_built(&self, count: usize) {
+                self.0.lock().unwrap().push(("statements_built", count));
+            }
+            fn proof_bytes(&self, bytes: usize) {
+                self.0.lock().unwrap().push(("proof_bytes", bytes));
+            }
+            fn verify_ms(&self, _ms: u64) {
+                self.0.lock().unwrap().push(("verify_ms", 0));
+            }
+            fn statement_count(&self, count: usize) {
+                self.0.lock().unwrap().push(("statement_count", count));
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let metrics = RecordingMetrics::default();
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString {
+                metrics: Some(&metrics),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                metrics: Some(&metrics),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        let proof_value = get_dataset_from_nquads(&derived_proof)
+            .unwrap()
+            .iter()
+            .find(|q| q.predicate == PROOF_VALUE)
+            .map(|q| match q.object {
+                TermRef::Literal(v) => v.value().len(),
+                _ => 0,
+            })
+            .unwrap();
+        assert_eq!(
+            *metrics.0.lock().unwrap(),
+            vec![
+                ("canonicalization_ms", 0),
+                ("statements_built", 2),
+                ("proof_bytes", proof_value),
+                ("statement_count", 2),
+                ("verify_ms", 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn derive_proof_string_with_progress_and_cancellation() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let derive = |rng: &mut StdRng,
+                      cancellation: Option<&CancellationToken>,
+                      progress: Option<&dyn Progress>| {
+            derive_proof_string(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                None,
+                None,
+                DeriveOptionsString {
+                    cancellation,
+                    progress,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // all the phases are reported in order
+        let phases = Mutex::new(vec![]);
+        let record = |phase: ProofPhase| phases.lock().unwrap().push(phase);
+        let derived_proof = derive(&mut rng, None, Some(&record));
+        assert!(derived_proof.is_ok(), "{:?}", derived_proof);
+        assert_eq!(*phases.lock().unwrap(), ProofPhase::ALL);
+
+        // cancelled before starting
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        assert!(matches!(
+            derive(&mut rng, Some(&cancellation), None),
+            Err(RDFProofsError::Cancelled)
+        ));
+
+        // cancelled while building statements, e.g., from UI thread
+        let cancellation = CancellationToken::new();
+        let phases = Mutex::new(vec![]);
+        let cancel_on_statement_build = |phase: ProofPhase| {
+            phases.lock().unwrap().push(phase);
+            if phase == ProofPhase::StatementBuild {
+                cancellation.cancel();
+            }
+        };
+        assert!(matches!(
+            derive(
+                &mut rng,
+                Some(&cancellation),
+                Some(&cancel_on_statement_build)
+            ),
+            Err(RDFProofsError::Cancelled)
+        ));
+        assert_eq!(
+            *phases.lock().unwrap(),
+            vec![ProofPhase::Canonicalization, ProofPhase::StatementBuild]
+        );
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_limits() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let tight_limits = Limits {
+            max_quads: Some(10),
+            ..Default::default()
+        };
+        let derive = |rng: &mut StdRng, limits: &Limits| {
+            derive_proof_string(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                None,
+                None,
+                DeriveOptionsString {
+                    limits: Some(limits),
+                    ..Default::default()
+                },
+            )
+        };
+        let verify = |rng: &mut StdRng, vp: &str, limits: &Limits| {
+            verify_proof_string(
+                rng,
+                vp,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString {
+                    limits: Some(limits),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // limits are opt-in
+        let derived_proof = derive(&mut rng, &Limits::default()).unwrap();
+        let verified = verify(&mut rng, &derived_proof, &Limits::default());
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // VPs exceeding limits are rejected before canonicalization
+        assert!(matches!(
+            derive(&mut rng, &tight_limits),
+            Err(RDFProofsError::CanonicalizationLimitExceeded(_))
+        ));
+        assert!(matches!(
+            verify(&mut rng, &derived_proof, &tight_limits),
+            Err(RDFProofsError::CanonicalizationLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_warnings() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+
+        // VP is verified a day before the first VC expires,
+        // by verifier whose clock is behind the holder's
+        let mut warning_policy =
+            WarningPolicy::new(chrono::Duration::hours(24), chrono::Duration::days(7));
+        warning_policy.set_clock(Box::new(FixedClock::new(
+            "2024-12-31T12:00:00Z".parse().unwrap(),
+        )));
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                warning_policy: Some(&warning_policy),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(verified.warnings.len(), 3, "{:?}", verified.warnings);
+        assert!(verified.warnings.iter().any(|w| matches!(
+            w,
+            VerificationWarning::CredentialExpiresSoon { expiration, .. }
+                if expiration.to_rfc3339() == "2025-01-01T00:00:00+00:00"
+        )));
+        assert!(verified.warnings.iter().any(|w| matches!(
+            w,
+            VerificationWarning::CredentialExpired { expiration, .. }
+                if expiration.to_rfc3339() == "2023-12-31T00:00:00+00:00"
+        )));
+        assert!(verified
+            .warnings
+            .iter()
+            .any(|w| matches!(w, VerificationWarning::CreatedInFuture { .. })));
+
+        // warnings are opt-in
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .unwrap();
+        assert!(verified.warnings.is_empty());
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_not_equal() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+
+        // prove that the hidden subject of VC 1 is not the hidden vaccine
+        let not_equals = vec![not_equal_request_string("_:e0", "_:e1").unwrap()];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString {
+                not_equals: Some(&not_equals),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof: {}", derived_proof);
+        assert!(derived_proof.contains("https://zkp-ld.org/security#notEqual"));
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // equal hidden terms cannot be proven unequal
+        let not_equals = vec![not_equal_request_string("_:e0", "_:e0").unwrap()];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString {
+                not_equals: Some(&not_equals),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            derived_proof,
+            Err(RDFProofsError::UnprovableInequality(_))
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_commitment_equality_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        // verifier commits to the expected (hidden) subject ID
+        let (commitment, blinding) = commit_to_term_string(&mut rng, "<did:example:john>").unwrap();
+        let commitment_equalities = vec![CommitmentEqualityRequestString {
+            commitment: commitment.clone(),
+            blinding,
+            value: "_:e0".to_string(),
+        }];
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                commitment_equalities: Some(&commitment_equalities),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof: {}", derived_proof);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString {
+                verifier_commitments: Some(&vec![commitment.clone()]),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // verifier's commitment must be included in VP
+        let (other_commitment, _) = commit_to_term_string(&mut rng, "<did:example:john>").unwrap();
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString {
+                verifier_commitments: Some(&vec![other_commitment]),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::MissingCommitmentEquality)
+        ))
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_commitment_to_different_value_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let (commitment, blinding) =
+            commit_to_term_string(&mut rng, "<did:example:alice>").unwrap();
+        let commitment_equalities = vec![CommitmentEqualityRequestString {
+            commitment: commitment.clone(),
+            blinding,
+            value: "_:e0".to_string(),
+        }];
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                commitment_equalities: Some(&commitment_equalities),
+                ..Default::default()
+            },
+        );
+
+        // either proving or verification must fail
+        if let Ok(derived_proof) = derived_proof {
+            let verified = verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    verifier_commitments: Some(&vec![commitment]),
+                    ..Default::default()
+                },
+            );
+            assert!(verified.is_err());
+        }
+    }
+
+    #[test]
+    fn verify_proof_success() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let vp = get_dataset_from_nquads(VP).unwrap();
+        let challenge = "abcde";
+        let verified = verify_proof(
+            &mut rng,
+            &vp,
+            &key_graph,
+            Some(challenge),
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    #[test]
+    fn verify_proof_string_success() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+        let challenge = "abcde";
+        let verified = verify_proof_string(
+            &mut rng,
+            VP,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    #[test]
+    fn verify_legacy_proof_without_version_tag() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let vp = get_dataset_from_nquads(VP).unwrap();
+
+        // the fixture is made before proof layout and credential context versions are introduced
+        let proof_value = VerifiablePresentation::try_from(&vp)
+            .unwrap()
+            .get_proof_value()
+            .unwrap();
+        let decoded = decode_proof_value(&proof_value).unwrap();
+        assert_eq!(decoded.version, 0);
+        assert_eq!(decoded.context_version, 0);
+
+        // verified against the legacy proof spec context
+        let verified = verify_proof(
+            &mut rng,
+            &vp,
+            &key_graph,
+            Some("abcde"),
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // application protocols are not bound into legacy proofs
+        let verified = verify_proof(
+            &mut rng,
+            &vp,
+            &key_graph,
+            Some("abcde"),
+            None,
+            VerifyOptions {
+                protocol: Some("https://example.org/protocol"),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_err());
+    }
+
+    #[test]
+    fn verify_proof_with_min_context_version() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &get_example_deanon_map_string(),
+            KEY_GRAPH,
+            Some("abcde"),
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        let verify = |rng: &mut StdRng, vp: &str| {
+            verify_proof_string(
+                rng,
+                vp,
+                KEY_GRAPH,
+                Some("abcde"),
+                None,
+                VerifyOptionsString {
+                    min_context_version: Some(PROOF_CONTEXT_VERSION),
+                    ..Default::default()
+                },
+            )
+        };
+        let verified = verify(&mut rng, &derived_proof);
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // legacy proof without per-credential contexts
+        assert!(matches!(
+            verify(&mut rng, VP),
+            Err(RDFProofsError::ProofContextVersionTooLow { version: 0, .. })
+        ));
+
+        // proof whose context version is downgraded by prover
+        let proof_value =
+            VerifiablePresentation::try_from(&get_dataset_from_nquads(&derived_proof).unwrap())
+                .unwrap()
+                .get_proof_value()
+                .unwrap();
+        let mut downgraded = decode_proof_value(&proof_value).unwrap();
+        downgraded.context_version = 0;
+        let downgraded =
+            derived_proof.replace(&proof_value, &encode_proof_value(&downgraded).unwrap());
+        assert!(matches!(
+            verify(&mut rng, &downgraded),
+            Err(RDFProofsError::ProofContextVersionTooLow { version: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn verify_proof_string_with_key_usage_constraints_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let challenge = "abcde";
+        // issuer0's key is restricted to a type the disclosed VC does not have
+        let key_graph = format!(
+            "{}<did:example:issuer0#bls12_381-g2-pub001> <https://zkp-ld.org/security#allowedCredentialType> <http://example.org/vocab/VaccinationCredential> .\n",
+            KEY_GRAPH
+        );
+        let verified = verify_proof_string(
+            &mut rng,
+            VP,
+            &key_graph,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(
+            matches!(verified, Err(RDFProofsError::UnauthorizedKeyUsage(_))),
+            "{:?}",
+            verified
+        )
+    }
+
+    #[test]
+    fn verify_proof_string_returns_canonical_vp() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let challenge = "abcde";
+        let verified = verify_proof_string(
+            &mut rng,
+            VP,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        )
+        .unwrap();
+
+        // canonical VP without proof value, which external signers can reproduce
+        let vp_without_proof_value = Dataset::from_iter(
+            get_dataset_from_nquads(VP)
+                .unwrap()
+                .iter()
+                .filter(|q| q.predicate != PROOF_VALUE),
+        );
+        assert_eq!(
+            verified.canonical_vp,
+            rdf_canon::canonicalize(&vp_without_proof_value).unwrap()
+        );
+        assert!(!verified.canonical_vp.contains(PROOF_VALUE.as_str()));
+    }
+
+    #[test]
+    fn export_key_graph_for_vp_and_verify() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph = export_key_graph_for_vp_string(VP, KEY_GRAPH).unwrap();
+        println!("key graph for VP:\n{}", key_graph);
+
+        // only public keys of the verification methods referenced by VP are exported
+        let verification_methods = get_graph_from_ntriples(&key_graph)
+            .unwrap()
+            .iter()
+            .map(|t| t.subject.to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            verification_methods,
+            HashSet::from([
+                "<did:example:issuer0#bls12_381-g2-pub001>".to_string(),
+                "<did:example:issuer3#bls12_381-g2-pub001>".to_string(),
+            ])
+        );
+        assert!(!key_graph.contains("secretKeyMultibase"));
+
+        let verified = verify_proof_string(
+            &mut rng,
+            VP,
+            &key_graph,
+            Some("abcde"),
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // verification methods missing in the registry
+        let partial_key_graph = KEY_GRAPH
+            .lines()
+            .filter(|line| !line.contains("did:example:issuer3"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(matches!(
+            export_key_graph_for_vp_string(VP, &partial_key_graph),
+            Err(RDFProofsError::InvalidVerificationMethod)
+        ));
+    }
+
+    #[test]
+    fn verify_bundle_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let bundle =
+            VerificationBundle::new(VP, KEY_GRAPH, &HashMap::new(), None, Some("abcde"), None)
+                .unwrap();
+        assert!(!bundle.key_graph.contains("secretKeyMultibase"));
+        let bytes = bundle.to_bytes().unwrap();
+        let verify =
+            |rng: &mut StdRng, bytes: &[u8], pinned_digests: Option<&HashMap<String, String>>| {
+                verify_bundle(
+                    rng,
+                    bytes,
+                    KEY_GRAPH,
+                    Some("abcde"),
+                    None,
+                    None,
+                    pinned_digests,
+                )
+            };
+        let verified = verify(&mut rng, &bytes, None);
+        assert!(verified.is_ok(), "{:?}", verified);
+        let verified = verify(&mut rng, &bytes, Some(&HashMap::new()));
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // inconsistent bundles
+        let with_secret_key = VerificationBundle {
+            key_graph: KEY_GRAPH.to_string(),
+            ..bundle.clone()
+        };
+        let unused_vk = VerificationBundle {
+            verifying_keys: BTreeMap::from([(
+                "http://example.org/circuit".to_string(),
+                BundledVerifyingKey {
+                    verifying_key: "uAA".to_string(),
+                    digest: hash_str_to_str("uAA"),
+                },
+            )]),
+            ..bundle.clone()
+        };
+        let mut tampered_vk = unused_vk.clone();
+        for vk in tampered_vk.verifying_keys.values_mut() {
+            vk.digest = hash_str_to_str("uAB");
+        }
+        // bundled digest is not the one pinned by verifier
+        let pinned_digests = HashMap::from([(
+            "http://example.org/circuit".to_string(),
+            hash_str_to_str("uAB"),
+        )]);
+        assert!(matches!(
+            verify(&mut rng, &unused_vk.to_bytes().unwrap(), Some(&pinned_digests)),
+            Err(RDFProofsError::InvalidVerificationBundle(msg)) if msg.contains("pinned")
+        ));
+        for invalid in [with_secret_key, unused_vk, tampered_vk] {
+            assert!(matches!(
+                verify(&mut rng, &invalid.to_bytes().unwrap(), None),
+                Err(RDFProofsError::InvalidVerificationBundle(_))
+            ));
+        }
+        assert!(matches!(
+            verify(&mut rng, b"garbage", None),
+            Err(RDFProofsError::InvalidVerificationBundle(_))
+        ));
+
+        // bundles not matching the key graph, policy, and challenge trusted by verifier
+        let forged_key_graph = VerificationBundle {
+            key_graph: format!(
+                "{}<did:example:issuer0#bls12_381-g2-pub001> <https://zkp-ld.org/security#allowedCredentialType> <http://example.org/vocab/VaccinationCredential> .\n",
+                bundle.key_graph
+            ),
+            ..bundle.clone()
+        };
+        let forged_policy = VerificationBundle {
+            policy: Some(
+                "_:b0 <https://zkp-ld.org/security#allowedIssuer> <did:example:issuer0> .\n"
+                    .to_string(),
+            ),
+            ..bundle.clone()
+        };
+        let wrong_challenge = VerificationBundle {
+            challenge: Some("fghij".to_string()),
+            ..bundle.clone()
+        };
+        for (mismatched, field) in [
+            (forged_key_graph, "key graph"),
+            (forged_policy, "policy"),
+            (wrong_challenge, "challenge"),
+        ] {
+            assert!(matches!(
+                verify(&mut rng, &mismatched.to_bytes().unwrap(), None),
+                Err(RDFProofsError::InvalidVerificationBundle(msg)) if msg.starts_with(field)
+            ));
+        }
+        assert!(matches!(
+            verify_bundle(&mut rng, &bytes, KEY_GRAPH, Some("fghij"), None, None, None),
+            Err(RDFProofsError::InvalidVerificationBundle(msg)) if msg.starts_with("challenge")
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_classic_vp() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let vcs = [(VC_1, VC_PROOF_1), (VC_2, VC_PROOF_2)];
+        let holder = "did:example:issuer2#bls12_381-g2-pub001";
+
+        let verify = |rng: &mut StdRng, vp: &str, challenge: Option<&str>| {
+            verify_proof_string(
+                rng,
+                vp,
+                KEY_GRAPH,
+                challenge,
+                None,
+                VerifyOptionsString::default(),
+            )
+        };
+
+        // VP signed by holder, binding challenge
+        let vp = derive_classic_vp_string(
+            &mut rng,
+            &vcs,
+            KEY_GRAPH,
+            Some(holder),
+            Some("abcde"),
+            None,
+            None,
+        )
+        .unwrap();
+        let verified = verify(&mut rng, &vp, Some("abcde"));
+        assert!(verified.is_ok(), "{:?}", verified);
+        let verified = verified.unwrap();
+        assert_eq!(verified.disclosed_documents.len(), 2);
+        assert_eq!(
+            verified.distinct_credential_counts.len(),
+            2,
+            "{:?}",
+            verified.distinct_credential_counts
+        );
+        assert!(matches!(
+            verify(&mut rng, &vp, Some("fghij")),
+            Err(RDFProofsError::MismatchedChallenge)
+        ));
+        assert!(verify(
+            &mut rng,
+            &vp.replace("AwesomeVaccine", "AwfulVaccine"),
+            Some("abcde")
+        )
+        .is_err());
+
+        // VP without holder's proof cannot bind challenge
+        let vp =
+            derive_classic_vp_string(&mut rng, &vcs, KEY_GRAPH, None, None, None, None).unwrap();
+        let verified = verify(&mut rng, &vp, None);
+        assert!(verified.is_ok(), "{:?}", verified);
+        assert!(matches!(
+            verify(&mut rng, &vp, Some("abcde")),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(matches!(
+            derive_classic_vp_string(&mut rng, &vcs, KEY_GRAPH, None, Some("abcde"), None, None),
+            Err(RDFProofsError::InvalidClassicVP(_))
+        ));
+    }
+
+    #[test]
+    fn verify_classic_vp_with_verifier_requirements() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let issuer0 = NamedNode::new_unchecked("did:example:issuer0#bls12_381-g2-pub001");
+        let verify = |rng: &mut StdRng, vcs: &[(&str, &str)], options: VerifyOptions| {
+            let vp = derive_classic_vp_string(rng, vcs, KEY_GRAPH, None, None, None, None).unwrap();
+            verify_proof(
+                rng,
+                &get_dataset_from_nquads(&vp).unwrap(),
+                &key_graph,
+                None,
+                None,
+                options,
+            )
+        };
+
+        // the same VC disclosed twice is counted once
+        let verified = verify(
+            &mut rng,
+            &[(VC_1, VC_PROOF_1), (VC_1, VC_PROOF_1)],
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+        assert_eq!(verified.unwrap().distinct_credential_counts[&issuer0], 1);
+        assert!(matches!(
+            verify(
+                &mut rng,
+                &[(VC_1, VC_PROOF_1), (VC_1, VC_PROOF_1)],
+                VerifyOptions {
+                    min_distinct_credentials: HashMap::from([(issuer0.clone(), 2)]),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::InsufficientDistinctCredentials(_))
+        ));
+
+        // verifier's policy is applied to the disclosed VCs
+        let policy = get_graph_from_ntriples(
+            r#"_:b0 <https://zkp-ld.org/security#allowedIssuer> <did:example:issuer0> ."#,
+        )
+        .unwrap();
+        let verified = verify(
+            &mut rng,
+            &[(VC_1, VC_PROOF_1)],
+            VerifyOptions {
+                policy: Some(&policy),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+        assert!(matches!(
+            verify(
+                &mut rng,
+                &[(VC_1, VC_PROOF_1), (VC_2, VC_PROOF_2)],
+                VerifyOptions {
+                    policy: Some(&policy),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::RejectedByPolicy { .. })
+        ));
+        let policy = get_graph_from_ntriples(
+            r#"_:p <https://zkp-ld.org/security#uniqueCredentials> "true"^^<http://www.w3.org/2001/XMLSchema#boolean> ."#,
+        )
+        .unwrap();
+        assert!(matches!(
+            verify(
+                &mut rng,
+                &[(VC_1, VC_PROOF_1), (VC_1, VC_PROOF_1)],
+                VerifyOptions {
+                    policy: Some(&policy),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::RejectedByPolicy { .. })
+        ));
+
+        // requirements on derived proofs are explicitly rejected
+        let warning_policy =
+            WarningPolicy::new(chrono::Duration::days(30), chrono::Duration::days(30));
+        assert!(matches!(
+            verify(
+                &mut rng,
+                &[(VC_1, VC_PROOF_1)],
+                VerifyOptions {
+                    warning_policy: Some(&warning_policy),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::InvalidClassicVP(_))
+        ));
+        assert!(matches!(
+            verify(
+                &mut rng,
+                &[(VC_1, VC_PROOF_1)],
+                VerifyOptions {
+                    public_values: HashMap::from([(
+                        NamedNode::new_unchecked("https://zkp-ld.org/circuit/lessThanPrvPub"),
+                        HashMap::new(),
+                    )]),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::InvalidClassicVP(_))
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_equivalence_classes() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        println!("derived_proof: {}", derived_proof);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // the vaccine in VC1 equals the subject of VC2
+        let classes = vp_equivalence_classes_string(&derived_proof).unwrap();
+        assert_eq!(classes.len(), 1);
+        let vaccine = NamedNode::new_unchecked("http://example.org/vocab/vaccine");
+        let credential_subject =
+            NamedNode::new_unchecked("https://www.w3.org/2018/credentials#credentialSubject");
+        let vaccine_occurrence = classes[0]
+            .occurrences
+            .iter()
+            .find(|o| o.triple.predicate == vaccine && o.triple.object == classes[0].term)
+            .unwrap();
+        let subject_occurrence = classes[0]
+            .occurrences
+            .iter()
+            .find(|o| {
+                o.triple.predicate == credential_subject && o.triple.object == classes[0].term
+            })
+            .unwrap();
+        assert_ne!(vaccine_occurrence.credential, subject_occurrence.credential);
+
+        // tampered equivalence classes must be rejected
+        let tampered_proof = derived_proof
+            .lines()
+            .filter(|line| !line.contains("<https://zkp-ld.org/security#inCredential>"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let verified = verify_proof_string(
+            &mut rng,
+            &tampered_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::InvalidEquivalenceClasses)
+        ));
+    }
+
+    #[test]
+    fn vp_metadata_success() {
+        let metadata = vp_metadata_string(VP).unwrap();
+        assert_eq!(
+            metadata.proof.cryptosuite.as_deref(),
+            Some("bbs-termwise-proof-2023")
+        );
+        assert_eq!(metadata.proof.challenge.as_deref(), Some("abcde"));
+        assert_eq!(metadata.proof.domain, None);
+        assert_eq!(
+            metadata.proof.created.as_deref(),
+            Some("2023-10-06T05:40:05.941640167Z")
+        );
+        assert_eq!(metadata.credentials.len(), 2);
+        let verification_methods = metadata
+            .credentials
+            .iter()
+            .map(|vc| vc.verification_method.clone().unwrap().into_string())
+            .collect::<Vec<_>>();
+        assert!(
+            verification_methods.contains(&"did:example:issuer0#bls12_381-g2-pub001".to_string())
+        );
+        assert!(
+            verification_methods.contains(&"did:example:issuer3#bls12_381-g2-pub001".to_string())
+        );
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_credential_summary() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        println!("derive_proof: {}", derived_proof);
+
+        let summary = vp_credential_summary_string(&derived_proof).unwrap();
+        assert_eq!(
+            summary,
+            vec![
+                CredentialSummary {
+                    verification_method: NamedNode::new_unchecked(
+                        "did:example:issuer0#bls12_381-g2-pub001"
+                    ),
+                    cryptosuite: "bbs-termwise-signature-2023".to_string(),
+                    count: 1,
+                },
+                CredentialSummary {
+                    verification_method: NamedNode::new_unchecked(
+                        "did:example:issuer3#bls12_381-g2-pub001"
+                    ),
+                    cryptosuite: "bbs-termwise-signature-2023".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // negative test: summary inconsistent with the disclosed VCs must be rejected
+        let tampered_proof = derived_proof.replace(
+            "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+            "\"2\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+        );
+        let verified = verify_proof_string(
+            &mut rng,
+            &tampered_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::InvalidCredentialSummary)
+        ));
+
+        // VPs without summary result in no entries
+        assert!(vp_credential_summary_string(VP).unwrap().is_empty());
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_secret_last_cryptosuite() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let secret = b"SECRET";
+
+        // bound VCs with the secret first (2023) and last (2024) in the message vector
+        let sign_with_cryptosuite = |rng: &mut StdRng, document: &str, cryptosuite: &str| {
+            let proof_options = format!(
+                "{}_:b0 <https://w3id.org/security#cryptosuite> \"{}\" .\n",
+                VC_PROOF_WITHOUT_PROOFVALUE_1, cryptosuite
+            );
+            let mut vc = VerifiableCredential::new(
+                get_graph_from_ntriples(document).unwrap(),
+                get_graph_from_ntriples(&proof_options).unwrap(),
+            );
+            sign(rng, &mut vc, &key_graph, Some(secret), None).unwrap();
+            vc
+        };
+        let vc_2023 = sign_with_cryptosuite(&mut rng, VC_1, "bbs-termwise-bound-signature-2023");
+        let vc_2024 = sign_with_cryptosuite(
+            &mut rng,
+            &VC_1.replace("vcred/00", "vcred/01"),
+            "bbs-termwise-bound-signature-2024",
+        );
+        let get_vc_pair = |original: &VerifiableCredential, credential_id: &str| {
+            VcPair::new(
+                original.clone(),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(&DISCLOSED_VC_1.replace("_:e2", credential_id))
+                        .unwrap(),
+                    get_graph_from_ntriples(&DISCLOSED_VC_PROOF_1.replace(
+                        "bbs-termwise-signature-2023",
+                        &original.get_cryptosuite().unwrap(),
+                    ))
+                    .unwrap(),
+                ),
+            )
+        };
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:cid1".to_string(),
+            "<http://example.org/vcred/01>".to_string(),
+        );
+        let deanon_map = get_deanon_map_from_string(&deanon_map).unwrap();
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vec![
+                get_vc_pair(&vc_2023, "_:e2"),
+                get_vc_pair(&vc_2024, "_:cid1"),
+            ],
+            &deanon_map,
+            &key_graph,
+            None,
+            None,
+            DeriveOptions {
+                holder_binding: HolderBindingPolicy::Bound { secret },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof.vp: {}", rdf_canon::serialize(&derived_proof));
+
+        let verified = verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            None,
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_split_iri_cryptosuite() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        // two VCs whose IDs share the namespace `http://example.org/vcred/`
+        let sign_with_split_iri = |rng: &mut StdRng, document: &str| {
+            let proof_options = format!(
+                "{}_:b0 <https://w3id.org/security#cryptosuite> \"{}\" .\n",
+                VC_PROOF_WITHOUT_PROOFVALUE_1, "bbs-termwise-split-iri-signature-2024"
+            );
+            let mut vc = VerifiableCredential::new(
+                get_graph_from_ntriples(document).unwrap(),
+                get_graph_from_ntriples(&proof_options).unwrap(),
+            );
+            sign(rng, &mut vc, &key_graph, None, None).unwrap();
+            vc
+        };
+        let vc_00 = sign_with_split_iri(&mut rng, VC_1);
+        let vc_01 = sign_with_split_iri(&mut rng, &VC_1.replace("vcred/00", "vcred/01"));
+        let get_vc_pair = |original: &VerifiableCredential, credential_id: &str| {
+            VcPair::new(
+                original.clone(),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(&DISCLOSED_VC_1.replace("_:e2", credential_id))
+                        .unwrap(),
+                    get_graph_from_ntriples(&DISCLOSED_VC_PROOF_1.replace(
+                        "bbs-termwise-signature-2023",
+                        "bbs-termwise-split-iri-signature-2024",
+                    ))
+                    .unwrap(),
+                ),
+            )
+        };
+        let vc_pairs = vec![get_vc_pair(&vc_00, "_:e2"), get_vc_pair(&vc_01, "_:cid1")];
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:cid1".to_string(),
+            "<http://example.org/vcred/01>".to_string(),
+        );
+        let deanon_map = get_deanon_map_from_string(&deanon_map).unwrap();
+        let derive = |rng: &mut StdRng, same_namespaces: Vec<(BlankNode, BlankNode)>| {
+            derive_proof(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                &key_graph,
+                None,
+                None,
+                DeriveOptions {
+                    same_namespaces,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // prove that the hidden credential IDs share a namespace
+        let derived_proof = derive(
+            &mut rng,
+            vec![(
+                BlankNode::new("e2").unwrap(),
+                BlankNode::new("cid1").unwrap(),
+            )],
+        )
+        .unwrap();
+        assert!(rdf_canon::serialize(&derived_proof)
+            .contains("https://zkp-ld.org/security#sameNamespace"));
+        let verified = verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            None,
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // hidden IRIs in different namespaces, e.g., `did:example:` and `http://example.org/vcred/`
+        let derived_proof = derive(
+            &mut rng,
+            vec![(
+                BlankNode::new("e0").unwrap(),
+                BlankNode::new("cid1").unwrap(),
+            )],
+        );
+        assert!(matches!(
+            derived_proof,
+            Err(RDFProofsError::UnprovableNamespaceEquality(_))
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_distinct_credentials() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let verification_method =
+            NamedNode::new("did:example:issuer0#bls12_381-g2-pub001").unwrap();
+
+        // two VCs from issuer0 with distinct IDs, which are hidden as `_:cid0` and `_:cid1` in VP
+        let vc_1 = VerifiableCredential::new(
+            get_graph_from_ntriples(VC_1).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+        );
+        let mut another_vc_1 = VerifiableCredential::new(
+            get_graph_from_ntriples(&VC_1.replace("vcred/00", "vcred/01")).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap(),
+        );
+        sign(&mut rng, &mut another_vc_1, &key_graph, None, None).unwrap();
+        let get_vc_pair = |original: &VerifiableCredential, credential_id: &str| {
+            VcPair::new(
+                original.clone(),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(&DISCLOSED_VC_1.replace("_:e2", credential_id))
+                        .unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+                ),
+            )
+        };
+        let get_deanon_map = |id0: &str, id1: &str| {
+            let mut deanon_map = get_example_deanon_map_string();
+            deanon_map.insert("_:cid0".to_string(), id0.to_string());
+            deanon_map.insert("_:cid1".to_string(), id1.to_string());
+            get_deanon_map_from_string(&deanon_map).unwrap()
+        };
+        let distinct_credentials = vec![
+            BlankNode::new("cid0").unwrap(),
+            BlankNode::new("cid1").unwrap(),
+        ];
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vec![
+                get_vc_pair(&vc_1, "_:cid0"),
+                get_vc_pair(&another_vc_1, "_:cid1"),
+            ],
+            &get_deanon_map(
+                "<http://example.org/vcred/00>",
+                "<http://example.org/vcred/01>",
+            ),
+            &key_graph,
+            None,
+            None,
+            DeriveOptions {
+                distinct_credentials: distinct_credentials.clone(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof.vp: {}", rdf_canon::serialize(&derived_proof));
+
+        let mut verify = |min: usize| {
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                None,
+                VerifyOptions {
+                    min_distinct_credentials: HashMap::from([(verification_method.clone(), min)]),
+                    ..Default::default()
+                },
+            )
+        };
+        let verified = verify(2);
+        assert!(verified.is_ok(), "{:?}", verified);
+        assert_eq!(
+            verified.unwrap().distinct_credential_counts,
+            HashMap::from([(verification_method.clone(), 2)])
+        );
+        assert!(matches!(
+            verify(3),
+            Err(RDFProofsError::InsufficientDistinctCredentials(_))
+        ));
+
+        // the same VC presented twice cannot be proven distinct
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vec![get_vc_pair(&vc_1, "_:cid0"), get_vc_pair(&vc_1, "_:cid1")],
+            &get_deanon_map(
+                "<http://example.org/vcred/00>",
+                "<http://example.org/vcred/00>",
+            ),
+            &key_graph,
+            None,
+            None,
+            DeriveOptions {
+                distinct_credentials,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            derived_proof,
+            Err(RDFProofsError::UnprovableInequality(_))
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_unique_credentials() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let policy = get_graph_from_ntriples(
+            r#"_:p <https://zkp-ld.org/security#uniqueCredentials> "true"^^<http://www.w3.org/2001/XMLSchema#boolean> ."#,
+        )
+        .unwrap();
+
+        let vc_1 = VerifiableCredential::new(
+            get_graph_from_ntriples(VC_1).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_1).unwrap(),
+        );
+        let mut another_vc_1 = VerifiableCredential::new(
+            get_graph_from_ntriples(&VC_1.replace("vcred/00", "vcred/01")).unwrap(),
+            get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap(),
+        );
+        sign(&mut rng, &mut another_vc_1, &key_graph, None, None).unwrap();
+        let get_vc_pair = |original: &VerifiableCredential, credential_id: &str| {
+            VcPair::new(
+                original.clone(),
+                VerifiableCredential::new(
+                    get_graph_from_ntriples(&DISCLOSED_VC_1.replace("_:e2", credential_id))
+                        .unwrap(),
+                    get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap(),
+                ),
+            )
+        };
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:cid0".to_string(),
+            "<http://example.org/vcred/00>".to_string(),
+        );
+        deanon_map.insert(
+            "_:cid1".to_string(),
+            "<http://example.org/vcred/01>".to_string(),
+        );
+        let deanon_map = get_deanon_map_from_string(&deanon_map).unwrap();
+
+        let derive = |rng: &mut StdRng, vc_pairs: Vec<VcPair>| {
+            derive_proof(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                &key_graph,
+                None,
+                None,
+                DeriveOptions {
+                    policy: Some(&policy),
+                    ..Default::default()
+                },
+            )
+        };
+        let verify = |rng: &mut StdRng, vp: &Dataset| {
+            verify_proof(
+                rng,
+                vp,
+                &key_graph,
+                None,
+                None,
+                VerifyOptions {
+                    policy: Some(&policy),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // hidden credential IDs are proven pairwise distinct as required by the policy
+        let derived_proof = derive(
+            &mut rng,
+            vec![
+                get_vc_pair(&vc_1, "_:cid0"),
+                get_vc_pair(&another_vc_1, "_:cid1"),
+            ],
+        )
+        .unwrap();
+        let verified = verify(&mut rng, &derived_proof);
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // the same VC presented twice with its ID hidden cannot be proven distinct
+        assert!(matches!(
+            derive(
+                &mut rng,
+                vec![get_vc_pair(&vc_1, "_:cid0"), get_vc_pair(&vc_1, "_:cid0")]
+            ),
+            Err(RDFProofsError::UnprovableInequality(_))
+        ));
+
+        // nor with its ID disclosed
+        let derived_proof = derive(
+            &mut rng,
+            vec![
+                get_vc_pair(&vc_1, "<http://example.org/vcred/00>"),
+                get_vc_pair(&vc_1, "<http://example.org/vcred/00>"),
+            ],
+        )
+        .unwrap();
+        assert!(matches!(
+            verify(&mut rng, &derived_proof),
+            Err(RDFProofsError::RejectedByPolicy { .. })
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_challenge_and_domain() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let vc_doc_1 = get_graph_from_ntriples(VC_1).unwrap();
+        let vc_proof_1 = get_graph_from_ntriples(VC_PROOF_1).unwrap();
+        let vc_1 = VerifiableCredential::new(vc_doc_1, vc_proof_1);
+
+        let disclosed_vc_doc_1 = get_graph_from_ntriples(DISCLOSED_VC_1).unwrap();
+        let disclosed_vc_proof_1 = get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap();
+        let disclosed_1 = VerifiableCredential::new(disclosed_vc_doc_1, disclosed_vc_proof_1);
+
+        let vc_doc_2 = get_graph_from_ntriples(VC_2).unwrap();
+        let vc_proof_2 = get_graph_from_ntriples(VC_PROOF_2).unwrap();
+        let vc_2 = VerifiableCredential::new(vc_doc_2, vc_proof_2);
+
+        let disclosed_vc_doc_2 = get_graph_from_ntriples(DISCLOSED_VC_2).unwrap();
+        let disclosed_vc_proof_2 = get_graph_from_ntriples(DISCLOSED_VC_PROOF_2).unwrap();
+        let disclosed_2 = VerifiableCredential::new(disclosed_vc_doc_2, disclosed_vc_proof_2);
+
+        let vc_with_disclosed_1 = VcPair::new(vc_1, disclosed_1);
+        let vc_with_disclosed_2 = VcPair::new(vc_2, disclosed_2);
+        let vcs = vec![vc_with_disclosed_1, vc_with_disclosed_2];
+
+        let deanon_map = get_example_deanon_map();
+
+        let challenge = Some("challenge");
+        let domain = Some("example.org");
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            challenge,
+            domain,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+        assert!(verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            challenge,
+            domain,
+            VerifyOptions::default(),
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                domain,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                challenge,
+                None,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInRequest)
+        ));
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                None,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            None,
+            domain,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                challenge,
+                domain,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            None,
+            domain,
+            VerifyOptions::default(),
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                challenge,
+                None,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                None,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInRequest)
+        ));
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            challenge,
+            None,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                challenge,
+                domain,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInVP)
+        ));
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                domain,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+        assert!(verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            challenge,
+            None,
+            VerifyOptions::default(),
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                None,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            None,
+            None,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                challenge,
+                domain,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                None,
+                domain,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInVP)
+        ));
+        assert!(matches!(
+            verify_proof(
+                &mut rng,
+                &derived_proof,
+                &key_graph,
+                challenge,
+                None,
+                VerifyOptions::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            None,
+            None,
+            VerifyOptions::default(),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_challenge_and_domain() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = Some("challenge");
+        let domain = Some("example.org");
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            challenge,
+            domain,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            challenge,
+            domain,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                domain,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                challenge,
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInRequest)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            domain,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                challenge,
+                domain,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            domain,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                challenge,
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInRequest)
+        ));
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            challenge,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                challenge,
+                domain,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInVP)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                domain,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            challenge,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInRequest)
+        ));
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                challenge,
+                domain,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                domain,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingDomainInVP)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                challenge,
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingChallengeInVP)
+        ));
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+    }
+
+    const DISCLOSED_VC_1_WITH_HIDDEN_LITERALS: &str = r#"
+        _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+        _:e0 <http://schema.org/name> _:e4 .
+        _:e0 <http://example.org/vocab/isPatientOf> _:b0 .
+        _:e0 <http://schema.org/worksFor> _:b1 .
         _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
         _:b0 <http://example.org/vocab/vaccine> _:e1 .
         _:b0 <http://example.org/vocab/vaccinationDate> _:e5 .
@@ -2309,87 +6415,1406 @@ mod tests {
         _:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
         _:e2 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
         "#;
-    const DEANON_MAP_WITH_HIDDEN_LITERAL: [(&str, &str); 2] = [
-        ("_:e4", "\"John Smith\""),
-        (
-            "_:e5",
-            "\"2022-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
-        ),
-    ];
-    fn get_example_deanon_map_string_with_hidden_literal() -> HashMap<String, String> {
-        DEANON_MAP_WITH_HIDDEN_LITERAL
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect()
+    const DEANON_MAP_WITH_HIDDEN_LITERAL: [(&str, &str); 2] = [
+        ("_:e4", "\"John Smith\""),
+        (
+            "_:e5",
+            "\"2022-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
+        ),
+    ];
+    fn get_example_deanon_map_string_with_hidden_literal() -> HashMap<String, String> {
+        DEANON_MAP_WITH_HIDDEN_LITERAL
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+    fn get_example_deanon_map_with_hidden_literal() -> HashMap<NamedOrBlankNode, Term> {
+        get_deanon_map_from_string(&&get_example_deanon_map_string_with_hidden_literal()).unwrap()
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_policy() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let policy = r#"
+_:b0 <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/name> .
+_:b0 <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/status> .
+"#;
+        let policy_relabeled = r#"
+_:x <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/status> .
+_:x <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/name> .
+"#;
+        let substituted_policy = r#"
+_:b0 <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/name> .
+"#;
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                policy: Some(policy),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof: {}", derived_proof);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString {
+                policy: Some(policy_relabeled),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    policy: Some(substituted_policy),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::MismatchedPolicyDigest)
+        ));
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString::default(),
+            ),
+            Err(RDFProofsError::MissingPolicyDigestInRequest)
+        ));
+
+        let metadata = vp_metadata_string(&derived_proof).unwrap();
+        assert_eq!(
+            metadata.proof.policy_digest.as_deref(),
+            Some(PolicyDigest::from_ntriples(policy).unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_acceptance_policy() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+
+        let mut derive_and_verify = |policy: &str| {
+            let derived_proof = derive_proof_string(
+                &mut rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                None,
+                None,
+                DeriveOptionsString {
+                    policy: Some(policy),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString {
+                    policy: Some(policy),
+                    ..Default::default()
+                },
+            )
+        };
+
+        let verified = derive_and_verify(
+            r#"
+_:b0 <https://zkp-ld.org/security#allowedIssuer> <did:example:issuer0> .
+_:b0 <https://zkp-ld.org/security#allowedIssuer> <did:example:issuer3> .
+_:b0 <https://zkp-ld.org/security#allowedCryptosuite> "bbs-termwise-signature-2023" .
+"#,
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // the failed rule is reported
+        let verified = derive_and_verify(
+            r#"
+_:b0 <https://zkp-ld.org/security#allowedIssuer> <did:example:issuer0> .
+"#,
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::RejectedByPolicy { rule, value })
+                if rule == "https://zkp-ld.org/security#allowedIssuer"
+                    && value == "did:example:issuer3"
+        ));
+        let verified = derive_and_verify(
+            r#"
+_:b0 <https://zkp-ld.org/security#deniedVerificationMethod> <did:example:issuer3#bls12_381-g2-pub001> .
+"#,
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::RejectedByPolicy { rule, .. })
+                if rule == "https://zkp-ld.org/security#deniedVerificationMethod"
+        ));
+        let verified = derive_and_verify(
+            r#"
+_:b0 <https://zkp-ld.org/security#deniedCryptosuite> "bbs-termwise-signature-2023" .
+"#,
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::RejectedByPolicy { rule, .. })
+                if rule == "https://zkp-ld.org/security#deniedCryptosuite"
+        ));
+    }
+
+    #[test]
+    fn verify_replayed_proof_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+
+        let replay_guard = InMemoryReplayGuard::new(Duration::from_secs(300));
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString {
+                replay_guard: Some(&replay_guard),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        assert!(matches!(
+            verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    replay_guard: Some(&replay_guard),
+                    ..Default::default()
+                },
+            ),
+            Err(RDFProofsError::ReplayedPresentation)
+        ));
+    }
+
+    #[test]
+    fn verify_proof_with_extensions_and_legacy_layout() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+
+        // rewrite the serialized proof in VP
+        let proof_value =
+            VerifiablePresentation::try_from(&get_dataset_from_nquads(&derived_proof).unwrap())
+                .unwrap()
+                .get_proof_value()
+                .unwrap();
+        let rewrite = |f: &dyn Fn(&mut ProofWithIndexMap)| {
+            let mut proof_with_index_map = decode_proof_value(&proof_value).unwrap();
+            f(&mut proof_with_index_map);
+            derived_proof.replace(
+                &proof_value,
+                &encode_proof_value(&proof_with_index_map).unwrap(),
+            )
+        };
+        let verify = |vp: &str, ignore_non_critical_extensions: bool| {
+            verify_proof_string(
+                &mut StdRng::seed_from_u64(0u64),
+                vp,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString {
+                    ignore_non_critical_extensions: Some(ignore_non_critical_extensions),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // non-critical extensions can be ignored, while critical ones cannot
+        let extension = ProofExtension {
+            tag: "example-extension".to_string(),
+            critical: false,
+            data: vec![],
+        };
+        let with_non_critical = rewrite(&|p| p.extensions.push(extension.clone()));
+        assert!(matches!(
+            verify(&with_non_critical, false),
+            Err(RDFProofsError::UnsupportedStatementType { tag }) if tag == "example-extension"
+        ));
+        let verified = verify(&with_non_critical, true);
+        assert!(verified.is_ok(), "{:?}", verified);
+        let with_critical = rewrite(&|p| {
+            p.extensions.push(ProofExtension {
+                critical: true,
+                ..extension.clone()
+            })
+        });
+        assert!(matches!(
+            verify(&with_critical, true),
+            Err(RDFProofsError::UnsupportedStatementType { .. })
+        ));
+
+        // unknown statement types are rejected before verifying proof
+        let with_unknown_statement = rewrite(&|p| p.statement_tags[1] = "range-proof".to_string());
+        assert!(matches!(
+            verify(&with_unknown_statement, true),
+            Err(RDFProofsError::UnsupportedStatementType { tag }) if tag == "range-proof"
+        ));
+        let with_mismatched_statements = rewrite(&|p| p.statement_tags.truncate(1));
+        assert!(matches!(
+            verify(&with_mismatched_statements, false),
+            Err(RDFProofsError::MismatchedStatementTypes)
+        ));
+
+        // proofs in legacy layout without statement tags are still verified
+        let legacy = rewrite(&|p| {
+            p.version = 0;
+            p.statement_tags.clear();
+        });
+        let verified = verify(&legacy, false);
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // proofs cannot be downgraded to the legacy context without per-credential sub-contexts
+        let downgraded = rewrite(&|p| p.context_version = 0);
+        assert!(verify(&downgraded, false).is_err());
+
+        // proofs serialized without version byte are still verified, and migrated into current format
+        let unversioned = derived_proof.replace(
+            &proof_value,
+            &multibase::encode(
+                Base::Base64Url,
+                serde_cbor::to_vec(&decode_proof_value(&proof_value).unwrap()).unwrap(),
+            ),
+        );
+        let verified = verify(&unversioned, false);
+        assert!(verified.is_ok(), "{:?}", verified);
+        let migrated = migrate_vp_string(&unversioned).unwrap();
+        let verified = verify(&migrated, false);
+        assert!(verified.is_ok(), "{:?}", verified);
+        assert_eq!(
+            get_dataset_from_nquads(&migrated).unwrap(),
+            get_dataset_from_nquads(&derived_proof).unwrap()
+        );
+
+        // extension policy for `verify_proof`
+        let vp = get_dataset_from_nquads(&with_non_critical).unwrap();
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let verified = verify_proof(
+            &mut rng,
+            &vp,
+            &key_graph,
+            None,
+            None,
+            VerifyOptions {
+                extension_policy: ExtensionPolicy::IgnoreNonCritical,
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_hidden_literals() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let mut deanon_map = get_example_deanon_map();
+        deanon_map.extend(get_example_deanon_map_with_hidden_literal());
+
+        let vc_doc_1 = get_graph_from_ntriples(VC_1).unwrap();
+        let vc_proof_1 = get_graph_from_ntriples(VC_PROOF_1).unwrap();
+        let vc_1 = VerifiableCredential::new(vc_doc_1, vc_proof_1);
+
+        let disclosed_vc_doc_1 =
+            get_graph_from_ntriples(DISCLOSED_VC_1_WITH_HIDDEN_LITERALS).unwrap();
+        let disclosed_vc_proof_1 = get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap();
+        let disclosed_1 = VerifiableCredential::new(disclosed_vc_doc_1, disclosed_vc_proof_1);
+
+        let vc_with_disclosed_1 = VcPair::new(vc_1, disclosed_1);
+        let vcs = vec![vc_with_disclosed_1];
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            Some(challenge),
+            None,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+        println!("derived_proof: {}", rdf_canon::serialize(&derived_proof));
+
+        let verified = verify_proof(
+            &mut rng,
+            &derived_proof,
+            &key_graph,
+            Some(challenge),
+            None,
+            VerifyOptions::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_hidden_literals() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            VC_PROOF_1,
+            DISCLOSED_VC_1,
+            DISCLOSED_VC_PROOF_1,
+        )];
+
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        );
+
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    #[test]
+    fn derive_proof_failed_invalid_vc() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let vc_ntriples = r#"
+    <did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+    <did:example:john> <http://schema.org/name> "**********************************" .  # modified
+    <did:example:john> <http://example.org/vocab/isPatientOf> _:b0 .
+    <did:example:john> <http://schema.org/worksFor> _:b1 .
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
+    _:b0 <http://example.org/vocab/lotNumber> "0000001" .
+    _:b0 <http://example.org/vocab/vaccinationDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
+    _:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/b> .
+    _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Organization> .
+    _:b1 <http://schema.org/name> "ABC inc." .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+        let vc_doc = get_graph_from_ntriples(vc_ntriples).unwrap();
+        let vc_proof = get_graph_from_ntriples(VC_PROOF_1).unwrap();
+        let vc = VerifiableCredential::new(vc_doc, vc_proof);
+
+        let disclosed_vc_doc = get_graph_from_ntriples(DISCLOSED_VC_1).unwrap();
+        let disclosed_vc_proof = get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap();
+        let disclosed = VerifiableCredential::new(disclosed_vc_doc, disclosed_vc_proof);
+
+        let vc_with_disclosed = VcPair::new(vc, disclosed);
+        let vcs = vec![vc_with_disclosed];
+
+        let deanon_map = get_example_deanon_map();
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vcs,
+            &deanon_map,
+            &key_graph,
+            Some(challenge),
+            None,
+            DeriveOptions::default(),
+        );
+        assert!(matches!(
+            derived_proof,
+            Err(RDFProofsError::BBSPlus(
+                bbs_plus::prelude::BBSPlusError::InvalidSignature
+            ))
+        ))
+    }
+
+    #[test]
+    fn derive_proof_string_failed_invalid_vc() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+
+        let vc_pairs = vec![VcPairString::new(
+            VC_1_MODIFIED,
+            VC_PROOF_1,
+            DISCLOSED_VC_1,
+            DISCLOSED_VC_PROOF_1,
+        )];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString::default(),
+        );
+
+        assert!(matches!(
+            derived_proof,
+            Err(RDFProofsError::BBSPlus(
+                bbs_plus::prelude::BBSPlusError::InvalidSignature
+            ))
+        ))
+    }
+
+    const VC_PROOF_BOUND_1: &str = r#"
+        _:b0 <https://w3id.org/security#proofValue> "utXwiR3cqE_vytaKRk1jO5bijPewZ8Vx67WqHBjJ1TAN8BoEnhdu7zXyZ1WTYuLHqAWQCF5cBR1F0h3FXGsm2xh7Fafg49VG-Slte0XnTgDzpRqn0nqhO4I57s-b3TPVbA_t5uyJnGllyB6QcwVtRQA"^^<https://w3id.org/security#multibase> .
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-bound-signature-2023" .
+        _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+        "#;
+    const DISCLOSED_VC_PROOF_BOUND_1: &str = r#"
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-bound-signature-2023" .
+        _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+        "#;
+
+    #[test]
+    fn derive_and_verify_proof_string_with_secret_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let secret = b"SECRET";
+
+        let vc_pairs = vec![
+            VcPairString::new(
+                VC_1,
+                VC_PROOF_BOUND_1,
+                DISCLOSED_VC_1,
+                DISCLOSED_VC_PROOF_BOUND_1,
+            ),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
+
+    // mock device attestation signed with a digest keyed by the device key,
+    // whose certificate is a digest of the attestation root and the device key
+    struct MockDeviceAttestationVerifier;
+
+    fn mock_device_certificate(root: &str, device_key: &str) -> String {
+        hash_str_to_str(&format!("{}{}", root, device_key))
+    }
+
+    fn mock_device_attestation(
+        device_key: &str,
+        certificate: &str,
+        challenge: &[u8],
+    ) -> DeviceAttestation {
+        let authenticator_data = "authenticator-data".to_string();
+        let client_data_json = format!(
+            r#"{{"type":"webauthn.get","challenge":"{}"}}"#,
+            multibase::encode(Base::Base64Url, challenge)
+        );
+        let signature = hash_str_to_str(&format!(
+            "{}{}{}",
+            device_key, authenticator_data, client_data_json
+        ));
+        DeviceAttestation {
+            device_key: device_key.to_string(),
+            certificate: certificate.to_string(),
+            authenticator_data,
+            client_data_json,
+            signature,
+        }
+    }
+
+    impl DeviceAttestationVerifier for MockDeviceAttestationVerifier {
+        fn verify_certificate(
+            &self,
+            attestation: &DeviceAttestation,
+            root: &str,
+        ) -> Result<(), RDFProofsError> {
+            if attestation.certificate == mock_device_certificate(root, &attestation.device_key) {
+                Ok(())
+            } else {
+                Err(RDFProofsError::Other(
+                    "invalid device certificate".to_string(),
+                ))
+            }
+        }
+
+        fn verify(
+            &self,
+            attestation: &DeviceAttestation,
+            challenge: &[u8],
+        ) -> Result<(), RDFProofsError> {
+            if *attestation
+                == mock_device_attestation(
+                    &attestation.device_key,
+                    &attestation.certificate,
+                    challenge,
+                )
+            {
+                Ok(())
+            } else {
+                Err(RDFProofsError::Other(
+                    "invalid device attestation".to_string(),
+                ))
+            }
+        }
+    }
+
+    struct MockDeviceSigner {
+        device_key: &'static str,
+        root: &'static str,
+    }
+
+    impl DeviceSigner for MockDeviceSigner {
+        fn sign(&self, challenge: &[u8]) -> Result<DeviceAttestation, RDFProofsError> {
+            Ok(mock_device_attestation(
+                self.device_key,
+                &mock_device_certificate(self.root, self.device_key),
+                challenge,
+            ))
+        }
+    }
+
+    // device signing the same attestation whatever the challenge is
+    struct ReplayingDeviceSigner(DeviceAttestation);
+
+    impl DeviceSigner for ReplayingDeviceSigner {
+        fn sign(&self, _challenge: &[u8]) -> Result<DeviceAttestation, RDFProofsError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_device_binding() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let secret = b"SECRET";
+
+        let vc_pairs = vec![
+            VcPairString::new(
+                VC_1,
+                VC_PROOF_BOUND_1,
+                DISCLOSED_VC_1,
+                DISCLOSED_VC_PROOF_BOUND_1,
+            ),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        // key handle committing to the secret, registered with the device key
+        // certified under the attestation root
+        let (key_handle, blinding) = device_key_handle_string(&mut rng, secret).unwrap();
+        let signer = MockDeviceSigner {
+            device_key: "device-key-1",
+            root: "root-1",
+        };
+        let device_binding = DeviceBindingString {
+            key_handle: key_handle.clone(),
+            blinding: blinding.clone(),
+            signer: &signer,
+        };
+
+        let derive_with_device_binding =
+            |rng: &mut StdRng, device_binding: &DeviceBindingString| {
+                derive_proof_string(
+                    rng,
+                    &vc_pairs,
+                    &deanon_map,
+                    KEY_GRAPH,
+                    Some(challenge),
+                    None,
+                    DeriveOptionsString {
+                        secret: Some(secret),
+                        device_binding: Some(device_binding),
+                        ..Default::default()
+                    },
+                )
+            };
+        let derived_proof = derive_with_device_binding(&mut rng, &device_binding).unwrap();
+
+        let roots = vec!["root-1".to_string()];
+        let verify_with_verifier = |rng: &mut StdRng,
+                                    vp: &str,
+                                    verifier: Option<&dyn DeviceAttestationVerifier>,
+                                    roots: &Vec<String>| {
+            verify_proof_string(
+                rng,
+                vp,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    device_attestation_verifier: verifier,
+                    device_attestation_roots: Some(roots),
+                    ..Default::default()
+                },
+            )
+        };
+        let verifier = MockDeviceAttestationVerifier;
+        let verified = verify_with_verifier(&mut rng, &derived_proof, Some(&verifier), &roots);
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // key handle is re-randomized for each VP, never revealing the registered one
+        let get_key_handle_in_vp = |vp: &str| {
+            vp.lines()
+                .find(|line| line.contains("https://zkp-ld.org/security#keyHandle"))
+                .and_then(|line| line.split('"').nth(1))
+                .unwrap()
+                .to_string()
+        };
+        let another_derived_proof = derive_with_device_binding(&mut rng, &device_binding).unwrap();
+        let verified =
+            verify_with_verifier(&mut rng, &another_derived_proof, Some(&verifier), &roots);
+        assert!(verified.is_ok(), "{:?}", verified);
+        assert_ne!(
+            get_key_handle_in_vp(&derived_proof),
+            get_key_handle_in_vp(&another_derived_proof)
+        );
+        assert!(!derived_proof.contains(&key_handle));
+        assert!(!another_derived_proof.contains(&key_handle));
+
+        // device binding must be validated if exists, and vice versa
+        assert!(matches!(
+            verify_with_verifier(&mut rng, &derived_proof, None, &roots),
+            Err(RDFProofsError::InvalidDeviceBinding(_))
+        ));
+        let derived_proof_without_binding = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_with_verifier(
+                &mut rng,
+                &derived_proof_without_binding,
+                Some(&verifier),
+                &roots
+            ),
+            Err(RDFProofsError::InvalidDeviceBinding(_))
+        ));
+
+        // device key must be certified under the attestation roots pinned by verifier
+        for roots in [vec![], vec!["root-2".to_string()]] {
+            assert!(matches!(
+                verify_with_verifier(&mut rng, &derived_proof, Some(&verifier), &roots),
+                Err(RDFProofsError::InvalidDeviceBinding(_))
+            ));
+        }
+        let self_certified_signer = MockDeviceSigner {
+            device_key: "device-key-2",
+            root: "root-2",
+        };
+        let derived_proof = derive_with_device_binding(
+            &mut rng,
+            &DeviceBindingString {
+                signer: &self_certified_signer,
+                ..device_binding
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_with_verifier(&mut rng, &derived_proof, Some(&verifier), &roots),
+            Err(RDFProofsError::InvalidDeviceBinding(_))
+        ));
+
+        // attestation over the challenge for another key handle
+        let replaying_signer = ReplayingDeviceSigner(mock_device_attestation(
+            "device-key-1",
+            &mock_device_certificate("root-1", "device-key-1"),
+            &device_binding_challenge_string(challenge, &key_handle).unwrap(),
+        ));
+        let replayed_binding = DeviceBindingString {
+            key_handle: key_handle.clone(),
+            blinding: blinding.clone(),
+            signer: &replaying_signer,
+        };
+        let derived_proof = derive_with_device_binding(&mut rng, &replayed_binding).unwrap();
+        assert!(verify_with_verifier(&mut rng, &derived_proof, Some(&verifier), &roots).is_err());
+
+        // key handle not committing to the holder's secret
+        let (wrong_key_handle, wrong_blinding) =
+            device_key_handle_string(&mut rng, b"INVALID").unwrap();
+        let wrong_binding = DeviceBindingString {
+            key_handle: wrong_key_handle,
+            blinding: wrong_blinding,
+            signer: &signer,
+        };
+        assert!(matches!(
+            derive_with_device_binding(&mut rng, &wrong_binding),
+            Err(RDFProofsError::InvalidDeviceBinding(_))
+        ));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_with_invalid_secret_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let secret = b"INVALID";
+
+        let vc_pairs = vec![
+            VcPairString::new(
+                VC_1,
+                VC_PROOF_BOUND_1,
+                DISCLOSED_VC_1,
+                DISCLOSED_VC_PROOF_BOUND_1,
+            ),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            derived_proof,
+            Err(RDFProofsError::BBSPlus(
+                bbs_plus::prelude::BBSPlusError::InvalidSignature
+            ))
+        ))
+    }
+
+    #[test]
+    fn derive_and_verify_proof_string_without_secret_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(
+                VC_1,
+                VC_PROOF_BOUND_1,
+                DISCLOSED_VC_1,
+                DISCLOSED_VC_PROOF_BOUND_1,
+            ),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString::default(),
+        );
+        assert!(matches!(derived_proof, Err(RDFProofsError::MissingSecret)))
+    }
+
+    const VC_PROOF_WITHOUT_PROOFVALUE_1: &str = r#"
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+        "#;
+    const VC_3: &str = r#"
+        <did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+        <did:example:john> <http://schema.org/address> "Somewhere" .
+        <http://example.org/vcred/10> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer1> .
+        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#issuanceDate> "2022-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#expirationDate> "2025-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE_3: &str = r#"
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <http://purl.org/dc/terms/created> "2023-07-08T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer1#bls12_381-g2-pub001> .
+        "#;
+    const DISCLOSED_VC_3: &str = r#"
+        _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
+        _:e0 <http://schema.org/address> "Somewhere" .
+        _:e9 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+        _:e9 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+        _:e9 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer1> .
+        _:e9 <https://www.w3.org/2018/credentials#issuanceDate> "2022-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:e9 <https://www.w3.org/2018/credentials#expirationDate> "2025-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        "#;
+    const DISCLOSED_VC_PROOF_BOUND_3: &str = r#"
+        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-bound-signature-2023" .
+        _:b0 <http://purl.org/dc/terms/created> "2023-07-08T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer1#bls12_381-g2-pub001> .
+        "#;
+
+    #[test]
+    fn derive_and_verify_two_bound_credentials_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let secret = b"SECRET";
+
+        let challenge1 = "challenge1";
+        let request1 =
+            request_blind_sign_string(&mut rng, secret, Some(challenge1), None, None, None)
+                .unwrap();
+        let verified1 = verify_blind_sign_request_string(
+            &mut rng,
+            &request1.commitment,
+            &request1.pok_for_commitment.unwrap(),
+            Some(challenge1),
+            None,
+            None,
+        );
+        assert!(verified1.is_ok());
+
+        let blinded_proof1 = blind_sign_string(
+            &mut rng,
+            &request1.commitment,
+            VC_1,
+            VC_PROOF_WITHOUT_PROOFVALUE_1,
+            KEY_GRAPH,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof1 = unblind_string(VC_1, &blinded_proof1, &request1.blinding).unwrap();
+        let result1 = blind_verify_string(secret, VC_1, &proof1, KEY_GRAPH);
+        assert!(result1.is_ok(), "{:?}", result1);
+
+        let challenge3 = "challenge3";
+        let request3 =
+            request_blind_sign_string(&mut rng, secret, Some(challenge3), None, None, None)
+                .unwrap();
+        let verified3 = verify_blind_sign_request_string(
+            &mut rng,
+            &request3.commitment,
+            &request3.pok_for_commitment.unwrap(),
+            Some(challenge3),
+            None,
+            None,
+        );
+        assert!(verified3.is_ok());
+        let blinded_proof3 = blind_sign_string(
+            &mut rng,
+            &request3.commitment,
+            VC_3,
+            VC_PROOF_WITHOUT_PROOFVALUE_3,
+            KEY_GRAPH,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof3 = unblind_string(VC_3, &blinded_proof3, &request3.blinding).unwrap();
+        let result3 = blind_verify_string(secret, VC_3, &proof3, KEY_GRAPH);
+        assert!(result3.is_ok(), "{:?}", result3);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, &proof1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_BOUND_1),
+            VcPairString::new(VC_3, &proof3, DISCLOSED_VC_3, DISCLOSED_VC_PROOF_BOUND_3),
+        ];
+
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:e9".to_string(),
+            "<http://example.org/vcred/10>".to_string(),
+        );
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof: {}", derived_proof);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
     }
-    fn get_example_deanon_map_with_hidden_literal() -> HashMap<NamedOrBlankNode, Term> {
-        get_deanon_map_from_string(&&get_example_deanon_map_string_with_hidden_literal()).unwrap()
+
+    #[test]
+    fn derive_and_verify_two_bound_credentials_with_different_secrets_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let secret1 = b"SECRET1";
+        let challenge1 = "challenge1";
+        let request1 =
+            request_blind_sign_string(&mut rng, secret1, Some(challenge1), None, None, None)
+                .unwrap();
+        let verified1 = verify_blind_sign_request_string(
+            &mut rng,
+            &request1.commitment,
+            &request1.pok_for_commitment.unwrap(),
+            Some(challenge1),
+            None,
+            None,
+        );
+        assert!(verified1.is_ok());
+
+        let blinded_proof1 = blind_sign_string(
+            &mut rng,
+            &request1.commitment,
+            VC_1,
+            VC_PROOF_WITHOUT_PROOFVALUE_1,
+            KEY_GRAPH,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof1 = unblind_string(VC_1, &blinded_proof1, &request1.blinding).unwrap();
+        let result1 = blind_verify_string(secret1, VC_1, &proof1, KEY_GRAPH);
+        assert!(result1.is_ok(), "{:?}", result1);
+
+        let secret3 = b"SECRET3";
+        let challenge3 = "challenge3";
+        let request3 =
+            request_blind_sign_string(&mut rng, secret3, Some(challenge3), None, None, None)
+                .unwrap();
+        let verified3 = verify_blind_sign_request_string(
+            &mut rng,
+            &request3.commitment,
+            &request3.pok_for_commitment.unwrap(),
+            Some(challenge3),
+            None,
+            None,
+        );
+        assert!(verified3.is_ok());
+        let blinded_proof3 = blind_sign_string(
+            &mut rng,
+            &request3.commitment,
+            VC_3,
+            VC_PROOF_WITHOUT_PROOFVALUE_3,
+            KEY_GRAPH,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof3 = unblind_string(VC_3, &blinded_proof3, &request3.blinding).unwrap();
+        let result3 = blind_verify_string(secret3, VC_3, &proof3, KEY_GRAPH);
+        assert!(result3.is_ok(), "{:?}", result3);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, &proof1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_BOUND_1),
+            VcPairString::new(VC_3, &proof3, DISCLOSED_VC_3, DISCLOSED_VC_PROOF_BOUND_3),
+        ];
+
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:e9".to_string(),
+            "<http://example.org/vcred/10>".to_string(),
+        );
+
+        let challenge = "abcde";
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                secret: Some(secret1),
+                ..Default::default()
+            },
+        );
+        assert!(derived_proof.is_err(), "{:?}", derived_proof)
     }
 
-    #[test]
-    fn derive_and_verify_proof_with_hidden_literals() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
-        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+    #[test]
+    fn derive_and_verify_two_bound_credentials_with_credential_secrets_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        // VCs bound to distinct secrets per issuer
+        let bind = |rng: &mut StdRng, secret: &[u8], vc: &str, proof_config: &str| {
+            let request =
+                request_blind_sign_string(rng, secret, None, Some(true), None, None).unwrap();
+            let blinded_proof = blind_sign_string(
+                rng,
+                &request.commitment,
+                vc,
+                proof_config,
+                KEY_GRAPH,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            unblind_string(vc, &blinded_proof, &request.blinding).unwrap()
+        };
+        let secret1 = b"SECRET1";
+        let secret3 = b"SECRET3";
+        let proof1 = bind(&mut rng, secret1, VC_1, VC_PROOF_WITHOUT_PROOFVALUE_1);
+        let proof3 = bind(&mut rng, secret3, VC_3, VC_PROOF_WITHOUT_PROOFVALUE_3);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, &proof1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_BOUND_1),
+            VcPairString::new(VC_3, &proof3, DISCLOSED_VC_3, DISCLOSED_VC_PROOF_BOUND_3),
+        ];
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:e9".to_string(),
+            "<http://example.org/vcred/10>".to_string(),
+        );
+        let challenge = "abcde";
+        let derive = |rng: &mut StdRng, credential_secrets: &CredentialSecrets| {
+            derive_proof_string(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                DeriveOptionsString {
+                    secret: Some(secret1),
+                    credential_secrets: Some(credential_secrets),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // the second VC is bound to the secret given by index, or by issuer
+        let mut by_index = CredentialSecrets::new();
+        by_index.insert(1, secret3);
+        let mut by_issuer = CredentialSecrets::new();
+        by_issuer
+            .insert_for_issuer_string("did:example:issuer1", secret3)
+            .unwrap();
+        let allowed_secret_groups =
+            vec![vec!["did:example:issuer1#bls12_381-g2-pub001".to_string()]];
+        for credential_secrets in [&by_index, &by_issuer] {
+            let derived_proof = derive(&mut rng, credential_secrets).unwrap();
+            println!("derived_proof: {}", derived_proof);
+            assert!(derived_proof.contains("https://zkp-ld.org/security#secretGroup"));
+            let verified = verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    allowed_secret_groups: Some(&allowed_secret_groups),
+                    ..Default::default()
+                },
+            );
+            assert!(verified.is_ok(), "{:?}", verified);
+
+            // the accepted group consists of the VC issued by the second issuer
+            let VerificationResult {
+                secret_groups,
+                disclosed_documents,
+                ..
+            } = verified.unwrap();
+            assert_eq!(secret_groups.len(), 1);
+            let group = secret_groups[0].iter().collect::<Vec<_>>();
+            assert_eq!(group.len(), 1);
+            assert!(disclosed_documents[*group[0]]
+                .to_string()
+                .contains("<did:example:issuer1>"));
+        }
 
-        let mut deanon_map = get_example_deanon_map();
-        deanon_map.extend(get_example_deanon_map_with_hidden_literal());
+        // per-credential secret must be the one bound to the VC
+        let mut wrong = CredentialSecrets::new();
+        wrong.insert(1, secret1.as_slice());
+        wrong.insert(0, secret3.as_slice());
+        assert!(derive(&mut rng, &wrong).is_err());
 
-        let vc_doc_1 = get_graph_from_ntriples(VC_1).unwrap();
-        let vc_proof_1 = get_graph_from_ntriples(VC_PROOF_1).unwrap();
-        let vc_1 = VerifiableCredential::new(vc_doc_1, vc_proof_1);
+        // VC index must be in range
+        let mut out_of_range = CredentialSecrets::new();
+        out_of_range.insert(2, secret3);
+        assert!(matches!(
+            derive(&mut rng, &out_of_range),
+            Err(RDFProofsError::InvalidCredentialSecrets(_))
+        ));
+    }
 
-        let disclosed_vc_doc_1 =
-            get_graph_from_ntriples(DISCLOSED_VC_1_WITH_HIDDEN_LITERALS).unwrap();
-        let disclosed_vc_proof_1 = get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap();
-        let disclosed_1 = VerifiableCredential::new(disclosed_vc_doc_1, disclosed_vc_proof_1);
+    #[test]
+    fn derive_and_verify_two_bound_credentials_with_unallowed_secret_groups_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
 
-        let vc_with_disclosed_1 = VcPair::new(vc_1, disclosed_1);
-        let vcs = vec![vc_with_disclosed_1];
+        let bind = |rng: &mut StdRng, secret: &[u8], vc: &str, proof_config: &str| {
+            let request =
+                request_blind_sign_string(rng, secret, None, Some(true), None, None).unwrap();
+            let blinded_proof = blind_sign_string(
+                rng,
+                &request.commitment,
+                vc,
+                proof_config,
+                KEY_GRAPH,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            unblind_string(vc, &blinded_proof, &request.blinding).unwrap()
+        };
+        let secret1 = b"SECRET1";
+        let secret3 = b"SECRET3";
+        let proof1 = bind(&mut rng, secret1, VC_1, VC_PROOF_WITHOUT_PROOFVALUE_1);
+        let proof3 = bind(&mut rng, secret3, VC_3, VC_PROOF_WITHOUT_PROOFVALUE_3);
 
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, &proof1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_BOUND_1),
+            VcPairString::new(VC_3, &proof3, DISCLOSED_VC_3, DISCLOSED_VC_PROOF_BOUND_3),
+        ];
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.insert(
+            "_:e9".to_string(),
+            "<http://example.org/vcred/10>".to_string(),
+        );
         let challenge = "abcde";
-
-        let derived_proof = derive_proof(
+        let mut credential_secrets = CredentialSecrets::new();
+        credential_secrets.insert(1, secret3);
+        let derived_proof = derive_proof_string(
             &mut rng,
-            &vcs,
+            &vc_pairs,
             &deanon_map,
-            &key_graph,
+            KEY_GRAPH,
             Some(challenge),
             None,
-            None,
-            None,
-            None,
-            vec![],
-            HashMap::new(),
-            None,
+            DeriveOptionsString {
+                secret: Some(secret1),
+                credential_secrets: Some(&credential_secrets),
+                ..Default::default()
+            },
         )
         .unwrap();
-        println!("derived_proof: {}", rdf_canon::serialize(&derived_proof));
 
-        let verified = verify_proof(
-            &mut rng,
-            &derived_proof,
-            &key_graph,
-            Some(challenge),
+        // secret groups declared by holder are rejected unless verifier allows them,
+        // so that bound VCs are proven to share a single secret by default
+        let not_allowed = vec![
             None,
-            HashMap::new(),
-            None,
-        );
-        assert!(verified.is_ok(), "{:?}", verified)
+            Some(vec![]),
+            Some(vec![vec![
+                "did:example:issuer0#bls12_381-g2-pub001".to_string()
+            ]]),
+        ];
+        for allowed_secret_groups in &not_allowed {
+            let verified = verify_proof_string(
+                &mut rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    allowed_secret_groups: allowed_secret_groups.as_ref(),
+                    ..Default::default()
+                },
+            );
+            assert!(
+                matches!(verified, Err(RDFProofsError::InvalidCredentialSecrets(_))),
+                "{:?}",
+                verified
+            );
+        }
     }
 
     #[test]
-    fn derive_and_verify_proof_string_with_hidden_literals() {
+    fn derive_and_verify_proof_with_commitment_success() {
         let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
 
+        let secret = b"SECRET";
+
+        let challenge1 = "challenge1";
+        let request1 =
+            request_blind_sign_string(&mut rng, secret, Some(challenge1), None, None, None)
+                .unwrap();
+        let verified1 = verify_blind_sign_request_string(
+            &mut rng,
+            &request1.commitment,
+            &request1.pok_for_commitment.unwrap(),
+            Some(challenge1),
+            None,
+            None,
+        );
+        assert!(verified1.is_ok());
+
+        let blinded_proof1 = blind_sign_string(
+            &mut rng,
+            &request1.commitment,
+            VC_1,
+            VC_PROOF_WITHOUT_PROOFVALUE_1,
+            KEY_GRAPH,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof1 = unblind_string(VC_1, &blinded_proof1, &request1.blinding).unwrap();
+        let result1 = blind_verify_string(secret, VC_1, &proof1, KEY_GRAPH);
+        assert!(result1.is_ok(), "{:?}", result1);
+
         let vc_pairs = vec![VcPairString::new(
             VC_1,
-            VC_PROOF_1,
+            &proof1,
             DISCLOSED_VC_1,
-            DISCLOSED_VC_PROOF_1,
+            DISCLOSED_VC_PROOF_BOUND_1,
         )];
 
-        let mut deanon_map = get_example_deanon_map_string();
-        deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
+        let deanon_map = get_example_deanon_map_string();
+
+        let blind_sign_request =
+            request_blind_sign_string(&mut rng, secret, None, Some(true), None, None).unwrap();
 
         let challenge = "abcde";
 
@@ -2400,12 +7825,11 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                blind_sign_request: Some(&blind_sign_request),
+                ..Default::default()
+            },
         )
         .unwrap();
 
@@ -2415,130 +7839,149 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            None,
-            None,
+            VerifyOptionsString::default(),
         );
-
         assert!(verified.is_ok(), "{:?}", verified)
     }
 
     #[test]
-    fn derive_proof_failed_invalid_vc() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
-        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
-
-        let vc_ntriples = r#"
-    <did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
-    <did:example:john> <http://schema.org/name> "**********************************" .  # modified
-    <did:example:john> <http://example.org/vocab/isPatientOf> _:b0 .
-    <did:example:john> <http://schema.org/worksFor> _:b1 .
-    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/Vaccination> .
-    _:b0 <http://example.org/vocab/lotNumber> "0000001" .
-    _:b0 <http://example.org/vocab/vaccinationDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-    _:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
-    _:b0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/b> .
-    _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Organization> .
-    _:b1 <http://schema.org/name> "ABC inc." .
-    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
-    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
-    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
-    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-    "#;
+    fn derive_and_verify_proof_with_secret_rotation_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
 
-        let vc_doc = get_graph_from_ntriples(vc_ntriples).unwrap();
-        let vc_proof = get_graph_from_ntriples(VC_PROOF_1).unwrap();
-        let vc = VerifiableCredential::new(vc_doc, vc_proof);
+        let old_secret = b"SECRET";
+        let new_secret = b"NEW_SECRET";
 
-        let disclosed_vc_doc = get_graph_from_ntriples(DISCLOSED_VC_1).unwrap();
-        let disclosed_vc_proof = get_graph_from_ntriples(DISCLOSED_VC_PROOF_1).unwrap();
-        let disclosed = VerifiableCredential::new(disclosed_vc_doc, disclosed_vc_proof);
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            VC_PROOF_BOUND_1,
+            DISCLOSED_VC_1,
+            DISCLOSED_VC_PROOF_BOUND_1,
+        )];
 
-        let vc_with_disclosed = VcPair::new(vc, disclosed);
-        let vcs = vec![vc_with_disclosed];
+        let deanon_map = get_example_deanon_map_string();
 
-        let deanon_map = get_example_deanon_map();
+        // request for re-binding credentials to the new secret
+        let rotation_request =
+            request_blind_sign_string(&mut rng, new_secret, None, Some(true), None, None).unwrap();
+        let new_blinding = rotation_request.blinding.clone();
 
         let challenge = "abcde";
 
-        let derived_proof = derive_proof(
+        let derived_proof = derive_proof_string(
             &mut rng,
-            &vcs,
+            &vc_pairs,
             &deanon_map,
-            &key_graph,
+            KEY_GRAPH,
             Some(challenge),
             None,
+            DeriveOptionsString {
+                secret: Some(old_secret),
+                blind_sign_request: Some(&rotation_request),
+                new_secret: Some(new_secret),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derived_proof: {}", derived_proof);
+
+        // issuer verifies VP and gets the commitment to the new secret
+        let new_commitment = verify_secret_rotation_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
             None,
+        )
+        .unwrap();
+
+        // issuer re-binds the credential to the new secret
+        let blinded_proof = blind_sign_string(
+            &mut rng,
+            &new_commitment,
+            VC_1,
+            VC_PROOF_WITHOUT_PROOFVALUE_1,
+            KEY_GRAPH,
             None,
             None,
-            vec![],
-            HashMap::new(),
             None,
-        );
-        assert!(matches!(
-            derived_proof,
-            Err(RDFProofsError::BBSPlus(
-                bbs_plus::prelude::BBSPlusError::InvalidSignature
-            ))
-        ))
+        )
+        .unwrap();
+        let proof = unblind_string(VC_1, &blinded_proof, &new_blinding).unwrap();
+        let result = blind_verify_string(new_secret, VC_1, &proof, KEY_GRAPH);
+        assert!(result.is_ok(), "{:?}", result);
+        let result = blind_verify_string(old_secret, VC_1, &proof, KEY_GRAPH);
+        assert!(result.is_err());
     }
 
     #[test]
-    fn derive_proof_string_failed_invalid_vc() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+    fn derive_proof_with_secret_rotation_without_request_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
 
         let vc_pairs = vec![VcPairString::new(
-            VC_1_MODIFIED,
-            VC_PROOF_1,
+            VC_1,
+            VC_PROOF_BOUND_1,
             DISCLOSED_VC_1,
-            DISCLOSED_VC_PROOF_1,
+            DISCLOSED_VC_PROOF_BOUND_1,
         )];
 
         let deanon_map = get_example_deanon_map_string();
 
-        let challenge = "abcde";
-
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
             KEY_GRAPH,
-            Some(challenge),
-            None,
-            None,
-            None,
-            None,
-            None,
             None,
             None,
+            DeriveOptionsString {
+                secret: Some(b"SECRET"),
+                new_secret: Some(b"NEW_SECRET"),
+                ..Default::default()
+            },
         );
-
         assert!(matches!(
             derived_proof,
-            Err(RDFProofsError::BBSPlus(
-                bbs_plus::prelude::BBSPlusError::InvalidSignature
-            ))
+            Err(RDFProofsError::MissingSecretRotationRequest)
         ))
     }
 
-    const VC_PROOF_BOUND_1: &str = r#"
-        _:b0 <https://w3id.org/security#proofValue> "utXwiR3cqE_vytaKRk1jO5bijPewZ8Vx67WqHBjJ1TAN8BoEnhdu7zXyZ1WTYuLHqAWQCF5cBR1F0h3FXGsm2xh7Fafg49VG-Slte0XnTgDzpRqn0nqhO4I57s-b3TPVbA_t5uyJnGllyB6QcwVtRQA"^^<https://w3id.org/security#multibase> .
-        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-bound-signature-2023" .
-        _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
-        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
-        "#;
-    const DISCLOSED_VC_PROOF_BOUND_1: &str = r#"
-        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-bound-signature-2023" .
-        _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
-        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
-        "#;
+    #[test]
+    fn derive_and_verify_proof_with_only_blind_sign_request_success() {
+        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+
+        let secret = b"SECRET";
+        let blind_sign_request =
+            request_blind_sign_string(&mut rng, secret, None, Some(true), None, None).unwrap();
+
+        let challenge = "abcde";
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vec![],
+            &HashMap::new(),
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                blind_sign_request: Some(&blind_sign_request),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified)
+    }
 
     #[test]
-    fn derive_and_verify_proof_string_with_secret_success() {
+    fn derive_and_verify_proof_with_ppid_success() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
         let secret = b"SECRET";
@@ -2556,6 +7999,7 @@ mod tests {
         let deanon_map = get_example_deanon_map_string();
 
         let challenge = "abcde";
+        let domain = "example.org";
 
         let derived_proof = derive_proof_string(
             &mut rng,
@@ -2563,33 +8007,32 @@ mod tests {
             &deanon_map,
             KEY_GRAPH,
             Some(challenge),
-            None,
-            Some(secret),
-            None,
-            None,
-            None,
-            None,
-            None,
+            Some(domain),
+            DeriveOptionsString {
+                secret: Some(secret),
+                with_ppid: Some(true),
+                ..Default::default()
+            },
         )
         .unwrap();
+        println!("derived_proof:\n{}", derived_proof);
 
         let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
             KEY_GRAPH,
             Some(challenge),
-            None,
-            None,
-            None,
+            Some(domain),
+            VerifyOptionsString::default(),
         );
         assert!(verified.is_ok(), "{:?}", verified)
     }
 
     #[test]
-    fn derive_and_verify_proof_string_with_invalid_secret_failure() {
+    fn derive_and_verify_proof_with_ppid_and_secret_commitment_success() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
-        let secret = b"INVALID";
+        let secret = b"SECRET";
 
         let vc_pairs = vec![
             VcPairString::new(
@@ -2603,7 +8046,11 @@ mod tests {
 
         let deanon_map = get_example_deanon_map_string();
 
+        let blind_sign_request =
+            request_blind_sign_string(&mut rng, secret, None, Some(true), None, None).unwrap();
+
         let challenge = "abcde";
+        let domain = "example.org";
 
         let derived_proof = derive_proof_string(
             &mut rng,
@@ -2611,26 +8058,33 @@ mod tests {
             &deanon_map,
             KEY_GRAPH,
             Some(challenge),
-            None,
-            Some(secret),
-            None,
-            None,
-            None,
-            None,
-            None,
+            Some(domain),
+            DeriveOptionsString {
+                secret: Some(secret),
+                blind_sign_request: Some(&blind_sign_request),
+                with_ppid: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
+            Some(domain),
+            VerifyOptionsString::default(),
         );
-        assert!(matches!(
-            derived_proof,
-            Err(RDFProofsError::BBSPlus(
-                bbs_plus::prelude::BBSPlusError::InvalidSignature
-            ))
-        ))
+        assert!(verified.is_ok(), "{:?}", verified)
     }
 
     #[test]
-    fn derive_and_verify_proof_string_without_secret_failure() {
+    fn derive_and_verify_revocable_secret() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
+        let secret = b"SECRET";
+
         let vc_pairs = vec![
             VcPairString::new(
                 VC_1,
@@ -2645,6 +8099,8 @@ mod tests {
 
         let challenge = "abcde";
 
+        let (opener_pub_key, _) = elliptic_elgamal_keygen(&mut rng).unwrap();
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -2652,115 +8108,53 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                opener_pub_keys: Some(&vec![opener_pub_key]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        print!("derived_proof: {}", derived_proof);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
             None,
-            None,
-            None,
-            None,
-            None,
-            None,
+            VerifyOptionsString {
+                opener_pub_keys: Some(&vec![opener_pub_key]),
+                ..Default::default()
+            },
         );
-        assert!(matches!(derived_proof, Err(RDFProofsError::MissingSecret)))
+        assert!(verified.is_ok(), "{:?}", verified)
     }
 
-    const VC_PROOF_WITHOUT_PROOFVALUE_1: &str = r#"
-        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-        _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
-        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
-        "#;
-    const VC_3: &str = r#"
-        <did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
-        <did:example:john> <http://schema.org/address> "Somewhere" .
-        <http://example.org/vcred/10> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
-        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
-        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer1> .
-        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#issuanceDate> "2022-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        <http://example.org/vcred/10> <https://www.w3.org/2018/credentials#expirationDate> "2025-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        "#;
-    const VC_PROOF_WITHOUT_PROOFVALUE_3: &str = r#"
-        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-        _:b0 <http://purl.org/dc/terms/created> "2023-07-08T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
-        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer1#bls12_381-g2-pub001> .
-        "#;
-    const DISCLOSED_VC_3: &str = r#"
-        _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .
-        _:e0 <http://schema.org/address> "Somewhere" .
-        _:e9 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
-        _:e9 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
-        _:e9 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer1> .
-        _:e9 <https://www.w3.org/2018/credentials#issuanceDate> "2022-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        _:e9 <https://www.w3.org/2018/credentials#expirationDate> "2025-07-08T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        "#;
-    const DISCLOSED_VC_PROOF_BOUND_3: &str = r#"
-        _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-        _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-bound-signature-2023" .
-        _:b0 <http://purl.org/dc/terms/created> "2023-07-08T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
-        _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
-        _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer1#bls12_381-g2-pub001> .
-        "#;
-
     #[test]
-    fn derive_and_verify_two_bound_credentials_success() {
+    fn derive_and_verify_revocable_secret_with_multiple_openers() {
         let mut rng = StdRng::seed_from_u64(0u64);
-        let secret = b"SECRET";
-
-        let challenge1 = "challenge1";
-        let request1 = request_blind_sign_string(&mut rng, secret, Some(challenge1), None).unwrap();
-        let verified1 = verify_blind_sign_request_string(
-            &mut rng,
-            &request1.commitment,
-            &request1.pok_for_commitment.unwrap(),
-            Some(challenge1),
-        );
-        assert!(verified1.is_ok());
-
-        let blinded_proof1 = blind_sign_string(
-            &mut rng,
-            &request1.commitment,
-            VC_1,
-            VC_PROOF_WITHOUT_PROOFVALUE_1,
-            KEY_GRAPH,
-        )
-        .unwrap();
-        let proof1 = unblind_string(VC_1, &blinded_proof1, &request1.blinding).unwrap();
-        let result1 = blind_verify_string(secret, VC_1, &proof1, KEY_GRAPH);
-        assert!(result1.is_ok(), "{:?}", result1);
 
-        let challenge3 = "challenge3";
-        let request3 = request_blind_sign_string(&mut rng, secret, Some(challenge3), None).unwrap();
-        let verified3 = verify_blind_sign_request_string(
-            &mut rng,
-            &request3.commitment,
-            &request3.pok_for_commitment.unwrap(),
-            Some(challenge3),
-        );
-        assert!(verified3.is_ok());
-        let blinded_proof3 = blind_sign_string(
-            &mut rng,
-            &request3.commitment,
-            VC_3,
-            VC_PROOF_WITHOUT_PROOFVALUE_3,
-            KEY_GRAPH,
-        )
-        .unwrap();
-        let proof3 = unblind_string(VC_3, &blinded_proof3, &request3.blinding).unwrap();
-        let result3 = blind_verify_string(secret, VC_3, &proof3, KEY_GRAPH);
-        assert!(result3.is_ok(), "{:?}", result3);
+        let secret = b"SECRET";
 
         let vc_pairs = vec![
-            VcPairString::new(VC_1, &proof1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_BOUND_1),
-            VcPairString::new(VC_3, &proof3, DISCLOSED_VC_3, DISCLOSED_VC_PROOF_BOUND_3),
+            VcPairString::new(
+                VC_1,
+                VC_PROOF_BOUND_1,
+                DISCLOSED_VC_1,
+                DISCLOSED_VC_PROOF_BOUND_1,
+            ),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
         ];
 
-        let mut deanon_map = get_example_deanon_map_string();
-        deanon_map.insert(
-            "_:e9".to_string(),
-            "<http://example.org/vcred/10>".to_string(),
-        );
+        let deanon_map = get_example_deanon_map_string();
 
         let challenge = "abcde";
 
+        let (opener_pub_keys, opener_secret_keys): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| elliptic_elgamal_keygen(&mut rng).unwrap())
+            .unzip();
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -2768,15 +8162,14 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            Some(secret),
-            None,
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString {
+                secret: Some(secret),
+                opener_pub_keys: Some(&opener_pub_keys),
+                ..Default::default()
+            },
         )
         .unwrap();
-        println!("derived_proof: {}", derived_proof);
+        print!("derived_proof: {}", derived_proof);
 
         let verified = verify_proof_string(
             &mut rng,
@@ -2784,76 +8177,71 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
+            VerifyOptionsString {
+                opener_pub_keys: Some(&opener_pub_keys),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // the ciphertexts must be verified against all the openers
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            Some(challenge),
             None,
-            None,
+            VerifyOptionsString {
+                opener_pub_keys: Some(&opener_pub_keys[..2].to_vec()),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(verified, Err(RDFProofsError::InvalidEncryptedUid)));
+
+        // all the openers jointly deanonymize the holder
+        let vp = get_dataset_from_nquads(&derived_proof).unwrap();
+        let encrypted_uid = vp
+            .quads_for_predicate(ENCRYPTED_UID)
+            .find_map(|q| match q.object {
+                TermRef::Literal(v) => Some(v.value().to_string()),
+                _ => None,
+            })
+            .unwrap();
+        let cipher_texts: Vec<ElGamalCiphertext> = multibase_to_ark(&encrypted_uid).unwrap();
+        let shares: Vec<_> = opener_secret_keys
+            .iter()
+            .zip(&cipher_texts)
+            .map(|(sk, cipher_text)| elliptic_elgamal_decrypt(sk, cipher_text).unwrap())
+            .collect();
+        assert_eq!(
+            ark_to_base64url(&combine_decrypted_shares(&shares)).unwrap(),
+            get_encrypted_uid(&secret.to_vec(), &generate_params(1).h[0]).unwrap()
         );
-        assert!(verified.is_ok(), "{:?}", verified)
     }
 
     #[test]
-    fn derive_and_verify_two_bound_credentials_with_different_secrets_failure() {
+    fn derive_and_verify_proof_with_attribute_encryption() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
-        let secret1 = b"SECRET1";
-        let challenge1 = "challenge1";
-        let request1 =
-            request_blind_sign_string(&mut rng, secret1, Some(challenge1), None).unwrap();
-        let verified1 = verify_blind_sign_request_string(
-            &mut rng,
-            &request1.commitment,
-            &request1.pok_for_commitment.unwrap(),
-            Some(challenge1),
-        );
-        assert!(verified1.is_ok());
-
-        let blinded_proof1 = blind_sign_string(
-            &mut rng,
-            &request1.commitment,
-            VC_1,
-            VC_PROOF_WITHOUT_PROOFVALUE_1,
-            KEY_GRAPH,
-        )
-        .unwrap();
-        let proof1 = unblind_string(VC_1, &blinded_proof1, &request1.blinding).unwrap();
-        let result1 = blind_verify_string(secret1, VC_1, &proof1, KEY_GRAPH);
-        assert!(result1.is_ok(), "{:?}", result1);
-
-        let secret3 = b"SECRET3";
-        let challenge3 = "challenge3";
-        let request3 =
-            request_blind_sign_string(&mut rng, secret3, Some(challenge3), None).unwrap();
-        let verified3 = verify_blind_sign_request_string(
-            &mut rng,
-            &request3.commitment,
-            &request3.pok_for_commitment.unwrap(),
-            Some(challenge3),
-        );
-        assert!(verified3.is_ok());
-        let blinded_proof3 = blind_sign_string(
-            &mut rng,
-            &request3.commitment,
-            VC_3,
-            VC_PROOF_WITHOUT_PROOFVALUE_3,
-            KEY_GRAPH,
-        )
-        .unwrap();
-        let proof3 = unblind_string(VC_3, &blinded_proof3, &request3.blinding).unwrap();
-        let result3 = blind_verify_string(secret3, VC_3, &proof3, KEY_GRAPH);
-        assert!(result3.is_ok(), "{:?}", result3);
-
         let vc_pairs = vec![
-            VcPairString::new(VC_1, &proof1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_BOUND_1),
-            VcPairString::new(VC_3, &proof3, DISCLOSED_VC_3, DISCLOSED_VC_PROOF_BOUND_3),
-        ];
-
-        let mut deanon_map = get_example_deanon_map_string();
-        deanon_map.insert(
-            "_:e9".to_string(),
-            "<http://example.org/vcred/10>".to_string(),
-        );
-
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+        let deanon_map = get_example_deanon_map_string();
         let challenge = "abcde";
 
+        // hidden subject ID is encrypted to verifier's key and authorizer's key
+        let (secret_keys, pub_keys): (Vec<_>, Vec<_>) = (0..2)
+            .map(|_| {
+                let (pk, sk) = elliptic_elgamal_keygen(&mut rng).unwrap();
+                (sk, ark_to_base64url(&pk).unwrap())
+            })
+            .unzip();
+        let attribute_encryptions = vec![AttributeEncryptionRequestString {
+            value: "_:e0".to_string(),
+            pub_keys: pub_keys.clone(),
+        }];
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -2861,58 +8249,168 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            Some(secret1),
-            None,
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString {
+                attribute_encryptions: Some(&attribute_encryptions),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let verify = |rng: &mut StdRng, keys: &Vec<Vec<String>>| {
+            verify_proof_string(
+                rng,
+                &derived_proof,
+                KEY_GRAPH,
+                Some(challenge),
+                None,
+                VerifyOptionsString {
+                    attribute_encryption_keys: Some(keys),
+                    ..Default::default()
+                },
+            )
+        };
+        let verified = verify(&mut rng, &vec![pub_keys.clone()]).unwrap();
+        assert_eq!(verified.encrypted_attributes.len(), 1);
+
+        // both keys are needed to decrypt the hidden term
+        let shares: Vec<_> = secret_keys
+            .iter()
+            .zip(&verified.encrypted_attributes[0].cipher_texts)
+            .map(|(sk, cipher_text)| elliptic_elgamal_decrypt(sk, cipher_text).unwrap())
+            .collect();
+        assert_eq!(
+            ark_to_base64url(&combine_decrypted_shares(&shares)).unwrap(),
+            get_encrypted_attribute_string("<did:example:john>").unwrap()
         );
-        assert!(derived_proof.is_err(), "{:?}", derived_proof)
+
+        // hidden term must be encrypted to the keys expected by verifier
+        assert!(matches!(
+            verify(&mut rng, &vec![pub_keys[..1].to_vec()]),
+            Err(RDFProofsError::MissingAttributeEncryption)
+        ));
+        assert!(matches!(
+            verify(&mut rng, &vec![]),
+            Err(RDFProofsError::MissingAttributeEncryption)
+        ));
     }
 
     #[test]
-    fn derive_and_verify_proof_with_commitment_success() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+    fn map_triple_positions_for_large_credential() {
+        let predicate = NamedNode::new_unchecked("http://example.org/vocab/value");
+        let original: Vec<Triple> = (0..1000)
+            .map(|i| {
+                Triple::new(
+                    NamedNode::new_unchecked(format!("http://example.org/s/{}", i)),
+                    predicate.clone(),
+                    Literal::new_simple_literal(i.to_string()),
+                )
+            })
+            .collect();
+        let disclosed: Vec<Triple> = original.iter().rev().step_by(2).cloned().collect();
+
+        let positions = map_triple_positions(&disclosed, &original).unwrap();
+
+        let expected: Vec<usize> = (0..1000).rev().step_by(2).collect();
+        assert_eq!(positions, expected);
+
+        // duplicated triples are mapped to their positions in order
+        let mut original_with_duplicates = original.clone();
+        original_with_duplicates.push(original[10].clone());
+        let positions =
+            map_triple_positions(&[original[10].clone()], &original_with_duplicates).unwrap();
+        assert_eq!(positions, vec![10]);
+        let positions = map_triple_positions(
+            &[original[10].clone(), original[10].clone()],
+            &original_with_duplicates,
+        )
+        .unwrap();
+        assert_eq!(positions, vec![10, 1000]);
 
-        let secret = b"SECRET";
+        // disclosed duplicates outnumbering those in the original cannot be mapped
+        assert!(matches!(
+            map_triple_positions(&[original[10].clone(), original[10].clone()], &original),
+            Err(RDFProofsError::DuplicatedDisclosedTriple(_))
+        ));
 
-        let challenge1 = "challenge1";
-        let request1 = request_blind_sign_string(&mut rng, secret, Some(challenge1), None).unwrap();
-        let verified1 = verify_blind_sign_request_string(
-            &mut rng,
-            &request1.commitment,
-            &request1.pok_for_commitment.unwrap(),
-            Some(challenge1),
+        // triples not in the original cannot be mapped
+        let unknown = Triple::new(
+            NamedNode::new_unchecked("http://example.org/s/unknown"),
+            predicate,
+            Literal::new_simple_literal("unknown"),
         );
-        assert!(verified1.is_ok());
+        assert!(matches!(
+            map_triple_positions(&[unknown], &original),
+            Err(RDFProofsError::DisclosedVCIsNotSubsetOfOriginalVC)
+        ));
+    }
 
-        let blinded_proof1 = blind_sign_string(
-            &mut rng,
-            &request1.commitment,
-            VC_1,
-            VC_PROOF_WITHOUT_PROOFVALUE_1,
-            KEY_GRAPH,
-        )
-        .unwrap();
-        let proof1 = unblind_string(VC_1, &blinded_proof1, &request1.blinding).unwrap();
-        let result1 = blind_verify_string(secret, VC_1, &proof1, KEY_GRAPH);
-        assert!(result1.is_ok(), "{:?}", result1);
+    #[test]
+    fn derive_proof_with_duplicated_triple_after_deanonymization_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
 
+        // `_:e0` is deanonymized into `<did:example:john>`, which yields the same triple twice
+        let disclosed_vc_1 = format!(
+            "{}<did:example:john> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .\n",
+            DISCLOSED_VC_1
+        );
         let vc_pairs = vec![VcPairString::new(
             VC_1,
-            &proof1,
-            DISCLOSED_VC_1,
-            DISCLOSED_VC_PROOF_BOUND_1,
+            VC_PROOF_1,
+            &disclosed_vc_1,
+            DISCLOSED_VC_PROOF_1,
         )];
-
         let deanon_map = get_example_deanon_map_string();
 
-        let blind_sign_request =
-            request_blind_sign_string(&mut rng, secret, None, Some(true)).unwrap();
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        );
+        assert!(
+            matches!(
+                derived_proof,
+                Err(RDFProofsError::DuplicatedDisclosedTriple(_))
+            ),
+            "{:?}",
+            derived_proof
+        );
+    }
+
+    const PREVIOUS_PROOF_1: &str = r#"
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000001> <https://w3id.org/security#cryptosuite> "ecdsa-rdfc-2019" .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000001> <https://w3id.org/security#proofPurpose> <https://w3id.org/security#authenticationMethod> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000001> <https://w3id.org/security#verificationMethod> <did:example:holder#key-1> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000001> <https://w3id.org/security#proofValue> "z3FXQjecWufY46yg5abdVZsXqLhxhueuSoZgNSARiKBk"^^<https://w3id.org/security#multibase> .
+"#;
+    const PREVIOUS_PROOF_2: &str = r#"
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000002> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000002> <https://w3id.org/security#cryptosuite> "ecdsa-rdfc-2019" .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000002> <https://w3id.org/security#proofPurpose> <https://w3id.org/security#authenticationMethod> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000002> <https://w3id.org/security#verificationMethod> <did:example:witness#key-1> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000002> <https://w3id.org/security#previousProof> <urn:uuid:2b4d6c8e-0000-4000-8000-000000000001> .
+<urn:uuid:2b4d6c8e-0000-4000-8000-000000000002> <https://w3id.org/security#proofValue> "z4mAs9uHU16jR4xwPcbhHyRUc6BbaiJQE5B4ZPyo2CFF"^^<https://w3id.org/security#multibase> .
+"#;
+
+    #[test]
+    fn derive_and_verify_proof_with_previous_proofs() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
 
         let challenge = "abcde";
 
+        let previous_proofs = vec![PREVIOUS_PROOF_1.to_string(), PREVIOUS_PROOF_2.to_string()];
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -2920,14 +8418,13 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            Some(secret),
-            Some(blind_sign_request),
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString {
+                previous_proofs: Some(&previous_proofs),
+                ..Default::default()
+            },
         )
         .unwrap();
+        println!("derived_proof: {}", derived_proof);
 
         let verified = verify_proof_string(
             &mut rng,
@@ -2935,36 +8432,41 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            None,
-            None,
+            VerifyOptionsString::default(),
         );
         assert!(verified.is_ok(), "{:?}", verified)
     }
 
     #[test]
-    fn derive_and_verify_proof_with_only_blind_sign_request_success() {
-        let mut rng = StdRng::seed_from_u64(0u64); // TODO: to be fixed
+    fn derive_and_verify_proof_with_missing_previous_proof_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
 
-        let secret = b"SECRET";
-        let blind_sign_request =
-            request_blind_sign_string(&mut rng, secret, None, Some(true)).unwrap();
+        let vc_pairs = vec![
+            VcPairString::new(VC_1, VC_PROOF_1, DISCLOSED_VC_1, DISCLOSED_VC_PROOF_1),
+            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
+        ];
+
+        let deanon_map = get_example_deanon_map_string();
 
         let challenge = "abcde";
+
+        // the first proof in the chain is missing
+        let previous_proofs = vec![PREVIOUS_PROOF_2.to_string()];
+
         let derived_proof = derive_proof_string(
             &mut rng,
-            &vec![],
-            &HashMap::new(),
+            &vc_pairs,
+            &deanon_map,
             KEY_GRAPH,
             Some(challenge),
             None,
-            Some(secret),
-            Some(blind_sign_request),
-            None,
-            None,
-            None,
-            None,
+            DeriveOptionsString {
+                previous_proofs: Some(&previous_proofs),
+                ..Default::default()
+            },
         )
         .unwrap();
+        println!("derived_proof: {}", derived_proof);
 
         let verified = verify_proof_string(
             &mut rng,
@@ -2972,197 +8474,509 @@ mod tests {
             KEY_GRAPH,
             Some(challenge),
             None,
-            None,
-            None,
+            VerifyOptionsString::default(),
         );
-        assert!(verified.is_ok(), "{:?}", verified)
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::MissingPreviousProof(_))
+        ))
     }
 
     #[test]
-    fn derive_and_verify_proof_with_ppid_success() {
+    fn generate_circuits() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
-        let secret = b"SECRET";
+        let circuit_id = "https://zkp-ld.org/circuit/lessThanPrvPrv".to_string();
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_prv_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_prv_64.wasm").unwrap();
+        let commit_witness_count = 2;
 
-        let vc_pairs = vec![
-            VcPairString::new(
-                VC_1,
-                VC_PROOF_BOUND_1,
-                DISCLOSED_VC_1,
-                DISCLOSED_VC_PROOF_BOUND_1,
-            ),
-            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
-        ];
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(commit_witness_count, &mut rng)
+            .unwrap();
 
-        let deanon_map = get_example_deanon_map_string();
+        // serialize
+        let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
+        let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
+        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
 
-        let challenge = "abcde";
-        let domain = "example.org";
+        // TODO: serde_json
+        let circuit_json = format!(
+            r#"{{
+      "id": "{circuit_id}",
+      "r1cs": "{circuit_r1cs}",
+      "wasm": "{circuit_wasm}",
+      "provingKey": "{snark_proving_key}"
+    }}"#
+        );
+        println!("{}", circuit_json);
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_less_than_predicates_datetime() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            VC_PROOF_1,
+            DISCLOSED_VC_1_WITH_HIDDEN_LITERALS,
+            DISCLOSED_VC_PROOF_1,
+        )];
+
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
+
+        // define predicates
+        let predicates = vec![
+                r#"
+                _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
+                _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+                _:b0 <https://zkp-ld.org/security#private> _:b1 .
+                _:b0 <https://zkp-ld.org/security#public> _:b3 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
+                _:b2 <https://zkp-ld.org/security#var> "lesser" .
+                _:b2 <https://zkp-ld.org/security#val> _:e5 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
+                _:b4 <https://zkp-ld.org/security#var> "greater" .
+                _:b4 <https://zkp-ld.org/security#val> "2022-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+                "#.to_string(),
+            ];
+
+        // define circuit
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_pub_64.wasm").unwrap();
+        let commit_witness_count = 1;
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(commit_witness_count, &mut rng)
+            .unwrap();
+
+        // serialize to multibase
+        let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
+        let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
+        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
+
+        // generate SNARK proving key (by Verifier)
+        let circuit = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            CircuitString {
+                circuit_r1cs: circuit_r1cs.clone(),
+                circuit_wasm: circuit_wasm.clone(),
+                snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
+            },
+        )]);
 
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
             KEY_GRAPH,
-            Some(challenge),
-            Some(domain),
-            Some(secret),
-            None,
-            Some(true),
-            None,
             None,
             None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derive_proof: {}", derived_proof);
+
+        let snark_verifying_keys = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            snark_proving_key.clone(),
+        )]);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // standalone predicate proof passes the pairing check of on-chain Groth16 verifiers,
+        // where `pi_d` is added to the linear combination of public signals
+        let vp = get_dataset_from_nquads(&derived_proof).unwrap();
+        let snark_proofs = extract_predicate_snark_proofs(&vp).unwrap();
+        assert_eq!(snark_proofs.len(), 1);
+        let (circuit, snark_proof, public_inputs) = &snark_proofs[0];
+        assert_eq!(
+            circuit.as_str(),
+            "https://zkp-ld.org/circuit/lessThanPrvPub"
+        );
+        let vk: VerifyingKey = multibase_to_ark(&snark_proving_key).unwrap();
+        let vk_x = public_inputs
+            .iter()
+            .zip(vk.gamma_abc_g1.iter().skip(1))
+            .fold(vk.gamma_abc_g1[0] + snark_proof.d, |acc, (input, base)| {
+                acc + *base * input
+            });
+        assert_eq!(
+            Curve::pairing(snark_proof.a, snark_proof.b),
+            Curve::pairing(vk.alpha_g1, vk.beta_g2)
+                + Curve::pairing(vk_x, vk.gamma_g2)
+                + Curve::pairing(snark_proof.c, vk.delta_g2)
+        );
+        let onchain_proofs = extract_onchain_predicate_proofs(&vp).unwrap();
+        assert_eq!(onchain_proofs[0].public_signals.len(), 2);
+        assert_eq!(onchain_proofs[0].public_signals[0], "1");
+        let onchain_vk =
+            export_onchain_verifying_key_string(&snark_proving_key, public_inputs.len()).unwrap();
+        assert_eq!(onchain_vk.ic.len(), 3);
+
+        // negative test: equality must be rejected
+        let predicates_same_datetime = vec![
+                r#"
+                _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
+                _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+                _:b0 <https://zkp-ld.org/security#private> _:b1 .
+                _:b0 <https://zkp-ld.org/security#public> _:b3 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
+                _:b2 <https://zkp-ld.org/security#var> "lesser" .
+                _:b2 <https://zkp-ld.org/security#val> _:e5 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
+                _:b4 <https://zkp-ld.org/security#var> "greater" .
+                _:b4 <https://zkp-ld.org/security#val> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+                "#.to_string(),
+            ];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates_same_datetime),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
-        println!("derived_proof:\n{}", derived_proof);
-
+        println!("derive_proof: {}", derived_proof);
         let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
             KEY_GRAPH,
-            Some(challenge),
-            Some(domain),
             None,
             None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
         );
-        assert!(verified.is_ok(), "{:?}", verified)
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::ProofSystem(
+                proof_system::prelude::ProofSystemError::LegoGroth16Error(_)
+            ))
+        ));
     }
 
     #[test]
-    fn derive_and_verify_proof_with_ppid_and_secret_commitment_success() {
+    fn derive_and_verify_proof_with_predicate_outputs() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
-        let secret = b"SECRET";
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            VC_PROOF_1,
+            DISCLOSED_VC_1_WITH_HIDDEN_LITERALS,
+            DISCLOSED_VC_PROOF_1,
+        )];
 
-        let vc_pairs = vec![
-            VcPairString::new(
-                VC_1,
-                VC_PROOF_BOUND_1,
-                DISCLOSED_VC_1,
-                DISCLOSED_VC_PROOF_BOUND_1,
-            ),
-            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
-        ];
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
 
-        let deanon_map = get_example_deanon_map_string();
+        // predicate with the declared output of the circuit
+        let get_predicates = |output: &str| {
+            vec![format!(
+                r#"
+                _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
+                _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+                _:b0 <https://zkp-ld.org/security#private> _:b1 .
+                _:b0 <https://zkp-ld.org/security#public> _:b3 .
+                _:b0 <https://zkp-ld.org/security#output> _:b5 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
+                _:b2 <https://zkp-ld.org/security#var> "lesser" .
+                _:b2 <https://zkp-ld.org/security#val> _:e5 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
+                _:b4 <https://zkp-ld.org/security#var> "greater" .
+                _:b4 <https://zkp-ld.org/security#val> "2022-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+                _:b5 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b6 .
+                _:b5 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b6 <https://zkp-ld.org/security#var> "out" .
+                _:b6 <https://zkp-ld.org/security#val> {} .
+                "#,
+                output
+            )]
+        };
 
-        let blind_sign_request =
-            request_blind_sign_string(&mut rng, secret, None, Some(true)).unwrap();
+        // define circuit
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_pub_64.wasm").unwrap();
+        let commit_witness_count = 1;
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(commit_witness_count, &mut rng)
+            .unwrap();
 
-        let challenge = "abcde";
-        let domain = "example.org";
+        // serialize to multibase
+        let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
+        let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
+        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
 
-        let derived_proof = derive_proof_string(
-            &mut rng,
-            &vc_pairs,
-            &deanon_map,
-            KEY_GRAPH,
-            Some(challenge),
-            Some(domain),
-            Some(secret),
-            Some(blind_sign_request),
-            Some(true),
-            None,
-            None,
-            None,
-        )
-        .unwrap();
+        let circuit = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            CircuitString {
+                circuit_r1cs: circuit_r1cs.clone(),
+                circuit_wasm: circuit_wasm.clone(),
+                snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
+            },
+        )]);
+        let snark_verifying_keys = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            snark_proving_key.clone(),
+        )]);
 
-        let verified = verify_proof_string(
-            &mut rng,
-            &derived_proof,
-            KEY_GRAPH,
-            Some(challenge),
-            Some(domain),
-            None,
-            None,
+        let derive_and_verify = |rng: &mut StdRng, output: &str| {
+            let derived_proof = derive_proof_string(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                KEY_GRAPH,
+                None,
+                None,
+                DeriveOptionsString {
+                    predicates: Some(&get_predicates(output)),
+                    circuits: Some(&circuit),
+                    ..Default::default()
+                },
+            )?;
+            println!("derive_proof: {}", derived_proof);
+            verify_proof_string(
+                rng,
+                &derived_proof,
+                KEY_GRAPH,
+                None,
+                None,
+                VerifyOptionsString {
+                    snark_verifying_keys: Some(&snark_verifying_keys),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // the output computed by the circuit is returned as the verification result
+        let output = "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>";
+        let verified = derive_and_verify(&mut rng, output).unwrap();
+        assert_eq!(
+            verified.predicate_outputs,
+            vec![PredicateOutput {
+                circuit: NamedNode::new_unchecked("https://zkp-ld.org/circuit/lessThanPrvPub"),
+                outputs: vec![(
+                    "out".to_string(),
+                    Literal::new_typed_literal(
+                        "1",
+                        NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#integer")
+                    )
+                    .into()
+                )],
+            }]
         );
-        assert!(verified.is_ok(), "{:?}", verified)
+
+        // negative test: the output different from the computed one must be rejected
+        let output = "\"0\"^^<http://www.w3.org/2001/XMLSchema#integer>";
+        assert!(derive_and_verify(&mut rng, output).is_err());
+
+        // negative test: the output must be a literal
+        let output = "<http://example.org/one>";
+        assert!(matches!(
+            derive_and_verify(&mut rng, output),
+            Err(RDFProofsError::InvalidPredicate)
+        ));
     }
 
+    // executable walkthrough of the whole lifecycle across modules;
+    // see also `examples/lifecycle.rs`
     #[test]
-    fn derive_and_verify_revocable_secret() {
+    fn issue_bind_present_and_audit_lifecycle() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
-        let secret = b"SECRET";
-
-        let vc_pairs = vec![
-            VcPairString::new(
-                VC_1,
-                VC_PROOF_BOUND_1,
-                DISCLOSED_VC_1,
-                DISCLOSED_VC_PROOF_BOUND_1,
-            ),
-            VcPairString::new(VC_2, VC_PROOF_2, DISCLOSED_VC_2, DISCLOSED_VC_PROOF_2),
-        ];
+        // issuer: generate keys and publish the key graph without the secret key
+        let keypair = generate_keypair(&mut rng).unwrap();
+        let issuer_key_graph = format!(
+            r#"
+        <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+        <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+        <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+        <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "{}" .
+        <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "{}" .
+        "#,
+            encode_secret_key(&keypair.secret_key, KeyEncoding::Ark).unwrap(),
+            encode_public_key(&keypair.public_key, KeyEncoding::Ark).unwrap()
+        );
+        let key_graph = issuer_key_graph
+            .lines()
+            .filter(|l| !l.contains("secretKeyMultibase"))
+            .map(|l| format!("{}\n", l))
+            .collect::<String>();
 
-        let deanon_map = get_example_deanon_map_string();
+        // holder and issuer: blind issuance of VC bound to holder's secret
+        let secret = b"SECRET";
+        let issuance_challenge = "issuance";
+        let request =
+            request_blind_sign_string(&mut rng, secret, Some(issuance_challenge), None, None, None)
+                .unwrap();
+        let verified_request = verify_blind_sign_request_string(
+            &mut rng,
+            &request.commitment,
+            request.pok_for_commitment.as_ref().unwrap(),
+            Some(issuance_challenge),
+            None,
+            None,
+        );
+        assert!(verified_request.is_ok(), "{:?}", verified_request);
+        let blinded_proof = blind_sign_string(
+            &mut rng,
+            &request.commitment,
+            VC_1,
+            VC_PROOF_WITHOUT_PROOFVALUE_1,
+            &issuer_key_graph,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof = unblind_string(VC_1, &blinded_proof, &request.blinding).unwrap();
+        let verified_vc = blind_verify_string(secret, VC_1, &proof, &key_graph);
+        assert!(verified_vc.is_ok(), "{:?}", verified_vc);
 
+        // verifier: circuit keys and opener's keys for auditing
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_pub_64.wasm").unwrap();
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(1, &mut rng)
+            .unwrap();
+        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
+        let circuit = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            CircuitString {
+                circuit_r1cs: ark_to_base64url(&circuit_r1cs).unwrap(),
+                circuit_wasm: multibase::encode(Base::Base64Url, circuit_wasm),
+                snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
+            },
+        )]);
+        let snark_verifying_keys = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            snark_proving_key,
+        )]);
+        let public_values = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            HashMap::from([(
+                "greater".to_string(),
+                "\"2022-12-31T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>".to_string(),
+            )]),
+        )]);
+        let (opener_pub_key, opener_secret_key) = elliptic_elgamal_keygen(&mut rng).unwrap();
         let challenge = "abcde";
+        let domain = "example.org";
 
-        let (opener_pub_key, _) = elliptic_elgamal_keygen(&mut rng).unwrap();
-
+        // holder: present the bound VC with PPID, hidden vaccination date proven by predicate,
+        // and encrypted uid
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            &proof,
+            DISCLOSED_VC_1_WITH_HIDDEN_LITERALS,
+            DISCLOSED_VC_PROOF_BOUND_1,
+        )];
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
+        let predicates =
+            vec![
+                PredicateGraph::new_string("https://zkp-ld.org/circuit/lessThanPrvPub")
+                    .unwrap()
+                    .private("lesser", VariableRef::new("e5").unwrap())
+                    .public(
+                        "greater",
+                        Literal::new_typed_literal("2022-12-31T00:00:00Z", xsd::DATE_TIME),
+                    )
+                    .to_ntriples(),
+            ];
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
             &deanon_map,
-            KEY_GRAPH,
+            &key_graph,
             Some(challenge),
-            None,
-            Some(secret),
-            None,
-            None,
-            None,
-            None,
-            Some(opener_pub_key),
+            Some(domain),
+            DeriveOptionsString {
+                secret: Some(secret),
+                with_ppid: Some(true),
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                opener_pub_keys: Some(&vec![opener_pub_key]),
+                ..Default::default()
+            },
         )
         .unwrap();
-        print!("derived_proof: {}", derived_proof);
+        println!("derived_proof:\n{}", derived_proof);
 
+        // verifier: verify VP against the minimal key graph
+        let verifier_key_graph =
+            export_key_graph_for_vp_string(&derived_proof, &key_graph).unwrap();
         let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
-            KEY_GRAPH,
+            &verifier_key_graph,
             Some(challenge),
-            None,
-            None,
-            Some(opener_pub_key),
+            Some(domain),
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                opener_pub_keys: Some(&vec![opener_pub_key]),
+                public_values: Some(&public_values),
+                ..Default::default()
+            },
         );
-        assert!(verified.is_ok(), "{:?}", verified)
-    }
-
-    #[test]
-    fn generate_circuits() {
-        let mut rng = StdRng::seed_from_u64(0u64);
-
-        let circuit_id = "https://zkp-ld.org/circuit/lessThanPrvPrv".to_string();
-        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_prv_64.r1cs").unwrap();
-        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_prv_64.wasm").unwrap();
-        let commit_witness_count = 2;
+        assert!(verified.is_ok(), "{:?}", verified);
 
-        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
-            .generate_proving_key(commit_witness_count, &mut rng)
+        // opener: deanonymize the holder by decrypting the uid
+        let vp = get_dataset_from_nquads(&derived_proof).unwrap();
+        let encrypted_uid = vp
+            .quads_for_predicate(ENCRYPTED_UID)
+            .find_map(|q| match q.object {
+                TermRef::Literal(v) => Some(v.value().to_string()),
+                _ => None,
+            })
             .unwrap();
-
-        // serialize
-        let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
-        let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
-        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
-
-        // TODO: serde_json
-        let circuit_json = format!(
-            r#"{{
-      "id": "{circuit_id}",
-      "r1cs": "{circuit_r1cs}",
-      "wasm": "{circuit_wasm}",
-      "provingKey": "{snark_proving_key}"
-    }}"#
+        let cipher_text: ElGamalCiphertext = multibase_to_ark(&encrypted_uid).unwrap();
+        let uid = elliptic_elgamal_decrypt(&opener_secret_key, &cipher_text).unwrap();
+        assert_eq!(
+            ark_to_base64url(&uid).unwrap(),
+            get_encrypted_uid(&secret.to_vec(), &generate_params(1).h[0]).unwrap()
         );
-        println!("{}", circuit_json);
     }
 
     #[test]
-    fn derive_and_verify_proof_with_less_than_predicates_datetime() {
+    fn derive_and_verify_proof_with_predicates_sharing_circuit() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
         let vc_pairs = vec![VcPairString::new(
@@ -3175,7 +8989,7 @@ mod tests {
         let mut deanon_map = get_example_deanon_map_string();
         deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
 
-        // define predicates
+        // define predicates using the same circuit
         let predicates = vec![
                 r#"
                 _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
@@ -3191,7 +9005,23 @@ mod tests {
                 _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
                 _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
                 _:b4 <https://zkp-ld.org/security#var> "greater" .
-                _:b4 <https://zkp-ld.org/security#val> "2022-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+                _:b4 <https://zkp-ld.org/security#val> "2022-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+                "#.to_string(),
+                r#"
+                _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
+                _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+                _:b0 <https://zkp-ld.org/security#private> _:b1 .
+                _:b0 <https://zkp-ld.org/security#public> _:b3 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
+                _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
+                _:b2 <https://zkp-ld.org/security#var> "lesser" .
+                _:b2 <https://zkp-ld.org/security#val> _:e5 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
+                _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+                _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
+                _:b4 <https://zkp-ld.org/security#var> "greater" .
+                _:b4 <https://zkp-ld.org/security#val> "2023-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
                 "#.to_string(),
             ];
 
@@ -3208,13 +9038,16 @@ mod tests {
         let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
         let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
 
-        // generate SNARK proving key (by Verifier)
         let circuit = HashMap::from([(
             "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
             CircuitString {
-                circuit_r1cs: circuit_r1cs.clone(),
-                circuit_wasm: circuit_wasm.clone(),
+                circuit_r1cs,
+                circuit_wasm,
                 snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::from([
+                    ("lesser".to_string(), 64),
+                    ("greater".to_string(), 64),
+                ]),
             },
         )]);
 
@@ -3225,19 +9058,18 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates),
-            Some(&circuit),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
         println!("derive_proof: {}", derived_proof);
 
         let snark_verifying_keys = HashMap::from([(
             "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
-            snark_proving_key.clone(),
+            snark_proving_key,
         )]);
 
         let verified = verify_proof_string(
@@ -3246,13 +9078,30 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys.clone()),
-            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
         );
         assert!(verified.is_ok(), "{:?}", verified);
+    }
 
-        // negative test: equality must be rejected
-        let predicates_same_datetime = vec![
+    #[test]
+    fn derive_proof_with_out_of_range_circuit_input_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![VcPairString::new(
+            VC_1,
+            VC_PROOF_1,
+            DISCLOSED_VC_1_WITH_HIDDEN_LITERALS,
+            DISCLOSED_VC_PROOF_1,
+        )];
+
+        let mut deanon_map = get_example_deanon_map_string();
+        deanon_map.extend(get_example_deanon_map_string_with_hidden_literal());
+
+        // pre-1970 dateTime is encoded as a negative (i.e., out-of-range) field element
+        let predicates = vec![
                 r#"
                 _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
                 _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
@@ -3267,9 +9116,31 @@ mod tests {
                 _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
                 _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
                 _:b4 <https://zkp-ld.org/security#var> "greater" .
-                _:b4 <https://zkp-ld.org/security#val> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+                _:b4 <https://zkp-ld.org/security#val> "1800-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
                 "#.to_string(),
             ];
+
+        // define circuit
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_pub_64.wasm").unwrap();
+        let commit_witness_count = 1;
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(commit_witness_count, &mut rng)
+            .unwrap();
+
+        let circuit = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            CircuitString {
+                circuit_r1cs: ark_to_base64url(&circuit_r1cs).unwrap(),
+                circuit_wasm: multibase::encode(Base::Base64Url, circuit_wasm),
+                snark_proving_key: ark_to_base64url(&snark_proving_key).unwrap(),
+                input_bit_lengths: HashMap::from([
+                    ("lesser".to_string(), 64),
+                    ("greater".to_string(), 64),
+                ]),
+            },
+        )]);
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -3277,29 +9148,15 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates_same_datetime),
-            Some(&circuit),
-            None,
-        )
-        .unwrap();
-        println!("derive_proof: {}", derived_proof);
-        let verified = verify_proof_string(
-            &mut rng,
-            &derived_proof,
-            KEY_GRAPH,
-            None,
-            None,
-            Some(snark_verifying_keys),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         );
         assert!(matches!(
-            verified,
-            Err(RDFProofsError::ProofSystem(
-                proof_system::prelude::ProofSystemError::LegoGroth16Error(_)
-            ))
+            derived_proof,
+            Err(RDFProofsError::InvalidCircuitInput(_))
         ));
     }
 
@@ -3360,6 +9217,7 @@ mod tests {
                 circuit_r1cs: circuit_r1cs.clone(),
                 circuit_wasm: circuit_wasm.clone(),
                 snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
             },
         )]);
 
@@ -3370,12 +9228,11 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates),
-            Some(&circuit),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
         println!("derive_proof: {}", derived_proof);
@@ -3391,8 +9248,10 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys.clone()),
-            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
         );
         assert!(verified.is_ok(), "{:?}", verified);
 
@@ -3422,12 +9281,11 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates_lesser_datetime),
-            Some(&circuit),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates_lesser_datetime),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
         println!("derive_proof: {}", derived_proof);
@@ -3437,8 +9295,10 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys),
-            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
         );
         assert!(matches!(
             verified,
@@ -3512,7 +9372,203 @@ mod tests {
             DISCLOSED_VC_PROOF_4,
         )];
 
-        let deanon_map = get_example_deanon_map_4();
+        let deanon_map = get_example_deanon_map_4();
+
+        // define predicates
+        let predicates = vec![
+            r#"
+            _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
+            _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+            _:b0 <https://zkp-ld.org/security#private> _:b1 .
+            _:b0 <https://zkp-ld.org/security#public> _:b3 .
+            _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
+            _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+            _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
+            _:b2 <https://zkp-ld.org/security#var> "lesser" .
+            _:b2 <https://zkp-ld.org/security#val> _:e1 .
+            _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
+            _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+            _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
+            _:b4 <https://zkp-ld.org/security#var> "greater" .
+            _:b4 <https://zkp-ld.org/security#val> "2022-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+            "#.to_string(),
+        ];
+
+        // define circuit
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_pub_64.wasm").unwrap();
+        let commit_witness_count = 1;
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(commit_witness_count, &mut rng)
+            .unwrap();
+
+        // serialize to multibase
+        let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
+        println!("\"r1cs\": \"{}\",", circuit_r1cs);
+        let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
+        println!("\"wasm\": \"{}\",", circuit_wasm);
+        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
+        println!("\"snarkProvingKey\": \"{}\"", snark_proving_key);
+
+        // generate SNARK proving key (by Verifier)
+        let circuit = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            CircuitString {
+                circuit_r1cs: circuit_r1cs.clone(),
+                circuit_wasm: circuit_wasm.clone(),
+                snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
+            },
+        )]);
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derive_proof: {}", derived_proof);
+
+        let snark_verifying_keys = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            snark_proving_key.clone(),
+        )]);
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // negative test
+        let predicates_lesser_datetime = vec![
+            r#"
+            _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
+            _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+            _:b0 <https://zkp-ld.org/security#private> _:b1 .
+            _:b0 <https://zkp-ld.org/security#public> _:b3 .
+            _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
+            _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+            _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
+            _:b2 <https://zkp-ld.org/security#var> "lesser" .
+            _:b2 <https://zkp-ld.org/security#val> _:e1 .
+            _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
+            _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
+            _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
+            _:b4 <https://zkp-ld.org/security#var> "greater" .
+            _:b4 <https://zkp-ld.org/security#val> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+            "#.to_string(),
+        ];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates_lesser_datetime),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        println!("derive_proof: {}", derived_proof);
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::ProofSystem(
+                proof_system::prelude::ProofSystemError::LegoGroth16Error(_)
+            ))
+        ));
+    }
+
+    const VC_5: &str = r#"
+    <urn:example:prod1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Product> .
+    <urn:example:prod1> <http://schema.org/name> "Awesome Product" .
+    <urn:example:prod1> <http://schema.org/price> "300"^^<http://www.w3.org/2001/XMLSchema#integer> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <urn:example:prod1> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_5: &str = r#"
+    _:b0 <https://w3id.org/security#proofValue> "upHBxGAvQcU1hUDdvsT8eNvU6g_z9y446mzT78wxCOOToYdDAkX11C-Ga0w_8WNUHnHL4DdyqBDvkUBbr0eTTUk3vNVI1LRxSfXRqqLng4Qx6SX7tptjtHzjJMkQnolGpiiFfE9k8OhOKcntcJwGSaQ"^^<https://w3id.org/security#multibase> .
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC_5: &str = r#"
+    _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Product> .
+    _:e0 <http://schema.org/price> _:e1 .
+    _:e2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e2 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+    _:e2 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    _:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e2 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const DISCLOSED_VC_PROOF_5: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DEANON_MAP_5: [(&str, &str); 3] = [
+        ("_:e0", "<urn:example:prod1>"),
+        (
+            "_:e1",
+            "\"300\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+        ),
+        ("_:e2", "<http://example.org/vcred/00>"),
+    ];
+    fn get_example_deanon_map_5() -> HashMap<String, String> {
+        DEANON_MAP_5
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_less_than_predicates_integer() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![VcPairString::new(
+            VC_5,
+            VC_PROOF_5,
+            DISCLOSED_VC_5,
+            DISCLOSED_VC_PROOF_5,
+        )];
+
+        let deanon_map = get_example_deanon_map_5();
 
         // define predicates
         let predicates = vec![
@@ -3530,7 +9586,7 @@ mod tests {
             _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
             _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
             _:b4 <https://zkp-ld.org/security#var> "greater" .
-            _:b4 <https://zkp-ld.org/security#val> "2022-12-31T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+            _:b4 <https://zkp-ld.org/security#val> "4300000000"^^<http://www.w3.org/2001/XMLSchema#integer> .
             "#.to_string(),
         ];
 
@@ -3557,6 +9613,7 @@ mod tests {
                 circuit_r1cs: circuit_r1cs.clone(),
                 circuit_wasm: circuit_wasm.clone(),
                 snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
             },
         )]);
 
@@ -3567,12 +9624,11 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates),
-            Some(&circuit),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
         println!("derive_proof: {}", derived_proof);
@@ -3588,13 +9644,84 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys.clone()),
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // verifier supplies the threshold used by prover
+        let public_values = |value: &str| {
+            HashMap::from([(
+                "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+                HashMap::from([(
+                    "greater".to_string(),
+                    format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#integer>", value),
+                )]),
+            )])
+        };
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
             None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&public_values("4300000000")),
+                ..Default::default()
+            },
         );
         assert!(verified.is_ok(), "{:?}", verified);
 
-        // negative test
-        let predicates_lesser_datetime = vec![
+        // negative test: verifier-supplied threshold different from prover's must be rejected
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&public_values("4200000000")),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::ProofSystem(
+                proof_system::prelude::ProofSystemError::LegoGroth16Error(_)
+            ))
+        ));
+
+        // negative test: verifier-supplied value not used by any predicate must be rejected
+        let unused_public_values = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPubPrv".to_string(),
+            HashMap::from([(
+                "lesser".to_string(),
+                "\"300\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string(),
+            )]),
+        )]);
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&unused_public_values),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::UnusedPredicatePublicValue(_))
+        ));
+
+        // negative test: equality must be rejected
+        let predicates_same_integer = vec![
             r#"
             _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
             _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
@@ -3609,9 +9736,10 @@ mod tests {
             _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
             _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
             _:b4 <https://zkp-ld.org/security#var> "greater" .
-            _:b4 <https://zkp-ld.org/security#val> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+            _:b4 <https://zkp-ld.org/security#val> "300"^^<http://www.w3.org/2001/XMLSchema#integer> .
             "#.to_string(),
         ];
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -3619,12 +9747,11 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates_lesser_datetime),
-            Some(&circuit),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates_same_integer),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
         println!("derive_proof: {}", derived_proof);
@@ -3634,8 +9761,10 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys),
-            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
         );
         assert!(matches!(
             verified,
@@ -3645,71 +9774,86 @@ mod tests {
         ));
     }
 
-    const VC_5: &str = r#"
+    const VC_5_WITH_DECIMAL: &str = r#"
     <urn:example:prod1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Product> .
     <urn:example:prod1> <http://schema.org/name> "Awesome Product" .
-    <urn:example:prod1> <http://schema.org/price> "300"^^<http://www.w3.org/2001/XMLSchema#integer> .
+    <urn:example:prod1> <http://schema.org/price> "299.99"^^<http://www.w3.org/2001/XMLSchema#decimal> .
+    <urn:example:prod1> <http://schema.org/inventoryLevel> "42"^^<http://www.w3.org/2001/XMLSchema#integer> .
     <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
     <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <urn:example:prod1> .
     <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
     <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
     <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
     "#;
-    const VC_PROOF_5: &str = r#"
-    _:b0 <https://w3id.org/security#proofValue> "upHBxGAvQcU1hUDdvsT8eNvU6g_z9y446mzT78wxCOOToYdDAkX11C-Ga0w_8WNUHnHL4DdyqBDvkUBbr0eTTUk3vNVI1LRxSfXRqqLng4Qx6SX7tptjtHzjJMkQnolGpiiFfE9k8OhOKcntcJwGSaQ"^^<https://w3id.org/security#multibase> .
+    const VC_PROOF_WITHOUT_PROOFVALUE_5_SCALED_DECIMAL: &str = r#"
     _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-scaled-decimal-signature-2024" .
     _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
     _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
     _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
     "#;
-    const DISCLOSED_VC_5: &str = r#"
+    const DISCLOSED_VC_5_WITH_DECIMAL: &str = r#"
     _:e0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Product> .
     _:e0 <http://schema.org/price> _:e1 .
+    _:e0 <http://schema.org/inventoryLevel> _:e3 .
     _:e2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
     _:e2 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
     _:e2 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
-    _:e2 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e2 <https://www.w3.org/2018/credentials#issuanceDate> _:e4 .
     _:e2 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
     "#;
-    const DISCLOSED_VC_PROOF_5: &str = r#"
+    const DISCLOSED_VC_PROOF_5_SCALED_DECIMAL: &str = r#"
     _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
-    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-scaled-decimal-signature-2024" .
     _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
     _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
     _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
     "#;
-    const DEANON_MAP_5: [(&str, &str); 3] = [
+    const DEANON_MAP_5_WITH_DECIMAL: [(&str, &str); 5] = [
         ("_:e0", "<urn:example:prod1>"),
         (
             "_:e1",
-            "\"300\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+            "\"299.99\"^^<http://www.w3.org/2001/XMLSchema#decimal>",
         ),
         ("_:e2", "<http://example.org/vcred/00>"),
+        ("_:e3", "\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>"),
+        (
+            "_:e4",
+            "\"2022-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
+        ),
     ];
-    fn get_example_deanon_map_5() -> HashMap<String, String> {
-        DEANON_MAP_5
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect()
-    }
 
     #[test]
-    fn derive_and_verify_proof_with_less_than_predicates_integer() {
+    fn derive_and_verify_proof_with_less_than_predicates_scaled_decimal() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
+        // sign with the scaled-decimal cryptosuite so that decimals can be compared in circuits
+        let vc_proof = sign_string(
+            &mut rng,
+            VC_5_WITH_DECIMAL,
+            VC_PROOF_WITHOUT_PROOFVALUE_5_SCALED_DECIMAL,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+
         let vc_pairs = vec![VcPairString::new(
-            VC_5,
-            VC_PROOF_5,
-            DISCLOSED_VC_5,
-            DISCLOSED_VC_PROOF_5,
+            VC_5_WITH_DECIMAL,
+            &vc_proof,
+            DISCLOSED_VC_5_WITH_DECIMAL,
+            DISCLOSED_VC_PROOF_5_SCALED_DECIMAL,
         )];
 
-        let deanon_map = get_example_deanon_map_5();
+        let deanon_map: HashMap<String, String> = DEANON_MAP_5_WITH_DECIMAL
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
 
-        // define predicates
-        let predicates = vec![
-            r#"
+        // define predicates over decimal, integer, and dateTime values
+        let less_than = |private: &str, public: &str| {
+            format!(
+                r#"
             _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
             _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
             _:b0 <https://zkp-ld.org/security#private> _:b1 .
@@ -3718,13 +9862,29 @@ mod tests {
             _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
             _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
             _:b2 <https://zkp-ld.org/security#var> "lesser" .
-            _:b2 <https://zkp-ld.org/security#val> _:e1 .
+            _:b2 <https://zkp-ld.org/security#val> {} .
             _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
             _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
             _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
             _:b4 <https://zkp-ld.org/security#var> "greater" .
-            _:b4 <https://zkp-ld.org/security#val> "4300000000"^^<http://www.w3.org/2001/XMLSchema#integer> .
-            "#.to_string(),
+            _:b4 <https://zkp-ld.org/security#val> {} .
+            "#,
+                private, public
+            )
+        };
+        let predicates = vec![
+            less_than(
+                "_:e1",
+                "\"300.5\"^^<http://www.w3.org/2001/XMLSchema#decimal>",
+            ),
+            less_than(
+                "_:e3",
+                "\"100\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+            ),
+            less_than(
+                "_:e4",
+                "\"2022-12-31T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
+            ),
         ];
 
         // define circuit
@@ -3734,24 +9894,22 @@ mod tests {
         let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
             .generate_proving_key(commit_witness_count, &mut rng)
             .unwrap();
-
-        // serialize to multibase
         let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
-        println!("\"r1cs\": \"{}\",", circuit_r1cs);
         let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
-        println!("\"wasm\": \"{}\",", circuit_wasm);
         let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
-        println!("\"snarkProvingKey\": \"{}\"", snark_proving_key);
-
-        // generate SNARK proving key (by Verifier)
         let circuit = HashMap::from([(
             "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
             CircuitString {
-                circuit_r1cs: circuit_r1cs.clone(),
-                circuit_wasm: circuit_wasm.clone(),
+                circuit_r1cs,
+                circuit_wasm,
                 snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::new(),
             },
         )]);
+        let snark_verifying_keys = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            snark_proving_key,
+        )]);
 
         let derived_proof = derive_proof_string(
             &mut rng,
@@ -3760,34 +9918,136 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
             None,
             None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // verifier supplies the decimal threshold, compared as a scaled integer rather than a hash
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
             None,
-            Some(&predicates),
-            Some(&circuit),
             None,
+            DeriveOptionsString {
+                predicates: Some(&predicates[..1].to_vec()),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
-        println!("derive_proof: {}", derived_proof);
-
-        let snark_verifying_keys = HashMap::from([(
-            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
-            snark_proving_key.clone(),
-        )]);
-
+        let public_values = |value: &str| {
+            HashMap::from([(
+                "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+                HashMap::from([(
+                    "greater".to_string(),
+                    format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#decimal>", value),
+                )]),
+            )])
+        };
         let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys.clone()),
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&public_values("300.5")),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // negative test: verifier-supplied threshold different from prover's must be rejected
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
             None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&public_values("299.5")),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            verified,
+            Err(RDFProofsError::ProofSystem(
+                proof_system::prelude::ProofSystemError::LegoGroth16Error(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn sign_and_verify_decimals_with_default_and_scaled_decimal_cryptosuites() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let vc = VC_5_WITH_DECIMAL.replace("299.99", "0.0000001");
+
+        // decimals are hashed as before under the default cryptosuite, whatever their precision
+        let vc_proof_without_proofvalue = VC_PROOF_WITHOUT_PROOFVALUE_5_SCALED_DECIMAL.replace(
+            "bbs-termwise-scaled-decimal-signature-2024",
+            "bbs-termwise-signature-2023",
         );
+        let vc_proof = sign_string(
+            &mut rng,
+            &vc,
+            &vc_proof_without_proofvalue,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+        let verified = verify_string(&vc, &vc_proof, KEY_GRAPH);
         assert!(verified.is_ok(), "{:?}", verified);
 
-        // negative test: equality must be rejected
-        let predicates_same_integer = vec![
+        // the scaled-decimal cryptosuite rejects decimals it cannot scale
+        let signed = sign_string(
+            &mut rng,
+            &vc,
+            VC_PROOF_WITHOUT_PROOFVALUE_5_SCALED_DECIMAL,
+            KEY_GRAPH,
+            None,
+            None,
+        );
+        assert!(matches!(signed, Err(RDFProofsError::InvalidDecimal(_))));
+    }
+
+    #[test]
+    fn derive_and_verify_proof_with_array_inputs() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let vc_pairs = vec![VcPairString::new(
+            VC_5,
+            VC_PROOF_5,
+            DISCLOSED_VC_5,
+            DISCLOSED_VC_PROOF_5,
+        )];
+
+        let deanon_map = get_example_deanon_map_5();
+
+        // scalar signals of the compiled circuit are bound as input arrays of length one,
+        // which are laid out by prover and verifier in the same way as longer arrays
+        let predicates = vec![
             r#"
             _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#Predicate> .
             _:b0 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
@@ -3796,16 +10056,39 @@ mod tests {
             _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b2 .
             _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
             _:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PrivateVariable> .
-            _:b2 <https://zkp-ld.org/security#var> "lesser" .
+            _:b2 <https://zkp-ld.org/security#var> "lesser[0]" .
             _:b2 <https://zkp-ld.org/security#val> _:e1 .
             _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b4 .
             _:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .
             _:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://zkp-ld.org/security#PublicVariable> .
-            _:b4 <https://zkp-ld.org/security#var> "greater" .
-            _:b4 <https://zkp-ld.org/security#val> "300"^^<http://www.w3.org/2001/XMLSchema#integer> .
+            _:b4 <https://zkp-ld.org/security#var> "greater[0]" .
+            _:b4 <https://zkp-ld.org/security#val> "400"^^<http://www.w3.org/2001/XMLSchema#integer> .
             "#.to_string(),
         ];
 
+        // define circuit
+        let circuit_r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let circuit_wasm = std::fs::read("circom/bls12381/less_than_prv_pub_64.wasm").unwrap();
+        let commit_witness_count = 1;
+        let snark_proving_key = CircomCircuit::setup(circuit_r1cs.clone())
+            .generate_proving_key(commit_witness_count, &mut rng)
+            .unwrap();
+        let circuit_r1cs = ark_to_base64url(&circuit_r1cs).unwrap();
+        let circuit_wasm = multibase::encode(Base::Base64Url, circuit_wasm);
+        let snark_proving_key = ark_to_base64url(&snark_proving_key).unwrap();
+        let circuit = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            CircuitString {
+                circuit_r1cs,
+                circuit_wasm,
+                snark_proving_key: snark_proving_key.clone(),
+                input_bit_lengths: HashMap::from([
+                    ("lesser".to_string(), 64),
+                    ("greater".to_string(), 64),
+                ]),
+            },
+        )]);
+
         let derived_proof = derive_proof_string(
             &mut rng,
             &vc_pairs,
@@ -3813,23 +10096,53 @@ mod tests {
             KEY_GRAPH,
             None,
             None,
-            None,
-            None,
-            None,
-            Some(&predicates_same_integer),
-            Some(&circuit),
-            None,
+            DeriveOptionsString {
+                predicates: Some(&predicates),
+                circuits: Some(&circuit),
+                ..Default::default()
+            },
         )
         .unwrap();
-        println!("derive_proof: {}", derived_proof);
+
+        let snark_verifying_keys = HashMap::from([(
+            "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+            snark_proving_key,
+        )]);
+        let public_values = |value: &str| {
+            HashMap::from([(
+                "https://zkp-ld.org/circuit/lessThanPrvPub".to_string(),
+                HashMap::from([(
+                    "greater[0]".to_string(),
+                    format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#integer>", value),
+                )]),
+            )])
+        };
         let verified = verify_proof_string(
             &mut rng,
             &derived_proof,
             KEY_GRAPH,
             None,
             None,
-            Some(snark_verifying_keys),
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&public_values("400")),
+                ..Default::default()
+            },
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+
+        // negative test: verifier-supplied threshold different from prover's must be rejected
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
             None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                public_values: Some(&public_values("301")),
+                ..Default::default()
+            },
         );
         assert!(matches!(
             verified,
@@ -3837,5 +10150,24 @@ mod tests {
                 proof_system::prelude::ProofSystemError::LegoGroth16Error(_)
             ))
         ));
+
+        // negative test: elements of input array out of order must be rejected by verifier
+        let tampered_proof = derived_proof.replace("\"greater[0]\"", "\"greater[1]\"");
+        let verified = verify_proof_string(
+            &mut rng,
+            &tampered_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString {
+                snark_verifying_keys: Some(&snark_verifying_keys),
+                ..Default::default()
+            },
+        );
+        assert!(
+            matches!(verified, Err(RDFProofsError::InvalidCircuitInput(_))),
+            "{:?}",
+            verified
+        );
     }
 }
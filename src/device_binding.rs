@@ -0,0 +1,296 @@
+use crate::{
+    common::{ark_to_base64url, get_hasher, hash_byte_to_field, multibase_to_ark, Fr},
+    context::{
+        AUTHENTICATOR_DATA, CLIENT_DATA_JSON, DATA_INTEGRITY_PROOF, DEVICE_BINDING,
+        DEVICE_CERTIFICATE, DEVICE_KEY, DEVICE_SIGNATURE, KEY_HANDLE, MULTIBASE,
+    },
+    curve::G1Affine,
+    error::RDFProofsError,
+    key_gen::generate_params,
+    redact::{redact_ark, redact_str},
+};
+use ark_std::{rand::RngCore, UniformRand};
+use oxrdf::{
+    dataset::GraphView, vocab::rdf::TYPE, BlankNode, Dataset, LiteralRef, QuadRef, TermRef,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// WebAuthn-style assertion by device key, whose signed client data
+/// must contain the challenge given by `device_binding_challenge`,
+/// along with the certificate of the device key issued under an attestation root,
+/// e.g., the `x5c` chain of the attestation statement;
+/// binary values are encoded as is by the authenticator, e.g., in base64url
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceAttestation {
+    pub device_key: String,
+    pub certificate: String,
+    pub authenticator_data: String,
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub signature: String,
+}
+
+/// device holding the device key, e.g., platform authenticator or security key,
+/// which signs the challenge given by `device_binding_challenge` for each presentation
+pub trait DeviceSigner {
+    fn sign(&self, challenge: &[u8]) -> Result<DeviceAttestation, RDFProofsError>;
+}
+
+/// holder's binding of the device to its secret, where `key_handle` registered with
+/// the device is the commitment to the secret opened by `blinding`;
+/// the registered key handle is never included in VPs, but re-randomized for each
+/// presentation and signed by `signer` so that VPs cannot be linked by the key handle
+pub struct DeviceBinding<'a> {
+    pub key_handle: G1Affine,
+    pub blinding: Fr,
+    pub signer: &'a dyn DeviceSigner,
+}
+
+pub struct DeviceBindingString<'a> {
+    pub key_handle: String,
+    pub blinding: String,
+    pub signer: &'a dyn DeviceSigner,
+}
+
+// blinding opens the key handle to the holder's secret, so it is redacted in logs
+impl std::fmt::Debug for DeviceBinding<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceBinding")
+            .field("key_handle", &self.key_handle)
+            .field("blinding", &redact_ark(&self.blinding))
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for DeviceBindingString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceBindingString")
+            .field("key_handle", &self.key_handle)
+            .field("blinding", &redact_str(&self.blinding))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> TryFrom<&DeviceBindingString<'a>> for DeviceBinding<'a> {
+    type Error = RDFProofsError;
+
+    fn try_from(binding: &DeviceBindingString<'a>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key_handle: multibase_to_ark(&binding.key_handle)?,
+            blinding: multibase_to_ark(&binding.blinding)?,
+            signer: binding.signer,
+        })
+    }
+}
+
+// device binding presented in VP, i.e., the re-randomized key handle opened by `blinding`
+// and the attestation over the challenge bound to it
+pub(crate) struct PresentedDeviceBinding {
+    pub key_handle: G1Affine,
+    pub blinding: Fr,
+    pub attestation: DeviceAttestation,
+}
+
+/// verifier-side validation of device attestations, i.e., checking that `certificate`
+/// certifies `device_key` under the attestation root pinned by verifier,
+/// and checking the signature by the device key over the authenticator data and the hash
+/// of the client data, and that the client data contains `challenge`
+/// as well as the expected type and origin
+pub trait DeviceAttestationVerifier {
+    fn verify_certificate(
+        &self,
+        attestation: &DeviceAttestation,
+        root: &str,
+    ) -> Result<(), RDFProofsError>;
+
+    fn verify(
+        &self,
+        attestation: &DeviceAttestation,
+        challenge: &[u8],
+    ) -> Result<(), RDFProofsError>;
+}
+
+/// key handle committing to the holder's secret, from which the device key is derived
+/// on registration, returning the key handle and its blinding
+pub fn device_key_handle<R: RngCore>(
+    rng: &mut R,
+    secret: &[u8],
+) -> Result<(G1Affine, Fr), RDFProofsError> {
+    let blinding = Fr::rand(rng);
+    Ok((get_key_handle(secret, &blinding)?, blinding))
+}
+
+pub fn device_key_handle_string<R: RngCore>(
+    rng: &mut R,
+    secret: &[u8],
+) -> Result<(String, String), RDFProofsError> {
+    let (key_handle, blinding) = device_key_handle(rng, secret)?;
+    Ok((ark_to_base64url(&key_handle)?, ark_to_base64url(&blinding)?))
+}
+
+/// challenge to be signed by the device key, binding VP challenge and key handle
+pub fn device_binding_challenge(
+    challenge: &str,
+    key_handle: &G1Affine,
+) -> Result<Vec<u8>, RDFProofsError> {
+    let mut hasher = Sha256::new();
+    hasher.update(challenge.as_bytes());
+    hasher.update(ark_to_base64url(key_handle)?.as_bytes());
+    Ok(hasher.finalize().to_vec())
+}
+
+pub fn device_binding_challenge_string(
+    challenge: &str,
+    key_handle: &str,
+) -> Result<Vec<u8>, RDFProofsError> {
+    device_binding_challenge(challenge, &multibase_to_ark(key_handle)?)
+}
+
+// check that the registered key handle commits to the holder's secret,
+// and let the device sign the challenge bound to the key handle re-randomized for this VP
+pub(crate) fn present_device_binding<R: RngCore>(
+    rng: &mut R,
+    binding: &DeviceBinding,
+    secret: &[u8],
+    challenge: &str,
+) -> Result<PresentedDeviceBinding, RDFProofsError> {
+    if get_key_handle(secret, &binding.blinding)? != binding.key_handle {
+        return Err(RDFProofsError::InvalidDeviceBinding(
+            "key handle does not commit to the holder's secret".to_string(),
+        ));
+    }
+    let blinding = Fr::rand(rng);
+    let key_handle = get_key_handle(secret, &blinding)?;
+    let attestation = binding
+        .signer
+        .sign(&device_binding_challenge(challenge, &key_handle)?)?;
+    Ok(PresentedDeviceBinding {
+        key_handle,
+        blinding,
+        attestation,
+    })
+}
+
+// check the device key against the attestation roots pinned by verifier,
+// and the attestation over the challenge bound to the key handle in VP
+pub(crate) fn verify_device_attestation(
+    verifier: &dyn DeviceAttestationVerifier,
+    roots: &[String],
+    attestation: &DeviceAttestation,
+    challenge: &str,
+    key_handle: &G1Affine,
+) -> Result<(), RDFProofsError> {
+    if roots.is_empty() {
+        return Err(RDFProofsError::InvalidDeviceBinding(
+            "no attestation root is pinned".to_string(),
+        ));
+    }
+    if !roots
+        .iter()
+        .any(|root| verifier.verify_certificate(attestation, root).is_ok())
+    {
+        return Err(RDFProofsError::InvalidDeviceBinding(
+            "device key is not certified under the pinned attestation roots".to_string(),
+        ));
+    }
+    verifier.verify(
+        attestation,
+        &device_binding_challenge(challenge, key_handle)?,
+    )
+}
+
+// key_handle := h_0^{blinding} * h[0]^{secret_int}
+pub(crate) fn get_key_handle(secret: &[u8], blinding: &Fr) -> Result<G1Affine, RDFProofsError> {
+    let params = generate_params(1);
+    let secret_int = hash_byte_to_field(secret, &get_hasher())?;
+    let committed_secret = BTreeMap::from([(0_usize, &secret_int)]);
+    Ok(params.commit_to_messages(committed_secret, blinding)?)
+}
+
+pub(crate) fn insert_device_binding(
+    vp: &mut Dataset,
+    vp_proof_id: &BlankNode,
+    vp_proof_graph_id: &BlankNode,
+    device_binding_id: &BlankNode,
+    PresentedDeviceBinding {
+        key_handle,
+        attestation,
+        ..
+    }: &PresentedDeviceBinding,
+) -> Result<(), RDFProofsError> {
+    vp.insert(QuadRef::new(
+        vp_proof_id,
+        DEVICE_BINDING,
+        device_binding_id,
+        vp_proof_graph_id,
+    ));
+    vp.insert(QuadRef::new(
+        device_binding_id,
+        KEY_HANDLE,
+        LiteralRef::new_typed_literal(&ark_to_base64url(key_handle)?, MULTIBASE),
+        vp_proof_graph_id,
+    ));
+    for (predicate, value) in [
+        (DEVICE_KEY, &attestation.device_key),
+        (DEVICE_CERTIFICATE, &attestation.certificate),
+        (AUTHENTICATOR_DATA, &attestation.authenticator_data),
+        (CLIENT_DATA_JSON, &attestation.client_data_json),
+        (DEVICE_SIGNATURE, &attestation.signature),
+    ] {
+        vp.insert(QuadRef::new(
+            device_binding_id,
+            predicate,
+            LiteralRef::new_simple_literal(value),
+            vp_proof_graph_id,
+        ));
+    }
+    Ok(())
+}
+
+// get key handle and device attestation from VP proof if exists
+pub(crate) fn get_device_binding(
+    vp_proof: &GraphView,
+) -> Result<Option<(G1Affine, DeviceAttestation)>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let mut bindings = vp_proof.objects_for_subject_predicate(proof_subject, DEVICE_BINDING);
+    let Some(binding) = bindings.next() else {
+        return Ok(None);
+    };
+    if bindings.next().is_some() {
+        return Err(RDFProofsError::InvalidDeviceBinding(
+            "VP must contain at most one device binding".to_string(),
+        ));
+    }
+    let TermRef::BlankNode(binding) = binding else {
+        return Err(RDFProofsError::InvalidDeviceBinding(
+            "device binding must be a blank node".to_string(),
+        ));
+    };
+    let get_literal = |predicate| match vp_proof.object_for_subject_predicate(binding, predicate) {
+        Some(TermRef::Literal(v)) => Ok(v),
+        _ => Err(RDFProofsError::InvalidDeviceBinding(format!(
+            "missing {}",
+            predicate
+        ))),
+    };
+
+    let key_handle = get_literal(KEY_HANDLE)?;
+    if key_handle.datatype() != MULTIBASE {
+        return Err(RDFProofsError::InvalidDeviceBinding(
+            "key handle must be typed as multibase".to_string(),
+        ));
+    }
+    let attestation = DeviceAttestation {
+        device_key: get_literal(DEVICE_KEY)?.value().to_string(),
+        certificate: get_literal(DEVICE_CERTIFICATE)?.value().to_string(),
+        authenticator_data: get_literal(AUTHENTICATOR_DATA)?.value().to_string(),
+        client_data_json: get_literal(CLIENT_DATA_JSON)?.value().to_string(),
+        signature: get_literal(DEVICE_SIGNATURE)?.value().to_string(),
+    };
+    Ok(Some((multibase_to_ark(key_handle.value())?, attestation)))
+}
@@ -0,0 +1,561 @@
+use crate::{
+    common::get_vc_from_ntriples,
+    context::{CREDENTIAL_SUBJECT, PROOF_VALUE, VERIFIABLE_CREDENTIAL_TYPE},
+    error::RDFProofsError,
+    skolem::is_skolem_iri,
+    subtree::get_subtree_triples,
+    vc::{VcPair, VcPairString, VerifiableCredential},
+};
+use oxrdf::{
+    vocab::rdf::TYPE, BlankNode, Graph, NamedNode, NamedOrBlankNode, Subject, SubjectRef, Term,
+    TermRef, Triple, TripleRef,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// disclosure preset for a type of credentials, e.g., "minimal", "kyc-lite", or "full",
+/// which is defined once and applied to any conforming VC to generate the disclosed VC
+/// and the deanonymization map to be given to `derive_proof`, so that wallets can ship
+/// curated presets, e.g.,
+///
+/// ```json
+/// {
+///   "name": "kyc-lite",
+///   "credentialTypes": ["https://example.org/KycCredential"],
+///   "disclosedPredicates": ["http://schema.org/name"],
+///   "hideIdentifiers": true
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisclosureProfile {
+    pub name: String,
+    /// types that conforming VCs must have in addition to `VerifiableCredential`
+    #[serde(default)]
+    pub credential_types: Vec<String>,
+    /// predicates of the triples to be disclosed, which must appear in conforming VCs;
+    /// the triples about VC itself, e.g., its issuer and credentialSubject, are always disclosed
+    #[serde(default)]
+    pub disclosed_predicates: Vec<String>,
+    /// disclose every triple regardless of `disclosed_predicates`
+    #[serde(default)]
+    pub disclose_all: bool,
+    /// hide the IRIs of VC and its credential subjects behind blank nodes
+    #[serde(default)]
+    pub hide_identifiers: bool,
+    /// hide the skolem IRIs, e.g., `https://example.org/.well-known/genid/b0`,
+    /// standing for the blank nodes skolemized by JSON-LD processors
+    #[serde(default)]
+    pub hide_skolem_iris: bool,
+    /// language ranges of the language-tagged literals to be disclosed, e.g., `["en"]`
+    /// matching `en` and `en-US`; literals in the other languages are left out of the disclosed VC
+    /// instead of being hidden behind blank nodes, so that no equivalence class can link them
+    /// across VCs or presentations; all languages are disclosed if empty
+    #[serde(default)]
+    pub disclosed_languages: Vec<String>,
+    /// predicates whose objects are left out of the disclosed VC with their subtrees as units,
+    /// e.g., `https://www.w3.org/2018/credentials#evidence`, taking precedence over the others
+    #[serde(default)]
+    pub hidden_subtrees: Vec<String>,
+}
+
+impl DisclosureProfile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn conforms(&self, vc: &VerifiableCredential) -> bool {
+        self.get_credential(vc).is_ok()
+    }
+
+    /// generate the disclosed VC according to the profile, where the hidden identifiers
+    /// are replaced with fresh blank nodes; returns the VC pair and the deanonymization map
+    /// for the hidden identifiers to be given to `derive_proof`
+    pub fn apply(
+        &self,
+        vc: &VerifiableCredential,
+    ) -> Result<(VcPair, HashMap<NamedOrBlankNode, Term>), RDFProofsError> {
+        let credential = self.get_credential(vc)?;
+        let disclosed_predicates = self
+            .disclosed_predicates
+            .iter()
+            .map(NamedNode::new)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut hidden_terms = HashSet::<Term>::new();
+        if self.hide_identifiers {
+            if let SubjectRef::NamedNode(n) = credential {
+                hidden_terms.insert(n.into_owned().into());
+            }
+            for subject in vc
+                .document
+                .objects_for_subject_predicate(credential, CREDENTIAL_SUBJECT)
+            {
+                if let TermRef::NamedNode(n) = subject {
+                    hidden_terms.insert(n.into_owned().into());
+                }
+            }
+        }
+
+        if self.hide_skolem_iris {
+            for triple in vc.document.iter() {
+                if let SubjectRef::NamedNode(n) = triple.subject {
+                    if is_skolem_iri(n) {
+                        hidden_terms.insert(n.into_owned().into());
+                    }
+                }
+                if let TermRef::NamedNode(n) = triple.object {
+                    if is_skolem_iri(n) {
+                        hidden_terms.insert(n.into_owned().into());
+                    }
+                }
+            }
+        }
+
+        let hidden_subtrees = self
+            .hidden_subtrees
+            .iter()
+            .map(NamedNode::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let hidden_subtree_triples = get_subtree_triples(&vc.document, &hidden_subtrees);
+
+        let mut disclosed_triples = vc
+            .document
+            .iter()
+            .filter(|t| !hidden_subtree_triples.contains(t))
+            .filter(|t| {
+                (self.disclose_all
+                    || t.subject == credential
+                    || disclosed_predicates
+                        .iter()
+                        .any(|p| p.as_ref() == t.predicate))
+                    && self.discloses_language(t.object)
+            })
+            .map(|t| t.into_owned())
+            .collect::<Vec<_>>();
+        // sort triples to label hidden terms deterministically
+        disclosed_triples.sort_by_cached_key(|t| t.to_string());
+
+        // labels of fresh blank nodes must not collide with blank nodes in the original VC
+        let original_bnodes: HashSet<_> = vc
+            .document
+            .iter()
+            .flat_map(|t| [t.subject.into(), t.object])
+            .filter_map(|term| match term {
+                TermRef::BlankNode(b) => Some(b.as_str().to_string()),
+                _ => None,
+            })
+            .collect();
+        let mut labels = (0..)
+            .map(|i| format!("e{}", i))
+            .filter(|l| !original_bnodes.contains(l));
+        let mut anonymized: HashMap<Term, BlankNode> = HashMap::new();
+        let mut anonymize = |term: Term| -> Term {
+            if !hidden_terms.contains(&term) {
+                return term;
+            }
+            anonymized
+                .entry(term)
+                .or_insert_with(|| BlankNode::new_unchecked(labels.next().unwrap_or_default()))
+                .clone()
+                .into()
+        };
+        let disclosed_document: Graph = disclosed_triples
+            .into_iter()
+            .map(|t| {
+                let subject = match anonymize(t.subject.into()) {
+                    Term::NamedNode(n) => Subject::NamedNode(n),
+                    Term::BlankNode(b) => Subject::BlankNode(b),
+                    _ => return Err(RDFProofsError::DeAnonymization),
+                };
+                Ok(Triple::new(subject, t.predicate, anonymize(t.object)))
+            })
+            .collect::<Result<_, RDFProofsError>>()?;
+
+        // proof value is not disclosed
+        let disclosed_proof: Graph = vc
+            .proof
+            .iter()
+            .filter(|t| t.predicate != PROOF_VALUE)
+            .collect();
+
+        let deanon_map = anonymized
+            .into_iter()
+            .map(|(term, bnode)| (bnode.into(), term))
+            .collect();
+
+        Ok((
+            VcPair::new(
+                vc.clone(),
+                VerifiableCredential::new(disclosed_document, disclosed_proof),
+            ),
+            deanon_map,
+        ))
+    }
+
+    /// `apply` with the original VC in N-Triples format,
+    /// returning the VC pair and the deanonymization map to be given to `derive_proof_string`
+    pub fn apply_string(
+        &self,
+        document: &str,
+        proof: &str,
+    ) -> Result<(VcPairString, HashMap<String, String>), RDFProofsError> {
+        let vc = get_vc_from_ntriples(document, proof)?;
+        let (VcPair { disclosed, .. }, deanon_map) = self.apply(&vc)?;
+        let to_ntriples =
+            |graph: &Graph| -> String { graph.iter().map(|t| format!("{} .\n", t)).collect() };
+        Ok((
+            VcPairString::new(
+                document,
+                proof,
+                &to_ntriples(&disclosed.document),
+                &to_ntriples(&disclosed.proof),
+            ),
+            deanon_map
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ))
+    }
+
+    // whether the object is a literal without language tag
+    // or with language tag matching `disclosed_languages` in the basic filtering of RFC 4647
+    fn discloses_language(&self, object: TermRef) -> bool {
+        let TermRef::Literal(literal) = object else {
+            return true;
+        };
+        let Some(language) = literal.language() else {
+            return true;
+        };
+        self.disclosed_languages.is_empty()
+            || self.disclosed_languages.iter().any(|range| {
+                let language = language.to_ascii_lowercase();
+                let range = range.to_ascii_lowercase();
+                range == "*" || language == range || language.starts_with(&format!("{}-", range))
+            })
+    }
+
+    // the VC node of `vc` if it conforms to the profile
+    fn get_credential<'a>(
+        &self,
+        vc: &'a VerifiableCredential,
+    ) -> Result<SubjectRef<'a>, RDFProofsError> {
+        let non_conforming = |reason: String| {
+            RDFProofsError::NonConformingDisclosureProfile(format!("{}: {}", self.name, reason))
+        };
+        let credential = vc
+            .document
+            .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+            .ok_or_else(|| non_conforming("missing VerifiableCredential".to_string()))?;
+        for credential_type in &self.credential_types {
+            let credential_type = NamedNode::new(credential_type)?;
+            if !vc
+                .document
+                .contains(TripleRef::new(credential, TYPE, credential_type.as_ref()))
+            {
+                return Err(non_conforming(format!("missing type {}", credential_type)));
+            }
+        }
+        for predicate in &self.disclosed_predicates {
+            let predicate = NamedNode::new(predicate)?;
+            if vc
+                .document
+                .triples_for_predicate(predicate.as_ref())
+                .next()
+                .is_none()
+            {
+                return Err(non_conforming(format!("missing predicate {}", predicate)));
+            }
+        }
+        Ok(credential)
+    }
+}
+
+#[cfg(all(test, feature = "verify"))]
+mod tests {
+    use super::DisclosureProfile;
+    use crate::{
+        common::get_vc_from_ntriples, derive_proof_string, error::RDFProofsError, sign_string,
+        verify_proof_string, vp_equivalence_classes_string, DeriveOptionsString,
+        VerifyOptionsString,
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    "#;
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/KycCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+
+    #[test]
+    fn derive_and_verify_proof_with_disclosure_profile() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let proof = sign_string(
+            &mut rng,
+            VC,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let profile = DisclosureProfile {
+            credential_types: vec!["https://example.org/KycCredential".to_string()],
+            disclosed_predicates: vec!["http://schema.org/name".to_string()],
+            hide_identifiers: true,
+            ..DisclosureProfile::new("kyc-lite")
+        };
+        // profiles can be shipped in any serde format
+        let serialized = serde_cbor::to_vec(&profile).unwrap();
+        let profile: DisclosureProfile = serde_cbor::from_slice(&serialized).unwrap();
+
+        let (vc_pair, deanon_map) = profile.apply_string(VC, &proof).unwrap();
+        assert!(vc_pair.disclosed_document.contains("\"John Smith\""));
+        assert!(vc_pair.disclosed_document.contains("credentials#issuer"));
+        assert!(!vc_pair.disclosed_document.contains("birthDate"));
+        assert!(!vc_pair.disclosed_document.contains("<did:example:john>"));
+        assert!(!vc_pair.disclosed_proof.contains("proofValue"));
+        assert_eq!(deanon_map.len(), 2);
+        assert!(deanon_map.values().any(|v| v == "<did:example:john>"));
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vec![vc_pair],
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+
+        // "full" discloses every triple as is
+        let full = DisclosureProfile {
+            disclose_all: true,
+            ..DisclosureProfile::new("full")
+        };
+        let vc = get_vc_from_ntriples(VC, &proof).unwrap();
+        let (vc_pair, deanon_map) = full.apply(&vc).unwrap();
+        assert_eq!(vc_pair.disclosed.document, vc.document);
+        assert!(deanon_map.is_empty());
+
+        // VCs of other types do not conform
+        let profile = DisclosureProfile {
+            credential_types: vec!["https://example.org/VaccinationCredential".to_string()],
+            ..DisclosureProfile::new("minimal")
+        };
+        assert!(!profile.conforms(&vc));
+        assert!(matches!(
+            profile.apply(&vc),
+            Err(RDFProofsError::NonConformingDisclosureProfile(_))
+        ));
+    }
+
+    const VC_WITH_EVIDENCE: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <https://example.org/checkedDocument> _:doc .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#evidence> _:ev .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#termsOfUse> _:tou .
+    _:ev <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/DocumentVerification> .
+    _:ev <https://example.org/document> _:doc .
+    _:doc <https://example.org/documentType> "Passport" .
+    _:tou <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/IssuerPolicy> .
+    "#;
+
+    #[test]
+    fn derive_and_verify_proof_with_hidden_subtrees() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let proof = sign_string(
+            &mut rng,
+            VC_WITH_EVIDENCE,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let profile = DisclosureProfile {
+            disclose_all: true,
+            hidden_subtrees: vec![
+                "https://www.w3.org/2018/credentials#evidence".to_string(),
+                "https://www.w3.org/2018/credentials#termsOfUse".to_string(),
+            ],
+            ..DisclosureProfile::new("no-evidence")
+        };
+        let (vc_pair, deanon_map) = profile.apply_string(VC_WITH_EVIDENCE, &proof).unwrap();
+        assert!(vc_pair.disclosed_document.contains("\"John Smith\""));
+        for hidden in [
+            "evidence",
+            "termsOfUse",
+            "checkedDocument",
+            "Passport",
+            "IssuerPolicy",
+        ] {
+            assert!(!vc_pair.disclosed_document.contains(hidden));
+        }
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vec![vc_pair],
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        let verified = verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        );
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    const VC_MULTILINGUAL: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith"@en .
+    <did:example:john> <http://schema.org/name> "Jean Smith"@fr .
+    <did:example:john> <http://schema.org/name> "ジョン・スミス"@ja .
+    <did:example:john> <http://schema.org/jobTitle> "Engineer"@en-US .
+    <did:example:john> <http://schema.org/jobTitle> "Ingénieur"@fr-CA .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+    #[test]
+    fn derive_and_verify_proof_with_disclosed_languages() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let proof = sign_string(
+            &mut rng,
+            VC_MULTILINGUAL,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let profile = DisclosureProfile {
+            disclosed_predicates: vec![
+                "http://schema.org/name".to_string(),
+                "http://schema.org/jobTitle".to_string(),
+            ],
+            hide_identifiers: true,
+            disclosed_languages: vec!["EN".to_string()],
+            ..DisclosureProfile::new("english")
+        };
+        let (vc_pair, deanon_map) = profile.apply_string(VC_MULTILINGUAL, &proof).unwrap();
+        assert!(vc_pair.disclosed_document.contains("\"John Smith\"@en"));
+        assert!(vc_pair.disclosed_document.contains("\"Engineer\"@en-US"));
+        assert!(!vc_pair.disclosed_document.contains("@fr"));
+        assert!(!vc_pair.disclosed_document.contains("@ja"));
+        // only identifiers are hidden, not the other language variants
+        assert_eq!(deanon_map.len(), 2);
+
+        let vc_pairs = vec![vc_pair];
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(!derived_proof.contains("@fr"));
+        assert!(!derived_proof.contains("@ja"));
+        assert!(vp_equivalence_classes_string(&derived_proof)
+            .unwrap()
+            .is_empty());
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+    }
+
+    const VC_SKOLEMIZED: &str = r#"
+    <https://example.org/.well-known/genid/a1> <http://schema.org/name> "John Smith" .
+    <https://example.org/.well-known/genid/a1> <http://schema.org/address> <https://example.org/.well-known/genid/a2> .
+    <https://example.org/.well-known/genid/a2> <http://schema.org/addressCountry> "JP" .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <https://example.org/.well-known/genid/a1> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+    #[test]
+    fn apply_disclosure_profile_hiding_skolem_iris() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let proof = sign_string(
+            &mut rng,
+            VC_SKOLEMIZED,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let profile = DisclosureProfile {
+            disclose_all: true,
+            hide_skolem_iris: true,
+            ..DisclosureProfile::new("full-unlinkable")
+        };
+        let (vc_pair, deanon_map) = profile.apply_string(VC_SKOLEMIZED, &proof).unwrap();
+        assert!(!vc_pair.disclosed_document.contains("genid"));
+        assert!(vc_pair
+            .disclosed_document
+            .contains("<http://example.org/vcred/00>"));
+        assert_eq!(deanon_map.len(), 2);
+        assert!(deanon_map
+            .values()
+            .any(|v| v == "<https://example.org/.well-known/genid/a1>"));
+    }
+}
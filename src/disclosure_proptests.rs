@@ -0,0 +1,260 @@
+// property-based tests for random credentials and random disclosure subsets,
+// covering duplicate triples, repeated literals, and shared blank nodes
+use crate::{
+    derive_proof_string, error::RDFProofsError, sign_string, verify_proof_string,
+    DeriveOptionsString, VcPairString, VerifyOptionsString,
+};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use proptest::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    "#;
+const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+
+const HOLDER: &str = "<did:example:john>";
+const VC_ID: &str = "<http://example.org/vcred/00>";
+// shared by the claims whose subject or object is `Node::Nested`
+const NESTED: &str = "_:b0";
+const PREDICATES: [&str; 3] = [
+    "<http://schema.org/name>",
+    "<http://schema.org/knows>",
+    "<http://example.org/vocab/value>",
+];
+const LITERALS: [&str; 4] = [
+    "\"Alice\"",
+    "\"Bob\"",
+    "\"2022-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>",
+    "\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+];
+const IRIS: [&str; 2] = ["<http://example.org/a>", "<http://example.org/b>"];
+const TAMPERED: &str = "\"**********\"";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    Holder,
+    Nested,
+    Literal(usize),
+    Iri(usize),
+}
+
+#[derive(Clone, Debug)]
+struct Claim {
+    subject: Node,
+    predicate: usize,
+    object: Node,
+    disclosed: bool,
+    hidden: bool,
+}
+
+impl Claim {
+    fn triple(&self) -> (Node, usize, Node) {
+        (self.subject.clone(), self.predicate, self.object.clone())
+    }
+
+    fn to_ntriples(&self, hide_holder: bool, hide: bool) -> String {
+        format!(
+            "{} {} {} .\n",
+            get_term(&self.subject, hide_holder, false),
+            PREDICATES[self.predicate],
+            get_term(&self.object, hide_holder, hide)
+        )
+    }
+}
+
+fn get_term(node: &Node, hide_holder: bool, hide: bool) -> String {
+    match node {
+        Node::Holder if hide_holder => "_:e0".to_string(),
+        Node::Holder => HOLDER.to_string(),
+        Node::Nested => NESTED.to_string(),
+        // the same term is hidden with the same blank node
+        Node::Literal(i) if hide => format!("_:e{}", 2 + i),
+        Node::Literal(i) => LITERALS[*i].to_string(),
+        Node::Iri(i) if hide => format!("_:e{}", 2 + LITERALS.len() + i),
+        Node::Iri(i) => IRIS[*i].to_string(),
+    }
+}
+
+fn claim() -> impl Strategy<Value = Claim> {
+    (
+        prop_oneof![3 => Just(Node::Holder), 1 => Just(Node::Nested)],
+        0..PREDICATES.len(),
+        prop_oneof![
+            (0..LITERALS.len()).prop_map(Node::Literal),
+            (0..IRIS.len()).prop_map(Node::Iri),
+            Just(Node::Nested),
+        ],
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(subject, predicate, object, disclosed, hidden)| Claim {
+            subject,
+            predicate,
+            object,
+            disclosed,
+            hidden,
+        })
+}
+
+// random credential given as claims with their disclosure decisions;
+// duplicated claims are kept in the credential to be merged into a triple,
+// whose disclosure is decided by the first one
+fn credential() -> impl Strategy<Value = (Vec<Claim>, bool)> {
+    (prop::collection::vec(claim(), 1..8), any::<bool>())
+}
+
+fn unique_claims(claims: &[Claim]) -> Vec<Claim> {
+    let mut seen = HashSet::new();
+    claims
+        .iter()
+        .filter(|c| seen.insert(c.triple()))
+        .cloned()
+        .collect()
+}
+
+fn envelope(holder: &str, vc_id: &str) -> String {
+    format!(
+        r#"{vc_id} <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+{vc_id} <https://www.w3.org/2018/credentials#credentialSubject> {holder} .
+{vc_id} <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+{vc_id} <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+{vc_id} <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#
+    )
+}
+
+fn get_document(claims: &[Claim]) -> String {
+    let mut document = envelope(HOLDER, VC_ID);
+    for claim in claims {
+        document.push_str(&claim.to_ntriples(false, false));
+    }
+    document
+}
+
+fn get_disclosed_document(claims: &[Claim], hide_holder: bool) -> String {
+    let holder = if hide_holder { "_:e0" } else { HOLDER };
+    let mut document = envelope(holder, "_:e1");
+    for claim in unique_claims(claims).iter().filter(|c| c.disclosed) {
+        let hide = claim.hidden && matches!(claim.object, Node::Literal(_) | Node::Iri(_));
+        document.push_str(&claim.to_ntriples(hide_holder, hide));
+    }
+    document
+}
+
+fn get_deanon_map() -> HashMap<String, String> {
+    let mut deanon_map = HashMap::from([
+        ("_:e0".to_string(), HOLDER.to_string()),
+        ("_:e1".to_string(), VC_ID.to_string()),
+    ]);
+    for (i, literal) in LITERALS.iter().enumerate() {
+        deanon_map.insert(format!("_:e{}", 2 + i), literal.to_string());
+    }
+    for (i, iri) in IRIS.iter().enumerate() {
+        deanon_map.insert(format!("_:e{}", 2 + LITERALS.len() + i), iri.to_string());
+    }
+    deanon_map
+}
+
+fn derive_and_verify(
+    rng: &mut StdRng,
+    document: &str,
+    proof: &str,
+    disclosed_document: &str,
+) -> Result<(), RDFProofsError> {
+    let disclosed_proof: String = proof
+        .lines()
+        .filter(|line| !line.contains("<https://w3id.org/security#proofValue>"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let vc_pairs = vec![VcPairString::new(
+        document,
+        proof,
+        disclosed_document,
+        &disclosed_proof,
+    )];
+    // deanon map can include unused entries, e.g., literals that are not hidden
+    let deanon_map = get_deanon_map();
+
+    let derived_proof = derive_proof_string(
+        rng,
+        &vc_pairs,
+        &deanon_map,
+        KEY_GRAPH,
+        None,
+        None,
+        DeriveOptionsString::default(),
+    )?;
+    verify_proof_string(
+        rng,
+        &derived_proof,
+        KEY_GRAPH,
+        None,
+        None,
+        VerifyOptionsString::default(),
+    )?;
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn derive_and_verify_random_disclosure(
+        (claims, hide_holder) in credential(),
+        seed in any::<u64>(),
+    ) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let document = get_document(&claims);
+        let proof = sign_string(&mut rng, &document, VC_PROOF_WITHOUT_PROOFVALUE, KEY_GRAPH, None, None)
+            .unwrap();
+        let disclosed_document = get_disclosed_document(&claims, hide_holder);
+
+        let verified = derive_and_verify(&mut rng, &document, &proof, &disclosed_document);
+        prop_assert!(verified.is_ok(), "{:?}", verified);
+    }
+
+    #[test]
+    fn modified_undisclosed_term_breaks_verification(
+        (claims, hide_holder) in credential(),
+        seed in any::<u64>(),
+    ) {
+        // modify a literal that is either undisclosed or hidden after signing
+        let unique = unique_claims(&claims);
+        let target = unique
+            .iter()
+            .find(|c| (!c.disclosed || c.hidden) && matches!(c.object, Node::Literal(_)));
+        prop_assume!(target.is_some());
+        let target = target.unwrap();
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let document = get_document(&claims);
+        let proof = sign_string(&mut rng, &document, VC_PROOF_WITHOUT_PROOFVALUE, KEY_GRAPH, None, None)
+            .unwrap();
+        let disclosed_document = get_disclosed_document(&claims, hide_holder);
+
+        let modified_document = document.replace(
+            &target.to_ntriples(false, false),
+            &format!(
+                "{} {} {} .\n",
+                get_term(&target.subject, false, false),
+                PREDICATES[target.predicate],
+                TAMPERED
+            ),
+        );
+        prop_assert_ne!(&modified_document, &document);
+
+        let verified =
+            derive_and_verify(&mut rng, &modified_document, &proof, &disclosed_document);
+        prop_assert!(verified.is_err());
+    }
+}
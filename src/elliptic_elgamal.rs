@@ -1,7 +1,7 @@
 use crate::common::{get_hasher, hash_byte_to_field, Fr, PedersenCommitmentStmt, Statements};
+use crate::curve::{Curve, G1Affine, G1Projective};
 use crate::error::RDFProofsError;
 use crate::{ark_to_base64url, multibase_to_ark};
-use ark_bls12_381::{Bls12_381, G1Affine, G1Projective};
 use ark_crypto_primitives::encryption::elgamal::{
     Ciphertext, ElGamal, Parameters, PublicKey, Randomness, SecretKey,
 };
@@ -9,8 +9,9 @@ use ark_crypto_primitives::encryption::AsymmetricEncryptionScheme;
 use ark_std::rand::RngCore;
 use ark_std::rand::{rngs::StdRng, SeedableRng};
 use ark_std::UniformRand;
+use std::collections::BTreeSet;
 
-use ark_ec::AffineRepr;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::PrimeField;
 use proof_system::witness::{Witness, Witnesses};
 
@@ -22,29 +23,41 @@ pub type ElGamalParams = Parameters<G1Projective>;
 pub struct ElGamalVerifiableEncryption {
     pub cipher_text: ElGamalCiphertext,
     pub statements: Statements,
-    pub witnesses: Witnesses<Bls12_381>,
+    pub witnesses: Witnesses<Curve>,
+}
+/// verifiable encryption of uid escrowed among multiple openers,
+/// with a ciphertext per opener in the same order as their public keys
+pub struct ElGamalEscrowedEncryption {
+    pub cipher_texts: Vec<ElGamalCiphertext>,
+    pub statements: Statements,
+    pub witnesses: Witnesses<Curve>,
+}
+
+// fixed parameters shared by all parties
+fn get_elgamal_params() -> Result<ElGamalParams, RDFProofsError> {
+    let mut param_rnd = StdRng::seed_from_u64(0u64);
+    Ok(Bls12381ElGamal::setup(&mut param_rnd)?)
 }
 
 pub fn str_to_secret_key(s: &str) -> Result<ElGamalSecretKey, RDFProofsError> {
-    let secret = multibase_to_ark(s).unwrap();
+    let secret = multibase_to_ark(s)?;
     Ok(SecretKey::<G1Projective>(secret))
 }
 
 pub fn get_encrypted_uid(uid: &Vec<u8>, hd_hat: &G1Affine) -> Result<String, RDFProofsError> {
     let hasher = get_hasher();
-    let uid = hash_byte_to_field(uid, &hasher).unwrap();
+    let uid = hash_byte_to_field(uid, &hasher)?;
     let encrypted = hd_hat.mul_bigint(uid.into_bigint());
     let encrypted: G1Affine = encrypted.into();
-    Ok(ark_to_base64url(&encrypted).unwrap())
+    ark_to_base64url(&encrypted)
 }
 
 pub fn elliptic_elgamal_keygen<R: RngCore>(
     rng: &mut R,
 ) -> Result<(ElGamalPublicKey, ElGamalSecretKey), RDFProofsError> {
-    let mut param_rnd = StdRng::seed_from_u64(0u64);
-    let params = Bls12381ElGamal::setup(&mut param_rnd).unwrap();
+    let params = get_elgamal_params()?;
 
-    let (pk, sk) = Bls12381ElGamal::keygen(&params, rng).unwrap();
+    let (pk, sk) = Bls12381ElGamal::keygen(&params, rng)?;
 
     Ok((pk, sk))
 }
@@ -54,12 +67,11 @@ pub fn elliptic_elgamal_encrypt<R: RngCore>(
     msg: &G1Affine,
     rng: &mut R,
 ) -> Result<ElGamalCiphertext, RDFProofsError> {
-    let mut param_rnd = StdRng::seed_from_u64(0u64);
-    let params = Bls12381ElGamal::setup(&mut param_rnd).unwrap();
+    let params = get_elgamal_params()?;
 
     let r = Randomness::rand(rng);
 
-    let (c1, c2) = Bls12381ElGamal::encrypt(&params, &pk, &msg, &r).unwrap();
+    let (c1, c2) = Bls12381ElGamal::encrypt(&params, &pk, &msg, &r)?;
     Ok((c1, c2))
 }
 
@@ -67,10 +79,9 @@ pub fn elliptic_elgamal_decrypt(
     sk: &ElGamalSecretKey,
     cipher: &ElGamalCiphertext,
 ) -> Result<G1Affine, RDFProofsError> {
-    let mut param_rnd = StdRng::seed_from_u64(0u64);
-    let params = Bls12381ElGamal::setup(&mut param_rnd).unwrap();
+    let params = get_elgamal_params()?;
 
-    let msg = Bls12381ElGamal::decrypt(&params, &sk, &cipher).unwrap();
+    let msg = Bls12381ElGamal::decrypt(&params, &sk, &cipher)?;
     Ok(msg)
 }
 
@@ -80,8 +91,7 @@ pub fn elliptic_elgamal_verifiable_encryption_with_bbs_plus<R: RngCore>(
     uid: &Fr,
     rng: &mut R,
 ) -> Result<ElGamalVerifiableEncryption, RDFProofsError> {
-    let mut param_rnd = StdRng::seed_from_u64(0u64);
-    let params: ElGamalParams = Bls12381ElGamal::setup(&mut param_rnd).unwrap();
+    let params = get_elgamal_params()?;
 
     let g0 = params.generator;
     let r: Fr = Fr::rand(rng);
@@ -117,8 +127,7 @@ pub fn verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus(
     hd_hat: &G1Affine,
     cipher_text: &ElGamalCiphertext,
 ) -> Result<Statements, RDFProofsError> {
-    let mut param_rnd = StdRng::seed_from_u64(0u64);
-    let params: ElGamalParams = Bls12381ElGamal::setup(&mut param_rnd).unwrap();
+    let params = get_elgamal_params()?;
 
     let g0 = params.generator;
     let (e1, e2) = cipher_text;
@@ -135,11 +144,145 @@ pub fn verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus(
     Ok(statements)
 }
 
+/// escrow uid among multiple openers so that no single opener can deanonymize the holder:
+/// uid is split into random additive shares `uid = s_1 + ... + s_n`,
+/// each of which is encrypted under the corresponding opener's public key,
+/// with the proof that the shares are consistent with uid
+pub fn elliptic_elgamal_escrowed_encryption_with_bbs_plus<R: RngCore>(
+    pks: &[ElGamalPublicKey],
+    hd_hat: &G1Affine,
+    uid: &Fr,
+    rng: &mut R,
+) -> Result<ElGamalEscrowedEncryption, RDFProofsError> {
+    if pks.is_empty() {
+        return Err(RDFProofsError::MissingSecretOrOpenerPubKey);
+    }
+    let params = get_elgamal_params()?;
+
+    let g0 = params.generator;
+
+    // s_n = uid - (s_1 + ... + s_{n-1})
+    let mut shares: Vec<Fr> = (1..pks.len()).map(|_| Fr::rand(rng)).collect();
+    let last_share = shares.iter().fold(*uid, |acc, s| acc - s);
+    shares.push(last_share);
+    let rs: Vec<Fr> = pks.iter().map(|_| Fr::rand(rng)).collect();
+
+    let mut cipher_texts = vec![];
+    let mut statements = Statements::new();
+    let mut witnesses = Witnesses::new();
+    for ((pk, share), r) in pks.iter().zip(&shares).zip(&rs) {
+        // e1_i = g0 * r_i
+        let e1 = g0.mul_bigint(r.into_bigint());
+        // e2_i = hd_hat * s_i + pk_i * r_i
+        let e2 = hd_hat.mul_bigint(share.into_bigint()) + pk.mul_bigint(r.into_bigint());
+        cipher_texts.push((e1.into_affine(), e2.into_affine()));
+
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            vec![g0],
+            e1.into(),
+        ));
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            vec![*hd_hat, *pk],
+            e2.into(),
+        ));
+        witnesses.add(Witness::PedersenCommitment(vec![*r]));
+        witnesses.add(Witness::PedersenCommitment(vec![*share, *r]));
+    }
+
+    // sum of e2_i = hd_hat * uid + pk_1 * r_1 + ... + pk_n * r_n
+    if pks.len() > 1 {
+        let (bases, e2_sum) = get_escrow_sum_bases_and_commitment(pks, hd_hat, &cipher_texts);
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            bases, e2_sum,
+        ));
+        witnesses.add(Witness::PedersenCommitment([vec![*uid], rs].concat()));
+    }
+
+    Ok(ElGamalEscrowedEncryption {
+        cipher_texts,
+        statements,
+        witnesses,
+    })
+}
+
+pub fn verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+    pks: &[ElGamalPublicKey],
+    hd_hat: &G1Affine,
+    cipher_texts: &[ElGamalCiphertext],
+) -> Result<Statements, RDFProofsError> {
+    if pks.is_empty() || pks.len() != cipher_texts.len() {
+        return Err(RDFProofsError::InvalidEncryptedUid);
+    }
+
+    let mut statements = Statements::new();
+    for (pk, cipher_text) in pks.iter().zip(cipher_texts) {
+        for statement in
+            verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus(pk, hd_hat, cipher_text)?.0
+        {
+            statements.add(statement);
+        }
+    }
+    if pks.len() > 1 {
+        let (bases, e2_sum) = get_escrow_sum_bases_and_commitment(pks, hd_hat, cipher_texts);
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            bases, e2_sum,
+        ));
+    }
+    Ok(statements)
+}
+
+/// each opener decrypts its own ciphertext into `hd_hat * s_i` with `elliptic_elgamal_decrypt`,
+/// and all the decrypted shares are combined into `hd_hat * uid`
+pub fn combine_decrypted_shares(shares: &[G1Affine]) -> G1Affine {
+    shares
+        .iter()
+        .fold(G1Projective::default(), |acc, s| acc + s)
+        .into_affine()
+}
+
+// witness equalities binding the randomness `r_i` shared among the statements for each opener,
+// and the position of the encrypted uid, for the escrowed encryption statements starting at `offset`
+pub(crate) fn get_escrowed_encryption_equalities(
+    offset: usize,
+    opener_count: usize,
+) -> (Vec<BTreeSet<(usize, usize)>>, (usize, usize)) {
+    let sum_index = offset + 2 * opener_count;
+    let equalities = (0..opener_count)
+        .map(|i| {
+            // `0` is r_i in e1_i, and `1` is r_i in e2_i (`0` corresponds to s_i)
+            let mut equality = BTreeSet::from([(offset + 2 * i, 0), (offset + 2 * i + 1, 1)]);
+            if opener_count > 1 {
+                // `1 + i` is r_i in the sum (`0` corresponds to uid)
+                equality.insert((sum_index, 1 + i));
+            }
+            equality
+        })
+        .collect();
+    let uid_position = if opener_count > 1 {
+        (sum_index, 0)
+    } else {
+        (offset + 1, 0)
+    };
+    (equalities, uid_position)
+}
+
+fn get_escrow_sum_bases_and_commitment(
+    pks: &[ElGamalPublicKey],
+    hd_hat: &G1Affine,
+    cipher_texts: &[ElGamalCiphertext],
+) -> (Vec<G1Affine>, G1Affine) {
+    let bases = [vec![*hd_hat], pks.to_vec()].concat();
+    let e2_sum =
+        combine_decrypted_shares(&cipher_texts.iter().map(|(_, e2)| *e2).collect::<Vec<_>>());
+    (bases, e2_sum)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::common::{get_hasher, hash_byte_to_field, Proof};
     use crate::constants::BLIND_SIG_REQUEST_CONTEXT;
 
+    use crate::curve::G1Affine;
     use crate::error::RDFProofsError;
     use crate::{
         ark_to_base64url, get_encrypted_uid, multibase_to_ark, str_to_secret_key, ElGamalPublicKey,
@@ -147,17 +290,19 @@ mod tests {
     use crate::{
         common::{BBSPlusHash, Fr},
         elliptic_elgamal::{
-            elliptic_elgamal_decrypt, elliptic_elgamal_encrypt, elliptic_elgamal_keygen,
+            combine_decrypted_shares, elliptic_elgamal_decrypt, elliptic_elgamal_encrypt,
+            elliptic_elgamal_escrowed_encryption_with_bbs_plus, elliptic_elgamal_keygen,
             elliptic_elgamal_verifiable_encryption_with_bbs_plus,
+            get_escrowed_encryption_equalities,
+            verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus,
             verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus,
         },
     };
-    use ark_bls12_381::G1Affine;
     use ark_std::rand::{rngs::StdRng, SeedableRng};
     use ark_std::UniformRand;
     use dock_crypto_utils::hashing_utils::projective_group_elem_from_try_and_incr;
 
-    use proof_system::meta_statement::MetaStatements;
+    use proof_system::meta_statement::{EqualWitnesses, MetaStatements};
     use proof_system::proof_spec::ProofSpec;
 
     pub fn hash_str_to_affine(payload: &str) -> Result<G1Affine, RDFProofsError> {
@@ -320,4 +465,97 @@ mod tests {
             )
             .is_err());
     }
+
+    #[test]
+    fn test_elliptic_elgamal_escrowed_encryption_with_bbs_plus() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let (pks, sks): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| elliptic_elgamal_keygen(&mut rng).unwrap())
+            .unzip();
+
+        let raw_uid = b"SECRET";
+        let hasher = get_hasher();
+        let uid = hash_byte_to_field(raw_uid, &hasher).unwrap();
+        let hd_hat = G1Affine::rand(&mut rng);
+
+        let res = elliptic_elgamal_escrowed_encryption_with_bbs_plus(&pks, &hd_hat, &uid, &mut rng)
+            .unwrap();
+        let (equalities, _) = get_escrowed_encryption_equalities(0, pks.len());
+        let mut meta_statements = MetaStatements::new();
+        for equality in equalities {
+            meta_statements.add_witness_equality(EqualWitnesses(equality));
+        }
+        let context = Some(BLIND_SIG_REQUEST_CONTEXT.to_vec());
+        let proof_spec = ProofSpec::new(
+            res.statements,
+            meta_statements.clone(),
+            vec![],
+            context.clone(),
+        );
+        proof_spec.validate().unwrap();
+
+        let proof = Proof::new::<StdRng, BBSPlusHash>(
+            &mut rng,
+            proof_spec,
+            res.witnesses,
+            Option::from("CHALLENGE".as_bytes().to_vec()),
+            Default::default(),
+        )
+        .unwrap()
+        .0;
+
+        let statements = verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+            &pks,
+            &hd_hat,
+            &res.cipher_texts,
+        )
+        .unwrap();
+        let proof_spec = ProofSpec::new(statements, meta_statements, vec![], context);
+        assert!(proof
+            .verify::<StdRng, BBSPlusHash>(
+                &mut rng,
+                proof_spec,
+                Option::from("CHALLENGE".as_bytes().to_vec()),
+                Default::default(),
+            )
+            .is_ok());
+
+        // all the openers are required to recover uid
+        let shares: Vec<_> = sks
+            .iter()
+            .zip(&res.cipher_texts)
+            .map(|(sk, cipher_text)| elliptic_elgamal_decrypt(sk, cipher_text).unwrap())
+            .collect();
+        let expected = get_encrypted_uid(&raw_uid.to_vec(), &hd_hat).unwrap();
+        assert_eq!(
+            ark_to_base64url(&combine_decrypted_shares(&shares)).unwrap(),
+            expected
+        );
+        assert_ne!(
+            ark_to_base64url(&combine_decrypted_shares(&shares[..2])).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_escrowed_encryption_with_mismatched_opener_pub_keys_failure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let pks: Vec<_> = (0..3)
+            .map(|_| elliptic_elgamal_keygen(&mut rng).unwrap().0)
+            .collect();
+        let uid: Fr = Fr::rand(&mut rng);
+        let hd_hat = G1Affine::rand(&mut rng);
+
+        let res = elliptic_elgamal_escrowed_encryption_with_bbs_plus(&pks, &hd_hat, &uid, &mut rng)
+            .unwrap();
+        let statements = verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+            &pks[..2],
+            &hd_hat,
+            &res.cipher_texts,
+        );
+        assert!(matches!(
+            statements,
+            Err(RDFProofsError::InvalidEncryptedUid)
+        ));
+    }
 }
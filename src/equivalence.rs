@@ -0,0 +1,245 @@
+use crate::{
+    common::{get_dataset_from_nquads, is_nym, BlankNodeLabeler},
+    context::{EQUIVALENCES, EQUIVALENCE_CLASS_TYPE, EQUIVALENT_TERM, IN_CREDENTIAL, OCCURRENCE},
+    error::RDFProofsError,
+    ordered_triple::OrderedVerifiableCredentialGraphViews,
+    vc::VerifiablePresentation,
+};
+use oxrdf::{
+    dataset::GraphView, vocab::rdf, BlankNode, Dataset, GraphNameRef, NamedOrBlankNode, QuadRef,
+    Subject, SubjectRef, Term, TermRef, Triple,
+};
+use std::collections::{HashMap, HashSet};
+
+/// occurrence of a hidden term in the document of a disclosed VC,
+/// identified by the VC graph name and the triple including the term
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TermOccurrence {
+    pub credential: NamedOrBlankNode,
+    pub triple: Triple,
+}
+
+/// hidden term proven equal across multiple disclosed VCs,
+/// named by its (canonical) label in VP
+#[derive(Clone, Debug, PartialEq)]
+pub struct EquivalenceClass {
+    pub term: Term,
+    pub occurrences: Vec<TermOccurrence>,
+}
+
+pub(crate) type Equivalences = HashMap<Term, HashSet<TermOccurrence>>;
+
+/// extract equivalence classes from VP, so that verifiers can explicitly assert
+/// the expected link structure among disclosed VCs, e.g.,
+/// "the vaccine in VC1 equals the subject of VC2";
+/// note that they are only guaranteed by `verify_proof`,
+/// and VPs without equivalence graph result in no classes
+pub fn vp_equivalence_classes(vp: &Dataset) -> Result<Vec<EquivalenceClass>, RDFProofsError> {
+    let vp: VerifiablePresentation = vp.try_into()?;
+    let equivalences = match vp.equivalences {
+        Some(graph) => parse_equivalence_graph(&graph)?,
+        None => Equivalences::new(),
+    };
+
+    let mut classes = equivalences
+        .into_iter()
+        .map(|(term, occurrences)| {
+            let mut occurrences = occurrences.into_iter().collect::<Vec<_>>();
+            occurrences.sort_by_cached_key(|o| (o.credential.to_string(), o.triple.to_string()));
+            EquivalenceClass { term, occurrences }
+        })
+        .collect::<Vec<_>>();
+    classes.sort_by_cached_key(|c| c.term.to_string());
+    Ok(classes)
+}
+
+pub fn vp_equivalence_classes_string(vp: &str) -> Result<Vec<EquivalenceClass>, RDFProofsError> {
+    let vp = get_dataset_from_nquads(vp)?;
+    vp_equivalence_classes(&vp)
+}
+
+// collect the hidden terms (blank nodes and nyms) occurring in multiple VC documents
+pub(crate) fn get_equivalences<'a, 'b: 'a>(
+    vc_documents: impl IntoIterator<Item = (GraphNameRef<'b>, &'a GraphView<'b>)>,
+) -> Result<Equivalences, RDFProofsError> {
+    let mut equivalences = Equivalences::new();
+    for (credential, document) in vc_documents {
+        let credential = match credential {
+            GraphNameRef::NamedNode(n) => NamedOrBlankNode::NamedNode(n.into_owned()),
+            GraphNameRef::BlankNode(b) => NamedOrBlankNode::BlankNode(b.into_owned()),
+            GraphNameRef::DefaultGraph => return Err(RDFProofsError::InvalidVP),
+        };
+        for triple in document.iter() {
+            let terms = [
+                TermRef::from(triple.subject),
+                TermRef::from(triple.predicate),
+                triple.object,
+            ];
+            for term in terms.into_iter().filter(is_hidden_term) {
+                equivalences
+                    .entry(term.into_owned())
+                    .or_default()
+                    .insert(TermOccurrence {
+                        credential: credential.clone(),
+                        triple: triple.into_owned(),
+                    });
+            }
+        }
+    }
+
+    // terms occurring only in a single VC do not link VCs
+    equivalences.retain(|_, occurrences| {
+        occurrences
+            .iter()
+            .map(|o| &o.credential)
+            .collect::<HashSet<_>>()
+            .len()
+            > 1
+    });
+    Ok(equivalences)
+}
+
+// add the equivalence graph linked from VP
+pub(crate) fn insert_equivalence_graph(
+    vp: &mut Dataset,
+    vp_id: &BlankNode,
+    equivalences: &Equivalences,
+    labeler: &mut BlankNodeLabeler,
+) {
+    if equivalences.is_empty() {
+        return;
+    }
+
+    let graph_id = labeler.generate();
+    vp.insert(QuadRef::new(
+        vp_id,
+        EQUIVALENCES,
+        &graph_id,
+        GraphNameRef::DefaultGraph,
+    ));
+    // sort classes and occurrences so that deterministic labels are assigned in a stable order
+    let mut classes = equivalences.iter().collect::<Vec<_>>();
+    classes.sort_by_cached_key(|(term, _)| term.to_string());
+    for (term, occurrences) in classes {
+        let class_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            &class_id,
+            rdf::TYPE,
+            EQUIVALENCE_CLASS_TYPE,
+            &graph_id,
+        ));
+        vp.insert(QuadRef::new(&class_id, EQUIVALENT_TERM, term, &graph_id));
+        let mut occurrences = occurrences.iter().collect::<Vec<_>>();
+        occurrences.sort_by_cached_key(|o| (o.credential.to_string(), o.triple.to_string()));
+        for TermOccurrence { credential, triple } in occurrences {
+            let occurrence_id = labeler.generate();
+            vp.insert(QuadRef::new(
+                &class_id,
+                OCCURRENCE,
+                &occurrence_id,
+                &graph_id,
+            ));
+            vp.insert(QuadRef::new(
+                &occurrence_id,
+                IN_CREDENTIAL,
+                credential,
+                &graph_id,
+            ));
+            vp.insert(QuadRef::new(
+                &occurrence_id,
+                rdf::SUBJECT,
+                &triple.subject,
+                &graph_id,
+            ));
+            vp.insert(QuadRef::new(
+                &occurrence_id,
+                rdf::PREDICATE,
+                &triple.predicate,
+                &graph_id,
+            ));
+            vp.insert(QuadRef::new(
+                &occurrence_id,
+                rdf::OBJECT,
+                &triple.object,
+                &graph_id,
+            ));
+        }
+    }
+}
+
+// check that the equivalence classes in VP, if any, are exactly those derived from the disclosed VCs;
+// VPs without the equivalence graph are accepted for backward compatibility
+pub(crate) fn validate_equivalences(
+    equivalence_graph: &Option<GraphView>,
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+) -> Result<(), RDFProofsError> {
+    let Some(equivalence_graph) = equivalence_graph else {
+        return Ok(());
+    };
+    let expected = get_equivalences(
+        disclosed_vcs
+            .iter()
+            .map(|(name, vc)| (GraphNameRef::from(name.clone()), &vc.document)),
+    )?;
+    let given = parse_equivalence_graph(equivalence_graph)?;
+    if expected == given {
+        Ok(())
+    } else {
+        Err(RDFProofsError::InvalidEquivalenceClasses)
+    }
+}
+
+fn parse_equivalence_graph(graph: &GraphView) -> Result<Equivalences, RDFProofsError> {
+    let mut equivalences = Equivalences::new();
+    for class in graph.subjects_for_predicate_object(rdf::TYPE, EQUIVALENCE_CLASS_TYPE) {
+        let term = graph
+            .object_for_subject_predicate(class, EQUIVALENT_TERM)
+            .ok_or(RDFProofsError::InvalidEquivalenceClasses)?
+            .into_owned();
+        let occurrences = graph
+            .objects_for_subject_predicate(class, OCCURRENCE)
+            .map(|occurrence| {
+                let TermRef::BlankNode(occurrence) = occurrence else {
+                    return Err(RDFProofsError::InvalidEquivalenceClasses);
+                };
+                let credential = match graph.object_for_subject_predicate(occurrence, IN_CREDENTIAL)
+                {
+                    Some(TermRef::NamedNode(n)) => NamedOrBlankNode::NamedNode(n.into_owned()),
+                    Some(TermRef::BlankNode(b)) => NamedOrBlankNode::BlankNode(b.into_owned()),
+                    _ => return Err(RDFProofsError::InvalidEquivalenceClasses),
+                };
+                let subject = match graph.object_for_subject_predicate(occurrence, rdf::SUBJECT) {
+                    Some(TermRef::NamedNode(n)) => Subject::NamedNode(n.into_owned()),
+                    Some(TermRef::BlankNode(b)) => Subject::BlankNode(b.into_owned()),
+                    _ => return Err(RDFProofsError::InvalidEquivalenceClasses),
+                };
+                let Some(TermRef::NamedNode(predicate)) =
+                    graph.object_for_subject_predicate(occurrence, rdf::PREDICATE)
+                else {
+                    return Err(RDFProofsError::InvalidEquivalenceClasses);
+                };
+                let object = graph
+                    .object_for_subject_predicate(occurrence, rdf::OBJECT)
+                    .ok_or(RDFProofsError::InvalidEquivalenceClasses)?;
+                Ok(TermOccurrence {
+                    credential,
+                    triple: Triple::new(subject, predicate.into_owned(), object.into_owned()),
+                })
+            })
+            .collect::<Result<HashSet<_>, _>>()?;
+        if !matches!(class, SubjectRef::BlankNode(_))
+            || equivalences.insert(term, occurrences).is_some()
+        {
+            return Err(RDFProofsError::InvalidEquivalenceClasses);
+        }
+    }
+    Ok(equivalences)
+}
+
+fn is_hidden_term(term: &TermRef) -> bool {
+    match term {
+        TermRef::BlankNode(_) => true,
+        TermRef::NamedNode(n) => is_nym(&n.into_owned()),
+        _ => false,
+    }
+}
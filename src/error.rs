@@ -1,3 +1,4 @@
+use crate::proof_layout::GENERATOR;
 use oxrdf::NamedNode;
 
 #[derive(Debug)]
@@ -7,6 +8,8 @@ pub enum RDFProofsError {
     HashToField,
     ArkSerialization(ark_serialize::SerializationError),
     CBORSerialization(serde_cbor::Error),
+    #[cfg(any(feature = "report", feature = "registry"))]
+    JSONSerialization(serde_json::Error),
     ProofTransformation,
     InvalidProofConfiguration,
     InvalidProofDatetime,
@@ -24,12 +27,19 @@ pub enum RDFProofsError {
     VCWithInvalidProofValue,
     VCWithoutVCType,
     VCWithoutCryptosuite,
-    VCWithUnsupportedCryptosuite,
+    VCWithUnsupportedCryptosuite {
+        expected: Vec<String>,
+        actual: String,
+    },
     InvalidVCGraphName,
     BlankNodeIdParse(oxrdf::BlankNodeIdParseError),
     LanguageTagParse(oxrdf::LanguageTagParseError),
     DeAnonymization,
     InvalidVP,
+    VPWithUnsupportedCryptosuite {
+        expected: Vec<String>,
+        actual: String,
+    },
     InvalidPPID,
     BlankNodeCollision,
     DisclosedVCIsNotSubsetOfOriginalVC,
@@ -42,7 +52,15 @@ pub enum RDFProofsError {
     MissingDomainInVP,
     MissingDomainInRequest,
     MismatchedDomain,
+    MissingPolicyDigestInVP,
+    MissingPolicyDigestInRequest,
+    MismatchedPolicyDigest,
+    ReplayedPresentation,
     MissingProofConfigLiteral(NamedNode),
+    NonRedactableProofConfig(NamedNode),
+    InvalidParamsProfile,
+    UnsupportedParamsProfile(String),
+    UnsupportedKeyType(String),
     InvalidChallengeDatatype,
     MessageSizeOverflow,
     MissingSecret,
@@ -50,15 +68,96 @@ pub enum RDFProofsError {
     InvalidPredicate,
     MissingPredicateCircuit,
     MissingSnarkVK(String),
+    UnusedPredicatePublicValue(String),
+    InvalidCircuitInput(String),
+    InvalidPredicateTemplate(String),
+    ConflictingPredicateTemplate(String),
+    PredicateArtifactDigestMismatch(String),
+    NonExtractablePredicateProof(String),
     InvalidInteger(String),
+    InvalidDecimal(String),
     InvalidDateTime(String),
     DateTimeParse(chrono::ParseError),
     ParseInt(std::num::ParseIntError),
+    #[cfg(feature = "predicates")]
     Circom(legogroth16::circom::CircomError),
     IO(std::io::Error),
+    #[cfg(feature = "store")]
+    Storage(oxigraph::store::StorageError),
+    #[cfg(feature = "sparql")]
+    SparqlParse(spargebra::ParseError),
+    #[cfg(feature = "sparql")]
+    SparqlEvaluation(oxigraph::sparql::EvaluationError),
+    #[cfg(feature = "sparql")]
+    UnsupportedSparqlQuery,
     Legogroth16(legogroth16::error::Error),
+    #[cfg(feature = "encryption")]
+    ElGamal(ark_crypto_primitives::Error),
     MissingSecretOrOpenerPubKey,
     MissingEncryptedSecret,
+    InvalidEncryptedUid,
+    InvalidHolderState,
+    MismatchedHolderStateTag,
+    MismatchedBlindSignRequestAudience,
+    ExpiredBlindSignRequest,
+    MissingSecretRotationRequest,
+    MissingRotatedSecretCommitment,
+    InvalidCommitmentEquality,
+    MissingCommitmentEquality,
+    InvalidInequality,
+    UnprovableInequality(String),
+    InsufficientDistinctCredentials(String),
+    NonConformingDisclosureProfile(String),
+    InvalidCreatedPrecision(String),
+    InvalidPrecomputedCanonicalization(String),
+    InvalidDeviceBinding(String),
+    ConflictingSessionPresentation(String),
+    InvalidAttachmentDigest,
+    AttachmentDigestMismatch,
+    MissingPreviousProof(String),
+    InvalidProofChain(String),
+    InvalidStatementIndexMap(String),
+    UnsupportedProofLayoutVersion(u32),
+    UnsupportedSerializationVersion(u8),
+    UnsupportedStatementType {
+        tag: String,
+    },
+    MismatchedStatementTypes,
+    InvalidStatementLayout(String),
+    InvalidEquivalenceClasses,
+    InvalidCredentialSummary,
+    VcNotFound(String),
+    InvalidVcStoreEntry,
+    StrictDisclosureViolation(String),
+    UnsupportedProofFeature(String),
+    ProofFromNewerVersion {
+        generator: String,
+        version: u32,
+    },
+    InvalidCredentialSecrets(String),
+    InvalidVerificationBundle(String),
+    DuplicatedDisclosedTriple(String),
+    InvalidClassicVP(String),
+    InvalidNonceToken(String),
+    ExpiredNonceToken,
+    RejectedByPolicy {
+        rule: String,
+        value: String,
+    },
+    UnresolvedPrivateVariable(String),
+    CanonicalizationLimitExceeded(String),
+    UnauthorizedKeyUsage(String),
+    UnsupportedRdfFeature(String),
+    InvalidAttributeEncryption,
+    MissingAttributeEncryption,
+    Cancelled,
+    InvalidNamespaceEquality,
+    UnprovableNamespaceEquality(String),
+    ProofValueAttachment(String),
+    ProofContextVersionTooLow {
+        version: u32,
+        min: u32,
+    },
     Other(String),
 }
 
@@ -70,6 +169,8 @@ impl std::fmt::Display for RDFProofsError {
             RDFProofsError::HashToField => write!(f, "hash to field is failed"),
             RDFProofsError::ArkSerialization(_) => write!(f, "arkworks serialization error"),
             RDFProofsError::CBORSerialization(_) => write!(f, "CBOR serialization error"),
+            #[cfg(any(feature = "report", feature = "registry"))]
+            RDFProofsError::JSONSerialization(_) => write!(f, "JSON serialization error"),
             RDFProofsError::ProofTransformation => write!(f, "proof transformation error"),
             RDFProofsError::InvalidProofConfiguration => {
                 write!(f, "invalid proof configuration error")
@@ -104,14 +205,21 @@ impl std::fmt::Display for RDFProofsError {
             }
             RDFProofsError::VCWithoutVCType => write!(f, "VC without VC type error"),
             RDFProofsError::VCWithoutCryptosuite => write!(f, "VC without cryptosuite error"),
-            RDFProofsError::VCWithUnsupportedCryptosuite => {
-                write!(f, "VC without cryptosuite error")
-            }
+            RDFProofsError::VCWithUnsupportedCryptosuite { expected, actual } => write!(
+                f,
+                "VC with unsupported cryptosuite `{}`: expected one of {:?}",
+                actual, expected
+            ),
             RDFProofsError::InvalidVCGraphName => write!(f, "invalid VC graph name error"),
             RDFProofsError::BlankNodeIdParse(_) => write!(f, "blank node ID parse error"),
             RDFProofsError::LanguageTagParse(_) => write!(f, "language tag parse error"),
             RDFProofsError::DeAnonymization => write!(f, "deanonymization error"),
             RDFProofsError::InvalidVP => write!(f, "invalid VP error"),
+            RDFProofsError::VPWithUnsupportedCryptosuite { expected, actual } => write!(
+                f,
+                "VP with unsupported cryptosuite `{}`: expected one of {:?}",
+                actual, expected
+            ),
             RDFProofsError::InvalidPPID => write!(f, "VP contains invalid PPID"),
             RDFProofsError::BlankNodeCollision => write!(f, "blank node collision error"),
             RDFProofsError::DisclosedVCIsNotSubsetOfOriginalVC => {
@@ -139,9 +247,36 @@ impl std::fmt::Display for RDFProofsError {
             RDFProofsError::MismatchedDomain => {
                 write!(f, "domain does not match the expected value")
             }
+            RDFProofsError::MissingPolicyDigestInVP => {
+                write!(f, "verifier's policy digest is not present in VP")
+            }
+            RDFProofsError::MissingPolicyDigestInRequest => {
+                write!(
+                    f,
+                    "policy digest is in VP but verifier's policy is not given"
+                )
+            }
+            RDFProofsError::MismatchedPolicyDigest => {
+                write!(f, "policy digest does not match the verifier's policy")
+            }
+            RDFProofsError::ReplayedPresentation => {
+                write!(f, "VP or its challenge has been already presented")
+            }
             RDFProofsError::MissingProofConfigLiteral(n) => {
                 write!(f, "`{}` is not in proof config", n)
             }
+            RDFProofsError::NonRedactableProofConfig(n) => {
+                write!(f, "`{}` in proof config must be disclosed", n)
+            }
+            RDFProofsError::InvalidParamsProfile => {
+                write!(f, "params profile in proof config must be a literal")
+            }
+            RDFProofsError::UnsupportedParamsProfile(profile) => {
+                write!(f, "params profile `{}` is not supported here", profile)
+            }
+            RDFProofsError::UnsupportedKeyType(key_type) => {
+                write!(f, "key type `{}` is not supported for BBS+ keys", key_type)
+            }
             RDFProofsError::InvalidChallengeDatatype => {
                 write!(f, "challenge in VP has invalid datatype")
             }
@@ -173,9 +308,38 @@ impl std::fmt::Display for RDFProofsError {
                     v
                 )
             }
+            RDFProofsError::UnusedPredicatePublicValue(v) => {
+                write!(
+                    f,
+                    "verifier-supplied public value {} is not used by any predicate in VP",
+                    v
+                )
+            }
+            RDFProofsError::InvalidCircuitInput(v) => {
+                write!(f, "invalid circuit input: {}", v)
+            }
+            RDFProofsError::InvalidPredicateTemplate(v) => {
+                write!(f, "invalid predicate template: {}", v)
+            }
+            RDFProofsError::ConflictingPredicateTemplate(v) => {
+                write!(f, "conflicting predicate templates for circuit {}", v)
+            }
+            RDFProofsError::PredicateArtifactDigestMismatch(v) => {
+                write!(f, "digest mismatch for predicate artifact {}", v)
+            }
+            RDFProofsError::NonExtractablePredicateProof(v) => {
+                write!(f, "predicate proof of {} cannot be extracted", v)
+            }
             RDFProofsError::InvalidInteger(v) => {
                 write!(f, "invalid integer: {}", v)
             }
+            RDFProofsError::InvalidDecimal(v) => {
+                write!(
+                    f,
+                    "invalid decimal (cannot encode without loss of precision): {}",
+                    v
+                )
+            }
             RDFProofsError::InvalidDateTime(v) => {
                 write!(
                     f,
@@ -185,9 +349,25 @@ impl std::fmt::Display for RDFProofsError {
             }
             RDFProofsError::DateTimeParse(e) => write!(f, "date time parse error: {}", e),
             RDFProofsError::ParseInt(e) => write!(f, "parse int error: {}", e),
+            #[cfg(feature = "predicates")]
             RDFProofsError::Circom(e) => write!(f, "circom error: {:?}", e),
             RDFProofsError::IO(e) => write!(f, "IO error: {}", e),
+            #[cfg(feature = "store")]
+            RDFProofsError::Storage(e) => write!(f, "storage error: {}", e),
+            #[cfg(feature = "sparql")]
+            RDFProofsError::SparqlParse(e) => write!(f, "SPARQL parse error: {}", e),
+            #[cfg(feature = "sparql")]
+            RDFProofsError::SparqlEvaluation(e) => write!(f, "SPARQL evaluation error: {}", e),
+            #[cfg(feature = "sparql")]
+            RDFProofsError::UnsupportedSparqlQuery => {
+                write!(
+                    f,
+                    "only SPARQL CONSTRUCT and DESCRIBE queries are supported"
+                )
+            }
             RDFProofsError::Legogroth16(e) => write!(f, "legogroth16 error: {:?}", e),
+            #[cfg(feature = "encryption")]
+            RDFProofsError::ElGamal(e) => write!(f, "ElGamal error: {}", e),
             RDFProofsError::MissingSecretOrOpenerPubKey => {
                 write!(
                     f,
@@ -197,6 +377,179 @@ impl std::fmt::Display for RDFProofsError {
             RDFProofsError::MissingEncryptedSecret => {
                 write!(f, "encrypted secret must be given")
             }
+            RDFProofsError::InvalidEncryptedUid => {
+                write!(f, "encrypted uid does not match the opener public keys")
+            }
+            RDFProofsError::InvalidHolderState => {
+                write!(f, "holder state is malformed or of unsupported version")
+            }
+            RDFProofsError::MismatchedHolderStateTag => {
+                write!(f, "integrity tag of holder state does not match")
+            }
+            RDFProofsError::MismatchedBlindSignRequestAudience => {
+                write!(
+                    f,
+                    "audience of blind sign request does not match the issuer"
+                )
+            }
+            RDFProofsError::ExpiredBlindSignRequest => write!(f, "blind sign request has expired"),
+            RDFProofsError::MissingSecretRotationRequest => {
+                write!(
+                    f,
+                    "blind sign request committing to the new secret must be given for secret rotation"
+                )
+            }
+            RDFProofsError::MissingRotatedSecretCommitment => {
+                write!(f, "rotated secret commitment is not present in VP")
+            }
+            RDFProofsError::InvalidCommitmentEquality => {
+                write!(f, "invalid equality to commitment")
+            }
+            RDFProofsError::MissingCommitmentEquality => {
+                write!(
+                    f,
+                    "verifier's commitment is not proven equal to any hidden term in VP"
+                )
+            }
+            RDFProofsError::InvalidInequality => write!(f, "invalid inequality of hidden terms"),
+            RDFProofsError::UnprovableInequality(e) => {
+                write!(f, "hidden terms to be proven unequal are equal: {}", e)
+            }
+            RDFProofsError::InsufficientDistinctCredentials(e) => {
+                write!(f, "too few distinct credentials are proven: {}", e)
+            }
+            RDFProofsError::NonConformingDisclosureProfile(e) => {
+                write!(f, "VC does not conform to the disclosure profile: {}", e)
+            }
+            RDFProofsError::InvalidCreatedPrecision(e) => {
+                write!(f, "invalid precision of `created`: {}", e)
+            }
+            RDFProofsError::InvalidPrecomputedCanonicalization(e) => {
+                write!(f, "invalid precomputed canonicalization: {}", e)
+            }
+            RDFProofsError::InvalidDeviceBinding(e) => write!(f, "invalid device binding: {}", e),
+            RDFProofsError::ConflictingSessionPresentation(e) => {
+                write!(f, "presentation conflicts with the session: {}", e)
+            }
+            RDFProofsError::InvalidAttachmentDigest => {
+                write!(
+                    f,
+                    "attachment digest must be a literal typed as digestMultibase"
+                )
+            }
+            RDFProofsError::AttachmentDigestMismatch => {
+                write!(f, "attachment does not match the digest")
+            }
+            RDFProofsError::MissingPreviousProof(id) => {
+                write!(f, "previous proof `{}` is not present in VP", id)
+            }
+            RDFProofsError::InvalidProofChain(msg) => write!(f, "invalid proof chain: {}", msg),
+            RDFProofsError::InvalidStatementIndexMap(msg) => {
+                write!(f, "invalid statement index map: {}", msg)
+            }
+            RDFProofsError::UnsupportedProofLayoutVersion(v) => {
+                write!(f, "unsupported proof layout version: {}", v)
+            }
+            RDFProofsError::UnsupportedSerializationVersion(v) => {
+                write!(f, "unsupported serialization version: {}", v)
+            }
+            RDFProofsError::UnsupportedStatementType { tag } => {
+                write!(f, "unsupported statement type: {}", tag)
+            }
+            RDFProofsError::MismatchedStatementTypes => {
+                write!(
+                    f,
+                    "statement types in proof do not match the statements expected from VP"
+                )
+            }
+            RDFProofsError::InvalidStatementLayout(e) => {
+                write!(f, "invalid statement layout: {}", e)
+            }
+            RDFProofsError::InvalidEquivalenceClasses => {
+                write!(f, "equivalence classes do not match the disclosed VCs")
+            }
+            RDFProofsError::InvalidCredentialSummary => {
+                write!(f, "credential summary does not match the disclosed VCs")
+            }
+            RDFProofsError::VcNotFound(id) => write!(f, "VC `{}` is not found in store", id),
+            RDFProofsError::InvalidVcStoreEntry => {
+                write!(f, "VC store entry is malformed or cannot be decrypted")
+            }
+            RDFProofsError::StrictDisclosureViolation(e) => {
+                write!(f, "disclosed term violates strict disclosure: {}", e)
+            }
+            RDFProofsError::UnsupportedProofFeature(feature) => {
+                write!(
+                    f,
+                    "proof requires feature `{}` not enabled in this build",
+                    feature
+                )
+            }
+            RDFProofsError::ProofFromNewerVersion { generator, version } => write!(
+                f,
+                "proof layout version {} generated by {} is newer than this verifier ({})",
+                version,
+                if generator.is_empty() {
+                    "unknown version"
+                } else {
+                    generator.as_str()
+                },
+                GENERATOR
+            ),
+            RDFProofsError::InvalidCredentialSecrets(e) => {
+                write!(f, "invalid per-credential secrets: {}", e)
+            }
+            RDFProofsError::InvalidVerificationBundle(e) => {
+                write!(f, "invalid verification bundle: {}", e)
+            }
+            RDFProofsError::DuplicatedDisclosedTriple(triple) => write!(
+                f,
+                "disclosed triple duplicated after deanonymization: {}",
+                triple
+            ),
+            RDFProofsError::InvalidClassicVP(e) => write!(f, "invalid classic VP: {}", e),
+            RDFProofsError::InvalidNonceToken(e) => write!(f, "invalid nonce token: {}", e),
+            RDFProofsError::ExpiredNonceToken => write!(f, "nonce token has expired"),
+            RDFProofsError::RejectedByPolicy { rule, value } => {
+                write!(f, "rejected by policy rule `{}`: {}", rule, value)
+            }
+            RDFProofsError::UnresolvedPrivateVariable(msg) => {
+                write!(f, "unresolved private variable in predicate: {}", msg)
+            }
+            RDFProofsError::CanonicalizationLimitExceeded(msg) => {
+                write!(f, "canonicalization limit exceeded: {}", msg)
+            }
+            RDFProofsError::UnauthorizedKeyUsage(msg) => {
+                write!(f, "unauthorized key usage: {}", msg)
+            }
+            RDFProofsError::UnsupportedRdfFeature(msg) => {
+                write!(f, "unsupported RDF feature: {}", msg)
+            }
+            RDFProofsError::InvalidAttributeEncryption => {
+                write!(f, "invalid verifiable encryption of hidden term")
+            }
+            RDFProofsError::MissingAttributeEncryption => {
+                write!(
+                    f,
+                    "hidden terms are not encrypted to the keys expected by verifier in VP"
+                )
+            }
+            RDFProofsError::Cancelled => write!(f, "proof derivation cancelled"),
+            RDFProofsError::InvalidNamespaceEquality => write!(
+                f,
+                "namespace equality must be of distinct hidden IRIs signed with split-IRI encoding"
+            ),
+            RDFProofsError::UnprovableNamespaceEquality(e) => {
+                write!(f, "hidden IRIs do not share a namespace: {}", e)
+            }
+            RDFProofsError::ProofValueAttachment(e) => {
+                write!(f, "failed to attach proof value: {}", e)
+            }
+            RDFProofsError::ProofContextVersionTooLow { version, min } => write!(
+                f,
+                "proof context version {} is lower than the minimum {} required by verifier",
+                version, min
+            ),
             RDFProofsError::Other(msg) => write!(f, "other error: {}", msg),
         }
     }
@@ -228,6 +581,13 @@ impl From<serde_cbor::Error> for RDFProofsError {
     }
 }
 
+#[cfg(any(feature = "report", feature = "registry"))]
+impl From<serde_json::Error> for RDFProofsError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::JSONSerialization(e)
+    }
+}
+
 impl From<multibase::Error> for RDFProofsError {
     fn from(e: multibase::Error) -> Self {
         Self::Multibase(e)
@@ -282,18 +642,47 @@ impl From<std::num::ParseIntError> for RDFProofsError {
     }
 }
 
+#[cfg(feature = "predicates")]
 impl From<legogroth16::circom::CircomError> for RDFProofsError {
     fn from(e: legogroth16::circom::CircomError) -> Self {
         Self::Circom(e)
     }
 }
 
+#[cfg(feature = "encryption")]
+impl From<ark_crypto_primitives::Error> for RDFProofsError {
+    fn from(e: ark_crypto_primitives::Error) -> Self {
+        Self::ElGamal(e)
+    }
+}
+
 impl From<std::io::Error> for RDFProofsError {
     fn from(e: std::io::Error) -> Self {
         Self::IO(e)
     }
 }
 
+#[cfg(feature = "store")]
+impl From<oxigraph::store::StorageError> for RDFProofsError {
+    fn from(e: oxigraph::store::StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+#[cfg(feature = "sparql")]
+impl From<spargebra::ParseError> for RDFProofsError {
+    fn from(e: spargebra::ParseError) -> Self {
+        Self::SparqlParse(e)
+    }
+}
+
+#[cfg(feature = "sparql")]
+impl From<oxigraph::sparql::EvaluationError> for RDFProofsError {
+    fn from(e: oxigraph::sparql::EvaluationError) -> Self {
+        Self::SparqlEvaluation(e)
+    }
+}
+
 impl From<legogroth16::error::Error> for RDFProofsError {
     fn from(e: legogroth16::error::Error) -> Self {
         Self::Legogroth16(e)
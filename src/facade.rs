@@ -0,0 +1,982 @@
+#[cfg(feature = "prove")]
+use crate::{
+    blind_signature::blind_verify,
+    derive_proof::{derive_proof, DeriveOptions},
+    holder_binding::HolderBindingPolicy,
+    predicate::{Circuit, CircuitString},
+    signature::verify,
+    vc::VcPair,
+};
+#[cfg(feature = "blind")]
+use crate::{
+    blind_signature::{blind_sign, verify_blind_sign_request},
+    common::Proof,
+    curve::G1Affine,
+};
+use crate::{
+    clock::Clock,
+    common::{multibase_to_ark, VerifyingKey},
+    conformance::{check_conformance, check_graph_conformance, RdfConformance},
+    error::RDFProofsError,
+    key_graph::KeyGraph,
+    limits::Limits,
+    metrics::Metrics,
+    signature::sign,
+    vc::VerifiableCredential,
+};
+#[cfg(feature = "verify")]
+use crate::{
+    nonce_token::NonceTokenValidation,
+    replay::ReplayGuard,
+    verification_cache::VerificationCache,
+    verify_proof::{verify_proof, VerificationResult, VerifyOptions},
+    warnings::WarningPolicy,
+};
+use ark_std::rand::RngCore;
+use oxrdf::{Dataset, Graph, NamedNode, NamedOrBlankNode, Term};
+use std::{collections::HashMap, sync::Arc};
+
+/// presentation request sent from verifier to holder
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PresentationRequest {
+    pub challenge: Option<String>,
+    pub domain: Option<String>,
+    /// whether PPID for `domain` is required
+    pub with_ppid: bool,
+    pub predicates: Vec<Graph>,
+    pub policy: Option<Graph>,
+    /// application protocol identifier bound into the proof
+    pub protocol: Option<String>,
+}
+
+/// how `Issuer::issue_credential` signs the credential
+#[derive(Clone, Debug)]
+pub enum IssuanceMode {
+    /// signed as is, with `bbs-termwise-signature-2023`
+    Plain,
+    /// blind-signed over the secret committed in holder's blind sign request,
+    /// with `bbs-termwise-bound-signature-2023`, after verifying the proof of knowledge
+    /// of the committed secret for `challenge` given by issuer, and that the request
+    /// is addressed to issuer and not expired if `audience` and `expires_at` are set
+    #[cfg(feature = "blind")]
+    Bound {
+        commitment: G1Affine,
+        pok_for_commitment: Proof,
+        challenge: Option<String>,
+        audience: Option<String>,
+        expires_at: Option<String>,
+    },
+}
+
+/// credential issued by `Issuer::issue_credential`
+#[derive(Clone, Debug)]
+pub enum IssuedCredential {
+    Plain(VerifiableCredential),
+    /// to be unblinded by holder with the blinding of the blind sign request
+    #[cfg(feature = "blind")]
+    Bound(VerifiableCredential),
+}
+
+impl IssuedCredential {
+    pub fn credential(&self) -> &VerifiableCredential {
+        match self {
+            IssuedCredential::Plain(credential) => credential,
+            #[cfg(feature = "blind")]
+            IssuedCredential::Bound(credential) => credential,
+        }
+    }
+
+    pub fn into_credential(self) -> VerifiableCredential {
+        match self {
+            IssuedCredential::Plain(credential) => credential,
+            #[cfg(feature = "blind")]
+            IssuedCredential::Bound(credential) => credential,
+        }
+    }
+
+    pub fn is_bound(&self) -> bool {
+        !matches!(self, IssuedCredential::Plain(_))
+    }
+}
+
+/// issuer holding its key graph including secret keys
+pub struct Issuer {
+    key_graph: KeyGraph,
+    clock: Option<Box<dyn Clock>>,
+    conformance: RdfConformance,
+}
+
+impl Issuer {
+    pub fn new(key_graph: KeyGraph) -> Self {
+        Self {
+            key_graph,
+            clock: None,
+            conformance: RdfConformance::default(),
+        }
+    }
+
+    /// take `created` of proofs from `clock` instead of the system clock
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// accept RDF features of `conformance` in credential documents, RDF 1.1 by default
+    pub fn set_conformance(&mut self, conformance: RdfConformance) {
+        self.conformance = conformance;
+    }
+
+    fn check_conformance(
+        &self,
+        document: &Graph,
+        proof_options: &Graph,
+    ) -> Result<(), RDFProofsError> {
+        check_graph_conformance(document, self.conformance)?;
+        check_graph_conformance(proof_options, self.conformance)
+    }
+
+    /// sign the credential document with proof options,
+    /// bound to the holder's secret if `shared_secret` is given
+    pub fn issue<R: RngCore>(
+        &self,
+        rng: &mut R,
+        document: Graph,
+        proof_options: Graph,
+        shared_secret: Option<&[u8]>,
+    ) -> Result<VerifiableCredential, RDFProofsError> {
+        self.check_conformance(&document, &proof_options)?;
+        let mut credential = VerifiableCredential::new(document, proof_options);
+        sign(
+            rng,
+            &mut credential,
+            &self.key_graph,
+            shared_secret,
+            self.clock.as_deref(),
+        )?;
+        Ok(credential)
+    }
+
+    /// sign the credential document with proof options in either mode,
+    /// setting the cryptosuite in the proof config accordingly
+    pub fn issue_credential<R: RngCore>(
+        &self,
+        rng: &mut R,
+        document: Graph,
+        proof_options: Graph,
+        mode: IssuanceMode,
+    ) -> Result<IssuedCredential, RDFProofsError> {
+        self.check_conformance(&document, &proof_options)?;
+        let mut credential = VerifiableCredential::new(document, proof_options);
+        match mode {
+            IssuanceMode::Plain => {
+                sign(
+                    rng,
+                    &mut credential,
+                    &self.key_graph,
+                    None,
+                    self.clock.as_deref(),
+                )?;
+                Ok(IssuedCredential::Plain(credential))
+            }
+            #[cfg(feature = "blind")]
+            IssuanceMode::Bound {
+                commitment,
+                pok_for_commitment,
+                challenge,
+                audience,
+                expires_at,
+            } => {
+                verify_blind_sign_request(
+                    rng,
+                    &commitment,
+                    pok_for_commitment,
+                    challenge.as_deref(),
+                    audience.as_deref(),
+                    expires_at.as_deref(),
+                )?;
+                blind_sign(
+                    rng,
+                    &commitment,
+                    &mut credential,
+                    &self.key_graph,
+                    audience.as_deref(),
+                    expires_at.as_deref(),
+                    self.clock.as_deref(),
+                )?;
+                Ok(IssuedCredential::Bound(credential))
+            }
+        }
+    }
+
+    pub fn key_graph(&self) -> &KeyGraph {
+        &self.key_graph
+    }
+}
+
+/// holder holding its secret, credentials, and predicate circuits;
+/// holder is `Send + Sync` and only read while deriving VPs, so that it can be shared across
+/// threads, e.g., in `Arc`, to derive independent VPs concurrently with per-thread RNGs
+#[cfg(feature = "prove")]
+pub struct Holder {
+    key_graph: Arc<KeyGraph>,
+    secret: Option<Vec<u8>>,
+    credentials: Vec<Arc<VerifiableCredential>>,
+    // circuits are parsed once on registration and cloned since `derive_proof` consumes them
+    circuits: HashMap<NamedNode, Circuit>,
+    clock: Option<Box<dyn Clock>>,
+    metrics: Option<Box<dyn Metrics>>,
+    limits: Option<Limits>,
+    conformance: RdfConformance,
+}
+
+#[cfg(feature = "prove")]
+impl Holder {
+    /// `key_graph` gives the issuers' public keys
+    pub fn new(key_graph: KeyGraph, secret: Option<&[u8]>) -> Self {
+        Self::with_shared_key_graph(Arc::new(key_graph), secret)
+    }
+
+    /// `new` with the key graph shared with others, e.g., a snapshot of `SharedKeyGraph`
+    pub fn with_shared_key_graph(key_graph: Arc<KeyGraph>, secret: Option<&[u8]>) -> Self {
+        Self {
+            key_graph,
+            secret: secret.map(|s| s.to_vec()),
+            credentials: vec![],
+            circuits: HashMap::new(),
+            clock: None,
+            metrics: None,
+            limits: None,
+            conformance: RdfConformance::default(),
+        }
+    }
+
+    /// take `created` of VP proofs from `clock` instead of the system clock
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// report timings and sizes of VP derivation to `metrics`
+    pub fn set_metrics(&mut self, metrics: Box<dyn Metrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// reject VP drafts exceeding `limits` before canonicalization
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = Some(limits);
+    }
+
+    /// accept RDF features of `conformance` in credentials, RDF 1.1 by default
+    pub fn set_conformance(&mut self, conformance: RdfConformance) {
+        self.conformance = conformance;
+    }
+
+    /// verify and store the credential, returning its index
+    pub fn add_credential(
+        &mut self,
+        credential: VerifiableCredential,
+    ) -> Result<usize, RDFProofsError> {
+        self.add_shared_credential(Arc::new(credential))
+    }
+
+    /// `add_credential` with the credential shared with others, e.g., a credential store,
+    /// which is not copied until VPs are derived
+    pub fn add_shared_credential(
+        &mut self,
+        credential: Arc<VerifiableCredential>,
+    ) -> Result<usize, RDFProofsError> {
+        check_graph_conformance(&credential.document, self.conformance)?;
+        check_graph_conformance(&credential.proof, self.conformance)?;
+        match (credential.is_bound()?, &self.secret) {
+            (false, _) => verify(&credential, &self.key_graph)?,
+            (true, Some(secret)) => blind_verify(secret, &credential, &self.key_graph)?,
+            (true, None) => return Err(RDFProofsError::MissingSecret),
+        }
+        self.credentials.push(credential);
+        Ok(self.credentials.len() - 1)
+    }
+
+    pub fn credentials(&self) -> &[Arc<VerifiableCredential>] {
+        &self.credentials
+    }
+
+    /// validate and store the circuit for predicates
+    pub fn add_circuit(
+        &mut self,
+        circuit_id: NamedNode,
+        circuit: CircuitString,
+    ) -> Result<(), RDFProofsError> {
+        self.circuits.insert(circuit_id, get_circuit(&circuit)?);
+        Ok(())
+    }
+
+    /// derive VP for `request`, where `disclosures` are the pairs of
+    /// the index of stored credential and its disclosed subset
+    pub fn present<R: RngCore>(
+        &self,
+        rng: &mut R,
+        request: &PresentationRequest,
+        disclosures: &[(usize, VerifiableCredential)],
+        deanon_map: &HashMap<NamedOrBlankNode, Term>,
+    ) -> Result<Dataset, RDFProofsError> {
+        let vc_pairs = disclosures
+            .iter()
+            .map(|(index, disclosed)| {
+                let original = self
+                    .credentials
+                    .get(*index)
+                    .ok_or(RDFProofsError::VcNotFound(index.to_string()))?;
+                Ok(VcPair::new(original.as_ref().clone(), disclosed.clone()))
+            })
+            .collect::<Result<Vec<_>, RDFProofsError>>()?;
+        let circuits = self.circuits.clone();
+        let holder_binding = HolderBindingPolicy::new(
+            self.secret.as_deref(),
+            request.with_ppid,
+            request.domain.as_deref(),
+            vec![],
+        )?;
+
+        derive_proof(
+            rng,
+            &vc_pairs,
+            deanon_map,
+            &self.key_graph,
+            request.challenge.as_deref(),
+            request.domain.as_deref(),
+            DeriveOptions {
+                holder_binding,
+                predicates: request.predicates.clone(),
+                circuits,
+                policy: request.policy.as_ref(),
+                protocol: request.protocol.as_deref(),
+                clock: self.clock.as_deref(),
+                metrics: self.metrics.as_deref(),
+                limits: self.limits.as_ref(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// trial verification of VP derived for `request`, returning the result the verifier
+    /// would compute with the same key graph and policy, so that mismatched keys or policies
+    /// are caught before sending it; SNARK verifying keys are taken from the holder's circuits,
+    /// and no replay guard or verification cache is involved so as not to consume the VP
+    #[cfg(feature = "verify")]
+    pub fn self_verify<R: RngCore>(
+        &self,
+        rng: &mut R,
+        vp: &Dataset,
+        request: &PresentationRequest,
+    ) -> Result<VerificationResult, RDFProofsError> {
+        let snark_verifying_keys = self
+            .circuits
+            .iter()
+            .map(|(circuit_id, circuit)| (circuit_id.clone(), circuit.get_verifying_key().clone()))
+            .collect();
+        verify_proof(
+            rng,
+            vp,
+            self.key_graph.as_ref(),
+            request.challenge.as_deref(),
+            request.domain.as_deref(),
+            VerifyOptions {
+                snark_verifying_keys,
+                policy: request.policy.as_ref(),
+                protocol: request.protocol.as_deref(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// open session for `request`, to which presentations are appended
+    pub fn open_session(&self, request: &PresentationRequest) -> PresentationSession<'_> {
+        PresentationSession {
+            holder: self,
+            request: request.clone(),
+            disclosures: vec![],
+            deanon_map: HashMap::new(),
+        }
+    }
+}
+
+/// presentations appended by holder during one verifier session,
+/// aggregated on `finalize` into a single VP, where the challenge is shared
+/// and the PPID statement is proved once
+#[cfg(feature = "prove")]
+pub struct PresentationSession<'a> {
+    holder: &'a Holder,
+    request: PresentationRequest,
+    disclosures: Vec<(usize, VerifiableCredential)>,
+    deanon_map: HashMap<NamedOrBlankNode, Term>,
+}
+
+#[cfg(feature = "prove")]
+impl PresentationSession<'_> {
+    /// append presentation with its disclosures and predicates as in `Holder::present`;
+    /// deanon map entries must be consistent with those of the previous presentations
+    /// since they are merged, i.e., the same hidden term is proved equal across presentations
+    pub fn append(
+        &mut self,
+        disclosures: &[(usize, VerifiableCredential)],
+        deanon_map: &HashMap<NamedOrBlankNode, Term>,
+        predicates: Vec<Graph>,
+    ) -> Result<(), RDFProofsError> {
+        for (index, _) in disclosures {
+            if self.holder.credentials.get(*index).is_none() {
+                return Err(RDFProofsError::VcNotFound(index.to_string()));
+            }
+        }
+        for (k, v) in deanon_map {
+            match self.deanon_map.get(k) {
+                Some(existing) if existing != v => {
+                    return Err(RDFProofsError::ConflictingSessionPresentation(format!(
+                        "{} is mapped to both {} and {}",
+                        k, existing, v
+                    )))
+                }
+                _ => (),
+            }
+        }
+        self.deanon_map
+            .extend(deanon_map.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.disclosures.extend_from_slice(disclosures);
+        self.request.predicates.extend(predicates);
+        Ok(())
+    }
+
+    /// derive the composite VP of the appended presentations
+    pub fn finalize<R: RngCore>(self, rng: &mut R) -> Result<Dataset, RDFProofsError> {
+        self.holder
+            .present(rng, &self.request, &self.disclosures, &self.deanon_map)
+    }
+}
+
+#[cfg(feature = "prove")]
+fn get_circuit(circuit: &CircuitString) -> Result<Circuit, RDFProofsError> {
+    Circuit::new(
+        &circuit.circuit_r1cs,
+        &circuit.circuit_wasm,
+        &circuit.snark_proving_key,
+        circuit.input_bit_lengths.clone(),
+    )
+}
+
+/// verifier holding issuers' public keys, its policy, and SNARK verifying keys for predicates
+#[cfg(feature = "verify")]
+pub struct Verifier {
+    key_graph: KeyGraph,
+    domain: Option<String>,
+    policy: Option<Graph>,
+    snark_verifying_keys: HashMap<NamedNode, VerifyingKey>,
+    replay_guard: Option<Box<dyn ReplayGuard>>,
+    protocol: Option<String>,
+    verification_cache: Option<Box<dyn VerificationCache>>,
+    nonce_token: Option<NonceTokenValidation>,
+    metrics: Option<Box<dyn Metrics>>,
+    limits: Option<Limits>,
+    conformance: RdfConformance,
+    warning_policy: Option<WarningPolicy>,
+}
+
+#[cfg(feature = "verify")]
+impl Verifier {
+    pub fn new(key_graph: KeyGraph, domain: Option<&str>, policy: Option<Graph>) -> Self {
+        Self {
+            key_graph,
+            domain: domain.map(String::from),
+            policy,
+            snark_verifying_keys: HashMap::new(),
+            replay_guard: None,
+            protocol: None,
+            verification_cache: None,
+            nonce_token: None,
+            metrics: None,
+            limits: None,
+            conformance: RdfConformance::default(),
+            warning_policy: None,
+        }
+    }
+
+    /// add multibase-encoded SNARK verifying key for the circuit
+    pub fn add_snark_verifying_key(
+        &mut self,
+        circuit_id: NamedNode,
+        snark_verifying_key: &str,
+    ) -> Result<(), RDFProofsError> {
+        self.snark_verifying_keys
+            .insert(circuit_id, multibase_to_ark(snark_verifying_key)?);
+        Ok(())
+    }
+
+    /// reject VPs presented more than once
+    pub fn set_replay_guard(&mut self, replay_guard: Box<dyn ReplayGuard>) {
+        self.replay_guard = Some(replay_guard);
+    }
+
+    /// reuse the results of VPs verified before, e.g., on retries
+    pub fn set_verification_cache(&mut self, verification_cache: Box<dyn VerificationCache>) {
+        self.verification_cache = Some(verification_cache);
+    }
+
+    /// require challenges to be nonce tokens issued by `issue_nonce_token`
+    pub fn set_nonce_token_validation(&mut self, nonce_token: NonceTokenValidation) {
+        self.nonce_token = Some(nonce_token);
+    }
+
+    /// report timings and sizes of VP verification to `metrics`
+    pub fn set_metrics(&mut self, metrics: Box<dyn Metrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// reject VPs exceeding `limits` before canonicalization
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = Some(limits);
+    }
+
+    /// accept RDF features of `conformance` in VPs, RDF 1.1 by default
+    pub fn set_conformance(&mut self, conformance: RdfConformance) {
+        self.conformance = conformance;
+    }
+
+    /// report non-fatal findings of `warning_policy` in verification results
+    pub fn set_warning_policy(&mut self, warning_policy: WarningPolicy) {
+        self.warning_policy = Some(warning_policy);
+    }
+
+    /// accept only VPs derived for the application `protocol`
+    pub fn set_protocol(&mut self, protocol: &str) {
+        self.protocol = Some(protocol.to_string());
+    }
+
+    /// presentation request with the fresh `challenge` and the verifier's domain and policy
+    pub fn request(
+        &self,
+        challenge: &str,
+        with_ppid: bool,
+        predicates: Vec<Graph>,
+    ) -> PresentationRequest {
+        PresentationRequest {
+            challenge: Some(challenge.to_string()),
+            domain: self.domain.clone(),
+            with_ppid,
+            predicates,
+            policy: self.policy.clone(),
+            protocol: self.protocol.clone(),
+        }
+    }
+
+    /// verify VP presented for `request`
+    pub fn verify<R: RngCore>(
+        &self,
+        rng: &mut R,
+        vp: &Dataset,
+        request: &PresentationRequest,
+    ) -> Result<VerificationResult, RDFProofsError> {
+        check_conformance(vp, self.conformance)?;
+        verify_proof(
+            rng,
+            vp,
+            &self.key_graph,
+            request.challenge.as_deref(),
+            request.domain.as_deref(),
+            VerifyOptions {
+                snark_verifying_keys: self.snark_verifying_keys.clone(),
+                policy: request.policy.as_ref(),
+                replay_guard: self.replay_guard.as_deref(),
+                protocol: self.protocol.as_deref(),
+                verification_cache: self.verification_cache.as_deref(),
+                nonce_token: self.nonce_token.as_ref(),
+                metrics: self.metrics.as_deref(),
+                limits: self.limits.as_ref(),
+                warning_policy: self.warning_policy.as_ref(),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[cfg(all(test, feature = "prove", feature = "verify"))]
+mod tests {
+    use super::{Holder, IssuanceMode, IssuedCredential, Issuer, PresentationRequest, Verifier};
+    use crate::{
+        blind_signature::{blind_verify, request_blind_sign, unblind, BlindSignRequest},
+        common::get_graph_from_ntriples,
+        conformance::RdfConformance,
+        constants::{CRYPTOSUITE_BOUND_SIGN, CRYPTOSUITE_SIGN},
+        error::RDFProofsError,
+        signature::verify,
+        vp_credential_summary, InMemoryReplayGuard, KeyGraph, VerifiableCredential,
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use oxrdf::{BlankNode, Literal, NamedNode, NamedNodeRef, NamedOrBlankNode, Term, TripleRef};
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    "#;
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC: &str = r#"
+    _:e0 <http://schema.org/name> "John Smith" .
+    _:e1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e1 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+    _:e1 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    _:e1 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e1 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+    #[test]
+    fn issue_present_and_verify_with_facades() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph = || get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let issuer = Issuer::new(key_graph());
+        let credential = issuer
+            .issue(
+                &mut rng,
+                get_graph_from_ntriples(VC).unwrap(),
+                get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE).unwrap(),
+                None,
+            )
+            .unwrap();
+
+        let mut holder = Holder::new(key_graph(), None);
+        let index = holder.add_credential(credential.clone()).unwrap();
+
+        let mut verifier = Verifier::new(key_graph(), Some("example.org"), None);
+        verifier.set_replay_guard(Box::new(InMemoryReplayGuard::new(Duration::from_secs(60))));
+        let request = verifier.request("abcde", false, vec![]);
+
+        // disclose name only, with proof config except for proof value
+        let disclosed_proof = credential.get_proof_config();
+        let disclosed = VerifiableCredential::new(
+            get_graph_from_ntriples(DISCLOSED_VC).unwrap(),
+            disclosed_proof,
+        );
+        let deanon_map: HashMap<NamedOrBlankNode, Term> = HashMap::from([
+            (
+                BlankNode::new_unchecked("e0").into(),
+                NamedNode::new_unchecked("did:example:john").into(),
+            ),
+            (
+                BlankNode::new_unchecked("e1").into(),
+                NamedNode::new_unchecked("http://example.org/vcred/00").into(),
+            ),
+        ]);
+        let vp = holder
+            .present(
+                &mut rng,
+                &request,
+                &[(index, disclosed.clone())],
+                &deanon_map,
+            )
+            .unwrap();
+        // trial verification by the holder does not consume the VP
+        let self_verified = holder.self_verify(&mut rng, &vp, &request).unwrap();
+        assert_eq!(
+            verifier.verify(&mut rng, &vp, &request).unwrap(),
+            self_verified
+        );
+        let mismatched_request = PresentationRequest {
+            challenge: Some("fghij".to_string()),
+            ..request.clone()
+        };
+        assert!(holder
+            .self_verify(&mut rng, &vp, &mismatched_request)
+            .is_err());
+
+        // replayed VP
+        assert!(matches!(
+            verifier.verify(&mut rng, &vp, &request),
+            Err(RDFProofsError::ReplayedPresentation)
+        ));
+
+        // unknown credential
+        assert!(matches!(
+            holder.present(&mut rng, &request, &[(1, disclosed)], &deanon_map),
+            Err(RDFProofsError::VcNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn present_concurrently_from_shared_holder() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Holder>();
+
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph = || -> KeyGraph { get_graph_from_ntriples(KEY_GRAPH).unwrap().into() };
+
+        let issuer = Issuer::new(key_graph());
+        let credential = Arc::new(
+            issuer
+                .issue(
+                    &mut rng,
+                    get_graph_from_ntriples(VC).unwrap(),
+                    get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE).unwrap(),
+                    None,
+                )
+                .unwrap(),
+        );
+
+        let mut holder = Holder::with_shared_key_graph(Arc::new(key_graph()), None);
+        let index = holder.add_shared_credential(credential.clone()).unwrap();
+        let holder = Arc::new(holder);
+
+        let verifier = Verifier::new(key_graph(), Some("example.org"), None);
+        let disclosed = VerifiableCredential::new(
+            get_graph_from_ntriples(DISCLOSED_VC).unwrap(),
+            credential.get_proof_config(),
+        );
+        let deanon_map: HashMap<NamedOrBlankNode, Term> = HashMap::from([
+            (
+                BlankNode::new_unchecked("e0").into(),
+                NamedNode::new_unchecked("did:example:john").into(),
+            ),
+            (
+                BlankNode::new_unchecked("e1").into(),
+                NamedNode::new_unchecked("http://example.org/vcred/00").into(),
+            ),
+        ]);
+
+        // independent VPs derived concurrently, each thread with its own RNG
+        let requests = (0..4u64)
+            .map(|i| verifier.request(&format!("challenge{}", i), false, vec![]))
+            .collect::<Vec<_>>();
+        let vps = std::thread::scope(|scope| {
+            requests
+                .iter()
+                .enumerate()
+                .map(|(i, request)| {
+                    let holder = holder.clone();
+                    let disclosed = disclosed.clone();
+                    let deanon_map = &deanon_map;
+                    scope.spawn(move || {
+                        let mut rng = StdRng::seed_from_u64(i as u64 + 1);
+                        // errors may not be `Send`
+                        holder
+                            .present(&mut rng, request, &[(index, disclosed)], deanon_map)
+                            .map_err(|e| e.to_string())
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for (vp, request) in vps.iter().zip(&requests) {
+            assert!(verifier.verify(&mut rng, vp, request).is_ok());
+        }
+        // the credential is shared, not copied, by the holder
+        assert!(Arc::ptr_eq(&holder.credentials()[index], &credential));
+    }
+
+    #[test]
+    fn aggregate_presentations_in_session() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph = || get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let issuer = Issuer::new(key_graph());
+        let credential = issuer
+            .issue(
+                &mut rng,
+                get_graph_from_ntriples(VC).unwrap(),
+                get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE).unwrap(),
+                None,
+            )
+            .unwrap();
+
+        let mut holder = Holder::new(key_graph(), None);
+        let index = holder.add_credential(credential.clone()).unwrap();
+
+        let verifier = Verifier::new(key_graph(), Some("example.org"), None);
+        let request = verifier.request("abcde", false, vec![]);
+
+        let disclosed = VerifiableCredential::new(
+            get_graph_from_ntriples(DISCLOSED_VC).unwrap(),
+            credential.get_proof_config(),
+        );
+        let deanon_map: HashMap<NamedOrBlankNode, Term> = HashMap::from([
+            (
+                BlankNode::new_unchecked("e0").into(),
+                NamedNode::new_unchecked("did:example:john").into(),
+            ),
+            (
+                BlankNode::new_unchecked("e1").into(),
+                NamedNode::new_unchecked("http://example.org/vcred/00").into(),
+            ),
+        ]);
+
+        // two presentations in the session aggregated into one VP
+        let mut session = holder.open_session(&request);
+        session
+            .append(&[(index, disclosed.clone())], &deanon_map, vec![])
+            .unwrap();
+        session
+            .append(&[(index, disclosed.clone())], &deanon_map, vec![])
+            .unwrap();
+
+        // the same hidden term mapped to another value
+        let conflicting_deanon_map: HashMap<NamedOrBlankNode, Term> = HashMap::from([(
+            BlankNode::new_unchecked("e0").into(),
+            NamedNode::new_unchecked("did:example:jane").into(),
+        )]);
+        assert!(matches!(
+            session.append(
+                &[(index, disclosed.clone())],
+                &conflicting_deanon_map,
+                vec![]
+            ),
+            Err(RDFProofsError::ConflictingSessionPresentation(_))
+        ));
+
+        // unknown credential
+        assert!(matches!(
+            session.append(&[(1, disclosed)], &deanon_map, vec![]),
+            Err(RDFProofsError::VcNotFound(_))
+        ));
+
+        let vp = session.finalize(&mut rng).unwrap();
+        assert_eq!(vp_credential_summary(&vp).unwrap().len(), 2);
+        assert!(verifier.verify(&mut rng, &vp, &request).is_ok());
+    }
+
+    #[test]
+    fn issue_plain_and_bound_credentials() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let issuer = Issuer::new(get_graph_from_ntriples(KEY_GRAPH).unwrap().into());
+        let issue = |rng: &mut StdRng, mode: IssuanceMode| {
+            issuer.issue_credential(
+                rng,
+                get_graph_from_ntriples(VC).unwrap(),
+                get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE).unwrap(),
+                mode,
+            )
+        };
+
+        let issued = issue(&mut rng, IssuanceMode::Plain).unwrap();
+        assert!(matches!(issued, IssuedCredential::Plain(_)));
+        assert_eq!(
+            issued.credential().get_cryptosuite().unwrap(),
+            CRYPTOSUITE_SIGN
+        );
+        assert!(verify(issued.credential(), &key_graph).is_ok());
+
+        let secret = b"SECRET";
+        let challenge = "blind sign challenge";
+        let audience = "did:example:issuer0";
+        let expires_at = "2100-01-01T00:00:00Z";
+        let request = request_blind_sign(
+            &mut rng,
+            secret,
+            Some(challenge),
+            None,
+            Some(audience),
+            Some(expires_at),
+        )
+        .unwrap();
+        let bound =
+            |request: &BlindSignRequest, challenge: &str, audience: &str| IssuanceMode::Bound {
+                commitment: request.commitment,
+                pok_for_commitment: request.pok_for_commitment.clone().unwrap(),
+                challenge: Some(challenge.to_string()),
+                audience: Some(audience.to_string()),
+                expires_at: request.expires_at.clone(),
+            };
+        let issued = issue(&mut rng, bound(&request, challenge, audience)).unwrap();
+        assert!(issued.is_bound());
+        assert_eq!(
+            issued.credential().get_cryptosuite().unwrap(),
+            CRYPTOSUITE_BOUND_SIGN
+        );
+        let mut credential = issued.into_credential();
+        unblind(&mut credential, &request.blinding).unwrap();
+        assert!(blind_verify(secret, &credential, &key_graph).is_ok());
+
+        // proof of knowledge for another challenge
+        assert!(issue(&mut rng, bound(&request, "another challenge", audience)).is_err());
+
+        // request addressed to another issuer
+        let audience = "did:example:issuer1";
+        let request = request_blind_sign(
+            &mut rng,
+            secret,
+            Some(challenge),
+            None,
+            Some(audience),
+            Some(expires_at),
+        )
+        .unwrap();
+        assert!(matches!(
+            issue(&mut rng, bound(&request, challenge, audience)),
+            Err(RDFProofsError::MismatchedBlindSignRequestAudience)
+        ));
+
+        // expired request
+        let audience = "did:example:issuer0";
+        let request = request_blind_sign(
+            &mut rng,
+            secret,
+            Some(challenge),
+            None,
+            Some(audience),
+            Some("2000-01-01T00:00:00Z"),
+        )
+        .unwrap();
+        assert!(matches!(
+            issue(&mut rng, bound(&request, challenge, audience)),
+            Err(RDFProofsError::ExpiredBlindSignRequest)
+        ));
+    }
+
+    #[test]
+    fn issue_and_hold_with_rdf_conformance() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph = || get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        // document with directional language tag of RDF 1.2
+        let mut document = get_graph_from_ntriples(VC).unwrap();
+        let greeting = Literal::new_language_tagged_literal_unchecked("مرحبا", "ar--rtl");
+        document.insert(TripleRef::new(
+            NamedNodeRef::new_unchecked("did:example:john"),
+            NamedNodeRef::new_unchecked("http://schema.org/description"),
+            &greeting,
+        ));
+        let proof_options = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE).unwrap();
+
+        let mut issuer = Issuer::new(key_graph());
+        assert!(matches!(
+            issuer.issue(&mut rng, document.clone(), proof_options.clone(), None),
+            Err(RDFProofsError::UnsupportedRdfFeature(_))
+        ));
+        issuer.set_conformance(RdfConformance::Rdf12);
+        let credential = issuer
+            .issue(&mut rng, document, proof_options, None)
+            .unwrap();
+
+        let mut holder = Holder::new(key_graph(), None);
+        assert!(matches!(
+            holder.add_credential(credential.clone()),
+            Err(RDFProofsError::UnsupportedRdfFeature(_))
+        ));
+        holder.set_conformance(RdfConformance::Rdf12);
+        assert!(holder.add_credential(credential).is_ok());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{error::RDFProofsError, redact::redact_bytes, ElGamalPublicKey};
+
+/// holder binding of VP, which encodes the valid combinations of
+/// holder's secret, PPID, and uid encrypted for openers (auditors)
+#[derive(Clone, Default)]
+pub enum HolderBindingPolicy<'a> {
+    /// no holder binding, where only unbound VCs can be presented
+    #[default]
+    None,
+    /// prove knowledge of the secret bound to VCs
+    Bound { secret: &'a [u8] },
+    /// additionally present PPID for `domain`, which is also set as VP domain
+    BoundWithPpid { secret: &'a [u8], domain: &'a str },
+    /// additionally encrypt uid for the openers, escrowed among them if multiple keys are given
+    BoundWithAudit {
+        secret: &'a [u8],
+        opener_pub_keys: Vec<ElGamalPublicKey>,
+    },
+    /// both of `BoundWithPpid` and `BoundWithAudit`
+    BoundWithPpidAndAudit {
+        secret: &'a [u8],
+        domain: &'a str,
+        opener_pub_keys: Vec<ElGamalPublicKey>,
+    },
+}
+
+// holder's secret is redacted in logs
+impl std::fmt::Debug for HolderBindingPolicy<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Bound { secret } => f
+                .debug_struct("Bound")
+                .field("secret", &redact_bytes(secret))
+                .finish(),
+            Self::BoundWithPpid { secret, domain } => f
+                .debug_struct("BoundWithPpid")
+                .field("secret", &redact_bytes(secret))
+                .field("domain", domain)
+                .finish(),
+            Self::BoundWithAudit {
+                secret,
+                opener_pub_keys,
+            } => f
+                .debug_struct("BoundWithAudit")
+                .field("secret", &redact_bytes(secret))
+                .field("opener_pub_keys", opener_pub_keys)
+                .finish(),
+            Self::BoundWithPpidAndAudit {
+                secret,
+                domain,
+                opener_pub_keys,
+            } => f
+                .debug_struct("BoundWithPpidAndAudit")
+                .field("secret", &redact_bytes(secret))
+                .field("domain", domain)
+                .field("opener_pub_keys", opener_pub_keys)
+                .finish(),
+        }
+    }
+}
+
+impl<'a> HolderBindingPolicy<'a> {
+    /// construct holder binding from the loosely-coupled inputs as given to `derive_proof_string`,
+    /// validating their combination
+    pub fn new(
+        secret: Option<&'a [u8]>,
+        with_ppid: bool,
+        domain: Option<&'a str>,
+        opener_pub_keys: Vec<ElGamalPublicKey>,
+    ) -> Result<Self, RDFProofsError> {
+        match (secret, with_ppid, domain, opener_pub_keys.is_empty()) {
+            (None, false, _, true) => Ok(Self::None),
+            (None, true, _, _) | (Some(_), true, None, _) => {
+                Err(RDFProofsError::MissingSecretOrDomain)
+            }
+            (None, false, _, false) => Err(RDFProofsError::MissingSecretOrOpenerPubKey),
+            (Some(secret), false, _, true) => Ok(Self::Bound { secret }),
+            (Some(secret), true, Some(domain), true) => Ok(Self::BoundWithPpid { secret, domain }),
+            (Some(secret), false, _, false) => Ok(Self::BoundWithAudit {
+                secret,
+                opener_pub_keys,
+            }),
+            (Some(secret), true, Some(domain), false) => Ok(Self::BoundWithPpidAndAudit {
+                secret,
+                domain,
+                opener_pub_keys,
+            }),
+        }
+    }
+
+    pub fn secret(&self) -> Option<&'a [u8]> {
+        match self {
+            Self::None => None,
+            Self::Bound { secret }
+            | Self::BoundWithPpid { secret, .. }
+            | Self::BoundWithAudit { secret, .. }
+            | Self::BoundWithPpidAndAudit { secret, .. } => Some(*secret),
+        }
+    }
+
+    /// secret and domain for PPID if required
+    pub fn ppid(&self) -> Option<(&'a [u8], &'a str)> {
+        match self {
+            Self::BoundWithPpid { secret, domain }
+            | Self::BoundWithPpidAndAudit { secret, domain, .. } => Some((*secret, *domain)),
+            _ => None,
+        }
+    }
+
+    /// secret and opener public keys for encrypted uid if required
+    pub fn audit(&self) -> Option<(&'a [u8], &[ElGamalPublicKey])> {
+        match self {
+            Self::BoundWithAudit {
+                secret,
+                opener_pub_keys,
+            }
+            | Self::BoundWithPpidAndAudit {
+                secret,
+                opener_pub_keys,
+                ..
+            } => Some((*secret, opener_pub_keys.as_slice())),
+            _ => None,
+        }
+    }
+
+    // VP domain must be the PPID domain if PPID is required
+    pub(crate) fn resolve_domain(
+        &self,
+        domain: Option<&'a str>,
+    ) -> Result<Option<&'a str>, RDFProofsError> {
+        match (self.ppid(), domain) {
+            (Some((_, ppid_domain)), Some(domain)) if ppid_domain != domain => {
+                Err(RDFProofsError::MismatchedDomain)
+            }
+            (Some((_, ppid_domain)), _) => Ok(Some(ppid_domain)),
+            (None, domain) => Ok(domain),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HolderBindingPolicy;
+    use crate::{elliptic_elgamal_keygen, error::RDFProofsError};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn holder_binding_policy_from_inputs() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let (opener_pub_key, _) = elliptic_elgamal_keygen(&mut rng).unwrap();
+        let secret = b"SECRET".as_slice();
+
+        assert!(matches!(
+            HolderBindingPolicy::new(None, false, Some("example.org"), vec![]),
+            Ok(HolderBindingPolicy::None)
+        ));
+        assert!(matches!(
+            HolderBindingPolicy::new(Some(secret), false, None, vec![]),
+            Ok(HolderBindingPolicy::Bound { .. })
+        ));
+        assert!(matches!(
+            HolderBindingPolicy::new(Some(secret), true, Some("example.org"), vec![]),
+            Ok(HolderBindingPolicy::BoundWithPpid {
+                domain: "example.org",
+                ..
+            })
+        ));
+        assert!(matches!(
+            HolderBindingPolicy::new(Some(secret), false, None, vec![opener_pub_key]),
+            Ok(HolderBindingPolicy::BoundWithAudit { .. })
+        ));
+        assert!(matches!(
+            HolderBindingPolicy::new(
+                Some(secret),
+                true,
+                Some("example.org"),
+                vec![opener_pub_key]
+            ),
+            Ok(HolderBindingPolicy::BoundWithPpidAndAudit { .. })
+        ));
+
+        // invalid combinations
+        assert!(matches!(
+            HolderBindingPolicy::new(Some(secret), true, None, vec![]),
+            Err(RDFProofsError::MissingSecretOrDomain)
+        ));
+        assert!(matches!(
+            HolderBindingPolicy::new(None, false, None, vec![opener_pub_key]),
+            Err(RDFProofsError::MissingSecretOrOpenerPubKey)
+        ));
+
+        // VP domain must be consistent with PPID domain
+        let policy = HolderBindingPolicy::BoundWithPpid {
+            secret,
+            domain: "example.org",
+        };
+        assert_eq!(policy.resolve_domain(None).unwrap(), Some("example.org"));
+        assert!(matches!(
+            policy.resolve_domain(Some("example.com")),
+            Err(RDFProofsError::MismatchedDomain)
+        ));
+
+        // secret is not leaked to logs
+        #[cfg(not(feature = "reveal-debug"))]
+        assert!(!format!("{:?}", policy).contains("83, 69, 67, 82, 69, 84"));
+    }
+}
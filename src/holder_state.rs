@@ -0,0 +1,199 @@
+use crate::{
+    blind_signature::{BlindSignRequest, BlindSignRequestString},
+    common::{ark_to_base64url, multibase_to_ark},
+    constants::{HOLDER_STATE_CONTEXT, HOLDER_STATE_VERSION},
+    error::RDFProofsError,
+};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use multibase::Base;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+/// holder's state of blind issuance in progress, i.e., the request with its blinding,
+/// the nonce (challenge) used for the request, and the intended issuer,
+/// which is needed to `unblind` the signature issued for the request
+#[derive(Debug)]
+pub struct BlindIssuanceState {
+    pub request: BlindSignRequest,
+    pub nonce: Option<String>,
+    pub issuer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlindIssuanceStateString {
+    pub request: BlindSignRequestString,
+    pub nonce: Option<String>,
+    pub issuer: Option<String>,
+}
+
+// versioned blob sealed with the integrity tag keyed by holder's secret
+#[derive(Serialize, Deserialize)]
+struct SealedHolderState {
+    #[serde(rename = "v")]
+    version: u8,
+    #[serde(rename = "s")]
+    state: Vec<u8>,
+    #[serde(rename = "t")]
+    tag: Vec<u8>,
+}
+
+impl TryFrom<&BlindIssuanceState> for BlindIssuanceStateString {
+    type Error = RDFProofsError;
+
+    fn try_from(state: &BlindIssuanceState) -> Result<Self, Self::Error> {
+        Ok(Self {
+            request: BlindSignRequestString {
+                commitment: ark_to_base64url(&state.request.commitment)?,
+                blinding: ark_to_base64url(&state.request.blinding)?,
+                pok_for_commitment: match &state.request.pok_for_commitment {
+                    Some(pok) => Some(ark_to_base64url(pok)?),
+                    None => None,
+                },
+                audience: state.request.audience.clone(),
+                expires_at: state.request.expires_at.clone(),
+            },
+            nonce: state.nonce.clone(),
+            issuer: state.issuer.clone(),
+        })
+    }
+}
+
+impl TryFrom<&BlindIssuanceStateString> for BlindIssuanceState {
+    type Error = RDFProofsError;
+
+    fn try_from(state: &BlindIssuanceStateString) -> Result<Self, Self::Error> {
+        Ok(Self {
+            request: BlindSignRequest {
+                commitment: multibase_to_ark(&state.request.commitment)?,
+                blinding: multibase_to_ark(&state.request.blinding)?,
+                pok_for_commitment: match &state.request.pok_for_commitment {
+                    Some(pok) => Some(multibase_to_ark(pok)?),
+                    None => None,
+                },
+                audience: state.request.audience.clone(),
+                expires_at: state.request.expires_at.clone(),
+            },
+            nonce: state.nonce.clone(),
+            issuer: state.issuer.clone(),
+        })
+    }
+}
+
+/// export the holder state as a multibase-encoded CBOR blob with the integrity tag keyed by `secret`,
+/// so that the issuance can be completed on another device holding the same secret;
+/// note that the blob is not encrypted and must be transferred over a confidential channel
+pub fn export_blind_issuance_state(
+    state: &BlindIssuanceState,
+    secret: &[u8],
+) -> Result<String, RDFProofsError> {
+    export_blind_issuance_state_string(&state.try_into()?, secret)
+}
+
+pub fn export_blind_issuance_state_string(
+    state: &BlindIssuanceStateString,
+    secret: &[u8],
+) -> Result<String, RDFProofsError> {
+    let state = serde_cbor::to_vec(state)?;
+    let tag = get_holder_state_tag(secret, HOLDER_STATE_VERSION, &state)?;
+    let sealed = SealedHolderState {
+        version: HOLDER_STATE_VERSION,
+        state,
+        tag,
+    };
+    Ok(multibase::encode(
+        Base::Base64Url,
+        serde_cbor::to_vec(&sealed)?,
+    ))
+}
+
+/// import the holder state exported by `export_blind_issuance_state`,
+/// checking its version and integrity tag with `secret`
+pub fn import_blind_issuance_state(
+    blob: &str,
+    secret: &[u8],
+) -> Result<BlindIssuanceState, RDFProofsError> {
+    (&import_blind_issuance_state_string(blob, secret)?).try_into()
+}
+
+pub fn import_blind_issuance_state_string(
+    blob: &str,
+    secret: &[u8],
+) -> Result<BlindIssuanceStateString, RDFProofsError> {
+    let (_, sealed) = multibase::decode(blob)?;
+    let SealedHolderState {
+        version,
+        state,
+        tag,
+    } = serde_cbor::from_slice(&sealed).map_err(|_| RDFProofsError::InvalidHolderState)?;
+    if version != HOLDER_STATE_VERSION {
+        return Err(RDFProofsError::InvalidHolderState);
+    }
+    let expected_tag = get_holder_state_tag(secret, version, &state)?;
+    // compare in constant time not to leak the tag
+    if !bool::from(expected_tag.as_slice().ct_eq(&tag)) {
+        return Err(RDFProofsError::MismatchedHolderStateTag);
+    }
+    serde_cbor::from_slice(&state).map_err(|_| RDFProofsError::InvalidHolderState)
+}
+
+// HKDF-SHA256 with the holder state context as salt, so that the MAC key is separated
+// from other uses of the secret
+fn derive_holder_state_key(secret: &[u8], version: u8) -> Result<[u8; 32], RDFProofsError> {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(HOLDER_STATE_CONTEXT), secret)
+        .expand(&[version], &mut key)
+        .map_err(|e| RDFProofsError::Other(e.to_string()))?;
+    Ok(key)
+}
+
+// HMAC-SHA256 keyed by the key derived from holder's secret over the version and the serialized state
+fn get_holder_state_tag(
+    secret: &[u8],
+    version: u8,
+    state: &[u8],
+) -> Result<Vec<u8>, RDFProofsError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&derive_holder_state_key(secret, version)?)
+        .map_err(|e| RDFProofsError::Other(e.to_string()))?;
+    mac.update(&[version]);
+    mac.update(state);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_blind_issuance_state, import_blind_issuance_state, BlindIssuanceState};
+    use crate::{error::RDFProofsError, request_blind_sign};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn export_and_import_blind_issuance_state() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let secret = b"SECRET";
+        let nonce = "abcde";
+
+        let request = request_blind_sign(&mut rng, secret, Some(nonce), None, None, None).unwrap();
+        let state = BlindIssuanceState {
+            request,
+            nonce: Some(nonce.to_string()),
+            issuer: Some("did:example:issuer3".to_string()),
+        };
+
+        let blob = export_blind_issuance_state(&state, secret).unwrap();
+        println!("holder state: {}", blob);
+
+        let imported = import_blind_issuance_state(&blob, secret).unwrap();
+        assert_eq!(imported.request.commitment, state.request.commitment);
+        assert_eq!(imported.request.blinding, state.request.blinding);
+        assert!(imported.request.pok_for_commitment.is_some());
+        assert_eq!(imported.nonce, state.nonce);
+        assert_eq!(imported.issuer, state.issuer);
+
+        // the blob can only be imported with the same secret
+        assert!(matches!(
+            import_blind_issuance_state(&blob, b"ANOTHER SECRET"),
+            Err(RDFProofsError::MismatchedHolderStateTag)
+        ));
+    }
+}
@@ -0,0 +1,423 @@
+use crate::{
+    common::{
+        ark_to_base64url, get_hasher, get_term_from_string, hash_term_to_field, multibase_to_ark,
+        BlankNodeLabeler, Fr, PedersenCommitmentStmt, Statements,
+    },
+    context::{
+        COMMITMENT, DATA_INTEGRITY_PROOF, LHS, MULTIBASE, NOT_EQUAL_TYPE, RHS, UNIQUE_CREDENTIALS,
+        VERIFIABLE_CREDENTIAL_TYPE,
+    },
+    curve::{Curve, G1Affine},
+    error::RDFProofsError,
+    key_gen::generate_params,
+    metadata::get_proof_metadata,
+    ordered_triple::OrderedVerifiableCredentialGraphViews,
+};
+use ark_ec::CurveGroup;
+use ark_ff::Field;
+use ark_std::{rand::RngCore, UniformRand};
+use oxrdf::{
+    dataset::GraphView, vocab::rdf::TYPE, BlankNode, BlankNodeRef, Dataset, Graph, LiteralRef,
+    NamedNode, NamedNodeRef, NamedOrBlankNode, QuadRef, SubjectRef, Term, TermRef, TripleRef,
+};
+use proof_system::witness::{Witness, Witnesses};
+use std::collections::{HashMap, HashSet};
+
+// Inequality of hidden terms `a` and `b` is proven by two Pedersen commitment statements:
+// `C = g^a * g^{-b} * h^r` and `g = C^x * h^y` with `x = (a - b)^{-1}` and `y = -r * x`,
+// where the latter cannot be satisfied if `a = b` since `C = h^r` then
+// and `x` would reveal the discrete log of `g` to the base `h`.
+
+/// commitment to the difference of two hidden terms in VP,
+/// which is proven to be non-zero
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Inequality {
+    pub commitment: G1Affine,
+    pub lhs: BlankNode,
+    pub rhs: BlankNode,
+}
+
+// prover's inequality with the blinding of the commitment, which must not be disclosed
+pub(crate) struct InequalityOpening {
+    pub inequality: Inequality,
+    pub blinding: Fr,
+}
+
+fn get_inequality_bases() -> (G1Affine, G1Affine) {
+    let params = generate_params(1);
+    (params.h[0], params.h_0)
+}
+
+// get the values of hidden terms mapped from `lhs` and `rhs` in deanon map,
+// which must be unequal
+pub(crate) fn get_unequal_values(
+    lhs: &BlankNode,
+    rhs: &BlankNode,
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<(Fr, Fr), RDFProofsError> {
+    let hasher = get_hasher();
+    let get_value = |node: &BlankNode| {
+        let term = deanon_map
+            .get(&node.clone().into())
+            .ok_or(RDFProofsError::InvalidInequality)?;
+        hash_term_to_field(term.into(), &hasher)
+    };
+    let (a, b) = (get_value(lhs)?, get_value(rhs)?);
+    if a == b {
+        return Err(RDFProofsError::UnprovableInequality(format!(
+            "{} and {}",
+            lhs, rhs
+        )));
+    }
+    Ok((a, b))
+}
+
+// commit to the difference of the terms mapped from `lhs` and `rhs` in deanon map
+pub(crate) fn commit_to_inequality<R: RngCore>(
+    rng: &mut R,
+    lhs: &BlankNode,
+    rhs: &BlankNode,
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<InequalityOpening, RDFProofsError> {
+    let (a, b) = get_unequal_values(lhs, rhs, deanon_map)?;
+    let (g, h) = get_inequality_bases();
+    let blinding = Fr::rand(rng);
+    let commitment = (g * (a - b) + h * blinding).into_affine();
+    Ok(InequalityOpening {
+        inequality: Inequality {
+            commitment,
+            lhs: lhs.clone(),
+            rhs: rhs.clone(),
+        },
+        blinding,
+    })
+}
+
+fn get_pairs(terms: &[BlankNode]) -> Vec<(&BlankNode, &BlankNode)> {
+    terms
+        .iter()
+        .enumerate()
+        .flat_map(|(i, lhs)| terms[i + 1..].iter().map(move |rhs| (lhs, rhs)))
+        .collect()
+}
+
+// commit to the pairwise differences of hidden terms, e.g., credential IDs
+pub(crate) fn commit_to_distinct_terms<R: RngCore>(
+    rng: &mut R,
+    terms: &[BlankNode],
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<Vec<InequalityOpening>, RDFProofsError> {
+    get_pairs(terms)
+        .into_iter()
+        .map(|(lhs, rhs)| commit_to_inequality(rng, lhs, rhs, deanon_map))
+        .collect()
+}
+
+// inequality of hidden terms with placeholder commitment,
+// which only determines the structure of VP for dry run
+pub(crate) fn plan_inequality(
+    lhs: &BlankNode,
+    rhs: &BlankNode,
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<Inequality, RDFProofsError> {
+    get_unequal_values(lhs, rhs, deanon_map)?;
+    Ok(Inequality {
+        commitment: G1Affine::identity(),
+        lhs: lhs.clone(),
+        rhs: rhs.clone(),
+    })
+}
+
+pub(crate) fn plan_distinct_terms(
+    terms: &[BlankNode],
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<Vec<Inequality>, RDFProofsError> {
+    get_pairs(terms)
+        .into_iter()
+        .map(|(lhs, rhs)| plan_inequality(lhs, rhs, deanon_map))
+        .collect()
+}
+
+// parse the request for proving two hidden terms unequal, which is given as graph
+// `_:ne a zkpld:NotEqual; zkpld:lhs _:a; zkpld:rhs _:b .` with blank nodes in deanon map
+pub(crate) fn parse_not_equal_request(
+    request: &Graph,
+) -> Result<(BlankNode, BlankNode), RDFProofsError> {
+    let request_subject = request
+        .subject_for_predicate_object(TYPE, NOT_EQUAL_TYPE)
+        .ok_or(RDFProofsError::InvalidInequality)?;
+    let (Some(TermRef::BlankNode(lhs)), Some(TermRef::BlankNode(rhs))) = (
+        request.object_for_subject_predicate(request_subject, LHS),
+        request.object_for_subject_predicate(request_subject, RHS),
+    ) else {
+        return Err(RDFProofsError::InvalidInequality);
+    };
+    Ok((lhs.into_owned(), rhs.into_owned()))
+}
+
+/// build the request for proving that the hidden terms `lhs` and `rhs` are unequal,
+/// to be passed to `derive_proof` as `not_equals`
+pub fn not_equal_request(lhs: &BlankNode, rhs: &BlankNode) -> Graph {
+    let request_subject = BlankNode::default();
+    let mut request = Graph::default();
+    request.insert(TripleRef::new(&request_subject, TYPE, NOT_EQUAL_TYPE));
+    request.insert(TripleRef::new(&request_subject, LHS, lhs));
+    request.insert(TripleRef::new(&request_subject, RHS, rhs));
+    request
+}
+
+pub fn not_equal_request_string(lhs: &str, rhs: &str) -> Result<String, RDFProofsError> {
+    let get_bnode = |bnode: &str| match get_term_from_string(bnode)? {
+        Term::BlankNode(n) => Ok(n),
+        _ => Err(RDFProofsError::InvalidInequality),
+    };
+    Ok(not_equal_request(&get_bnode(lhs)?, &get_bnode(rhs)?).to_string())
+}
+
+// add inequalities to VP proof graph, linked from VP proof by `predicate`
+pub(crate) fn insert_inequalities(
+    vp: &mut Dataset,
+    vp_proof_id: &BlankNode,
+    vp_proof_graph_id: &BlankNode,
+    predicate: NamedNodeRef,
+    inequalities: &[Inequality],
+    labeler: &mut BlankNodeLabeler,
+) -> Result<(), RDFProofsError> {
+    for Inequality {
+        commitment,
+        lhs,
+        rhs,
+    } in inequalities
+    {
+        let inequality_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            vp_proof_id,
+            predicate,
+            &inequality_id,
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &inequality_id,
+            COMMITMENT,
+            LiteralRef::new_typed_literal(&ark_to_base64url(commitment)?, MULTIBASE),
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(&inequality_id, LHS, lhs, vp_proof_graph_id));
+        vp.insert(QuadRef::new(&inequality_id, RHS, rhs, vp_proof_graph_id));
+    }
+    Ok(())
+}
+
+// get inequalities linked from VP proof by `predicate`, which are expected to be
+// in canonicalized VP
+pub(crate) fn get_inequalities(
+    vp_proof: &GraphView,
+    predicate: NamedNodeRef,
+) -> Result<Vec<Inequality>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let mut inequalities = vp_proof
+        .objects_for_subject_predicate(proof_subject, predicate)
+        .map(|inequality| {
+            let TermRef::BlankNode(inequality) = inequality else {
+                return Err(RDFProofsError::InvalidInequality);
+            };
+            let (
+                Some(TermRef::Literal(commitment)),
+                Some(TermRef::BlankNode(lhs)),
+                Some(TermRef::BlankNode(rhs)),
+            ) = (
+                vp_proof.object_for_subject_predicate(inequality, COMMITMENT),
+                vp_proof.object_for_subject_predicate(inequality, LHS),
+                vp_proof.object_for_subject_predicate(inequality, RHS),
+            )
+            else {
+                return Err(RDFProofsError::InvalidInequality);
+            };
+            if commitment.datatype() != MULTIBASE {
+                return Err(RDFProofsError::InvalidInequality);
+            }
+            Ok(Inequality {
+                commitment: multibase_to_ark(commitment.value())?,
+                lhs: lhs.into_owned(),
+                rhs: rhs.into_owned(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // ordered by canonical labels so that prover and verifier build the same statements
+    inequalities.sort_by_key(|inequality| {
+        (
+            inequality.lhs.as_str().to_string(),
+            inequality.rhs.as_str().to_string(),
+        )
+    });
+    Ok(inequalities)
+}
+
+// get inequalities linked from canonicalized VP proof by `predicate`,
+// along with the blindings of the prover's commitments
+pub(crate) fn get_inequality_openings(
+    vp_proof: &GraphView,
+    predicate: NamedNodeRef,
+    openings: &[InequalityOpening],
+) -> Result<Vec<InequalityOpening>, RDFProofsError> {
+    get_inequalities(vp_proof, predicate)?
+        .into_iter()
+        .map(|inequality| {
+            let opening = openings
+                .iter()
+                .find(|opening| opening.inequality.commitment == inequality.commitment)
+                .ok_or(RDFProofsError::InvalidInequality)?;
+            Ok(InequalityOpening {
+                inequality,
+                blinding: opening.blinding,
+            })
+        })
+        .collect()
+}
+
+// two statements per inequality, where the witnesses `0` and `1` of the first one
+// correspond to the left-hand and right-hand terms
+pub(crate) fn get_inequality_statements(commitment: &G1Affine) -> Statements {
+    let (g, h) = get_inequality_bases();
+    let mut statements = Statements::new();
+    statements.add(PedersenCommitmentStmt::new_statement_from_params(
+        vec![g, -g, h],
+        *commitment,
+    ));
+    statements.add(PedersenCommitmentStmt::new_statement_from_params(
+        vec![*commitment, h],
+        g,
+    ));
+    statements
+}
+
+pub(crate) fn get_inequality_witnesses(
+    lhs: &Fr,
+    rhs: &Fr,
+    blinding: &Fr,
+) -> Result<Witnesses<Curve>, RDFProofsError> {
+    let x = (*lhs - rhs)
+        .inverse()
+        .ok_or(RDFProofsError::UnprovableInequality(
+            "equal hidden terms".to_string(),
+        ))?;
+    let mut witnesses = Witnesses::new();
+    witnesses.add(Witness::PedersenCommitment(vec![*lhs, *rhs, *blinding]));
+    witnesses.add(Witness::PedersenCommitment(vec![x, -(*blinding * x)]));
+    Ok(witnesses)
+}
+
+// count the credentials proven pairwise distinct for each verification method,
+// where every term in the inequalities must be the hidden ID of a disclosed VC
+// and every pair of those IDs must be covered; a single VC counts as one
+// if no distinctness is proven for its verification method
+pub(crate) fn count_distinct_credentials(
+    inequalities: &[Inequality],
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+) -> Result<HashMap<NamedNode, usize>, RDFProofsError> {
+    let mut verification_methods = HashSet::new();
+    let mut credential_ids: HashMap<BlankNode, NamedNode> = HashMap::new();
+    for vc in disclosed_vcs.values() {
+        let Some(verification_method) = get_proof_metadata(&vc.proof)?.verification_method else {
+            continue;
+        };
+        if let Some(SubjectRef::BlankNode(id)) = vc
+            .document
+            .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+        {
+            credential_ids.insert(id.into_owned(), verification_method.clone());
+        }
+        verification_methods.insert(verification_method);
+    }
+
+    let mut terms = HashSet::new();
+    let mut pairs = HashSet::new();
+    for Inequality { lhs, rhs, .. } in inequalities {
+        if !credential_ids.contains_key(lhs) || !credential_ids.contains_key(rhs) {
+            return Err(RDFProofsError::InvalidInequality);
+        }
+        terms.insert(lhs);
+        terms.insert(rhs);
+        pairs.insert((lhs, rhs));
+        pairs.insert((rhs, lhs));
+    }
+    for lhs in &terms {
+        for rhs in &terms {
+            if lhs != rhs && !pairs.contains(&(*lhs, *rhs)) {
+                return Err(RDFProofsError::InvalidInequality);
+            }
+        }
+    }
+
+    let mut counts = HashMap::new();
+    for term in terms {
+        *counts.entry(credential_ids[term].clone()).or_insert(0) += 1;
+    }
+    for verification_method in verification_methods {
+        counts.entry(verification_method).or_insert(1);
+    }
+    Ok(counts)
+}
+
+// hidden IDs of disclosed VCs, which are to be proven pairwise distinct
+// when verifier's policy requires unique credentials
+pub(crate) fn get_hidden_credential_ids<'a>(
+    disclosed_documents: impl IntoIterator<Item = &'a Graph>,
+) -> Vec<BlankNode> {
+    disclosed_documents
+        .into_iter()
+        .filter_map(|document| {
+            match document.subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE) {
+                Some(SubjectRef::BlankNode(id)) => Some(id.into_owned()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// check that disclosed VCs are pairwise distinct credentials, i.e., their IDs are either
+// all disclosed and different, or all hidden and proven pairwise unequal;
+// VCs without IDs, and hidden IDs along with disclosed ones, cannot be told apart
+pub(crate) fn validate_unique_credentials(
+    inequalities: &[Inequality],
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+) -> Result<(), RDFProofsError> {
+    let rejected = |value: &str| RDFProofsError::RejectedByPolicy {
+        rule: UNIQUE_CREDENTIALS.as_str().to_string(),
+        value: value.to_string(),
+    };
+    if disclosed_vcs.len() < 2 {
+        return Ok(());
+    }
+
+    let proven: HashSet<BlankNodeRef> = inequalities
+        .iter()
+        .flat_map(|Inequality { lhs, rhs, .. }| [lhs.as_ref(), rhs.as_ref()])
+        .collect();
+    let mut disclosed_ids = HashSet::new();
+    let mut hidden_ids = HashSet::new();
+    for vc in disclosed_vcs.values() {
+        match vc
+            .document
+            .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+        {
+            Some(SubjectRef::NamedNode(id)) => {
+                if !disclosed_ids.insert(id) {
+                    return Err(rejected(id.as_str()));
+                }
+            }
+            Some(SubjectRef::BlankNode(id)) => {
+                if !proven.contains(&id) || !hidden_ids.insert(id) {
+                    return Err(rejected("(hidden)"));
+                }
+            }
+            _ => return Err(rejected("(no credential ID)")),
+        }
+    }
+    if !disclosed_ids.is_empty() && !hidden_ids.is_empty() {
+        return Err(rejected("(hidden)"));
+    }
+    Ok(())
+}
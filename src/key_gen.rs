@@ -1,19 +1,56 @@
 use crate::{
-    common::{get_hasher, hash_byte_to_field, BBSPlusHash, BBSPlusKeypair, BBSPlusParams},
+    common::{
+        get_hasher, hash_byte_to_field, BBSPlusHash, BBSPlusKeypair, BBSPlusParams,
+        BBSPlusPublicKey, BBSPlusSecretKey,
+    },
     constants::{GENERATOR_SEED, PPID_SEED},
+    context::PARAMS_PROFILE,
+    curve::{
+        G1Affine, G1_COMPRESSED_SIZE, G2_COMPRESSED_SIZE, KEY_TYPE_G1_PUB, KEY_TYPE_G2_PRIV,
+        KEY_TYPE_G2_PUB, MULTICODEC_G1_PUB, MULTICODEC_G2_PRIV, MULTICODEC_G2_PUB,
+    },
     error::RDFProofsError,
+    migrate::{deserialize_versioned, serialize_versioned},
 };
-use ark_bls12_381::G1Affine;
 use ark_ec::Group;
 use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::RngCore;
 use dock_crypto_utils::{concat_slices, hashing_utils::projective_group_elem_from_try_and_incr};
+use multibase::Base;
+use oxrdf::{TermRef, TripleRef};
 
 pub fn generate_params(message_count: u32) -> BBSPlusParams {
     // Note: Parameters here are shared among all the issuers.
     BBSPlusParams::new::<BBSPlusHash>(GENERATOR_SEED, message_count)
 }
 
+/// generate params for the profile given in proof config as `paramsProfile`,
+/// which allows issuers to use their own generators;
+/// the shared params by `generate_params` are used if no profile is given
+pub fn generate_params_with_profile(profile: Option<&str>, message_count: u32) -> BBSPlusParams {
+    match profile {
+        Some(profile) => BBSPlusParams::new::<BBSPlusHash>(
+            &concat_slices!(GENERATOR_SEED, b":", profile.as_bytes()),
+            message_count,
+        ),
+        None => generate_params(message_count),
+    }
+}
+
+pub(crate) fn get_params_profile<'a>(
+    proof: impl IntoIterator<Item = TripleRef<'a>>,
+) -> Result<Option<&'a str>, RDFProofsError> {
+    match proof.into_iter().find(|t| t.predicate == PARAMS_PROFILE) {
+        Some(TripleRef {
+            object: TermRef::Literal(v),
+            ..
+        }) => Ok(Some(v.value())),
+        Some(_) => Err(RDFProofsError::InvalidParamsProfile),
+        None => Ok(None),
+    }
+}
+
 pub fn generate_keypair<R: RngCore>(rng: &mut R) -> Result<BBSPlusKeypair, RDFProofsError> {
     // generate parameters to get `g_2` for generating public key in G2
     // Note: We do not need `h_i` here but `message_count` cannot be omitted so just set it `1`.
@@ -22,6 +59,84 @@ pub fn generate_keypair<R: RngCore>(rng: &mut R) -> Result<BBSPlusKeypair, RDFPr
     Ok(BBSPlusKeypair::generate_using_rng(rng, &base_params))
 }
 
+/// encoding of BBS+ keys given as `publicKeyMultibase` and `secretKeyMultibase`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyEncoding {
+    /// compressed arkworks serialization prefixed with `SERIALIZATION_VERSION` in base64url
+    Ark,
+    /// Multikey used by other BBS implementations, i.e., base58btc with multicodec prefix
+    /// (`bls12_381-g2-pub` or `bls12_381-g2-priv`), where secret key is in big-endian;
+    /// unavailable in `rdf-proofs-bn254`
+    Multikey,
+}
+
+pub fn encode_public_key(
+    public_key: &BBSPlusPublicKey,
+    encoding: KeyEncoding,
+) -> Result<String, RDFProofsError> {
+    Ok(match encoding {
+        KeyEncoding::Ark => multibase::encode(Base::Base64Url, serialize_versioned(public_key)?),
+        KeyEncoding::Multikey => {
+            let mut bytes = Vec::new();
+            public_key.serialize_compressed(&mut bytes)?;
+            let prefix = MULTICODEC_G2_PUB.ok_or(RDFProofsError::UnsupportedKeyType(
+                KEY_TYPE_G2_PUB.to_string(),
+            ))?;
+            multibase::encode(Base::Base58Btc, [prefix.as_slice(), &bytes].concat())
+        }
+    })
+}
+
+pub fn encode_secret_key(
+    secret_key: &BBSPlusSecretKey,
+    encoding: KeyEncoding,
+) -> Result<String, RDFProofsError> {
+    Ok(match encoding {
+        KeyEncoding::Ark => multibase::encode(Base::Base64Url, serialize_versioned(secret_key)?),
+        KeyEncoding::Multikey => {
+            let mut bytes = Vec::new();
+            secret_key.serialize_compressed(&mut bytes)?;
+            let prefix = MULTICODEC_G2_PRIV.ok_or(RDFProofsError::UnsupportedKeyType(
+                KEY_TYPE_G2_PRIV.to_string(),
+            ))?;
+            bytes.reverse();
+            multibase::encode(Base::Base58Btc, [prefix.as_slice(), &bytes].concat())
+        }
+    })
+}
+
+/// decode public key in either of `KeyEncoding`s, detected by multicodec prefix
+pub fn decode_public_key(public_key_multibase: &str) -> Result<BBSPlusPublicKey, RDFProofsError> {
+    let (_, bytes) = multibase::decode(public_key_multibase)?;
+    // raw compressed G2 point has a fixed size, so that prefixed one is distinguished by its length
+    if let Some(key) = MULTICODEC_G2_PUB.and_then(|prefix| bytes.strip_prefix(prefix.as_slice())) {
+        if key.len() == G2_COMPRESSED_SIZE {
+            return Ok(BBSPlusPublicKey::deserialize_compressed(key)?);
+        }
+    }
+    if let Some(key) = MULTICODEC_G1_PUB.and_then(|prefix| bytes.strip_prefix(prefix.as_slice())) {
+        if key.len() == G1_COMPRESSED_SIZE {
+            return Err(RDFProofsError::UnsupportedKeyType(
+                KEY_TYPE_G1_PUB.to_string(),
+            ));
+        }
+    }
+    Ok(deserialize_versioned(&bytes)?)
+}
+
+/// decode secret key in either of `KeyEncoding`s, detected by multicodec prefix
+pub fn decode_secret_key(secret_key_multibase: &str) -> Result<BBSPlusSecretKey, RDFProofsError> {
+    let (_, bytes) = multibase::decode(secret_key_multibase)?;
+    if let Some(key) = MULTICODEC_G2_PRIV.and_then(|prefix| bytes.strip_prefix(prefix.as_slice())) {
+        if key.len() == 32 {
+            // Multikey secret key is in big-endian while ark is in little-endian
+            let key = key.iter().rev().cloned().collect::<Vec<_>>();
+            return Ok(BBSPlusSecretKey::deserialize_compressed(key.as_slice())?);
+        }
+    }
+    Ok(deserialize_versioned(&bytes)?)
+}
+
 pub struct PPID {
     pub ppid: G1Affine,
     pub base: G1Affine,
@@ -56,9 +171,18 @@ pub fn generate_ppid(domain: &str, secret: &[u8]) -> Result<PPID, RDFProofsError
 
 #[cfg(test)]
 mod tests {
-    use super::generate_keypair;
-    use crate::{common::ark_to_base64url, key_gen::generate_params};
+    use super::{
+        decode_public_key, decode_secret_key, encode_public_key, encode_secret_key,
+        generate_keypair, KeyEncoding,
+    };
+    use crate::{
+        common::{ark_to_base64url, get_graph_from_ntriples},
+        error::RDFProofsError,
+        key_gen::generate_params,
+        KeyGraph,
+    };
     use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use oxrdf::NamedNodeRef;
 
     #[test]
     fn params_gen_success() {
@@ -93,4 +217,53 @@ mod tests {
         assert!(keypair2.is_ok());
         assert!(keypair3.is_ok());
     }
+
+    #[test]
+    fn key_encoding_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let keypair = generate_keypair(&mut rng).unwrap();
+
+        for encoding in [KeyEncoding::Ark, KeyEncoding::Multikey] {
+            let secret_key_multibase = encode_secret_key(&keypair.secret_key, encoding).unwrap();
+            let public_key_multibase = encode_public_key(&keypair.public_key, encoding).unwrap();
+            println!("{:?} secret_key: {}", encoding, secret_key_multibase);
+            println!("{:?} public_key: {}", encoding, public_key_multibase);
+            assert_eq!(
+                decode_secret_key(&secret_key_multibase).unwrap(),
+                keypair.secret_key
+            );
+            assert_eq!(
+                decode_public_key(&public_key_multibase).unwrap(),
+                keypair.public_key
+            );
+        }
+
+        // Multikey-encoded keys in key graph are auto-detected
+        let key_graph = format!(
+            r#"
+        <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "{}" .
+        <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "{}" .
+        "#,
+            encode_secret_key(&keypair.secret_key, KeyEncoding::Multikey).unwrap(),
+            encode_public_key(&keypair.public_key, KeyEncoding::Multikey).unwrap()
+        );
+        let key_graph: KeyGraph = get_graph_from_ntriples(&key_graph).unwrap().into();
+        let (secret_key, public_key) = key_graph
+            .get_keypair(NamedNodeRef::new_unchecked(
+                "did:example:issuer0#bls12_381-g2-pub001",
+            ))
+            .unwrap();
+        assert_eq!(secret_key, keypair.secret_key);
+        assert_eq!(public_key, keypair.public_key);
+
+        // G1 public keys are not supported for BBS+
+        let g1_public_key = multibase::encode(
+            multibase::Base::Base58Btc,
+            [[0xea, 0x01].as_slice(), &[0u8; 48]].concat(),
+        );
+        assert!(matches!(
+            decode_public_key(&g1_public_key),
+            Err(RDFProofsError::UnsupportedKeyType(_))
+        ));
+    }
 }
@@ -1,29 +1,150 @@
 use crate::{
-    common::{multibase_to_ark, BBSPlusPublicKey, BBSPlusSecretKey},
-    context::{PUBLIC_KEY_MULTIBASE, SECRET_KEY_MULTIBASE},
+    common::{
+        get_dataset_from_nquads, get_graph_from_ntriples, BBSPlusPublicKey, BBSPlusSecretKey,
+    },
+    context::{
+        ALLOWED_CREDENTIAL_TYPE, KEY_EXPIRES, PUBLIC_KEY_MULTIBASE, SECRET_KEY_MULTIBASE,
+        VERIFIABLE_CREDENTIAL_TYPE,
+    },
     error::RDFProofsError,
+    key_gen::{decode_public_key, decode_secret_key},
+    metadata::get_proof_metadata,
+    redact::redact_str,
+    vc::VerifiablePresentation,
+};
+use chrono::{DateTime, Utc};
+use oxrdf::{Dataset, Graph, NamedNode, NamedNodeRef, TermRef, Triple};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
 };
-use oxrdf::{Graph, NamedNodeRef, TermRef, Triple};
+
+/// source of issuer public keys for verification,
+/// which can be backed by data updated concurrently with verification
+pub trait KeyStore: Send + Sync {
+    fn get_public_key(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<BBSPlusPublicKey, RDFProofsError>;
+
+    /// credential types the key is restricted to sign, where empty means unrestricted
+    fn get_allowed_credential_types(
+        &self,
+        _verification_method_identifier: NamedNodeRef,
+    ) -> Result<Vec<NamedNode>, RDFProofsError> {
+        Ok(vec![])
+    }
+
+    /// time at which the key expires and is due to be rotated, or `None` if not scheduled
+    fn get_key_expiration(
+        &self,
+        _verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<DateTime<Utc>>, RDFProofsError> {
+        Ok(None)
+    }
+}
+
+/// source of issuer secret keys fetched per verification method at runtime,
+/// e.g., a secret manager, so that they are never written into key graphs;
+/// closures taking the verification method IRI are providers as well
+pub trait SecretProvider: Send + Sync {
+    /// multibase-encoded secret key of the verification method, or `None` if unknown
+    fn get_secret_key_multibase(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<String>, RDFProofsError>;
+}
+
+impl<F> SecretProvider for F
+where
+    F: Fn(NamedNodeRef) -> Result<Option<String>, RDFProofsError> + Send + Sync,
+{
+    fn get_secret_key_multibase(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<String>, RDFProofsError> {
+        self(verification_method_identifier)
+    }
+}
+
+// secret keys read from environment variables named per verification method
+struct EnvSecretProvider {
+    variables: HashMap<NamedNode, String>,
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn get_secret_key_multibase(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<String>, RDFProofsError> {
+        Ok(self
+            .variables
+            .get(&verification_method_identifier.into_owned())
+            .and_then(|name| std::env::var(name).ok()))
+    }
+}
 
 pub struct KeyGraph {
     inner: Graph,
+    secret_provider: Option<Box<dyn SecretProvider>>,
+}
+
+// issuer secret keys are redacted in logs
+impl std::fmt::Debug for KeyGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+        for triple in self.inner.iter() {
+            match triple.object {
+                TermRef::Literal(v) if triple.predicate == SECRET_KEY_MULTIBASE => {
+                    list.entry(&format_args!(
+                        "{} {} {}",
+                        triple.subject,
+                        triple.predicate,
+                        redact_str(v.value())
+                    ))
+                }
+                _ => list.entry(&format_args!("{}", triple)),
+            };
+        }
+        list.finish()
+    }
 }
 
 impl From<Graph> for KeyGraph {
     fn from(value: Graph) -> Self {
-        Self { inner: value }
+        Self {
+            inner: value,
+            secret_provider: None,
+        }
     }
 }
 
 impl From<Vec<Triple>> for KeyGraph {
     fn from(value: Vec<Triple>) -> Self {
-        Self {
-            inner: Graph::from_iter(value),
-        }
+        Graph::from_iter(value).into()
     }
 }
 
 impl KeyGraph {
+    /// key graph with the public parts of verification methods,
+    /// whose secret keys are fetched from `secret_provider` when signing
+    /// unless given in the graph
+    pub fn from_provider(key_graph: Graph, secret_provider: Box<dyn SecretProvider>) -> Self {
+        Self {
+            inner: key_graph,
+            secret_provider: Some(secret_provider),
+        }
+    }
+
+    /// `from_provider` with secret keys read from the environment variables
+    /// named in `variables` for each verification method, at the time of signing
+    pub fn from_env(key_graph: Graph, variables: HashMap<NamedNode, String>) -> Self {
+        Self::from_provider(key_graph, Box::new(EnvSecretProvider { variables }))
+    }
+
     // TODO: add dereferencing external controller document URL
     pub fn retrieve_verification_method(
         &self,
@@ -42,14 +163,20 @@ impl KeyGraph {
         let verification_method =
             self.retrieve_verification_method(verification_method_identifier)?;
 
-        let secret_key_term = verification_method
+        let secret_key_multibase = match verification_method
             .object_for_subject_predicate(verification_method_identifier, SECRET_KEY_MULTIBASE)
-            .ok_or(RDFProofsError::InvalidVerificationMethod)?;
-        let secret_key_multibase = match secret_key_term {
-            TermRef::Literal(v) => v.value(),
-            _ => return Err(RDFProofsError::InvalidVerificationMethod),
+        {
+            Some(TermRef::Literal(v)) => v.value().to_string(),
+            Some(_) => return Err(RDFProofsError::InvalidVerificationMethod),
+            None => self
+                .secret_provider
+                .as_ref()
+                .map(|provider| provider.get_secret_key_multibase(verification_method_identifier))
+                .transpose()?
+                .flatten()
+                .ok_or(RDFProofsError::InvalidVerificationMethod)?,
         };
-        let secret_key = multibase_to_ark(secret_key_multibase)?;
+        let secret_key = decode_secret_key(&secret_key_multibase)?;
         Ok(secret_key)
     }
 
@@ -67,10 +194,40 @@ impl KeyGraph {
             TermRef::Literal(v) => v.value(),
             _ => return Err(RDFProofsError::InvalidVerificationMethod),
         };
-        let public_key = multibase_to_ark(public_key_multibase)?;
+        let public_key = decode_public_key(public_key_multibase)?;
         Ok(public_key)
     }
 
+    /// credential types given as `allowedCredentialType` of the verification method,
+    /// which restrict the key to sign credentials of those types if any
+    pub fn get_allowed_credential_types(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Vec<NamedNode>, RDFProofsError> {
+        self.inner
+            .objects_for_subject_predicate(verification_method_identifier, ALLOWED_CREDENTIAL_TYPE)
+            .map(|t| match t {
+                TermRef::NamedNode(n) => Ok(n.into_owned()),
+                _ => Err(RDFProofsError::InvalidVerificationMethod),
+            })
+            .collect()
+    }
+
+    /// `expires` of the verification method given as xsd:dateTime if any
+    pub fn get_key_expiration(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<DateTime<Utc>>, RDFProofsError> {
+        match self
+            .inner
+            .object_for_subject_predicate(verification_method_identifier, KEY_EXPIRES)
+        {
+            None => Ok(None),
+            Some(TermRef::Literal(v)) => Ok(Some(v.value().parse()?)),
+            Some(_) => Err(RDFProofsError::InvalidVerificationMethod),
+        }
+    }
+
     pub fn get_keypair(
         &self,
         verification_method_identifier: NamedNodeRef,
@@ -80,3 +237,287 @@ impl KeyGraph {
         Ok((secret_key, public_key))
     }
 }
+
+/// extract the minimal key graph for verifying VP from a larger registry graph, i.e.,
+/// the triples of the verification methods referenced by the disclosed VCs in VP,
+/// dropping secret keys, e.g., for shipping compact bundles to offline verifiers
+pub fn export_key_graph_for_vp(
+    vp: &Dataset,
+    key_graph: &KeyGraph,
+) -> Result<Graph, RDFProofsError> {
+    let vp: VerifiablePresentation = vp.try_into()?;
+    let verification_methods = vp
+        .disclosed_vcs
+        .values()
+        .map(|vc| {
+            get_proof_metadata(&vc.proof)?
+                .verification_method
+                .ok_or(RDFProofsError::InvalidVerificationMethod)
+        })
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    let mut subset = Graph::new();
+    for verification_method in &verification_methods {
+        let triples = key_graph
+            .inner
+            .triples_for_subject(verification_method)
+            .filter(|t| t.predicate != SECRET_KEY_MULTIBASE)
+            .collect::<Vec<_>>();
+        if !triples.iter().any(|t| t.predicate == PUBLIC_KEY_MULTIBASE) {
+            return Err(RDFProofsError::InvalidVerificationMethod);
+        }
+        subset.extend(triples);
+    }
+    Ok(subset)
+}
+
+pub fn export_key_graph_for_vp_string(vp: &str, key_graph: &str) -> Result<String, RDFProofsError> {
+    let vp = get_dataset_from_nquads(vp)?;
+    let key_graph = get_graph_from_ntriples(key_graph)?.into();
+    let subset = export_key_graph_for_vp(&vp, &key_graph)?;
+    Ok(subset.iter().map(|t| format!("{} .\n", t)).collect())
+}
+
+impl KeyStore for KeyGraph {
+    fn get_public_key(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<BBSPlusPublicKey, RDFProofsError> {
+        KeyGraph::get_public_key(self, verification_method_identifier)
+    }
+
+    fn get_allowed_credential_types(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Vec<NamedNode>, RDFProofsError> {
+        KeyGraph::get_allowed_credential_types(self, verification_method_identifier)
+    }
+
+    fn get_key_expiration(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<DateTime<Utc>>, RDFProofsError> {
+        KeyGraph::get_key_expiration(self, verification_method_identifier)
+    }
+}
+
+// check that the key is allowed to sign the credential of the given types
+// other than `VerifiableCredential`, which must be disclosed to be checked
+pub(crate) fn check_key_usage<'a>(
+    verification_method_identifier: NamedNodeRef,
+    credential_types: impl IntoIterator<Item = TermRef<'a>>,
+    key_graph: &dyn KeyStore,
+) -> Result<(), RDFProofsError> {
+    let allowed_types = key_graph.get_allowed_credential_types(verification_method_identifier)?;
+    if allowed_types.is_empty() {
+        return Ok(());
+    }
+    let credential_types = credential_types
+        .into_iter()
+        .filter(|t| *t != TermRef::from(VERIFIABLE_CREDENTIAL_TYPE))
+        .collect::<Vec<_>>();
+    if credential_types.iter().any(|t| match t {
+        TermRef::NamedNode(n) => allowed_types.iter().any(|allowed| allowed.as_ref() == *n),
+        _ => false,
+    }) {
+        return Ok(());
+    }
+    Err(RDFProofsError::UnauthorizedKeyUsage(format!(
+        "{} is not allowed to sign credentials of types [{}]",
+        verification_method_identifier,
+        credential_types
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )))
+}
+
+/// callback notified with the new version when `SharedKeyGraph` is updated
+pub trait KeyGraphWatcher: Send + Sync {
+    fn on_update(&self, version: u64);
+}
+
+/// key graph shared among long-running verifiers,
+/// which can be atomically swapped without restart;
+/// snapshots taken before the swap are not affected
+pub struct SharedKeyGraph {
+    current: RwLock<Arc<KeyGraph>>,
+    version: AtomicU64,
+    watchers: Mutex<Vec<Box<dyn KeyGraphWatcher>>>,
+}
+
+impl From<KeyGraph> for SharedKeyGraph {
+    fn from(value: KeyGraph) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(value)),
+            version: AtomicU64::new(0),
+            watchers: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl SharedKeyGraph {
+    /// current key graph, which is not affected by later updates
+    pub fn snapshot(&self) -> Arc<KeyGraph> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// replace the key graph, and return the new version after notifying watchers
+    pub fn swap(&self, key_graph: KeyGraph) -> u64 {
+        let version = {
+            let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+            *current = Arc::new(key_graph);
+            self.version.fetch_add(1, Ordering::SeqCst) + 1
+        };
+        for watcher in self
+            .watchers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            watcher.on_update(version);
+        }
+        version
+    }
+
+    pub fn watch(&self, watcher: Box<dyn KeyGraphWatcher>) {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(watcher);
+    }
+}
+
+impl KeyStore for SharedKeyGraph {
+    fn get_public_key(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<BBSPlusPublicKey, RDFProofsError> {
+        self.snapshot()
+            .get_public_key(verification_method_identifier)
+    }
+
+    fn get_allowed_credential_types(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Vec<NamedNode>, RDFProofsError> {
+        self.snapshot()
+            .get_allowed_credential_types(verification_method_identifier)
+    }
+
+    fn get_key_expiration(
+        &self,
+        verification_method_identifier: NamedNodeRef,
+    ) -> Result<Option<DateTime<Utc>>, RDFProofsError> {
+        self.snapshot()
+            .get_key_expiration(verification_method_identifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyGraph, KeyGraphWatcher, KeyStore, SharedKeyGraph};
+    use crate::{common::get_graph_from_ntriples, error::RDFProofsError};
+    use oxrdf::NamedNodeRef;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    };
+
+    const KEY_GRAPH_0: &str = r#"
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+"#;
+    const KEY_GRAPH_1: &str = r#"
+<did:example:issuer1#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "usFM3CcvBMl_Dg5ixhQkHKGdqzY3GU9Uck6lj2i8vpbzLFOiZnjDNOpsItrkbNf2iCku-SZu5kO3nbLis-fuRhz_QwFcKw9IBpbPRPwXNQTX3zzcFsoNzs_wo8tkLQlcS" .
+"#;
+
+    const SECRET_KEY_0: &str = "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk";
+
+    struct LastVersion(Arc<AtomicU64>);
+
+    impl KeyGraphWatcher for LastVersion {
+        fn on_update(&self, version: u64) {
+            self.0.store(version, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn swap_shared_key_graph() {
+        let issuer0 = NamedNodeRef::new_unchecked("did:example:issuer0#bls12_381-g2-pub001");
+        let issuer1 = NamedNodeRef::new_unchecked("did:example:issuer1#bls12_381-g2-pub001");
+
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH_0).unwrap().into();
+        let shared = SharedKeyGraph::from(key_graph);
+        let last_version = Arc::new(AtomicU64::new(0));
+        shared.watch(Box::new(LastVersion(last_version.clone())));
+
+        let snapshot = shared.snapshot();
+        assert_eq!(shared.version(), 0);
+        assert!(KeyStore::get_public_key(&shared, issuer0).is_ok());
+        assert!(matches!(
+            KeyStore::get_public_key(&shared, issuer1),
+            Err(RDFProofsError::InvalidVerificationMethod)
+        ));
+
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH_1).unwrap().into();
+        assert_eq!(shared.swap(key_graph), 1);
+        assert_eq!(shared.version(), 1);
+        assert_eq!(last_version.load(Ordering::SeqCst), 1);
+        assert!(KeyStore::get_public_key(&shared, issuer1).is_ok());
+        assert!(KeyStore::get_public_key(&shared, issuer0).is_err());
+
+        // snapshot taken before the swap is unaffected
+        assert!(snapshot.get_public_key(issuer0).is_ok());
+    }
+
+    #[test]
+    fn key_graph_with_secret_provider() {
+        let issuer0 = NamedNodeRef::new_unchecked("did:example:issuer0#bls12_381-g2-pub001");
+        let issuer1 = NamedNodeRef::new_unchecked("did:example:issuer1#bls12_381-g2-pub001");
+        let expected = get_graph_from_ntriples(&format!(
+            "{}<{}> <https://w3id.org/security#secretKeyMultibase> \"{}\" .",
+            KEY_GRAPH_0,
+            issuer0.as_str(),
+            SECRET_KEY_0
+        ))
+        .map(KeyGraph::from)
+        .unwrap()
+        .get_keypair(issuer0)
+        .unwrap();
+
+        let key_graph = KeyGraph::from_provider(
+            get_graph_from_ntriples(KEY_GRAPH_0).unwrap(),
+            Box::new(
+                move |vm: NamedNodeRef| -> Result<Option<String>, RDFProofsError> {
+                    Ok((vm == issuer0).then(|| SECRET_KEY_0.to_string()))
+                },
+            ),
+        );
+        assert_eq!(key_graph.get_keypair(issuer0).unwrap(), expected);
+        assert!(matches!(
+            key_graph.get_secret_key(issuer1),
+            Err(RDFProofsError::InvalidVerificationMethod)
+        ));
+
+        let variable = "RDF_PROOFS_TEST_ISSUER0_SECRET_KEY";
+        let key_graph = KeyGraph::from_env(
+            get_graph_from_ntriples(KEY_GRAPH_0).unwrap(),
+            HashMap::from([(issuer0.into_owned(), variable.to_string())]),
+        );
+        assert!(key_graph.get_secret_key(issuer0).is_err());
+        std::env::set_var(variable, SECRET_KEY_0);
+        assert_eq!(key_graph.get_keypair(issuer0).unwrap(), expected);
+        std::env::remove_var(variable);
+    }
+}
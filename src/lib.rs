@@ -1,35 +1,263 @@
+// helpers shared by prover and verifier are unused in a partial build
+#![cfg_attr(
+    not(all(feature = "prove", feature = "verify")),
+    allow(dead_code, unused_imports)
+)]
+// library paths must return errors instead of panicking
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
+#[macro_use]
+mod macros;
+
+mod attachment;
+#[cfg(feature = "encryption")]
+mod attribute_encryption;
+#[cfg(feature = "blind")]
 mod blind_signature;
+#[cfg(feature = "verify")]
+mod bundle;
+mod capabilities;
+mod classic_vp;
+mod clock;
+pub mod codec;
+mod commitment;
 mod common;
+mod conformance;
 mod constants;
 pub mod context;
+mod credential_context;
+mod curve;
+#[cfg(feature = "prove")]
+mod deanon_map;
+#[cfg(feature = "prove")]
 mod derive_proof;
+mod device_binding;
+#[cfg(feature = "prove")]
+mod disclosure_profile;
+#[cfg(all(test, feature = "prove", feature = "verify"))]
+mod disclosure_proptests;
+#[cfg(feature = "encryption")]
 mod elgamal;
+#[cfg(feature = "encryption")]
 mod elliptic_elgamal;
+mod equivalence;
 pub mod error;
+mod facade;
+#[cfg(feature = "prove")]
+mod holder_binding;
+#[cfg(feature = "blind")]
+mod holder_state;
+mod inequality;
 pub mod key_gen;
 mod key_graph;
+mod limits;
+#[cfg(feature = "prove")]
+mod linkability;
+mod message_layout;
+mod metadata;
+mod metrics;
+mod migrate;
+mod namespace;
+#[cfg(all(test, feature = "prove", feature = "verify"))]
+mod no_panic_tests;
+mod nonce_token;
+#[cfg(feature = "verify")]
+mod notary;
+#[cfg(feature = "verify")]
+mod onchain;
 mod ordered_triple;
+mod policy;
+mod precheck;
+mod precomputed_canonicalization;
+#[cfg(feature = "predicates")]
 mod predicate;
+mod predicate_graph;
+#[cfg(feature = "prove")]
+mod progress;
+mod proof_layout;
+mod proof_value;
+mod redact;
+#[cfg(feature = "registry")]
+mod registry;
+#[cfg(feature = "verify")]
+mod replay;
+#[cfg(feature = "report")]
+mod report;
+mod secret_group;
 mod signature;
+mod skolem;
+#[cfg(feature = "sparql")]
+mod sparql_disclosure;
+#[cfg(feature = "store")]
+mod store;
+mod subtree;
 mod vc;
+#[cfg(feature = "vc-store")]
+mod vc_store;
+#[cfg(feature = "verify")]
+mod verification_cache;
+#[cfg(feature = "verify")]
 mod verify_proof;
+#[cfg(feature = "verify")]
+mod warnings;
 
+pub use attachment::{
+    digest_large_literals, verify_attachment_digest, verify_attachment_digest_string,
+};
+#[cfg(feature = "encryption")]
+pub use attribute_encryption::{
+    get_encrypted_attribute, get_encrypted_attribute_string, AttributeEncryptionRequest,
+    AttributeEncryptionRequestString, EncryptedAttribute,
+};
+#[cfg(feature = "blind")]
 pub use blind_signature::{
     blind_sign, blind_sign_string, blind_verify, blind_verify_string, request_blind_sign,
     request_blind_sign_string, unblind, unblind_string, verify_blind_sign_request,
     verify_blind_sign_request_string, BlindSignRequest, BlindSignRequestString,
 };
-pub use common::{ark_to_base64url, ark_to_multibase, multibase_to_ark};
-pub use derive_proof::{derive_proof, derive_proof_string};
+#[cfg(feature = "verify")]
+pub use bundle::{verify_bundle, BundledVerifyingKey, VerificationBundle};
+pub use capabilities::{capabilities, Capabilities, CapabilityLimits};
+pub use classic_vp::{derive_classic_vp, derive_classic_vp_string};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use commitment::{
+    commit_to_term, commit_to_term_string, CommitmentEqualityRequest,
+    CommitmentEqualityRequestString,
+};
+pub use common::{
+    ark_to_base64url, ark_to_multibase, encode_literal_to_field, multibase_to_ark,
+    StatementIndexMap,
+};
+pub use conformance::{check_conformance, RdfConformance};
+#[cfg(feature = "prove")]
+pub use deanon_map::{
+    generate_deanon_map_skeleton, generate_deanon_map_skeleton_string, DeanonMapSkeletonEntry,
+    HiddenPredicates, ScopedDeanonMap,
+};
+#[cfg(all(feature = "prove", feature = "async"))]
+pub use derive_proof::derive_proof_to_async_writer;
+#[cfg(feature = "prove")]
+pub use derive_proof::{
+    derive_proof, derive_proof_dry_run, derive_proof_string, derive_proof_to_writer,
+    BlankNodeLabeling, CreatedPrecision, DeriveOptions, DeriveOptionsString, PlannedStatement,
+    ProofPlan,
+};
+pub use device_binding::{
+    device_binding_challenge, device_binding_challenge_string, device_key_handle,
+    device_key_handle_string, DeviceAttestation, DeviceAttestationVerifier, DeviceBinding,
+    DeviceBindingString, DeviceSigner,
+};
+#[cfg(feature = "prove")]
+pub use disclosure_profile::DisclosureProfile;
+#[cfg(feature = "encryption")]
 pub use elgamal::{elgamal_decrypt, elgamal_encrypt, elgamal_keygen};
+#[cfg(feature = "encryption")]
 pub use elliptic_elgamal::{
-    elliptic_elgamal_decrypt, elliptic_elgamal_encrypt, elliptic_elgamal_keygen,
+    combine_decrypted_shares, elliptic_elgamal_decrypt, elliptic_elgamal_encrypt,
+    elliptic_elgamal_escrowed_encryption_with_bbs_plus, elliptic_elgamal_keygen,
     elliptic_elgamal_verifiable_encryption_with_bbs_plus, get_encrypted_uid, str_to_secret_key,
+    verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus,
     verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus, ElGamalCiphertext,
-    ElGamalPublicKey, ElGamalSecretKey, ElGamalVerifiableEncryption,
+    ElGamalEscrowedEncryption, ElGamalPublicKey, ElGamalSecretKey, ElGamalVerifiableEncryption,
+};
+pub use equivalence::{
+    vp_equivalence_classes, vp_equivalence_classes_string, EquivalenceClass, TermOccurrence,
+};
+#[cfg(feature = "verify")]
+pub use facade::Verifier;
+#[cfg(feature = "prove")]
+pub use facade::{Holder, PresentationSession};
+pub use facade::{IssuanceMode, IssuedCredential, Issuer, PresentationRequest};
+#[cfg(feature = "prove")]
+pub use holder_binding::HolderBindingPolicy;
+#[cfg(feature = "blind")]
+pub use holder_state::{
+    export_blind_issuance_state, export_blind_issuance_state_string, import_blind_issuance_state,
+    import_blind_issuance_state_string, BlindIssuanceState, BlindIssuanceStateString,
+};
+pub use inequality::{not_equal_request, not_equal_request_string};
+pub use key_graph::{
+    export_key_graph_for_vp, export_key_graph_for_vp_string, KeyGraph, KeyGraphWatcher, KeyStore,
+    SecretProvider, SharedKeyGraph,
 };
-pub use key_graph::KeyGraph;
+pub use limits::Limits;
+#[cfg(feature = "prove")]
+pub use linkability::{
+    lint_disclosure, lint_disclosure_string, LinkabilityFinding, LinkabilityReason,
+    LinkabilityReport, LinkabilityRisk, StrictDisclosure,
+};
+pub use message_layout::{
+    message_layout, message_layout_string, MessageLayoutEntry, MessageRole, SecretSlot,
+    TermEncoding, TermPosition,
+};
+pub use metadata::{
+    vp_credential_summary, vp_credential_summary_string, vp_metadata, vp_metadata_string,
+    CredentialSummary, ProofMetadata, VpMetadata,
+};
+pub use metrics::Metrics;
+pub use migrate::{
+    migrate_ark, migrate_key_graph, migrate_key_graph_string, migrate_proof_value, migrate_vc,
+    migrate_vc_string, migrate_vp, migrate_vp_string, SERIALIZATION_VERSION,
+};
+pub use nonce_token::{issue_nonce_token, issue_nonce_token_string, NonceTokenValidation};
+#[cfg(feature = "verify")]
+pub use notary::{attestation_credential, notarize, notarize_string, vp_digest, vp_digest_string};
+#[cfg(feature = "verify")]
+pub use onchain::{
+    export_onchain_verifying_key, export_onchain_verifying_key_string,
+    extract_onchain_predicate_proofs, extract_onchain_predicate_proofs_string,
+    OnChainPredicateProof, OnChainProof, OnChainVerifyingKey,
+};
+pub use policy::PolicyDigest;
+pub use precheck::{precheck, precheck_string, PrecheckProblem, PrecheckReason};
+pub use precomputed_canonicalization::PrecomputedCanonicalization;
+#[cfg(feature = "predicates")]
 pub use predicate::CircuitString;
+pub use predicate_graph::{PredicateGraph, VariableRef};
+#[cfg(feature = "prove")]
+pub use progress::{CancellationToken, Progress, ProofPhase};
+pub use proof_layout::{ExtensionPolicy, ProofExtension};
+pub use proof_value::{
+    attach_proof_value, attach_proof_value_string, detach_proof_value, detach_proof_value_string,
+    ProofValue,
+};
+#[cfg(feature = "registry")]
+pub use registry::{
+    artifact_digest, generate_predicate_request, ArtifactRef, CircuitArtifacts, PredicateRegistry,
+    PredicateRegistryManifest, PredicateRequest, PredicateTemplate,
+};
+#[cfg(feature = "verify")]
+pub use replay::{InMemoryReplayGuard, ReplayGuard};
+#[cfg(feature = "report")]
+pub use report::{
+    verification_report, verification_report_string, PredicateReport, VerificationReport,
+};
+pub use secret_group::CredentialSecrets;
 pub use signature::{sign, sign_string, verify, verify_string};
-pub use vc::{VcPair, VcPairString, VerifiableCredential};
-pub use verify_proof::{verify_proof, verify_proof_string};
+pub use skolem::{deskolemize, deskolemize_string, is_skolem_iri, HiddenSkolemIris};
+#[cfg(feature = "sparql")]
+pub use sparql_disclosure::{disclose_with_sparql, disclose_with_sparql_string};
+#[cfg(feature = "store")]
+pub use store::{
+    get_key_graph_from_store, get_vp_from_store, insert_disclosed_triples_into_store,
+    insert_vp_into_store, verify_proof_in_store,
+};
+pub use subtree::{hide_subtrees, hide_subtrees_string};
+pub use vc::{supported_cryptosuites, VcPair, VcPairString, VerifiableCredential};
+#[cfg(feature = "vc-store")]
+pub use vc_store::{
+    get_vc_pairs_from_store, get_vc_pairs_from_store_string, EncryptedFileVcStore,
+    StoredVcDisclosure, StoredVcDisclosureString, VcStore,
+};
+#[cfg(feature = "verify")]
+pub use verification_cache::{InMemoryVerificationCache, VerificationCache};
+#[cfg(feature = "verify")]
+pub use verify_proof::{
+    verify_proof, verify_proof_string, verify_secret_rotation, verify_secret_rotation_string,
+    PredicateOutput, VerificationResult, VerifyOptions, VerifyOptionsString,
+};
+#[cfg(feature = "verify")]
+pub use warnings::{VerificationWarning, WarningPolicy};
@@ -0,0 +1,150 @@
+use crate::error::RDFProofsError;
+use oxrdf::{BlankNodeRef, Dataset, GraphNameRef, QuadRef, SubjectRef, TermRef};
+use std::collections::HashMap;
+
+/// opt-in limits on the VP canonicalized in `derive_proof` and `verify_proof`,
+/// which reject adversarial graphs with `CanonicalizationLimitExceeded` up front
+/// instead of running RDF canonicalization into its worst case.
+///
+/// the cost of canonicalization explodes when many blank nodes are indistinguishable
+/// by the quads they directly appear in, i.e., share the same first-degree hash,
+/// since they are told apart by hashing every permutation of their neighborhoods
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// maximum number of quads
+    pub max_quads: Option<usize>,
+    /// maximum number of blank nodes
+    pub max_blank_nodes: Option<usize>,
+    /// maximum number of blank nodes sharing the same first-degree hash
+    pub max_indistinguishable_blank_nodes: Option<usize>,
+}
+
+// check dataset against limits before canonicalization
+pub(crate) fn check_canonicalization_limits(
+    dataset: &Dataset,
+    limits: Option<&Limits>,
+) -> Result<(), RDFProofsError> {
+    let Some(limits) = limits else {
+        return Ok(());
+    };
+    let exceeded = |what: &str, count: usize, max: usize| {
+        RDFProofsError::CanonicalizationLimitExceeded(format!(
+            "{} {} exceeding {}",
+            count, what, max
+        ))
+    };
+
+    let quad_count = dataset.len();
+    if let Some(max) = limits.max_quads {
+        if quad_count > max {
+            return Err(exceeded("quads", quad_count, max));
+        }
+    }
+
+    // quads of each blank node, with itself and the other blank nodes masked
+    // as in the first-degree hash of RDF canonicalization
+    let mut first_degree_quads: HashMap<BlankNodeRef, Vec<String>> = HashMap::new();
+    for quad in dataset.iter() {
+        for bnode in get_bnodes(quad) {
+            first_degree_quads
+                .entry(bnode)
+                .or_default()
+                .push(mask_bnodes(quad, bnode));
+        }
+    }
+    if let Some(max) = limits.max_blank_nodes {
+        if first_degree_quads.len() > max {
+            return Err(exceeded("blank nodes", first_degree_quads.len(), max));
+        }
+    }
+
+    if let Some(max) = limits.max_indistinguishable_blank_nodes {
+        let mut group_sizes: HashMap<Vec<String>, usize> = HashMap::new();
+        for (_, mut quads) in first_degree_quads {
+            quads.sort();
+            *group_sizes.entry(quads).or_default() += 1;
+        }
+        if let Some(largest) = group_sizes.into_values().max().filter(|size| *size > max) {
+            return Err(exceeded("indistinguishable blank nodes", largest, max));
+        }
+    }
+    Ok(())
+}
+
+fn get_bnodes(quad: QuadRef) -> Vec<BlankNodeRef> {
+    let mut bnodes = vec![];
+    let candidates = [
+        match quad.subject {
+            SubjectRef::BlankNode(b) => Some(b),
+            _ => None,
+        },
+        match quad.object {
+            TermRef::BlankNode(b) => Some(b),
+            _ => None,
+        },
+        match quad.graph_name {
+            GraphNameRef::BlankNode(b) => Some(b),
+            _ => None,
+        },
+    ];
+    for b in candidates.into_iter().flatten() {
+        if !bnodes.contains(&b) {
+            bnodes.push(b);
+        }
+    }
+    bnodes
+}
+
+fn mask_bnodes(quad: QuadRef, reference: BlankNodeRef) -> String {
+    let mask = |b: BlankNodeRef| if b == reference { "_:a" } else { "_:z" };
+    let subject = match quad.subject {
+        SubjectRef::BlankNode(b) => mask(b).to_string(),
+        s => s.to_string(),
+    };
+    let object = match quad.object {
+        TermRef::BlankNode(b) => mask(b).to_string(),
+        o => o.to_string(),
+    };
+    let graph_name = match quad.graph_name {
+        GraphNameRef::BlankNode(b) => mask(b).to_string(),
+        g => g.to_string(),
+    };
+    format!("{} {} {} {}", subject, quad.predicate, object, graph_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_canonicalization_limits, Limits};
+    use crate::{common::get_dataset_from_nquads, error::RDFProofsError};
+
+    #[test]
+    fn canonicalization_limits() {
+        // cycle of blank nodes indistinguishable by their first-degree quads
+        let cycle = (0..6)
+            .map(|i| format!("_:c{} <http://example.org/p> _:c{} .\n", i, (i + 1) % 6))
+            .collect::<String>();
+        let dataset = get_dataset_from_nquads(&cycle).unwrap();
+
+        assert!(check_canonicalization_limits(&dataset, None).is_ok());
+        assert!(check_canonicalization_limits(&dataset, Some(&Limits::default())).is_ok());
+        for limits in [
+            Limits {
+                max_quads: Some(5),
+                ..Default::default()
+            },
+            Limits {
+                max_blank_nodes: Some(5),
+                ..Default::default()
+            },
+            Limits {
+                max_indistinguishable_blank_nodes: Some(5),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                check_canonicalization_limits(&dataset, Some(&limits)),
+                Err(RDFProofsError::CanonicalizationLimitExceeded(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use crate::{
+    common::{get_vc_from_ntriples, is_nym},
+    context::{CREATED, CREDENTIAL_SUBJECT, ISSUER},
+    derive_proof::CreatedPrecision,
+    error::RDFProofsError,
+    vc::{VcPair, VcPairString},
+};
+use chrono::{DateTime, FixedOffset, Timelike};
+use oxrdf::{
+    vocab::{rdf, xsd},
+    Graph, LiteralRef, NamedNode, NamedNodeRef, SubjectRef, Term, TermRef, Triple, TripleRef,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// severity of a linkability finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LinkabilityRisk {
+    Medium,
+    High,
+}
+
+/// reason why a disclosed term may make the holder re-identifiable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LinkabilityReason {
+    /// IRI of a credential or its subject, which is correlatable across presentations
+    DisclosedIdentifier,
+    /// IRI other than types, issuers, and proof parameters, which few holders share
+    RareIri,
+    /// string literal likely to be unique to the holder, e.g., names and identifiers
+    UniqueLiteral,
+    /// dateTime literal with time-of-day precision
+    PreciseTimestamp,
+}
+
+/// disclosed term flagged by `lint_disclosure`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkabilityFinding {
+    pub risk: LinkabilityRisk,
+    pub reason: LinkabilityReason,
+    pub term: Term,
+    /// first disclosed triple containing the term
+    pub triple: Triple,
+}
+
+/// linkability risks of a planned disclosure, sorted by descending risk
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkabilityReport {
+    pub findings: Vec<LinkabilityFinding>,
+}
+
+impl LinkabilityReport {
+    /// the highest risk found, or `None` if nothing is flagged
+    pub fn max_risk(&self) -> Option<LinkabilityRisk> {
+        self.findings.iter().map(|f| f.risk).max()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// analyze disclosed VCs to be given to `derive_proof` for terms that make the holder
+/// re-identifiable across presentations, so that wallets can warn holders in advance.
+/// this is a heuristic without knowledge of other holders' credentials,
+/// so an empty report does not guarantee unlinkability
+pub fn lint_disclosure(vc_pairs: &Vec<VcPair>) -> Result<LinkabilityReport, RDFProofsError> {
+    let mut findings = vec![];
+    let mut flagged = HashSet::new();
+
+    for VcPair { disclosed, .. } in vc_pairs {
+        lint_graph(&disclosed.document, &mut flagged, &mut findings);
+        // only timestamps matter in proof, e.g., creation time of VC proof
+        for triple in disclosed.proof.triples_for_predicate(CREATED) {
+            if let TermRef::Literal(v) = triple.object {
+                if is_precise_timestamp(v) {
+                    push_finding(
+                        LinkabilityRisk::Medium,
+                        LinkabilityReason::PreciseTimestamp,
+                        triple.object,
+                        triple,
+                        &mut flagged,
+                        &mut findings,
+                    );
+                }
+            }
+        }
+    }
+
+    findings.sort_by_cached_key(|f| (std::cmp::Reverse(f.risk), f.reason, f.term.to_string()));
+    Ok(LinkabilityReport { findings })
+}
+
+pub fn lint_disclosure_string(
+    vc_pairs: &Vec<VcPairString>,
+) -> Result<LinkabilityReport, RDFProofsError> {
+    lint_disclosure(&get_vc_pairs_from_string(vc_pairs)?)
+}
+
+/// strict minimization mode of `derive_proof`, rejecting disclosed literals likely to
+/// correlate presentations, i.e., dateTime with time-of-day precision and string literals
+/// likely unique to the holder, unless their predicates are whitelisted, e.g.,
+///
+/// ```json
+/// {
+///   "allowedPredicates": ["https://www.w3.org/2018/credentials#issuanceDate"]
+/// }
+/// ```
+///
+/// `created` of VC proofs not redacted and that of VP proof with `CreatedPrecision::Exact`
+/// are rejected as well unless `http://purl.org/dc/terms/created` is whitelisted
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrictDisclosure {
+    /// predicates whose literals may be disclosed as they are
+    #[serde(default)]
+    pub allowed_predicates: Vec<String>,
+}
+
+impl StrictDisclosure {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// findings in the disclosed VC documents violating the strict disclosure,
+    /// e.g., for wallets to warn holders instead of failing `derive_proof`
+    pub fn report(&self, vc_pairs: &Vec<VcPair>) -> Result<LinkabilityReport, RDFProofsError> {
+        self.report_with_proofs(vc_pairs, false)
+    }
+
+    pub fn report_string(
+        &self,
+        vc_pairs: &Vec<VcPairString>,
+    ) -> Result<LinkabilityReport, RDFProofsError> {
+        self.report(&get_vc_pairs_from_string(vc_pairs)?)
+    }
+
+    pub(crate) fn check(
+        &self,
+        vc_pairs: &Vec<VcPair>,
+        redacted_proof_configs: &[NamedNode],
+        created_precision: CreatedPrecision,
+    ) -> Result<(), RDFProofsError> {
+        let with_proofs = !redacted_proof_configs.contains(&CREATED.into_owned());
+        if let Some(finding) = self
+            .report_with_proofs(vc_pairs, with_proofs)?
+            .findings
+            .first()
+        {
+            return Err(RDFProofsError::StrictDisclosureViolation(
+                finding.triple.to_string(),
+            ));
+        }
+        if created_precision == CreatedPrecision::Exact && !self.allows(CREATED)? {
+            return Err(RDFProofsError::StrictDisclosureViolation(
+                "exact `created` of VP proof".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn allows(&self, predicate: NamedNodeRef) -> Result<bool, RDFProofsError> {
+        for allowed in &self.allowed_predicates {
+            if NamedNode::new(allowed)? == predicate {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn report_with_proofs(
+        &self,
+        vc_pairs: &Vec<VcPair>,
+        with_proofs: bool,
+    ) -> Result<LinkabilityReport, RDFProofsError> {
+        let allowed_predicates = self
+            .allowed_predicates
+            .iter()
+            .map(NamedNode::new)
+            .collect::<Result<HashSet<_>, _>>()?;
+        let is_allowed = |t: &TripleRef| allowed_predicates.contains(&t.predicate.into_owned());
+
+        // lint the disclosed VCs without the whitelisted triples
+        let mut findings = vec![];
+        let mut flagged = HashSet::new();
+        for VcPair { disclosed, .. } in vc_pairs {
+            let document = disclosed
+                .document
+                .iter()
+                .filter(|t| !is_allowed(t))
+                .collect::<Graph>();
+            lint_graph(&document, &mut flagged, &mut findings);
+            if !with_proofs {
+                continue;
+            }
+            for triple in disclosed.proof.triples_for_predicate(CREATED) {
+                if let TermRef::Literal(v) = triple.object {
+                    if is_precise_timestamp(v) && !is_allowed(&triple) {
+                        push_finding(
+                            LinkabilityRisk::Medium,
+                            LinkabilityReason::PreciseTimestamp,
+                            triple.object,
+                            triple,
+                            &mut flagged,
+                            &mut findings,
+                        );
+                    }
+                }
+            }
+        }
+
+        findings.retain(|f| match f.reason {
+            LinkabilityReason::PreciseTimestamp => true,
+            LinkabilityReason::UniqueLiteral => f.risk == LinkabilityRisk::High,
+            _ => false,
+        });
+        findings.sort_by_cached_key(|f| (std::cmp::Reverse(f.risk), f.reason, f.term.to_string()));
+        Ok(LinkabilityReport { findings })
+    }
+}
+
+fn get_vc_pairs_from_string(vc_pairs: &[VcPairString]) -> Result<Vec<VcPair>, RDFProofsError> {
+    vc_pairs
+        .iter()
+        .map(|pair| {
+            Ok(VcPair::new(
+                get_vc_from_ntriples(&pair.original_document, &pair.original_proof)?,
+                get_vc_from_ntriples(&pair.disclosed_document, &pair.disclosed_proof)?,
+            ))
+        })
+        .collect()
+}
+
+// predicates whose IRI objects are shared by many holders
+const COMMON_IRI_PREDICATES: [NamedNodeRef; 2] = [rdf::TYPE, ISSUER];
+
+fn lint_graph(
+    graph: &Graph,
+    flagged: &mut HashSet<(Term, LinkabilityReason)>,
+    findings: &mut Vec<LinkabilityFinding>,
+) {
+    for triple in graph.iter() {
+        if let SubjectRef::NamedNode(s) = triple.subject {
+            if !is_nym(&s.into_owned()) {
+                push_finding(
+                    LinkabilityRisk::High,
+                    LinkabilityReason::DisclosedIdentifier,
+                    s.into(),
+                    triple,
+                    flagged,
+                    findings,
+                );
+            }
+        }
+
+        let (risk, reason) = match triple.object {
+            TermRef::NamedNode(o) if is_nym(&o.into_owned()) => continue,
+            TermRef::NamedNode(_) if triple.predicate == CREDENTIAL_SUBJECT => (
+                LinkabilityRisk::High,
+                LinkabilityReason::DisclosedIdentifier,
+            ),
+            TermRef::NamedNode(_) if COMMON_IRI_PREDICATES.contains(&triple.predicate) => continue,
+            TermRef::NamedNode(_) => (LinkabilityRisk::Medium, LinkabilityReason::RareIri),
+            TermRef::Literal(v) if is_precise_timestamp(v) => {
+                (LinkabilityRisk::Medium, LinkabilityReason::PreciseTimestamp)
+            }
+            TermRef::Literal(v) if v.datatype() == xsd::STRING || v.language().is_some() => {
+                // values mixing letters and digits are likely identifiers, e.g., lot numbers
+                let digits = v.value().chars().filter(|c| c.is_ascii_digit()).count();
+                let risk =
+                    if digits >= 6 || (digits > 0 && v.value().chars().any(char::is_alphabetic)) {
+                        LinkabilityRisk::High
+                    } else {
+                        LinkabilityRisk::Medium
+                    };
+                (risk, LinkabilityReason::UniqueLiteral)
+            }
+            _ => continue,
+        };
+        push_finding(risk, reason, triple.object, triple, flagged, findings);
+    }
+}
+
+fn push_finding(
+    risk: LinkabilityRisk,
+    reason: LinkabilityReason,
+    term: TermRef,
+    triple: TripleRef,
+    flagged: &mut HashSet<(Term, LinkabilityReason)>,
+    findings: &mut Vec<LinkabilityFinding>,
+) {
+    if flagged.insert((term.into_owned(), reason)) {
+        findings.push(LinkabilityFinding {
+            risk,
+            reason,
+            term: term.into_owned(),
+            triple: triple.into_owned(),
+        });
+    }
+}
+
+// dateTime with non-zero time of day is precise enough to single out holders
+fn is_precise_timestamp(literal: LiteralRef) -> bool {
+    if literal.datatype() != xsd::DATE_TIME {
+        return false;
+    }
+    match DateTime::<FixedOffset>::parse_from_rfc3339(literal.value()) {
+        Ok(t) => t.num_seconds_from_midnight() != 0 || t.nanosecond() != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint_disclosure_string, LinkabilityReason, LinkabilityRisk, StrictDisclosure};
+    use crate::VcPairString;
+
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://example.org/vocab/lotNumber> "0000001" .
+    <did:example:john> <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC: &str = r#"
+    _:e0 <http://example.org/vocab/vaccine> <http://example.org/vaccine/a> .
+    _:e1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e1 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+    _:e1 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    _:e1 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+    #[test]
+    fn lint_disclosure_success() {
+        // disclosing everything
+        let report =
+            lint_disclosure_string(&vec![VcPairString::new(VC, VC_PROOF, VC, VC_PROOF)]).unwrap();
+        assert_eq!(report.max_risk(), Some(LinkabilityRisk::High));
+        let flagged = report
+            .findings
+            .iter()
+            .map(|f| (f.risk, f.reason, f.term.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            vec![
+                (
+                    LinkabilityRisk::High,
+                    LinkabilityReason::DisclosedIdentifier,
+                    "<did:example:john>".to_string()
+                ),
+                (
+                    LinkabilityRisk::High,
+                    LinkabilityReason::DisclosedIdentifier,
+                    "<http://example.org/vcred/00>".to_string()
+                ),
+                (
+                    LinkabilityRisk::High,
+                    LinkabilityReason::UniqueLiteral,
+                    "\"0000001\"".to_string()
+                ),
+                (
+                    LinkabilityRisk::Medium,
+                    LinkabilityReason::RareIri,
+                    "<http://example.org/vaccine/a>".to_string()
+                ),
+                (
+                    LinkabilityRisk::Medium,
+                    LinkabilityReason::UniqueLiteral,
+                    "\"John Smith\"".to_string()
+                ),
+                (
+                    LinkabilityRisk::Medium,
+                    LinkabilityReason::PreciseTimestamp,
+                    "\"2023-02-09T09:35:07Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>"
+                        .to_string()
+                ),
+            ]
+        );
+
+        // hiding identifiers and claims except for vaccine
+        let report = lint_disclosure_string(&vec![VcPairString::new(
+            VC,
+            VC_PROOF,
+            DISCLOSED_VC,
+            VC_PROOF,
+        )])
+        .unwrap();
+        assert_eq!(report.max_risk(), Some(LinkabilityRisk::Medium));
+        assert!(report
+            .findings
+            .iter()
+            .all(|f| f.reason != LinkabilityReason::DisclosedIdentifier));
+    }
+
+    #[test]
+    fn strict_disclosure_report() {
+        let vc_pairs = vec![VcPairString::new(VC, VC_PROOF, VC, VC_PROOF)];
+        let report = StrictDisclosure::new().report_string(&vc_pairs).unwrap();
+        let flagged = report
+            .findings
+            .iter()
+            .map(|f| f.term.to_string())
+            .collect::<Vec<_>>();
+        // identifiers and low-entropy literals are out of scope
+        assert_eq!(flagged, vec!["\"0000001\"".to_string()]);
+
+        let strict_disclosure = StrictDisclosure {
+            allowed_predicates: vec!["http://example.org/vocab/lotNumber".to_string()],
+        };
+        assert!(strict_disclosure
+            .report_string(&vc_pairs)
+            .unwrap()
+            .is_empty());
+    }
+}
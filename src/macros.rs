@@ -0,0 +1,18 @@
+// debug output of intermediate values, printed only with `debug-print` feature
+macro_rules! debug_println {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "debug-print") {
+            println!($($arg)*);
+        }
+    };
+}
+
+// internal invariant checks, which run in debug builds and also in release builds
+// with `paranoid` feature to detect logic violations caused by unexpected inputs
+macro_rules! invariant {
+    ($($arg:tt)*) => {
+        if cfg!(any(debug_assertions, feature = "paranoid")) {
+            assert!($($arg)*);
+        }
+    };
+}
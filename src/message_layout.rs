@@ -0,0 +1,348 @@
+use crate::{
+    common::get_vc_from_ntriples, error::RDFProofsError, namespace::get_namespace,
+    ordered_triple::OrderedNamedOrBlankNode, signature::transform, vc::VerifiableCredential,
+};
+use oxrdf::{NamedOrBlankNode, Term};
+use std::collections::BTreeMap;
+
+/// position of a term in a triple
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TermPosition {
+    Subject,
+    Predicate,
+    Object,
+}
+
+pub(crate) const POSITIONS: [TermPosition; 3] = [
+    TermPosition::Subject,
+    TermPosition::Predicate,
+    TermPosition::Object,
+];
+
+/// slot of the holder's secret, or the constant `1` for unbound VCs, in the message vector,
+/// which is versioned as part of the cryptosuite identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretSlot {
+    /// before the VC document, used by `*-2023` cryptosuites
+    First,
+    /// after the proof config, used by `*-2024` cryptosuites
+    Last,
+}
+
+/// encoding of each term into messages, which is versioned as part of the cryptosuite identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermEncoding {
+    /// one message per term
+    Termwise,
+    /// each term followed by the message of its IRI namespace, e.g., `urn:uuid:`,
+    /// so that hidden IRIs can be proven to share a namespace; used by `*-split-iri-*` cryptosuites
+    SplitIri,
+    /// one message per term, where `xsd:decimal` literals are encoded into integers scaled by
+    /// 10^`DECIMAL_SCALE` rather than hashed, so that circuits can compare them;
+    /// used by `*-scaled-decimal-*` cryptosuites
+    ScaledDecimal,
+}
+
+impl TermEncoding {
+    pub(crate) fn messages_per_term(self) -> usize {
+        match self {
+            TermEncoding::Termwise | TermEncoding::ScaledDecimal => 1,
+            TermEncoding::SplitIri => 2,
+        }
+    }
+}
+
+/// role of a message in the term-wise message vector signed for VC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageRole {
+    /// holder's secret for bound VCs, or the constant `1` otherwise
+    Secret { bound: bool },
+    /// term of the `triple`-th triple in the sorted canonicalized VC document
+    Document {
+        triple: usize,
+        position: TermPosition,
+    },
+    /// delimiter separating VC document and proof config
+    Delimiter,
+    /// term of the `triple`-th triple in the sorted canonicalized proof config
+    ProofConfig {
+        triple: usize,
+        position: TermPosition,
+    },
+    /// IRI namespace of the term in the preceding message, which is empty for non-IRI terms
+    Namespace { namespace: String },
+}
+
+/// message at `index` in the message vector, with the canonicalized term it encodes if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageLayoutEntry {
+    pub index: usize,
+    pub role: MessageRole,
+    pub term: Option<Term>,
+}
+
+/// describe the message vector signed for VC, i.e., the secret slot, the terms of
+/// canonicalized VC document, the delimiter, and the terms of canonicalized proof config,
+/// whose indexes are those used in `sign` and `derive_proof`;
+/// the secret slot and namespace messages are placed as specified by the cryptosuite of VC;
+/// blank nodes in the terms are labeled as in canonicalization
+pub fn message_layout(
+    vc: &VerifiableCredential,
+) -> Result<Vec<MessageLayoutEntry>, RDFProofsError> {
+    let bound = vc.is_bound()?;
+    let secret_slot = vc.secret_slot()?;
+    let term_encoding = vc.term_encoding()?;
+    let document_terms = transform(&vc.document)?;
+    let proof_config_terms = transform(&vc.get_proof_config())?;
+
+    let mut layout = vec![];
+    if secret_slot == SecretSlot::First {
+        layout.push(MessageLayoutEntry {
+            index: 0,
+            role: MessageRole::Secret { bound },
+            term: None,
+        });
+    }
+    for (i, term) in document_terms.into_iter().enumerate() {
+        push_term(
+            &mut layout,
+            MessageRole::Document {
+                triple: i / 3,
+                position: POSITIONS[i % 3],
+            },
+            term,
+            term_encoding,
+        );
+    }
+    layout.push(MessageLayoutEntry {
+        index: layout.len(),
+        role: MessageRole::Delimiter,
+        term: None,
+    });
+    for (i, term) in proof_config_terms.into_iter().enumerate() {
+        push_term(
+            &mut layout,
+            MessageRole::ProofConfig {
+                triple: i / 3,
+                position: POSITIONS[i % 3],
+            },
+            term,
+            term_encoding,
+        );
+    }
+    if secret_slot == SecretSlot::Last {
+        layout.push(MessageLayoutEntry {
+            index: layout.len(),
+            role: MessageRole::Secret { bound },
+            term: None,
+        });
+    }
+    Ok(layout)
+}
+
+// push the message of term, followed by that of its namespace if split
+fn push_term(
+    layout: &mut Vec<MessageLayoutEntry>,
+    role: MessageRole,
+    term: Term,
+    term_encoding: TermEncoding,
+) {
+    let namespace = get_namespace(term.as_ref()).to_string();
+    layout.push(MessageLayoutEntry {
+        index: layout.len(),
+        role,
+        term: Some(term),
+    });
+    if term_encoding == TermEncoding::SplitIri {
+        layout.push(MessageLayoutEntry {
+            index: layout.len(),
+            role: MessageRole::Namespace { namespace },
+            term: None,
+        });
+    }
+}
+
+pub fn message_layout_string(
+    document: &str,
+    proof: &str,
+) -> Result<Vec<MessageLayoutEntry>, RDFProofsError> {
+    message_layout(&get_vc_from_ntriples(document, proof)?)
+}
+
+// encoding of hidden term as a message, which is `TermEncoding::ScaledDecimal` if it occurs in VC
+// whose cryptosuite scales decimals, and `TermEncoding::Termwise` otherwise
+pub(crate) fn get_hidden_term_encoding(
+    term: &NamedOrBlankNode,
+    equivs: &BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>>,
+    term_encodings: &[TermEncoding],
+) -> TermEncoding {
+    let is_scaled = equivs
+        .get(&OrderedNamedOrBlankNode(term.clone()))
+        .into_iter()
+        .flatten()
+        .any(|(vc_index, _)| term_encodings.get(*vc_index) == Some(&TermEncoding::ScaledDecimal));
+    if is_scaled {
+        TermEncoding::ScaledDecimal
+    } else {
+        TermEncoding::Termwise
+    }
+}
+
+// encoding of public inputs of predicate, which scales decimals if any of its private inputs
+// is scaled, so that public decimals are compared with the signed messages of private ones
+pub(crate) fn get_predicate_term_encoding(
+    private: &[(String, NamedOrBlankNode)],
+    equivs: &BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>>,
+    term_encodings: &[TermEncoding],
+) -> TermEncoding {
+    if private.iter().any(|(_, term)| {
+        get_hidden_term_encoding(term, equivs, term_encodings) == TermEncoding::ScaledDecimal
+    }) {
+        TermEncoding::ScaledDecimal
+    } else {
+        TermEncoding::Termwise
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{message_layout_string, MessageRole, SecretSlot, TermEncoding, TermPosition};
+    use crate::{
+        common::{get_graph_from_ntriples, Fr},
+        signature::{hash, transform},
+    };
+    use oxrdf::{NamedNode, Term};
+
+    const DOCUMENT: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    "#;
+    const PROOF: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+
+    #[test]
+    fn message_layout_success() {
+        let layout = message_layout_string(DOCUMENT, PROOF).unwrap();
+        assert_eq!(layout.len(), 1 + 3 * 3 + 1 + 3 * 3);
+        assert_eq!(layout[0].role, MessageRole::Secret { bound: false });
+        assert_eq!(layout[10].role, MessageRole::Delimiter);
+        assert_eq!(
+            layout[11].role,
+            MessageRole::ProofConfig {
+                triple: 0,
+                position: TermPosition::Subject
+            }
+        );
+        for (i, entry) in layout.iter().enumerate() {
+            assert_eq!(entry.index, i);
+        }
+
+        // the layout agrees with the messages hashed for signing
+        let document = transform(&get_graph_from_ntriples(DOCUMENT).unwrap()).unwrap();
+        let proof = transform(&get_graph_from_ntriples(PROOF).unwrap()).unwrap();
+        let messages = hash(
+            None,
+            &document,
+            &proof,
+            SecretSlot::First,
+            TermEncoding::Termwise,
+        )
+        .unwrap();
+        assert_eq!(messages.len(), layout.len());
+        assert_eq!(
+            layout[1..10]
+                .iter()
+                .map(|entry| entry.term.clone().unwrap())
+                .collect::<Vec<_>>(),
+            document
+        );
+        assert_eq!(
+            layout[1].term,
+            Some(Term::NamedNode(NamedNode::new("did:example:john").unwrap()))
+        );
+    }
+
+    #[test]
+    fn message_layout_with_secret_last_success() {
+        let proof = PROOF.replace("signature-2023", "signature-2024");
+        let layout = message_layout_string(DOCUMENT, &proof).unwrap();
+        assert_eq!(layout.len(), 3 * 3 + 1 + 3 * 3 + 1);
+        assert_eq!(
+            layout[0].role,
+            MessageRole::Document {
+                triple: 0,
+                position: TermPosition::Subject
+            }
+        );
+        assert_eq!(layout[9].role, MessageRole::Delimiter);
+        assert_eq!(layout[19].role, MessageRole::Secret { bound: false });
+        for (i, entry) in layout.iter().enumerate() {
+            assert_eq!(entry.index, i);
+        }
+
+        let document = transform(&get_graph_from_ntriples(DOCUMENT).unwrap()).unwrap();
+        let proof = transform(&get_graph_from_ntriples(&proof).unwrap()).unwrap();
+        let messages = hash(
+            None,
+            &document,
+            &proof,
+            SecretSlot::Last,
+            TermEncoding::Termwise,
+        )
+        .unwrap();
+        assert_eq!(messages.len(), layout.len());
+        assert_eq!(messages[19], Fr::from(1));
+    }
+
+    #[test]
+    fn message_layout_with_split_iri_success() {
+        let proof = PROOF.replace("signature-2023", "split-iri-signature-2024");
+        let layout = message_layout_string(DOCUMENT, &proof).unwrap();
+        assert_eq!(layout.len(), 2 * 3 * 3 + 1 + 2 * 3 * 3 + 1);
+        assert_eq!(
+            layout[0].term,
+            Some(Term::NamedNode(NamedNode::new("did:example:john").unwrap()))
+        );
+        assert_eq!(
+            layout[1].role,
+            MessageRole::Namespace {
+                namespace: "did:example:".to_string()
+            }
+        );
+        assert_eq!(
+            layout[3].role,
+            MessageRole::Namespace {
+                namespace: "http://schema.org/".to_string()
+            }
+        );
+        // literals have empty namespaces
+        assert_eq!(
+            layout[5].role,
+            MessageRole::Namespace {
+                namespace: "".to_string()
+            }
+        );
+        assert_eq!(layout[18].role, MessageRole::Delimiter);
+        assert_eq!(layout[37].role, MessageRole::Secret { bound: false });
+        for (i, entry) in layout.iter().enumerate() {
+            assert_eq!(entry.index, i);
+        }
+
+        let document = transform(&get_graph_from_ntriples(DOCUMENT).unwrap()).unwrap();
+        let proof = transform(&get_graph_from_ntriples(&proof).unwrap()).unwrap();
+        let messages = hash(
+            None,
+            &document,
+            &proof,
+            SecretSlot::Last,
+            TermEncoding::SplitIri,
+        )
+        .unwrap();
+        assert_eq!(messages.len(), layout.len());
+        assert_eq!(messages[37], Fr::from(1));
+    }
+}
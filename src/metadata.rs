@@ -0,0 +1,244 @@
+use crate::{
+    common::{get_dataset_from_nquads, BlankNodeLabeler},
+    context::{
+        CHALLENGE, CREATED, CREDENTIAL_COUNT, CREDENTIAL_SUMMARY, CRYPTOSUITE,
+        DATA_INTEGRITY_PROOF, DOMAIN, POLICY_DIGEST, PROOF_PURPOSE, VERIFICATION_METHOD,
+    },
+    error::RDFProofsError,
+    ordered_triple::OrderedVerifiableCredentialGraphViews,
+    vc::VerifiablePresentation,
+};
+use oxrdf::{
+    dataset::GraphView,
+    vocab::{rdf::TYPE, xsd},
+    BlankNode, Dataset, LiteralRef, NamedNode, NamedNodeRef, QuadRef, TermRef,
+};
+use std::collections::BTreeMap;
+
+/// properties of a DataIntegrityProof, extracted without verification
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProofMetadata {
+    pub cryptosuite: Option<String>,
+    pub created: Option<String>,
+    pub challenge: Option<String>,
+    pub domain: Option<String>,
+    pub policy_digest: Option<String>,
+    pub proof_purpose: Option<NamedNode>,
+    pub verification_method: Option<NamedNode>,
+}
+
+/// verification method and cryptosuite relied on by the disclosed VCs in VP,
+/// with the number of VCs using them
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CredentialSummary {
+    pub verification_method: NamedNode,
+    pub cryptosuite: String,
+    pub count: usize,
+}
+
+/// proof metadata of VP and its disclosed VCs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VpMetadata {
+    pub proof: ProofMetadata,
+    pub credentials: Vec<ProofMetadata>,
+}
+
+/// extract proof metadata from VP without cryptographic verification,
+/// e.g., for routing or inspecting VPs before `verify_proof`
+pub fn vp_metadata(vp: &Dataset) -> Result<VpMetadata, RDFProofsError> {
+    let vp: VerifiablePresentation = vp.try_into()?;
+
+    let proof = get_proof_metadata(&vp.proof)?;
+    let credentials = vp
+        .disclosed_vcs
+        .values()
+        .map(|vc| get_proof_metadata(&vc.proof))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(VpMetadata { proof, credentials })
+}
+
+pub fn vp_metadata_string(vp: &str) -> Result<VpMetadata, RDFProofsError> {
+    let vp = get_dataset_from_nquads(vp)?;
+    vp_metadata(&vp)
+}
+
+pub(crate) fn get_proof_metadata(proof: &GraphView) -> Result<ProofMetadata, RDFProofsError> {
+    let proof_subject = proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+
+    // terms redacted by holder are given as blank nodes
+    let get_literal = |predicate: NamedNodeRef| match proof
+        .object_for_subject_predicate(proof_subject, predicate)
+    {
+        Some(TermRef::Literal(v)) => Ok(Some(v.value().to_string())),
+        Some(TermRef::BlankNode(_)) => Ok(None),
+        Some(_) => Err(RDFProofsError::MissingProofConfigLiteral(predicate.into())),
+        None => Ok(None),
+    };
+    let get_named_node = |predicate: NamedNodeRef| match proof
+        .object_for_subject_predicate(proof_subject, predicate)
+    {
+        Some(TermRef::NamedNode(n)) => Ok(Some(n.into_owned())),
+        Some(TermRef::BlankNode(_)) => Ok(None),
+        Some(_) => Err(RDFProofsError::InvalidProofConfiguration),
+        None => Ok(None),
+    };
+
+    Ok(ProofMetadata {
+        cryptosuite: get_literal(CRYPTOSUITE)?,
+        created: get_literal(CREATED)?,
+        challenge: get_literal(CHALLENGE)?,
+        domain: get_literal(DOMAIN)?,
+        policy_digest: get_literal(POLICY_DIGEST)?,
+        proof_purpose: get_named_node(PROOF_PURPOSE)?,
+        verification_method: get_named_node(VERIFICATION_METHOD)?,
+    })
+}
+
+/// extract the summary of verification methods and cryptosuites the VP relies on
+/// from VP proof without cryptographic verification;
+/// note that it is only guaranteed by `verify_proof` to be consistent with the disclosed VCs,
+/// and VPs without summary result in no entries
+pub fn vp_credential_summary(vp: &Dataset) -> Result<Vec<CredentialSummary>, RDFProofsError> {
+    let vp: VerifiablePresentation = vp.try_into()?;
+    parse_credential_summary(&vp.proof)
+}
+
+pub fn vp_credential_summary_string(vp: &str) -> Result<Vec<CredentialSummary>, RDFProofsError> {
+    let vp = get_dataset_from_nquads(vp)?;
+    vp_credential_summary(&vp)
+}
+
+// aggregate pairs of verification method and cryptosuite, sorted for comparison
+pub(crate) fn get_credential_summary(
+    proofs: impl IntoIterator<Item = (NamedNode, String)>,
+) -> Vec<CredentialSummary> {
+    let mut counts = BTreeMap::new();
+    for proof in proofs {
+        *counts.entry(proof).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(
+            |((verification_method, cryptosuite), count)| CredentialSummary {
+                verification_method,
+                cryptosuite,
+                count,
+            },
+        )
+        .collect()
+}
+
+// add the summary under VP proof node
+pub(crate) fn insert_credential_summary(
+    vp: &mut Dataset,
+    vp_proof_id: &BlankNode,
+    vp_proof_graph_id: &BlankNode,
+    summary: &[CredentialSummary],
+    labeler: &mut BlankNodeLabeler,
+) {
+    for CredentialSummary {
+        verification_method,
+        cryptosuite,
+        count,
+    } in summary
+    {
+        let summary_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            vp_proof_id,
+            CREDENTIAL_SUMMARY,
+            &summary_id,
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &summary_id,
+            VERIFICATION_METHOD,
+            verification_method,
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &summary_id,
+            CRYPTOSUITE,
+            LiteralRef::new_simple_literal(cryptosuite),
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(
+            &summary_id,
+            CREDENTIAL_COUNT,
+            LiteralRef::new_typed_literal(&count.to_string(), xsd::INTEGER),
+            vp_proof_graph_id,
+        ));
+    }
+}
+
+// check that the summary in VP proof, if any, is exactly that derived from the disclosed VCs;
+// VPs without the summary are accepted for backward compatibility
+pub(crate) fn validate_credential_summary(
+    vp_proof: &GraphView,
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+) -> Result<(), RDFProofsError> {
+    let given = parse_credential_summary(vp_proof)?;
+    if given.is_empty() {
+        return Ok(());
+    }
+    let expected = get_credential_summary(
+        disclosed_vcs
+            .values()
+            .map(|vc| match get_proof_metadata(&vc.proof)? {
+                ProofMetadata {
+                    verification_method: Some(verification_method),
+                    cryptosuite: Some(cryptosuite),
+                    ..
+                } => Ok((verification_method, cryptosuite)),
+                _ => Err(RDFProofsError::InvalidCredentialSummary),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    );
+    if expected == given {
+        Ok(())
+    } else {
+        Err(RDFProofsError::InvalidCredentialSummary)
+    }
+}
+
+fn parse_credential_summary(
+    vp_proof: &GraphView,
+) -> Result<Vec<CredentialSummary>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let mut summary = vp_proof
+        .objects_for_subject_predicate(proof_subject, CREDENTIAL_SUMMARY)
+        .map(|entry| {
+            let TermRef::BlankNode(entry) = entry else {
+                return Err(RDFProofsError::InvalidCredentialSummary);
+            };
+            let Some(TermRef::NamedNode(verification_method)) =
+                vp_proof.object_for_subject_predicate(entry, VERIFICATION_METHOD)
+            else {
+                return Err(RDFProofsError::InvalidCredentialSummary);
+            };
+            let Some(TermRef::Literal(cryptosuite)) =
+                vp_proof.object_for_subject_predicate(entry, CRYPTOSUITE)
+            else {
+                return Err(RDFProofsError::InvalidCredentialSummary);
+            };
+            let count = match vp_proof.object_for_subject_predicate(entry, CREDENTIAL_COUNT) {
+                Some(TermRef::Literal(v)) if v.datatype() == xsd::INTEGER => v
+                    .value()
+                    .parse()
+                    .map_err(|_| RDFProofsError::InvalidCredentialSummary)?,
+                _ => return Err(RDFProofsError::InvalidCredentialSummary),
+            };
+            Ok(CredentialSummary {
+                verification_method: verification_method.into_owned(),
+                cryptosuite: cryptosuite.value().to_string(),
+                count,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    summary.sort();
+    Ok(summary)
+}
@@ -0,0 +1,26 @@
+use std::time::Instant;
+
+/// callbacks invoked from `derive_proof` and `verify_proof` with timings and sizes,
+/// e.g., to export them as Prometheus metrics; callbacks not overridden are no-ops
+pub trait Metrics: Send + Sync {
+    /// time spent canonicalizing the original VCs in `derive_proof`
+    fn canonicalization_ms(&self, _ms: u64) {}
+    /// number of statements built by `derive_proof`
+    fn statements_built(&self, _count: usize) {}
+    /// size of the multibase-encoded proof value derived by `derive_proof`
+    fn proof_bytes(&self, _bytes: usize) {}
+    /// time spent verifying VP in `verify_proof`, reported only on success
+    fn verify_ms(&self, _ms: u64) {}
+    /// number of statements verified by `verify_proof`
+    fn statement_count(&self, _count: usize) {}
+}
+
+// start timer only if metrics are collected
+pub(crate) fn start_timer(metrics: Option<&dyn Metrics>) -> Option<Instant> {
+    metrics.map(|_| Instant::now())
+}
+
+// milliseconds elapsed since the timer started
+pub(crate) fn elapsed_ms(started: Instant) -> u64 {
+    started.elapsed().as_millis().try_into().unwrap_or(u64::MAX)
+}
@@ -0,0 +1,359 @@
+use crate::{
+    common::{
+        ark_to_base64url, get_dataset_from_nquads, get_graph_from_ntriples, get_vc_from_ntriples,
+        multibase_to_ark, BBSPlusSignature, ProofWithIndexMap,
+    },
+    context::{PROOF_VALUE, PUBLIC_KEY_MULTIBASE, SECRET_KEY_MULTIBASE},
+    error::RDFProofsError,
+    key_gen::{
+        decode_public_key, decode_secret_key, encode_public_key, encode_secret_key, KeyEncoding,
+    },
+    proof_layout::diagnose_undecodable_proof,
+    vc::{VerifiableCredential, VerifiablePresentation},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use multibase::Base;
+use oxrdf::{Dataset, Graph, Literal, Quad, Term, TermRef, Triple};
+
+/// version of serialized artifacts, i.e., keys, signatures, proofs, and other arkworks objects
+/// in multibase, which is prepended to the serialized bytes;
+/// artifacts without version byte are read as the legacy format (version `0`)
+pub const SERIALIZATION_VERSION: u8 = 1;
+
+// first bytes of CBOR maps, with which legacy proof values start
+const CBOR_MAP_HEADERS: std::ops::RangeInclusive<u8> = 0xa0..=0xbf;
+
+pub(crate) fn serialize_versioned<A: CanonicalSerialize>(
+    ark: &A,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = vec![SERIALIZATION_VERSION];
+    ark.serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+// read arkworks object with or without version byte;
+// legacy bytes that happen to start with the version byte are still read as legacy
+// since the rest of them cannot be consumed exactly by the same type
+pub(crate) fn deserialize_versioned<A: CanonicalDeserialize>(
+    bytes: &[u8],
+) -> Result<A, SerializationError> {
+    if let Some((&SERIALIZATION_VERSION, mut rest)) = bytes.split_first() {
+        if let Ok(ark) = A::deserialize_compressed(&mut rest) {
+            if rest.is_empty() {
+                return Ok(ark);
+            }
+        }
+    }
+    A::deserialize_compressed(bytes)
+}
+
+pub(crate) fn encode_proof_value(
+    proof_with_index_map: &ProofWithIndexMap,
+) -> Result<String, RDFProofsError> {
+    let mut bytes = vec![SERIALIZATION_VERSION];
+    bytes.extend(serde_cbor::to_vec(proof_with_index_map)?);
+    Ok(multibase::encode(Base::Base64Url, bytes))
+}
+
+pub(crate) fn decode_proof_value(proof_value: &str) -> Result<ProofWithIndexMap, RDFProofsError> {
+    let (_, bytes) = multibase::decode(proof_value)?;
+    let decode = |cbor: &[u8]| {
+        serde_cbor::from_slice(cbor).map_err(|e| diagnose_undecodable_proof(cbor, e.into()))
+    };
+    match bytes.split_first() {
+        Some((&SERIALIZATION_VERSION, cbor)) => decode(cbor),
+        Some((header, _)) if CBOR_MAP_HEADERS.contains(header) => decode(&bytes),
+        Some((version, _)) => Err(RDFProofsError::UnsupportedSerializationVersion(*version)),
+        None => Err(RDFProofsError::VCWithInvalidProofValue),
+    }
+}
+
+/// re-emit serialized proof (`proofValue` of VP) in the current format;
+/// the proof layout inside is kept as is since statement tags of legacy layout
+/// cannot be recovered without the statements
+pub fn migrate_proof_value(proof_value: &str) -> Result<String, RDFProofsError> {
+    encode_proof_value(&decode_proof_value(proof_value)?)
+}
+
+/// re-emit multibase-encoded arkworks object, e.g., commitment or SNARK verifying key,
+/// in the current format
+pub fn migrate_ark<A: CanonicalSerialize + CanonicalDeserialize>(
+    s: &str,
+) -> Result<String, RDFProofsError> {
+    ark_to_base64url(&multibase_to_ark::<A>(s)?)
+}
+
+// Multikey is an external format, so that only arkworks-encoded keys are re-emitted
+fn migrate_key_literal(triple: &Triple) -> Result<Triple, RDFProofsError> {
+    let Term::Literal(v) = &triple.object else {
+        return Ok(triple.clone());
+    };
+    if v.value().starts_with(Base::Base58Btc.code()) {
+        return Ok(triple.clone());
+    }
+    let migrated = if triple.predicate == SECRET_KEY_MULTIBASE {
+        encode_secret_key(&decode_secret_key(v.value())?, KeyEncoding::Ark)?
+    } else {
+        encode_public_key(&decode_public_key(v.value())?, KeyEncoding::Ark)?
+    };
+    Ok(Triple::new(
+        triple.subject.clone(),
+        triple.predicate.clone(),
+        Literal::new_typed_literal(migrated, v.datatype()),
+    ))
+}
+
+/// re-emit keys in key graph in the current format
+pub fn migrate_key_graph(key_graph: &Graph) -> Result<Graph, RDFProofsError> {
+    key_graph
+        .iter()
+        .map(|t| {
+            let triple = t.into_owned();
+            if t.predicate == SECRET_KEY_MULTIBASE || t.predicate == PUBLIC_KEY_MULTIBASE {
+                migrate_key_literal(&triple)
+            } else {
+                Ok(triple)
+            }
+        })
+        .collect()
+}
+
+pub fn migrate_key_graph_string(key_graph: &str) -> Result<String, RDFProofsError> {
+    let migrated = migrate_key_graph(&get_graph_from_ntriples(key_graph)?)?;
+    Ok(migrated.iter().map(|t| format!("{} .\n", t)).collect())
+}
+
+/// re-emit signature (`proofValue` of VC) in the current format,
+/// which is not covered by the signature itself
+pub fn migrate_vc(vc: &VerifiableCredential) -> Result<VerifiableCredential, RDFProofsError> {
+    let proof = vc
+        .proof
+        .iter()
+        .map(|t| match t.object {
+            TermRef::Literal(v) if t.predicate == PROOF_VALUE => Ok(Triple::new(
+                t.subject.into_owned(),
+                t.predicate.into_owned(),
+                Literal::new_typed_literal(
+                    migrate_ark::<BBSPlusSignature>(v.value())?,
+                    v.datatype(),
+                ),
+            )),
+            _ => Ok(t.into_owned()),
+        })
+        .collect::<Result<Graph, RDFProofsError>>()?;
+    Ok(VerifiableCredential::new(vc.document.clone(), proof))
+}
+
+/// returns migrated proof of VC
+pub fn migrate_vc_string(document: &str, proof: &str) -> Result<String, RDFProofsError> {
+    let migrated = migrate_vc(&get_vc_from_ntriples(document, proof)?)?;
+    Ok(migrated
+        .proof
+        .iter()
+        .map(|t| format!("{} .\n", t))
+        .collect())
+}
+
+/// re-emit serialized proof in VP in the current format;
+/// other artifacts in VP, e.g., commitments and ciphertexts, are kept as is
+/// since they are covered by the proof
+pub fn migrate_vp(vp: &Dataset) -> Result<Dataset, RDFProofsError> {
+    let proof_graph_name = VerifiablePresentation::try_from(vp)?
+        .proof_graph_name
+        .into_owned();
+    vp.iter()
+        .map(|q| match q.object {
+            TermRef::Literal(v)
+                if q.predicate == PROOF_VALUE && q.graph_name == proof_graph_name.as_ref() =>
+            {
+                Ok(Quad::new(
+                    q.subject.into_owned(),
+                    q.predicate.into_owned(),
+                    Literal::new_typed_literal(migrate_proof_value(v.value())?, v.datatype()),
+                    proof_graph_name.clone(),
+                ))
+            }
+            _ => Ok(q.into_owned()),
+        })
+        .collect()
+}
+
+pub fn migrate_vp_string(vp: &str) -> Result<String, RDFProofsError> {
+    let migrated = migrate_vp(&get_dataset_from_nquads(vp)?)?;
+    Ok(migrated.iter().map(|q| format!("{} .\n", q)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_proof_value, deserialize_versioned, migrate_ark, migrate_key_graph_string,
+        migrate_vc_string, serialize_versioned, SERIALIZATION_VERSION,
+    };
+    use crate::{
+        common::{
+            get_graph_from_ntriples, get_vc_from_ntriples, multibase_to_ark, BBSPlusPublicKey,
+            BBSPlusSecretKey, BBSPlusSignature,
+        },
+        curve::G1Affine,
+        error::RDFProofsError,
+        key_gen::generate_keypair,
+        sign_string, verify_string, KeyGraph,
+    };
+    use ark_ec::AffineRepr;
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use multibase::Base;
+    use oxrdf::NamedNodeRef;
+    use std::collections::BTreeMap;
+
+    const DOCUMENT: &str = r#"
+<did:example:john> <http://schema.org/name> "John Smith" .
+<did:example:john> <http://example.org/vocab/isPatientOf> _:b0 .
+_:b0 <http://example.org/vocab/lotNumber> "0000001" .
+"#;
+    const PROOF_OPTIONS: &str = r#"
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+_:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-signature-2023" .
+_:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+_:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+_:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+"#;
+
+    fn legacy_multibase<A: CanonicalSerialize>(ark: &A) -> String {
+        let mut bytes = vec![];
+        ark.serialize_compressed(&mut bytes).unwrap();
+        multibase::encode(Base::Base64Url, bytes)
+    }
+
+    #[test]
+    fn versioned_ark_round_trip() {
+        let point = G1Affine::generator();
+        let bytes = serialize_versioned(&point).unwrap();
+        assert_eq!(bytes[0], SERIALIZATION_VERSION);
+        assert_eq!(deserialize_versioned::<G1Affine>(&bytes).unwrap(), point);
+
+        // legacy format is still readable, and migrated into the current one
+        let legacy = legacy_multibase(&point);
+        let migrated = migrate_ark::<G1Affine>(&legacy).unwrap();
+        assert_ne!(migrated, legacy);
+        assert_eq!(
+            multibase::decode(&migrated).unwrap().1,
+            serialize_versioned(&point).unwrap()
+        );
+        assert_eq!(migrate_ark::<G1Affine>(&migrated).unwrap(), migrated);
+
+        // proof value with unknown version
+        assert!(matches!(
+            decode_proof_value(&multibase::encode(Base::Base64Url, [0x02, 0xa0])),
+            Err(RDFProofsError::UnsupportedSerializationVersion(2))
+        ));
+    }
+
+    #[test]
+    fn migrate_legacy_key_graph_and_vc() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let keypair = generate_keypair(&mut rng).unwrap();
+        let legacy_key_graph = format!(
+            r#"
+<did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+<did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "{}" .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "{}" .
+"#,
+            legacy_multibase(&keypair.secret_key),
+            legacy_multibase(&keypair.public_key)
+        );
+
+        let key_graph = migrate_key_graph_string(&legacy_key_graph).unwrap();
+        println!("migrated key graph:\n{}", key_graph);
+        assert_ne!(key_graph, legacy_key_graph);
+        let (secret_key, public_key): (BBSPlusSecretKey, BBSPlusPublicKey) =
+            KeyGraph::from(get_graph_from_ntriples(&key_graph).unwrap())
+                .get_keypair(NamedNodeRef::new_unchecked(
+                    "did:example:issuer0#bls12_381-g2-pub001",
+                ))
+                .unwrap();
+        assert_eq!(secret_key, keypair.secret_key);
+        assert_eq!(public_key, keypair.public_key);
+        assert_eq!(migrate_key_graph_string(&key_graph).unwrap(), key_graph);
+
+        // VC with legacy signature is verified before and after migration
+        let proof = sign_string(
+            &mut rng,
+            DOCUMENT,
+            PROOF_OPTIONS,
+            &legacy_key_graph,
+            None,
+            None,
+        )
+        .unwrap();
+        let proof_value = get_vc_from_ntriples(DOCUMENT, &proof)
+            .unwrap()
+            .get_proof_value()
+            .unwrap();
+        let signature: BBSPlusSignature = multibase_to_ark(&proof_value).unwrap();
+        let legacy_proof = proof.replace(&proof_value, &legacy_multibase(&signature));
+        let migrated_proof = migrate_vc_string(DOCUMENT, &legacy_proof).unwrap();
+        assert_ne!(migrated_proof, legacy_proof);
+        for key_graph in [&legacy_key_graph, &key_graph] {
+            assert!(verify_string(DOCUMENT, &legacy_proof, key_graph).is_ok());
+            assert!(verify_string(DOCUMENT, &migrated_proof, key_graph).is_ok());
+        }
+    }
+
+    #[test]
+    fn decode_undecodable_proof_value_with_header() {
+        let encode = |header: Vec<(&str, serde_cbor::Value)>| {
+            let mut bytes = vec![SERIALIZATION_VERSION];
+            bytes.extend(
+                serde_cbor::to_vec(&header.into_iter().collect::<BTreeMap<_, _>>()).unwrap(),
+            );
+            multibase::encode(Base::Base64Url, bytes)
+        };
+        let garbage = ("a", serde_cbor::Value::Text("garbage".to_string()));
+
+        // newer layout is reported with the generator instead of the CBOR error
+        assert!(matches!(
+            decode_proof_value(&encode(vec![
+                garbage.clone(),
+                ("v", serde_cbor::Value::Integer(2)),
+                ("g", serde_cbor::Value::Text("rdf-proofs/9.9.9".to_string())),
+            ])),
+            Err(RDFProofsError::ProofFromNewerVersion { generator, version: 2 })
+                if generator == "rdf-proofs/9.9.9"
+        ));
+        // unknown statements and features
+        assert!(matches!(
+            decode_proof_value(&encode(vec![
+                garbage.clone(),
+                ("v", serde_cbor::Value::Integer(1)),
+                (
+                    "t",
+                    serde_cbor::Value::Array(vec![serde_cbor::Value::Text(
+                        "range-proof".to_string()
+                    )])
+                ),
+            ])),
+            Err(RDFProofsError::UnsupportedStatementType { tag }) if tag == "range-proof"
+        ));
+        assert!(matches!(
+            decode_proof_value(&encode(vec![
+                garbage.clone(),
+                ("v", serde_cbor::Value::Integer(1)),
+                (
+                    "f",
+                    serde_cbor::Value::Array(vec![serde_cbor::Value::Text(
+                        "quantum".to_string()
+                    )])
+                ),
+            ])),
+            Err(RDFProofsError::UnsupportedProofFeature(feature)) if feature == "quantum"
+        ));
+        // otherwise the CBOR error is returned as is
+        assert!(matches!(
+            decode_proof_value(&encode(vec![garbage, ("v", serde_cbor::Value::Integer(1))])),
+            Err(RDFProofsError::CBORSerialization(_))
+        ));
+    }
+}
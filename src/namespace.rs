@@ -0,0 +1,182 @@
+use crate::{
+    common::{hash_byte_to_field, BBSPlusDefaultFieldHasher, BlankNodeLabeler, Fr},
+    context::{DATA_INTEGRITY_PROOF, LHS, RHS, SAME_NAMESPACE},
+    error::RDFProofsError,
+    message_layout::TermEncoding,
+    ordered_triple::OrderedNamedOrBlankNode,
+};
+use oxrdf::{
+    dataset::GraphView, vocab::rdf::TYPE, BlankNode, Dataset, NamedOrBlankNode, QuadRef, Term,
+    TermRef,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// IRI namespace up to and including the last of these separators,
+// e.g., `https://example.org/users/`, `http://schema.org/`, and `urn:uuid:`
+const NAMESPACE_SEPARATORS: [char; 3] = ['/', '#', ':'];
+
+// namespace of IRI hashed into the message following the term under `TermEncoding::SplitIri`,
+// which is empty for blank nodes and literals
+pub(crate) fn get_namespace(term: TermRef) -> &str {
+    match term {
+        TermRef::NamedNode(n) => {
+            let iri = n.as_str();
+            iri.rfind(&NAMESPACE_SEPARATORS[..])
+                .map_or("", |i| &iri[..=i])
+        }
+        _ => "",
+    }
+}
+
+pub(crate) fn hash_namespace_to_field(
+    term: TermRef,
+    hasher: &BBSPlusDefaultFieldHasher,
+) -> Result<Fr, RDFProofsError> {
+    hash_byte_to_field(get_namespace(term).as_bytes(), hasher)
+}
+
+// check that the hidden terms requested to be proven to share a namespace are distinct IRIs
+// in the same namespace, since non-IRI terms trivially share the empty namespace
+pub(crate) fn validate_same_namespaces(
+    same_namespaces: &[(BlankNode, BlankNode)],
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+) -> Result<(), RDFProofsError> {
+    let get_iri = |value: &BlankNode| match deanon_map.get(&NamedOrBlankNode::from(value.clone())) {
+        Some(Term::NamedNode(n)) => Ok(n.as_ref()),
+        _ => Err(RDFProofsError::InvalidNamespaceEquality),
+    };
+    for (lhs, rhs) in same_namespaces {
+        if lhs == rhs {
+            return Err(RDFProofsError::InvalidNamespaceEquality);
+        }
+        let (lhs_iri, rhs_iri) = (get_iri(lhs)?, get_iri(rhs)?);
+        if get_namespace(lhs_iri.into()) != get_namespace(rhs_iri.into()) {
+            return Err(RDFProofsError::UnprovableNamespaceEquality(format!(
+                "{} and {}",
+                lhs, rhs
+            )));
+        }
+    }
+    Ok(())
+}
+
+// add pairs of hidden IRIs proven to share a namespace to VP proof, i.e.,
+// `_:proof sameNamespace [ lhs _:a; rhs _:b ]`
+pub(crate) fn insert_same_namespaces(
+    vp: &mut Dataset,
+    vp_proof_id: &BlankNode,
+    vp_proof_graph_id: &BlankNode,
+    same_namespaces: &[(BlankNode, BlankNode)],
+    labeler: &mut BlankNodeLabeler,
+) {
+    for (lhs, rhs) in same_namespaces {
+        let equality_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            vp_proof_id,
+            SAME_NAMESPACE,
+            &equality_id,
+            vp_proof_graph_id,
+        ));
+        vp.insert(QuadRef::new(&equality_id, LHS, lhs, vp_proof_graph_id));
+        vp.insert(QuadRef::new(&equality_id, RHS, rhs, vp_proof_graph_id));
+    }
+}
+
+// get pairs of hidden IRIs proven to share a namespace from canonicalized VP proof
+pub(crate) fn get_same_namespaces(
+    vp_proof: &GraphView,
+) -> Result<Vec<(BlankNode, BlankNode)>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let mut same_namespaces = vp_proof
+        .objects_for_subject_predicate(proof_subject, SAME_NAMESPACE)
+        .map(|equality| {
+            let TermRef::BlankNode(equality) = equality else {
+                return Err(RDFProofsError::InvalidNamespaceEquality);
+            };
+            let (Some(TermRef::BlankNode(lhs)), Some(TermRef::BlankNode(rhs))) = (
+                vp_proof.object_for_subject_predicate(equality, LHS),
+                vp_proof.object_for_subject_predicate(equality, RHS),
+            ) else {
+                return Err(RDFProofsError::InvalidNamespaceEquality);
+            };
+            if lhs == rhs {
+                return Err(RDFProofsError::InvalidNamespaceEquality);
+            }
+            Ok((lhs.into_owned(), rhs.into_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // ordered by canonical labels so that prover and verifier build the same meta statements
+    same_namespaces.sort_by_key(|(lhs, rhs)| (lhs.as_str().to_string(), rhs.as_str().to_string()));
+    Ok(same_namespaces)
+}
+
+// equalities of the namespace messages following the hidden IRIs proven to share a namespace,
+// each of which must occur in some VC whose cryptosuite splits IRIs;
+// overlapping pairs are merged so that each message belongs to at most one equality
+pub(crate) fn get_namespace_equalities(
+    same_namespaces: &[(BlankNode, BlankNode)],
+    equivs: &BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>>,
+    term_encodings: &[TermEncoding],
+) -> Result<Vec<BTreeSet<(usize, usize)>>, RDFProofsError> {
+    let get_namespace_position = |value: &BlankNode| {
+        equivs
+            .get(&OrderedNamedOrBlankNode(value.clone().into()))
+            .into_iter()
+            .flatten()
+            .find(|(vc_index, _)| term_encodings.get(*vc_index) == Some(&TermEncoding::SplitIri))
+            .map(|(vc_index, term_index)| (*vc_index, term_index + 1))
+            .ok_or(RDFProofsError::InvalidNamespaceEquality)
+    };
+    let mut equalities: Vec<BTreeSet<(usize, usize)>> = vec![];
+    for (lhs, rhs) in same_namespaces {
+        let mut merged =
+            BTreeSet::from([get_namespace_position(lhs)?, get_namespace_position(rhs)?]);
+        equalities.retain(|equality| {
+            if equality.is_disjoint(&merged) {
+                return true;
+            }
+            merged.extend(equality);
+            false
+        });
+        equalities.push(merged);
+    }
+    Ok(equalities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_namespace;
+    use oxrdf::{BlankNode, Literal, NamedNode, TermRef};
+
+    #[test]
+    fn namespaces_of_terms() {
+        for (iri, namespace) in [
+            ("urn:uuid:f81d4fae-7dec-11d0-a765-00a0c91e6bf6", "urn:uuid:"),
+            (
+                "https://example.org/users/alice",
+                "https://example.org/users/",
+            ),
+            ("http://schema.org/name", "http://schema.org/"),
+            (
+                "https://w3id.org/security#proofValue",
+                "https://w3id.org/security#",
+            ),
+            ("did:example:john", "did:example:"),
+        ] {
+            let iri = NamedNode::new(iri).unwrap();
+            assert_eq!(get_namespace(TermRef::NamedNode(iri.as_ref())), namespace);
+        }
+        assert_eq!(
+            get_namespace(TermRef::Literal(
+                Literal::new_simple_literal("a/b").as_ref()
+            )),
+            ""
+        );
+        assert_eq!(
+            get_namespace(TermRef::BlankNode(BlankNode::default().as_ref())),
+            ""
+        );
+    }
+}
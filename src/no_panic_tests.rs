@@ -0,0 +1,188 @@
+// feed garbage into the public string-based entry points,
+// which must return errors instead of panicking when embedded in services
+use crate::{
+    blind_sign_string, blind_verify_string, commit_to_term_string, derive_proof_string,
+    generate_deanon_map_skeleton_string, import_blind_issuance_state_string, sign_string,
+    unblind_string, verify_attachment_digest_string, verify_blind_sign_request_string,
+    verify_proof_string, verify_secret_rotation_string, verify_string,
+    vp_credential_summary_string, vp_equivalence_classes_string, vp_metadata_string,
+    BlindSignRequestString, DeriveOptionsString, VcPairString, VerifyOptionsString,
+};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+const SECRET: &[u8] = b"SECRET";
+
+// inputs that are invalid for every entry point
+const GARBAGE: [&str; 6] = [
+    "garbage",
+    "\u{0}\u{1}\u{2}",
+    "<http://example.org/a> <http://example.org/b> .",
+    "\"unterminated literal",
+    "uAAAA",
+    "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz",
+];
+
+// call every entry point with `input` in each of its string arguments,
+// collecting the results of those expected to fail
+fn call_all(rng: &mut StdRng, input: &str) -> Vec<(&'static str, bool)> {
+    let vc_pairs = vec![VcPairString::new(input, input, input, input)];
+    let deanon_map = HashMap::from([(input.to_string(), input.to_string())]);
+    let predicates = vec![input.to_string()];
+    let previous_proofs = vec![input.to_string()];
+    let verifier_commitments = vec![input.to_string()];
+    let blind_sign_request = BlindSignRequestString {
+        commitment: input.to_string(),
+        blinding: input.to_string(),
+        pok_for_commitment: Some(input.to_string()),
+        audience: Some(input.to_string()),
+        expires_at: Some(input.to_string()),
+    };
+
+    vec![
+        (
+            "sign_string",
+            sign_string(rng, input, input, input, Some(SECRET), None).is_err(),
+        ),
+        ("verify_string", verify_string(input, input, input).is_err()),
+        (
+            "derive_proof_string",
+            derive_proof_string(
+                rng,
+                &vc_pairs,
+                &deanon_map,
+                input,
+                Some(input),
+                Some(input),
+                DeriveOptionsString {
+                    secret: Some(SECRET),
+                    blind_sign_request: Some(&blind_sign_request),
+                    with_ppid: Some(true),
+                    predicates: Some(&predicates),
+                    previous_proofs: Some(&previous_proofs),
+                    new_secret: Some(SECRET),
+                    policy: Some(input),
+                    ..Default::default()
+                },
+            )
+            .is_err(),
+        ),
+        (
+            "verify_proof_string",
+            verify_proof_string(
+                rng,
+                input,
+                input,
+                Some(input),
+                Some(input),
+                VerifyOptionsString {
+                    snark_verifying_keys: Some(&HashMap::from([(
+                        input.to_string(),
+                        input.to_string(),
+                    )])),
+                    verifier_commitments: Some(&verifier_commitments),
+                    policy: Some(input),
+                    ..Default::default()
+                },
+            )
+            .is_err(),
+        ),
+        (
+            "verify_secret_rotation_string",
+            verify_secret_rotation_string(rng, input, input, Some(input), Some(input)).is_err(),
+        ),
+        (
+            "generate_deanon_map_skeleton_string",
+            generate_deanon_map_skeleton_string(&vc_pairs).is_err(),
+        ),
+        (
+            "verify_blind_sign_request_string",
+            verify_blind_sign_request_string(
+                rng,
+                input,
+                input,
+                Some(input),
+                Some(input),
+                Some(input),
+            )
+            .is_err(),
+        ),
+        (
+            "blind_sign_string",
+            blind_sign_string(rng, input, input, input, input, None, None, None).is_err(),
+        ),
+        (
+            "unblind_string",
+            unblind_string(input, input, input).is_err(),
+        ),
+        (
+            "blind_verify_string",
+            blind_verify_string(SECRET, input, input, input).is_err(),
+        ),
+        (
+            "import_blind_issuance_state_string",
+            import_blind_issuance_state_string(input, SECRET).is_err(),
+        ),
+        (
+            "commit_to_term_string",
+            commit_to_term_string(rng, input).is_err(),
+        ),
+        (
+            "verify_attachment_digest_string",
+            verify_attachment_digest_string(input, input).is_err(),
+        ),
+        ("vp_metadata_string", vp_metadata_string(input).is_err()),
+        (
+            "vp_credential_summary_string",
+            vp_credential_summary_string(input).is_err(),
+        ),
+        (
+            "vp_equivalence_classes_string",
+            vp_equivalence_classes_string(input).is_err(),
+        ),
+    ]
+}
+
+#[test]
+fn garbage_inputs_return_errors() {
+    let mut rng = StdRng::seed_from_u64(0u64);
+    for input in GARBAGE {
+        for (name, is_err) in call_all(&mut rng, input) {
+            assert!(is_err, "{} accepted garbage input {:?}", name, input);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    // arbitrary inputs can happen to be valid, e.g., an empty graph,
+    // so only check that no entry point panics
+    #[test]
+    fn arbitrary_inputs_do_not_panic(input in ".*", seed in any::<u64>()) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        call_all(&mut rng, &input);
+    }
+
+    #[test]
+    fn arbitrary_ntriples_do_not_panic(
+        terms in prop::collection::vec(
+            prop_oneof![
+                "<[a-z:/#.]{0,16}>",
+                "_:[a-z0-9]{0,4}",
+                "\"[ -~]{0,8}\"(\\^\\^<[a-z:/#.]{0,16}>|@[a-z-]{0,4})?",
+                "[ -~]{0,4}",
+            ],
+            0..16,
+        ),
+        seed in any::<u64>(),
+    ) {
+        let input: String = terms
+            .chunks(3)
+            .map(|t| format!("{} .\n", t.join(" ")))
+            .collect();
+        let mut rng = StdRng::seed_from_u64(seed);
+        call_all(&mut rng, &input);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    clock::{clock_or_system, Clock},
+    common::get_graph_from_ntriples,
+    constants::CRYPTOSUITE_SIGN,
+    context::{
+        ASSERTION_METHOD, AUDIENCE, CREATED, CRYPTOSUITE, DATA_INTEGRITY_PROOF, EXPIRES, NONCE,
+        NONCE_TOKEN, PROOF_PURPOSE, PROOF_VALUE, VERIFICATION_METHOD,
+    },
+    error::RDFProofsError,
+    key_graph::{KeyGraph, KeyStore},
+    signature::{sign, verify_with_key_store},
+    vc::VerifiableCredential,
+};
+use ark_std::rand::RngCore;
+use chrono::{DateTime, Utc};
+use multibase::Base;
+use oxrdf::{
+    vocab::{rdf::TYPE, xsd},
+    BlankNode, Graph, LiteralRef, NamedNode, NamedNodeRef, TripleRef,
+};
+use serde::{Deserialize, Serialize};
+
+const NONCE_LENGTH: usize = 16;
+
+// verifier-signed token given to holder as VP challenge, carried as multibase-encoded CBOR
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct NonceToken {
+    #[serde(rename = "n")]
+    nonce: String,
+    #[serde(rename = "a", default)]
+    audience: Option<String>,
+    #[serde(rename = "e")]
+    expires_at: String,
+    #[serde(rename = "c")]
+    created: String,
+    #[serde(rename = "m")]
+    verification_method: String,
+    #[serde(rename = "p")]
+    proof_value: String,
+}
+
+impl NonceToken {
+    fn encode(&self) -> Result<String, RDFProofsError> {
+        Ok(multibase::encode(
+            Base::Base64Url,
+            serde_cbor::to_vec(self)?,
+        ))
+    }
+
+    fn decode(token: &str) -> Result<Self, RDFProofsError> {
+        let invalid = |e: &dyn std::fmt::Display| RDFProofsError::InvalidNonceToken(e.to_string());
+        let (base, bytes) = multibase::decode(token).map_err(|e| invalid(&e))?;
+        if base != Base::Base64Url {
+            return Err(invalid(&"token must be base64url-encoded"));
+        }
+        serde_cbor::from_slice(&bytes).map_err(|e| invalid(&e))
+    }
+}
+
+/// verifier's expectations on the challenge given as nonce token issued by `issue_nonce_token`,
+/// i.e., the token must be signed with the key of `verification_method`, be addressed to
+/// `audience`, and not be expired at the time of `clock`, which defaults to the system clock
+pub struct NonceTokenValidation {
+    verification_method: NamedNode,
+    audience: Option<String>,
+    clock: Option<Box<dyn Clock>>,
+}
+
+impl NonceTokenValidation {
+    pub fn new(verification_method: NamedNode, audience: Option<&str>) -> Self {
+        Self {
+            verification_method,
+            audience: audience.map(String::from),
+            clock: None,
+        }
+    }
+
+    /// `new` with the verification method IRI without angle brackets
+    pub fn new_string(
+        verification_method: &str,
+        audience: Option<&str>,
+    ) -> Result<Self, RDFProofsError> {
+        Ok(Self::new(NamedNode::new(verification_method)?, audience))
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+}
+
+/// issue nonce token to be used as VP challenge, which embeds a fresh nonce, `audience`,
+/// and `expires_at` given as xsd:dateTime, signed with the verifier's key of
+/// `verification_method` in `key_graph`; holders pass it to `derive_proof` verbatim
+pub fn issue_nonce_token<R: RngCore>(
+    rng: &mut R,
+    key_graph: &KeyGraph,
+    verification_method: NamedNodeRef,
+    audience: Option<&str>,
+    expires_at: &str,
+    clock: Option<&dyn Clock>,
+) -> Result<String, RDFProofsError> {
+    expires_at.parse::<DateTime<Utc>>()?;
+
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rng.fill_bytes(&mut nonce);
+    let mut token = NonceToken {
+        nonce: multibase::encode(Base::Base64Url, nonce),
+        audience: audience.map(String::from),
+        expires_at: expires_at.to_string(),
+        created: format!("{:?}", clock_or_system(clock).now()),
+        verification_method: verification_method.as_str().to_string(),
+        proof_value: String::new(),
+    };
+
+    let mut credential = get_nonce_token_credential(&token)?;
+    sign(rng, &mut credential, key_graph, None, clock)?;
+    token.proof_value = credential.get_proof_value()?;
+    token.encode()
+}
+
+/// `issue_nonce_token` with the key graph in N-Triples and
+/// the verification method IRI without angle brackets
+pub fn issue_nonce_token_string<R: RngCore>(
+    rng: &mut R,
+    key_graph: &str,
+    verification_method: &str,
+    audience: Option<&str>,
+    expires_at: &str,
+    clock: Option<&dyn Clock>,
+) -> Result<String, RDFProofsError> {
+    let key_graph = get_graph_from_ntriples(key_graph)?.into();
+    issue_nonce_token(
+        rng,
+        &key_graph,
+        NamedNode::new(verification_method)?.as_ref(),
+        audience,
+        expires_at,
+        clock,
+    )
+}
+
+// validate signature, audience, and expiry of nonce token given as challenge
+pub(crate) fn validate_nonce_token(
+    token: &str,
+    key_graph: &dyn KeyStore,
+    validation: &NonceTokenValidation,
+) -> Result<(), RDFProofsError> {
+    let token = NonceToken::decode(token)?;
+    if token.verification_method != validation.verification_method.as_str() {
+        return Err(RDFProofsError::InvalidNonceToken(
+            "token is not signed by the verifier".to_string(),
+        ));
+    }
+    if token.audience != validation.audience {
+        return Err(RDFProofsError::InvalidNonceToken(
+            "token is not addressed to the verifier".to_string(),
+        ));
+    }
+
+    let mut credential = get_nonce_token_credential(&token)?;
+    let proof_id = credential
+        .proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidProofConfiguration)?
+        .into_owned();
+    credential.proof.insert(TripleRef::new(
+        &proof_id,
+        PROOF_VALUE,
+        LiteralRef::new_simple_literal(&token.proof_value),
+    ));
+    verify_with_key_store(&credential, key_graph)
+        .map_err(|e| RDFProofsError::InvalidNonceToken(e.to_string()))?;
+
+    let clock = clock_or_system(validation.clock.as_deref());
+    if token.expires_at.parse::<DateTime<Utc>>()? <= clock.now() {
+        return Err(RDFProofsError::ExpiredNonceToken);
+    }
+    Ok(())
+}
+
+// document and proof options signed by the verifier, i.e.,
+// `_:t a NonceToken; nonce "..."; audience "..."; expires "..."^^xsd:dateTime`
+fn get_nonce_token_credential(token: &NonceToken) -> Result<VerifiableCredential, RDFProofsError> {
+    let token_id = BlankNode::default();
+    let mut document = Graph::default();
+    document.insert(TripleRef::new(&token_id, TYPE, NONCE_TOKEN));
+    document.insert(TripleRef::new(
+        &token_id,
+        NONCE,
+        LiteralRef::new_simple_literal(&token.nonce),
+    ));
+    if let Some(audience) = &token.audience {
+        document.insert(TripleRef::new(
+            &token_id,
+            AUDIENCE,
+            LiteralRef::new_simple_literal(audience),
+        ));
+    }
+    document.insert(TripleRef::new(
+        &token_id,
+        EXPIRES,
+        LiteralRef::new_typed_literal(&token.expires_at, xsd::DATE_TIME),
+    ));
+
+    let proof_id = BlankNode::default();
+    let mut proof_options = Graph::default();
+    proof_options.insert(TripleRef::new(&proof_id, TYPE, DATA_INTEGRITY_PROOF));
+    proof_options.insert(TripleRef::new(
+        &proof_id,
+        CRYPTOSUITE,
+        LiteralRef::new_simple_literal(CRYPTOSUITE_SIGN),
+    ));
+    proof_options.insert(TripleRef::new(&proof_id, PROOF_PURPOSE, ASSERTION_METHOD));
+    proof_options.insert(TripleRef::new(
+        &proof_id,
+        VERIFICATION_METHOD,
+        NamedNodeRef::new(&token.verification_method)?,
+    ));
+    proof_options.insert(TripleRef::new(
+        &proof_id,
+        CREATED,
+        LiteralRef::new_typed_literal(&token.created, xsd::DATE_TIME),
+    ));
+    Ok(VerifiableCredential::new(document, proof_options))
+}
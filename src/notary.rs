@@ -0,0 +1,273 @@
+use crate::{
+    clock::{clock_or_system, Clock},
+    common::{get_dataset_from_nquads, get_graph_from_ntriples},
+    context::{
+        CREDENTIAL_SUBJECT, DIGEST_MULTIBASE, ISSUANCE_DATE, ISSUER, VERIFIABLE_CREDENTIAL,
+        VERIFIABLE_CREDENTIAL_TYPE, VERIFIABLE_PRESENTATION_TYPE, WAS_DERIVED_FROM,
+    },
+    error::RDFProofsError,
+    key_graph::KeyGraph,
+    proof_value::remove_proof_value,
+    signature::sign,
+    vc::{VerifiableCredential, VerifiablePresentation},
+    verify_proof::{get_vp_digest, VerificationResult},
+};
+use ark_std::rand::RngCore;
+use oxrdf::{
+    vocab::{rdf::TYPE, xsd},
+    BlankNode, Dataset, Graph, LiteralRef, NamedNodeRef, TermRef, TripleRef,
+};
+
+/// digest of VP given in `VerificationResult::vp_digest`, i.e., multibase-encoded SHA-256 hash
+/// of its canonical N-Quads, which auditors can recompute from the VP referenced by
+/// attestation credentials
+pub fn vp_digest(vp_dataset: &Dataset) -> Result<String, RDFProofsError> {
+    let vp: VerifiablePresentation = vp_dataset.try_into()?;
+    // blank nodes are labeled without proof value as in `verify_proof`
+    let vp_without_proof_value = remove_proof_value(vp_dataset, &vp);
+    let c14n_map = rdf_canon::issue(&vp_without_proof_value)?;
+    get_vp_digest(vp_dataset, &c14n_map)
+}
+
+pub fn vp_digest_string(vp: &str) -> Result<String, RDFProofsError> {
+    vp_digest(&get_dataset_from_nquads(vp)?)
+}
+
+/// construct an unsigned attestation credential issued by `notary`, which re-asserts the
+/// disclosed data of the verified VP with provenance triples linking to the VP digest, i.e.,
+/// `<attestation_id> prov:wasDerivedFrom [ a VerifiablePresentation; digestMultibase "..." ]`,
+/// so that audits can chain; the original VCs are linked from the VP node instead of being
+/// typed as VCs, and their credential subjects become those of the attestation credential;
+/// its issuance date is taken from `clock`, which defaults to the system clock
+pub fn attestation_credential(
+    verification_result: &VerificationResult,
+    attestation_id: NamedNodeRef,
+    notary: NamedNodeRef,
+    proof_options: &Graph,
+    clock: Option<&dyn Clock>,
+) -> Result<VerifiableCredential, RDFProofsError> {
+    let VerificationResult {
+        vp_digest,
+        disclosed_documents,
+        ..
+    } = verification_result;
+    if disclosed_documents.is_empty() {
+        return Err(RDFProofsError::InvalidVP);
+    }
+
+    let mut document = Graph::new();
+    let vp = BlankNode::default();
+    for disclosed_document in disclosed_documents {
+        for triple in disclosed_document.iter() {
+            if triple.predicate == TYPE
+                && triple.object == TermRef::from(VERIFIABLE_CREDENTIAL_TYPE)
+            {
+                document.insert(TripleRef::new(
+                    vp.as_ref(),
+                    VERIFIABLE_CREDENTIAL,
+                    triple.subject,
+                ));
+                continue;
+            }
+            if triple.predicate == CREDENTIAL_SUBJECT {
+                document.insert(TripleRef::new(
+                    attestation_id,
+                    CREDENTIAL_SUBJECT,
+                    triple.object,
+                ));
+            }
+            document.insert(triple);
+        }
+    }
+
+    // provenance of the attestation
+    document.insert(TripleRef::new(
+        attestation_id,
+        TYPE,
+        VERIFIABLE_CREDENTIAL_TYPE,
+    ));
+    document.insert(TripleRef::new(attestation_id, ISSUER, notary));
+    document.insert(TripleRef::new(
+        attestation_id,
+        ISSUANCE_DATE,
+        LiteralRef::new_typed_literal(
+            &format!("{:?}", clock_or_system(clock).now()),
+            xsd::DATE_TIME,
+        ),
+    ));
+    document.insert(TripleRef::new(
+        attestation_id,
+        WAS_DERIVED_FROM,
+        vp.as_ref(),
+    ));
+    document.insert(TripleRef::new(
+        vp.as_ref(),
+        TYPE,
+        VERIFIABLE_PRESENTATION_TYPE,
+    ));
+    document.insert(TripleRef::new(
+        vp.as_ref(),
+        DIGEST_MULTIBASE,
+        LiteralRef::new_simple_literal(vp_digest),
+    ));
+
+    Ok(VerifiableCredential::new(document, proof_options.clone()))
+}
+
+/// construct the attestation credential by `attestation_credential` and sign it with
+/// the notary's key in `key_graph`
+pub fn notarize<R: RngCore>(
+    rng: &mut R,
+    verification_result: &VerificationResult,
+    attestation_id: NamedNodeRef,
+    notary: NamedNodeRef,
+    proof_options: &Graph,
+    key_graph: &KeyGraph,
+    clock: Option<&dyn Clock>,
+) -> Result<VerifiableCredential, RDFProofsError> {
+    let mut attestation = attestation_credential(
+        verification_result,
+        attestation_id,
+        notary,
+        proof_options,
+        clock,
+    )?;
+    sign(rng, &mut attestation, key_graph, None, clock)?;
+    Ok(attestation)
+}
+
+/// `notarize` with string-based input, returning the document and proof of the signed
+/// attestation credential in N-Triples format
+pub fn notarize_string<R: RngCore>(
+    rng: &mut R,
+    verification_result: &VerificationResult,
+    attestation_id: &str,
+    notary: &str,
+    proof_options: &str,
+    key_graph: &str,
+    clock: Option<&dyn Clock>,
+) -> Result<(String, String), RDFProofsError> {
+    let proof_options = get_graph_from_ntriples(proof_options)?;
+    let key_graph = get_graph_from_ntriples(key_graph)?.into();
+    let attestation = notarize(
+        rng,
+        verification_result,
+        NamedNodeRef::new(attestation_id)?,
+        NamedNodeRef::new(notary)?,
+        &proof_options,
+        &key_graph,
+        clock,
+    )?;
+    let to_ntriples =
+        |graph: &Graph| -> String { graph.iter().map(|t| format!("{} .\n", t)).collect() };
+    Ok((
+        to_ntriples(&attestation.document),
+        to_ntriples(&attestation.proof),
+    ))
+}
+
+#[cfg(all(test, feature = "prove"))]
+mod tests {
+    use super::{notarize_string, vp_digest_string};
+    use crate::{
+        derive_proof_string, sign_string, verify_proof_string, verify_string, DeriveOptionsString,
+        DisclosureProfile, VerifyOptionsString,
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    # notary
+    <did:example:issuer1> <https://w3id.org/security#verificationMethod> <did:example:issuer1#bls12_381-g2-pub001> .
+    <did:example:issuer1#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer1#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer1> .
+    <did:example:issuer1#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uQkpZn0SW42c2tlYa0IIFXyabAYHbwc0z3l_GvXQbWSg" .
+    <did:example:issuer1#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "usFM3CcvBMl_Dg5ixhQkHKGdqzY3GU9Uck6lj2i8vpbzLFOiZnjDNOpsItrkbNf2iCku-SZu5kO3nbLis-fuRhz_QwFcKw9IBpbPRPwXNQTX3zzcFsoNzs_wo8tkLQlcS" .
+    "#;
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const ATTESTATION_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer1#bls12_381-g2-pub001> .
+    "#;
+
+    #[test]
+    fn notarize_verified_vp_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let proof = sign_string(
+            &mut rng,
+            VC,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+        let profile = DisclosureProfile {
+            disclosed_predicates: vec!["http://schema.org/name".to_string()],
+            hide_identifiers: true,
+            ..DisclosureProfile::new("minimal")
+        };
+        let (vc_pair, deanon_map) = profile.apply_string(VC, &proof).unwrap();
+        let vp = derive_proof_string(
+            &mut rng,
+            &vec![vc_pair],
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        let verification_result = verify_proof_string(
+            &mut rng,
+            &vp,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            verification_result.vp_digest,
+            vp_digest_string(&vp).unwrap()
+        );
+
+        let (document, proof) = notarize_string(
+            &mut rng,
+            &verification_result,
+            "http://example.org/attestation/00",
+            "did:example:issuer1",
+            ATTESTATION_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+        )
+        .unwrap();
+        assert!(verify_string(&document, &proof, KEY_GRAPH).is_ok());
+        assert!(document.contains("\"John Smith\""));
+        assert!(!document.contains("birthDate"));
+        assert!(document.contains(&format!("\"{}\"", verification_result.vp_digest)));
+        assert!(document.contains(
+            "<http://example.org/attestation/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer1>"
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{
+    common::{
+        get_dataset_from_nquads, get_hasher, hash_term_to_field, hash_term_to_field_with_encoding,
+        multibase_to_ark, Fr, ProofWithIndexMap, VerifyingKey,
+    },
+    context::PROOF_VALUE,
+    curve::{Curve, G1Affine, G2Affine, SNARKJS_CURVE_NAME},
+    error::RDFProofsError,
+    message_layout::get_predicate_term_encoding,
+    migrate::decode_proof_value,
+    ordered_triple::OrderedNamedOrBlankNode,
+    predicate_graph::PredicateGraph,
+    proof_layout::PREDICATE_TAG,
+    vc::VerifiablePresentation,
+};
+use ark_ff::{One, PrimeField};
+use oxrdf::{Dataset, NamedNode, NamedOrBlankNode, Term};
+use proof_system::statement_proof::StatementProof;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// SNARK verifying key of predicate circuit in the `verification_key.json` format of snarkjs,
+/// which is consumed by the standard Groth16 on-chain verifiers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainVerifyingKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+/// standalone LegoGroth16 proof of predicate in the `proof.json` format of snarkjs,
+/// with `pi_d` committing to the private inputs, which on-chain verifiers must add to
+/// the linear combination of `IC` and public signals before pairing check
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainProof {
+    pub protocol: String,
+    pub curve: String,
+    pub pi_a: Vec<String>,
+    pub pi_b: Vec<Vec<String>>,
+    pub pi_c: Vec<String>,
+    pub pi_d: Vec<String>,
+}
+
+/// predicate proof extracted from VP with its public signals,
+/// i.e., circuit outputs (or `1` if not declared) followed by public inputs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainPredicateProof {
+    pub circuit: String,
+    pub proof: OnChainProof,
+    #[serde(rename = "publicSignals")]
+    pub public_signals: Vec<String>,
+}
+
+const PROTOCOL: &str = "groth16";
+
+/// export SNARK verifying key for on-chain verifiers, where `n_public` is the number of
+/// public signals of the predicate; the bases for private inputs, which are committed in `pi_d`,
+/// are omitted
+pub fn export_onchain_verifying_key(
+    snark_verifying_key: &VerifyingKey,
+    n_public: usize,
+) -> Result<OnChainVerifyingKey, RDFProofsError> {
+    let ic = snark_verifying_key.gamma_abc_g1.get(..=n_public).ok_or(
+        RDFProofsError::InvalidCircuitInput(format!(
+            "verifying key has fewer than {} public inputs",
+            n_public
+        )),
+    )?;
+    Ok(OnChainVerifyingKey {
+        protocol: PROTOCOL.to_string(),
+        curve: SNARKJS_CURVE_NAME.to_string(),
+        n_public,
+        vk_alpha_1: g1_to_strings(&snark_verifying_key.alpha_g1),
+        vk_beta_2: g2_to_strings(&snark_verifying_key.beta_g2),
+        vk_gamma_2: g2_to_strings(&snark_verifying_key.gamma_g2),
+        vk_delta_2: g2_to_strings(&snark_verifying_key.delta_g2),
+        ic: ic.iter().map(g1_to_strings).collect(),
+    })
+}
+
+pub fn export_onchain_verifying_key_string(
+    snark_verifying_key: &str,
+    n_public: usize,
+) -> Result<OnChainVerifyingKey, RDFProofsError> {
+    export_onchain_verifying_key(&multibase_to_ark(snark_verifying_key)?, n_public)
+}
+
+/// extract the LegoGroth16 proofs of predicates from VP, in the order of predicates in proof;
+/// note that they are not verified here, and that they are bound to the hidden terms in VCs
+/// only through the composite proof, so that on-chain verification alone does not guarantee
+/// that the private inputs come from the credentials
+pub fn extract_onchain_predicate_proofs(
+    vp: &Dataset,
+) -> Result<Vec<OnChainPredicateProof>, RDFProofsError> {
+    Ok(extract_predicate_snark_proofs(vp)?
+        .into_iter()
+        .map(|(circuit, proof, public_inputs)| OnChainPredicateProof {
+            circuit: circuit.into_string(),
+            proof: OnChainProof {
+                protocol: PROTOCOL.to_string(),
+                curve: SNARKJS_CURVE_NAME.to_string(),
+                pi_a: g1_to_strings(&proof.a),
+                pi_b: g2_to_strings(&proof.b),
+                pi_c: g1_to_strings(&proof.c),
+                pi_d: g1_to_strings(&proof.d),
+            },
+            public_signals: public_inputs.iter().map(field_to_string).collect(),
+        })
+        .collect())
+}
+
+pub fn extract_onchain_predicate_proofs_string(
+    vp: &str,
+) -> Result<Vec<OnChainPredicateProof>, RDFProofsError> {
+    extract_onchain_predicate_proofs(&get_dataset_from_nquads(vp)?)
+}
+
+// get LegoGroth16 proofs and public inputs of predicates from VP,
+// in the same order as the statements built by verifier
+pub(crate) fn extract_predicate_snark_proofs(
+    vp: &Dataset,
+) -> Result<Vec<(NamedNode, legogroth16::Proof<Curve>, Vec<Fr>)>, RDFProofsError> {
+    let hasher = get_hasher();
+
+    let decomposed_vp = VerifiablePresentation::try_from(vp)?;
+    let proof_value = decomposed_vp.get_proof_value()?;
+    let ProofWithIndexMap {
+        proof,
+        statement_tags,
+        ..
+    } = decode_proof_value(&proof_value)?;
+    // statement types are required to locate predicates in composite proof
+    if statement_tags.is_empty() {
+        return Err(RDFProofsError::UnsupportedProofLayoutVersion(0));
+    }
+    let predicate_indexes = statement_tags
+        .iter()
+        .enumerate()
+        .filter(|(_, tag)| tag.as_str() == PREDICATE_TAG)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    // predicates are ordered by their graph names in canonicalized VP as in verification
+    let vp_without_proof_value = Dataset::from_iter(vp.iter().filter(|q| {
+        !(q.predicate == PROOF_VALUE && q.graph_name == decomposed_vp.proof_graph_name)
+    }));
+    let c14n_map = rdf_canon::issue(&vp_without_proof_value)?;
+    let canonicalized_vp = rdf_canon::relabel(&vp_without_proof_value, &c14n_map)?;
+    let VerifiablePresentation {
+        predicates,
+        disclosed_vcs,
+        ..
+    } = (&canonicalized_vp).try_into()?;
+    if predicates.len() != predicate_indexes.len() {
+        return Err(RDFProofsError::MismatchedStatementTypes);
+    }
+
+    // VCs in which each term occurs, so that public inputs are encoded as in verification,
+    // where the positions of terms are not needed
+    let term_encodings = disclosed_vcs
+        .values()
+        .map(|vc| vc.term_encoding())
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut equivs: BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>> = BTreeMap::new();
+    for (vc_index, vc) in disclosed_vcs.values().enumerate() {
+        for triple in vc.document.iter() {
+            for term in [
+                Term::from(triple.subject.into_owned()),
+                triple.predicate.into_owned().into(),
+                triple.object.into_owned(),
+            ] {
+                let term: NamedOrBlankNode = match term {
+                    Term::NamedNode(n) => n.into(),
+                    Term::BlankNode(b) => b.into(),
+                    _ => continue,
+                };
+                equivs
+                    .entry(OrderedNamedOrBlankNode(term))
+                    .or_default()
+                    .push((vc_index, 0));
+            }
+        }
+    }
+
+    predicates
+        .values()
+        .zip(predicate_indexes)
+        .map(|(predicate_graph, index)| {
+            let PredicateGraph {
+                circuit,
+                private,
+                public,
+                output,
+            } = PredicateGraph::try_from(predicate_graph)?;
+            let public_term_encoding =
+                get_predicate_term_encoding(&private, &equivs, &term_encodings);
+
+            // circuit outputs precede public inputs as in verification
+            let mut public_inputs = match output {
+                Some(outputs) => outputs
+                    .iter()
+                    .map(|(_, value)| hash_term_to_field(value.into(), &hasher))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => vec![Fr::one()],
+            };
+            for (_, value) in public {
+                public_inputs.push(hash_term_to_field_with_encoding(
+                    (&value).into(),
+                    public_term_encoding,
+                    &hasher,
+                )?);
+            }
+
+            // only non-aggregated LegoGroth16 proofs can be verified standalone
+            match proof.statement_proofs.get(index) {
+                Some(StatementProof::R1CSLegoGroth16(p)) => {
+                    Ok((circuit, p.snark_proof.clone(), public_inputs))
+                }
+                _ => Err(RDFProofsError::NonExtractablePredicateProof(
+                    circuit.to_string(),
+                )),
+            }
+        })
+        .collect()
+}
+
+// field elements and coordinates are in decimal as in snarkjs
+fn field_to_string<F: PrimeField>(v: &F) -> String {
+    v.into_bigint().to_string()
+}
+
+fn g1_to_strings(p: &G1Affine) -> Vec<String> {
+    vec![
+        field_to_string(&p.x),
+        field_to_string(&p.y),
+        "1".to_string(),
+    ]
+}
+
+fn g2_to_strings(p: &G2Affine) -> Vec<Vec<String>> {
+    vec![
+        vec![field_to_string(&p.x.c0), field_to_string(&p.x.c1)],
+        vec![field_to_string(&p.y.c0), field_to_string(&p.y.c1)],
+        vec!["1".to_string(), "0".to_string()],
+    ]
+}
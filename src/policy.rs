@@ -0,0 +1,165 @@
+use crate::{
+    attachment::SHA2_256_MULTIHASH_PREFIX,
+    common::get_graph_from_ntriples,
+    context::{
+        ALLOWED_CRYPTOSUITE, ALLOWED_ISSUER, ALLOWED_VERIFICATION_METHOD, DATA_INTEGRITY_PROOF,
+        DENIED_CRYPTOSUITE, DENIED_ISSUER, DENIED_VERIFICATION_METHOD, ISSUER, UNIQUE_CREDENTIALS,
+        VERIFIABLE_CREDENTIAL_TYPE, VERIFICATION_METHOD,
+    },
+    error::RDFProofsError,
+    ordered_triple::OrderedVerifiableCredentialGraphViews,
+};
+use multibase::Base;
+use oxrdf::{vocab::rdf::TYPE, Graph, NamedNodeRef, TermRef};
+use sha2::{Digest, Sha256};
+
+/// digest of verifier's policy (requested attributes, predicates, limits, etc.) given as RDF graph;
+/// it is included in VP proof by prover and recomputed by verifier,
+/// so that the VP cannot be presented against a policy other than the one it was derived for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyDigest(String);
+
+impl PolicyDigest {
+    /// digest is calculated over the canonical N-Triples of the policy graph
+    /// so that it is independent of blank node labels and triple order
+    pub fn new(policy: &Graph) -> Result<Self, RDFProofsError> {
+        let serialized_canonical_form = rdf_canon::canonicalize_graph(policy)?;
+        let mut multihash = SHA2_256_MULTIHASH_PREFIX.to_vec();
+        multihash.extend(Sha256::digest(serialized_canonical_form.as_bytes()));
+        Ok(Self(multibase::encode(Base::Base58Btc, multihash)))
+    }
+
+    /// `PolicyDigest::new` with policy graph in N-Triples format
+    pub fn from_ntriples(policy: &str) -> Result<Self, RDFProofsError> {
+        Self::new(&get_graph_from_ntriples(policy)?)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// check the issuers, verification methods, and cryptosuites of disclosed VCs against
+/// the allow and deny lists in verifier's policy, e.g.,
+/// `_:p zkp:allowedIssuer <did:example:issuer0>; zkp:deniedCryptosuite "bbs-termwise-signature-2023"`,
+/// where values are rejected if denied or, when any is allowed, if not allowed;
+/// hidden issuers are never allowed
+pub(crate) fn validate_acceptance_rules(
+    policy: &Graph,
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+) -> Result<(), RDFProofsError> {
+    for vc in disclosed_vcs.values() {
+        let issuer = vc
+            .document
+            .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+            .and_then(|vc_id| vc.document.object_for_subject_predicate(vc_id, ISSUER));
+        let verification_method = vc
+            .proof
+            .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+            .and_then(|proof_id| {
+                vc.proof
+                    .object_for_subject_predicate(proof_id, VERIFICATION_METHOD)
+            });
+        let cryptosuite = vc.get_cryptosuite()?;
+
+        validate_value(
+            policy,
+            ALLOWED_ISSUER,
+            DENIED_ISSUER,
+            issuer.and_then(get_value),
+        )?;
+        validate_value(
+            policy,
+            ALLOWED_VERIFICATION_METHOD,
+            DENIED_VERIFICATION_METHOD,
+            verification_method.and_then(get_value),
+        )?;
+        validate_value(
+            policy,
+            ALLOWED_CRYPTOSUITE,
+            DENIED_CRYPTOSUITE,
+            Some(cryptosuite.as_str()),
+        )?;
+    }
+    Ok(())
+}
+
+/// whether verifier's policy requires disclosed VCs to be pairwise distinct credentials,
+/// i.e., `_:p zkp:uniqueCredentials true`, so that holders must prove their hidden IDs distinct
+pub(crate) fn requires_unique_credentials(policy: &Graph) -> bool {
+    policy
+        .triples_for_predicate(UNIQUE_CREDENTIALS)
+        .any(|t| matches!(t.object, TermRef::Literal(v) if v.value() == "true"))
+}
+
+// value of IRI or literal, or none for hidden terms
+fn get_value(term: TermRef) -> Option<&str> {
+    match term {
+        TermRef::NamedNode(n) => Some(n.as_str()),
+        TermRef::Literal(v) => Some(v.value()),
+        _ => None,
+    }
+}
+
+fn validate_value(
+    policy: &Graph,
+    allowed: NamedNodeRef,
+    denied: NamedNodeRef,
+    value: Option<&str>,
+) -> Result<(), RDFProofsError> {
+    let listed = |rule: NamedNodeRef| {
+        policy
+            .triples_for_predicate(rule)
+            .filter_map(|t| get_value(t.object))
+            .collect::<Vec<_>>()
+    };
+    let rejected = |rule: NamedNodeRef| RDFProofsError::RejectedByPolicy {
+        rule: rule.as_str().to_string(),
+        value: value.unwrap_or("(hidden)").to_string(),
+    };
+    if value.is_some_and(|value| listed(denied).contains(&value)) {
+        return Err(rejected(denied));
+    }
+    let allowed_values = listed(allowed);
+    if !allowed_values.is_empty() && !value.is_some_and(|value| allowed_values.contains(&value)) {
+        return Err(rejected(allowed));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolicyDigest;
+
+    const POLICY: &str = r#"
+_:b0 <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/name> .
+_:b0 <https://zkp-ld.org/security#predicate> _:b1 .
+_:b1 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+"#;
+
+    const POLICY_RELABELED: &str = r#"
+_:x <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+_:y <https://zkp-ld.org/security#predicate> _:x .
+_:y <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/name> .
+"#;
+
+    const POLICY_SUBSTITUTED: &str = r#"
+_:b0 <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/name> .
+_:b0 <https://zkp-ld.org/security#requestedAttribute> <http://schema.org/birthDate> .
+_:b0 <https://zkp-ld.org/security#predicate> _:b1 .
+_:b1 <https://zkp-ld.org/security#circuit> <https://zkp-ld.org/circuit/lessThanPrvPub> .
+"#;
+
+    #[test]
+    fn policy_digest_is_independent_of_labels_and_order() {
+        let digest = PolicyDigest::from_ntriples(POLICY).unwrap();
+        assert_eq!(
+            digest,
+            PolicyDigest::from_ntriples(POLICY_RELABELED).unwrap()
+        );
+        assert_ne!(
+            digest,
+            PolicyDigest::from_ntriples(POLICY_SUBSTITUTED).unwrap()
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    common::{encode_literal_to_field, get_graph_from_ntriples},
+    context::VERIFIABLE_CREDENTIAL_TYPE,
+    error::RDFProofsError,
+};
+#[cfg(feature = "rdf-star")]
+use oxrdf::SubjectRef;
+use oxrdf::{vocab::rdf::TYPE, Graph, TermRef, Triple};
+
+// number of terms per triple hashed into messages
+const TERMS_PER_TRIPLE: usize = 3;
+
+/// problem in credential document that makes signing fail
+/// or the credential unusable in presentations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrecheckReason {
+    /// no subject typed as `VerifiableCredential`
+    MissingCredentialType,
+    /// more terms than the message count of BBS+ signatures can hold
+    TooManyTerms,
+    /// numeric or temporal literal that cannot be encoded into a field element,
+    /// e.g., malformed `xsd:dateTime` or `xsd:integer` beyond 64 bits
+    InvalidLiteral,
+    /// literal longer than the given limit, which should be replaced with its digest
+    /// by `digest_large_literals`
+    OversizedLiteral,
+    /// quoted triple, which is not supported in signing
+    QuotedTriple,
+}
+
+/// problem found by `precheck` with the triple at issue if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecheckProblem {
+    pub reason: PrecheckReason,
+    pub triple: Option<Triple>,
+    pub message: String,
+}
+
+/// check credential document before signing for structures that would fail deep in `sign`
+/// or `blind_sign`, so that issuance pipelines can reject bad inputs early;
+/// literals longer than `max_literal_length` bytes are reported if given.
+/// an empty list does not guarantee that signing succeeds, e.g., with missing keys
+pub fn precheck(document: &Graph, max_literal_length: Option<usize>) -> Vec<PrecheckProblem> {
+    let mut problems = vec![];
+
+    if document
+        .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+        .is_none()
+    {
+        problems.push(PrecheckProblem {
+            reason: PrecheckReason::MissingCredentialType,
+            triple: None,
+            message: format!(
+                "no subject is typed as <{}>",
+                VERIFIABLE_CREDENTIAL_TYPE.as_str()
+            ),
+        });
+    }
+
+    let term_count = document.len().saturating_mul(TERMS_PER_TRIPLE);
+    if u32::try_from(term_count).is_err() {
+        problems.push(PrecheckProblem {
+            reason: PrecheckReason::TooManyTerms,
+            triple: None,
+            message: format!("{} terms exceed {}", term_count, u32::MAX),
+        });
+    }
+
+    for triple in document.iter() {
+        #[cfg(feature = "rdf-star")]
+        if matches!(triple.subject, SubjectRef::Triple(_))
+            || matches!(triple.object, TermRef::Triple(_))
+        {
+            problems.push(PrecheckProblem {
+                reason: PrecheckReason::QuotedTriple,
+                triple: Some(triple.into_owned()),
+                message: "quoted triples cannot be signed".to_string(),
+            });
+        }
+
+        let TermRef::Literal(literal) = triple.object else {
+            continue;
+        };
+        if let Err(e) = encode_literal_to_field(literal) {
+            problems.push(PrecheckProblem {
+                reason: PrecheckReason::InvalidLiteral,
+                triple: Some(triple.into_owned()),
+                message: format!("{} cannot be encoded: {}", literal, e),
+            });
+        }
+        if let Some(max) = max_literal_length {
+            if literal.value().len() > max {
+                problems.push(PrecheckProblem {
+                    reason: PrecheckReason::OversizedLiteral,
+                    triple: Some(triple.into_owned()),
+                    message: format!(
+                        "literal of {} bytes exceeds {} bytes; digest it with `digest_large_literals`",
+                        literal.value().len(),
+                        max
+                    ),
+                });
+            }
+        }
+    }
+
+    problems.sort_by_cached_key(|p| (p.reason, p.triple.as_ref().map(Triple::to_string)));
+    problems
+}
+
+/// `precheck` with the document in N-Triples format, which fails if it cannot be parsed
+pub fn precheck_string(
+    document: &str,
+    max_literal_length: Option<usize>,
+) -> Result<Vec<PrecheckProblem>, RDFProofsError> {
+    Ok(precheck(
+        &get_graph_from_ntriples(document)?,
+        max_literal_length,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{precheck_string, PrecheckReason};
+
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    "#;
+
+    #[test]
+    fn precheck_detects_unsignable_structures() {
+        assert!(precheck_string(VC, Some(64)).unwrap().is_empty());
+
+        let reasons = |document: &str, max: Option<usize>| {
+            precheck_string(document, max)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.reason)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            reasons(VC, Some(4)),
+            vec![PrecheckReason::OversizedLiteral; 2]
+        );
+        assert_eq!(
+            reasons(&VC.replace("1990-01-01T00:00:00Z", "1990-13-01"), None),
+            vec![PrecheckReason::InvalidLiteral]
+        );
+        assert_eq!(
+            reasons(
+                &VC.replace(
+                    "https://www.w3.org/2018/credentials#VerifiableCredential",
+                    "http://schema.org/Thing"
+                ),
+                None
+            ),
+            vec![PrecheckReason::MissingCredentialType]
+        );
+    }
+}
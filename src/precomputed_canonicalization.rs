@@ -0,0 +1,154 @@
+use crate::{
+    common::{canonicalize_graph, get_graph_from_ntriples, hash_str_to_str},
+    error::RDFProofsError,
+};
+use oxrdf::{Dataset, Graph, GraphNameRef, SubjectRef, TermRef};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// canonical blank node labels of VC document and the digest of its canonical form,
+/// precomputed by publishers of large VCs so that holders can skip re-canonicalization
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrecomputedCanonicalization {
+    /// mapping from the blank node labels in the document to `c14nN`
+    pub labels: HashMap<String, String>,
+    /// multibase-encoded SHA-256 digest of the canonical N-Quads of the document
+    pub digest: String,
+}
+
+impl PrecomputedCanonicalization {
+    /// precompute the canonical labels and digest of the document
+    pub fn new(document: &Graph) -> Result<Self, RDFProofsError> {
+        Ok(Self {
+            labels: rdf_canon::issue_graph(document)?,
+            digest: hash_str_to_str(&rdf_canon::canonicalize_graph(document)?),
+        })
+    }
+
+    pub fn new_string(document: &str) -> Result<Self, RDFProofsError> {
+        Self::new(&get_graph_from_ntriples(document)?)
+    }
+}
+
+/// canonicalize graph as in `canonicalize_graph` if no precomputed canonicalization is given;
+/// otherwise spot-check the precomputed labels, i.e., that they bijectively map the blank nodes
+/// to `c14n0`, `c14n1`, ..., and that the relabeled graph matches the digest,
+/// without running the canonicalization algorithm
+/// (note that non-canonical labels passing the check make the derived proof invalid
+/// since the signed messages are not reconstructed)
+pub(crate) fn canonicalize_graph_with_precomputed(
+    graph: &Graph,
+    precomputed: Option<&PrecomputedCanonicalization>,
+) -> Result<(Graph, HashMap<String, String>), RDFProofsError> {
+    let Some(PrecomputedCanonicalization { labels, digest }) = precomputed else {
+        return canonicalize_graph(graph);
+    };
+
+    let mut bnodes = HashSet::new();
+    for triple in graph.iter() {
+        if let SubjectRef::BlankNode(b) = triple.subject {
+            bnodes.insert(b.as_str());
+        }
+        if let TermRef::BlankNode(b) = triple.object {
+            bnodes.insert(b.as_str());
+        }
+    }
+    if bnodes.len() != labels.len() || bnodes.iter().any(|b| !labels.contains_key(*b)) {
+        return Err(RDFProofsError::InvalidPrecomputedCanonicalization(
+            "labels do not cover the blank nodes in the document".to_string(),
+        ));
+    }
+    let issued = labels
+        .values()
+        .filter_map(|label| label.strip_prefix("c14n")?.parse::<usize>().ok())
+        .filter(|n| *n < labels.len())
+        .collect::<HashSet<_>>();
+    if issued.len() != labels.len() {
+        return Err(RDFProofsError::InvalidPrecomputedCanonicalization(
+            "labels are not canonical blank node identifiers".to_string(),
+        ));
+    }
+
+    let relabeled_graph = rdf_canon::relabel_graph(graph, labels)?;
+    let serialized_canonical_form = rdf_canon::serialize(&Dataset::from_iter(
+        relabeled_graph
+            .iter()
+            .map(|t| t.in_graph(GraphNameRef::DefaultGraph)),
+    ));
+    if hash_str_to_str(&serialized_canonical_form) != *digest {
+        return Err(RDFProofsError::InvalidPrecomputedCanonicalization(
+            "digest does not match the relabeled document".to_string(),
+        ));
+    }
+
+    let global_issued_identifiers_map = labels
+        .iter()
+        .map(|(k, v)| (k.clone(), format!("{}.{}", v, digest)))
+        .collect::<HashMap<_, _>>();
+    let canonicalized_graph = rdf_canon::relabel_graph(graph, &global_issued_identifiers_map)?;
+
+    Ok((canonicalized_graph, global_issued_identifiers_map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonicalize_graph_with_precomputed, PrecomputedCanonicalization};
+    use crate::{
+        common::{canonicalize_graph, get_graph_from_ntriples},
+        error::RDFProofsError,
+    };
+
+    const DOCUMENT: &str = r#"
+    _:b0 <http://schema.org/name> "John Smith" .
+    _:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:b1 <https://www.w3.org/2018/credentials#credentialSubject> _:b0 .
+    "#;
+
+    #[test]
+    fn canonicalize_graph_with_precomputed_success() {
+        let document = get_graph_from_ntriples(DOCUMENT).unwrap();
+        let precomputed = PrecomputedCanonicalization::new_string(DOCUMENT).unwrap();
+        assert_eq!(
+            canonicalize_graph_with_precomputed(&document, Some(&precomputed)).unwrap(),
+            canonicalize_graph(&document).unwrap()
+        );
+    }
+
+    #[test]
+    fn canonicalize_graph_with_invalid_precomputed_failure() {
+        let document = get_graph_from_ntriples(DOCUMENT).unwrap();
+        let precomputed = PrecomputedCanonicalization::new(&document).unwrap();
+
+        let bnodes = precomputed.labels.keys().cloned().collect::<Vec<_>>();
+
+        // swapped labels
+        let mut swapped = precomputed.clone();
+        swapped
+            .labels
+            .insert(bnodes[0].clone(), precomputed.labels[&bnodes[1]].clone());
+        swapped
+            .labels
+            .insert(bnodes[1].clone(), precomputed.labels[&bnodes[0]].clone());
+        assert!(matches!(
+            canonicalize_graph_with_precomputed(&document, Some(&swapped)),
+            Err(RDFProofsError::InvalidPrecomputedCanonicalization(_))
+        ));
+
+        // non-canonical labels
+        let mut renamed = precomputed.clone();
+        renamed.labels.insert(bnodes[0].clone(), "x".to_string());
+        assert!(matches!(
+            canonicalize_graph_with_precomputed(&document, Some(&renamed)),
+            Err(RDFProofsError::InvalidPrecomputedCanonicalization(_))
+        ));
+
+        // missing labels
+        let mut missing = precomputed;
+        missing.labels.remove(&bnodes[0]);
+        assert!(matches!(
+            canonicalize_graph_with_precomputed(&document, Some(&missing)),
+            Err(RDFProofsError::InvalidPrecomputedCanonicalization(_))
+        ));
+    }
+}
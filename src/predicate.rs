@@ -1,18 +1,32 @@
 use crate::{
-    common::{ProvingKey, R1CS},
+    common::{Fr, ProvingKey, SetupParams, VerifyingKey, R1CS},
+    context::{SCO_DATE, SCO_DATETIME},
     error::RDFProofsError,
     multibase_to_ark,
 };
+use ark_ff::{BigInteger, PrimeField};
+use oxrdf::{
+    vocab::xsd::{DATE, DATE_TIME, INTEGER},
+    TermRef,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct Circuit {
     r1cs: R1CS,
     wasm: Vec<u8>,
     proving_key: ProvingKey,
+    input_bit_lengths: HashMap<String, u32>,
 }
 
 impl Circuit {
-    pub fn new(r1cs: &str, wasm: &str, proving_key: &str) -> Result<Self, RDFProofsError> {
+    pub fn new(
+        r1cs: &str,
+        wasm: &str,
+        proving_key: &str,
+        input_bit_lengths: HashMap<String, u32>,
+    ) -> Result<Self, RDFProofsError> {
         let r1cs: R1CS = multibase_to_ark(r1cs)?;
         let (_, wasm) = multibase::decode(wasm)?;
         let proving_key: ProvingKey = multibase_to_ark(proving_key)?;
@@ -20,19 +34,28 @@ impl Circuit {
             r1cs,
             wasm,
             proving_key,
+            input_bit_lengths,
         })
     }
 
-    pub fn get_r1cs(&self) -> R1CS {
-        self.r1cs.clone()
+    /// maximum bit lengths of the circuit inputs keyed by variable names
+    pub fn get_input_bit_lengths(&self) -> &HashMap<String, u32> {
+        &self.input_bit_lengths
     }
 
-    pub fn get_wasm(&self) -> Vec<u8> {
-        self.wasm.clone()
+    /// SNARK verifying key paired with the proving key of the circuit
+    pub fn get_verifying_key(&self) -> &VerifyingKey {
+        &self.proving_key.vk
     }
 
-    pub fn get_proving_key(&self) -> ProvingKey {
-        self.proving_key.clone()
+    /// setup parameters (R1CS, wasm, and proving key, in this order)
+    /// to be shared among the predicates using this circuit
+    pub fn into_setup_params(self) -> [SetupParams; 3] {
+        [
+            SetupParams::R1CS(self.r1cs),
+            SetupParams::Bytes(self.wasm),
+            SetupParams::LegoSnarkProvingKey(self.proving_key),
+        ]
     }
 }
 
@@ -44,4 +67,38 @@ pub struct CircuitString {
     pub circuit_wasm: String,
     #[serde(rename = "provingKey")]
     pub snark_proving_key: String,
+    /// maximum bit lengths of inputs keyed by variable names, e.g., `{"lesser": 64}`
+    /// for the bundled `less_than_*_64` circuits; unconstrained if omitted
+    #[serde(rename = "inputBitLengths", default)]
+    pub input_bit_lengths: HashMap<String, u32>,
+}
+
+// check that the circuit input is a numeric (integer, date, or dateTime) literal
+// whose field encoding fits within the bit length expected by the circuit,
+// since otherwise the (e.g., 64-bit) comparison circuits give meaningless results
+pub(crate) fn validate_input_bit_length(
+    var: &str,
+    term: TermRef,
+    value: &Fr,
+    bit_length: u32,
+) -> Result<(), RDFProofsError> {
+    let is_numeric = match term {
+        TermRef::Literal(v) => [INTEGER, DATE_TIME, SCO_DATETIME, DATE, SCO_DATE]
+            .iter()
+            .any(|datatype| v.datatype() == *datatype),
+        _ => false,
+    };
+    if !is_numeric {
+        return Err(RDFProofsError::InvalidCircuitInput(format!(
+            "`{}` must be an integer, date, or dateTime literal: {}",
+            var, term
+        )));
+    }
+    if value.into_bigint().num_bits() > bit_length {
+        return Err(RDFProofsError::InvalidCircuitInput(format!(
+            "`{}` must be a non-negative value within {} bits: {}",
+            var, bit_length, term
+        )));
+    }
+    Ok(())
 }
@@ -0,0 +1,440 @@
+use crate::{
+    common::{
+        get_graph_from_ntriples, read_predicate_outputs, read_private_var_list,
+        read_public_var_list, Fr,
+    },
+    context::{
+        CIRCUIT, OUTPUT, PREDICATE_TYPE, PREDICATE_VAL, PREDICATE_VAR, PRIVATE, PRIVATE_VARIABLE,
+        PUBLIC, PUBLIC_VARIABLE,
+    },
+    error::RDFProofsError,
+};
+use oxrdf::{
+    dataset::GraphView,
+    vocab::rdf::{FIRST, NIL, REST, TYPE},
+    BlankNode, Dataset, Graph, GraphNameRef, Literal, LiteralRef, NamedNode, NamedNodeRef,
+    NamedOrBlankNode, Subject, SubjectRef, Term, TermRef, TripleRef,
+};
+use std::collections::HashMap;
+
+/// reference to hidden term shared by disclosed VCs and predicates, i.e., the blank node
+/// standing for the term in disclosed VCs and keyed in deanonymization map like `_:e4`,
+/// which predicates bind to their private variables;
+/// it is kept as is while the other blank nodes in predicates are randomized in `derive_proof`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VariableRef(BlankNode);
+
+impl VariableRef {
+    pub fn new(label: &str) -> Result<Self, RDFProofsError> {
+        Ok(Self(BlankNode::new(label)?))
+    }
+
+    pub fn as_blank_node(&self) -> &BlankNode {
+        &self.0
+    }
+
+    pub fn label(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<BlankNode> for VariableRef {
+    fn from(bnode: BlankNode) -> Self {
+        Self(bnode)
+    }
+}
+
+impl From<VariableRef> for NamedOrBlankNode {
+    fn from(variable: VariableRef) -> Self {
+        variable.0.into()
+    }
+}
+
+impl From<VariableRef> for Subject {
+    fn from(variable: VariableRef) -> Self {
+        variable.0.into()
+    }
+}
+
+impl From<VariableRef> for Term {
+    fn from(variable: VariableRef) -> Self {
+        variable.0.into()
+    }
+}
+
+/// typed model of predicate graph applying circuit to private variables,
+/// i.e., hidden terms keyed in deanon map like `_:e4`, and public variables,
+/// serialized into `zkp-ld:Predicate` with `rdf:first`/`rdf:rest` lists
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PredicateGraph {
+    pub circuit: NamedNode,
+    pub private: Vec<(String, NamedOrBlankNode)>,
+    pub public: Vec<(String, Term)>,
+    /// circuit outputs disclosed as literals; the circuit is expected to output `1` if omitted
+    pub output: Option<Vec<(String, Term)>>,
+}
+
+impl PredicateGraph {
+    pub fn new(circuit: NamedNode) -> Self {
+        Self {
+            circuit,
+            private: vec![],
+            public: vec![],
+            output: None,
+        }
+    }
+
+    pub fn new_string(circuit: &str) -> Result<Self, RDFProofsError> {
+        Ok(Self::new(NamedNode::new(circuit)?))
+    }
+
+    /// add private variable bound to hidden term, e.g., `VariableRef` from disclosure builders
+    pub fn private(mut self, var: &str, val: impl Into<NamedOrBlankNode>) -> Self {
+        self.private.push((var.to_string(), val.into()));
+        self
+    }
+
+    /// add public variable bound to term, e.g., typed literal like `"2000-01-01"^^xsd:date`
+    pub fn public(mut self, var: &str, val: impl Into<Term>) -> Self {
+        self.public.push((var.to_string(), val.into()));
+        self
+    }
+
+    /// add circuit output disclosed as literal
+    pub fn output(mut self, var: &str, val: Literal) -> Self {
+        self.output
+            .get_or_insert_with(Vec::new)
+            .push((var.to_string(), val.into()));
+        self
+    }
+
+    /// serialize into RDF graph with fresh blank nodes
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        let predicate_subject = BlankNode::default();
+        graph.insert(TripleRef::new(&predicate_subject, TYPE, PREDICATE_TYPE));
+        graph.insert(TripleRef::new(
+            &predicate_subject,
+            CIRCUIT,
+            self.circuit.as_ref(),
+        ));
+
+        let private = self
+            .private
+            .iter()
+            .map(|(var, val)| (var.as_str(), TermRef::from(val.as_ref())))
+            .collect::<Vec<_>>();
+        let private_list = insert_var_list(&mut graph, PRIVATE_VARIABLE, &private);
+        graph.insert(TripleRef::new(
+            &predicate_subject,
+            PRIVATE,
+            private_list.as_ref(),
+        ));
+
+        let public = self
+            .public
+            .iter()
+            .map(|(var, val)| (var.as_str(), val.as_ref()))
+            .collect::<Vec<_>>();
+        let public_list = insert_var_list(&mut graph, PUBLIC_VARIABLE, &public);
+        graph.insert(TripleRef::new(
+            &predicate_subject,
+            PUBLIC,
+            public_list.as_ref(),
+        ));
+
+        if let Some(output) = &self.output {
+            let output = output
+                .iter()
+                .map(|(var, val)| (var.as_str(), val.as_ref()))
+                .collect::<Vec<_>>();
+            let output_list = insert_var_list(&mut graph, PUBLIC_VARIABLE, &output);
+            graph.insert(TripleRef::new(
+                &predicate_subject,
+                OUTPUT,
+                output_list.as_ref(),
+            ));
+        }
+
+        graph
+    }
+
+    /// N-Triples version of `to_graph` to be given to `derive_proof_string`
+    pub fn to_ntriples(&self) -> String {
+        self.to_graph()
+            .iter()
+            .map(|t| format!("{} .\n", t))
+            .collect()
+    }
+
+    pub fn from_graph(graph: &Graph) -> Result<Self, RDFProofsError> {
+        let dataset = graph
+            .iter()
+            .map(|t| t.in_graph(GraphNameRef::DefaultGraph))
+            .collect::<Dataset>();
+        Self::try_from(&dataset.graph(GraphNameRef::DefaultGraph))
+    }
+
+    pub fn from_ntriples(ntriples: &str) -> Result<Self, RDFProofsError> {
+        Self::from_graph(&get_graph_from_ntriples(ntriples)?)
+    }
+}
+
+// parse predicate graph in VP or given to derive_proof
+impl TryFrom<&GraphView<'_>> for PredicateGraph {
+    type Error = RDFProofsError;
+
+    fn try_from(graph: &GraphView) -> Result<Self, Self::Error> {
+        let predicate_subject = graph
+            .subject_for_predicate_object(TYPE, PREDICATE_TYPE)
+            .ok_or(RDFProofsError::InvalidPredicate)?;
+        let (
+            Some(TermRef::NamedNode(circuit)),
+            Some(TermRef::BlankNode(private_list)),
+            Some(TermRef::BlankNode(public_list)),
+        ) = (
+            graph.object_for_subject_predicate(predicate_subject, CIRCUIT),
+            graph.object_for_subject_predicate(predicate_subject, PRIVATE),
+            graph.object_for_subject_predicate(predicate_subject, PUBLIC),
+        )
+        else {
+            return Err(RDFProofsError::InvalidPredicate);
+        };
+
+        let mut private = vec![];
+        read_private_var_list(private_list, &mut private, graph)?;
+        let mut public = vec![];
+        read_public_var_list(public_list, &mut public, graph)?;
+        let output = read_predicate_outputs(predicate_subject, graph)?;
+
+        Ok(Self {
+            circuit: circuit.into_owned(),
+            private,
+            public,
+            output,
+        })
+    }
+}
+
+// every private variable in predicates must resolve to exactly one hidden term, i.e.,
+// be keyed in deanonymization map and occur in disclosed VCs, so that it is neither
+// randomized along with the other blank nodes in predicates nor left unbound
+pub(crate) fn validate_private_variables<'a>(
+    predicates: &[Graph],
+    deanon_map: &HashMap<NamedOrBlankNode, Term>,
+    disclosed_documents: impl IntoIterator<Item = &'a Graph>,
+) -> Result<(), RDFProofsError> {
+    let disclosed_documents = disclosed_documents.into_iter().collect::<Vec<_>>();
+    let occurs = |val: &NamedOrBlankNode| {
+        disclosed_documents.iter().any(|document| {
+            document.iter().any(|t| {
+                t.subject == SubjectRef::from(val.as_ref())
+                    || t.object == TermRef::from(val.as_ref())
+            })
+        })
+    };
+    for predicate in predicates {
+        for (var, val) in PredicateGraph::from_graph(predicate)?.private {
+            if !deanon_map.contains_key(&val) {
+                return Err(RDFProofsError::UnresolvedPrivateVariable(format!(
+                    "{} bound to {} is not in deanonymization map",
+                    var, val
+                )));
+            }
+            if !occurs(&val) {
+                return Err(RDFProofsError::UnresolvedPrivateVariable(format!(
+                    "{} bound to {} does not occur in disclosed VCs",
+                    var, val
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+// split circuit input name into signal name and index if it is an element of input array,
+// e.g., `addends[1]` into `addends` and `1`
+pub(crate) fn split_array_input(var: &str) -> Option<(&str, usize)> {
+    let (name, index) = var.strip_suffix(']')?.split_once('[')?;
+    Some((name, index.parse().ok()?))
+}
+
+// group circuit inputs into signals, where the elements of input array must be given
+// consecutively in the order of indexes so that they line up with the circuit witnesses
+pub(crate) fn group_array_inputs(
+    inputs: Vec<(String, Fr)>,
+) -> Result<Vec<(String, Vec<Fr>)>, RDFProofsError> {
+    let mut signals: Vec<(String, Vec<Fr>)> = vec![];
+    for (var, val) in inputs {
+        let Some((name, index)) = split_array_input(&var) else {
+            signals.push((var, vec![val]));
+            continue;
+        };
+        if let Some((last, values)) = signals.last_mut() {
+            if *last == name && values.len() == index {
+                values.push(val);
+                continue;
+            }
+        }
+        if index != 0 || signals.iter().any(|(s, _)| s == name) {
+            return Err(RDFProofsError::InvalidCircuitInput(format!(
+                "`{}` must follow the preceding elements of `{}`",
+                var, name
+            )));
+        }
+        signals.push((name.to_string(), vec![val]));
+    }
+    Ok(signals)
+}
+
+// insert `rdf:first`/`rdf:rest` list of variables, returning its head
+fn insert_var_list(
+    graph: &mut Graph,
+    var_type: NamedNodeRef,
+    vars: &[(&str, TermRef)],
+) -> NamedOrBlankNode {
+    let mut list: NamedOrBlankNode = NIL.into_owned().into();
+    for (var, val) in vars.iter().rev() {
+        let node = BlankNode::default();
+        let var_and_val = BlankNode::default();
+        graph.insert(TripleRef::new(&var_and_val, TYPE, var_type));
+        graph.insert(TripleRef::new(
+            &var_and_val,
+            PREDICATE_VAR,
+            LiteralRef::new_simple_literal(var),
+        ));
+        graph.insert(TripleRef::new(&var_and_val, PREDICATE_VAL, *val));
+        graph.insert(TripleRef::new(&node, FIRST, &var_and_val));
+        graph.insert(TripleRef::new(&node, REST, list.as_ref()));
+        list = node.into();
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group_array_inputs, validate_private_variables, PredicateGraph, VariableRef};
+    use crate::{
+        common::{get_graph_from_ntriples, Fr},
+        context::CIRCUIT,
+        error::RDFProofsError,
+    };
+    use oxrdf::{vocab::xsd, BlankNode, Literal, NamedNode, NamedOrBlankNode, Term};
+    use std::collections::HashMap;
+
+    const LESS_THAN_PRV_PUB_64: &str = "https://zkp-ld.org/circuit/ekomon/lessThanPrvPub64";
+
+    #[test]
+    fn group_array_inputs_in_order() {
+        let input = |var: &str, val: u64| (var.to_string(), Fr::from(val));
+        assert_eq!(
+            group_array_inputs(vec![
+                input("addends[0]", 1),
+                input("addends[1]", 2),
+                input("threshold", 3),
+            ])
+            .unwrap(),
+            vec![
+                ("addends".to_string(), vec![Fr::from(1u64), Fr::from(2u64)]),
+                ("threshold".to_string(), vec![Fr::from(3u64)]),
+            ]
+        );
+
+        for inputs in [
+            vec![input("addends[1]", 1)],
+            vec![input("addends[0]", 1), input("addends[0]", 2)],
+            vec![
+                input("addends[0]", 1),
+                input("threshold", 2),
+                input("addends[1]", 3),
+            ],
+        ] {
+            assert!(matches!(
+                group_array_inputs(inputs),
+                Err(RDFProofsError::InvalidCircuitInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn predicate_graph_roundtrip() {
+        let predicate = PredicateGraph::new_string(LESS_THAN_PRV_PUB_64)
+            .unwrap()
+            .private("lesser", BlankNode::new_unchecked("e1"))
+            .public(
+                "greater",
+                Literal::new_typed_literal("2000-01-01T00:00:00Z", xsd::DATE_TIME),
+            );
+        let ntriples = predicate.to_ntriples();
+        assert_eq!(PredicateGraph::from_ntriples(&ntriples).unwrap(), predicate);
+
+        let predicate = predicate.output("result", Literal::from(1));
+        assert_eq!(
+            PredicateGraph::from_graph(&predicate.to_graph()).unwrap(),
+            predicate
+        );
+    }
+
+    #[test]
+    fn predicate_graph_invalid_failure() {
+        // missing circuit
+        let mut predicate = PredicateGraph::new(NamedNode::new_unchecked(LESS_THAN_PRV_PUB_64))
+            .private("lesser", BlankNode::new_unchecked("e1"))
+            .to_graph();
+        assert!(PredicateGraph::from_graph(&predicate).is_ok());
+        let circuit_triple = predicate
+            .iter()
+            .find(|t| t.predicate == CIRCUIT)
+            .unwrap()
+            .into_owned();
+        predicate.remove(&circuit_triple);
+        assert!(matches!(
+            PredicateGraph::from_graph(&predicate),
+            Err(RDFProofsError::InvalidPredicate)
+        ));
+    }
+
+    #[test]
+    fn private_variables_resolve_to_hidden_terms() {
+        let disclosed = get_graph_from_ntriples(
+            r#"_:e0 <http://schema.org/birthDate> _:e1 .
+            _:e0 <http://schema.org/name> _:b0 ."#,
+        )
+        .unwrap();
+        let deanon_map: HashMap<NamedOrBlankNode, Term> = HashMap::from([
+            (
+                BlankNode::new_unchecked("e0").into(),
+                NamedNode::new_unchecked("did:example:john").into(),
+            ),
+            (
+                BlankNode::new_unchecked("e1").into(),
+                Literal::new_typed_literal("2000-01-01T00:00:00Z", xsd::DATE_TIME).into(),
+            ),
+            (
+                BlankNode::new_unchecked("e2").into(),
+                Literal::new_simple_literal("John Smith").into(),
+            ),
+        ]);
+        let predicate = |label: &str| {
+            PredicateGraph::new_string(LESS_THAN_PRV_PUB_64)
+                .unwrap()
+                .private("lesser", VariableRef::new(label).unwrap())
+                .to_graph()
+        };
+        let validate = |label: &str| {
+            validate_private_variables(&[predicate(label)], &deanon_map, [&disclosed])
+        };
+
+        assert!(validate("e1").is_ok());
+        // not in deanonymization map
+        assert!(matches!(
+            validate("b0"),
+            Err(RDFProofsError::UnresolvedPrivateVariable(_))
+        ));
+        // not in disclosed VCs
+        assert!(matches!(
+            validate("e2"),
+            Err(RDFProofsError::UnresolvedPrivateVariable(_))
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::RDFProofsError;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// coarse phase of `derive_proof` reported to `Progress` in this order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProofPhase {
+    /// canonicalization of the VP draft and the original VCs
+    Canonicalization,
+    /// building statements and meta statements of the proof spec
+    StatementBuild,
+    /// building witnesses, including those of SNARK predicates
+    SnarkWitness,
+    /// generating the proof
+    Proof,
+}
+
+impl ProofPhase {
+    /// all the phases in the order they are entered
+    pub const ALL: [ProofPhase; 4] = [
+        ProofPhase::Canonicalization,
+        ProofPhase::StatementBuild,
+        ProofPhase::SnarkWitness,
+        ProofPhase::Proof,
+    ];
+}
+
+/// callback invoked from `derive_proof` when entering each phase,
+/// e.g., to show progress in wallet UIs
+pub trait Progress: Send + Sync {
+    fn phase_started(&self, phase: ProofPhase);
+}
+
+impl<F: Fn(ProofPhase) + Send + Sync> Progress for F {
+    fn phase_started(&self, phase: ProofPhase) {
+        self(phase)
+    }
+}
+
+/// cooperative cancellation of `derive_proof`, which is checked before entering each phase,
+/// so that `derive_proof` returns `RDFProofsError::Cancelled` at the next phase boundary
+/// after `cancel` is called, e.g., from UI thread holding a clone of the token
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// abort if cancelled, and otherwise report entering the phase
+pub(crate) fn enter_phase(
+    phase: ProofPhase,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&dyn Progress>,
+) -> Result<(), RDFProofsError> {
+    if cancellation.is_some_and(CancellationToken::is_cancelled) {
+        return Err(RDFProofsError::Cancelled);
+    }
+    if let Some(progress) = progress {
+        progress.phase_started(phase);
+    }
+    Ok(())
+}
@@ -0,0 +1,409 @@
+use crate::error::RDFProofsError;
+use serde::{Deserialize, Serialize};
+
+/// version of the serialized proof layout, where `0` is the legacy layout without statement tags
+pub(crate) const PROOF_LAYOUT_VERSION: u32 = 1;
+
+/// version of the proof spec context, where `0` is the legacy context
+/// without per-credential sub-contexts
+pub(crate) const PROOF_CONTEXT_VERSION: u32 = 1;
+
+/// identifier of the proof format bound into the proof spec context, which is bumped by hand
+/// only on incompatible changes to the proof format, so that proofs keep verifying
+/// across releases of the crate
+pub(crate) const PROOF_FORMAT: &str = "rdf-proofs/proof-format/1";
+
+/// crate name and version recorded in serialized proofs for diagnostics
+pub(crate) const GENERATOR: &str = concat!("rdf-proofs/", env!("CARGO_PKG_VERSION"));
+
+// tags of statements in the serialized proof, in the order of statements
+pub(crate) const BBS_PLUS_SIGNATURE_TAG: &str = "bbs+";
+pub(crate) const PPID_TAG: &str = "ppid";
+pub(crate) const VERIFIABLE_ENCRYPTION_TAG: &str = "verifiable-encryption";
+pub(crate) const SECRET_COMMITMENT_TAG: &str = "secret-commitment";
+pub(crate) const PREDICATE_TAG: &str = "predicate";
+pub(crate) const COMMITMENT_EQUALITY_TAG: &str = "commitment-equality";
+pub(crate) const INEQUALITY_TAG: &str = "inequality";
+pub(crate) const DEVICE_BINDING_TAG: &str = "device-binding";
+pub(crate) const ATTRIBUTE_ENCRYPTION_TAG: &str = "attribute-encryption";
+
+const KNOWN_STATEMENT_TAGS: [&str; 9] = [
+    BBS_PLUS_SIGNATURE_TAG,
+    PPID_TAG,
+    VERIFIABLE_ENCRYPTION_TAG,
+    SECRET_COMMITMENT_TAG,
+    PREDICATE_TAG,
+    COMMITMENT_EQUALITY_TAG,
+    INEQUALITY_TAG,
+    DEVICE_BINDING_TAG,
+    ATTRIBUTE_ENCRYPTION_TAG,
+];
+
+/// kinds of statements in composite proofs in the order they are laid out, i.e.,
+/// 1. BBS+ signatures of VCs, in the order of VC graphs in canonicalized VP
+/// 2. PPID
+/// 3. verifiable encryption of uid, one statement per opener
+/// 4. secret commitment for blind signing
+/// 5. predicates, in the order of predicate graphs in canonicalized VP
+/// 6. equalities to verifier's commitments, in the order of commitments in VP proof
+/// 7. inequalities, each consisting of several statements
+/// 8. device binding
+/// 9. verifiable encryption of hidden terms, in the order of encryptions in VP proof,
+///    each consisting of the statements per key
+///
+/// prover and verifier must agree on the order, so that it must not be changed
+/// without bumping `PROOF_LAYOUT_VERSION`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum StatementKind {
+    BbsPlusSignature,
+    Ppid,
+    VerifiableEncryption,
+    SecretCommitment,
+    Predicate,
+    CommitmentEquality,
+    Inequality,
+    DeviceBinding,
+    AttributeEncryption,
+}
+
+impl StatementKind {
+    pub(crate) fn tag(self) -> &'static str {
+        match self {
+            StatementKind::BbsPlusSignature => BBS_PLUS_SIGNATURE_TAG,
+            StatementKind::Ppid => PPID_TAG,
+            StatementKind::VerifiableEncryption => VERIFIABLE_ENCRYPTION_TAG,
+            StatementKind::SecretCommitment => SECRET_COMMITMENT_TAG,
+            StatementKind::Predicate => PREDICATE_TAG,
+            StatementKind::CommitmentEquality => COMMITMENT_EQUALITY_TAG,
+            StatementKind::Inequality => INEQUALITY_TAG,
+            StatementKind::DeviceBinding => DEVICE_BINDING_TAG,
+            StatementKind::AttributeEncryption => ATTRIBUTE_ENCRYPTION_TAG,
+        }
+    }
+}
+
+/// layout of statements in composite proof, built alongside the statements by prover
+/// and verifier alike, which rejects statements added out of the order of `StatementKind`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct StatementLayout(Vec<StatementKind>);
+
+impl StatementLayout {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// append `count` statements of `kind`, returning the index of the first one
+    pub(crate) fn push(
+        &mut self,
+        kind: StatementKind,
+        count: usize,
+    ) -> Result<usize, RDFProofsError> {
+        if self.0.last().is_some_and(|last| *last > kind) {
+            return Err(RDFProofsError::InvalidStatementLayout(format!(
+                "{} statement after {} statement",
+                kind.tag(),
+                self.0.last().map(|last| last.tag()).unwrap_or_default()
+            )));
+        }
+        let index = self.0.len();
+        self.0.extend(std::iter::repeat(kind).take(count));
+        Ok(index)
+    }
+
+    pub(crate) fn tags(&self) -> Vec<&'static str> {
+        self.0.iter().map(|kind| kind.tag()).collect()
+    }
+}
+
+// cargo features required to verify the statements, and whether they are enabled in this build
+const STATEMENT_FEATURES: [(&str, &str, bool); 3] = [
+    (PREDICATE_TAG, "predicates", cfg!(feature = "predicates")),
+    (
+        VERIFIABLE_ENCRYPTION_TAG,
+        "encryption",
+        cfg!(feature = "encryption"),
+    ),
+    (
+        ATTRIBUTE_ENCRYPTION_TAG,
+        "encryption",
+        cfg!(feature = "encryption"),
+    ),
+];
+
+// statement tags that this build can prove and verify, in the order of `StatementKind`
+pub(crate) fn supported_statement_tags() -> Vec<&'static str> {
+    KNOWN_STATEMENT_TAGS
+        .into_iter()
+        .filter(|tag| {
+            STATEMENT_FEATURES
+                .iter()
+                .all(|(t, _, enabled)| t != tag || *enabled)
+        })
+        .collect()
+}
+
+/// header fields of serialized proof, which are read leniently even if the rest of
+/// the proof cannot be decoded, e.g., when generated by a newer version
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ProofHeader {
+    #[serde(rename = "v", default)]
+    pub version: u32,
+    #[serde(rename = "t", default)]
+    pub statement_tags: Vec<String>,
+    #[serde(rename = "g", default)]
+    pub generator: String,
+    #[serde(rename = "f", default)]
+    pub features: Vec<String>,
+}
+
+/// extension carried in the serialized proof alongside the composite proof,
+/// which is not covered by the composite proof and thus must be self-verifying;
+/// verifiers that do not understand a critical extension must reject the proof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofExtension {
+    #[serde(rename = "a")]
+    pub tag: String,
+    #[serde(rename = "b")]
+    pub critical: bool,
+    #[serde(rename = "c")]
+    pub data: Vec<u8>,
+}
+
+/// how verifier handles extensions it does not understand
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExtensionPolicy {
+    /// reject any unknown extension
+    #[default]
+    Reject,
+    /// ignore unknown non-critical extensions, while rejecting unknown critical ones
+    IgnoreNonCritical,
+}
+
+// check that every statement and extension in the serialized proof is supported
+pub(crate) fn validate_proof_layout(
+    version: u32,
+    statement_tags: &[String],
+    extensions: &[ProofExtension],
+    extension_policy: ExtensionPolicy,
+) -> Result<(), RDFProofsError> {
+    if version > PROOF_LAYOUT_VERSION {
+        return Err(RDFProofsError::UnsupportedProofLayoutVersion(version));
+    }
+    if let Some(tag) = statement_tags
+        .iter()
+        .find(|tag| !KNOWN_STATEMENT_TAGS.contains(&tag.as_str()))
+    {
+        return Err(RDFProofsError::UnsupportedStatementType { tag: tag.clone() });
+    }
+    // no extensions are defined yet
+    for extension in extensions {
+        if extension.critical || extension_policy == ExtensionPolicy::Reject {
+            return Err(RDFProofsError::UnsupportedStatementType {
+                tag: extension.tag.clone(),
+            });
+        }
+        debug_println!("ignored non-critical extension: {}", extension.tag);
+    }
+    Ok(())
+}
+
+// cargo features required to verify the statements, in the order of `STATEMENT_FEATURES`
+pub(crate) fn get_required_features(statement_tags: &[String]) -> Vec<String> {
+    let mut features: Vec<String> = STATEMENT_FEATURES
+        .iter()
+        .filter(|(tag, _, _)| statement_tags.iter().any(|t| t == tag))
+        .map(|(_, feature, _)| feature.to_string())
+        .collect();
+    // features shared by several kinds of statements are listed once
+    features.dedup();
+    features
+}
+
+// check that the features required by the proof are enabled in this build
+pub(crate) fn validate_proof_features(features: &[String]) -> Result<(), RDFProofsError> {
+    match features.iter().find(|feature| {
+        !STATEMENT_FEATURES
+            .iter()
+            .any(|(_, f, enabled)| f == feature && *enabled)
+    }) {
+        Some(feature) => Err(RDFProofsError::UnsupportedProofFeature(feature.clone())),
+        None => Ok(()),
+    }
+}
+
+// explain why the serialized proof cannot be decoded from its header if possible,
+// e.g., proof layout newer than this verifier, instead of the bare CBOR error
+pub(crate) fn diagnose_undecodable_proof(cbor: &[u8], error: RDFProofsError) -> RDFProofsError {
+    let Ok(header) = serde_cbor::from_slice::<ProofHeader>(cbor) else {
+        return error;
+    };
+    if header.version > PROOF_LAYOUT_VERSION {
+        return RDFProofsError::ProofFromNewerVersion {
+            generator: header.generator,
+            version: header.version,
+        };
+    }
+    validate_proof_layout(
+        header.version,
+        &header.statement_tags,
+        &[],
+        ExtensionPolicy::Reject,
+    )
+    .and_then(|_| validate_proof_features(&header.features))
+    .err()
+    .unwrap_or(error)
+}
+
+// check that the statement tags in the serialized proof match the statements
+// reconstructed by verifier, unless the proof is in the legacy layout
+pub(crate) fn validate_statement_tags(
+    version: u32,
+    statement_tags: &[String],
+    expected_tags: &[&str],
+) -> Result<(), RDFProofsError> {
+    if version > 0 && statement_tags.iter().ne(expected_tags.iter()) {
+        return Err(RDFProofsError::MismatchedStatementTypes);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        validate_proof_layout, validate_statement_tags, ExtensionPolicy, ProofExtension,
+        StatementKind, StatementLayout, BBS_PLUS_SIGNATURE_TAG, KNOWN_STATEMENT_TAGS, PPID_TAG,
+        PROOF_LAYOUT_VERSION,
+    };
+    use crate::error::RDFProofsError;
+
+    // golden layout, which must not change within `PROOF_LAYOUT_VERSION`
+    #[test]
+    fn statement_layout_is_fixed() {
+        assert_eq!(
+            KNOWN_STATEMENT_TAGS,
+            [
+                "bbs+",
+                "ppid",
+                "verifiable-encryption",
+                "secret-commitment",
+                "predicate",
+                "commitment-equality",
+                "inequality",
+                "device-binding",
+                "attribute-encryption",
+            ]
+        );
+
+        let mut layout = StatementLayout::new();
+        assert_eq!(layout.push(StatementKind::BbsPlusSignature, 2).unwrap(), 0);
+        assert_eq!(layout.push(StatementKind::Ppid, 1).unwrap(), 2);
+        assert_eq!(
+            layout.push(StatementKind::VerifiableEncryption, 3).unwrap(),
+            3
+        );
+        assert_eq!(layout.push(StatementKind::SecretCommitment, 1).unwrap(), 6);
+        assert_eq!(layout.push(StatementKind::Predicate, 1).unwrap(), 7);
+        assert_eq!(layout.push(StatementKind::Predicate, 1).unwrap(), 8);
+        assert_eq!(
+            layout.push(StatementKind::CommitmentEquality, 1).unwrap(),
+            9
+        );
+        assert_eq!(layout.push(StatementKind::Inequality, 4).unwrap(), 10);
+        assert_eq!(layout.push(StatementKind::DeviceBinding, 1).unwrap(), 14);
+        assert_eq!(layout.tags().len(), 15);
+        assert_eq!(
+            layout.tags(),
+            [
+                vec!["bbs+"; 2],
+                vec!["ppid"],
+                vec!["verifiable-encryption"; 3],
+                vec!["secret-commitment", "predicate", "predicate"],
+                vec!["commitment-equality"],
+                vec!["inequality"; 4],
+                vec!["device-binding"],
+            ]
+            .concat()
+        );
+
+        // statements out of order are rejected
+        let mut layout = StatementLayout::new();
+        layout.push(StatementKind::BbsPlusSignature, 1).unwrap();
+        layout.push(StatementKind::Predicate, 1).unwrap();
+        assert!(matches!(
+            layout.push(StatementKind::Ppid, 1),
+            Err(RDFProofsError::InvalidStatementLayout(_))
+        ));
+    }
+
+    #[test]
+    fn validate_proof_layout_with_unknown_statements_and_extensions() {
+        let tags = vec![BBS_PLUS_SIGNATURE_TAG.to_string(), PPID_TAG.to_string()];
+        let non_critical = ProofExtension {
+            tag: "example-extension".to_string(),
+            critical: false,
+            data: vec![0, 1, 2],
+        };
+        let critical = ProofExtension {
+            critical: true,
+            ..non_critical.clone()
+        };
+
+        assert!(
+            validate_proof_layout(PROOF_LAYOUT_VERSION, &tags, &[], ExtensionPolicy::Reject)
+                .is_ok()
+        );
+        assert!(matches!(
+            validate_proof_layout(
+                PROOF_LAYOUT_VERSION + 1,
+                &tags,
+                &[],
+                ExtensionPolicy::Reject
+            ),
+            Err(RDFProofsError::UnsupportedProofLayoutVersion(_))
+        ));
+        assert!(matches!(
+            validate_proof_layout(
+                PROOF_LAYOUT_VERSION,
+                &["range-proof".to_string()],
+                &[],
+                ExtensionPolicy::IgnoreNonCritical
+            ),
+            Err(RDFProofsError::UnsupportedStatementType { tag }) if tag == "range-proof"
+        ));
+
+        // unknown extensions
+        assert!(matches!(
+            validate_proof_layout(
+                PROOF_LAYOUT_VERSION,
+                &tags,
+                &[non_critical.clone()],
+                ExtensionPolicy::Reject
+            ),
+            Err(RDFProofsError::UnsupportedStatementType { tag }) if tag == "example-extension"
+        ));
+        assert!(validate_proof_layout(
+            PROOF_LAYOUT_VERSION,
+            &tags,
+            &[non_critical],
+            ExtensionPolicy::IgnoreNonCritical
+        )
+        .is_ok());
+        assert!(matches!(
+            validate_proof_layout(
+                PROOF_LAYOUT_VERSION,
+                &tags,
+                &[critical],
+                ExtensionPolicy::IgnoreNonCritical
+            ),
+            Err(RDFProofsError::UnsupportedStatementType { .. })
+        ));
+
+        // statement tags are not checked for legacy layout
+        assert!(validate_statement_tags(0, &[], &[BBS_PLUS_SIGNATURE_TAG]).is_ok());
+        assert!(validate_statement_tags(1, &tags, &[BBS_PLUS_SIGNATURE_TAG, PPID_TAG]).is_ok());
+        assert!(matches!(
+            validate_statement_tags(1, &tags, &[BBS_PLUS_SIGNATURE_TAG]),
+            Err(RDFProofsError::MismatchedStatementTypes)
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{
+    common::{get_dataset_from_nquads, hash_str_to_str},
+    context::{DATA_INTEGRITY_PROOF, MULTIBASE, PROOF_VALUE},
+    error::RDFProofsError,
+    vc::VerifiablePresentation,
+};
+use oxrdf::{vocab::rdf::TYPE, Dataset, LiteralRef, QuadRef};
+use serde::{Deserialize, Serialize};
+
+/// proof value detached from VP by `detach_proof_value`, along with the digest of
+/// the detached VP, i.e., multibase-encoded SHA-256 hash of its canonical N-Quads,
+/// which equals that of `VerificationResult::canonical_vp`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofValue {
+    /// multibase-encoded proof value
+    pub value: String,
+    pub vp_digest: String,
+}
+
+// VP without the proof value of VP proof, which is what prover and verifier canonicalize
+// (otherwise the canonicalization could differ from the prover's one)
+pub(crate) fn remove_proof_value(vp_dataset: &Dataset, vp: &VerifiablePresentation) -> Dataset {
+    Dataset::from_iter(
+        vp_dataset
+            .iter()
+            .filter(|q| !(q.predicate == PROOF_VALUE && q.graph_name == vp.proof_graph_name)),
+    )
+}
+
+/// strip the proof value from VP, e.g., for logging its canonical content,
+/// to be re-attached later by `attach_proof_value`
+pub fn detach_proof_value(vp_dataset: &Dataset) -> Result<(Dataset, ProofValue), RDFProofsError> {
+    let vp: VerifiablePresentation = vp_dataset.try_into()?;
+    let value = vp.get_proof_value()?;
+    let detached = remove_proof_value(vp_dataset, &vp);
+    let vp_digest = hash_str_to_str(&rdf_canon::canonicalize(&detached)?);
+    Ok((detached, ProofValue { value, vp_digest }))
+}
+
+/// re-attach the proof value detached by `detach_proof_value`, checking that the detached VP
+/// is unchanged up to blank node labels and does not have another proof value
+pub fn attach_proof_value(
+    vp_dataset: &Dataset,
+    proof_value: &ProofValue,
+) -> Result<Dataset, RDFProofsError> {
+    let vp: VerifiablePresentation = vp_dataset.try_into()?;
+    match vp.get_proof_value() {
+        Err(RDFProofsError::VCWithoutProofValue) => (),
+        Ok(_) => {
+            return Err(RDFProofsError::ProofValueAttachment(
+                "VP already has proof value".to_string(),
+            ))
+        }
+        Err(e) => return Err(e),
+    }
+    if hash_str_to_str(&rdf_canon::canonicalize(vp_dataset)?) != proof_value.vp_digest {
+        return Err(RDFProofsError::ProofValueAttachment(
+            "VP has been modified since the proof value was detached".to_string(),
+        ));
+    }
+    let vp_proof_subject = vp
+        .proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let mut attached = vp_dataset.clone();
+    attached.insert(QuadRef::new(
+        vp_proof_subject,
+        PROOF_VALUE,
+        LiteralRef::new_typed_literal(&proof_value.value, MULTIBASE),
+        vp.proof_graph_name,
+    ));
+    Ok(attached)
+}
+
+/// N-Quads version of `detach_proof_value`
+pub fn detach_proof_value_string(vp: &str) -> Result<(String, ProofValue), RDFProofsError> {
+    let (detached, proof_value) = detach_proof_value(&get_dataset_from_nquads(vp)?)?;
+    Ok((rdf_canon::serialize(&detached), proof_value))
+}
+
+/// N-Quads version of `attach_proof_value`
+pub fn attach_proof_value_string(
+    vp: &str,
+    proof_value: &ProofValue,
+) -> Result<String, RDFProofsError> {
+    let attached = attach_proof_value(&get_dataset_from_nquads(vp)?, proof_value)?;
+    Ok(rdf_canon::serialize(&attached))
+}
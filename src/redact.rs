@@ -0,0 +1,73 @@
+use ark_serialize::CanonicalSerialize;
+use std::fmt;
+
+/// secret value shown in `Debug` and `Display` as its type and length only,
+/// unless the `reveal-debug` feature is enabled for development
+pub(crate) struct Redacted<'a, T: fmt::Debug + ?Sized> {
+    #[cfg_attr(not(feature = "reveal-debug"), allow(dead_code))]
+    value: &'a T,
+    len: usize,
+}
+
+impl<'a, T: fmt::Debug + ?Sized> Redacted<'a, T> {
+    fn new(value: &'a T, len: usize) -> Self {
+        Self { value, len }
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for Redacted<'_, T> {
+    #[cfg(feature = "reveal-debug")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.value, f)
+    }
+
+    #[cfg(not(feature = "reveal-debug"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // type name without its path and generic parameters
+        let type_name = std::any::type_name::<T>();
+        let type_name = type_name.split('<').next().unwrap_or(type_name);
+        let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+        write!(f, "<redacted {}: {} bytes>", type_name, self.len)
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Display for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+pub(crate) fn redact_ark<T: CanonicalSerialize + fmt::Debug>(value: &T) -> Redacted<T> {
+    Redacted::new(value, value.compressed_size())
+}
+
+pub(crate) fn redact_bytes(value: &[u8]) -> Redacted<[u8]> {
+    Redacted::new(value, value.len())
+}
+
+pub(crate) fn redact_str(value: &str) -> Redacted<str> {
+    Redacted::new(value, value.len())
+}
+
+#[cfg(all(test, not(feature = "reveal-debug")))]
+mod tests {
+    use super::{redact_ark, redact_bytes, redact_str};
+    use crate::common::Fr;
+    use ark_std::One;
+
+    #[test]
+    fn redacted_debug_and_display() {
+        assert_eq!(
+            format!("{:?}", redact_bytes(b"SECRET")),
+            "<redacted [u8]: 6 bytes>"
+        );
+        assert_eq!(
+            format!("{}", redact_str("uSECRET")),
+            "<redacted str: 7 bytes>"
+        );
+        assert_eq!(
+            format!("{:?}", redact_ark(&Fr::one())),
+            "<redacted Fp: 32 bytes>"
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use crate::{
+    common::{ark_to_base64url, multibase_to_ark, ProvingKey, VerifyingKey, R1CS},
+    error::RDFProofsError,
+    predicate::{Circuit, CircuitString},
+    predicate_graph::{PredicateGraph, VariableRef},
+};
+use ark_std::rand::RngCore;
+use legogroth16::circom::CircomCircuit;
+use multibase::Base;
+use oxrdf::{NamedNode, Term};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// manifest of a predicate registry shared by wallets and verifiers, e.g.,
+///
+/// ```json
+/// {
+///   "predicates": [{
+///     "circuit": "https://zkp-ld.org/circuit/lessThanPrvPub",
+///     "private": ["lesser"],
+///     "public": ["greater"],
+///     "inputBitLengths": {"lesser": 64, "greater": 64},
+///     "artifacts": {
+///       "r1cs": {"url": "https://example.org/less_than_prv_pub_64.r1cs", "digest": "u..."},
+///       "wasm": {"url": "https://example.org/less_than_prv_pub_64.wasm", "digest": "u..."},
+///       "provingKey": {"url": "https://example.org/less_than_prv_pub_64.pk", "digest": "u..."},
+///       "verifyingKey": {"url": "https://example.org/less_than_prv_pub_64.vk", "digest": "u..."}
+///     }
+///   }]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PredicateRegistryManifest {
+    pub predicates: Vec<PredicateTemplate>,
+}
+
+/// predicate template keyed by circuit IRI
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PredicateTemplate {
+    pub circuit: String,
+    /// names of private variables bound to hidden terms
+    #[serde(default)]
+    pub private: Vec<String>,
+    /// names of public variables
+    #[serde(default)]
+    pub public: Vec<String>,
+    #[serde(default)]
+    pub input_bit_lengths: HashMap<String, u32>,
+    pub artifacts: CircuitArtifacts,
+}
+
+/// circuit artifacts; verifiers can omit the prover-only ones and vice versa
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitArtifacts {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r1cs: Option<ArtifactRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<ArtifactRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proving_key: Option<ArtifactRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifying_key: Option<ArtifactRef>,
+}
+
+/// location of an artifact with its digest given by `artifact_digest`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRef {
+    pub url: String,
+    pub digest: String,
+}
+
+/// digest of a multibase-encoded artifact, i.e., multibase (base64url)-encoded SHA-256 hash
+/// of the decoded bytes so that it does not depend on the multibase encoding
+pub fn artifact_digest(artifact: &str) -> Result<String, RDFProofsError> {
+    let (_, bytes) = multibase::decode(artifact)?;
+    Ok(multibase::encode(Base::Base64Url, Sha256::digest(bytes)))
+}
+
+/// predicate templates and their artifacts loaded from registry manifests,
+/// whose digests are validated
+#[derive(Clone, Debug, Default)]
+pub struct PredicateRegistry {
+    templates: BTreeMap<String, PredicateTemplate>,
+    // multibase-encoded artifacts keyed by digests
+    artifacts: HashMap<String, String>,
+}
+
+impl PredicateRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// load predicate templates from JSON `manifest`, where `fetch` gets the multibase-encoded
+    /// artifact for each URL, e.g., over HTTP or from a local cache
+    pub fn load<F>(manifest: &str, fetch: F) -> Result<Self, RDFProofsError>
+    where
+        F: FnMut(&str) -> Result<String, RDFProofsError>,
+    {
+        let manifest: PredicateRegistryManifest = serde_json::from_str(manifest)?;
+        Self::load_manifest(manifest, fetch)
+    }
+
+    pub fn load_manifest<F>(
+        manifest: PredicateRegistryManifest,
+        mut fetch: F,
+    ) -> Result<Self, RDFProofsError>
+    where
+        F: FnMut(&str) -> Result<String, RDFProofsError>,
+    {
+        let mut registry = Self::new();
+        for template in manifest.predicates {
+            validate_template(&template)?;
+            for artifact_ref in template.artifacts.iter() {
+                if registry.artifacts.contains_key(&artifact_ref.digest) {
+                    continue;
+                }
+                let artifact = fetch(&artifact_ref.url)?;
+                if artifact_digest(&artifact)? != artifact_ref.digest {
+                    return Err(RDFProofsError::PredicateArtifactDigestMismatch(
+                        artifact_ref.url.clone(),
+                    ));
+                }
+                registry
+                    .artifacts
+                    .insert(artifact_ref.digest.clone(), artifact);
+            }
+            if registry.templates.contains_key(&template.circuit) {
+                return Err(RDFProofsError::ConflictingPredicateTemplate(
+                    template.circuit,
+                ));
+            }
+            registry
+                .templates
+                .insert(template.circuit.clone(), template);
+        }
+        Ok(registry)
+    }
+
+    /// merge templates from another registry; templates for the same circuit must be identical
+    pub fn merge(&mut self, other: PredicateRegistry) -> Result<(), RDFProofsError> {
+        for (circuit, template) in &other.templates {
+            if self.templates.get(circuit).is_some_and(|t| t != template) {
+                return Err(RDFProofsError::ConflictingPredicateTemplate(
+                    circuit.clone(),
+                ));
+            }
+        }
+        self.templates.extend(other.templates);
+        self.artifacts.extend(other.artifacts);
+        Ok(())
+    }
+
+    pub fn templates(&self) -> impl Iterator<Item = &PredicateTemplate> {
+        self.templates.values()
+    }
+
+    pub fn get_template(&self, circuit: &str) -> Option<&PredicateTemplate> {
+        self.templates.get(circuit)
+    }
+
+    /// circuits with prover artifacts to be given to `derive_proof_string`
+    pub fn circuit_strings(&self) -> HashMap<String, CircuitString> {
+        self.templates
+            .iter()
+            .filter_map(|(circuit, template)| {
+                let CircuitArtifacts {
+                    r1cs: Some(r1cs),
+                    wasm: Some(wasm),
+                    proving_key: Some(proving_key),
+                    ..
+                } = &template.artifacts
+                else {
+                    return None;
+                };
+                Some((
+                    circuit.clone(),
+                    CircuitString {
+                        circuit_r1cs: self.artifacts.get(&r1cs.digest)?.clone(),
+                        circuit_wasm: self.artifacts.get(&wasm.digest)?.clone(),
+                        snark_proving_key: self.artifacts.get(&proving_key.digest)?.clone(),
+                        input_bit_lengths: template.input_bit_lengths.clone(),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// circuits with prover artifacts to be given to `derive_proof`
+    pub fn circuits(&self) -> Result<HashMap<NamedNode, Circuit>, RDFProofsError> {
+        self.circuit_strings()
+            .into_iter()
+            .map(|(circuit_id, circuit_str)| {
+                let circuit = Circuit::new(
+                    &circuit_str.circuit_r1cs,
+                    &circuit_str.circuit_wasm,
+                    &circuit_str.snark_proving_key,
+                    circuit_str.input_bit_lengths,
+                )?;
+                Ok((NamedNode::new(circuit_id)?, circuit))
+            })
+            .collect()
+    }
+
+    /// SNARK verifying keys to be given to `verify_proof_string`
+    pub fn snark_verifying_key_strings(&self) -> HashMap<String, String> {
+        self.templates
+            .iter()
+            .filter_map(|(circuit, template)| {
+                let vk = template.artifacts.verifying_key.as_ref()?;
+                Some((circuit.clone(), self.artifacts.get(&vk.digest)?.clone()))
+            })
+            .collect()
+    }
+
+    /// SNARK verifying keys to be given to `verify_proof`
+    pub fn snark_verifying_keys(&self) -> Result<HashMap<NamedNode, VerifyingKey>, RDFProofsError> {
+        self.snark_verifying_key_strings()
+            .into_iter()
+            .map(|(circuit_id, vk)| Ok((NamedNode::new(circuit_id)?, multibase_to_ark(&vk)?)))
+            .collect()
+    }
+}
+
+impl CircuitArtifacts {
+    fn iter(&self) -> impl Iterator<Item = &ArtifactRef> {
+        [
+            &self.r1cs,
+            &self.wasm,
+            &self.proving_key,
+            &self.verifying_key,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// predicate request generated by verifier with fresh SNARK keys, bundling the predicate graph
+/// and the proving key to be sent to the prover, and the verifying key to be retained by verifier
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PredicateRequest {
+    pub circuit: String,
+    /// predicate graph in N-Triples to be given to `derive_proof_string`
+    pub predicate: String,
+    /// multibase-encoded proving key to be given to the prover along with `predicate`
+    pub proving_key: String,
+    /// multibase-encoded verifying key to be retained by verifier
+    pub verifying_key: String,
+    /// digest of `verifying_key` given by `artifact_digest`
+    pub verifying_key_digest: String,
+    #[serde(default)]
+    pub input_bit_lengths: HashMap<String, u32>,
+}
+
+impl PredicateRequest {
+    /// circuit to be given to `derive_proof_string` with the prover's own R1CS and wasm
+    pub fn circuit_string(&self, r1cs: &str, wasm: &str) -> CircuitString {
+        CircuitString {
+            circuit_r1cs: r1cs.to_string(),
+            circuit_wasm: wasm.to_string(),
+            snark_proving_key: self.proving_key.clone(),
+            input_bit_lengths: self.input_bit_lengths.clone(),
+        }
+    }
+
+    /// verifying key to be given to `verify_proof`, checked against its digest
+    pub fn snark_verifying_key(&self) -> Result<VerifyingKey, RDFProofsError> {
+        if artifact_digest(&self.verifying_key)? != self.verifying_key_digest {
+            return Err(RDFProofsError::PredicateArtifactDigestMismatch(
+                self.circuit.clone(),
+            ));
+        }
+        multibase_to_ark(&self.verifying_key)
+    }
+}
+
+/// generate predicate request applying the circuit of `template` to the hidden terms `private`
+/// and the terms `public`, given in the order of the variables declared in `template`,
+/// with fresh SNARK keys set up for the multibase-encoded `r1cs` of the circuit,
+/// so that verifiers need not share the keys out-of-band
+pub fn generate_predicate_request<R: RngCore>(
+    rng: &mut R,
+    template: &PredicateTemplate,
+    r1cs: &str,
+    private: &[VariableRef],
+    public: &[Term],
+) -> Result<PredicateRequest, RDFProofsError> {
+    validate_template(template)?;
+    if private.len() != template.private.len() || public.len() != template.public.len() {
+        return Err(RDFProofsError::InvalidCircuitInput(format!(
+            "{} private and {} public inputs are expected for {}",
+            template.private.len(),
+            template.public.len(),
+            template.circuit
+        )));
+    }
+    if let Some(r1cs_ref) = &template.artifacts.r1cs {
+        if artifact_digest(r1cs)? != r1cs_ref.digest {
+            return Err(RDFProofsError::PredicateArtifactDigestMismatch(
+                r1cs_ref.url.clone(),
+            ));
+        }
+    }
+
+    let predicate = template.private.iter().zip(private).fold(
+        PredicateGraph::new_string(&template.circuit)?,
+        |predicate, (var, val)| predicate.private(var, val.clone()),
+    );
+    let predicate = template
+        .public
+        .iter()
+        .zip(public)
+        .fold(predicate, |predicate, (var, val)| {
+            predicate.public(var, val.clone())
+        });
+
+    // every private input is committed so that it can be bound to hidden terms
+    let r1cs: R1CS = multibase_to_ark(r1cs)?;
+    let proving_key: ProvingKey = CircomCircuit::setup(r1cs)
+        .generate_proving_key(private.len() as u32, rng)
+        .map_err(|e| RDFProofsError::Other(format!("{:?}", e)))?;
+    let verifying_key = ark_to_base64url(&proving_key.vk)?;
+
+    Ok(PredicateRequest {
+        circuit: template.circuit.clone(),
+        predicate: predicate.to_ntriples(),
+        proving_key: ark_to_base64url(&proving_key)?,
+        verifying_key_digest: artifact_digest(&verifying_key)?,
+        verifying_key,
+        input_bit_lengths: template.input_bit_lengths.clone(),
+    })
+}
+
+// circuit must be an IRI and bit lengths must be given for the declared variables
+fn validate_template(template: &PredicateTemplate) -> Result<(), RDFProofsError> {
+    NamedNode::new(&template.circuit)?;
+    let mut variables = HashSet::new();
+    for var in template.private.iter().chain(template.public.iter()) {
+        if !variables.insert(var) {
+            return Err(RDFProofsError::InvalidPredicateTemplate(format!(
+                "duplicate variable `{}` in {}",
+                var, template.circuit
+            )));
+        }
+    }
+    if let Some(var) = template
+        .input_bit_lengths
+        .keys()
+        .find(|var| !variables.contains(var))
+    {
+        return Err(RDFProofsError::InvalidPredicateTemplate(format!(
+            "bit length is given for undeclared variable `{}` in {}",
+            var, template.circuit
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        artifact_digest, generate_predicate_request, ArtifactRef, CircuitArtifacts,
+        PredicateRegistry, PredicateRequest, PredicateTemplate,
+    };
+    use crate::{
+        common::{ark_to_base64url, R1CS},
+        error::RDFProofsError,
+        predicate_graph::{PredicateGraph, VariableRef},
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use oxrdf::{vocab::xsd, Literal, Term};
+    use std::collections::HashMap;
+
+    const CIRCUIT: &str = "https://zkp-ld.org/circuit/lessThanPrvPub";
+
+    fn get_manifest(artifacts: &HashMap<&str, &str>) -> String {
+        let digest = |url: &str| artifact_digest(artifacts[url]).unwrap();
+        format!(
+            r#"{{
+  "predicates": [{{
+    "circuit": "{CIRCUIT}",
+    "private": ["lesser"],
+    "public": ["greater"],
+    "inputBitLengths": {{"lesser": 64, "greater": 64}},
+    "artifacts": {{
+      "r1cs": {{"url": "https://example.org/r1cs", "digest": "{}"}},
+      "wasm": {{"url": "https://example.org/wasm", "digest": "{}"}},
+      "provingKey": {{"url": "https://example.org/pk", "digest": "{}"}},
+      "verifyingKey": {{"url": "https://example.org/vk", "digest": "{}"}}
+    }}
+  }}]
+}}"#,
+            digest("https://example.org/r1cs"),
+            digest("https://example.org/wasm"),
+            digest("https://example.org/pk"),
+            digest("https://example.org/vk"),
+        )
+    }
+
+    #[test]
+    fn load_predicate_registry() {
+        let artifacts = HashMap::from([
+            ("https://example.org/r1cs", "uAAEC"),
+            ("https://example.org/wasm", "uAwQF"),
+            ("https://example.org/pk", "uBgcI"),
+            ("https://example.org/vk", "uCQoL"),
+        ]);
+        let manifest = get_manifest(&artifacts);
+        let fetch =
+            |url: &str| -> Result<String, RDFProofsError> { Ok(artifacts[url].to_string()) };
+
+        let mut registry = PredicateRegistry::load(&manifest, fetch).unwrap();
+        let circuits = registry.circuit_strings();
+        assert_eq!(circuits[CIRCUIT].circuit_r1cs, "uAAEC");
+        assert_eq!(circuits[CIRCUIT].input_bit_lengths["lesser"], 64);
+        assert_eq!(registry.snark_verifying_key_strings()[CIRCUIT], "uCQoL");
+
+        // the same artifact in another multibase encoding has the same digest
+        let fetch_base58 = |url: &str| -> Result<String, RDFProofsError> {
+            let (_, bytes) = multibase::decode(artifacts[url]).unwrap();
+            Ok(multibase::encode(multibase::Base::Base58Btc, bytes))
+        };
+        let same = PredicateRegistry::load(&manifest, fetch_base58).unwrap();
+        assert!(registry.merge(same).is_ok());
+
+        // tampered artifact
+        let tampered = |url: &str| -> Result<String, RDFProofsError> {
+            Ok(if url == "https://example.org/wasm" {
+                "uAwQG".to_string()
+            } else {
+                artifacts[url].to_string()
+            })
+        };
+        assert!(matches!(
+            PredicateRegistry::load(&manifest, tampered),
+            Err(RDFProofsError::PredicateArtifactDigestMismatch(url)) if url == "https://example.org/wasm"
+        ));
+
+        // conflicting template for the same circuit
+        let conflicting = PredicateRegistry::load(
+            &manifest.replace(r#""lesser": 64"#, r#""lesser": 32"#),
+            fetch,
+        )
+        .unwrap();
+        assert!(matches!(
+            registry.merge(conflicting),
+            Err(RDFProofsError::ConflictingPredicateTemplate(_))
+        ));
+
+        // bit length for undeclared variable
+        assert!(matches!(
+            PredicateRegistry::load(
+                &manifest.replace(r#""greater": 64"#, r#""other": 64"#),
+                fetch
+            ),
+            Err(RDFProofsError::InvalidPredicateTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn generate_predicate_request_with_fresh_keys() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let r1cs = R1CS::from_file("circom/bls12381/less_than_prv_pub_64.r1cs").unwrap();
+        let r1cs = ark_to_base64url(&r1cs).unwrap();
+        let template = PredicateTemplate {
+            circuit: CIRCUIT.to_string(),
+            private: vec!["lesser".to_string()],
+            public: vec!["greater".to_string()],
+            input_bit_lengths: HashMap::from([("lesser".to_string(), 64)]),
+            artifacts: CircuitArtifacts {
+                r1cs: Some(ArtifactRef {
+                    url: "https://example.org/r1cs".to_string(),
+                    digest: artifact_digest(&r1cs).unwrap(),
+                }),
+                wasm: None,
+                proving_key: None,
+                verifying_key: None,
+            },
+        };
+        let private = [VariableRef::new("e5").unwrap()];
+        let public = [Term::from(Literal::new_typed_literal(
+            "2022-12-31T00:00:00Z",
+            xsd::DATE_TIME,
+        ))];
+
+        let request =
+            generate_predicate_request(&mut rng, &template, &r1cs, &private, &public).unwrap();
+        let predicate = PredicateGraph::from_ntriples(&request.predicate).unwrap();
+        assert_eq!(predicate.circuit.as_str(), CIRCUIT);
+        assert_eq!(predicate.private[0].0, "lesser");
+        assert_eq!(predicate.public[0].0, "greater");
+        assert!(request.snark_verifying_key().is_ok());
+        assert_eq!(
+            request.circuit_string(&r1cs, "uAwQF").input_bit_lengths["lesser"],
+            64
+        );
+
+        // serializable bundle for sending the predicate and proving key to the prover
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PredicateRequest>(&json).unwrap(),
+            request
+        );
+
+        // keys are freshly generated for each request
+        let another =
+            generate_predicate_request(&mut rng, &template, &r1cs, &private, &public).unwrap();
+        assert_ne!(another.verifying_key_digest, request.verifying_key_digest);
+
+        // tampered verifying key
+        let mut tampered = request.clone();
+        tampered.verifying_key = another.verifying_key;
+        assert!(matches!(
+            tampered.snark_verifying_key(),
+            Err(RDFProofsError::PredicateArtifactDigestMismatch(_))
+        ));
+
+        // missing public input
+        assert!(matches!(
+            generate_predicate_request(&mut rng, &template, &r1cs, &private, &[]),
+            Err(RDFProofsError::InvalidCircuitInput(_))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::RDFProofsError;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// replay protection invoked by `verify_proof` after successful verification
+/// with the challenge, the domain, and the digest of the proof value of VP;
+/// implementations must atomically check and record them,
+/// returning `RDFProofsError::ReplayedPresentation` if they have been already seen.
+///
+/// `InMemoryReplayGuard` is sufficient for single-node verifiers;
+/// verifiers behind a load balancer can share the records among nodes
+/// by implementing this trait on top of a shared store, e.g., with Redis,
+/// `SET <key> <proof_value_digest> NX EX <ttl>` records the key only if absent,
+/// so that the presentation is replayed if the command returns nil
+pub trait ReplayGuard: Send + Sync {
+    fn check_and_record(
+        &self,
+        challenge: Option<&str>,
+        domain: Option<&str>,
+        proof_value_digest: &str,
+    ) -> Result<(), RDFProofsError>;
+}
+
+// VPs with challenge are identified by the challenge (and domain) so that
+// each challenge is accepted only once, otherwise by the proof value
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ReplayKey {
+    Challenge(String, Option<String>),
+    ProofValue(String),
+}
+
+/// in-memory replay guard, whose records expire after `ttl`;
+/// `ttl` should be longer than the lifetime of challenges issued by the verifier
+pub struct InMemoryReplayGuard {
+    ttl: Duration,
+    seen: Mutex<HashMap<ReplayKey, Instant>>,
+}
+
+impl InMemoryReplayGuard {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl ReplayGuard for InMemoryReplayGuard {
+    fn check_and_record(
+        &self,
+        challenge: Option<&str>,
+        domain: Option<&str>,
+        proof_value_digest: &str,
+    ) -> Result<(), RDFProofsError> {
+        let key = match challenge {
+            Some(challenge) => {
+                ReplayKey::Challenge(challenge.to_string(), domain.map(|d| d.to_string()))
+            }
+            None => ReplayKey::ProofValue(proof_value_digest.to_string()),
+        };
+
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, recorded_at| now.duration_since(*recorded_at) < self.ttl);
+        if seen.contains_key(&key) {
+            return Err(RDFProofsError::ReplayedPresentation);
+        }
+        seen.insert(key, now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InMemoryReplayGuard, ReplayGuard};
+    use crate::error::RDFProofsError;
+    use std::{thread::sleep, time::Duration};
+
+    #[test]
+    fn in_memory_replay_guard() {
+        let guard = InMemoryReplayGuard::new(Duration::from_millis(50));
+
+        assert!(guard
+            .check_and_record(Some("abcde"), Some("example.org"), "digest1")
+            .is_ok());
+        // the same challenge must not be reused even with another proof value
+        assert!(matches!(
+            guard.check_and_record(Some("abcde"), Some("example.org"), "digest2"),
+            Err(RDFProofsError::ReplayedPresentation)
+        ));
+        assert!(guard
+            .check_and_record(Some("abcde"), Some("example.com"), "digest3")
+            .is_ok());
+        // VPs without challenge are identified by the proof value
+        assert!(guard.check_and_record(None, None, "digest4").is_ok());
+        assert!(matches!(
+            guard.check_and_record(None, None, "digest4"),
+            Err(RDFProofsError::ReplayedPresentation)
+        ));
+
+        // records expire after TTL
+        sleep(Duration::from_millis(100));
+        assert!(guard
+            .check_and_record(Some("abcde"), Some("example.org"), "digest1")
+            .is_ok());
+        assert!(guard.check_and_record(None, None, "digest4").is_ok());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{
+    common::{get_dataset_from_nquads, hash_str_to_str, read_predicate_outputs},
+    constants::PPID_PREFIX,
+    context::{CIRCUIT, CREATED, HOLDER, ISSUER, PREDICATE_TYPE, VERIFIABLE_PRESENTATION_TYPE},
+    error::RDFProofsError,
+    proof_value::remove_proof_value,
+    vc::VerifiablePresentation,
+};
+use oxrdf::{vocab::rdf::TYPE, Dataset, TermRef};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// summary of verified VP to be forwarded to other systems, e.g., via webhooks,
+/// whose JCS (RFC 8785) serialization given by `to_jcs` is stable
+/// so that signatures over the report can be reproduced
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    /// issuers of the disclosed VCs, excluding hidden ones
+    pub issuers: Vec<String>,
+    /// disclosed triples of VCs in canonical N-Triples, where hidden terms are canonical blank nodes
+    pub claims: Vec<String>,
+    /// predicates proven in VP
+    pub predicates: Vec<PredicateReport>,
+    /// PPID of holder if presented
+    pub nym: Option<String>,
+    /// creation time of VP proof
+    pub created: Option<String>,
+    /// time of verification given by verifier
+    pub verified_at: String,
+    /// digest of VP proof value
+    pub proof_digest: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PredicateReport {
+    pub circuit: String,
+    /// declared circuit outputs as N-Triples literals
+    pub outputs: BTreeMap<String, String>,
+}
+
+impl VerificationReport {
+    /// serialize report in JSON Canonicalization Scheme (RFC 8785)
+    pub fn to_jcs(&self) -> Result<String, RDFProofsError> {
+        let mut jcs = String::new();
+        write_jcs(&serde_json::to_value(self)?, &mut jcs)?;
+        Ok(jcs)
+    }
+}
+
+/// build report of VP, which must be verified by `verify_proof` in advance
+pub fn verification_report(
+    vp_dataset: &Dataset,
+    verified_at: &str,
+) -> Result<VerificationReport, RDFProofsError> {
+    let vp: VerifiablePresentation = vp_dataset.try_into()?;
+    let proof_digest = hash_str_to_str(&vp.get_proof_value()?);
+    let created = vp.get_proof_config_literal(CREATED)?;
+
+    // canonicalize VP without proof value so that claims do not depend on blank node labels
+    let vp_without_proof_value = remove_proof_value(vp_dataset, &vp);
+    let c14n_map = rdf_canon::issue(&vp_without_proof_value)?;
+    let canonicalized_vp = rdf_canon::relabel(&vp_without_proof_value, &c14n_map)?;
+    let VerifiablePresentation {
+        metadata,
+        predicates: predicate_graphs,
+        disclosed_vcs,
+        ..
+    } = (&canonicalized_vp).try_into()?;
+
+    let mut issuers = BTreeSet::new();
+    let mut claims = BTreeSet::new();
+    for vc in disclosed_vcs.values() {
+        for triple in vc.document.iter() {
+            if let TermRef::NamedNode(issuer) = triple.object {
+                if triple.predicate == ISSUER {
+                    issuers.insert(issuer.as_str().to_string());
+                }
+            }
+            claims.insert(format!("{} .", triple));
+        }
+    }
+
+    let mut predicates = vec![];
+    for (_, predicate_graph) in predicate_graphs {
+        let predicate_subject = predicate_graph
+            .subject_for_predicate_object(TYPE, PREDICATE_TYPE)
+            .ok_or(RDFProofsError::InvalidPredicate)?;
+        let Some(TermRef::NamedNode(circuit)) =
+            predicate_graph.object_for_subject_predicate(predicate_subject, CIRCUIT)
+        else {
+            return Err(RDFProofsError::InvalidPredicate);
+        };
+        let outputs = read_predicate_outputs(predicate_subject, &predicate_graph)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(var, value)| (var, value.to_string()))
+            .collect();
+        predicates.push(PredicateReport {
+            circuit: circuit.as_str().to_string(),
+            outputs,
+        });
+    }
+
+    let vp_subject = metadata
+        .subject_for_predicate_object(TYPE, VERIFIABLE_PRESENTATION_TYPE)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let nym = match metadata.object_for_subject_predicate(vp_subject, HOLDER) {
+        Some(TermRef::NamedNode(n)) if n.as_str().starts_with(PPID_PREFIX) => {
+            Some(n.as_str().to_string())
+        }
+        _ => None,
+    };
+
+    Ok(VerificationReport {
+        issuers: issuers.into_iter().collect(),
+        claims: claims.into_iter().collect(),
+        predicates,
+        nym,
+        created,
+        verified_at: verified_at.to_string(),
+        proof_digest,
+    })
+}
+
+pub fn verification_report_string(
+    vp: &str,
+    verified_at: &str,
+) -> Result<VerificationReport, RDFProofsError> {
+    let vp = get_dataset_from_nquads(vp)?;
+    verification_report(&vp, verified_at)
+}
+
+// object members are sorted by UTF-16 code units and strings are escaped as in ECMAScript,
+// which serde_json complies with; floating-point numbers do not appear in reports
+fn write_jcs(value: &Value, jcs: &mut String) -> Result<(), RDFProofsError> {
+    match value {
+        Value::Array(values) => {
+            jcs.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    jcs.push(',');
+                }
+                write_jcs(v, jcs)?;
+            }
+            jcs.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<_> = members.iter().collect();
+            members.sort_by_cached_key(|(k, _)| k.encode_utf16().collect::<Vec<_>>());
+            jcs.push('{');
+            for (i, (k, v)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    jcs.push(',');
+                }
+                jcs.push_str(&serde_json::to_string(k)?);
+                jcs.push(':');
+                write_jcs(v, jcs)?;
+            }
+            jcs.push('}');
+        }
+        Value::Number(n) if n.is_f64() => {
+            return Err(RDFProofsError::Other(
+                "floating-point numbers are not supported in JCS".to_string(),
+            ))
+        }
+        _ => jcs.push_str(&serde_json::to_string(value)?),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_jcs;
+    use serde_json::json;
+
+    #[test]
+    fn write_jcs_success() {
+        let value = json!({
+            "b": [1, "\u{20ac}", {"z": null, "a": true}],
+            "a\n": "\"\u{1f}",
+            "\u{e000}": 0,
+            "\u{1f600}": 1,
+        });
+        let mut jcs = String::new();
+        write_jcs(&value, &mut jcs).unwrap();
+        // U+1F600 (surrogate pair D83D DE00) precedes U+E000 in UTF-16 order
+        assert_eq!(
+            jcs,
+            "{\"a\\n\":\"\\\"\\u001f\",\"b\":[1,\"\u{20ac}\",{\"a\":true,\"z\":null}],\"\u{1f600}\":1,\"\u{e000}\":0}"
+        );
+    }
+}
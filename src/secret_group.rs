@@ -0,0 +1,262 @@
+use crate::{
+    common::BlankNodeLabeler,
+    context::{
+        BOUND_CREDENTIAL, DATA_INTEGRITY_PROOF, ISSUER, SECRET_GROUP, VERIFIABLE_CREDENTIAL_TYPE,
+    },
+    error::RDFProofsError,
+    metadata::get_proof_metadata,
+    ordered_triple::OrderedVerifiableCredentialGraphViews,
+    redact::redact_bytes,
+    vc::{VcPair, VerifiableCredential},
+};
+use oxrdf::{
+    dataset::GraphView, vocab::rdf::TYPE, BlankNode, Dataset, GraphNameRef, NamedNode,
+    NamedNodeRef, QuadRef, TermRef,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// secrets bound to individual VCs, for holders using distinct secrets per issuer ecosystem,
+/// given by VC index or issuer IRI where the former takes precedence;
+/// the other bound VCs are bound to the secret in `HolderBindingPolicy`,
+/// which remains the designated secret for PPID, audit, secret commitment, and device binding
+#[derive(Clone, Default)]
+pub struct CredentialSecrets<'a> {
+    by_index: BTreeMap<usize, &'a [u8]>,
+    by_issuer: BTreeMap<NamedNode, &'a [u8]>,
+}
+
+// secrets are redacted in logs
+impl std::fmt::Debug for CredentialSecrets<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CredentialSecrets")
+            .field(
+                "by_index",
+                &self
+                    .by_index
+                    .iter()
+                    .map(|(i, secret)| (i, redact_bytes(secret)))
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "by_issuer",
+                &self
+                    .by_issuer
+                    .iter()
+                    .map(|(issuer, secret)| (issuer, redact_bytes(secret)))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<'a> CredentialSecrets<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// bind the VC at `vc_index` in `vc_pairs` to `secret`
+    pub fn insert(&mut self, vc_index: usize, secret: &'a [u8]) {
+        self.by_index.insert(vc_index, secret);
+    }
+
+    /// bind the VCs issued by `issuer` to `secret`
+    pub fn insert_for_issuer(&mut self, issuer: NamedNode, secret: &'a [u8]) {
+        self.by_issuer.insert(issuer, secret);
+    }
+
+    /// `insert_for_issuer` with the issuer IRI without angle brackets, e.g., `did:example:issuer0`
+    pub fn insert_for_issuer_string(
+        &mut self,
+        issuer: &str,
+        secret: &'a [u8],
+    ) -> Result<(), RDFProofsError> {
+        self.insert_for_issuer(NamedNode::new(issuer)?, secret);
+        Ok(())
+    }
+
+    // secret bound to each VC, falling back to the holder's designated secret
+    pub(crate) fn get_vc_secrets(
+        &self,
+        vc_pairs: &[VcPair],
+        secret: Option<&'a [u8]>,
+    ) -> Result<Vec<Option<&'a [u8]>>, RDFProofsError> {
+        if let Some(vc_index) = self.by_index.keys().find(|i| **i >= vc_pairs.len()) {
+            return Err(RDFProofsError::InvalidCredentialSecrets(format!(
+                "VC index {} is out of range",
+                vc_index
+            )));
+        }
+        Ok(vc_pairs
+            .iter()
+            .enumerate()
+            .map(|(i, VcPair { original, .. })| {
+                self.by_index
+                    .get(&i)
+                    .or_else(|| {
+                        get_issuer(original)
+                            .and_then(|issuer| self.by_issuer.get(&issuer.into_owned()))
+                    })
+                    .copied()
+                    .or(secret)
+            })
+            .collect())
+    }
+}
+
+fn get_issuer(vc: &VerifiableCredential) -> Option<NamedNodeRef> {
+    let vc_id = vc
+        .document
+        .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)?;
+    match vc.document.object_for_subject_predicate(vc_id, ISSUER) {
+        Some(TermRef::NamedNode(issuer)) => Some(issuer),
+        _ => None,
+    }
+}
+
+// indexes of bound VCs grouped by their secrets other than the holder's designated secret
+pub(crate) fn get_secret_group_indexes(
+    vc_secrets: &[Option<&[u8]>],
+    is_bounds: &[bool],
+    secret: Option<&[u8]>,
+) -> Vec<Vec<usize>> {
+    let mut groups: BTreeMap<&[u8], Vec<usize>> = BTreeMap::new();
+    for (i, (vc_secret, is_bound)) in vc_secrets.iter().zip(is_bounds).enumerate() {
+        match vc_secret {
+            Some(vc_secret) if *is_bound && Some(*vc_secret) != secret => {
+                groups.entry(vc_secret).or_default().push(i)
+            }
+            _ => (),
+        }
+    }
+    groups.into_values().collect()
+}
+
+// add groups of VCs bound to the same per-credential secret, referring to VC document graphs
+pub(crate) fn insert_secret_groups(
+    vp: &mut Dataset,
+    vp_proof_id: &BlankNode,
+    vp_proof_graph_id: &BlankNode,
+    secret_groups: &[Vec<usize>],
+    vc_document_graph_names: &[BlankNode],
+    labeler: &mut BlankNodeLabeler,
+) -> Result<(), RDFProofsError> {
+    for group in secret_groups {
+        let group_id = labeler.generate();
+        vp.insert(QuadRef::new(
+            vp_proof_id,
+            SECRET_GROUP,
+            &group_id,
+            vp_proof_graph_id,
+        ));
+        for i in group {
+            let graph_name = vc_document_graph_names.get(*i).ok_or_else(|| {
+                RDFProofsError::InvalidCredentialSecrets(format!("VC index {} is out of range", i))
+            })?;
+            vp.insert(QuadRef::new(
+                &group_id,
+                BOUND_CREDENTIAL,
+                graph_name,
+                vp_proof_graph_id,
+            ));
+        }
+    }
+    Ok(())
+}
+
+// get groups of VC indexes bound to the same per-credential secret from canonicalized VP proof,
+// where each bound VC belongs to at most one group
+pub(crate) fn get_secret_groups(
+    vp_proof: &GraphView,
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+) -> Result<Vec<BTreeSet<usize>>, RDFProofsError> {
+    let proof_subject = vp_proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    let invalid = |msg: &str| RDFProofsError::InvalidCredentialSecrets(msg.to_string());
+
+    let mut grouped = BTreeSet::new();
+    let mut groups = vp_proof
+        .objects_for_subject_predicate(proof_subject, SECRET_GROUP)
+        .map(|group| {
+            let TermRef::BlankNode(group) = group else {
+                return Err(invalid("secret group must be a blank node"));
+            };
+            let indexes = vp_proof
+                .objects_for_subject_predicate(group, BOUND_CREDENTIAL)
+                .map(|credential| {
+                    let TermRef::BlankNode(credential) = credential else {
+                        return Err(invalid("bound credential must be a VC graph name"));
+                    };
+                    let (index, (_, vc)) = disclosed_vcs
+                        .iter()
+                        .enumerate()
+                        .find(|(_, (name, _))| {
+                            GraphNameRef::from((*name).clone()) == GraphNameRef::from(credential)
+                        })
+                        .ok_or_else(|| invalid("bound credential must be a VC graph name"))?;
+                    if !vc.is_bound()? {
+                        return Err(invalid("bound credential must be bound to secret"));
+                    }
+                    if !grouped.insert(index) {
+                        return Err(invalid("VC belongs to multiple secret groups"));
+                    }
+                    Ok(index)
+                })
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            if indexes.is_empty() {
+                return Err(invalid("secret group must not be empty"));
+            }
+            Ok(indexes)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    groups.sort();
+    Ok(groups)
+}
+
+// accept only groups whose VCs are all signed with verification methods in one of the sets
+// allowed by verifier, so that bound VCs are proven to share the holder's designated secret
+// unless verifier opts in to per-credential secrets
+pub(crate) fn validate_secret_groups(
+    secret_groups: &[BTreeSet<usize>],
+    disclosed_vcs: &OrderedVerifiableCredentialGraphViews,
+    allowed_secret_groups: &[BTreeSet<NamedNode>],
+) -> Result<(), RDFProofsError> {
+    let verification_methods = disclosed_vcs
+        .values()
+        .map(|vc| Ok(get_proof_metadata(&vc.proof)?.verification_method))
+        .collect::<Result<Vec<_>, RDFProofsError>>()?;
+    for group in secret_groups {
+        let group_verification_methods = group
+            .iter()
+            .map(|i| verification_methods.get(*i).and_then(Option::as_ref))
+            .collect::<Option<BTreeSet<_>>>();
+        let is_allowed = group_verification_methods.is_some_and(|methods| {
+            allowed_secret_groups
+                .iter()
+                .any(|allowed| methods.iter().all(|method| allowed.contains(*method)))
+        });
+        if !is_allowed {
+            return Err(RDFProofsError::InvalidCredentialSecrets(
+                "secret group is not allowed by verifier".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// split embedded secrets given as (statement index, witness index) into those bound to
+// the holder's designated secret and those in each group bound to a per-credential secret
+pub(crate) fn split_embedded_secrets(
+    embedded_secrets: Vec<(usize, usize)>,
+    secret_groups: &[BTreeSet<usize>],
+) -> (BTreeSet<(usize, usize)>, Vec<BTreeSet<(usize, usize)>>) {
+    let mut group_sets = vec![BTreeSet::new(); secret_groups.len()];
+    let mut designated_set = BTreeSet::new();
+    for (i, witness_index) in embedded_secrets {
+        match secret_groups.iter().position(|group| group.contains(&i)) {
+            Some(g) => group_sets[g].insert((i, witness_index)),
+            None => designated_set.insert((i, witness_index)),
+        };
+    }
+    (designated_set, group_sets)
+}
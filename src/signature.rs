@@ -1,27 +1,49 @@
 use crate::{
+    clock::{clock_or_system, Clock},
     common::{
-        ark_to_base64url, canonicalize_graph_into_terms, configure_proof_core, get_delimiter,
-        get_graph_from_ntriples, get_hasher, get_vc_from_ntriples,
-        get_verification_method_identifier, hash_byte_to_field, hash_terms_to_field,
-        multibase_to_ark, BBSPlusSignature, Fr,
+        ark_to_base64url, canonicalize_graph_into_terms, canonicalized_graph_into_terms,
+        configure_proof_core, get_delimiter, get_graph_from_ntriples, get_hasher,
+        get_vc_from_ntriples, get_verification_method_identifier, hash_byte_to_field,
+        hash_term_to_field_with_encoding, multibase_to_ark, BBSPlusDefaultFieldHasher,
+        BBSPlusSignature, Fr,
+    },
+    constants::{
+        CRYPTOSUITE_BOUND_SIGN, CRYPTOSUITE_BOUND_SIGN_2024, CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN,
+        CRYPTOSUITE_SCALED_DECIMAL_SIGN, CRYPTOSUITE_SIGN, CRYPTOSUITE_SIGN_2024,
+        CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN, CRYPTOSUITE_SPLIT_IRI_SIGN,
+    },
+    context::{
+        CRYPTOSUITE, DATA_INTEGRITY_PROOF, MULTIBASE, PROOF_VALUE, VERIFIABLE_CREDENTIAL_TYPE,
     },
-    constants::{CRYPTOSUITE_BOUND_SIGN, CRYPTOSUITE_SIGN},
-    context::{DATA_INTEGRITY_PROOF, MULTIBASE, PROOF_VALUE},
     error::RDFProofsError,
-    key_gen::generate_params,
-    key_graph::KeyGraph,
-    vc::VerifiableCredential,
+    key_gen::{generate_params_with_profile, get_params_profile},
+    key_graph::{check_key_usage, KeyGraph, KeyStore},
+    message_layout::{SecretSlot, TermEncoding},
+    namespace::hash_namespace_to_field,
+    precomputed_canonicalization::{
+        canonicalize_graph_with_precomputed, PrecomputedCanonicalization,
+    },
+    vc::{get_cryptosuite_layout, VerifiableCredential},
 };
 use ark_std::rand::RngCore;
-use oxrdf::{vocab::rdf::TYPE, Graph, LiteralRef, Term, TripleRef};
+use oxrdf::{vocab::rdf::TYPE, Graph, LiteralRef, Term, TermRef, TripleRef};
 
+/// sign the credential, where `created` is taken from `clock` (the system clock by default)
+/// unless given in proof options
 pub fn sign<R: RngCore>(
     rng: &mut R,
     unsecured_credential: &mut VerifiableCredential,
     key_graph: &KeyGraph,
     shared_secret: Option<&[u8]>,
+    clock: Option<&dyn Clock>,
 ) -> Result<(), RDFProofsError> {
-    let proof = sign_core(rng, unsecured_credential, key_graph, shared_secret)?;
+    let proof = sign_core(
+        rng,
+        unsecured_credential,
+        key_graph,
+        shared_secret,
+        clock_or_system(clock),
+    )?;
     unsecured_credential.proof = proof;
     Ok(())
 }
@@ -32,10 +54,17 @@ pub fn sign_string<R: RngCore>(
     proof_options: &str,
     key_graph: &str,
     shared_secret: Option<&[u8]>,
+    clock: Option<&dyn Clock>,
 ) -> Result<String, RDFProofsError> {
     let unsecured_credential = get_vc_from_ntriples(document, proof_options)?;
     let key_graph = get_graph_from_ntriples(key_graph)?.into();
-    let proof = sign_core(rng, &unsecured_credential, &key_graph, shared_secret)?;
+    let proof = sign_core(
+        rng,
+        &unsecured_credential,
+        &key_graph,
+        shared_secret,
+        clock_or_system(clock),
+    )?;
     let result: String = proof
         .iter()
         .map(|t| format!("{} .\n", t.to_string()))
@@ -48,18 +77,43 @@ fn sign_core<R: RngCore>(
     unsecured_credential: &VerifiableCredential,
     key_graph: &KeyGraph,
     shared_secret: Option<&[u8]>,
+    clock: &dyn Clock,
 ) -> Result<Graph, RDFProofsError> {
     let VerifiableCredential {
         document,
         proof: proof_option,
     } = unsecured_credential;
     let transformed_data = transform(document)?;
-    let proof_config = match shared_secret {
-        Some(_secret) => configure_proof_for_bound(&proof_option)?,
-        None => configure_proof(&proof_option)?,
+    let cryptosuite = match shared_secret {
+        Some(_secret) => select_cryptosuite(
+            &proof_option,
+            CRYPTOSUITE_BOUND_SIGN,
+            &[
+                CRYPTOSUITE_BOUND_SIGN_2024,
+                CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN,
+                CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN,
+            ],
+        ),
+        None => select_cryptosuite(
+            &proof_option,
+            CRYPTOSUITE_SIGN,
+            &[
+                CRYPTOSUITE_SIGN_2024,
+                CRYPTOSUITE_SPLIT_IRI_SIGN,
+                CRYPTOSUITE_SCALED_DECIMAL_SIGN,
+            ],
+        ),
     };
+    let proof_config = configure_proof_core(&proof_option, cryptosuite, clock)?;
+    let (_, secret_slot, term_encoding) = get_cryptosuite_layout(cryptosuite)?;
     let canonical_proof_config = transform(&proof_config)?;
-    let hash_data = hash(shared_secret, &transformed_data, &canonical_proof_config)?;
+    let hash_data = hash(
+        shared_secret,
+        &transformed_data,
+        &canonical_proof_config,
+        secret_slot,
+        term_encoding,
+    )?;
     let proof = serialize_proof(rng, &hash_data, &proof_config, key_graph)?;
     Ok(proof)
 }
@@ -67,15 +121,31 @@ fn sign_core<R: RngCore>(
 pub fn verify(
     secured_credential: &VerifiableCredential,
     key_graph: &KeyGraph,
+) -> Result<(), RDFProofsError> {
+    verify_with_key_store(secured_credential, key_graph)
+}
+
+/// `verify` with the issuer public keys in any key store
+pub(crate) fn verify_with_key_store(
+    secured_credential: &VerifiableCredential,
+    key_graph: &dyn KeyStore,
 ) -> Result<(), RDFProofsError> {
     let VerifiableCredential { document, .. } = secured_credential;
     let proof_config = secured_credential.get_proof_config();
     let proof_value = secured_credential.get_proof_value()?;
     // TODO: validate proof_config
+    let secret_slot = secured_credential.secret_slot()?;
+    let term_encoding = secured_credential.term_encoding()?;
     let transformed_data = transform(document)?;
     let canonical_proof_config = transform(&proof_config)?;
-    let hash_data = hash(None, &transformed_data, &canonical_proof_config)?;
-    verify_base_proof(hash_data, &proof_value, &proof_config, key_graph)
+    let hash_data = hash(
+        None,
+        &transformed_data,
+        &canonical_proof_config,
+        secret_slot,
+        term_encoding,
+    )?;
+    verify_base_proof(hash_data, &proof_value, document, &proof_config, key_graph)
 }
 
 pub fn verify_string(document: &str, proof: &str, key_graph: &str) -> Result<(), RDFProofsError> {
@@ -84,22 +154,63 @@ pub fn verify_string(document: &str, proof: &str, key_graph: &str) -> Result<(),
     verify(&vc, &key_graph)
 }
 
-pub(crate) fn transform(graph: &Graph) -> Result<Vec<Term>, RDFProofsError> {
-    canonicalize_graph_into_terms(graph)
+/// verify VC whose document is canonicalized with the labels precomputed by publisher,
+/// given the holder's secret if VC is bound
+pub(crate) fn verify_with_precomputed_canonicalization(
+    secret: Option<&[u8]>,
+    secured_credential: &VerifiableCredential,
+    precomputed: &PrecomputedCanonicalization,
+    key_graph: &KeyGraph,
+) -> Result<(), RDFProofsError> {
+    let VerifiableCredential { document, .. } = secured_credential;
+    let proof_config = secured_credential.get_proof_config();
+    let proof_value = secured_credential.get_proof_value()?;
+    let secret_slot = secured_credential.secret_slot()?;
+    let term_encoding = secured_credential.term_encoding()?;
+    let (canonicalized_document, _) =
+        canonicalize_graph_with_precomputed(document, Some(precomputed))?;
+    let transformed_data = canonicalized_graph_into_terms(&canonicalized_document);
+    let canonical_proof_config = transform(&proof_config)?;
+    let hash_data = hash(
+        secret,
+        &transformed_data,
+        &canonical_proof_config,
+        secret_slot,
+        term_encoding,
+    )?;
+    verify_base_proof(hash_data, &proof_value, document, &proof_config, key_graph)
 }
 
-fn configure_proof(proof_options: &Graph) -> Result<Graph, RDFProofsError> {
-    configure_proof_core(proof_options, CRYPTOSUITE_SIGN)
+pub(crate) fn transform(graph: &Graph) -> Result<Vec<Term>, RDFProofsError> {
+    canonicalize_graph_into_terms(graph)
 }
 
-fn configure_proof_for_bound(proof_options: &Graph) -> Result<Graph, RDFProofsError> {
-    configure_proof_core(proof_options, CRYPTOSUITE_BOUND_SIGN)
+// the cryptosuites with the secret-last layout or split IRIs are used only if requested
+// in proof options; any other given cryptosuite is rejected by `configure_proof_core`
+fn select_cryptosuite<'a>(
+    proof_options: &Graph,
+    default_cryptosuite: &'a str,
+    optional_cryptosuites: &[&'a str],
+) -> &'a str {
+    let given_cryptosuite = proof_options
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .and_then(|subject| proof_options.object_for_subject_predicate(subject, CRYPTOSUITE));
+    match given_cryptosuite {
+        Some(TermRef::Literal(v)) => optional_cryptosuites
+            .iter()
+            .find(|cryptosuite| **cryptosuite == v.value())
+            .copied()
+            .unwrap_or(default_cryptosuite),
+        _ => default_cryptosuite,
+    }
 }
 
 pub(crate) fn hash(
     secret: Option<&[u8]>,
     transformed_document: &Vec<Term>,
     canonical_proof_config: &Vec<Term>,
+    secret_slot: SecretSlot,
+    term_encoding: TermEncoding,
 ) -> Result<Vec<Fr>, RDFProofsError> {
     let hasher = get_hasher();
 
@@ -107,17 +218,42 @@ pub(crate) fn hash(
         Some(s) => hash_byte_to_field(s, &hasher)?,
         None => Fr::from(1),
     };
-    let mut hashed_document = hash_terms_to_field(transformed_document, &hasher)?;
-    let mut hashed_proof = hash_terms_to_field(canonical_proof_config, &hasher)?;
+    let mut hashed_document = hash_encoded_terms(transformed_document, term_encoding, &hasher)?;
+    let mut hashed_proof = hash_encoded_terms(canonical_proof_config, term_encoding, &hasher)?;
     let delimiter = get_delimiter()?;
 
-    let mut result =
-        Vec::with_capacity(transformed_document.len() + canonical_proof_config.len() + 1);
+    let mut result = Vec::with_capacity(hashed_document.len() + hashed_proof.len() + 2);
 
-    result.push(secret);
+    if secret_slot == SecretSlot::First {
+        result.push(secret);
+    }
     result.append(&mut hashed_document);
     result.push(delimiter);
     result.append(&mut hashed_proof);
+    if secret_slot == SecretSlot::Last {
+        result.push(secret);
+    }
+    Ok(result)
+}
+
+// hash terms into messages, each followed by the hash of its namespace if IRIs are split,
+// where decimals are scaled rather than hashed if the cryptosuite says so
+fn hash_encoded_terms(
+    terms: &[Term],
+    term_encoding: TermEncoding,
+    hasher: &BBSPlusDefaultFieldHasher,
+) -> Result<Vec<Fr>, RDFProofsError> {
+    let mut result = Vec::with_capacity(terms.len() * term_encoding.messages_per_term());
+    for term in terms {
+        result.push(hash_term_to_field_with_encoding(
+            term.as_ref(),
+            term_encoding,
+            hasher,
+        )?);
+        if term_encoding == TermEncoding::SplitIri {
+            result.push(hash_namespace_to_field(term.as_ref(), hasher)?);
+        }
+    }
     Ok(result)
 }
 
@@ -131,7 +267,8 @@ fn serialize_proof<R: RngCore>(
         .len()
         .try_into()
         .map_err(|_| RDFProofsError::MessageSizeOverflow)?;
-    let params = generate_params(message_count);
+    let params_profile = get_params_profile(proof_options)?;
+    let params = generate_params_with_profile(params_profile, message_count);
 
     let verification_method_identifier = get_verification_method_identifier(proof_options)?;
     let (secret_key, _public_key) = key_graph.get_keypair(verification_method_identifier)?;
@@ -155,13 +292,24 @@ fn serialize_proof<R: RngCore>(
 pub(crate) fn verify_base_proof(
     hash_data: Vec<Fr>,
     proof_value: &str,
+    document: &Graph,
     proof_config: &Graph,
-    key_graph: &KeyGraph,
+    key_graph: &dyn KeyStore,
 ) -> Result<(), RDFProofsError> {
     let signature: BBSPlusSignature = multibase_to_ark(proof_value)?;
     let verification_method_identifier = get_verification_method_identifier(proof_config)?;
     let pk = key_graph.get_public_key(verification_method_identifier)?;
-    let params = generate_params(
+    let credential_types = document
+        .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+        .map(|vc_id| {
+            document
+                .objects_for_subject_predicate(vc_id, TYPE)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_else(Vec::new);
+    check_key_usage(verification_method_identifier, credential_types, key_graph)?;
+    let params = generate_params_with_profile(
+        get_params_profile(proof_config)?,
         hash_data
             .len()
             .try_into()
@@ -172,12 +320,20 @@ pub(crate) fn verify_base_proof(
 
 #[cfg(test)]
 mod tests {
+    use super::{hash, transform};
+    #[cfg(feature = "blind")]
+    use crate::blind_verify;
     use crate::{
-        blind_verify,
-        common::{get_graph_from_ntriples, multibase_to_ark, BBSPlusSignature},
+        common::{
+            get_graph_from_ntriples, get_verification_method_identifier, multibase_to_ark,
+            BBSPlusSignature,
+        },
         context::PROOF_VALUE,
         error::RDFProofsError,
-        sign, sign_string, verify, verify_string, KeyGraph, VerifiableCredential,
+        key_gen::generate_params,
+        message_layout::{SecretSlot, TermEncoding},
+        sign, sign_string, supported_cryptosuites, verify, verify_string, KeyGraph,
+        VerifiableCredential,
     };
     use ark_std::rand::{rngs::StdRng, SeedableRng};
     use oxrdf::TermRef;
@@ -245,6 +401,13 @@ mod tests {
     _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
     _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
     "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_PARAMS_PROFILE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    _:b0 <https://zkp-ld.org/security#paramsProfile> "example-profile" .
+    "#;
     const VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_INVALID_CRYPTOSUITE: &str = r#"
     _:b0 <https://w3id.org/security#cryptosuite> "bbs-termwise-blind-signature-2023" . # invalid cryptosuite
     _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
@@ -321,13 +484,14 @@ mod tests {
         let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
         let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        sign(&mut rng, &mut vc, &key_graph, None).unwrap();
+        sign(&mut rng, &mut vc, &key_graph, None, None).unwrap();
         println!("vc: {}", vc);
         print_signature(&vc);
         assert!(verify(&vc, &key_graph).is_ok())
     }
 
     #[test]
+    #[cfg(feature = "blind")]
     fn sign_with_shared_secret_and_blind_verify() {
         let mut rng = StdRng::seed_from_u64(0u64);
 
@@ -336,7 +500,7 @@ mod tests {
         let proof_config = get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
         let secret = b"SECRET";
-        sign(&mut rng, &mut vc, &key_graph, Some(secret)).unwrap();
+        sign(&mut rng, &mut vc, &key_graph, Some(secret), None).unwrap();
         println!("vc: {}", vc);
         print_signature(&vc);
         assert!(blind_verify(secret, &vc, &key_graph).is_ok())
@@ -351,7 +515,7 @@ mod tests {
         let proof_config =
             get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_AND_DATETIME_1).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        sign(&mut rng, &mut vc, &key_graph, None).unwrap();
+        sign(&mut rng, &mut vc, &key_graph, None, None).unwrap();
         println!("vc: {}", vc);
         print_signature(&vc);
         assert!(verify(&vc, &key_graph).is_ok())
@@ -366,10 +530,82 @@ mod tests {
         let proof_config =
             get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_CRYPTOSUITE).unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        sign(&mut rng, &mut vc, &key_graph, None).unwrap();
+        sign(&mut rng, &mut vc, &key_graph, None, None).unwrap();
         assert!(verify(&vc, &key_graph).is_ok())
     }
 
+    #[test]
+    fn sign_and_verify_with_secret_last_cryptosuite_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
+        let proof_config = get_graph_from_ntriples(
+            &VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_CRYPTOSUITE
+                .replace("signature-2023", "signature-2024"),
+        )
+        .unwrap();
+        let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
+        sign(&mut rng, &mut vc, &key_graph, None, None).unwrap();
+        assert_eq!(vc.secret_slot().unwrap(), SecretSlot::Last);
+        assert!(verify(&vc, &key_graph).is_ok());
+
+        // the signature is not valid under the secret-first layout
+        let signature: BBSPlusSignature = multibase_to_ark(&vc.get_proof_value().unwrap()).unwrap();
+        let proof_config = vc.get_proof_config();
+        let hash_data = hash(
+            None,
+            &transform(&vc.document).unwrap(),
+            &transform(&proof_config).unwrap(),
+            SecretSlot::First,
+            TermEncoding::Termwise,
+        )
+        .unwrap();
+        let pk = key_graph
+            .get_public_key(get_verification_method_identifier(&proof_config).unwrap())
+            .unwrap();
+        let message_count = hash_data.len().try_into().unwrap();
+        assert!(signature
+            .verify(&hash_data, pk, generate_params(message_count))
+            .is_err());
+    }
+
+    #[test]
+    fn sign_and_verify_with_split_iri_cryptosuite_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
+        let proof_config = get_graph_from_ntriples(
+            &VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_CRYPTOSUITE
+                .replace("signature-2023", "split-iri-signature-2024"),
+        )
+        .unwrap();
+        let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
+        sign(&mut rng, &mut vc, &key_graph, None, None).unwrap();
+        assert_eq!(vc.term_encoding().unwrap(), TermEncoding::SplitIri);
+        assert!(verify(&vc, &key_graph).is_ok());
+
+        // the signature is not valid under the termwise encoding
+        let signature: BBSPlusSignature = multibase_to_ark(&vc.get_proof_value().unwrap()).unwrap();
+        let proof_config = vc.get_proof_config();
+        let hash_data = hash(
+            None,
+            &transform(&vc.document).unwrap(),
+            &transform(&proof_config).unwrap(),
+            SecretSlot::Last,
+            TermEncoding::Termwise,
+        )
+        .unwrap();
+        let pk = key_graph
+            .get_public_key(get_verification_method_identifier(&proof_config).unwrap())
+            .unwrap();
+        let message_count = hash_data.len().try_into().unwrap();
+        assert!(signature
+            .verify(&hash_data, pk, generate_params(message_count))
+            .is_err());
+    }
+
     #[test]
     fn sign_and_verify_with_invalid_cryptosuite_failure() {
         let mut rng = StdRng::seed_from_u64(0u64);
@@ -380,10 +616,60 @@ mod tests {
             get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_INVALID_CRYPTOSUITE)
                 .unwrap();
         let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
-        let result = sign(&mut rng, &mut vc, &key_graph, None);
+        let result = sign(&mut rng, &mut vc, &key_graph, None, None);
         assert!(result.is_err())
     }
 
+    #[test]
+    fn sign_and_verify_with_params_profile_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
+        let proof_config =
+            get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_PARAMS_PROFILE).unwrap();
+        let mut vc = VerifiableCredential::new(unsecured_document, proof_config);
+        sign(&mut rng, &mut vc, &key_graph, None, None).unwrap();
+        print_signature(&vc);
+        assert!(verify(&vc, &key_graph).is_ok());
+
+        // the signature is not valid under the shared params
+        let signature = vc.get_proof_value().unwrap();
+        let proof_config = vc.get_proof_config();
+        let hash_data = hash(
+            None,
+            &transform(&vc.document).unwrap(),
+            &transform(&proof_config).unwrap(),
+            SecretSlot::First,
+            TermEncoding::Termwise,
+        )
+        .unwrap();
+        let signature: BBSPlusSignature = multibase_to_ark(&signature).unwrap();
+        let pk = key_graph
+            .get_public_key(get_verification_method_identifier(&proof_config).unwrap())
+            .unwrap();
+        let message_count = hash_data.len().try_into().unwrap();
+        assert!(signature
+            .verify(&hash_data, pk, generate_params(message_count))
+            .is_err());
+    }
+
+    #[test]
+    fn vc_with_unsupported_cryptosuite_failure() {
+        let unsecured_document = get_graph_from_ntriples(VC_1).unwrap();
+        let proof_config =
+            get_graph_from_ntriples(VC_PROOF_WITHOUT_PROOFVALUE_1_WITH_INVALID_CRYPTOSUITE)
+                .unwrap();
+        let vc = VerifiableCredential::new(unsecured_document, proof_config);
+        assert!(matches!(
+            vc.is_bound(),
+            Err(RDFProofsError::VCWithUnsupportedCryptosuite { expected, actual })
+                if actual == "bbs-termwise-blind-signature-2023"
+                    && expected.contains(&"bbs-termwise-signature-2023".to_string())
+        ));
+        assert!(supported_cryptosuites().contains(&"bbs-termwise-signature-2023"));
+    }
+
     #[test]
     fn sign_and_verify_string_success() {
         let mut rng = StdRng::seed_from_u64(0u64);
@@ -394,6 +680,7 @@ mod tests {
             VC_PROOF_WITHOUT_PROOFVALUE_1,
             KEY_GRAPH,
             None,
+            None,
         )
         .unwrap();
         assert!(verify_string(VC_1, &proof, KEY_GRAPH).is_ok())
@@ -465,4 +752,37 @@ mod tests {
             ))
         ))
     }
+
+    #[test]
+    fn verify_with_key_usage_constraints() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let restricted_key_graph = format!(
+            "{}<did:example:issuer0#bls12_381-g2-pub001> <https://zkp-ld.org/security#allowedCredentialType> <http://example.org/vocab/VaccinationCredential> .\n",
+            KEY_GRAPH
+        );
+
+        // credential without the allowed type
+        assert!(verify_string(VC_1, VC_PROOF_1, KEY_GRAPH).is_ok());
+        assert!(matches!(
+            verify_string(VC_1, VC_PROOF_1, &restricted_key_graph),
+            Err(RDFProofsError::UnauthorizedKeyUsage(_))
+        ));
+
+        // credential with the allowed type
+        let typed_vc = format!(
+            "{}<http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/vocab/VaccinationCredential> .\n",
+            VC_1
+        );
+        let proof = sign_string(
+            &mut rng,
+            &typed_vc,
+            VC_PROOF_WITHOUT_PROOFVALUE_1,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+        let verified = verify_string(&typed_vc, &proof, &restricted_key_graph);
+        assert!(verified.is_ok(), "{:?}", verified);
+    }
 }
@@ -0,0 +1,179 @@
+use crate::{
+    common::{get_graph_from_ntriples, hash_str_to_str},
+    constants::{PPID_PREFIX, SKOLEM_IRI_INFIX},
+    error::RDFProofsError,
+};
+use oxrdf::{
+    BlankNode, Graph, NamedNode, NamedNodeRef, NamedOrBlankNode, Subject, SubjectRef, Term,
+    TermRef, Triple,
+};
+use std::collections::HashMap;
+
+/// whether the IRI is a skolem IRI `<authority>/.well-known/genid/<id>` standing for
+/// a blank node, e.g., generated by JSON-LD processors skolemizing blank nodes;
+/// PPIDs are not regarded as skolem IRIs
+pub fn is_skolem_iri(iri: NamedNodeRef) -> bool {
+    iri.as_str().contains(SKOLEM_IRI_INFIX) && !iri.as_str().starts_with(PPID_PREFIX)
+}
+
+// blank node replacing the skolem IRI, labeled by its digest
+fn get_deskolemized_bnode(iri: NamedNodeRef) -> BlankNode {
+    BlankNode::new_unchecked(hash_str_to_str(iri.as_str()))
+}
+
+/// replace skolem IRIs in the graph with blank nodes, e.g., when ingesting documents
+/// from skolemizing producers before signing them, so that they can be hidden
+/// and equated as the other blank nodes; the same skolem IRI becomes the same blank node
+pub fn deskolemize(graph: &Graph) -> Graph {
+    let deskolemize_subject = |subject: SubjectRef| match subject {
+        SubjectRef::NamedNode(n) if is_skolem_iri(n) => {
+            Subject::BlankNode(get_deskolemized_bnode(n))
+        }
+        _ => subject.into_owned(),
+    };
+    let deskolemize_term = |term: TermRef| match term {
+        TermRef::NamedNode(n) if is_skolem_iri(n) => Term::BlankNode(get_deskolemized_bnode(n)),
+        _ => term.into_owned(),
+    };
+    graph
+        .iter()
+        .map(|t| {
+            Triple::new(
+                deskolemize_subject(t.subject),
+                t.predicate.into_owned(),
+                deskolemize_term(t.object),
+            )
+        })
+        .collect()
+}
+
+pub fn deskolemize_string(ntriples: &str) -> Result<String, RDFProofsError> {
+    Ok(deskolemize(&get_graph_from_ntriples(ntriples)?)
+        .iter()
+        .map(|t| format!("{} .\n", t))
+        .collect())
+}
+
+/// hider of skolem IRIs in disclosed VCs, which replaces them with random blank nodes
+/// to be hidden like the other blank nodes in `derive_proof`;
+/// the same skolem IRI is replaced with the same blank node across disclosed VCs,
+/// so that the verifier learns only that the hidden terms are equal
+#[derive(Debug, Default, Clone)]
+pub struct HiddenSkolemIris {
+    bnodes: HashMap<NamedNode, BlankNode>,
+}
+
+impl HiddenSkolemIris {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// replace the skolem IRIs in disclosed VC document with blank nodes
+    pub fn apply(&mut self, disclosed: &Graph) -> Graph {
+        let mut hide = |iri: NamedNodeRef| {
+            self.bnodes
+                .entry(iri.into_owned())
+                .or_insert_with(BlankNode::default)
+                .clone()
+        };
+        disclosed
+            .iter()
+            .map(|t| {
+                let subject = match t.subject {
+                    SubjectRef::NamedNode(n) if is_skolem_iri(n) => Subject::BlankNode(hide(n)),
+                    _ => t.subject.into_owned(),
+                };
+                let object = match t.object {
+                    TermRef::NamedNode(n) if is_skolem_iri(n) => Term::BlankNode(hide(n)),
+                    _ => t.object.into_owned(),
+                };
+                Triple::new(subject, t.predicate.into_owned(), object)
+            })
+            .collect()
+    }
+
+    pub fn apply_string(&mut self, disclosed: &str) -> Result<String, RDFProofsError> {
+        let disclosed = get_graph_from_ntriples(disclosed)?;
+        Ok(self
+            .apply(&disclosed)
+            .iter()
+            .map(|t| format!("{} .\n", t))
+            .collect())
+    }
+
+    /// entries of deanonymization map from blank nodes to the hidden skolem IRIs,
+    /// to be merged into the one given to `derive_proof`
+    pub fn deanon_map(&self) -> HashMap<NamedOrBlankNode, Term> {
+        self.bnodes
+            .iter()
+            .map(|(iri, bnode)| (bnode.clone().into(), iri.clone().into()))
+            .collect()
+    }
+
+    pub fn deanon_map_string(&self) -> HashMap<String, String> {
+        self.bnodes
+            .iter()
+            .map(|(iri, bnode)| (bnode.to_string(), iri.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deskolemize_string, is_skolem_iri, HiddenSkolemIris};
+    use crate::common::get_graph_from_ntriples;
+    use oxrdf::NamedNodeRef;
+
+    const DOCUMENT: &str = r#"
+    <https://example.org/.well-known/genid/a1> <http://schema.org/name> "John Smith" .
+    <https://example.org/.well-known/genid/a1> <http://schema.org/address> <https://example.org/.well-known/genid/a2> .
+    <https://example.org/.well-known/genid/a2> <http://schema.org/addressCountry> "JP" .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <https://example.org/.well-known/genid/a1> .
+    "#;
+
+    #[test]
+    fn skolem_iris() {
+        assert!(is_skolem_iri(NamedNodeRef::new_unchecked(
+            "https://example.org/.well-known/genid/a1"
+        )));
+        assert!(!is_skolem_iri(NamedNodeRef::new_unchecked(
+            "http://example.org/vcred/00"
+        )));
+        // PPIDs are not skolem IRIs
+        assert!(!is_skolem_iri(NamedNodeRef::new_unchecked(
+            "https://zkp-ld.org/.well-known/genid/ppid/abc"
+        )));
+    }
+
+    #[test]
+    fn deskolemize_document() {
+        let deskolemized = get_graph_from_ntriples(&deskolemize_string(DOCUMENT).unwrap()).unwrap();
+        assert_eq!(deskolemized.len(), 4);
+        assert!(!deskolemized.iter().any(|t| t.to_string().contains("genid")));
+        // the same skolem IRI becomes the same blank node, which is deterministic
+        assert_eq!(
+            deskolemize_string(DOCUMENT).unwrap(),
+            deskolemize_string(DOCUMENT).unwrap()
+        );
+        let subjects = deskolemized
+            .iter()
+            .map(|t| t.subject.to_string())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(subjects.len(), 3);
+    }
+
+    #[test]
+    fn hide_skolem_iris() {
+        let mut hidden = HiddenSkolemIris::new();
+        let disclosed_1 = hidden.apply_string(DOCUMENT).unwrap();
+        let disclosed_2 = hidden.apply_string(DOCUMENT).unwrap();
+        assert!(!disclosed_1.contains("genid"));
+        // equated across disclosed VCs
+        assert_eq!(disclosed_1, disclosed_2);
+        let deanon_map = hidden.deanon_map_string();
+        assert_eq!(deanon_map.len(), 2);
+        assert!(deanon_map
+            .values()
+            .any(|v| v == "<https://example.org/.well-known/genid/a2>"));
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    common::get_vc_from_ntriples,
+    context::PROOF_VALUE,
+    error::RDFProofsError,
+    vc::{VcPair, VcPairString, VerifiableCredential},
+};
+use oxigraph::{
+    sparql::{QueryResults, QuerySolution},
+    store::Store,
+};
+use oxrdf::{BlankNode, Graph, GraphNameRef, NamedOrBlankNode, Subject, Term, TermRef, Triple};
+use spargebra::Query;
+use std::collections::{HashMap, HashSet};
+
+/// generate the disclosed VC from the result of SPARQL CONSTRUCT or DESCRIBE `query`
+/// evaluated against the original VC document, where the terms bound to `hidden_variables`
+/// in the query pattern are replaced with fresh blank nodes in the disclosed VC;
+/// returns the VC pair and the deanonymization map for the hidden terms to be given to `derive_proof`.
+/// note that the query has to construct the VC envelope (type, issuer, credentialSubject, etc.) as well
+pub fn disclose_with_sparql(
+    vc: &VerifiableCredential,
+    query: &str,
+    hidden_variables: &[&str],
+) -> Result<(VcPair, HashMap<NamedOrBlankNode, Term>), RDFProofsError> {
+    let query = Query::parse(query, None)?;
+    // the query pattern without its template, to obtain the terms bound to hidden variables
+    let select = match &query {
+        Query::Construct {
+            dataset,
+            pattern,
+            base_iri,
+            ..
+        }
+        | Query::Describe {
+            dataset,
+            pattern,
+            base_iri,
+        } => Query::Select {
+            dataset: dataset.clone(),
+            pattern: pattern.clone(),
+            base_iri: base_iri.clone(),
+        },
+        _ => return Err(RDFProofsError::UnsupportedSparqlQuery),
+    };
+
+    let store = Store::new()?;
+    for triple in vc.document.iter() {
+        store.insert(triple.in_graph(GraphNameRef::DefaultGraph))?;
+    }
+
+    let QueryResults::Graph(triples) = store.query(query.to_string().as_str())? else {
+        return Err(RDFProofsError::UnsupportedSparqlQuery);
+    };
+    let mut disclosed_triples = triples.collect::<Result<Vec<_>, _>>()?;
+    // disclosed triples must be taken from the original VC
+    if disclosed_triples
+        .iter()
+        .any(|t| !vc.document.contains(t.as_ref()))
+    {
+        return Err(RDFProofsError::DisclosedVCIsNotSubsetOfOriginalVC);
+    }
+    // sort triples to label hidden terms deterministically
+    disclosed_triples.sort_by_cached_key(|t| t.to_string());
+
+    let QueryResults::Solutions(solutions) = store.query(select.to_string().as_str())? else {
+        return Err(RDFProofsError::UnsupportedSparqlQuery);
+    };
+    let mut hidden_terms = HashSet::new();
+    for solution in solutions {
+        let solution: QuerySolution = solution?;
+        for variable in hidden_variables {
+            if let Some(term) = solution.get(*variable) {
+                hidden_terms.insert(term.clone());
+            }
+        }
+    }
+
+    // labels of fresh blank nodes must not collide with blank nodes in the original VC
+    let original_bnodes: HashSet<_> = vc
+        .document
+        .iter()
+        .flat_map(|t| [t.subject.into(), t.object])
+        .filter_map(|term| match term {
+            TermRef::BlankNode(b) => Some(b.as_str().to_string()),
+            _ => None,
+        })
+        .collect();
+    let mut labels = (0..)
+        .map(|i| format!("e{}", i))
+        .filter(|l| !original_bnodes.contains(l));
+    let mut anonymized: HashMap<Term, BlankNode> = HashMap::new();
+    let mut anonymize = |term: Term| -> Term {
+        if !hidden_terms.contains(&term) {
+            return term;
+        }
+        anonymized
+            .entry(term)
+            .or_insert_with(|| BlankNode::new_unchecked(labels.next().unwrap_or_default()))
+            .clone()
+            .into()
+    };
+    let disclosed_document: Graph = disclosed_triples
+        .into_iter()
+        .map(|t| {
+            let subject = match anonymize(t.subject.into()) {
+                Term::NamedNode(n) => Subject::NamedNode(n),
+                Term::BlankNode(b) => Subject::BlankNode(b),
+                _ => return Err(RDFProofsError::DeAnonymization),
+            };
+            Ok(Triple::new(subject, t.predicate, anonymize(t.object)))
+        })
+        .collect::<Result<_, RDFProofsError>>()?;
+
+    // proof value is not disclosed
+    let disclosed_proof: Graph = vc
+        .proof
+        .iter()
+        .filter(|t| t.predicate != PROOF_VALUE)
+        .collect();
+
+    let deanon_map = anonymized
+        .into_iter()
+        .map(|(term, bnode)| (bnode.into(), term))
+        .collect();
+
+    Ok((
+        VcPair::new(
+            vc.clone(),
+            VerifiableCredential::new(disclosed_document, disclosed_proof),
+        ),
+        deanon_map,
+    ))
+}
+
+/// `disclose_with_sparql` with the original VC in N-Triples format,
+/// returning the VC pair and the deanonymization map to be given to `derive_proof_string`
+pub fn disclose_with_sparql_string(
+    document: &str,
+    proof: &str,
+    query: &str,
+    hidden_variables: &[&str],
+) -> Result<(VcPairString, HashMap<String, String>), RDFProofsError> {
+    let vc = get_vc_from_ntriples(document, proof)?;
+    let (VcPair { disclosed, .. }, deanon_map) =
+        disclose_with_sparql(&vc, query, hidden_variables)?;
+    let to_ntriples =
+        |graph: &Graph| -> String { graph.iter().map(|t| format!("{} .\n", t)).collect() };
+    Ok((
+        VcPairString::new(
+            document,
+            proof,
+            &to_ntriples(&disclosed.document),
+            &to_ntriples(&disclosed.proof),
+        ),
+        deanon_map
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    ))
+}
+
+#[cfg(all(test, feature = "prove"))]
+mod tests {
+    use super::disclose_with_sparql_string;
+    use crate::{
+        derive_proof_string, error::RDFProofsError, sign_string, verify_proof_string,
+        DeriveOptionsString, VerifyOptionsString,
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    "#;
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const QUERY: &str = r#"
+    PREFIX cred: <https://www.w3.org/2018/credentials#>
+    PREFIX schema: <http://schema.org/>
+    CONSTRUCT {
+        ?vc ?p ?o .
+        ?subject schema:name ?name .
+    } WHERE {
+        ?vc a cred:VerifiableCredential ;
+            cred:credentialSubject ?subject ;
+            ?p ?o .
+        ?subject schema:name ?name .
+    }
+    "#;
+
+    #[test]
+    fn derive_and_verify_proof_with_sparql_disclosure() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let proof = sign_string(
+            &mut rng,
+            VC,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let (vc_pair, deanon_map) =
+            disclose_with_sparql_string(VC, &proof, QUERY, &["vc", "subject"]).unwrap();
+        assert!(vc_pair.disclosed_document.contains("\"John Smith\""));
+        assert!(!vc_pair.disclosed_document.contains("birthDate"));
+        assert!(!vc_pair.disclosed_document.contains("<did:example:john>"));
+        assert!(!vc_pair.disclosed_proof.contains("proofValue"));
+        assert_eq!(deanon_map.len(), 2);
+        assert!(deanon_map.values().any(|v| v == "<did:example:john>"));
+
+        let derived_proof = derive_proof_string(
+            &mut rng,
+            &vec![vc_pair],
+            &deanon_map,
+            KEY_GRAPH,
+            None,
+            None,
+            DeriveOptionsString::default(),
+        )
+        .unwrap();
+        assert!(verify_proof_string(
+            &mut rng,
+            &derived_proof,
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+
+        // only CONSTRUCT and DESCRIBE are supported
+        assert!(matches!(
+            disclose_with_sparql_string(VC, &proof, "SELECT * WHERE { ?s ?p ?o }", &[]),
+            Err(RDFProofsError::UnsupportedSparqlQuery)
+        ));
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{
+    common::VerifyingKey,
+    context::{EQUIVALENCES, PREDICATE, PROOF, VERIFIABLE_CREDENTIAL},
+    curve::G1Affine,
+    error::RDFProofsError,
+    vc::VerifiablePresentation,
+    verify_proof, ElGamalPublicKey, ExtensionPolicy, KeyGraph, ReplayGuard, VerificationResult,
+    VerifyOptions,
+};
+use ark_std::rand::RngCore;
+use oxigraph::store::Store;
+use oxrdf::{
+    Dataset, Graph, GraphName, GraphNameRef, NamedNode, NamedNodeRef, NamedOrBlankNode, QuadRef,
+    Term, TripleRef,
+};
+use std::collections::{HashMap, HashSet};
+
+// predicates by which the graphs of VP are referenced from VP metadata or VC graphs
+const GRAPH_REFERENCES: [NamedNodeRef; 4] = [PROOF, PREDICATE, EQUIVALENCES, VERIFIABLE_CREDENTIAL];
+
+/// read VP from `store`, where the VP metadata (default graph of VP) is stored in the named graph `vp_graph`
+/// and the other graphs of VP are the named graphs transitively referenced from it,
+/// e.g., VC graphs, their proof graphs, VP proof graph, and predicate graphs
+pub fn get_vp_from_store(store: &Store, vp_graph: NamedNodeRef) -> Result<Dataset, RDFProofsError> {
+    let root: NamedOrBlankNode = vp_graph.into_owned().into();
+    let mut vp = Dataset::new();
+    let mut visited = HashSet::new();
+    let mut graphs = vec![root.clone()];
+    while let Some(graph) = graphs.pop() {
+        if !visited.insert(graph.clone()) {
+            continue;
+        }
+        let graph_name_in_vp = if graph == root {
+            GraphName::DefaultGraph
+        } else {
+            graph.clone().into()
+        };
+        for quad in store.quads_for_pattern(None, None, None, Some(graph.as_ref().into())) {
+            let quad = quad?;
+            if GRAPH_REFERENCES.contains(&quad.predicate.as_ref()) {
+                match &quad.object {
+                    Term::NamedNode(n) => graphs.push(n.clone().into()),
+                    Term::BlankNode(b) => graphs.push(b.clone().into()),
+                    _ => return Err(RDFProofsError::InvalidVP),
+                }
+            }
+            vp.insert(QuadRef::new(
+                &quad.subject,
+                &quad.predicate,
+                &quad.object,
+                &graph_name_in_vp,
+            ));
+        }
+    }
+    if vp.is_empty() {
+        return Err(RDFProofsError::InvalidVP);
+    }
+    Ok(vp)
+}
+
+/// write VP into `store` in the layout read by `get_vp_from_store`
+pub fn insert_vp_into_store(
+    store: &Store,
+    vp: &Dataset,
+    vp_graph: NamedNodeRef,
+) -> Result<(), RDFProofsError> {
+    for quad in vp.iter() {
+        let graph_name = match quad.graph_name {
+            GraphNameRef::DefaultGraph => vp_graph.into(),
+            graph_name => graph_name,
+        };
+        store.insert(QuadRef::new(
+            quad.subject,
+            quad.predicate,
+            quad.object,
+            graph_name,
+        ))?;
+    }
+    Ok(())
+}
+
+/// read key graph from the named graph `key_graph` in `store`
+pub fn get_key_graph_from_store(
+    store: &Store,
+    key_graph: NamedNodeRef,
+) -> Result<KeyGraph, RDFProofsError> {
+    let mut graph = Graph::new();
+    for quad in store.quads_for_pattern(None, None, None, Some(key_graph.into())) {
+        let quad = quad?;
+        graph.insert(TripleRef::new(&quad.subject, &quad.predicate, &quad.object));
+    }
+    Ok(graph.into())
+}
+
+/// write the disclosed triples of VCs in VP into the named graph `graph` in `store`,
+/// where hidden terms remain blank nodes; it must be called after VP is verified
+pub fn insert_disclosed_triples_into_store(
+    store: &Store,
+    vp: &Dataset,
+    graph: NamedNodeRef,
+) -> Result<(), RDFProofsError> {
+    let vp: VerifiablePresentation = vp.try_into()?;
+    for vc in vp.disclosed_vcs.values() {
+        for triple in vc.document.iter() {
+            store.insert(triple.in_graph(graph))?;
+        }
+    }
+    Ok(())
+}
+
+/// verify VP read from `store` with the key graph in `store`,
+/// writing the disclosed triples into the named graph `disclosed_graph` if given and verified
+pub fn verify_proof_in_store<R: RngCore>(
+    rng: &mut R,
+    store: &Store,
+    vp_graph: NamedNodeRef,
+    key_graph: NamedNodeRef,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+    snark_verifying_keys: HashMap<NamedNode, VerifyingKey>,
+    opener_pub_keys: Vec<ElGamalPublicKey>,
+    verifier_commitments: Vec<G1Affine>,
+    policy: Option<&Graph>,
+    replay_guard: Option<&dyn ReplayGuard>,
+    public_values: HashMap<NamedNode, HashMap<String, Term>>,
+    extension_policy: ExtensionPolicy,
+    disclosed_graph: Option<NamedNodeRef>,
+) -> Result<VerificationResult, RDFProofsError> {
+    let vp = get_vp_from_store(store, vp_graph)?;
+    let key_graph = get_key_graph_from_store(store, key_graph)?;
+    let result = verify_proof(
+        rng,
+        &vp,
+        &key_graph,
+        challenge,
+        domain,
+        VerifyOptions {
+            snark_verifying_keys,
+            opener_pub_keys,
+            verifier_commitments,
+            policy,
+            replay_guard,
+            public_values,
+            extension_policy,
+            ..Default::default()
+        },
+    )?;
+    if let Some(disclosed_graph) = disclosed_graph {
+        insert_disclosed_triples_into_store(store, &vp, disclosed_graph)?;
+    }
+    Ok(result)
+}
+
+#[cfg(all(test, feature = "prove"))]
+mod tests {
+    use super::{
+        get_key_graph_from_store, get_vp_from_store, insert_vp_into_store, verify_proof_in_store,
+    };
+    use crate::{
+        common::{get_graph_from_ntriples, get_vc_from_ntriples},
+        derive_proof, sign_string, DeriveOptions, KeyGraph, VcPair,
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use oxigraph::store::Store;
+    use oxrdf::{BlankNode, GraphNameRef, Literal, NamedNode, NamedNodeRef, QuadRef};
+    use std::collections::HashMap;
+
+    const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    "#;
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC: &str = r#"
+    _:e0 <http://schema.org/name> "John Smith" .
+    _:e1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e1 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+    _:e1 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    _:e1 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e1 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+    #[test]
+    fn verify_proof_in_store_success() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let proof = sign_string(
+            &mut rng,
+            VC,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+        let disclosed_proof: String = proof
+            .lines()
+            .filter(|line| !line.contains("<https://w3id.org/security#proofValue>"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let vc_pairs = vec![VcPair::new(
+            get_vc_from_ntriples(VC, &proof).unwrap(),
+            get_vc_from_ntriples(DISCLOSED_VC, &disclosed_proof).unwrap(),
+        )];
+        let deanon_map = HashMap::from([
+            (
+                BlankNode::new_unchecked("e0").into(),
+                NamedNode::new_unchecked("did:example:john").into(),
+            ),
+            (
+                BlankNode::new_unchecked("e1").into(),
+                NamedNode::new_unchecked("http://example.org/vcred/00").into(),
+            ),
+        ]);
+        let vp = derive_proof(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            &key_graph,
+            None,
+            None,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+
+        let store = Store::new().unwrap();
+        let vp_graph = NamedNodeRef::new_unchecked("http://example.org/vp/00");
+        let key_graph_name = NamedNodeRef::new_unchecked("http://example.org/keys");
+        let disclosed_graph = NamedNodeRef::new_unchecked("http://example.org/disclosed");
+        insert_vp_into_store(&store, &vp, vp_graph).unwrap();
+        for triple in get_graph_from_ntriples(KEY_GRAPH).unwrap().iter() {
+            store.insert(triple.in_graph(key_graph_name)).unwrap();
+        }
+        assert_eq!(get_vp_from_store(&store, vp_graph).unwrap().len(), vp.len());
+        assert!(get_key_graph_from_store(&store, key_graph_name).is_ok());
+
+        let result = verify_proof_in_store(
+            &mut rng,
+            &store,
+            vp_graph,
+            key_graph_name,
+            None,
+            None,
+            HashMap::new(),
+            vec![],
+            vec![],
+            None,
+            None,
+            HashMap::new(),
+            ExtensionPolicy::Reject,
+            Some(disclosed_graph),
+        );
+        assert!(result.is_ok(), "{:?}", result);
+
+        // disclosed triples are written back into the store, without undisclosed ones
+        let name = Literal::new_simple_literal("John Smith");
+        let birth_date = NamedNodeRef::new_unchecked("http://schema.org/birthDate");
+        assert_eq!(
+            store
+                .quads_for_pattern(
+                    None,
+                    None,
+                    Some(name.as_ref().into()),
+                    Some(disclosed_graph.into())
+                )
+                .count(),
+            1
+        );
+        assert_eq!(
+            store
+                .quads_for_pattern(None, Some(birth_date), None, Some(disclosed_graph.into()))
+                .count(),
+            0
+        );
+        assert!(!store
+            .contains(QuadRef::new(
+                NamedNodeRef::new_unchecked("did:example:john"),
+                NamedNodeRef::new_unchecked("http://schema.org/name"),
+                name.as_ref(),
+                GraphNameRef::from(disclosed_graph),
+            ))
+            .unwrap());
+
+        // VP without its graphs is rejected
+        assert!(get_vp_from_store(
+            &store,
+            NamedNodeRef::new_unchecked("http://example.org/vp/01")
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{common::get_graph_from_ntriples, error::RDFProofsError};
+use oxrdf::{Graph, NamedNode, SubjectRef, TermRef, TripleRef};
+use std::collections::HashSet;
+
+/// leave the subtrees rooted at the objects of `predicates`, e.g., `evidence` and `termsOfUse`,
+/// out of the disclosed VC document as units, i.e., the triples with those predicates,
+/// the triples about their objects, and the triples connected to them via blank nodes,
+/// so that no partially-hidden structure dangles in the disclosed VC;
+/// note that blank nodes shared with the rest of VC take their connected triples with them
+pub fn hide_subtrees(disclosed: &Graph, predicates: &[NamedNode]) -> Graph {
+    let subtrees = get_subtree_triples(disclosed, predicates);
+    disclosed.iter().filter(|t| !subtrees.contains(t)).collect()
+}
+
+/// `hide_subtrees` with the disclosed VC document in N-Triples and
+/// the predicate IRIs without angle brackets
+pub fn hide_subtrees_string(
+    disclosed: &str,
+    predicates: &[&str],
+) -> Result<String, RDFProofsError> {
+    let disclosed = get_graph_from_ntriples(disclosed)?;
+    let predicates = predicates
+        .iter()
+        .map(|p| NamedNode::new(*p))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hide_subtrees(&disclosed, &predicates)
+        .iter()
+        .map(|t| format!("{} .\n", t))
+        .collect())
+}
+
+// triples in the subtrees rooted at the objects of `predicates`,
+// traversing blank nodes transitively in both directions
+pub(crate) fn get_subtree_triples<'a>(
+    document: &'a Graph,
+    predicates: &[NamedNode],
+) -> HashSet<TripleRef<'a>> {
+    let links = document
+        .iter()
+        .filter(|t| predicates.iter().any(|p| p.as_ref() == t.predicate))
+        .collect::<Vec<_>>();
+    let mut subtrees = links.iter().copied().collect::<HashSet<_>>();
+
+    // roots may be IRIs, but only blank nodes are traversed further
+    // since IRIs can be referred to from the rest of the VC, e.g., the issuer
+    let mut visited = HashSet::new();
+    let mut pending = links.iter().map(|t| t.object).collect::<Vec<_>>();
+    let mut is_root = true;
+    while !pending.is_empty() {
+        let mut next = vec![];
+        for node in pending {
+            if !visited.insert(node) {
+                continue;
+            }
+            let subject = match node {
+                TermRef::BlankNode(b) => SubjectRef::BlankNode(b),
+                TermRef::NamedNode(n) if is_root => SubjectRef::NamedNode(n),
+                _ => continue,
+            };
+            for t in document.triples_for_subject(subject) {
+                subtrees.insert(t);
+                next.push(t.object);
+            }
+            if let TermRef::BlankNode(b) = node {
+                for t in document.triples_for_object(b) {
+                    subtrees.insert(t);
+                    next.push(t.subject.into());
+                }
+            }
+        }
+        pending = next;
+        is_root = false;
+    }
+    subtrees
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hide_subtrees_string;
+    use crate::common::get_graph_from_ntriples;
+
+    const DOCUMENT: &str = r#"
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#evidence> _:ev .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#termsOfUse> <http://example.org/tou/00> .
+    _:ev <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/DocumentVerification> .
+    _:ev <https://example.org/verifier> <did:example:issuer0> .
+    _:ev <https://example.org/document> _:doc .
+    _:doc <https://example.org/documentType> "Passport" .
+    <did:example:john> <https://example.org/checkedDocument> _:doc .
+    <http://example.org/tou/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/IssuerPolicy> .
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    "#;
+
+    #[test]
+    fn hide_evidence_and_terms_of_use() {
+        let hidden = hide_subtrees_string(
+            DOCUMENT,
+            &[
+                "https://www.w3.org/2018/credentials#evidence",
+                "https://www.w3.org/2018/credentials#termsOfUse",
+            ],
+        )
+        .unwrap();
+        let expected = get_graph_from_ntriples(
+            r#"
+        <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+        <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+        <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+        <did:example:john> <http://schema.org/name> "John Smith" .
+        "#,
+        )
+        .unwrap();
+        // the nested document referred to from the subject is hidden with the evidence,
+        // while the issuer IRI referred to from the evidence remains
+        assert_eq!(get_graph_from_ntriples(&hidden).unwrap(), expected);
+
+        // nothing is hidden without the predicates
+        let hidden = hide_subtrees_string(DOCUMENT, &["http://schema.org/knows"]).unwrap();
+        assert_eq!(
+            get_graph_from_ntriples(&hidden).unwrap(),
+            get_graph_from_ntriples(DOCUMENT).unwrap()
+        );
+    }
+}
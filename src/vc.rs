@@ -1,10 +1,15 @@
 use crate::{
-    constants::{CRYPTOSUITE_BOUND_SIGN, CRYPTOSUITE_SIGN},
+    constants::{
+        CRYPTOSUITE_BOUND_SIGN, CRYPTOSUITE_BOUND_SIGN_2024, CRYPTOSUITE_PROOF,
+        CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN, CRYPTOSUITE_SCALED_DECIMAL_SIGN, CRYPTOSUITE_SIGN,
+        CRYPTOSUITE_SIGN_2024, CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN, CRYPTOSUITE_SPLIT_IRI_SIGN,
+    },
     context::{
-        CRYPTOSUITE, DATA_INTEGRITY_PROOF, MULTIBASE, PREDICATE, PROOF, PROOF_VALUE,
+        CRYPTOSUITE, DATA_INTEGRITY_PROOF, EQUIVALENCES, MULTIBASE, PREDICATE, PROOF, PROOF_VALUE,
         VERIFIABLE_CREDENTIAL,
     },
     error::RDFProofsError,
+    message_layout::{SecretSlot, TermEncoding},
     ordered_triple::{
         OrderedGraphNameRef, OrderedGraphViews, OrderedVerifiableCredentialGraphViews,
     },
@@ -46,11 +51,18 @@ impl VerifiableCredential {
     }
 
     pub fn is_bound(&self) -> Result<bool, RDFProofsError> {
-        match self.get_cryptosuite()?.as_str() {
-            CRYPTOSUITE_BOUND_SIGN => Ok(true),
-            CRYPTOSUITE_SIGN => Ok(false),
-            _ => Err(RDFProofsError::VCWithUnsupportedCryptosuite),
-        }
+        let (is_bound, _, _) = get_cryptosuite_layout(&self.get_cryptosuite()?)?;
+        Ok(is_bound)
+    }
+
+    pub fn secret_slot(&self) -> Result<SecretSlot, RDFProofsError> {
+        let (_, secret_slot, _) = get_cryptosuite_layout(&self.get_cryptosuite()?)?;
+        Ok(secret_slot)
+    }
+
+    pub fn term_encoding(&self) -> Result<TermEncoding, RDFProofsError> {
+        let (_, _, term_encoding) = get_cryptosuite_layout(&self.get_cryptosuite()?)?;
+        Ok(term_encoding)
     }
 
     pub fn add_proof_value(self: &mut Self, proof_value: String) -> Result<(), RDFProofsError> {
@@ -162,11 +174,18 @@ impl<'a> VerifiableCredentialView<'a> {
     }
 
     pub fn is_bound(&self) -> Result<bool, RDFProofsError> {
-        match self.get_cryptosuite()?.as_str() {
-            CRYPTOSUITE_BOUND_SIGN => Ok(true),
-            CRYPTOSUITE_SIGN => Ok(false),
-            _ => Err(RDFProofsError::VCWithUnsupportedCryptosuite),
-        }
+        let (is_bound, _, _) = get_cryptosuite_layout(&self.get_cryptosuite()?)?;
+        Ok(is_bound)
+    }
+
+    pub fn secret_slot(&self) -> Result<SecretSlot, RDFProofsError> {
+        let (_, secret_slot, _) = get_cryptosuite_layout(&self.get_cryptosuite()?)?;
+        Ok(secret_slot)
+    }
+
+    pub fn term_encoding(&self) -> Result<TermEncoding, RDFProofsError> {
+        let (_, _, term_encoding) = get_cryptosuite_layout(&self.get_cryptosuite()?)?;
+        Ok(term_encoding)
     }
 }
 
@@ -258,6 +277,13 @@ impl VcPair {
         }
     }
 
+    /// pair with the fully hidden document, disclosing nothing but the issuer and
+    /// the proof configuration of `original`, e.g., to prove possession of a valid credential
+    pub fn possession_only(original: VerifiableCredential) -> Self {
+        let disclosed = VerifiableCredential::new(Graph::new(), original.get_proof_config());
+        Self::new(original, disclosed)
+    }
+
     pub fn to_string(&self) -> String {
         format!(
             "vc:\n{}vc_proof:\n{}\ndisclosed_vc:\n{}disclosed_vc_proof:\n{}\n",
@@ -310,13 +336,20 @@ impl VcPairString {
             disclosed_proof: disclosed_proof.to_string(),
         }
     }
+
+    /// N-Triples version of `VcPair::possession_only`
+    pub fn possession_only(original_document: &str, original_proof: &str) -> Self {
+        Self::new(original_document, original_proof, "", original_proof)
+    }
 }
 
 pub struct VerifiablePresentation<'a> {
     pub metadata: GraphView<'a>,
     pub proof: GraphView<'a>,
     pub proof_graph_name: GraphNameRef<'a>,
+    pub previous_proofs: OrderedGraphViews<'a>,
     pub predicates: OrderedGraphViews<'a>,
+    pub equivalences: Option<GraphView<'a>>,
     pub disclosed_vcs: OrderedVerifiableCredentialGraphViews<'a>,
 }
 
@@ -333,12 +366,43 @@ impl<'a> TryFrom<&'a Dataset> for VerifiablePresentation<'a> {
                 "VP graphs must have default graph".to_owned(),
             ))?;
 
-        // extract VP proof graph
-        let (vp_proof_graph_name, vp_proof) = remove_graph(&mut vp_graphs, &metadata, PROOF)?;
+        // extract VP proof graph, which is distinguished by its cryptosuite
+        // from the previous proofs in the proof chain if any
+        let (mut vp_proofs, previous_proofs): (OrderedGraphViews, OrderedGraphViews) =
+            remove_graphs(&mut vp_graphs, &metadata, PROOF)?
+                .into_iter()
+                .partition(|(_, proof)| is_derived_proof(proof));
+        let (vp_proof_graph_name, vp_proof) = match vp_proofs.pop_first() {
+            Some(vp_proof) => vp_proof,
+            None => {
+                // report the cryptosuite of VP proof if it is neither derived proof nor
+                // the signatures of previous proofs
+                return Err(previous_proofs
+                    .values()
+                    .filter_map(get_proof_cryptosuite)
+                    .find(|c| get_cryptosuite_layout(c).is_err())
+                    .map_or(RDFProofsError::InvalidVP, |actual| {
+                        RDFProofsError::VPWithUnsupportedCryptosuite {
+                            expected: vec![CRYPTOSUITE_PROOF.to_string()],
+                            actual: actual.to_string(),
+                        }
+                    }));
+            }
+        };
+        if !vp_proofs.is_empty() {
+            return Err(RDFProofsError::InvalidVP);
+        }
 
         // extract predicate graphs if any
         let predicates = remove_graphs(&mut vp_graphs, &metadata, PREDICATE)?;
 
+        // extract equivalence graph if any
+        let mut equivalence_graphs = remove_graphs(&mut vp_graphs, &metadata, EQUIVALENCES)?;
+        let equivalences = equivalence_graphs.pop_first().map(|(_, graph)| graph);
+        if !equivalence_graphs.is_empty() {
+            return Err(RDFProofsError::InvalidVP);
+        }
+
         // extract VC graphs
         let vcs = remove_graphs(&mut vp_graphs, &metadata, VERIFIABLE_CREDENTIAL)?;
 
@@ -360,7 +424,9 @@ impl<'a> TryFrom<&'a Dataset> for VerifiablePresentation<'a> {
             metadata,
             proof: vp_proof,
             proof_graph_name: vp_proof_graph_name.into(),
+            previous_proofs,
             predicates,
+            equivalences,
             disclosed_vcs,
         })
     }
@@ -409,6 +475,80 @@ impl<'a> VerifiablePresentation<'a> {
     }
 }
 
+/// cryptosuites supported by this build, depending on the enabled features,
+/// which can be used for capability negotiation with issuers and verifiers
+pub fn supported_cryptosuites() -> Vec<&'static str> {
+    let mut cryptosuites = vec![
+        CRYPTOSUITE_SIGN,
+        CRYPTOSUITE_SIGN_2024,
+        CRYPTOSUITE_SPLIT_IRI_SIGN,
+        CRYPTOSUITE_SCALED_DECIMAL_SIGN,
+    ];
+    if cfg!(feature = "blind") {
+        cryptosuites.push(CRYPTOSUITE_BOUND_SIGN);
+        cryptosuites.push(CRYPTOSUITE_BOUND_SIGN_2024);
+        cryptosuites.push(CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN);
+        cryptosuites.push(CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN);
+    }
+    if cfg!(any(feature = "prove", feature = "verify")) {
+        cryptosuites.push(CRYPTOSUITE_PROOF);
+    }
+    cryptosuites
+}
+
+fn unsupported_vc_cryptosuite(actual: &str) -> RDFProofsError {
+    RDFProofsError::VCWithUnsupportedCryptosuite {
+        expected: vec![
+            CRYPTOSUITE_SIGN.to_string(),
+            CRYPTOSUITE_BOUND_SIGN.to_string(),
+            CRYPTOSUITE_SIGN_2024.to_string(),
+            CRYPTOSUITE_BOUND_SIGN_2024.to_string(),
+            CRYPTOSUITE_SPLIT_IRI_SIGN.to_string(),
+            CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN.to_string(),
+            CRYPTOSUITE_SCALED_DECIMAL_SIGN.to_string(),
+            CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN.to_string(),
+        ],
+        actual: actual.to_string(),
+    }
+}
+
+/// message vector layout of VC signed with `cryptosuite`, i.e.,
+/// whether it is bound to holder's secret, where the secret slot is placed,
+/// and how each term is encoded into messages
+pub(crate) fn get_cryptosuite_layout(
+    cryptosuite: &str,
+) -> Result<(bool, SecretSlot, TermEncoding), RDFProofsError> {
+    match cryptosuite {
+        CRYPTOSUITE_SIGN => Ok((false, SecretSlot::First, TermEncoding::Termwise)),
+        CRYPTOSUITE_BOUND_SIGN => Ok((true, SecretSlot::First, TermEncoding::Termwise)),
+        CRYPTOSUITE_SIGN_2024 => Ok((false, SecretSlot::Last, TermEncoding::Termwise)),
+        CRYPTOSUITE_BOUND_SIGN_2024 => Ok((true, SecretSlot::Last, TermEncoding::Termwise)),
+        CRYPTOSUITE_SPLIT_IRI_SIGN => Ok((false, SecretSlot::Last, TermEncoding::SplitIri)),
+        CRYPTOSUITE_SPLIT_IRI_BOUND_SIGN => Ok((true, SecretSlot::Last, TermEncoding::SplitIri)),
+        CRYPTOSUITE_SCALED_DECIMAL_SIGN => {
+            Ok((false, SecretSlot::Last, TermEncoding::ScaledDecimal))
+        }
+        CRYPTOSUITE_SCALED_DECIMAL_BOUND_SIGN => {
+            Ok((true, SecretSlot::Last, TermEncoding::ScaledDecimal))
+        }
+        actual => Err(unsupported_vc_cryptosuite(actual)),
+    }
+}
+
+fn get_proof_cryptosuite<'a>(proof: &GraphView<'a>) -> Option<&'a str> {
+    proof
+        .subject_for_predicate_object(vocab::rdf::TYPE, DATA_INTEGRITY_PROOF)
+        .and_then(|proof_subject| proof.object_for_subject_predicate(proof_subject, CRYPTOSUITE))
+        .and_then(|cryptosuite| match cryptosuite {
+            TermRef::Literal(v) => Some(v.value()),
+            _ => None,
+        })
+}
+
+fn is_derived_proof(proof: &GraphView) -> bool {
+    get_proof_cryptosuite(proof).is_some_and(|cryptosuite| cryptosuite == CRYPTOSUITE_PROOF)
+}
+
 fn dataset_into_ordered_graphs(dataset: &Dataset) -> OrderedGraphViews {
     let graph_name_set = dataset
         .iter()
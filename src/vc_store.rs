@@ -0,0 +1,340 @@
+use crate::{
+    common::get_vc_from_ntriples,
+    constants::{VC_STORE_CONTEXT, VC_STORE_VERSION},
+    error::RDFProofsError,
+    vc::{VcPair, VerifiableCredential},
+};
+use ark_std::rand::RngCore;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use hkdf::Hkdf;
+use oxrdf::Graph;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const XNONCE_SIZE: usize = 24;
+
+/// storage of holder's VCs, from which VC pairs to be presented are pulled by their IDs
+pub trait VcStore {
+    fn get(&self, id: &str) -> Result<VerifiableCredential, RDFProofsError>;
+    fn put<R: RngCore>(
+        &self,
+        rng: &mut R,
+        id: &str,
+        vc: &VerifiableCredential,
+    ) -> Result<(), RDFProofsError>;
+}
+
+/// disclosed VC to be paired with the original VC stored under `id`
+pub struct StoredVcDisclosure {
+    pub id: String,
+    pub disclosed: VerifiableCredential,
+}
+
+pub struct StoredVcDisclosureString {
+    pub id: String,
+    pub disclosed_document: String,
+    pub disclosed_proof: String,
+}
+
+impl StoredVcDisclosureString {
+    pub fn new(id: &str, disclosed_document: &str, disclosed_proof: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            disclosed_document: disclosed_document.to_string(),
+            disclosed_proof: disclosed_proof.to_string(),
+        }
+    }
+}
+
+// plaintext of stored VC
+#[derive(Serialize, Deserialize)]
+struct StoredVc {
+    #[serde(rename = "d")]
+    document: String,
+    #[serde(rename = "p")]
+    proof: String,
+}
+
+// versioned blob of stored VC encrypted with XChaCha20-Poly1305
+#[derive(Serialize, Deserialize)]
+struct SealedVc {
+    #[serde(rename = "v")]
+    version: u8,
+    #[serde(rename = "n")]
+    nonce: Vec<u8>,
+    #[serde(rename = "c")]
+    ciphertext: Vec<u8>,
+}
+
+/// file-based VC store where each VC is encrypted at rest with the key derived from holder's secret;
+/// VC IDs are bound to the ciphertexts as associated data, and file names are their hashes
+pub struct EncryptedFileVcStore {
+    dir: PathBuf,
+    cipher: XChaCha20Poly1305,
+}
+
+impl EncryptedFileVcStore {
+    pub fn new(dir: impl AsRef<Path>, secret: &[u8]) -> Result<Self, RDFProofsError> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            cipher: XChaCha20Poly1305::new(&derive_vc_store_key(secret)?.into()),
+        })
+    }
+
+    fn get_path(&self, id: &str) -> PathBuf {
+        let file_name: String = Sha256::digest(id.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        self.dir.join(file_name)
+    }
+}
+
+impl VcStore for EncryptedFileVcStore {
+    fn get(&self, id: &str) -> Result<VerifiableCredential, RDFProofsError> {
+        let sealed = match std::fs::read(self.get_path(id)) {
+            Ok(sealed) => sealed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(RDFProofsError::VcNotFound(id.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let SealedVc {
+            version,
+            nonce,
+            ciphertext,
+        } = serde_cbor::from_slice(&sealed).map_err(|_| RDFProofsError::InvalidVcStoreEntry)?;
+        if version != VC_STORE_VERSION || nonce.len() != XNONCE_SIZE {
+            return Err(RDFProofsError::InvalidVcStoreEntry);
+        }
+        let plaintext = self
+            .cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: id.as_bytes(),
+                },
+            )
+            .map_err(|_| RDFProofsError::InvalidVcStoreEntry)?;
+        let StoredVc { document, proof } =
+            serde_cbor::from_slice(&plaintext).map_err(|_| RDFProofsError::InvalidVcStoreEntry)?;
+        get_vc_from_ntriples(&document, &proof)
+    }
+
+    fn put<R: RngCore>(
+        &self,
+        rng: &mut R,
+        id: &str,
+        vc: &VerifiableCredential,
+    ) -> Result<(), RDFProofsError> {
+        let plaintext = serde_cbor::to_vec(&StoredVc {
+            document: graph_to_ntriples(&vc.document),
+            proof: graph_to_ntriples(&vc.proof),
+        })?;
+        let mut nonce = [0u8; XNONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: id.as_bytes(),
+                },
+            )
+            .map_err(|_| RDFProofsError::InvalidVcStoreEntry)?;
+        let sealed = SealedVc {
+            version: VC_STORE_VERSION,
+            nonce: nonce.to_vec(),
+            ciphertext,
+        };
+        std::fs::write(self.get_path(id), serde_cbor::to_vec(&sealed)?)?;
+        Ok(())
+    }
+}
+
+// HKDF-SHA256 with the store context as salt, so that the key is separated from other uses of the secret
+fn derive_vc_store_key(secret: &[u8]) -> Result<[u8; 32], RDFProofsError> {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(VC_STORE_CONTEXT), secret)
+        .expand(&[VC_STORE_VERSION], &mut key)
+        .map_err(|e| RDFProofsError::Other(e.to_string()))?;
+    Ok(key)
+}
+
+fn graph_to_ntriples(graph: &Graph) -> String {
+    graph.iter().map(|t| format!("{} .\n", t)).collect()
+}
+
+/// pull VC pairs to be given to `derive_proof` from `store`
+pub fn get_vc_pairs_from_store(
+    store: &impl VcStore,
+    disclosures: Vec<StoredVcDisclosure>,
+) -> Result<Vec<VcPair>, RDFProofsError> {
+    disclosures
+        .into_iter()
+        .map(|StoredVcDisclosure { id, disclosed }| Ok(VcPair::new(store.get(&id)?, disclosed)))
+        .collect()
+}
+
+pub fn get_vc_pairs_from_store_string(
+    store: &impl VcStore,
+    disclosures: &[StoredVcDisclosureString],
+) -> Result<Vec<VcPair>, RDFProofsError> {
+    get_vc_pairs_from_store(
+        store,
+        disclosures
+            .iter()
+            .map(|disclosure| {
+                Ok(StoredVcDisclosure {
+                    id: disclosure.id.clone(),
+                    disclosed: get_vc_from_ntriples(
+                        &disclosure.disclosed_document,
+                        &disclosure.disclosed_proof,
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    )
+}
+
+#[cfg(all(test, feature = "prove", feature = "verify"))]
+mod tests {
+    use super::{
+        get_vc_pairs_from_store_string, EncryptedFileVcStore, StoredVcDisclosureString, VcStore,
+    };
+    use crate::{
+        common::{get_graph_from_ntriples, get_vc_from_ntriples},
+        derive_proof,
+        error::RDFProofsError,
+        sign_string, verify_proof_string, DeriveOptions, KeyGraph, VerifyOptionsString,
+    };
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use oxrdf::{BlankNode, NamedNode};
+    use std::collections::HashMap;
+
+    const KEY_GRAPH: &str = r#"
+    <did:example:issuer0> <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    <did:example:issuer0#bls12_381-g2-pub001> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Multikey> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#controller> <did:example:issuer0> .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#secretKeyMultibase> "uekl-7abY7R84yTJEJ6JRqYohXxPZPDoTinJ7XCcBkmk" .
+    <did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+    "#;
+    const VC: &str = r#"
+    <did:example:john> <http://schema.org/name> "John Smith" .
+    <did:example:john> <http://schema.org/birthDate> "1990-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:john> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    <http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+    const VC_PROOF_WITHOUT_PROOFVALUE: &str = r#"
+    _:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+    _:b0 <http://purl.org/dc/terms/created> "2023-02-09T09:35:07Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:b0 <https://w3id.org/security#proofPurpose> <https://w3id.org/security#assertionMethod> .
+    _:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+    "#;
+    const DISCLOSED_VC: &str = r#"
+    _:e0 <http://schema.org/name> "John Smith" .
+    _:e1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+    _:e1 <https://www.w3.org/2018/credentials#credentialSubject> _:e0 .
+    _:e1 <https://www.w3.org/2018/credentials#issuer> <did:example:issuer0> .
+    _:e1 <https://www.w3.org/2018/credentials#issuanceDate> "2022-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    _:e1 <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+    "#;
+
+    #[test]
+    fn derive_and_verify_proof_from_encrypted_file_vc_store() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let secret = b"SECRET";
+        let dir = std::env::temp_dir().join("rdf-proofs-vc-store-test");
+        let store = EncryptedFileVcStore::new(&dir, secret).unwrap();
+
+        let proof = sign_string(
+            &mut rng,
+            VC,
+            VC_PROOF_WITHOUT_PROOFVALUE,
+            KEY_GRAPH,
+            None,
+            None,
+        )
+        .unwrap();
+        let vc = get_vc_from_ntriples(VC, &proof).unwrap();
+        store.put(&mut rng, "urn:vc:00", &vc).unwrap();
+
+        // the stored file does not contain plaintext credential
+        let sealed = std::fs::read(store.get_path("urn:vc:00")).unwrap();
+        assert!(!sealed.windows(10).any(|w| w == b"John Smith"));
+
+        let disclosed_proof: String = proof
+            .lines()
+            .filter(|line| !line.contains("<https://w3id.org/security#proofValue>"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let vc_pairs = get_vc_pairs_from_store_string(
+            &store,
+            &[StoredVcDisclosureString::new(
+                "urn:vc:00",
+                DISCLOSED_VC,
+                &disclosed_proof,
+            )],
+        )
+        .unwrap();
+        let deanon_map = HashMap::from([
+            (
+                BlankNode::new_unchecked("e0").into(),
+                NamedNode::new_unchecked("did:example:john").into(),
+            ),
+            (
+                BlankNode::new_unchecked("e1").into(),
+                NamedNode::new_unchecked("http://example.org/vcred/00").into(),
+            ),
+        ]);
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+
+        let derived_proof = derive_proof(
+            &mut rng,
+            &vc_pairs,
+            &deanon_map,
+            &key_graph,
+            None,
+            None,
+            DeriveOptions::default(),
+        )
+        .unwrap();
+        assert!(verify_proof_string(
+            &mut rng,
+            &rdf_canon::serialize(&derived_proof),
+            KEY_GRAPH,
+            None,
+            None,
+            VerifyOptionsString::default(),
+        )
+        .is_ok());
+
+        // stored VCs cannot be loaded with another secret or under another ID
+        let another_store = EncryptedFileVcStore::new(&dir, b"ANOTHER SECRET").unwrap();
+        assert!(matches!(
+            another_store.get("urn:vc:00"),
+            Err(RDFProofsError::InvalidVcStoreEntry)
+        ));
+        std::fs::copy(store.get_path("urn:vc:00"), store.get_path("urn:vc:01")).unwrap();
+        assert!(matches!(
+            store.get("urn:vc:01"),
+            Err(RDFProofsError::InvalidVcStoreEntry)
+        ));
+        assert!(matches!(
+            store.get("urn:vc:02"),
+            Err(RDFProofsError::VcNotFound(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
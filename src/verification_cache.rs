@@ -0,0 +1,130 @@
+use crate::{common::hash_str_to_str, verify_proof::VerificationResult};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// cache of successful verification results consulted by `verify_proof`
+/// after all the checks independent of the proof itself and just before verifying the proof,
+/// keyed by the digests of the canonicalized VP, the challenge, the issuer public keys taken
+/// from the key graph, the other verifier's inputs, and the policy,
+/// so that identical VPs re-received on retries are not verified again
+pub trait VerificationCache: Send + Sync {
+    fn get(&self, key: &str) -> Option<VerificationResult>;
+    fn insert(&self, key: &str, result: &VerificationResult);
+}
+
+pub(crate) fn get_verification_cache_key(
+    vp_digest: &str,
+    challenge: Option<&str>,
+    public_keys_digest: &str,
+    options_digest: &str,
+    policy_digest: Option<&str>,
+) -> String {
+    hash_str_to_str(&format!(
+        "{}\n{:?}\n{}\n{}\n{:?}",
+        vp_digest, challenge, public_keys_digest, options_digest, policy_digest
+    ))
+}
+
+struct CacheEntry {
+    result: VerificationResult,
+    inserted_at: Instant,
+    last_used: u64,
+}
+
+/// in-memory LRU cache holding at most `capacity` results, which expire after `ttl`
+pub struct InMemoryVerificationCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<(HashMap<String, CacheEntry>, u64)>,
+}
+
+impl InMemoryVerificationCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new((HashMap::new(), 0)),
+        }
+    }
+}
+
+impl VerificationCache for InMemoryVerificationCache {
+    fn get(&self, key: &str) -> Option<VerificationResult> {
+        let mut guard = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (entries, clock) = &mut *guard;
+        let entry = entries.get_mut(key)?;
+        if entry.inserted_at.elapsed() >= self.ttl {
+            entries.remove(key);
+            return None;
+        }
+        *clock += 1;
+        entry.last_used = *clock;
+        Some(entry.result.clone())
+    }
+
+    fn insert(&self, key: &str, result: &VerificationResult) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut guard = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (entries, clock) = &mut *guard;
+        let ttl = self.ttl;
+        entries.retain(|_, entry| entry.inserted_at.elapsed() < ttl);
+        // evict the least recently used entry
+        if entries.len() >= self.capacity && !entries.contains_key(key) {
+            if let Some(lru) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(k, _)| k.clone())
+            {
+                entries.remove(&lru);
+            }
+        }
+        *clock += 1;
+        entries.insert(
+            key.to_string(),
+            CacheEntry {
+                result: result.clone(),
+                inserted_at: Instant::now(),
+                last_used: *clock,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InMemoryVerificationCache, VerificationCache};
+    use crate::verify_proof::VerificationResult;
+    use std::{thread::sleep, time::Duration};
+
+    fn result(vp_digest: &str) -> VerificationResult {
+        VerificationResult {
+            vp_digest: vp_digest.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn in_memory_verification_cache() {
+        let cache = InMemoryVerificationCache::new(Duration::from_millis(50), 2);
+
+        cache.insert("key1", &result("digest1"));
+        cache.insert("key2", &result("digest2"));
+        assert_eq!(cache.get("key1"), Some(result("digest1")));
+
+        // the least recently used entry is evicted
+        cache.insert("key3", &result("digest3"));
+        assert_eq!(cache.get("key2"), None);
+        assert_eq!(cache.get("key1"), Some(result("digest1")));
+        assert_eq!(cache.get("key3"), Some(result("digest3")));
+
+        // entries expire after TTL
+        sleep(Duration::from_millis(100));
+        assert_eq!(cache.get("key1"), None);
+        assert_eq!(cache.get("key3"), None);
+    }
+}
@@ -1,63 +1,270 @@
 use crate::{
+    ark_to_base64url,
+    attribute_encryption::{
+        get_encrypted_attributes, validate_encryption_keys, EncryptedAttribute,
+    },
+    classic_vp::{is_classic_vp, verify_classic_vp},
+    commitment::{get_commitment_bases, get_commitment_equalities},
     common::{
         generate_proof_spec_context, get_dataset_from_nquads, get_delimiter,
-        get_graph_from_ntriples, get_hasher, hash_term_to_field, is_nym, read_private_var_list,
-        read_public_var_list, reorder_vc_triples, BBSPlusHash, BBSPlusPublicKey, Fr,
-        PedersenCommitmentStmt, PoKBBSPlusStmt, ProofWithIndexMap, Statements, VerifyingKey,
+        get_graph_from_ntriples, get_hasher, get_term_from_string, hash_str_to_str,
+        hash_term_to_field, hash_term_to_field_with_encoding, is_nym, reorder_vc_triples,
+        validate_index_maps, validate_vp_created, BBSPlusHash, BBSPlusPublicKey, Fr,
+        PedersenCommitmentStmt, PoKBBSPlusStmt, ProofWithIndexMap, SetupParams, Statements,
+        VerifyingKey,
     },
     constants::PPID_PREFIX,
     context::{
-        CHALLENGE, CIRCUIT, DOMAIN, ENCRYPTED_UID, HOLDER, PREDICATE_TYPE, PRIVATE, PROOF_VALUE,
-        PUBLIC, SECRET_COMMITMENT, VERIFIABLE_PRESENTATION_TYPE, VERIFICATION_METHOD,
+        CHALLENGE, CREATED, DATA_INTEGRITY_PROOF, DISTINCT_CREDENTIALS, DOMAIN, ENCRYPTED_UID,
+        HOLDER, NOT_EQUAL, POLICY_DIGEST, PREVIOUS_PROOF, ROTATED_SECRET_COMMITMENT,
+        SECRET_COMMITMENT, VERIFIABLE_CREDENTIAL_TYPE, VERIFIABLE_PRESENTATION_TYPE,
+        VERIFICATION_METHOD,
+    },
+    credential_context::get_credential_contexts,
+    curve::G1Affine,
+    device_binding::{get_device_binding, verify_device_attestation, DeviceAttestationVerifier},
+    elliptic_elgamal::{
+        get_escrowed_encryption_equalities,
+        verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus,
     },
+    equivalence::validate_equivalences,
     error::RDFProofsError,
-    key_gen::{generate_params, generate_ppid_base},
-    key_graph::KeyGraph,
+    inequality::{
+        count_distinct_credentials, get_inequalities, get_inequality_statements,
+        validate_unique_credentials, Inequality,
+    },
+    key_gen::{
+        generate_params, generate_params_with_profile, generate_ppid_base, get_params_profile,
+    },
+    key_graph::{check_key_usage, KeyGraph, KeyStore},
+    limits::{check_canonicalization_limits, Limits},
+    message_layout::{get_predicate_term_encoding, SecretSlot, TermEncoding},
+    metadata::validate_credential_summary,
+    metrics::{elapsed_ms, start_timer, Metrics},
+    migrate::decode_proof_value,
     multibase_to_ark,
+    namespace::{get_namespace_equalities, get_same_namespaces, hash_namespace_to_field},
+    nonce_token::{validate_nonce_token, NonceTokenValidation},
     ordered_triple::OrderedNamedOrBlankNode,
-    vc::{DisclosedVerifiableCredential, VerifiableCredentialTriples, VerifiablePresentation},
-    verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus, ElGamalPublicKey,
+    policy::{requires_unique_credentials, validate_acceptance_rules, PolicyDigest},
+    predicate_graph::{group_array_inputs, PredicateGraph},
+    proof_layout::{
+        validate_proof_features, validate_proof_layout, validate_statement_tags, ExtensionPolicy,
+        StatementKind, StatementLayout, PROOF_LAYOUT_VERSION,
+    },
+    proof_value::remove_proof_value,
+    replay::ReplayGuard,
+    secret_group::{get_secret_groups, split_embedded_secrets, validate_secret_groups},
+    vc::{
+        DisclosedVerifiableCredential, VerifiableCredentialTriples, VerifiableCredentialView,
+        VerifiablePresentation,
+    },
+    verification_cache::{get_verification_cache_key, VerificationCache},
+    warnings::{collect_warnings, VerificationWarning, WarningPolicy},
+    ElGamalCiphertext, ElGamalPublicKey,
 };
-use ark_bls12_381::G1Affine;
 use ark_std::{rand::RngCore, One};
 use oxrdf::{
-    dataset::GraphView, vocab::rdf::TYPE, Dataset, NamedNode, NamedOrBlankNode,
-    NamedOrBlankNodeRef, Subject, Term, TermRef, Triple,
+    dataset::GraphView, vocab::rdf::TYPE, Dataset, Graph, NamedNode, NamedNodeRef,
+    NamedOrBlankNode, NamedOrBlankNodeRef, Subject, SubjectRef, Term, TermRef, Triple,
 };
 use proof_system::{
     prelude::{EqualWitnesses, MetaStatements},
     proof_spec::ProofSpec,
     statement::r1cs_legogroth16::R1CSCircomVerifier,
 };
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// result of successful verification
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerificationResult {
+    /// public outputs of the predicates that declare them, in the order of predicates in VP
+    pub predicate_outputs: Vec<PredicateOutput>,
+    /// number of credentials proven pairwise distinct for each verification method,
+    /// or `1` for those of which no distinctness is proven
+    pub distinct_credential_counts: HashMap<NamedNode, usize>,
+    /// digest of the verified VP, i.e., `vp_digest` of VP
+    pub vp_digest: String,
+    /// disclosed VC documents with canonical blank node labels, in the order of VC graphs in VP
+    pub disclosed_documents: Vec<Graph>,
+    /// canonical N-Quads of the verified VP without proof value, which is one of the inputs
+    /// bound into the proof spec context along with the index map, proof format version,
+    /// protocol, and per-credential contexts; gateways can compute counter-signatures over it
+    pub canonical_vp: String,
+    /// hidden terms encrypted to the keys expected by verifier, in the order of encryptions in VP
+    pub encrypted_attributes: Vec<EncryptedAttribute>,
+    /// non-fatal findings collected if `warning_policy` is given to `verify_proof`
+    pub warnings: Vec<VerificationWarning>,
+    /// groups of disclosed VCs, given as indexes in the order of VC graphs in VP, whose secrets
+    /// are proven equal within each group rather than to the holder's designated secret,
+    /// as allowed by `allowed_secret_groups`
+    pub secret_groups: Vec<BTreeSet<usize>>,
+}
+
+/// public outputs of predicate circuit, e.g., age bracket, which are disclosed in VP
+/// and enforced by the circuit
+#[derive(Clone, Debug, PartialEq)]
+pub struct PredicateOutput {
+    pub circuit: NamedNode,
+    /// output values keyed by variable names in the order of circuit outputs
+    pub outputs: Vec<(String, Term)>,
+}
+
+/// optional inputs to `verify_proof`, which default to none, i.e., no SNARK verifying keys,
+/// no policy, rejecting unknown extensions, and so on; collections are owned and default
+/// to empty, whereas single values and services given by the caller are borrowed as `Option<&'a _>`
+#[derive(Default)]
+pub struct VerifyOptions<'a> {
+    pub snark_verifying_keys: HashMap<NamedNode, VerifyingKey>,
+    pub opener_pub_keys: Vec<ElGamalPublicKey>,
+    pub verifier_commitments: Vec<G1Affine>,
+    pub policy: Option<&'a Graph>,
+    pub replay_guard: Option<&'a dyn ReplayGuard>,
+    /// public values of predicates keyed by circuit IRIs and variable names
+    pub public_values: HashMap<NamedNode, HashMap<String, Term>>,
+    pub extension_policy: ExtensionPolicy,
+    /// keyed by verification methods
+    pub min_distinct_credentials: HashMap<NamedNode, usize>,
+    pub protocol: Option<&'a str>,
+    pub device_attestation_verifier: Option<&'a dyn DeviceAttestationVerifier>,
+    /// attestation roots trusted by verifier, under one of which the device key
+    /// of device binding must be certified
+    pub device_attestation_roots: Vec<String>,
+    pub verification_cache: Option<&'a dyn VerificationCache>,
+    pub nonce_token: Option<&'a NonceTokenValidation>,
+    pub metrics: Option<&'a dyn Metrics>,
+    pub limits: Option<&'a Limits>,
+    pub attribute_encryption_keys: Vec<Vec<ElGamalPublicKey>>,
+    pub warning_policy: Option<&'a WarningPolicy>,
+    /// sets of verification methods whose VCs may be bound to per-credential secrets,
+    /// where each secret group in VP must be within one of the sets; VPs with secret groups
+    /// are rejected by default
+    pub allowed_secret_groups: Vec<BTreeSet<NamedNode>>,
+    /// proofs whose context versions, recorded by prover, are lower than this are rejected,
+    /// e.g., `1` rejects legacy proofs without per-credential contexts; defaults to `0`
+    pub min_context_version: u32,
+}
+
+/// string-based version of `VerifyOptions` given to `verify_proof_string`
+#[derive(Default)]
+pub struct VerifyOptionsString<'a> {
+    pub snark_verifying_keys: Option<&'a HashMap<String, String>>,
+    pub opener_pub_keys: Option<&'a Vec<ElGamalPublicKey>>,
+    pub verifier_commitments: Option<&'a Vec<String>>,
+    pub policy: Option<&'a str>,
+    pub replay_guard: Option<&'a dyn ReplayGuard>,
+    pub public_values: Option<&'a HashMap<String, HashMap<String, String>>>,
+    pub ignore_non_critical_extensions: Option<bool>,
+    pub min_distinct_credentials: Option<&'a HashMap<String, usize>>,
+    pub protocol: Option<&'a str>,
+    pub device_attestation_verifier: Option<&'a dyn DeviceAttestationVerifier>,
+    pub device_attestation_roots: Option<&'a Vec<String>>,
+    pub verification_cache: Option<&'a dyn VerificationCache>,
+    pub nonce_token: Option<&'a NonceTokenValidation>,
+    pub metrics: Option<&'a dyn Metrics>,
+    pub limits: Option<&'a Limits>,
+    pub attribute_encryption_keys: Option<&'a Vec<Vec<String>>>,
+    pub warning_policy: Option<&'a WarningPolicy>,
+    /// verification methods without angle brackets
+    pub allowed_secret_groups: Option<&'a Vec<Vec<String>>>,
+    pub min_context_version: Option<u32>,
+}
 
 /// verify VP
 pub fn verify_proof<R: RngCore>(
     rng: &mut R,
     vp_dataset: &Dataset,
-    key_graph: &KeyGraph,
+    key_graph: &dyn KeyStore,
     challenge: Option<&str>,
     domain: Option<&str>,
-    snark_verifying_keys: HashMap<NamedNode, VerifyingKey>,
-    opener_pub_key: Option<ElGamalPublicKey>,
-) -> Result<(), RDFProofsError> {
+    options: VerifyOptions,
+) -> Result<VerificationResult, RDFProofsError> {
+    let options_digest = get_verify_options_digest(&options, domain)?;
+    let VerifyOptions {
+        snark_verifying_keys,
+        opener_pub_keys,
+        verifier_commitments,
+        policy,
+        replay_guard,
+        public_values,
+        extension_policy,
+        min_distinct_credentials,
+        protocol,
+        device_attestation_verifier,
+        device_attestation_roots,
+        verification_cache,
+        nonce_token,
+        metrics,
+        limits,
+        attribute_encryption_keys,
+        warning_policy,
+        allowed_secret_groups,
+        min_context_version,
+    } = options;
     let hasher = get_hasher();
+    let started = start_timer(metrics);
 
-    println!("VP:\n{}", rdf_canon::serialize(vp_dataset));
+    debug_println!("VP:\n{}", rdf_canon::serialize(vp_dataset));
+
+    // challenge given as verifier-signed nonce token must be valid before checking VP
+    if let Some(nonce_token) = nonce_token {
+        let challenge = challenge.ok_or(RDFProofsError::MissingChallengeInRequest)?;
+        validate_nonce_token(challenge, key_graph, nonce_token)?;
+    }
+
+    // reject VP too complex to be canonicalized before any canonicalization
+    check_canonicalization_limits(vp_dataset, limits)?;
+
+    // classic VP embedding the original VCs as is, without derived proof,
+    // which cannot carry encrypted hidden terms nor satisfy requirements on derived proofs
+    if is_classic_vp(vp_dataset) {
+        if !attribute_encryption_keys.is_empty() {
+            return Err(RDFProofsError::MissingAttributeEncryption);
+        }
+        let unsatisfiable = [
+            ("opener public keys", !opener_pub_keys.is_empty()),
+            ("verifier commitments", !verifier_commitments.is_empty()),
+            (
+                "device attestation verifier",
+                device_attestation_verifier.is_some(),
+            ),
+            ("public values of predicates", !public_values.is_empty()),
+            ("SNARK verifying keys", !snark_verifying_keys.is_empty()),
+            ("warning policy", warning_policy.is_some()),
+            ("minimum context version", min_context_version > 0),
+        ];
+        if let Some((requirement, _)) = unsatisfiable.iter().find(|(_, given)| *given) {
+            return Err(RDFProofsError::InvalidClassicVP(format!(
+                "{} cannot be satisfied by classic VP",
+                requirement
+            )));
+        }
+        return verify_classic_vp(
+            vp_dataset,
+            key_graph,
+            challenge,
+            domain,
+            policy,
+            replay_guard,
+            &min_distinct_credentials,
+            protocol,
+        );
+    }
 
     // decompose VP into graphs
     let vp: VerifiablePresentation = vp_dataset.try_into()?;
 
+    // validate proof chain if any
+    // (note that the previous proofs themselves are not verified here)
+    validate_proof_chain(&vp)?;
+
     // get proof value
     let proof_value_encoded = vp.get_proof_value()?;
+    let proof_value_digest = hash_str_to_str(&proof_value_encoded);
 
     // drop proof value from VP proof before canonicalization
     // (otherwise it could differ from the prover's canonicalization)
-    let vp_without_proof_value = Dataset::from_iter(
-        vp_dataset
-            .iter()
-            .filter(|q| !(q.predicate == PROOF_VALUE && q.graph_name == vp.proof_graph_name)),
-    );
+    let vp_without_proof_value = remove_proof_value(vp_dataset, &vp);
 
     // validate challenge
     match (challenge, vp.get_proof_config_literal(CHALLENGE)?) {
@@ -87,10 +294,40 @@ pub fn verify_proof<R: RngCore>(
         }
     }?;
 
+    // validate policy digest recomputed from verifier's policy
+    match (policy, vp.get_proof_config_literal(POLICY_DIGEST)?) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(RDFProofsError::MissingPolicyDigestInRequest),
+        (Some(_), None) => Err(RDFProofsError::MissingPolicyDigestInVP),
+        (Some(policy), Some(policy_digest_in_vp)) => {
+            if PolicyDigest::new(policy)?.as_str() == policy_digest_in_vp {
+                Ok(())
+            } else {
+                Err(RDFProofsError::MismatchedPolicyDigest)
+            }
+        }
+    }?;
+
+    // accept only issuers, verification methods, and cryptosuites allowed by verifier's policy
+    if let Some(policy) = policy {
+        validate_acceptance_rules(policy, &vp.disclosed_vcs)?;
+    }
+
+    // validate `created`, which may be truncated to the precision chosen by holder
+    let vp_proof_subject = vp
+        .proof
+        .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+        .ok_or(RDFProofsError::InvalidVP)?;
+    validate_vp_created(
+        vp.proof
+            .object_for_subject_predicate(vp_proof_subject, CREATED),
+    )?;
+
     // canonicalize VP
     let c14n_map_for_disclosed = rdf_canon::issue(&vp_without_proof_value)?;
     let canonicalized_vp = rdf_canon::relabel(&vp_without_proof_value, &c14n_map_for_disclosed)?;
-    println!(
+    let vp_digest = get_vp_digest(vp_dataset, &c14n_map_for_disclosed)?;
+    debug_println!(
         "canonicalized VP:\n{}",
         rdf_canon::serialize(&canonicalized_vp)
     );
@@ -98,32 +335,116 @@ pub fn verify_proof<R: RngCore>(
     // decompose canonicalized VP into graphs
     let VerifiablePresentation {
         metadata: vp_metadata, // TODO: validate VP metadata
-        proof: _,
+        proof: c14n_vp_proof,
         proof_graph_name: _,
+        previous_proofs: _,
         predicates: predicate_graphs,
+        equivalences: equivalence_graph,
         disclosed_vcs: c14n_disclosed_vc_graphs,
     } = (&canonicalized_vp).try_into()?;
 
+    // collect non-fatal findings, which depend on the time of verification
+    // and thus are never taken from the cache
+    let warnings = match warning_policy {
+        Some(warning_policy) => collect_warnings(
+            warning_policy,
+            vp.proof
+                .object_for_subject_predicate(vp_proof_subject, CREATED),
+            c14n_disclosed_vc_graphs.values(),
+            key_graph,
+        )?,
+        None => vec![],
+    };
+
+    // groups of bound VCs whose embedded secrets are proven equal separately
+    // from the holder's secret, as they are bound to per-credential secrets,
+    // which must be allowed by verifier even for the cached result
+    let secret_groups = get_secret_groups(&c14n_vp_proof, &c14n_disclosed_vc_graphs)?;
+    validate_secret_groups(
+        &secret_groups,
+        &c14n_disclosed_vc_graphs,
+        &allowed_secret_groups,
+    )?;
+
+    // validate equivalence classes of hidden terms shared among disclosed VCs
+    validate_equivalences(&equivalence_graph, &c14n_disclosed_vc_graphs)?;
+
+    // keep disclosed VC documents to be reported once verified
+    let disclosed_documents = c14n_disclosed_vc_graphs
+        .values()
+        .map(|vc| vc.document.iter().collect())
+        .collect::<Vec<Graph>>();
+
+    // validate summary of verification methods and cryptosuites relied on by disclosed VCs
+    validate_credential_summary(&c14n_vp_proof, &c14n_disclosed_vc_graphs)?;
+
+    // count credentials proven pairwise distinct via their hidden IDs,
+    // which must satisfy the minimum required for each verification method
+    let distinct_credentials = get_inequalities(&c14n_vp_proof, DISTINCT_CREDENTIALS)?;
+    let distinct_credential_counts =
+        count_distinct_credentials(&distinct_credentials, &c14n_disclosed_vc_graphs)?;
+    for (verification_method, min) in &min_distinct_credentials {
+        let count = distinct_credential_counts
+            .get(verification_method)
+            .copied()
+            .unwrap_or(0);
+        if count < *min {
+            return Err(RDFProofsError::InsufficientDistinctCredentials(format!(
+                "{} of {} required for {}",
+                count, min, verification_method
+            )));
+        }
+    }
+    if policy.is_some_and(requires_unique_credentials) {
+        validate_unique_credentials(&distinct_credentials, &c14n_disclosed_vc_graphs)?;
+    }
+
     // get PPID
     let ppid = get_ppid(&vp_metadata)?;
-    println!("PPID: {:#?}", ppid);
+    debug_println!("PPID: {:#?}", ppid);
 
-    // get secret commitment
-    let secret_commitment = get_secret_commitment(&vp_metadata)?;
-    println!("secret_commitment: {:#?}", secret_commitment);
+    // get secret commitment, or rotated secret commitment committing to
+    // the holder's new secret, which is not required to be equal to the bound secret
+    let (secret_commitment, is_secret_rotation) = match (
+        get_secret_commitment(&vp_metadata, SECRET_COMMITMENT)?,
+        get_secret_commitment(&vp_metadata, ROTATED_SECRET_COMMITMENT)?,
+    ) {
+        (Some(_), Some(_)) => return Err(RDFProofsError::InvalidVP),
+        (Some(c), None) => (Some(c), false),
+        (None, Some(c)) => (Some(c), true),
+        (None, None) => (None, false),
+    };
+    debug_println!("secret_commitment: {:#?}", secret_commitment);
 
-    // get issuer public keys
+    // get issuer public keys allowed to sign the disclosed VCs
     let public_keys = c14n_disclosed_vc_graphs
         .iter()
-        .map(|(_, vc)| get_public_keys_from_graphview(&vc.proof, key_graph))
+        .map(|(_, vc)| get_public_keys_from_graphview(vc, key_graph))
         .collect::<Result<Vec<_>, _>>()?;
-    println!("public_keys:\n{:#?}\n", public_keys);
+    debug_println!("public_keys:\n{:#?}\n", public_keys);
+    let public_keys_digest = hash_str_to_str(
+        &public_keys
+            .iter()
+            .map(ark_to_base64url)
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n"),
+    );
 
     // if the VC is bound to secret or not
     let is_bounds = c14n_disclosed_vc_graphs
         .iter()
         .map(|(_, vc)| vc.is_bound())
         .collect::<Result<Vec<_>, _>>()?;
+    // where the secret slot is placed in the message vector of each VC
+    let secret_slots = c14n_disclosed_vc_graphs
+        .iter()
+        .map(|(_, vc)| vc.secret_slot())
+        .collect::<Result<Vec<_>, _>>()?;
+    // whether each term is followed by its IRI namespace in the message vector of each VC
+    let term_encodings = c14n_disclosed_vc_graphs
+        .iter()
+        .map(|(_, vc)| vc.term_encoding())
+        .collect::<Result<Vec<_>, _>>()?;
 
     // convert to Vecs
     let disclosed_vec = c14n_disclosed_vc_graphs
@@ -132,14 +453,39 @@ pub fn verify_proof<R: RngCore>(
         .collect::<Vec<VerifiableCredentialTriples>>();
 
     // deserialize proof value into proof and index_map
-    let (_, proof_value_bytes) = multibase::decode(proof_value_encoded)?;
-    let ProofWithIndexMap { proof, index_map } = serde_cbor::from_slice(&proof_value_bytes)?;
-    println!("proof:\n{:#?}\n", proof);
-    println!("index_map:\n{:#?}\n", index_map);
+    let ProofWithIndexMap {
+        proof,
+        index_map,
+        version,
+        statement_tags,
+        extensions,
+        generator,
+        features,
+        context_version,
+    } = decode_proof_value(&proof_value_encoded)?;
+    debug_println!("proof:\n{:#?}\n", proof);
+    debug_println!("index_map:\n{:#?}\n", index_map);
+    // fail early on statements, extensions, and features unknown to this verifier
+    if version > PROOF_LAYOUT_VERSION {
+        return Err(RDFProofsError::ProofFromNewerVersion { generator, version });
+    }
+    validate_proof_layout(version, &statement_tags, &extensions, extension_policy)?;
+    validate_proof_features(&features)?;
+    validate_index_maps(&index_map, &disclosed_vec)?;
+    // context version is chosen by prover, so that verifier must reject the lower ones
+    // to prevent downgrading to the legacy context
+    if context_version < min_context_version {
+        return Err(RDFProofsError::ProofContextVersionTooLow {
+            version: context_version,
+            min: min_context_version,
+        });
+    }
+    // legacy proofs without per-credential sub-contexts are verified against the legacy context
+    let credential_contexts = get_credential_contexts(context_version, &disclosed_vec)?;
 
     // reorder statements according to index map
     let reordered_vc_triples = reorder_vc_triples(&disclosed_vec, &index_map)?;
-    println!(
+    debug_println!(
         "reordered_disclosed_vc_triples:\n{:#?}\n",
         reordered_vc_triples
     );
@@ -147,13 +493,25 @@ pub fn verify_proof<R: RngCore>(
     // identify disclosed terms
     let disclosed_terms = reordered_vc_triples
         .iter()
-        .zip(&is_bounds)
+        .zip(
+            is_bounds
+                .iter()
+                .zip(secret_slots.into_iter().zip(&term_encodings)),
+        )
         .enumerate()
-        .map(|(i, (disclosed_vc_triples, is_bound))| {
-            get_disclosed_terms(disclosed_vc_triples, i, is_bound)
-        })
+        .map(
+            |(i, (disclosed_vc_triples, (is_bound, (secret_slot, term_encoding))))| {
+                get_disclosed_terms(
+                    disclosed_vc_triples,
+                    i,
+                    is_bound,
+                    secret_slot,
+                    *term_encoding,
+                )
+            },
+        )
         .collect::<Result<Vec<_>, RDFProofsError>>()?;
-    println!("disclosed_terms:\n{:#?}\n", disclosed_terms);
+    debug_println!("disclosed_terms:\n{:#?}\n", disclosed_terms);
 
     let term_counts = disclosed_terms
         .iter()
@@ -163,11 +521,16 @@ pub fn verify_proof<R: RngCore>(
                 .map_err(|_| RDFProofsError::MessageSizeOverflow)
         })
         .collect::<Result<Vec<u32>, _>>()?;
+    let params_profiles = disclosed_vec
+        .iter()
+        .map(|vc| get_params_profile(vc.proof.iter().map(|t| t.as_ref())))
+        .collect::<Result<Vec<_>, _>>()?;
     let params_for_commitment = generate_params(1);
     let params_and_pks = term_counts
         .iter()
+        .zip(params_profiles)
         .zip(public_keys)
-        .map(|(t, pk)| (generate_params(*t), pk));
+        .map(|((t, profile), pk)| (generate_params_with_profile(profile, *t), pk));
 
     // merge each partial equivs
     let mut equivs: BTreeMap<OrderedNamedOrBlankNode, Vec<(usize, usize)>> = BTreeMap::new();
@@ -184,8 +547,9 @@ pub fn verify_proof<R: RngCore>(
         }
     }
 
-    // build statements
+    // build statements in the order of `StatementKind`, along with their expected tags
     let mut statements = Statements::new();
+    let mut layout = StatementLayout::new();
     // statements for BBS+ signatures
     for (DisclosedTerms { disclosed, .. }, (params, public_key)) in
         disclosed_terms.iter().zip(params_and_pks)
@@ -195,6 +559,7 @@ pub fn verify_proof<R: RngCore>(
             public_key,
             disclosed.clone(),
         ));
+        layout.push(StatementKind::BbsPlusSignature, 1)?;
     }
     // statement for PPID
     let mut ppid_index = None;
@@ -205,28 +570,40 @@ pub fn verify_proof<R: RngCore>(
                 vec![base],
                 ppid,
             ));
-            ppid_index = Some(statements.len() - 1);
+            ppid_index = Some(layout.push(StatementKind::Ppid, 1)?);
         }
     }
-    // statement for verifiable encryption of uid
-    if let Some(opener_pub_key) = opener_pub_key {
+    // statements for verifiable encryption of uid escrowed among openers
+    let mut encryption_equalities = None;
+    if !opener_pub_keys.is_empty() {
         let params = generate_params(1);
-        let cipher_text = vp.get_proof_config_literal(ENCRYPTED_UID).unwrap();
-        let cipher_text = match (&cipher_text).as_ref() {
-            Some(cipher_text) => multibase_to_ark(cipher_text),
-            _ => return Err(RDFProofsError::MissingEncryptedSecret),
-        }
-        .unwrap();
+        let cipher_text = vp.get_proof_config_literal(ENCRYPTED_UID)?;
+        let Some(cipher_text) = cipher_text else {
+            return Err(RDFProofsError::MissingEncryptedSecret);
+        };
+        // a single opener's ciphertext is encoded as is for backward compatibility
+        let cipher_texts: Vec<ElGamalCiphertext> = if opener_pub_keys.len() == 1 {
+            vec![multibase_to_ark(&cipher_text)?]
+        } else {
+            multibase_to_ark(&cipher_text)?
+        };
         let verifiable_encryption_statements =
-            verify_elliptic_elgamal_verifiable_encryption_with_bbs_plus(
-                &opener_pub_key,
+            verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+                &opener_pub_keys,
                 &params.h[0],
-                &cipher_text,
-            )
-            .unwrap();
+                &cipher_texts,
+            )?;
+        let encryption_index = layout.push(
+            StatementKind::VerifiableEncryption,
+            verifiable_encryption_statements.0.len(),
+        )?;
         for statement in verifiable_encryption_statements.0.iter() {
             statements.add(statement.clone());
         }
+        encryption_equalities = Some(get_escrowed_encryption_equalities(
+            encryption_index,
+            opener_pub_keys.len(),
+        ));
     }
     // statement for secret commitment
     let mut secret_commitment_index = None;
@@ -235,84 +612,288 @@ pub fn verify_proof<R: RngCore>(
             vec![params_for_commitment.h_0, params_for_commitment.h[0]],
             s,
         ));
-        secret_commitment_index = Some(statements.len() - 1);
+        secret_commitment_index = Some(layout.push(StatementKind::SecretCommitment, 1)?);
     }
-    // statements for predicates
+    // statements for predicates, where the verifying key of each circuit
+    // is shared among the predicates using the same circuit
+    let mut setup_params: Vec<SetupParams> = vec![];
+    let mut circuit_setup_params_indexes: HashMap<NamedNode, usize> = HashMap::new();
     let mut predicate_indexes = vec![];
     let mut predicate_privates = vec![];
     let mut predicate_publics = vec![];
+    let mut used_public_values = HashSet::new();
+    let mut predicate_outputs = vec![];
     for (_, predicate_graph) in predicate_graphs {
-        let predicate_subject = predicate_graph
-            .subject_for_predicate_object(TYPE, PREDICATE_TYPE)
-            .ok_or(RDFProofsError::InvalidPredicate)?;
-        let TermRef::NamedNode(predicate_circuit) = predicate_graph
-            .object_for_subject_predicate(predicate_subject, CIRCUIT)
-            .ok_or(RDFProofsError::InvalidPredicate)?
-        else {
-            return Err(RDFProofsError::InvalidPredicate);
-        };
+        let PredicateGraph {
+            circuit: predicate_circuit,
+            private,
+            public: mut publics,
+            output,
+        } = PredicateGraph::try_from(&predicate_graph)?;
+        // public inputs are encoded as the private ones, i.e., the signed messages
+        let public_term_encoding = get_predicate_term_encoding(&private, &equivs, &term_encodings);
+        // the elements of input array like `addends[0]` must be laid out as required by prover,
+        // since private inputs are committed in the order given
+        group_array_inputs(
+            private
+                .iter()
+                .map(|(var, _)| (var.to_string(), Fr::one()))
+                .collect(),
+        )?;
+        predicate_privates.push(private);
 
-        let mut privates = vec![];
-        let TermRef::BlankNode(predicate_private) = predicate_graph
-            .object_for_subject_predicate(predicate_subject, PRIVATE)
-            .ok_or(RDFProofsError::InvalidPredicate)?
-        else {
-            return Err(RDFProofsError::InvalidPredicate);
-        };
-        read_private_var_list(predicate_private, &mut privates, &predicate_graph)?;
-        predicate_privates.push(privates);
-
-        let mut publics = vec![];
-        let TermRef::BlankNode(predicate_public) = predicate_graph
-            .object_for_subject_predicate(predicate_subject, PUBLIC)
-            .ok_or(RDFProofsError::InvalidPredicate)?
-        else {
-            return Err(RDFProofsError::InvalidPredicate);
-        };
-        read_public_var_list(predicate_public, &mut publics, &predicate_graph)?;
+        // override the public values designated by verifier,
+        // so that the proof fails if prover used different values
+        if let Some(values) = public_values.get(&predicate_circuit) {
+            for (var, value) in publics.iter_mut() {
+                if let Some(v) = values.get(var) {
+                    *value = v.clone();
+                    used_public_values.insert((predicate_circuit.clone(), var.clone()));
+                }
+            }
+        }
         predicate_publics.push(publics.clone());
 
-        let mut public_inputs = vec![Fr::one()]; // predicate must return 1
-        for (_, public_value) in publics {
-            public_inputs.push(hash_term_to_field((&public_value).into(), &hasher)?);
+        // circuit outputs precede public inputs,
+        // where predicate must return 1 unless its outputs are declared
+        let mut public_inputs = match output {
+            Some(outputs) => {
+                let output_values = outputs
+                    .iter()
+                    .map(|(_, value)| hash_term_to_field(value.into(), &hasher))
+                    .collect::<Result<Vec<_>, _>>()?;
+                predicate_outputs.push(PredicateOutput {
+                    circuit: predicate_circuit.clone(),
+                    outputs,
+                });
+                output_values
+            }
+            None => vec![Fr::one()],
+        };
+        // the elements of input array are flattened into the public inputs of the array signal
+        let mut public_array_inputs = vec![];
+        for (var, public_value) in publics {
+            public_array_inputs.push((
+                var,
+                hash_term_to_field_with_encoding(
+                    (&public_value).into(),
+                    public_term_encoding,
+                    &hasher,
+                )?,
+            ));
+        }
+        for (_, values) in group_array_inputs(public_array_inputs)? {
+            public_inputs.extend(values);
         }
 
-        statements.add(R1CSCircomVerifier::new_statement_from_params(
+        let setup_params_index = match circuit_setup_params_indexes.get(&predicate_circuit) {
+            Some(idx) => *idx,
+            None => {
+                let snark_verifying_key = snark_verifying_keys
+                    .get(&predicate_circuit)
+                    .ok_or(RDFProofsError::MissingSnarkVK(
+                        predicate_circuit.to_string(),
+                    ))?
+                    .clone();
+                let idx = setup_params.len();
+                setup_params.push(SetupParams::LegoSnarkVerifyingKey(snark_verifying_key));
+                circuit_setup_params_indexes.insert(predicate_circuit, idx);
+                idx
+            }
+        };
+        statements.add(R1CSCircomVerifier::new_statement_from_params_ref(
             public_inputs,
-            snark_verifying_keys
-                .get(&predicate_circuit.into_owned())
-                .ok_or(RDFProofsError::MissingSnarkVK(
-                    predicate_circuit.to_string(),
-                ))?
-                .clone(),
+            setup_params_index,
         )?);
-        predicate_indexes.push(statements.len() - 1);
+        predicate_indexes.push(layout.push(StatementKind::Predicate, 1)?);
+    }
+    // every public value supplied by verifier must be enforced by some predicate
+    for (circuit, values) in &public_values {
+        for var in values.keys() {
+            if !used_public_values.contains(&(circuit.clone(), var.clone())) {
+                return Err(RDFProofsError::UnusedPredicatePublicValue(format!(
+                    "{} of {}",
+                    var, circuit
+                )));
+            }
+        }
     }
-    println!("statements: {:?}", statements);
+    // statements for equalities to verifier's commitments,
+    // all of which must be included in VP and be bound to hidden terms in VCs
+    let commitment_equalities = get_commitment_equalities(&c14n_vp_proof)?;
+    if commitment_equalities.len() != verifier_commitments.len()
+        || verifier_commitments.iter().any(|c| {
+            !commitment_equalities
+                .iter()
+                .any(|(c_in_vp, _)| c_in_vp == c)
+        })
+    {
+        return Err(RDFProofsError::MissingCommitmentEquality);
+    }
+    let mut commitment_equality_indexes = vec![];
+    for (commitment, value) in &commitment_equalities {
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(value.clone().into())) {
+            return Err(RDFProofsError::MissingCommitmentEquality);
+        }
+        statements.add(PedersenCommitmentStmt::new_statement_from_params(
+            get_commitment_bases(),
+            *commitment,
+        ));
+        commitment_equality_indexes.push(layout.push(StatementKind::CommitmentEquality, 1)?);
+    }
+    // statements for inequalities of hidden credential IDs and those of requested terms
+    let inequalities = [
+        distinct_credentials,
+        get_inequalities(&c14n_vp_proof, NOT_EQUAL)?,
+    ]
+    .concat();
+    let mut inequality_indexes = vec![];
+    for Inequality {
+        commitment,
+        lhs,
+        rhs,
+    } in &inequalities
+    {
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(lhs.clone().into()))
+            || !equivs.contains_key(&OrderedNamedOrBlankNode(rhs.clone().into()))
+        {
+            return Err(RDFProofsError::InvalidInequality);
+        }
+        let inequality_statements = get_inequality_statements(commitment).0;
+        inequality_indexes
+            .push(layout.push(StatementKind::Inequality, inequality_statements.len())?);
+        for statement in inequality_statements {
+            statements.add(statement);
+        }
+    }
+    // statement for device binding, whose attestation over the challenge
+    // bound to the key handle is validated by verifier
+    let mut device_binding_index = None;
+    match (
+        get_device_binding(&c14n_vp_proof)?,
+        device_attestation_verifier,
+    ) {
+        (None, None) => (),
+        (None, Some(_)) => {
+            return Err(RDFProofsError::InvalidDeviceBinding(
+                "missing device binding in VP".to_string(),
+            ))
+        }
+        (Some(_), None) => {
+            return Err(RDFProofsError::InvalidDeviceBinding(
+                "no verifier for device attestation".to_string(),
+            ))
+        }
+        (Some((key_handle, attestation)), Some(verifier)) => {
+            let challenge = challenge.ok_or(RDFProofsError::InvalidDeviceBinding(
+                "challenge is required".to_string(),
+            ))?;
+            verify_device_attestation(
+                verifier,
+                &device_attestation_roots,
+                &attestation,
+                challenge,
+                &key_handle,
+            )?;
+            statements.add(PedersenCommitmentStmt::new_statement_from_params(
+                get_commitment_bases(),
+                key_handle,
+            ));
+            device_binding_index = Some(layout.push(StatementKind::DeviceBinding, 1)?);
+        }
+    }
+    // statements for verifiable encryptions of hidden terms, which must be encrypted
+    // exactly to the keys expected by verifier and be bound to hidden terms in VCs
+    let encrypted_attributes = get_encrypted_attributes(&c14n_vp_proof)?;
+    validate_encryption_keys(&encrypted_attributes, &attribute_encryption_keys)?;
+    let mut attribute_encryption_equalities = vec![];
+    for EncryptedAttribute {
+        value,
+        pub_keys,
+        cipher_texts,
+    } in &encrypted_attributes
+    {
+        if !equivs.contains_key(&OrderedNamedOrBlankNode(value.clone().into())) {
+            return Err(RDFProofsError::InvalidAttributeEncryption);
+        }
+        let encryption_statements = verify_elliptic_elgamal_escrowed_encryption_with_bbs_plus(
+            pub_keys,
+            &params_for_commitment.h[0],
+            cipher_texts,
+        )?
+        .0;
+        let encryption_index = layout.push(
+            StatementKind::AttributeEncryption,
+            encryption_statements.len(),
+        )?;
+        for statement in encryption_statements {
+            statements.add(statement);
+        }
+        attribute_encryption_equalities.push(get_escrowed_encryption_equalities(
+            encryption_index,
+            cipher_texts.len(),
+        ));
+    }
+    debug_println!("statements: {:?}", statements);
+    validate_statement_tags(version, &statement_tags, &layout.tags())?;
 
     // build meta statements
     let mut meta_statements = MetaStatements::new();
 
-    // proof of equality for embedded secrets
-    let mut secret_equiv_set: BTreeSet<(usize, usize)> = is_bounds
-        .iter()
-        .enumerate()
-        .filter(|(_, &is_bound)| is_bound)
-        .map(|(i, _)| (i, 0)) // `0` is the index for embedded secret in VC
-        .collect();
+    // proof of equality for embedded secrets, separately for each group of VCs
+    // bound to per-credential secret
+    let (mut secret_equiv_set, secret_group_equiv_sets) = split_embedded_secrets(
+        is_bounds
+            .iter()
+            .zip(&disclosed_terms)
+            .enumerate()
+            .filter(|(_, (&is_bound, _))| is_bound)
+            .map(|(i, (_, terms))| (i, terms.secret_index)) // embedded secret in VC
+            .collect(),
+        &secret_groups,
+    );
+    for equiv_set in secret_group_equiv_sets {
+        if equiv_set.len() > 1 {
+            meta_statements.add_witness_equality(EqualWitnesses(equiv_set));
+        }
+    }
     // add PPID to the proof of equalities if exists
     if let Some(idx) = ppid_index {
         // `0` corresponds to the committed secret in PPID
         secret_equiv_set.insert((idx, 0));
     }
-    // add secret commitment to the proof of equalities if exists
-    if let Some(idx) = secret_commitment_index {
+    // add secret commitment to the proof of equalities if exists,
+    // except for the rotated one
+    if let (Some(idx), false) = (secret_commitment_index, is_secret_rotation) {
         // `1` corresponds to the committed secret in Pedersen Commitment (`0` corresponds to the blinding)
         secret_equiv_set.insert((idx, 1));
     }
+    // add key handle of device binding to the proof of equalities if exists
+    if let Some(idx) = device_binding_index {
+        secret_equiv_set.insert((idx, 1));
+    }
+    // add encrypted uid to the proof of equalities if exists,
+    // along with the equalities of the randomness used for each opener
+    if let Some((randomness_equalities, uid_position)) = encryption_equalities {
+        secret_equiv_set.insert(uid_position);
+        for equality in randomness_equalities {
+            meta_statements.add_witness_equality(EqualWitnesses(equality));
+        }
+    }
     if secret_equiv_set.len() > 1 {
         meta_statements.add_witness_equality(EqualWitnesses(secret_equiv_set));
     }
+    // equalities of the randomness used for each key of encrypted hidden terms
+    for (randomness_equalities, _) in &attribute_encryption_equalities {
+        for equality in randomness_equalities {
+            meta_statements.add_witness_equality(EqualWitnesses(equality.clone()));
+        }
+    }
+    // equalities of the namespaces of hidden IRIs
+    let same_namespaces = get_same_namespaces(&c14n_vp_proof)?;
+    for equality in get_namespace_equalities(&same_namespaces, &equivs, &term_encodings)? {
+        meta_statements.add_witness_equality(EqualWitnesses(equality));
+    }
 
     // proof of equality
     for (equiv_c14n_id, equiv_vec) in equivs {
@@ -330,24 +911,192 @@ pub fn verify_proof<R: RngCore>(
                 equiv_set.insert((*predicate_index, idx_in_predicate));
             }
         }
-        println!("equiv_set: {:?}", equiv_set);
+
+        // add equality for committed values
+        for ((_, value), commitment_index) in commitment_equalities
+            .iter()
+            .zip(&commitment_equality_indexes)
+        {
+            if NamedOrBlankNode::from(value.clone()) == equiv_c14n_id.0 {
+                // `1` corresponds to the committed value (`0` corresponds to the blinding)
+                equiv_set.insert((*commitment_index, 1));
+            }
+        }
+
+        // add equality for terms proven unequal
+        for (Inequality { lhs, rhs, .. }, inequality_index) in
+            inequalities.iter().zip(&inequality_indexes)
+        {
+            // `0` and `1` correspond to the left-hand and right-hand terms
+            if NamedOrBlankNode::from(lhs.clone()) == equiv_c14n_id.0 {
+                equiv_set.insert((*inequality_index, 0));
+            }
+            if NamedOrBlankNode::from(rhs.clone()) == equiv_c14n_id.0 {
+                equiv_set.insert((*inequality_index, 1));
+            }
+        }
+
+        // add equality for encrypted hidden terms
+        for (EncryptedAttribute { value, .. }, (_, value_position)) in encrypted_attributes
+            .iter()
+            .zip(&attribute_encryption_equalities)
+        {
+            if NamedOrBlankNode::from(value.clone()) == equiv_c14n_id.0 {
+                equiv_set.insert(*value_position);
+            }
+        }
+        debug_println!("equiv_set: {:?}", equiv_set);
         if equiv_set.len() > 1 {
             meta_statements.add_witness_equality(EqualWitnesses(equiv_set));
         }
     }
 
     // build proof spec
-    let context = generate_proof_spec_context(&canonicalized_vp, &index_map)?;
-    let proof_spec = ProofSpec::new(statements, meta_statements, vec![], Some(context));
+    if let Some(metrics) = metrics {
+        metrics.statement_count(statements.len());
+    }
+    let context = generate_proof_spec_context(
+        &canonicalized_vp,
+        &index_map,
+        version,
+        protocol,
+        credential_contexts,
+    )?;
+    let proof_spec = ProofSpec::new(statements, meta_statements, setup_params, Some(context));
     proof_spec.validate()?;
 
+    // return the cached result of the identical VP verified against the same keys
+    // and verifier's inputs, still rejecting replayed VP
+    let cache_key = get_verification_cache_key(
+        &vp_digest,
+        challenge,
+        &public_keys_digest,
+        &options_digest,
+        policy
+            .map(PolicyDigest::new)
+            .transpose()?
+            .as_ref()
+            .map(PolicyDigest::as_str),
+    );
+    if let Some(result) = verification_cache.and_then(|cache| cache.get(&cache_key)) {
+        if let Some(replay_guard) = replay_guard {
+            replay_guard.check_and_record(challenge, domain, &proof_value_digest)?;
+        }
+        return Ok(VerificationResult { warnings, ..result });
+    }
+
     // verify proof
-    Ok(proof.verify::<R, BBSPlusHash>(
+    proof.verify::<R, BBSPlusHash>(
         rng,
         proof_spec,
         challenge.map(|v| v.as_bytes().to_vec()),
         Default::default(),
-    )?)
+    )?;
+
+    // reject replayed VP, which is recorded only after successful verification
+    // so that invalid VPs cannot consume the challenge
+    if let Some(replay_guard) = replay_guard {
+        replay_guard.check_and_record(challenge, domain, &proof_value_digest)?;
+    }
+
+    let result = VerificationResult {
+        predicate_outputs,
+        distinct_credential_counts,
+        vp_digest,
+        disclosed_documents,
+        canonical_vp: rdf_canon::serialize(&canonicalized_vp),
+        encrypted_attributes,
+        warnings,
+        secret_groups,
+    };
+    if let Some(verification_cache) = verification_cache {
+        verification_cache.insert(&cache_key, &result);
+    }
+    if let (Some(metrics), Some(started)) = (metrics, started) {
+        metrics.verify_ms(elapsed_ms(started));
+    }
+
+    Ok(result)
+}
+
+// digest of verifier's inputs determining the statements to be verified,
+// which is part of the verification cache key
+fn get_verify_options_digest(
+    options: &VerifyOptions,
+    domain: Option<&str>,
+) -> Result<String, RDFProofsError> {
+    let sorted = |mut inputs: Vec<String>| {
+        inputs.sort();
+        inputs.join(",")
+    };
+    let inputs = [
+        format!("{:?}", domain),
+        format!("{:?}", options.protocol),
+        format!("{:?}", options.extension_policy),
+        sorted(
+            options
+                .snark_verifying_keys
+                .iter()
+                .map(|(circuit, vk)| Ok(format!("{}={}", circuit, ark_to_base64url(vk)?)))
+                .collect::<Result<_, RDFProofsError>>()?,
+        ),
+        options
+            .opener_pub_keys
+            .iter()
+            .map(ark_to_base64url)
+            .collect::<Result<Vec<_>, _>>()?
+            .join(","),
+        options
+            .verifier_commitments
+            .iter()
+            .map(ark_to_base64url)
+            .collect::<Result<Vec<_>, _>>()?
+            .join(","),
+        sorted(
+            options
+                .public_values
+                .iter()
+                .flat_map(|(circuit, values)| {
+                    values
+                        .iter()
+                        .map(move |(var, value)| format!("{} {}={}", circuit, var, value))
+                })
+                .collect(),
+        ),
+        sorted(
+            options
+                .min_distinct_credentials
+                .iter()
+                .map(|(verification_method, min)| format!("{}={}", verification_method, min))
+                .collect(),
+        ),
+        options
+            .attribute_encryption_keys
+            .iter()
+            .map(|keys| {
+                Ok(keys
+                    .iter()
+                    .map(ark_to_base64url)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(" "))
+            })
+            .collect::<Result<Vec<_>, RDFProofsError>>()?
+            .join(","),
+        format!("{:?}", options.allowed_secret_groups),
+        format!("{}", options.device_attestation_verifier.is_some()),
+        sorted(options.device_attestation_roots.clone()),
+    ];
+    Ok(hash_str_to_str(&inputs.join("\n")))
+}
+
+// digest of canonical N-Quads of VP including proof value,
+// whose blank nodes are labeled by `c14n_map` computed without proof value
+pub(crate) fn get_vp_digest(
+    vp_dataset: &Dataset,
+    c14n_map: &HashMap<String, String>,
+) -> Result<String, RDFProofsError> {
+    let canonicalized_vp = rdf_canon::relabel(vp_dataset, c14n_map)?;
+    Ok(hash_str_to_str(&rdf_canon::serialize(&canonicalized_vp)))
 }
 
 pub fn verify_proof_string<R: RngCore>(
@@ -356,12 +1105,32 @@ pub fn verify_proof_string<R: RngCore>(
     key_graph: &str,
     challenge: Option<&str>,
     domain: Option<&str>,
-    snark_verifying_keys: Option<HashMap<String, String>>,
-    opener_pub_key: Option<ElGamalPublicKey>,
-) -> Result<(), RDFProofsError> {
+    options: VerifyOptionsString,
+) -> Result<VerificationResult, RDFProofsError> {
+    let VerifyOptionsString {
+        snark_verifying_keys,
+        opener_pub_keys,
+        verifier_commitments,
+        policy,
+        replay_guard,
+        public_values,
+        ignore_non_critical_extensions,
+        min_distinct_credentials,
+        protocol,
+        device_attestation_verifier,
+        device_attestation_roots,
+        verification_cache,
+        nonce_token,
+        metrics,
+        limits,
+        attribute_encryption_keys,
+        warning_policy,
+        allowed_secret_groups,
+        min_context_version,
+    } = options;
     // construct input for `verify_proof` from string-based input
     let vp = get_dataset_from_nquads(vp)?;
-    let key_graph = get_graph_from_ntriples(key_graph)?.into();
+    let key_graph: KeyGraph = get_graph_from_ntriples(key_graph)?.into();
     let snark_verifying_key = match snark_verifying_keys {
         None => HashMap::new(),
         Some(predicate_id_and_vks) => predicate_id_and_vks
@@ -369,6 +1138,57 @@ pub fn verify_proof_string<R: RngCore>(
             .map(|(predicate_id, vk)| Ok((NamedNode::new(predicate_id)?, multibase_to_ark(vk)?)))
             .collect::<Result<HashMap<_, VerifyingKey>, RDFProofsError>>()?,
     };
+    let verifier_commitments = match verifier_commitments {
+        None => vec![],
+        Some(commitments) => commitments
+            .iter()
+            .map(|c| multibase_to_ark(c))
+            .collect::<Result<Vec<_>, RDFProofsError>>()?,
+    };
+    let policy = policy.map(get_graph_from_ntriples).transpose()?;
+    let public_values = match public_values {
+        None => HashMap::new(),
+        Some(circuit_id_and_values) => circuit_id_and_values
+            .iter()
+            .map(|(circuit_id, values)| {
+                Ok((
+                    NamedNode::new(circuit_id)?,
+                    values
+                        .iter()
+                        .map(|(var, value)| Ok((var.clone(), get_term_from_string(value)?)))
+                        .collect::<Result<HashMap<_, _>, RDFProofsError>>()?,
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, RDFProofsError>>()?,
+    };
+    let min_distinct_credentials = match min_distinct_credentials {
+        None => HashMap::new(),
+        Some(verification_method_and_mins) => verification_method_and_mins
+            .iter()
+            .map(|(verification_method, min)| Ok((NamedNode::new(verification_method)?, *min)))
+            .collect::<Result<HashMap<_, _>, RDFProofsError>>()?,
+    };
+    let attribute_encryption_keys = attribute_encryption_keys
+        .map(|key_sets| {
+            key_sets
+                .iter()
+                .map(|keys| keys.iter().map(|pk| multibase_to_ark(pk)).collect())
+                .collect::<Result<Vec<Vec<_>>, RDFProofsError>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let allowed_secret_groups = match allowed_secret_groups {
+        None => vec![],
+        Some(groups) => groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|v| Ok(NamedNode::new(v)?))
+                    .collect::<Result<BTreeSet<_>, RDFProofsError>>()
+            })
+            .collect::<Result<Vec<BTreeSet<_>>, RDFProofsError>>()?,
+    };
 
     verify_proof(
         rng,
@@ -376,11 +1196,144 @@ pub fn verify_proof_string<R: RngCore>(
         &key_graph,
         challenge,
         domain,
-        snark_verifying_key,
-        opener_pub_key,
+        VerifyOptions {
+            snark_verifying_keys: snark_verifying_key,
+            opener_pub_keys: opener_pub_keys.cloned().unwrap_or_default(),
+            verifier_commitments,
+            policy: policy.as_ref(),
+            replay_guard,
+            public_values,
+            extension_policy: if ignore_non_critical_extensions.unwrap_or(false) {
+                ExtensionPolicy::IgnoreNonCritical
+            } else {
+                ExtensionPolicy::Reject
+            },
+            min_distinct_credentials,
+            protocol,
+            device_attestation_verifier,
+            device_attestation_roots: device_attestation_roots.cloned().unwrap_or_default(),
+            verification_cache,
+            nonce_token,
+            metrics,
+            limits,
+            attribute_encryption_keys,
+            warning_policy,
+            allowed_secret_groups,
+            min_context_version: min_context_version.unwrap_or_default(),
+        },
     )
 }
 
+/// verify VP for secret rotation, and return the rotated secret commitment
+/// to which the issuer can bind the credentials via `blind_sign`
+pub fn verify_secret_rotation<R: RngCore>(
+    rng: &mut R,
+    vp_dataset: &Dataset,
+    key_graph: &dyn KeyStore,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+) -> Result<G1Affine, RDFProofsError> {
+    verify_proof(
+        rng,
+        vp_dataset,
+        key_graph,
+        challenge,
+        domain,
+        VerifyOptions::default(),
+    )?;
+
+    let vp: VerifiablePresentation = vp_dataset.try_into()?;
+    get_secret_commitment(&vp.metadata, ROTATED_SECRET_COMMITMENT)?
+        .ok_or(RDFProofsError::MissingRotatedSecretCommitment)
+}
+
+pub fn verify_secret_rotation_string<R: RngCore>(
+    rng: &mut R,
+    vp: &str,
+    key_graph: &str,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+) -> Result<String, RDFProofsError> {
+    let vp = get_dataset_from_nquads(vp)?;
+    let key_graph: KeyGraph = get_graph_from_ntriples(key_graph)?.into();
+    let commitment = verify_secret_rotation(rng, &vp, &key_graph, challenge, domain)?;
+    ark_to_base64url(&commitment)
+}
+
+// check that the previous proofs in VP form chains
+// starting from the VP proof's `previousProof`s, without any missing, cyclic,
+// or dangling previous proof
+fn validate_proof_chain(vp: &VerifiablePresentation) -> Result<(), RDFProofsError> {
+    let get_previous_proof_refs = |proof: &GraphView| -> Result<Vec<NamedNode>, RDFProofsError> {
+        let proof_subject = proof
+            .subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF)
+            .ok_or(RDFProofsError::InvalidProofChain(
+                "proof must be a DataIntegrityProof".to_string(),
+            ))?;
+        proof
+            .objects_for_subject_predicate(proof_subject, PREVIOUS_PROOF)
+            .map(|o| match o {
+                TermRef::NamedNode(n) => Ok(n.into_owned()),
+                _ => Err(RDFProofsError::InvalidProofChain(
+                    "previousProof must be an IRI".to_string(),
+                )),
+            })
+            .collect()
+    };
+
+    // map previous proof IDs to their own previous proofs
+    let mut previous_proofs = HashMap::new();
+    for (_, previous_proof) in &vp.previous_proofs {
+        let id = match previous_proof.subject_for_predicate_object(TYPE, DATA_INTEGRITY_PROOF) {
+            Some(SubjectRef::NamedNode(n)) => Ok(n.into_owned()),
+            _ => Err(RDFProofsError::InvalidProofChain(
+                "previous proof must be identified by IRI".to_string(),
+            )),
+        }?;
+        let refs = get_previous_proof_refs(previous_proof)?;
+        if previous_proofs.insert(id.clone(), refs).is_some() {
+            return Err(RDFProofsError::InvalidProofChain(format!(
+                "duplicated previous proof `{}`",
+                id
+            )));
+        }
+    }
+
+    // traverse the chains from the VP proof
+    let mut visited = HashSet::new();
+    let mut stack: Vec<(NamedNode, Vec<NamedNode>)> = get_previous_proof_refs(&vp.proof)?
+        .into_iter()
+        .map(|id| (id, vec![]))
+        .collect();
+    while let Some((id, ancestors)) = stack.pop() {
+        if ancestors.contains(&id) {
+            return Err(RDFProofsError::InvalidProofChain(format!(
+                "cyclic previous proof `{}`",
+                id
+            )));
+        }
+        let refs = previous_proofs
+            .get(&id)
+            .ok_or(RDFProofsError::MissingPreviousProof(id.to_string()))?;
+        if !visited.insert(id.clone()) {
+            continue;
+        }
+        let mut ancestors = ancestors;
+        ancestors.push(id);
+        for r in refs {
+            stack.push((r.clone(), ancestors.clone()));
+        }
+    }
+
+    if visited.len() != previous_proofs.len() {
+        return Err(RDFProofsError::InvalidProofChain(
+            "VP contains previous proofs unreachable from VP proof".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 fn get_ppid(metadata: &GraphView) -> Result<Option<G1Affine>, RDFProofsError> {
     let vp_subject = metadata
         .subject_for_predicate_object(TYPE, VERIFIABLE_PRESENTATION_TYPE)
@@ -395,7 +1348,10 @@ fn get_ppid(metadata: &GraphView) -> Result<Option<G1Affine>, RDFProofsError> {
     Ok(Some(multibase_to_ark(ppid_multibase)?))
 }
 
-fn get_secret_commitment(metadata: &GraphView) -> Result<Option<G1Affine>, RDFProofsError> {
+fn get_secret_commitment(
+    metadata: &GraphView,
+    commitment_predicate: NamedNodeRef,
+) -> Result<Option<G1Affine>, RDFProofsError> {
     let vp_subject = metadata
         .subject_for_predicate_object(TYPE, VERIFIABLE_PRESENTATION_TYPE)
         .ok_or(RDFProofsError::InvalidVP)?;
@@ -405,7 +1361,7 @@ fn get_secret_commitment(metadata: &GraphView) -> Result<Option<G1Affine>, RDFPr
         _ => return Ok(None),
     };
     let commitment = if let Some(TermRef::Literal(commitment_multibase)) =
-        metadata.object_for_subject_predicate(holder_subject, SECRET_COMMITMENT)
+        metadata.object_for_subject_predicate(holder_subject, commitment_predicate)
     {
         Some(multibase_to_ark(commitment_multibase.value())?)
     } else {
@@ -419,12 +1375,15 @@ struct DisclosedTerms {
     disclosed: BTreeMap<usize, Fr>,
     equivs: HashMap<NamedOrBlankNode, Vec<(usize, usize)>>,
     term_count: usize,
+    secret_index: usize,
 }
 
 fn get_disclosed_terms(
     disclosed_vc_triples: &DisclosedVerifiableCredential,
     vc_index: usize,
     is_bound: &bool,
+    secret_slot: SecretSlot,
+    term_encoding: TermEncoding,
 ) -> Result<DisclosedTerms, RDFProofsError> {
     let mut disclosed_terms = BTreeMap::<usize, Fr>::new();
     let mut equivs = HashMap::<NamedOrBlankNode, Vec<(usize, usize)>>::new();
@@ -434,12 +1393,20 @@ fn get_disclosed_terms(
         proof: disclosed_proof,
     } = disclosed_vc_triples;
 
-    let mut current_term_index = 0;
-
+    // the secret slot precedes the document or follows the proof config, depending on cryptosuite
+    let triple_width = 3 * term_encoding.messages_per_term();
+    let secret_index = match secret_slot {
+        SecretSlot::First => 0,
+        SecretSlot::Last => triple_width * (disclosed_document.len() + disclosed_proof.len()) + 1,
+    };
     if !is_bound {
-        disclosed_terms.insert(current_term_index, Fr::from(1));
+        disclosed_terms.insert(secret_index, Fr::from(1));
     };
-    current_term_index += 1;
+
+    let mut current_term_index = 0;
+    if secret_slot == SecretSlot::First {
+        current_term_index += 1;
+    }
 
     for (_, disclosed_triple) in disclosed_document {
         build_disclosed_terms(
@@ -448,8 +1415,9 @@ fn get_disclosed_terms(
             vc_index,
             &mut disclosed_terms,
             &mut equivs,
+            term_encoding,
         )?;
-        current_term_index += 3;
+        current_term_index += triple_width;
     }
 
     let delimiter = get_delimiter()?;
@@ -463,13 +1431,18 @@ fn get_disclosed_terms(
             vc_index,
             &mut disclosed_terms,
             &mut equivs,
+            term_encoding,
         )?;
-        current_term_index += 3;
+        current_term_index += triple_width;
+    }
+    if secret_slot == SecretSlot::Last {
+        current_term_index += 1;
     }
     Ok(DisclosedTerms {
         disclosed: disclosed_terms,
         equivs,
         term_count: current_term_index,
+        secret_index,
     })
 }
 
@@ -479,9 +1452,10 @@ fn build_disclosed_terms(
     vc_index: usize,
     disclosed_terms: &mut BTreeMap<usize, Fr>,
     equivs: &mut HashMap<NamedOrBlankNode, Vec<(usize, usize)>>,
+    term_encoding: TermEncoding,
 ) -> Result<(), RDFProofsError> {
-    let predicate_index = subject_index + 1;
-    let object_index = subject_index + 2;
+    let predicate_index = subject_index + term_encoding.messages_per_term();
+    let object_index = subject_index + 2 * term_encoding.messages_per_term();
 
     let hasher = get_hasher();
 
@@ -501,7 +1475,8 @@ fn build_disclosed_terms(
                         .push((vc_index, subject_index));
                 }
                 Subject::NamedNode(n) => {
-                    let subject_fr = hash_term_to_field(n.into(), &hasher)?;
+                    let subject_fr =
+                        hash_term_to_field_with_encoding(n.into(), term_encoding, &hasher)?;
                     disclosed_terms.insert(subject_index, subject_fr);
                 }
                 #[cfg(feature = "rdf-star")]
@@ -514,7 +1489,11 @@ fn build_disclosed_terms(
                     .or_default()
                     .push((vc_index, predicate_index));
             } else {
-                let predicate_fr = hash_term_to_field((&triple.predicate).into(), &hasher)?;
+                let predicate_fr = hash_term_to_field_with_encoding(
+                    (&triple.predicate).into(),
+                    term_encoding,
+                    &hasher,
+                )?;
                 disclosed_terms.insert(predicate_index, predicate_fr);
             };
 
@@ -532,16 +1511,31 @@ fn build_disclosed_terms(
                         .push((vc_index, object_index));
                 }
                 Term::NamedNode(n) => {
-                    let object_fr = hash_term_to_field(n.into(), &hasher)?;
+                    let object_fr =
+                        hash_term_to_field_with_encoding(n.into(), term_encoding, &hasher)?;
                     disclosed_terms.insert(object_index, object_fr);
                 }
                 Term::Literal(v) => {
-                    let object_fr = hash_term_to_field(v.into(), &hasher)?;
+                    let object_fr =
+                        hash_term_to_field_with_encoding(v.into(), term_encoding, &hasher)?;
                     disclosed_terms.insert(object_index, object_fr);
                 }
                 #[cfg(feature = "rdf-star")]
                 Term::Triple(_) => return Err(RDFProofsError::DeriveProofValue),
             };
+
+            // namespace following each term is disclosed along with the term
+            if term_encoding == TermEncoding::SplitIri {
+                for (index, term) in [
+                    (subject_index, TermRef::from(&triple.subject)),
+                    (predicate_index, TermRef::from(&triple.predicate)),
+                    (object_index, TermRef::from(&triple.object)),
+                ] {
+                    if disclosed_terms.contains_key(&index) {
+                        disclosed_terms.insert(index + 1, hash_namespace_to_field(term, &hasher)?);
+                    }
+                }
+            }
         }
 
         None => {}
@@ -551,10 +1545,11 @@ fn build_disclosed_terms(
 
 // TODO: to be integrated with `get_public_keys`
 fn get_public_keys_from_graphview(
-    proof_graph: &GraphView,
-    key_graph: &KeyGraph,
+    vc: &VerifiableCredentialView,
+    key_graph: &dyn KeyStore,
 ) -> Result<BBSPlusPublicKey, RDFProofsError> {
-    let vm_triple = proof_graph
+    let vm_triple = vc
+        .proof
         .triples_for_predicate(VERIFICATION_METHOD)
         .next()
         .ok_or(RDFProofsError::InvalidVerificationMethod)?;
@@ -562,5 +1557,17 @@ fn get_public_keys_from_graphview(
         TermRef::NamedNode(v) => Ok(v),
         _ => Err(RDFProofsError::InvalidVerificationMethodURL),
     }?;
-    key_graph.get_public_key(vm)
+    let public_key = key_graph.get_public_key(vm)?;
+    // hidden credential types are not allowed by the key restricted to specific types
+    let credential_types = vc
+        .document
+        .subject_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+        .map(|vc_id| {
+            vc.document
+                .objects_for_subject_predicate(vc_id, TYPE)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_else(Vec::new);
+    check_key_usage(vm, credential_types, key_graph)?;
+    Ok(public_key)
 }
@@ -0,0 +1,260 @@
+use crate::{
+    clock::{clock_or_system, Clock},
+    context::{EXPIRATION_DATE, VERIFIABLE_CREDENTIAL_TYPE, VERIFICATION_METHOD},
+    error::RDFProofsError,
+    key_graph::KeyStore,
+    vc::VerifiableCredentialView,
+};
+use chrono::{DateTime, Duration, Utc};
+use oxrdf::{vocab::rdf::TYPE, NamedNode, TermRef};
+use std::{collections::BTreeSet, fmt};
+
+/// non-fatal finding during verification reported in `VerificationResult::warnings`,
+/// on which policy engines can act without turning it into a verification failure
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationWarning {
+    /// disclosed `expirationDate` of the `credential`-th VC in VP has passed
+    CredentialExpired {
+        credential: usize,
+        expiration: DateTime<Utc>,
+    },
+    /// disclosed `expirationDate` of the `credential`-th VC in VP is within the expiry window
+    CredentialExpiresSoon {
+        credential: usize,
+        expiration: DateTime<Utc>,
+    },
+    /// issuer key has passed its `expires`
+    KeyExpired {
+        verification_method: NamedNode,
+        expiration: DateTime<Utc>,
+    },
+    /// issuer key is due to be rotated, i.e., its `expires` is within the key rotation window
+    KeyExpiresSoon {
+        verification_method: NamedNode,
+        expiration: DateTime<Utc>,
+    },
+    /// VP is created later than the verifier's clock by `skew`, e.g., due to clock drift
+    CreatedInFuture { skew: Duration },
+}
+
+impl fmt::Display for VerificationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationWarning::CredentialExpired {
+                credential,
+                expiration,
+            } => write!(f, "credential {} expired at {}", credential, expiration),
+            VerificationWarning::CredentialExpiresSoon {
+                credential,
+                expiration,
+            } => write!(f, "credential {} expires at {}", credential, expiration),
+            VerificationWarning::KeyExpired {
+                verification_method,
+                expiration,
+            } => write!(
+                f,
+                "issuer key {} expired at {}",
+                verification_method, expiration
+            ),
+            VerificationWarning::KeyExpiresSoon {
+                verification_method,
+                expiration,
+            } => write!(
+                f,
+                "issuer key {} is due to be rotated at {}",
+                verification_method, expiration
+            ),
+            VerificationWarning::CreatedInFuture { skew } => {
+                write!(f, "VP created {}ms in the future", skew.num_milliseconds())
+            }
+        }
+    }
+}
+
+/// thresholds of the warnings collected by `verify_proof` at the time of `clock`,
+/// which defaults to the system clock
+pub struct WarningPolicy {
+    expiry_window: Duration,
+    key_rotation_window: Duration,
+    clock: Option<Box<dyn Clock>>,
+}
+
+impl WarningPolicy {
+    /// warn about credentials expiring within `expiry_window`
+    /// and issuer keys expiring within `key_rotation_window`
+    pub fn new(expiry_window: Duration, key_rotation_window: Duration) -> Self {
+        Self {
+            expiry_window,
+            key_rotation_window,
+            clock: None,
+        }
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+}
+
+// collect warnings on the disclosed VCs in the order of VC graphs in VP,
+// their issuer keys, and `created` of VP, ignoring values hidden or not given as xsd:dateTime
+pub(crate) fn collect_warnings<'a>(
+    policy: &WarningPolicy,
+    vp_created: Option<TermRef>,
+    disclosed_vcs: impl IntoIterator<Item = &'a VerifiableCredentialView<'a>>,
+    key_graph: &dyn KeyStore,
+) -> Result<Vec<VerificationWarning>, RDFProofsError> {
+    let now = clock_or_system(policy.clock.as_deref()).now();
+    let mut warnings = vec![];
+    let mut verification_methods = BTreeSet::new();
+
+    for (credential, vc) in disclosed_vcs.into_iter().enumerate() {
+        let expirations = vc
+            .document
+            .subjects_for_predicate_object(TYPE, VERIFIABLE_CREDENTIAL_TYPE)
+            .flat_map(|id| {
+                vc.document
+                    .objects_for_subject_predicate(id, EXPIRATION_DATE)
+            })
+            .filter_map(parse_date_time);
+        for expiration in expirations {
+            if expiration <= now {
+                warnings.push(VerificationWarning::CredentialExpired {
+                    credential,
+                    expiration,
+                });
+            } else if expiration - now <= policy.expiry_window {
+                warnings.push(VerificationWarning::CredentialExpiresSoon {
+                    credential,
+                    expiration,
+                });
+            }
+        }
+        for t in vc.proof.triples_for_predicate(VERIFICATION_METHOD) {
+            if let TermRef::NamedNode(vm) = t.object {
+                verification_methods.insert(vm.into_owned());
+            }
+        }
+    }
+
+    for verification_method in verification_methods {
+        let Some(expiration) = key_graph.get_key_expiration(verification_method.as_ref())? else {
+            continue;
+        };
+        if expiration <= now {
+            warnings.push(VerificationWarning::KeyExpired {
+                verification_method,
+                expiration,
+            });
+        } else if expiration - now <= policy.key_rotation_window {
+            warnings.push(VerificationWarning::KeyExpiresSoon {
+                verification_method,
+                expiration,
+            });
+        }
+    }
+
+    if let Some(created) = vp_created.and_then(parse_date_time) {
+        if created > now {
+            warnings.push(VerificationWarning::CreatedInFuture {
+                skew: created - now,
+            });
+        }
+    }
+
+    Ok(warnings)
+}
+
+fn parse_date_time(term: TermRef) -> Option<DateTime<Utc>> {
+    match term {
+        TermRef::Literal(v) => v.value().parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_warnings, VerificationWarning, WarningPolicy};
+    use crate::{
+        clock::FixedClock, common::get_graph_from_ntriples, key_graph::KeyGraph,
+        vc::VerifiableCredentialView,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use oxrdf::{
+        vocab::xsd, Dataset, GraphNameRef, LiteralRef, NamedNode, NamedNodeRef, QuadRef, TermRef,
+    };
+
+    const KEY_GRAPH: &str = r#"
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#publicKeyMultibase> "ukiiQxfsSfV0E2QyBlnHTK2MThnd7_-Fyf6u76BUd24uxoDF4UjnXtxUo8b82iuPZBOa8BXd1NpE20x3Rfde9udcd8P8nPVLr80Xh6WLgI9SYR6piNzbHhEVIfgd_Vo9P" .
+<did:example:issuer0#bls12_381-g2-pub001> <https://w3id.org/security#expires> "2025-01-03T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+    const DOCUMENT: &str = r#"
+<http://example.org/vcred/00> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .
+<http://example.org/vcred/00> <https://www.w3.org/2018/credentials#expirationDate> "2025-01-01T12:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> .
+"#;
+    const PROOF: &str = r#"
+_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#DataIntegrityProof> .
+_:b0 <https://w3id.org/security#verificationMethod> <did:example:issuer0#bls12_381-g2-pub001> .
+"#;
+
+    #[test]
+    fn collect_verification_warnings() {
+        let key_graph: KeyGraph = get_graph_from_ntriples(KEY_GRAPH).unwrap().into();
+        let mut dataset = Dataset::new();
+        let document = NamedNodeRef::new_unchecked("http://example.org/document");
+        let proof = NamedNodeRef::new_unchecked("http://example.org/proof");
+        for (graph, triples) in [(document, DOCUMENT), (proof, PROOF)] {
+            for t in get_graph_from_ntriples(triples).unwrap().iter() {
+                dataset.insert(QuadRef::new(t.subject, t.predicate, t.object, graph));
+            }
+        }
+        let vc = VerifiableCredentialView::new(
+            dataset.graph(GraphNameRef::NamedNode(document)),
+            dataset.graph(GraphNameRef::NamedNode(proof)),
+        );
+        let now: DateTime<Utc> = "2025-01-01T00:00:00Z".parse().unwrap();
+        let created = LiteralRef::new_typed_literal("2025-01-01T00:00:02Z", xsd::DATE_TIME);
+        let warnings_at = |now: DateTime<Utc>| {
+            let mut policy = WarningPolicy::new(Duration::hours(24), Duration::days(7));
+            policy.set_clock(Box::new(FixedClock::new(now)));
+            collect_warnings(&policy, Some(TermRef::Literal(created)), [&vc], &key_graph).unwrap()
+        };
+
+        let vm = NamedNode::new_unchecked("did:example:issuer0#bls12_381-g2-pub001");
+        assert_eq!(
+            warnings_at(now),
+            vec![
+                VerificationWarning::CredentialExpiresSoon {
+                    credential: 0,
+                    expiration: "2025-01-01T12:00:00Z".parse().unwrap(),
+                },
+                VerificationWarning::KeyExpiresSoon {
+                    verification_method: vm.clone(),
+                    expiration: "2025-01-03T00:00:00Z".parse().unwrap(),
+                },
+                VerificationWarning::CreatedInFuture {
+                    skew: Duration::seconds(2),
+                },
+            ]
+        );
+        assert_eq!(
+            warnings_at(now + Duration::days(3)),
+            vec![
+                VerificationWarning::CredentialExpired {
+                    credential: 0,
+                    expiration: "2025-01-01T12:00:00Z".parse().unwrap(),
+                },
+                VerificationWarning::KeyExpired {
+                    verification_method: vm,
+                    expiration: "2025-01-03T00:00:00Z".parse().unwrap(),
+                },
+            ]
+        );
+        // only clock drift is reported outside the windows
+        assert_eq!(
+            warnings_at(now - Duration::days(30)),
+            vec![VerificationWarning::CreatedInFuture {
+                skew: Duration::days(30) + Duration::seconds(2),
+            }]
+        );
+    }
+}